@@ -0,0 +1,458 @@
+#![cfg_attr(feature = "fail-on-warnings", deny(warnings))]
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![allow(clippy::multiple_crate_versions)]
+
+//! Mock AI provider for `ChadReview`.
+//!
+//! This crate provides an implementation of the `AiProvider` trait that
+//! answers with scripted responses instead of running an AI, so the AI
+//! comment flow can be tested, or demoed offline, without `OpenCode`
+//! installed. Responses report synthetic progress steps, stream their text
+//! and can be slowed down to exercise progress updates and cancellation.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chadreview_ai_provider::{
+    AbortSignal, AiProvider, AiProviderError,
+    models::{
+        AgentCapabilities, AgentSource, AiActionDefinition, AiContext, AiProgress, AiResponse,
+    },
+    mpsc,
+};
+use chadreview_local_comment_models::{
+    AiAction, ExecutionDetails, ProgressEntry, TokenUsage, ToolExecution,
+};
+use chrono::Utc;
+
+/// Name the mock provider is installed under.
+pub const PROVIDER_NAME: &str = "mock";
+
+/// Model reported by mock executions.
+pub const MOCK_MODEL: &str = "mock-model";
+
+/// A scripted answer to one execution.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    /// The response, or the error the execution fails with.
+    outcome: Result<String, String>,
+    /// Tool calls reported before answering, as `(tool, title)`.
+    steps: Vec<(String, String)>,
+    /// Wait before each progress update.
+    delay: Duration,
+    /// Cost reported in the execution details.
+    cost: Option<f64>,
+}
+
+impl MockResponse {
+    /// A response answering with `content`.
+    #[must_use]
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            outcome: Ok(content.into()),
+            steps: vec![],
+            delay: Duration::ZERO,
+            cost: None,
+        }
+    }
+
+    /// A response failing the execution with `message`.
+    #[must_use]
+    pub fn failure(message: impl Into<String>) -> Self {
+        Self {
+            outcome: Err(message.into()),
+            ..Self::new("")
+        }
+    }
+
+    /// Report a tool call before answering.
+    #[must_use]
+    pub fn with_step(mut self, tool: impl Into<String>, title: impl Into<String>) -> Self {
+        self.steps.push((tool.into(), title.into()));
+        self
+    }
+
+    /// Wait `delay` before each progress update: each step and each line
+    /// of the streamed response.
+    #[must_use]
+    pub const fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Report `cost` dollars in the execution details.
+    #[must_use]
+    pub const fn with_cost(mut self, cost: f64) -> Self {
+        self.cost = Some(cost);
+        self
+    }
+}
+
+/// An execution the mock provider ran, recorded for assertions.
+#[derive(Debug, Clone)]
+pub struct MockExecution {
+    /// The context it was given.
+    pub context: AiContext,
+    /// The action it ran.
+    pub action: AiAction,
+    /// The session it continued, if any.
+    pub session_id: Option<String>,
+}
+
+/// AI provider answering with scripted responses.
+///
+/// Executions take the scripted responses in order, then answer with the
+/// fallback, which echoes the request unless replaced.
+#[derive(Debug)]
+pub struct MockProvider {
+    agents: Vec<AiActionDefinition>,
+    script: Mutex<VecDeque<MockResponse>>,
+    fallback: Option<MockResponse>,
+    executions: Mutex<Vec<MockExecution>>,
+}
+
+impl MockProvider {
+    /// Create a mock provider offering a read-only `echo` agent and an
+    /// `edit` agent that can write files.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            agents: vec![],
+            script: Mutex::new(VecDeque::new()),
+            fallback: None,
+            executions: Mutex::new(vec![]),
+        }
+        .with_agent("echo", "Answers with the request it was given", false)
+        .with_agent("edit", "Pretends to edit files", true)
+    }
+
+    /// Offer another agent named `name`.
+    #[must_use]
+    pub fn with_agent(mut self, name: &str, description: &str, can_write: bool) -> Self {
+        self.agents.retain(|agent| agent.name != name);
+        self.agents.push(AiActionDefinition {
+            id: format!("{PROVIDER_NAME}:{name}"),
+            name: name.to_string(),
+            description: description.to_string(),
+            provider: PROVIDER_NAME.to_string(),
+            default_model: Some(MOCK_MODEL.to_string()),
+            capabilities: AgentCapabilities {
+                can_read: true,
+                can_write,
+                can_execute: false,
+            },
+            source: AgentSource::BuiltIn,
+        });
+        self
+    }
+
+    /// Answer the next execution without a response of its own with
+    /// `response`.
+    #[must_use]
+    pub fn with_response(self, response: MockResponse) -> Self {
+        self.script().push_back(response);
+        self
+    }
+
+    /// Answer executions once the scripted responses run out with
+    /// `response`, instead of echoing their request.
+    #[must_use]
+    pub fn with_fallback(mut self, response: MockResponse) -> Self {
+        self.fallback = Some(response);
+        self
+    }
+
+    /// The executions run so far, oldest first.
+    #[must_use]
+    pub fn executions(&self) -> Vec<MockExecution> {
+        self.executions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn script(&self) -> MutexGuard<'_, VecDeque<MockResponse>> {
+        self.script.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn next_response(&self, context: &AiContext) -> MockResponse {
+        self.script()
+            .pop_front()
+            .or_else(|| self.fallback.clone())
+            .unwrap_or_else(|| {
+                MockResponse::new(format!("Mock response to:\n\n> {}", context.comment_body))
+            })
+    }
+}
+
+impl Default for MockProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AiProvider for MockProvider {
+    fn provider_name(&self) -> &'static str {
+        PROVIDER_NAME
+    }
+
+    async fn list_agents(
+        &self,
+        _repo_path: &Path,
+    ) -> Result<Vec<AiActionDefinition>, AiProviderError> {
+        Ok(self.agents.clone())
+    }
+
+    async fn execute(
+        &self,
+        context: &AiContext,
+        action: &AiAction,
+        session_id: Option<&str>,
+        progress_tx: mpsc::Sender<AiProgress>,
+        abort: AbortSignal,
+    ) -> Result<AiResponse, AiProviderError> {
+        if !self.agents.iter().any(|agent| agent.name == action.agent) {
+            return Err(AiProviderError::AgentNotFound(action.agent.clone()));
+        }
+
+        self.executions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(MockExecution {
+                context: context.clone(),
+                action: action.clone(),
+                session_id: session_id.map(str::to_string),
+            });
+
+        let response = self.next_response(context);
+        let start_time = Instant::now();
+
+        for (tool, title) in &response.steps {
+            wait(response.delay, &abort).await?;
+            let _ = progress_tx.send(AiProgress::Step(ProgressEntry {
+                tool: tool.clone(),
+                title: title.clone(),
+                timestamp: Utc::now(),
+            }));
+        }
+
+        let answer = match response.outcome {
+            Ok(answer) => answer,
+            Err(message) => {
+                wait(response.delay, &abort).await?;
+                return Err(AiProviderError::ExecutionFailed(message));
+            }
+        };
+
+        let mut streamed = String::new();
+        for line in answer.split_inclusive('\n') {
+            wait(response.delay, &abort).await?;
+            streamed.push_str(line);
+            let _ = progress_tx.send(AiProgress::Text(streamed.clone()));
+        }
+
+        let execution_details = ExecutionDetails {
+            model_used: action
+                .model
+                .clone()
+                .unwrap_or_else(|| MOCK_MODEL.to_string()),
+            tools_used: response
+                .steps
+                .iter()
+                .map(|(tool, title)| ToolExecution {
+                    tool: tool.clone(),
+                    title: title.clone(),
+                    input: serde_json::Value::Null,
+                    output_preview: None,
+                })
+                .collect(),
+            tokens: TokenUsage {
+                input: word_count(&context.comment_body),
+                output: word_count(&answer),
+            },
+            cost: response.cost,
+            duration_seconds: start_time.elapsed().as_secs(),
+        };
+
+        Ok(AiResponse {
+            content: answer,
+            model_used: execution_details.model_used.clone(),
+            execution_details: Some(execution_details),
+            session_id: Some(session_id.map_or_else(
+                || format!("mock-session-{}", Utc::now().timestamp_micros()),
+                str::to_string,
+            )),
+        })
+    }
+}
+
+/// Wait `delay`, failing with [`AiProviderError::Cancelled`] if the
+/// execution is aborted first.
+async fn wait(delay: Duration, abort: &AbortSignal) -> Result<(), AiProviderError> {
+    if abort.is_aborted() {
+        return Err(AiProviderError::Cancelled);
+    }
+    if delay.is_zero() {
+        return Ok(());
+    }
+
+    abort
+        .abortable(switchy::unsync::time::sleep(delay))
+        .await
+        .ok_or(AiProviderError::Cancelled)
+}
+
+fn word_count(text: &str) -> u64 {
+    text.split_whitespace().count() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use chadreview_ai_provider::{ProviderRegistry, abort_pair};
+
+    use super::*;
+
+    fn context(body: &str) -> AiContext {
+        AiContext::new(
+            PathBuf::from("/repo"),
+            "main..feature".to_string(),
+            body.to_string(),
+        )
+    }
+
+    fn action(agent: &str) -> AiAction {
+        AiAction {
+            provider: PROVIDER_NAME.to_string(),
+            agent: agent.to_string(),
+            model: None,
+            custom_instructions: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scripted_responses_then_echo() {
+        let provider = MockProvider::new()
+            .with_response(MockResponse::new("First").with_cost(0.5))
+            .with_response(MockResponse::failure("Out of tokens"));
+        let (tx, _rx) = mpsc::unbounded();
+
+        let first = provider
+            .execute(
+                &context("Why?"),
+                &action("echo"),
+                None,
+                tx.clone(),
+                AbortSignal::never(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(first.content, "First");
+        assert_eq!(first.execution_details.unwrap().cost, Some(0.5));
+
+        let second = provider
+            .execute(
+                &context("Why?"),
+                &action("echo"),
+                None,
+                tx.clone(),
+                AbortSignal::never(),
+            )
+            .await;
+        assert!(
+            matches!(second, Err(AiProviderError::ExecutionFailed(message)) if message == "Out of tokens")
+        );
+
+        let third = provider
+            .execute(
+                &context("Why?"),
+                &action("echo"),
+                Some("session"),
+                tx,
+                AbortSignal::never(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(third.content, "Mock response to:\n\n> Why?");
+        assert_eq!(third.session_id.as_deref(), Some("session"));
+
+        let executions = provider.executions();
+        assert_eq!(executions.len(), 3);
+        assert_eq!(executions[2].session_id.as_deref(), Some("session"));
+    }
+
+    #[tokio::test]
+    async fn test_reports_steps_and_streams_text() {
+        let provider = MockProvider::new().with_fallback(
+            MockResponse::new("Line one\nLine two").with_step("read", "Reading src/lib.rs"),
+        );
+        let (tx, rx) = mpsc::unbounded();
+
+        provider
+            .execute(
+                &context("Explain"),
+                &action("echo"),
+                None,
+                tx,
+                AbortSignal::never(),
+            )
+            .await
+            .unwrap();
+
+        let updates: Vec<_> = rx.try_iter().collect();
+        assert_eq!(updates.len(), 3);
+        assert!(
+            matches!(&updates[0], AiProgress::Step(entry) if entry.title == "Reading src/lib.rs")
+        );
+        assert!(matches!(&updates[1], AiProgress::Text(text) if text == "Line one\n"));
+        assert!(matches!(&updates[2], AiProgress::Text(text) if text == "Line one\nLine two"));
+    }
+
+    #[tokio::test]
+    async fn test_aborted_execution_is_cancelled() {
+        let provider = MockProvider::new()
+            .with_fallback(MockResponse::new("Slow").with_delay(Duration::from_mins(1)));
+        let (tx, _rx) = mpsc::unbounded();
+        let (handle, signal) = abort_pair();
+        handle.abort();
+
+        let result = provider
+            .execute(&context("Explain"), &action("echo"), None, tx, signal)
+            .await;
+
+        assert!(matches!(result, Err(AiProviderError::Cancelled)));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_agent_fails() {
+        let registry = ProviderRegistry::new().with_provider(Arc::new(MockProvider::new()));
+        let provider = registry.get(PROVIDER_NAME).unwrap();
+        let (tx, _rx) = mpsc::unbounded();
+
+        let agents = provider.list_agents(Path::new("/repo")).await.unwrap();
+        assert_eq!(
+            agents
+                .iter()
+                .map(|agent| agent.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["mock:echo", "mock:edit"]
+        );
+
+        let result = provider
+            .execute(
+                &context("Explain"),
+                &action("plan"),
+                None,
+                tx,
+                AbortSignal::never(),
+            )
+            .await;
+        assert!(matches!(result, Err(AiProviderError::AgentNotFound(agent)) if agent == "plan"));
+    }
+}
@@ -28,10 +28,15 @@ pub struct AiContext {
     pub line: Option<String>,
     /// Code snippet around the commented line.
     pub diff_hunk: Option<String>,
+    /// The entire diff, when the action is about all of it (e.g. reviewing
+    /// the whole diff).
+    pub diff: Option<String>,
     /// The user's comment body (their instruction/question).
     pub comment_body: String,
     /// Previous messages in the thread for context.
     pub thread_history: Vec<ThreadMessage>,
+    /// The repository's template replacing the built prompt, if it has one.
+    pub prompt_template: Option<String>,
 }
 
 /// A message in a comment thread (for conversation context).
@@ -89,6 +94,16 @@ pub enum AgentSource {
     BuiltIn,
 }
 
+/// An update sent by a provider while it executes an action.
+#[derive(Debug, Clone)]
+pub enum AiProgress {
+    /// The provider started a step, such as a tool call.
+    Step(models::ProgressEntry),
+    /// The response text generated so far (markdown), replacing any earlier
+    /// partial text.
+    Text(String),
+}
+
 /// Response from AI execution.
 #[derive(Debug, Clone)]
 pub struct AiResponse {
@@ -113,8 +128,10 @@ impl AiContext {
             file_path: None,
             line: None,
             diff_hunk: None,
+            diff: None,
             comment_body,
             thread_history: vec![],
+            prompt_template: None,
         }
     }
 
@@ -139,10 +156,24 @@ impl AiContext {
         self
     }
 
+    /// Set the entire diff.
+    #[must_use]
+    pub fn with_diff(mut self, diff: String) -> Self {
+        self.diff = Some(diff);
+        self
+    }
+
     /// Set the thread history.
     #[must_use]
     pub fn with_thread_history(mut self, history: Vec<ThreadMessage>) -> Self {
         self.thread_history = history;
         self
     }
+
+    /// Set the prompt template.
+    #[must_use]
+    pub fn with_prompt_template(mut self, template: String) -> Self {
+        self.prompt_template = Some(template);
+        self
+    }
 }
@@ -0,0 +1,152 @@
+//! Cancellation of running AI executions.
+
+use std::future::{Future, poll_fn};
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::Poll;
+
+use switchy::unsync::sync::mpsc;
+
+/// Create a connected abort handle and signal. Aborting the handle stops
+/// the execution holding the signal.
+#[must_use]
+pub fn abort_pair() -> (AbortHandle, AbortSignal) {
+    let aborted = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = mpsc::unbounded();
+
+    (
+        AbortHandle {
+            aborted: Arc::clone(&aborted),
+            tx,
+        },
+        AbortSignal { aborted, rx },
+    )
+}
+
+/// Aborts the execution holding the connected [`AbortSignal`].
+#[derive(Clone)]
+pub struct AbortHandle {
+    aborted: Arc<AtomicBool>,
+    tx: mpsc::Sender<()>,
+}
+
+impl AbortHandle {
+    /// Abort the execution. Aborting more than once has no further effect.
+    pub fn abort(&self) {
+        if !self.aborted.swap(true, Ordering::SeqCst) {
+            let _ = self.tx.send(());
+        }
+    }
+}
+
+impl std::fmt::Debug for AbortHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AbortHandle")
+            .field("aborted", &self.aborted.load(Ordering::SeqCst))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Tells an [`AiProvider::execute`](crate::AiProvider::execute) call to
+/// stop, handing back control of its process.
+pub struct AbortSignal {
+    aborted: Arc<AtomicBool>,
+    rx: mpsc::Receiver<()>,
+}
+
+impl AbortSignal {
+    /// A signal that is never aborted.
+    #[must_use]
+    pub fn never() -> Self {
+        abort_pair().1
+    }
+
+    /// Whether the execution has been aborted.
+    #[must_use]
+    pub fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the execution is aborted. Never completes if every
+    /// [`AbortHandle`] is dropped without aborting.
+    pub async fn aborted(&self) {
+        if self.is_aborted() {
+            return;
+        }
+        if self.rx.recv_async().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// Run `future` until it completes or the execution is aborted,
+    /// returning `None` if it was aborted first.
+    pub async fn abortable<F: Future>(&self, future: F) -> Option<F::Output> {
+        let mut future = pin!(future);
+        let mut aborted = pin!(self.aborted());
+
+        poll_fn(|cx| {
+            if let Poll::Ready(output) = future.as_mut().poll(cx) {
+                Poll::Ready(Some(output))
+            } else if aborted.as_mut().poll(cx).is_ready() {
+                Poll::Ready(None)
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl std::fmt::Debug for AbortSignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AbortSignal")
+            .field("aborted", &self.is_aborted())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::{Context, Waker};
+
+    use super::*;
+
+    fn poll_once<F: Future>(future: F) -> Poll<F::Output> {
+        pin!(future).poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    #[test]
+    fn test_abortable_completes_when_not_aborted() {
+        let (_handle, signal) = abort_pair();
+
+        assert_eq!(
+            poll_once(signal.abortable(async { 42 })),
+            Poll::Ready(Some(42))
+        );
+        assert!(!signal.is_aborted());
+    }
+
+    #[test]
+    fn test_abortable_stops_when_aborted() {
+        let (handle, signal) = abort_pair();
+        handle.abort();
+        handle.abort();
+
+        assert!(signal.is_aborted());
+        assert_eq!(
+            poll_once(signal.abortable(std::future::pending::<()>())),
+            Poll::Ready(None)
+        );
+    }
+
+    #[test]
+    fn test_never_aborted_signal_keeps_waiting() {
+        let signal = AbortSignal::never();
+
+        assert_eq!(
+            poll_once(signal.abortable(std::future::pending::<()>())),
+            Poll::Pending
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Conversation history of comment threads.
+//!
+//! An AI reply is handed the messages of its thread posted before the
+//! comment it answers, so a new session knows what was discussed. Long
+//! threads are cut to fit a token budget: the opening message and the most
+//! recent ones are kept, and the messages in between are replaced with a
+//! short summary of what was left out.
+
+use std::fmt::Write as _;
+
+use chadreview_ai_provider_models::ThreadMessage;
+use chadreview_local_comment_models::LocalComment;
+
+/// Token budget of the thread history given to an agent.
+pub const DEFAULT_HISTORY_TOKENS: usize = 8_000;
+
+/// Author of the summary replacing left out messages.
+const SUMMARY_AUTHOR: &str = "ChadReview";
+
+/// Length, in characters, of the excerpts of left out messages.
+const EXCERPT_LEN: usize = 80;
+
+/// The messages of `thread` posted before `comment`, oldest first.
+///
+/// Replies are taken in the order they were posted, wherever they sit in
+/// the thread. AI replies still being written, which have no body yet, are
+/// skipped.
+#[must_use]
+pub fn thread_history(thread: &LocalComment, comment: &LocalComment) -> Vec<ThreadMessage> {
+    let mut comments = vec![];
+    collect_comments(thread, &mut comments);
+    comments.sort_by_key(|c| c.created_at);
+
+    comments
+        .into_iter()
+        .take_while(|c| c.id != comment.id)
+        .filter(|c| !c.body.trim().is_empty())
+        .map(|c| ThreadMessage {
+            author: c.author.name.clone(),
+            body: c.body.clone(),
+            is_ai_response: c.author.is_ai(),
+            timestamp: c.created_at,
+        })
+        .collect()
+}
+
+/// Cut `history` down to about `max_tokens` tokens.
+///
+/// The opening message, which frames the discussion, is kept if it fits in
+/// half the budget, and the rest is filled with the most recent messages;
+/// the latest message is always kept. Messages left out in between are
+/// replaced with a single message listing their authors and first lines.
+#[must_use]
+pub fn truncate_thread_history(
+    history: Vec<ThreadMessage>,
+    max_tokens: usize,
+) -> Vec<ThreadMessage> {
+    let total: usize = history.iter().map(message_tokens).sum();
+    if total <= max_tokens || history.len() < 2 {
+        return history;
+    }
+
+    let keep_first = message_tokens(&history[0]) <= max_tokens / 2;
+    let mut budget = if keep_first {
+        max_tokens - message_tokens(&history[0])
+    } else {
+        max_tokens
+    };
+
+    let start = usize::from(keep_first);
+    let mut recent_from = history.len() - 1;
+    budget = budget.saturating_sub(message_tokens(&history[recent_from]));
+    while recent_from > start {
+        let tokens = message_tokens(&history[recent_from - 1]);
+        if tokens > budget {
+            break;
+        }
+        budget -= tokens;
+        recent_from -= 1;
+    }
+
+    let mut history = history;
+    let recent = history.split_off(recent_from);
+    let elided = history.split_off(start);
+
+    history.push(summarize_elided(&elided));
+    history.extend(recent);
+    history
+}
+
+/// Rough token count of `text`, at about four characters a token.
+#[must_use]
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+fn message_tokens(msg: &ThreadMessage) -> usize {
+    estimate_tokens(&msg.author) + estimate_tokens(&msg.body)
+}
+
+fn collect_comments<'a>(comment: &'a LocalComment, comments: &mut Vec<&'a LocalComment>) {
+    comments.push(comment);
+    for reply in &comment.replies {
+        collect_comments(reply, comments);
+    }
+}
+
+fn summarize_elided(elided: &[ThreadMessage]) -> ThreadMessage {
+    let mut body = if elided.len() == 1 {
+        "1 earlier message was left out to fit the context:\n".to_string()
+    } else {
+        format!(
+            "{} earlier messages were left out to fit the context:\n",
+            elided.len()
+        )
+    };
+    for msg in elided {
+        let first_line = msg.body.lines().find(|line| !line.trim().is_empty());
+        let first_line = first_line.unwrap_or_default().trim();
+        let excerpt: String = first_line.chars().take(EXCERPT_LEN).collect();
+        let ellipsis = if excerpt.len() < first_line.len() {
+            "..."
+        } else {
+            ""
+        };
+        writeln!(body, "- @{}: {excerpt}{ellipsis}", msg.author).unwrap();
+    }
+
+    ThreadMessage {
+        author: SUMMARY_AUTHOR.to_string(),
+        body,
+        is_ai_response: false,
+        timestamp: elided
+            .last()
+            .map_or_else(Default::default, |msg| msg.timestamp),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chadreview_local_comment_models::{LocalCommentType, LocalUser};
+    use chrono::DateTime;
+
+    use super::*;
+
+    fn user(name: &str) -> LocalUser {
+        LocalUser {
+            name: name.to_string(),
+            email: format!("{name}@example.com"),
+        }
+    }
+
+    fn message(author: &str, body: &str) -> ThreadMessage {
+        ThreadMessage {
+            author: author.to_string(),
+            body: body.to_string(),
+            is_ai_response: false,
+            timestamp: DateTime::default(),
+        }
+    }
+
+    #[test]
+    fn test_thread_history_stops_at_comment() {
+        let mut thread = LocalComment::new(
+            user("alice"),
+            "Why is this cloned?".to_string(),
+            LocalCommentType::General,
+        );
+        let reply = |author: LocalUser, body: &str| {
+            LocalComment::new(
+                author,
+                body.to_string(),
+                LocalCommentType::Reply {
+                    root_comment_id: thread.id,
+                    in_reply_to: thread.id,
+                },
+            )
+        };
+        let answer = reply(LocalUser::ai("plan"), "It outlives the borrow.");
+        let pending = reply(LocalUser::ai("plan"), "");
+        let question = reply(user("bob"), "Can we avoid it?");
+        let later = reply(user("alice"), "Thanks");
+        thread.replies = vec![answer, pending, question.clone(), later];
+
+        let history = thread_history(&thread, &question);
+
+        assert_eq!(
+            history
+                .iter()
+                .map(|msg| (msg.author.as_str(), msg.is_ai_response))
+                .collect::<Vec<_>>(),
+            vec![("alice", false), ("AI (plan)", true)]
+        );
+    }
+
+    #[test]
+    fn test_truncate_keeps_opening_and_recent_messages() {
+        let history = vec![
+            message("alice", "Why is this cloned?"),
+            message("bob", &"a".repeat(400)),
+            message("carol", &"b".repeat(400)),
+            message("alice", "Can we avoid it?"),
+        ];
+
+        let truncated = truncate_thread_history(history.clone(), 150);
+
+        assert_eq!(truncated.len(), 4);
+        assert_eq!(truncated[0].body, history[0].body);
+        assert_eq!(truncated[1].author, SUMMARY_AUTHOR);
+        assert!(truncated[1].body.starts_with("1 earlier message was"));
+        assert!(truncated[1].body.contains("- @bob: "));
+        assert_eq!(truncated[2].body, history[2].body);
+        assert_eq!(truncated[3].body, history[3].body);
+
+        assert_eq!(truncate_thread_history(history, 1_000).len(), 4);
+    }
+}
@@ -4,12 +4,28 @@
 
 //! AI provider abstraction for `ChadReview`.
 //!
-//! This crate provides the `AiProvider` trait for integrating with AI systems.
+//! This crate provides the `AiProvider` trait for integrating with AI systems,
+//! the `ProviderRegistry` choosing which installed provider runs an action,
+//! the `AbortHandle` cancelling a running one, the conversation history
+//! handed to replies, and the custom prompt templates a repository can
+//! replace the built prompt with.
 
+mod abort;
+mod history;
+mod prompt;
 mod provider;
+mod registry;
 
+pub use abort::{AbortHandle, AbortSignal, abort_pair};
 pub use chadreview_ai_provider_models as models;
+pub use history::{
+    DEFAULT_HISTORY_TOKENS, estimate_tokens, thread_history, truncate_thread_history,
+};
+pub use prompt::{
+    PROMPTS_DIR, format_thread_history, load_prompt_template, render_prompt_template,
+};
 pub use provider::{AiProvider, AiProviderError};
+pub use registry::ProviderRegistry;
 
 // Re-export the channel types for convenience
 pub use switchy::unsync::sync::mpsc;
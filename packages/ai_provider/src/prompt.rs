@@ -0,0 +1,177 @@
+//! Custom prompt templates.
+//!
+//! A repository can replace the prompt built from an [`AiContext`] with its
+//! own, e.g. to hold its coding standards or review checklist. Templates
+//! live in [`PROMPTS_DIR`]: `<agent>.md` is used for the agent of that name
+//! and `default.md` for every other agent.
+//!
+//! Templates are markdown with `{{variable}}` placeholders, filled in from
+//! the context:
+//!
+//! - `{{repo_path}}` - repository root path
+//! - `{{diff_description}}` - what is being diffed (e.g. `main..feature`)
+//! - `{{file_path}}`, `{{line}}` - location of a file or line comment
+//! - `{{diff_hunk}}` - code snippet around the commented line
+//! - `{{diff}}` - the entire diff, for actions about all of it
+//! - `{{thread_history}}` - previous messages of the thread
+//! - `{{comment_body}}` - the request itself
+//!
+//! Variables the context doesn't have are left empty, and unknown ones are
+//! kept as written.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use chadreview_ai_provider_models::{AiContext, ThreadMessage};
+
+/// Directory holding prompt templates, relative to the repository root.
+pub const PROMPTS_DIR: &str = ".chadreview/prompts";
+
+/// Template used for agents without one of their own.
+const DEFAULT_TEMPLATE: &str = "default";
+
+/// Load the prompt template of `agent` in the repository at `repo_path`,
+/// falling back to the repository's default template.
+///
+/// # Errors
+///
+/// Returns an error if a template exists but can't be read.
+pub fn load_prompt_template(repo_path: &Path, agent: &str) -> io::Result<Option<String>> {
+    let dir = repo_path.join(PROMPTS_DIR);
+
+    for name in [agent, DEFAULT_TEMPLATE] {
+        // Agent names come from requests, so never let them leave the directory
+        if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+            continue;
+        }
+        match fs::read_to_string(dir.join(format!("{name}.md"))) {
+            Ok(template) => return Ok(Some(template)),
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(None)
+}
+
+/// Fill in the `{{variable}}` placeholders of `template` from `context`.
+#[must_use]
+pub fn render_prompt_template(template: &str, context: &AiContext) -> String {
+    let mut prompt = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let placeholder = &rest[start..start + 2 + len + 2];
+        prompt.push_str(&rest[..start]);
+        match variable(rest[start + 2..start + 2 + len].trim(), context) {
+            Some(value) => prompt.push_str(&value),
+            None => prompt.push_str(placeholder),
+        }
+        rest = &rest[start + placeholder.len()..];
+    }
+    prompt.push_str(rest);
+
+    prompt
+}
+
+/// Format the messages of a thread for a prompt, each led by its author
+/// and time.
+#[must_use]
+pub fn format_thread_history(history: &[ThreadMessage]) -> String {
+    let mut text = String::new();
+
+    for msg in history {
+        let author = if msg.is_ai_response {
+            "AI"
+        } else {
+            &msg.author
+        };
+        write!(
+            text,
+            "[@{} at {}]:\n{}\n\n",
+            author,
+            msg.timestamp.format("%Y-%m-%d %H:%M"),
+            msg.body
+        )
+        .unwrap();
+    }
+
+    text
+}
+
+fn variable(name: &str, context: &AiContext) -> Option<String> {
+    Some(match name {
+        "repo_path" => context.repo_path.display().to_string(),
+        "diff_description" => context.diff_description.clone(),
+        "file_path" => context.file_path.clone().unwrap_or_default(),
+        "line" => context.line.clone().unwrap_or_default(),
+        "diff_hunk" => context.diff_hunk.clone().unwrap_or_default(),
+        "diff" => context.diff.clone().unwrap_or_default(),
+        "thread_history" => format_thread_history(&context.thread_history),
+        "comment_body" => context.comment_body.clone(),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn context() -> AiContext {
+        AiContext::new(
+            PathBuf::from("/repo"),
+            "main..feature".to_string(),
+            "Why?".to_string(),
+        )
+        .with_file_path("src/lib.rs".to_string())
+        .with_line("n42".to_string())
+    }
+
+    #[test]
+    fn test_render_prompt_template() {
+        let prompt = render_prompt_template(
+            "Follow STYLE.md.\n{{ file_path }}:{{line}} in {{diff_description}}\n{{diff}}{{comment_body}} {{unknown}} {{",
+            &context(),
+        );
+
+        assert_eq!(
+            prompt,
+            "Follow STYLE.md.\nsrc/lib.rs:n42 in main..feature\nWhy? {{unknown}} {{"
+        );
+    }
+
+    #[test]
+    fn test_load_prompt_template_prefers_agent_template() {
+        let repo =
+            std::env::temp_dir().join(format!("chadreview-prompt-test-{}", std::process::id()));
+        let dir = repo.join(PROMPTS_DIR);
+        fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(load_prompt_template(&repo, "plan").unwrap(), None);
+
+        fs::write(dir.join("default.md"), "Default").unwrap();
+        fs::write(dir.join("plan.md"), "Plan").unwrap();
+
+        assert_eq!(
+            load_prompt_template(&repo, "plan").unwrap().as_deref(),
+            Some("Plan")
+        );
+        assert_eq!(
+            load_prompt_template(&repo, "build").unwrap().as_deref(),
+            Some("Default")
+        );
+        assert_eq!(
+            load_prompt_template(&repo, "../plan").unwrap().as_deref(),
+            Some("Default")
+        );
+
+        fs::remove_dir_all(&repo).unwrap();
+    }
+}
@@ -7,9 +7,11 @@ use async_trait::async_trait;
 use switchy::unsync::sync::mpsc;
 
 use chadreview_ai_provider_models::{
-    AiActionDefinition, AiContext, AiResponse, models::ExecutionDetails,
+    AiActionDefinition, AiContext, AiProgress, AiResponse, models::ExecutionDetails,
 };
-use chadreview_local_comment_models::{AiAction, ProgressEntry};
+use chadreview_local_comment_models::AiAction;
+
+use crate::AbortSignal;
 
 /// Errors that can occur when using an AI provider.
 #[derive(Debug, thiserror::Error)]
@@ -49,6 +51,10 @@ pub enum AiProviderError {
     /// Failed to parse output.
     #[error("Failed to parse output: {0}")]
     ParseError(String),
+
+    /// Execution was cancelled through its abort handle.
+    #[error("Execution cancelled")]
+    Cancelled,
 }
 
 /// Trait for AI provider implementations.
@@ -75,8 +81,12 @@ pub trait AiProvider: Send + Sync {
 
     /// Execute an AI action with the given context.
     ///
-    /// Progress updates should be sent through the provided channel.
-    /// The caller can use these to update the UI in real-time.
+    /// Progress updates (steps taken and the response text generated so far)
+    /// should be sent through the provided channel. The caller can use these
+    /// to update the UI in real-time.
+    ///
+    /// Once `abort` is aborted, the provider should stop any process it
+    /// started and return [`AiProviderError::Cancelled`].
     ///
     /// # Arguments
     ///
@@ -84,16 +94,18 @@ pub trait AiProvider: Send + Sync {
     /// * `action` - The action to execute (agent, model, etc.)
     /// * `session_id` - Optional session ID to continue a previous conversation
     /// * `progress_tx` - Channel to send progress updates
+    /// * `abort` - Signal to stop the execution early
     ///
     /// # Errors
     ///
-    /// Returns an error if execution fails.
+    /// Returns an error if execution fails or is cancelled.
     async fn execute(
         &self,
         context: &AiContext,
         action: &AiAction,
         session_id: Option<&str>,
-        progress_tx: mpsc::Sender<ProgressEntry>,
+        progress_tx: mpsc::Sender<AiProgress>,
+        abort: AbortSignal,
     ) -> Result<AiResponse, AiProviderError>;
 
     /// Format execution details as markdown ("How I worked on this").
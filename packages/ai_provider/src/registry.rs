@@ -0,0 +1,148 @@
+//! Registry of installed AI providers.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::{AiProvider, AiProviderError};
+
+/// The installed AI providers, keyed by [`AiProvider::provider_name`].
+///
+/// An `AiAction`'s `provider` picks the provider that executes it, so each
+/// comment can use any installed provider.
+#[derive(Default, Clone)]
+pub struct ProviderRegistry {
+    providers: BTreeMap<&'static str, Arc<dyn AiProvider>>,
+}
+
+impl ProviderRegistry {
+    /// Create an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Install `provider`, replacing any provider with the same name.
+    #[must_use]
+    pub fn with_provider(mut self, provider: Arc<dyn AiProvider>) -> Self {
+        self.register(provider);
+        self
+    }
+
+    /// Install `provider`, replacing any provider with the same name.
+    pub fn register(&mut self, provider: Arc<dyn AiProvider>) {
+        self.providers.insert(provider.provider_name(), provider);
+    }
+
+    /// Get the provider named `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AiProviderError::ProviderNotFound`] if no provider with that
+    /// name is installed.
+    pub fn get(&self, name: &str) -> Result<Arc<dyn AiProvider>, AiProviderError> {
+        self.providers
+            .get(name)
+            .cloned()
+            .ok_or_else(|| AiProviderError::ProviderNotFound(name.to_string()))
+    }
+
+    /// Names of the installed providers, in alphabetical order.
+    #[must_use]
+    pub fn provider_names(&self) -> Vec<&'static str> {
+        self.providers.keys().copied().collect()
+    }
+
+    /// The installed providers, in alphabetical order of their names.
+    pub fn providers(&self) -> impl Iterator<Item = &Arc<dyn AiProvider>> {
+        self.providers.values()
+    }
+
+    /// Whether no provider is installed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+}
+
+impl std::fmt::Debug for ProviderRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProviderRegistry")
+            .field("providers", &self.provider_names())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use async_trait::async_trait;
+    use chadreview_ai_provider_models::{AiActionDefinition, AiContext, AiProgress, AiResponse};
+    use chadreview_local_comment_models::AiAction;
+    use switchy::unsync::sync::mpsc;
+
+    use super::*;
+    use crate::AbortSignal;
+
+    struct NamedProvider(&'static str);
+
+    #[async_trait]
+    impl AiProvider for NamedProvider {
+        fn provider_name(&self) -> &'static str {
+            self.0
+        }
+
+        async fn list_agents(
+            &self,
+            _repo_path: &Path,
+        ) -> Result<Vec<AiActionDefinition>, AiProviderError> {
+            Ok(vec![])
+        }
+
+        async fn execute(
+            &self,
+            _context: &AiContext,
+            _action: &AiAction,
+            _session_id: Option<&str>,
+            _progress_tx: mpsc::Sender<AiProgress>,
+            _abort: AbortSignal,
+        ) -> Result<AiResponse, AiProviderError> {
+            Err(AiProviderError::ExecutionFailed(self.0.to_string()))
+        }
+    }
+
+    #[test]
+    fn test_get_provider_by_name() {
+        let registry = ProviderRegistry::new()
+            .with_provider(Arc::new(NamedProvider("opencode")))
+            .with_provider(Arc::new(NamedProvider("claude")));
+
+        assert_eq!(registry.provider_names(), vec!["claude", "opencode"]);
+        assert_eq!(
+            registry
+                .providers()
+                .map(|provider| provider.provider_name())
+                .collect::<Vec<_>>(),
+            vec!["claude", "opencode"]
+        );
+        assert_eq!(
+            registry.get("opencode").unwrap().provider_name(),
+            "opencode"
+        );
+        assert!(matches!(
+            registry.get("codex"),
+            Err(AiProviderError::ProviderNotFound(name)) if name == "codex"
+        ));
+    }
+
+    #[test]
+    fn test_register_replaces_provider_with_same_name() {
+        let mut registry = ProviderRegistry::new();
+        assert!(registry.is_empty());
+
+        registry.register(Arc::new(NamedProvider("opencode")));
+        registry.register(Arc::new(NamedProvider("opencode")));
+
+        assert_eq!(registry.provider_names(), vec!["opencode"]);
+    }
+}
@@ -0,0 +1,222 @@
+//! The AI providers installed for local comments.
+//!
+//! Providers are installed once, from the `[ai]` config section, into a
+//! [`ProviderRegistry`]. A comment's AI action names the provider that
+//! executes it, so comments can use different providers.
+//!
+//! A repository's prompt templates replace the prompt built for its AI
+//! actions, see [`chadreview_ai_provider::PROMPTS_DIR`].
+//!
+//! AI spend is recorded per repository and, once it reaches the `ai.budget`
+//! of the repository's config, new executions are blocked until the budget
+//! is overridden for the session.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard, OnceLock, PoisonError};
+
+use chadreview_ai_provider::{
+    AbortHandle, AbortSignal, AiProvider, ProviderRegistry, abort_pair,
+    models::{AiActionDefinition, AiContext},
+};
+use chadreview_config::Config;
+use chadreview_local_comment::LocalCommentStore;
+use chadreview_local_comment_models::{AiAction, ExecutionDetails, UsageEntry};
+use chrono::Utc;
+use switchy::uuid::Uuid;
+
+use crate::settings;
+
+/// Names of the providers this build can install.
+pub const AVAILABLE_PROVIDERS: &[&str] = &[
+    #[cfg(feature = "ai-integration-opencode")]
+    "opencode",
+    #[cfg(feature = "ai-integration-mock")]
+    "mock",
+];
+
+static PROVIDERS: OnceLock<ProviderRegistry> = OnceLock::new();
+
+/// Abort handles of the running executions, keyed by the ID of the comment
+/// holding the AI action.
+static RUNNING: LazyLock<Mutex<HashMap<Uuid, AbortHandle>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Repositories whose AI budget was overridden until the app restarts.
+static BUDGET_OVERRIDES: LazyLock<Mutex<HashSet<PathBuf>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Create a registry with the providers enabled in `config`: those listed
+/// in `ai.providers`, or every available one if none are listed.
+#[must_use]
+pub fn create_provider_registry(config: &Config) -> ProviderRegistry {
+    for name in &config.ai.providers {
+        if !AVAILABLE_PROVIDERS.contains(&name.as_str()) {
+            log::warn!("AI provider {name} is not available in this build");
+        }
+    }
+
+    AVAILABLE_PROVIDERS
+        .iter()
+        .copied()
+        .filter(|name| {
+            config.ai.providers.is_empty() || config.ai.providers.iter().any(|p| p == name)
+        })
+        .filter_map(|name| create_provider(name, config))
+        .fold(ProviderRegistry::new(), ProviderRegistry::with_provider)
+}
+
+/// Install the providers enabled in `config`. Only the first call installs
+/// providers; later calls return the installed ones.
+pub fn init_providers(config: &Config) -> &'static ProviderRegistry {
+    PROVIDERS.get_or_init(|| create_provider_registry(config))
+}
+
+/// The installed providers, installed from the global config if
+/// [`init_providers`] hasn't been called.
+#[must_use]
+pub fn providers() -> &'static ProviderRegistry {
+    PROVIDERS.get_or_init(|| create_provider_registry(&settings::load_config(None)))
+}
+
+/// The agents every installed provider offers for the repository at
+/// `repo_path`. Providers failing to list their agents are logged and
+/// skipped.
+pub async fn list_agents(repo_path: &Path) -> Vec<AiActionDefinition> {
+    let mut agents = vec![];
+
+    for provider in providers().providers() {
+        match provider.list_agents(repo_path).await {
+            Ok(provider_agents) => agents.extend(provider_agents),
+            Err(e) => log::warn!("Failed to list {} agents: {e}", provider.provider_name()),
+        }
+    }
+
+    agents
+}
+
+/// `context` with the prompt template of the repository it is for that
+/// applies to `ai_action`, if there is one. Templates that can't be read
+/// are logged and skipped.
+#[must_use]
+pub fn with_prompt_template(context: AiContext, ai_action: &AiAction) -> AiContext {
+    match chadreview_ai_provider::load_prompt_template(&context.repo_path, &ai_action.agent) {
+        Ok(Some(template)) => context.with_prompt_template(template),
+        Ok(None) => context,
+        Err(e) => {
+            log::warn!("Failed to read prompt template: {e}");
+            context
+        }
+    }
+}
+
+/// Record that the AI action of comment `comment_id` started executing,
+/// returning the signal that [`cancel_execution`] aborts.
+#[must_use]
+pub fn start_execution(comment_id: Uuid) -> AbortSignal {
+    let (handle, signal) = abort_pair();
+    running().insert(comment_id, handle);
+    signal
+}
+
+/// Record that the AI action of comment `comment_id` finished executing.
+pub fn finish_execution(comment_id: Uuid) {
+    running().remove(&comment_id);
+}
+
+/// Cancel the running AI action of comment `comment_id`. Returns whether it
+/// was running.
+pub fn cancel_execution(comment_id: Uuid) -> bool {
+    running()
+        .remove(&comment_id)
+        .inspect(AbortHandle::abort)
+        .is_some()
+}
+
+/// Whether the AI action of comment `comment_id` is executing in this
+/// process.
+#[must_use]
+pub fn is_running(comment_id: Uuid) -> bool {
+    running().contains_key(&comment_id)
+}
+
+fn running() -> MutexGuard<'static, HashMap<Uuid, AbortHandle>> {
+    RUNNING.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The spend and budget of the repository at `repo_path`, in dollars, if
+/// its AI spend reached its budget and the budget isn't overridden.
+///
+/// A ledger that can't be read is logged and doesn't block executions.
+#[must_use]
+pub fn exceeded_budget(repo_path: &Path, store: &LocalCommentStore) -> Option<(f64, f64)> {
+    let budget = settings::load_config(Some(repo_path)).ai.budget?;
+    if budget_overridden(repo_path) {
+        return None;
+    }
+
+    let spent = store
+        .load_usage()
+        .inspect_err(|e| log::warn!("Failed to load AI usage: {e}"))
+        .ok()?
+        .total_cost();
+
+    (spent >= budget).then_some((spent, budget))
+}
+
+/// Let AI actions in the repository at `repo_path` run past its budget
+/// until the app restarts.
+pub fn override_budget(repo_path: &Path) {
+    budget_overrides().insert(repo_path.to_path_buf());
+}
+
+/// Whether the budget of the repository at `repo_path` was overridden.
+#[must_use]
+pub fn budget_overridden(repo_path: &Path) -> bool {
+    budget_overrides().contains(repo_path)
+}
+
+fn budget_overrides() -> MutexGuard<'static, HashSet<PathBuf>> {
+    BUDGET_OVERRIDES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Record the usage of an execution in the repository's ledger, along with
+/// the comment whose AI action ran, if any. Failures are logged.
+pub fn record_usage(
+    store: &LocalCommentStore,
+    comment_id: Option<Uuid>,
+    details: &ExecutionDetails,
+) {
+    let entry = UsageEntry {
+        comment_id,
+        recorded_at: Utc::now(),
+        model: details.model_used.clone(),
+        tokens: details.tokens.clone(),
+        cost: details.cost,
+    };
+
+    store
+        .record_usage(entry)
+        .inspect_err(|e| log::warn!("Failed to record AI usage: {e}"))
+        .ok();
+}
+
+#[cfg_attr(not(feature = "ai-integration-opencode"), allow(unused_variables))]
+#[cfg_attr(
+    not(any(feature = "ai-integration-opencode", feature = "ai-integration-mock")),
+    allow(clippy::match_single_binding, clippy::unnecessary_wraps)
+)]
+fn create_provider(name: &str, config: &Config) -> Option<Arc<dyn AiProvider>> {
+    match name {
+        #[cfg(feature = "ai-integration-opencode")]
+        "opencode" => Some(Arc::new(config.ai.opencode_binary.clone().map_or_else(
+            chadreview_opencode_provider::OpenCodeProvider::new,
+            chadreview_opencode_provider::OpenCodeProvider::with_binary_path,
+        ))),
+        #[cfg(feature = "ai-integration-mock")]
+        "mock" => Some(Arc::new(chadreview_ai_provider_mock::MockProvider::new())),
+        _ => None,
+    }
+}
@@ -0,0 +1,309 @@
+//! Signing in to GitHub through the OAuth device flow.
+//!
+//! `/login` shows a one-time code to enter on github.com while a background
+//! task polls for the access token. The token is handed to the
+//! [`GitHubProvider`] and, with the `keyring` feature, saved in the OS
+//! keyring so later runs start signed in without `GITHUB_TOKEN`.
+
+use std::sync::{Arc, RwLock};
+
+use chadreview_github::{DeviceFlow, GitHubProvider};
+use chrono::{DateTime, Utc};
+use hyperchad::router::{Container, RouteRequest, Router};
+use hyperchad_template::{Containers, Selector, container};
+use switchy::http::models::Method;
+
+/// Environment variable holding the client ID of the OAuth app to sign in
+/// with. The app must have device flow enabled.
+pub const CLIENT_ID_ENV: &str = "GITHUB_CLIENT_ID";
+
+const LOGIN_STATUS_ID: &str = "login-status";
+
+/// Error type for auth routes.
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    /// The request method is not supported for this route.
+    #[error("Unsupported method")]
+    UnsupportedMethod,
+}
+
+/// Where the sign-in flow is at.
+#[derive(Debug, Clone)]
+pub enum LoginState {
+    SignedOut,
+    /// Waiting for the user to enter `user_code` at `verification_uri`.
+    Pending {
+        user_code: String,
+        verification_uri: String,
+        expires_at: DateTime<Utc>,
+    },
+    SignedIn,
+    Failed(String),
+}
+
+/// Sign-in state shared by the auth routes.
+pub struct Auth {
+    provider: Arc<GitHubProvider>,
+    client_id: Option<String>,
+    state: RwLock<LoginState>,
+}
+
+impl Auth {
+    /// Create the sign-in state for `provider`. Without a `client_id`,
+    /// `/login` explains how to configure one instead of signing in.
+    #[must_use]
+    pub fn new(provider: Arc<GitHubProvider>, client_id: Option<String>) -> Arc<Self> {
+        let state = if provider.has_token() {
+            LoginState::SignedIn
+        } else {
+            LoginState::SignedOut
+        };
+
+        Arc::new(Self {
+            provider,
+            client_id,
+            state: RwLock::new(state),
+        })
+    }
+
+    /// Read the client ID from [`CLIENT_ID_ENV`].
+    #[must_use]
+    pub fn from_env(provider: Arc<GitHubProvider>) -> Arc<Self> {
+        Self::new(provider, std::env::var(CLIENT_ID_ENV).ok())
+    }
+
+    #[must_use]
+    pub fn state(&self) -> LoginState {
+        self.state
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    fn set_state(&self, state: LoginState) {
+        *self
+            .state
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = state;
+    }
+
+    /// Start a device authorization unless one is already pending, and
+    /// poll for its token in the background.
+    async fn start_login(self: &Arc<Self>) {
+        let Some(client_id) = &self.client_id else {
+            return;
+        };
+        match self.state() {
+            LoginState::SignedIn => return,
+            LoginState::Pending { expires_at, .. } if expires_at > Utc::now() => return,
+            LoginState::SignedOut | LoginState::Pending { .. } | LoginState::Failed(_) => {}
+        }
+
+        let flow = DeviceFlow::new(client_id.clone());
+        let code = match flow.request_code().await {
+            Ok(code) => code,
+            Err(e) => {
+                log::error!("Failed to start GitHub sign-in: {e}");
+                self.set_state(LoginState::Failed(e.to_string()));
+                return;
+            }
+        };
+
+        let expires_in = i64::try_from(code.expires_in).unwrap_or(i64::MAX);
+        self.set_state(LoginState::Pending {
+            user_code: code.user_code.clone(),
+            verification_uri: code.verification_uri.clone(),
+            expires_at: Utc::now() + chrono::Duration::seconds(expires_in),
+        });
+
+        let auth = Arc::clone(self);
+        switchy::unsync::task::spawn(async move {
+            match flow.wait_for_token(&code).await {
+                Ok(token) => {
+                    log::info!("Signed in to GitHub");
+                    token_store::save_token(&token);
+                    auth.provider.set_token(Some(token));
+                    auth.set_state(LoginState::SignedIn);
+                }
+                Err(e) => {
+                    log::warn!("GitHub sign-in failed: {e}");
+                    auth.set_state(LoginState::Failed(e.to_string()));
+                }
+            }
+        });
+    }
+
+    fn logout(&self) {
+        token_store::delete_token();
+        self.provider.set_token(None);
+        self.set_state(LoginState::SignedOut);
+    }
+}
+
+/// Persisting the token between runs.
+pub mod token_store {
+    #[cfg(feature = "keyring")]
+    const SERVICE: &str = "chadreview";
+    #[cfg(feature = "keyring")]
+    const USER: &str = "github";
+
+    /// The token saved by a previous sign-in, if any.
+    #[must_use]
+    pub fn load_token() -> Option<String> {
+        #[cfg(feature = "keyring")]
+        match keyring::Entry::new(SERVICE, USER).and_then(|entry| entry.get_password()) {
+            Ok(token) => return Some(token),
+            Err(keyring::Error::NoEntry) => {}
+            Err(e) => log::warn!("Failed to read GitHub token from the keyring: {e}"),
+        }
+
+        None
+    }
+
+    /// Save the token for the next run. Without the `keyring` feature the
+    /// token only lasts until the app exits.
+    pub fn save_token(token: &str) {
+        #[cfg(feature = "keyring")]
+        if let Err(e) =
+            keyring::Entry::new(SERVICE, USER).and_then(|entry| entry.set_password(token))
+        {
+            log::warn!("Failed to save GitHub token to the keyring: {e}");
+        }
+        #[cfg(not(feature = "keyring"))]
+        let _ = token;
+    }
+
+    pub fn delete_token() {
+        #[cfg(feature = "keyring")]
+        match keyring::Entry::new(SERVICE, USER).and_then(|entry| entry.delete_credential()) {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) => log::warn!("Failed to delete GitHub token from the keyring: {e}"),
+        }
+    }
+}
+
+/// Add the `/login`, `/login/status` and `/logout` routes to an existing
+/// router.
+#[must_use]
+pub fn add_auth_routes(router: Router, auth: &Arc<Auth>) -> Router {
+    router
+        .with_route_result("/login", {
+            let auth = Arc::clone(auth);
+            move |req: RouteRequest| {
+                let auth = Arc::clone(&auth);
+                async move { login_route(&req, &auth).await }
+            }
+        })
+        .with_route_result("/login/status", {
+            let auth = Arc::clone(auth);
+            move |req: RouteRequest| {
+                let auth = Arc::clone(&auth);
+                async move { login_status_route(&req, &auth) }
+            }
+        })
+        .with_route_result("/logout", {
+            let auth = Arc::clone(auth);
+            move |req: RouteRequest| {
+                let auth = Arc::clone(&auth);
+                async move { logout_route(&req, &auth) }
+            }
+        })
+}
+
+async fn login_route(req: &RouteRequest, auth: &Arc<Auth>) -> Result<Container, AuthError> {
+    if !matches!(req.method, Method::Get) {
+        return Err(AuthError::UnsupportedMethod);
+    }
+
+    auth.start_login().await;
+
+    Ok(container! {
+        div padding=20 gap=12 {
+            h1 font-size=24 font-weight=600 color="#24292f" { "Sign in to GitHub" }
+            (render_login_status(auth))
+        }
+    }
+    .into())
+}
+
+fn login_status_route(req: &RouteRequest, auth: &Auth) -> Result<Container, AuthError> {
+    if !matches!(req.method, Method::Get) {
+        return Err(AuthError::UnsupportedMethod);
+    }
+
+    Ok(render_login_status(auth).into())
+}
+
+fn logout_route(req: &RouteRequest, auth: &Auth) -> Result<Container, AuthError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(AuthError::UnsupportedMethod);
+    }
+
+    auth.logout();
+
+    Ok(render_login_status(auth).into())
+}
+
+fn render_login_status(auth: &Auth) -> Containers {
+    if auth.client_id.is_none() && !matches!(auth.state(), LoginState::SignedIn) {
+        return container! {
+            div id=(LOGIN_STATUS_ID) gap=8 {
+                span font-size=14 color="#24292f" {
+                    (format!(
+                        "Set {CLIENT_ID_ENV} to the client ID of a GitHub OAuth app with device flow enabled, or set GITHUB_TOKEN."
+                    ))
+                }
+            }
+        };
+    }
+
+    container! {
+        div id=(LOGIN_STATUS_ID) gap=8 {
+            @match auth.state() {
+                LoginState::SignedIn => {
+                    span font-size=14 color="#1a7f37" { "Signed in to GitHub." }
+                    form
+                        hx-post="/logout"
+                        hx-swap="outerHTML"
+                        hx-target=(Selector::Id(LOGIN_STATUS_ID.to_string()))
+                    {
+                        button type=submit color="#cf222e" cursor=pointer font-size=14 {
+                            "Sign out"
+                        }
+                    }
+                }
+                LoginState::Pending { user_code, verification_uri, .. } => {
+                    span font-size=14 color="#24292f" { "Open this page and enter the code:" }
+                    anchor href=(verification_uri) color="#0969da" font-size=14 {
+                        (verification_uri)
+                    }
+                    span font-size=24 font-weight=600 { (user_code) }
+                    (render_check_button("Check again"))
+                }
+                LoginState::Failed(error) => {
+                    span font-size=14 color="#cf222e" { (format!("Sign-in failed: {error}")) }
+                    anchor href="/login" color="#0969da" font-size=14 { "Try again" }
+                }
+                LoginState::SignedOut => {
+                    anchor href="/login" color="#0969da" font-size=14 { "Sign in" }
+                }
+            }
+        }
+    }
+}
+
+fn render_check_button(label: &str) -> Containers {
+    container! {
+        button
+            type=button
+            hx-get="/login/status"
+            hx-swap="outerHTML"
+            hx-target=(Selector::Id(LOGIN_STATUS_ID.to_string()))
+            cursor=pointer
+            font-size=14
+            color="#0969da"
+        {
+            (label)
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Responses for the autocomplete dropdowns of comment forms.
+//!
+//! The routes backing a dropdown (see
+//! [`chadreview_app_ui::autocomplete`]) collect their candidates as
+//! [`Suggestion`]s and answer with [`suggestions_response`], which picks the
+//! ones matching what was typed.
+
+use std::collections::HashSet;
+
+use hyperchad::renderer::Content;
+
+/// Most suggestions shown in a dropdown.
+pub const MAX_SUGGESTIONS: usize = 10;
+
+/// A candidate for completing a word in a comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// Text inserted after the trigger character.
+    pub value: String,
+    /// Text shown in the dropdown.
+    pub label: String,
+}
+
+/// Respond with the `suggestions` matching `query`, as a JSON array of
+/// `{value, label}` objects.
+///
+/// Suggestions whose value starts with the query come first, followed by
+/// the ones containing it anywhere in their label, each in the order given
+/// and ignoring case. Duplicate values are only listed once.
+#[must_use]
+pub fn suggestions_response(
+    suggestions: impl IntoIterator<Item = Suggestion>,
+    query: &str,
+) -> Content {
+    let query = query.to_lowercase();
+    let mut prefix_matches = vec![];
+    let mut other_matches = vec![];
+    let mut seen = HashSet::new();
+
+    for suggestion in suggestions {
+        if !seen.insert(suggestion.value.clone()) {
+            continue;
+        }
+        if suggestion.value.to_lowercase().starts_with(&query) {
+            prefix_matches.push(suggestion);
+        } else if suggestion.label.to_lowercase().contains(&query) {
+            other_matches.push(suggestion);
+        }
+    }
+
+    let matches: Vec<_> = prefix_matches
+        .into_iter()
+        .chain(other_matches)
+        .take(MAX_SUGGESTIONS)
+        .map(|suggestion| {
+            serde_json::json!({
+                "value": suggestion.value,
+                "label": suggestion.label,
+            })
+        })
+        .collect();
+
+    Content::Raw {
+        data: serde_json::Value::Array(matches)
+            .to_string()
+            .into_bytes()
+            .into(),
+        content_type: "application/json".to_string(),
+    }
+}
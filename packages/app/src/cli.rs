@@ -0,0 +1,480 @@
+//! The `chadreview` command line.
+//!
+//! Without a subcommand the server runs in this process, configured by the
+//! `PORT` and `HOST` environment variables. Subcommands that open a view
+//! start the server as a child process on `--port` (any free port for `0`)
+//! and open the browser at the view once it is listening. `chadreview
+//! comments` and `chadreview export` work with local comments without
+//! starting the server, and `chadreview tui` reviews local changes in the
+//! terminal.
+
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command as Process, Stdio};
+use std::time::{Duration, Instant};
+
+use clap::{Parser, Subcommand};
+
+#[cfg(feature = "local-git")]
+use {
+    crate::local_routes::{LocalRouteError, describe_line, load_threads},
+    chadreview_git_backend::GitBackend,
+    chadreview_local_comment::LocalCommentStore,
+    chadreview_local_comment_models::{LocalComment, LocalCommentType, ThreadState},
+    chadreview_local_diff::LocalDiffProvider,
+    chadreview_local_diff_models::{DiffSpec, DiffSpecError},
+    chadreview_pr_models::DiffFile,
+    std::sync::Arc,
+};
+
+/// How long to wait for the server to start listening.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Error type for the command line.
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    /// The PR reference isn't `owner/repo#123` or a PR URL.
+    #[error("Invalid PR reference: {0}")]
+    InvalidPrReference(String),
+    /// Error starting or reaching the server, or writing output.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The server exited or didn't listen in time.
+    #[error("Server didn't start listening on port {0}")]
+    ServerNotListening(u16),
+    /// Invalid diff arguments.
+    #[cfg(feature = "local-git")]
+    #[error("Invalid diff spec: {0}")]
+    DiffSpec(#[from] DiffSpecError),
+    /// Error loading the diff or local comments.
+    #[cfg(feature = "local-git")]
+    #[error(transparent)]
+    Local(#[from] LocalRouteError),
+    /// Error running the terminal frontend.
+    #[cfg(feature = "tui")]
+    #[error(transparent)]
+    Tui(#[from] crate::tui::TuiCommandError),
+}
+
+/// Arguments for `chadreview`.
+#[derive(Debug, Parser)]
+#[command(
+    name = "chadreview",
+    about = "Review pull requests and local git changes"
+)]
+pub struct Cli {
+    /// What to do instead of serving in this process
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Subcommands of `chadreview`.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Review local git changes in the browser
+    #[cfg(feature = "local-git")]
+    Local {
+        /// What to diff: `base..head`, `base...head`, a commit or
+        /// comma-separated commits (defaults to the working tree)
+        revisions: Option<String>,
+
+        /// Path to the repository (defaults to the current directory)
+        #[arg(long)]
+        repo: Option<PathBuf>,
+
+        /// Only show staged changes
+        #[arg(long, conflicts_with = "revisions")]
+        staged: bool,
+
+        /// What to diff the working tree against
+        #[arg(long, conflicts_with = "revisions")]
+        against: Option<String>,
+
+        /// Exclude untracked files
+        #[arg(long, conflicts_with = "revisions")]
+        no_untracked: bool,
+
+        #[command(flatten)]
+        server: ServerArgs,
+    },
+    /// Review a pull request in the browser
+    Pr {
+        /// The PR, as `owner/repo#123` or its URL
+        reference: String,
+
+        #[command(flatten)]
+        server: ServerArgs,
+    },
+    /// Export a diff and its local comments as a self-contained HTML page
+    #[cfg(feature = "local-git")]
+    Export {
+        /// The diff, as for `chadreview local` (defaults to the working tree)
+        #[arg(long)]
+        spec: Option<String>,
+
+        /// Path to the repository (defaults to the current directory)
+        #[arg(long)]
+        repo: Option<PathBuf>,
+
+        /// File to write to (defaults to stdout)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// List or export local comments without starting the server
+    #[cfg(feature = "local-git")]
+    Comments {
+        #[command(subcommand)]
+        command: CommentsCommand,
+    },
+    /// Review local git changes in the terminal
+    #[cfg(feature = "tui")]
+    Tui(crate::tui::TuiArgs),
+    /// Move the local comments of every repository from JSON files into
+    /// SQLite
+    #[cfg(feature = "sqlite")]
+    MigrateComments,
+}
+
+/// Where to serve a view and whether to open it.
+#[derive(Debug, clap::Args)]
+pub struct ServerArgs {
+    /// Address to serve on (defaults to `HOST`, else 127.0.0.1)
+    #[arg(long)]
+    host: Option<String>,
+
+    /// Port to serve on, or 0 for any free port (defaults to `PORT`, else 3000)
+    #[arg(long)]
+    port: Option<u16>,
+
+    /// Print the view's URL without opening the browser
+    #[arg(long)]
+    no_open: bool,
+}
+
+/// Subcommands of `chadreview comments`.
+#[cfg(feature = "local-git")]
+#[derive(Debug, Subcommand)]
+pub enum CommentsCommand {
+    /// List the comment threads of a repository
+    List {
+        /// Path to the repository (defaults to the current directory)
+        #[arg(long)]
+        repo: Option<PathBuf>,
+
+        /// Include resolved and saved-for-later threads
+        #[arg(long)]
+        all: bool,
+    },
+    /// Export the comment threads on a diff as markdown
+    Export {
+        /// The diff, as for `chadreview local` (defaults to the working tree)
+        revisions: Option<String>,
+
+        /// Path to the repository (defaults to the current directory)
+        #[arg(long)]
+        repo: Option<PathBuf>,
+
+        /// File to write to (defaults to stdout)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+impl Command {
+    /// Run the command.
+    ///
+    /// # Errors
+    ///
+    /// * If the command's arguments are invalid
+    /// * If the server fails to start, for commands that open a view
+    /// * If the diff or local comments can't be loaded
+    #[cfg(feature = "local-git")]
+    pub fn run<B: GitBackend>(&self, backend: Arc<B>) -> Result<(), CliError> {
+        match self {
+            Self::Export { spec, repo, out } => {
+                let (title, threads, diffs) = load_review(backend, repo.as_ref(), spec.as_deref())?;
+                write_output(
+                    out.as_ref(),
+                    &chadreview_local_comment::export_html(&title, &threads, &diffs),
+                )
+            }
+            Self::Comments { command } => command.run(backend),
+            #[cfg(feature = "tui")]
+            Self::Tui(args) => Ok(crate::tui::run(backend, args)?),
+            #[cfg(feature = "sqlite")]
+            Self::MigrateComments => migrate_comments(),
+            _ => self.open_view(),
+        }
+    }
+
+    /// Run the command.
+    ///
+    /// # Errors
+    ///
+    /// * If the command's arguments are invalid
+    /// * If the server fails to start
+    #[cfg(not(feature = "local-git"))]
+    pub fn run(&self) -> Result<(), CliError> {
+        self.open_view()
+    }
+
+    /// Serve the command's view and open it.
+    fn open_view(&self) -> Result<(), CliError> {
+        match self {
+            #[cfg(feature = "local-git")]
+            Self::Local {
+                revisions,
+                repo,
+                staged,
+                against,
+                no_untracked,
+                server,
+            } => {
+                let spec = match revisions {
+                    Some(revisions) => DiffSpec::from_revisions(revisions)?,
+                    None => DiffSpec::WorkingTree {
+                        against: against.clone().unwrap_or_else(|| "HEAD".to_string()),
+                        staged_only: *staged,
+                        include_untracked: !no_untracked,
+                    },
+                };
+                let repo = repo_path(repo.as_ref())?.canonicalize()?;
+                let path = format!(
+                    "/local?repo={}&{}",
+                    urlencoding::encode(&repo.to_string_lossy()),
+                    spec.to_query_string()
+                );
+                server.serve(&path)
+            }
+            Self::Pr { reference, server } => {
+                let (owner, repo, number) = parse_pr_reference(reference)
+                    .ok_or_else(|| CliError::InvalidPrReference(reference.clone()))?;
+                server.serve(&format!("/pr?owner={owner}&repo={repo}&number={number}"))
+            }
+            #[cfg(feature = "local-git")]
+            Self::Export { .. } | Self::Comments { .. } => Ok(()),
+            #[cfg(feature = "tui")]
+            Self::Tui(_) => Ok(()),
+            #[cfg(feature = "sqlite")]
+            Self::MigrateComments => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "local-git")]
+impl CommentsCommand {
+    fn run<B: GitBackend>(&self, backend: Arc<B>) -> Result<(), CliError> {
+        match self {
+            Self::List { repo, all } => {
+                let store = LocalCommentStore::new(&repo_path(repo.as_ref())?)
+                    .map_err(LocalRouteError::from)?;
+                let threads = load_threads(&store)?;
+
+                for thread in threads
+                    .iter()
+                    .filter(|thread| *all || thread.state == ThreadState::Open)
+                {
+                    let location = match &thread.comment_type {
+                        LocalCommentType::FileLevelComment { path } => path.clone(),
+                        LocalCommentType::LineLevelComment { path, line } => {
+                            describe_line(path, *line)
+                        }
+                        LocalCommentType::General | LocalCommentType::Reply { .. } => {
+                            "general".to_string()
+                        }
+                    };
+                    let state = match thread.state {
+                        ThreadState::Open => "open",
+                        ThreadState::Resolved => "resolved",
+                        ThreadState::SavedForLater => "saved",
+                    };
+                    println!(
+                        "{} [{state}] {location} - {}: {} ({} replies)",
+                        thread.id,
+                        thread.author.name,
+                        thread.body.lines().next().unwrap_or_default(),
+                        thread.replies.len(),
+                    );
+                }
+            }
+            Self::Export {
+                revisions,
+                repo,
+                out,
+            } => {
+                let (title, threads, diffs) =
+                    load_review(backend, repo.as_ref(), revisions.as_deref())?;
+                write_output(
+                    out.as_ref(),
+                    &chadreview_local_comment::export_markdown(&title, &threads, &diffs),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The description, comment threads and files of the diff of `revisions`
+/// (the working tree by default) in `repo`.
+#[cfg(feature = "local-git")]
+fn load_review<B: GitBackend>(
+    backend: Arc<B>,
+    repo: Option<&PathBuf>,
+    revisions: Option<&str>,
+) -> Result<(String, Vec<LocalComment>, Vec<DiffFile>), CliError> {
+    let repo = repo_path(repo)?;
+    let spec = DiffSpec::from_revisions(revisions.unwrap_or_default())?;
+    let provider = LocalDiffProvider::from_path(backend, &repo)
+        .map_err(|e| LocalRouteError::InvalidRepoPath(e.to_string()))?;
+    let info = provider
+        .get_diff_info(&spec)
+        .map_err(LocalRouteError::from)?;
+    let diffs = provider.get_diff(&spec).map_err(LocalRouteError::from)?;
+    let store = LocalCommentStore::new(&repo).map_err(LocalRouteError::from)?;
+    let threads = load_threads(&store)?;
+
+    Ok((info.description, threads, diffs))
+}
+
+/// Move every repository's JSON comment store into SQLite and report what
+/// was copied.
+#[cfg(feature = "sqlite")]
+fn migrate_comments() -> Result<(), CliError> {
+    let reports = chadreview_local_comment::migrate_json_stores().map_err(LocalRouteError::from)?;
+
+    let output: String = if reports.is_empty() {
+        "No JSON comment stores to migrate\n".to_string()
+    } else {
+        reports
+            .iter()
+            .map(|report| format!("Migrated {report}\n"))
+            .collect()
+    };
+
+    write_output(None, &output)
+}
+
+/// Write `contents` to `out`, or to stdout without one.
+#[cfg(feature = "local-git")]
+fn write_output(out: Option<&PathBuf>, contents: &str) -> Result<(), CliError> {
+    match out {
+        Some(out) => std::fs::write(out, contents)?,
+        None => print!("{contents}"),
+    }
+    Ok(())
+}
+
+impl ServerArgs {
+    /// Start the server as a child process and open `path` on it once it
+    /// is listening, then wait for the server to exit.
+    fn serve(&self, path: &str) -> Result<(), CliError> {
+        let host = self
+            .host
+            .clone()
+            .or_else(|| std::env::var("HOST").ok())
+            .unwrap_or_else(|| "127.0.0.1".to_string());
+        let port = match self
+            .port
+            .or_else(|| std::env::var("PORT").ok().and_then(|p| p.parse().ok()))
+        {
+            Some(0) => TcpListener::bind((host.as_str(), 0))?.local_addr()?.port(),
+            Some(port) => port,
+            None => 3000,
+        };
+
+        let mut server = Process::new(std::env::current_exe()?)
+            .env("HOST", &host)
+            .env("PORT", port.to_string())
+            .spawn()?;
+
+        // A server listening on every address is reached through loopback
+        let connect_host = if host == "0.0.0.0" {
+            "127.0.0.1"
+        } else {
+            &host
+        };
+        wait_until_listening(&mut server, connect_host, port)?;
+
+        let url = format!("http://{connect_host}:{port}{path}");
+        println!("Opening {url}");
+        if !self.no_open {
+            open_browser(&url).unwrap_or_else(|e| {
+                log::warn!("Failed to open the browser: {e}");
+            });
+        }
+
+        server.wait()?;
+
+        Ok(())
+    }
+}
+
+/// Wait for `server` to accept connections on `port`.
+fn wait_until_listening(server: &mut Child, host: &str, port: u16) -> Result<(), CliError> {
+    let started = Instant::now();
+
+    while started.elapsed() < STARTUP_TIMEOUT {
+        if TcpStream::connect((host, port)).is_ok() {
+            return Ok(());
+        }
+        if server.try_wait()?.is_some() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    Err(CliError::ServerNotListening(port))
+}
+
+/// Open `url` with the system's default browser.
+fn open_browser(url: &str) -> std::io::Result<()> {
+    #[cfg(target_os = "macos")]
+    let mut command = Process::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Process::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = Process::new("xdg-open");
+
+    command
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+/// `repo`, or the current directory.
+#[cfg_attr(not(feature = "local-git"), allow(dead_code))]
+fn repo_path(repo: Option<&PathBuf>) -> std::io::Result<PathBuf> {
+    repo.map_or_else(std::env::current_dir, |repo| Ok(repo.clone()))
+}
+
+/// Parse a PR reference, either `owner/repo#123` or a PR URL such as
+/// `https://github.com/owner/repo/pull/123`, into its owner, repo and number.
+#[must_use]
+pub fn parse_pr_reference(reference: &str) -> Option<(String, String, u64)> {
+    let reference = reference.trim();
+
+    if !reference.contains("://") {
+        let (repo_path, number) = reference.split_once('#')?;
+        let (owner, repo) = repo_path.split_once('/')?;
+        return Some((owner.to_string(), repo.to_string(), number.parse().ok()?));
+    }
+
+    // `.../owner/repo/pull/123[/files]` on GitHub, `.../owner/repo/pulls/123` on Gitea
+    let path = reference.split(['#', '?']).next()?;
+    let segments: Vec<&str> = path.split('/').collect();
+    let index = segments
+        .iter()
+        .rposition(|segment| matches!(*segment, "pull" | "pulls"))?;
+    let number = segments.get(index + 1)?.parse().ok()?;
+    let repo = segments.get(index.checked_sub(1)?)?;
+    let owner = segments.get(index.checked_sub(2)?)?;
+
+    Some((owner.to_string(), repo.to_string(), number))
+}
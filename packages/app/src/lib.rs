@@ -7,10 +7,27 @@ use std::sync::OnceLock;
 use hyperchad::renderer::Renderer;
 
 pub mod actions;
+#[cfg(feature = "_ai-integration")]
+pub mod ai;
+pub mod auth;
+pub mod autocomplete;
+pub mod cli;
 pub mod events;
+pub mod live;
 #[cfg(feature = "local-git")]
 pub mod local_routes;
+pub mod maintenance;
+#[cfg(feature = "local-git")]
+pub mod repos;
 pub mod routes;
+pub mod settings;
+#[cfg(feature = "local-git")]
+pub mod share_routes;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "local-git")]
+pub mod watch;
+pub mod webhook;
 
 /// Global renderer instance for rendering UI components and pushing partial view updates.
 pub static RENDERER: OnceLock<Box<dyn Renderer>> = OnceLock::new();
@@ -20,12 +37,18 @@ pub static RENDERER: OnceLock<Box<dyn Renderer>> = OnceLock::new();
 pub mod sse {
     use std::collections::HashSet;
 
+    use chadreview_app_ui::diff_viewer::local::file_container_id;
     use chadreview_app_ui::local_comments::{
-        ai_status_str_id, local_thread_replies_id, render_ai_status_container,
-        render_thread_replies,
+        ai_partial_reply_str_id, ai_status_str_id, local_thread_replies_id,
+        render_ai_status_container, render_partial_reply, render_thread_replies,
+    };
+    use chadreview_app_ui::local_summaries::{
+        SUMMARY_STATUS_ID, file_summary_id, render_file_summary, render_summary_status,
     };
     use chadreview_local_comment_models::{AiExecutionStatus, LocalComment};
     use hyperchad::renderer::View;
+    use hyperchad::router::Container;
+    use hyperchad::transformer::models::Selector;
     use switchy::uuid::Uuid;
 
     use crate::RENDERER;
@@ -61,6 +84,121 @@ pub mod sse {
             .ok();
     }
 
+    /// Push the AI response generated so far for thread `thread_id` to the
+    /// client via SSE, as `(author, text)`, or `None` to clear it.
+    ///
+    /// This replaces the thread's partial reply slot, so the response appears
+    /// in the thread as it is generated.
+    ///
+    /// Any errors are logged but don't cause the function to fail.
+    pub async fn push_partial_reply(thread_id: Uuid, partial: Option<(&str, &str)>) {
+        let Some(renderer) = RENDERER.get() else {
+            log::warn!("RENDERER not initialized, cannot push SSE update");
+            return;
+        };
+
+        let container = render_partial_reply(thread_id, partial);
+        let view = View::builder().with_fragment(container).build();
+
+        renderer
+            .render(view)
+            .await
+            .inspect(|()| {
+                log::trace!(
+                    "Pushed partial reply for thread {}: {:?}",
+                    thread_id,
+                    ai_partial_reply_str_id(thread_id)
+                );
+            })
+            .inspect_err(|e| {
+                log::error!("Failed to push partial reply for thread {thread_id}: {e:?}");
+            })
+            .ok();
+    }
+
+    /// Push the AI summary of file `path` to the client via SSE, filling in
+    /// the file's summary slot.
+    ///
+    /// Any errors are logged but don't cause the function to fail.
+    pub async fn push_file_summary(path: &str, summary: &str) {
+        let Some(renderer) = RENDERER.get() else {
+            log::warn!("RENDERER not initialized, cannot push SSE update");
+            return;
+        };
+
+        let container = render_file_summary(path, Some(summary));
+        let view = View::builder().with_fragment(container).build();
+
+        renderer
+            .render(view)
+            .await
+            .inspect(|()| {
+                log::debug!(
+                    "Pushed summary for file {path}: {:?}",
+                    file_summary_id(path)
+                );
+            })
+            .inspect_err(|e| {
+                log::error!("Failed to push summary for file {path}: {e:?}");
+            })
+            .ok();
+    }
+
+    /// Push the progress of summarizing a diff's files to the client via
+    /// SSE, replacing the summary form's status.
+    ///
+    /// Any errors are logged but don't cause the function to fail.
+    pub async fn push_summary_status(message: &str) {
+        let Some(renderer) = RENDERER.get() else {
+            log::warn!("RENDERER not initialized, cannot push SSE update");
+            return;
+        };
+
+        let container = render_summary_status(message);
+        let view = View::builder().with_fragment(container).build();
+
+        renderer
+            .render(view)
+            .await
+            .inspect(|()| {
+                log::debug!("Pushed {SUMMARY_STATUS_ID}: {message}");
+            })
+            .inspect_err(|e| {
+                log::error!("Failed to push summary status: {e:?}");
+            })
+            .ok();
+    }
+
+    /// Push re-rendered diff files to the client via SSE, replacing them,
+    /// and remove the files at `removed_paths`.
+    ///
+    /// Any errors are logged but don't cause the function to fail.
+    pub async fn push_diff_files(files: Vec<Container>, removed_paths: &[String]) {
+        let Some(renderer) = RENDERER.get() else {
+            log::warn!("RENDERER not initialized, cannot push SSE update");
+            return;
+        };
+
+        let mut view = View::builder();
+        for file in files {
+            view = view.with_fragment(file);
+        }
+        for path in removed_paths {
+            view = view.with_delete_selector(Selector::Id(file_container_id(path)));
+        }
+
+        renderer
+            .render(view.build())
+            .await
+            .inspect(|()| {
+                log::debug!("Pushed diff file updates, removed {removed_paths:?}");
+            })
+            .inspect_err(|e| {
+                log::error!("Failed to push diff file updates: {e:?}");
+            })
+            .ok();
+    }
+
     /// Push updated thread replies to the client via SSE.
     ///
     /// This sends a partial view update that targets the replies container
@@ -0,0 +1,314 @@
+//! Live updates of the open `/pr` page from webhook events.
+//!
+//! Events from the relay server or delivered directly are applied by
+//! refetching what they changed from the provider and pushing the
+//! re-rendered parts of the page as partial view updates over SSE, the same
+//! way local diffs push new thread replies.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use chadreview_app_ui::{
+    comment_thread::{comment_id, comment_thread_id, render_comment_item, render_comment_thread},
+    diff_viewer::render_file,
+    general_comments::{render_general_comments_list, render_general_comments_summary},
+    merge_panel::render_merge_panel,
+    pr_header::{render_description, render_header_main},
+};
+use chadreview_git_provider::GitProvider;
+use chadreview_pr::{PendingReviewKey, PrReviewStateStore};
+use chadreview_pr_models::Comment;
+use chadreview_relay_models::{CommentAction, GitHubReviewComment, PrKey, WebhookEvent};
+use hyperchad::{
+    renderer::View,
+    router::Container,
+    transformer::models::Selector,
+};
+
+use crate::RENDERER;
+
+/// Applies a PR's webhook events to the open views of it.
+pub struct PrLiveUpdates {
+    provider: Arc<dyn GitProvider>,
+    review_state: Option<Arc<PrReviewStateStore>>,
+    pr_key: PrKey,
+}
+
+impl PrLiveUpdates {
+    /// Update the views of `pr_key` with data refetched from `provider`.
+    /// `review_state` tells which files to keep collapsed when a file is
+    /// re-rendered.
+    #[must_use]
+    pub const fn new(
+        provider: Arc<dyn GitProvider>,
+        review_state: Option<Arc<PrReviewStateStore>>,
+        pr_key: PrKey,
+    ) -> Self {
+        Self {
+            provider,
+            review_state,
+            pr_key,
+        }
+    }
+
+    /// Push the parts of the page `event` changed.
+    ///
+    /// New, edited and deleted comments are inserted, replaced and removed
+    /// in place, and changes to the PR itself update its header and merge
+    /// panel. Other events don't change anything on the page yet.
+    ///
+    /// # Errors
+    ///
+    /// * If refetching the changed data from the provider fails
+    pub async fn apply(&self, event: &WebhookEvent) -> anyhow::Result<()> {
+        match event {
+            WebhookEvent::IssueComment { .. } => self.push_general_comments().await,
+            WebhookEvent::PullRequestReviewComment {
+                action,
+                comment,
+                pull_request,
+                ..
+            } => {
+                self.push_review_comment(*action, comment, &pull_request.head.sha)
+                    .await
+            }
+            WebhookEvent::PullRequest { .. } => self.push_pr_state().await,
+            WebhookEvent::PullRequestReview { .. }
+            | WebhookEvent::PullRequestReviewThread { .. }
+            | WebhookEvent::CheckRun { .. }
+            | WebhookEvent::Status { .. } => Ok(()),
+        }
+    }
+
+    /// Re-render the general comments, which is where issue comments on a
+    /// PR are shown, along with their count.
+    async fn push_general_comments(&self) -> anyhow::Result<()> {
+        let PrKey {
+            owner,
+            repo,
+            number,
+        } = &self.pr_key;
+
+        let comments = self.provider.get_comments(owner, repo, *number).await?;
+
+        let view = View::builder()
+            .with_fragment(Container::from(render_general_comments_summary(
+                &comments,
+            )))
+            .with_fragment(Container::from(render_general_comments_list(
+                &comments,
+                self.provider.provider_name(),
+                owner,
+                repo,
+                *number,
+            )))
+            .build();
+        push(view, "general comments").await;
+
+        Ok(())
+    }
+
+    /// Apply a review comment event.
+    ///
+    /// Replies re-render their thread, and new threads re-render their
+    /// file, since a thread's line may not have a slot for it on the page.
+    async fn push_review_comment(
+        &self,
+        action: CommentAction,
+        comment: &GitHubReviewComment,
+        head_sha: &str,
+    ) -> anyhow::Result<()> {
+        let PrKey {
+            owner,
+            repo,
+            number,
+        } = &self.pr_key;
+
+        let view = match (action, comment.in_reply_to_id) {
+            (CommentAction::Deleted, _) => comment_deleted_view(comment.id),
+            (CommentAction::Edited, root_id) => {
+                let edited = self
+                    .provider
+                    .get_comment(owner, repo, *number, comment.id, false)
+                    .await?;
+                comment_updated_view(
+                    &edited,
+                    root_id.is_none(),
+                    self.provider.provider_name(),
+                    owner,
+                    repo,
+                    *number,
+                )
+            }
+            (CommentAction::Created, Some(root_id)) => {
+                let root = self
+                    .provider
+                    .get_comment(owner, repo, *number, root_id, true)
+                    .await?;
+                View::builder()
+                    .with_fragment(Container::from(render_comment_thread(
+                        root_id,
+                        &root,
+                        0,
+                        self.provider.provider_name(),
+                        owner,
+                        repo,
+                        *number,
+                    )))
+                    .build()
+            }
+            (CommentAction::Created, None) => {
+                return self.push_file(&comment.path, head_sha).await;
+            }
+        };
+        push(view, &comment_id(comment.id)).await;
+
+        Ok(())
+    }
+
+    /// Re-render file `path` of the diff with its comments.
+    async fn push_file(&self, path: &str, head_sha: &str) -> anyhow::Result<()> {
+        let PrKey {
+            owner,
+            repo,
+            number,
+        } = &self.pr_key;
+
+        let diffs = self.provider.get_diff(owner, repo, *number).await?;
+        let Some(file) = diffs.iter().find(|file| file.filename == path) else {
+            log::debug!("{path} is not in the diff of {owner}/{repo} #{number}");
+            return Ok(());
+        };
+        let comments = self.provider.get_comments(owner, repo, *number).await?;
+        let viewed = self.viewed_paths(head_sha).contains(path);
+
+        let view = View::builder()
+            .with_fragment(Container::from(render_file(
+                head_sha,
+                file,
+                &comments,
+                viewed,
+                self.provider.provider_name(),
+                owner,
+                repo,
+                *number,
+            )))
+            .build();
+        push(view, path).await;
+
+        Ok(())
+    }
+
+    /// Re-render the PR's title, state and description, and the merge panel
+    /// whose buttons depend on them.
+    async fn push_pr_state(&self) -> anyhow::Result<()> {
+        let PrKey {
+            owner,
+            repo,
+            number,
+        } = &self.pr_key;
+
+        let pr = self.provider.get_pr(owner, repo, *number).await?;
+        let checks = self
+            .provider
+            .get_checks(owner, repo, &pr.head_sha)
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to fetch checks for {owner}/{repo} #{number}: {e}");
+                Vec::new()
+            });
+
+        let view = View::builder()
+            .with_fragment(render_header_main(&pr))
+            .with_fragment(render_description(&pr))
+            .with_fragment(Container::from(render_merge_panel(&pr, &checks)))
+            .build();
+        push(view, "PR state").await;
+
+        Ok(())
+    }
+
+    fn viewed_paths(&self, head_sha: &str) -> HashSet<String> {
+        let Some(store) = &self.review_state else {
+            return HashSet::new();
+        };
+        let PrKey {
+            owner,
+            repo,
+            number,
+        } = &self.pr_key;
+        let key = PendingReviewKey::new(self.provider.provider_name(), owner, repo, *number);
+
+        store.viewed_paths(&key, head_sha).unwrap_or_else(|e| {
+            log::warn!("Failed to load viewed files for {owner}/{repo} #{number}: {e}");
+            HashSet::new()
+        })
+    }
+}
+
+/// Push an edited comment to the open views of its PR, so other tabs see
+/// the edit without waiting for the webhook. `root` tells whether it starts
+/// a thread.
+pub async fn push_comment_updated(
+    comment: &Comment,
+    root: bool,
+    provider: &str,
+    owner: &str,
+    repo: &str,
+    number: u64,
+) {
+    push(
+        comment_updated_view(comment, root, provider, owner, repo, number),
+        &comment_id(comment.id),
+    )
+    .await;
+}
+
+/// Remove a deleted comment, along with its replies, from the open views of
+/// its PR.
+pub async fn push_comment_deleted(id: u64) {
+    push(comment_deleted_view(id), &comment_id(id)).await;
+}
+
+fn comment_updated_view(
+    comment: &Comment,
+    root: bool,
+    provider: &str,
+    owner: &str,
+    repo: &str,
+    number: u64,
+) -> View {
+    View::builder()
+        .with_fragment(Container::from(render_comment_item(
+            comment, root, provider, owner, repo, number,
+        )))
+        .build()
+}
+
+fn comment_deleted_view(id: u64) -> View {
+    View::builder()
+        .with_delete_selector(Selector::Id(comment_thread_id(id)))
+        .build()
+}
+
+/// Push `view` to the client via SSE. `what` names what it updates for
+/// logging.
+///
+/// Any errors are logged but don't cause the function to fail.
+async fn push(view: View, what: &str) {
+    let Some(renderer) = RENDERER.get() else {
+        log::warn!("RENDERER not initialized, cannot push SSE update");
+        return;
+    };
+
+    renderer
+        .render(view)
+        .await
+        .inspect(|()| {
+            log::debug!("Pushed live update of {what}");
+        })
+        .inspect_err(|e| {
+            log::error!("Failed to push live update of {what}: {e:?}");
+        })
+        .ok();
+}
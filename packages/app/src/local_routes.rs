@@ -3,35 +3,59 @@
 //! Routes for viewing local git diffs without GitHub integration,
 //! including local commenting with AI integration.
 
-use std::path::PathBuf;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use chadreview_app_ui::{diff_viewer::local as diff_viewer, local_comments, local_header};
-use chadreview_git_backend::GitBackend;
-use chadreview_local_comment::LocalCommentStore;
+use chadreview_app_ui::{
+    autocomplete, checklist,
+    diff_viewer::{copy, local as diff_viewer, permalink},
+    local_agents, local_attachments, local_comments,
+    local_commit::{self, CommitDraft},
+    local_commits, local_conflicts, local_describe, local_drafts, local_export, local_header,
+    local_import, local_outdated, local_saved_replies, local_search,
+    local_staging::{self, Staging},
+    local_summaries, local_symbols, local_thread_filter, markdown_preview,
+};
+use chadreview_config::{Config, SavedReplies};
+use chadreview_git_backend::{CommitInfo, GitBackend, RepoOperation, Worktree};
+use chadreview_git_provider::GitProvider;
+use chadreview_hooks::HookEvent;
+use chadreview_local_comment::{LocalCommentStore, LocalCommentStoreError};
 use chadreview_local_comment_models::{
-    AiAction, AiExecutionStatus, LocalComment, LocalCommentType, LocalUser, ProgressEntry,
-    ThreadState,
+    AiAction, AiExecutionStatus, DescriptionKind, DiffScope, LineNumber, LocalComment,
+    LocalCommentType, LocalUser, ReviewSession, ThreadFilter, ThreadState, UsageLedger,
+};
+use chadreview_local_diff::{
+    CommitDiff, LocalDiffProvider, SymbolIndexes, filter_commit_diffs, sort_diff_files,
 };
-use chadreview_local_diff::LocalDiffProvider;
-use chadreview_local_diff_models::{DiffSpec, DiffSpecError};
+use chadreview_local_diff_models::{
+    CommitFilter, DiffSpec, DiffSpecError, FileOrder, LocalDiffInfo,
+};
+use chadreview_pr_models::DiffFile;
 use chrono::Utc;
 use hyperchad::{
-    router::{Container, RouteRequest, Router},
+    renderer::Content,
+    router::{Container, RoutePath, RouteRequest, Router},
     template::container,
 };
 use switchy::http::models::Method;
 use switchy::uuid::Uuid;
 
-use crate::sse::{push_ai_status_update, push_thread_replies};
+use crate::autocomplete::{Suggestion, suggestions_response};
+use crate::cli::parse_pr_reference;
+use crate::repos;
+use crate::settings;
+use crate::sse::{push_ai_status_update, push_summary_status, push_thread_replies};
+use crate::watch::WorkingTreeWatchers;
 
 // Conditional imports for AI integration
-#[cfg(feature = "ai-integration-opencode")]
-use chadreview_ai_provider::AiProvider;
-#[cfg(feature = "ai-integration-opencode")]
-use chadreview_ai_provider_models::AiContext;
-#[cfg(feature = "ai-integration-opencode")]
-use chadreview_opencode_provider::OpenCodeProvider;
+#[cfg(feature = "_ai-integration")]
+use chadreview_ai_provider_models::{AiContext, AiProgress};
+
+#[cfg(feature = "_ai-integration")]
+use crate::sse::{push_file_summary, push_partial_reply};
 
 /// Error type for local route operations.
 #[derive(Debug, thiserror::Error)]
@@ -54,6 +78,9 @@ pub enum LocalRouteError {
     /// Missing required parameter.
     #[error("Missing required parameter: {0}")]
     MissingParameter(String),
+    /// A parameter has an invalid value.
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
     /// Invalid UUID.
     #[error("Invalid UUID: {0}")]
     InvalidUuid(String),
@@ -69,6 +96,12 @@ pub enum LocalRouteError {
     /// Provider error.
     #[error("Provider error: {0}")]
     ProviderError(String),
+    /// The comment has no suggested change that can be applied.
+    #[error("No applicable suggestion in comment {0}")]
+    NoSuggestion(Uuid),
+    /// The comment's AI action isn't running.
+    #[error("No running AI execution for comment {0}")]
+    AiNotRunning(Uuid),
 }
 
 /// Form data for creating a new local comment.
@@ -100,26 +133,245 @@ struct ReplyCommentForm {
     ai_agent: Option<String>,
 }
 
+/// Form data for starting an AI review of the whole diff.
+#[derive(serde::Deserialize)]
+struct ReviewDiffForm {
+    /// The review agent in format "provider:agent".
+    ai_agent: Option<String>,
+}
+
+/// Form data for summarizing the files of a diff.
+#[derive(serde::Deserialize)]
+struct SummarizeFilesForm {
+    /// The summary agent in format "provider:agent".
+    ai_agent: Option<String>,
+}
+
+/// Form data for asking an AI agent to describe a diff.
+#[derive(serde::Deserialize)]
+struct DescribeDiffForm {
+    /// The describing agent in format "provider:agent".
+    ai_agent: Option<String>,
+    /// The kind of description to write.
+    #[serde(default)]
+    kind: DescriptionKind,
+}
+
+/// Form data for editing a comment.
+#[derive(serde::Deserialize)]
+struct UpdateCommentForm {
+    /// The new comment body text.
+    body: String,
+}
+
+/// Form data for re-attaching an outdated thread to another line.
+#[derive(serde::Deserialize)]
+struct ReattachForm {
+    /// The new line: a new-side line number, or `old:` and an old-side one.
+    line: String,
+}
+
+/// Form data for saving a comment form draft.
+#[derive(serde::Deserialize)]
+struct DraftForm {
+    /// Element ID of the comment form the draft belongs to.
+    key: String,
+    /// The draft text; empty to discard the draft.
+    #[serde(default)]
+    body: String,
+}
+
+/// Form data for fetching and comparing a branch from another remote.
+#[derive(serde::Deserialize)]
+struct CompareForm {
+    /// URL (or path) of the remote repository.
+    url: String,
+    /// Branch to fetch from the remote.
+    branch: String,
+    /// Local ref to compare against (defaults to `HEAD`).
+    base: Option<String>,
+}
+
+/// Form data for checking or unchecking a review checklist item.
+#[derive(serde::Deserialize)]
+struct ChecklistForm {
+    /// Text of the item.
+    item: String,
+    /// `true` to check the item, anything else to uncheck it.
+    checked: String,
+}
+
+/// Form data for searching a diff.
+#[derive(serde::Deserialize)]
+struct SearchForm {
+    /// Text to search for.
+    q: String,
+    /// `files` to also search the changed files' full contents.
+    scope: Option<String>,
+}
+
+/// Form data for looking up a symbol.
+#[derive(serde::Deserialize)]
+struct SymbolForm {
+    /// Name of the symbol.
+    name: String,
+}
+
+/// Form data for staging, unstaging or discarding a file or hunk.
+#[derive(serde::Deserialize)]
+struct StagingForm {
+    /// File path.
+    path: String,
+    /// `stage`, `unstage`, `discard`, or `hunk` to stage one of the file's
+    /// hunks.
+    action: String,
+    /// Index of the hunk to stage.
+    hunk: Option<usize>,
+    /// Whether discarding may delete a new file.
+    #[serde(default)]
+    delete_added: bool,
+}
+
+/// Form data for committing the staged changes or suggesting a message.
+#[derive(serde::Deserialize)]
+struct CommitForm {
+    /// The commit message.
+    #[serde(default)]
+    message: String,
+    /// `commit`, or `suggest` to ask an agent for the message.
+    action: String,
+    /// The agent suggesting the message, in format "provider:agent".
+    ai_agent: Option<String>,
+    /// Whether to amend the last commit.
+    #[serde(default)]
+    amend: bool,
+    /// Whether to sign the commit off.
+    #[serde(default)]
+    sign_off: bool,
+}
+
+/// Form data for marking a conflicted file resolved.
+#[derive(serde::Deserialize)]
+struct ResolveForm {
+    /// File path.
+    path: String,
+}
+
+/// Form data for filtering comment threads.
+#[derive(Default, serde::Deserialize)]
+struct ThreadFilterForm {
+    /// Text to find in the threads' comments.
+    #[serde(default)]
+    q: String,
+    /// Part of the file path.
+    #[serde(default)]
+    path: String,
+    /// Part of a participant's name.
+    #[serde(default)]
+    author: String,
+    /// Current state filter: "open", "resolved", "saved_for_later" or empty.
+    #[serde(default)]
+    state: String,
+    /// Current AI action filter: "yes", "no" or empty.
+    #[serde(default)]
+    ai: String,
+    /// New state filter, set by the state toggle that was clicked.
+    set_state: Option<String>,
+    /// New AI action filter, set by the AI toggle that was clicked.
+    set_ai: Option<String>,
+}
+
+impl ThreadFilterForm {
+    fn into_filter(self) -> ThreadFilter {
+        let text = |value: String| {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+        let state = self.set_state.unwrap_or(self.state);
+        let ai = self.set_ai.unwrap_or(self.ai);
+
+        ThreadFilter {
+            state: match state.as_str() {
+                "open" => Some(ThreadState::Open),
+                "resolved" => Some(ThreadState::Resolved),
+                "saved_for_later" => Some(ThreadState::SavedForLater),
+                _ => None,
+            },
+            path: text(self.path),
+            author: text(self.author),
+            has_ai_action: match ai.as_str() {
+                "yes" => Some(true),
+                "no" => Some(false),
+                _ => None,
+            },
+            query: text(self.q),
+        }
+    }
+}
+
+/// Form data for importing a PR's comments.
+#[derive(serde::Deserialize)]
+struct ImportForm {
+    /// The PR, as `owner/repo#123` or its URL.
+    pr: String,
+}
+
+/// Maximum number of matches returned by a diff search.
+const SEARCH_LIMIT: usize = 200;
+
+/// Maximum number of references returned by a symbol lookup.
+const SYMBOL_REFERENCE_LIMIT: usize = 200;
+
+/// Number of files of a squashed range diff loaded at once, the rest being
+/// loaded page by page from `/api/local/files`.
+const DIFF_PAGE_FILES: usize = 100;
+
 /// Add local routes to an existing router.
+///
+/// `provider` is the git hosting provider that PR comments are imported
+/// from.
 #[must_use]
 pub fn add_local_routes<B: GitBackend + Send + Sync + 'static>(
     router: Router,
     backend: Arc<B>,
+    provider: Arc<dyn GitProvider>,
 ) -> Router {
     let backend_local = Arc::clone(&backend);
     let backend_create = Arc::clone(&backend);
     let backend_reply = Arc::clone(&backend);
+    let backend_update = Arc::clone(&backend);
+    let backend_reattach = Arc::clone(&backend);
     let backend_delete = Arc::clone(&backend);
     let backend_resolve = Arc::clone(&backend);
     let backend_reply_view = Arc::clone(&backend);
     let backend_file_view = Arc::clone(&backend);
     let backend_file_diff = Arc::clone(&backend);
+    let backend_files = Arc::clone(&backend);
+    let backend_compare = Arc::clone(&backend);
+    let backend_suggestion = Arc::clone(&backend);
+    let backend_search = Arc::clone(&backend);
+    let backend_symbols = Arc::clone(&backend);
+    let backend_staging = Arc::clone(&backend);
+    let backend_commit = Arc::clone(&backend);
+    let backend_conflict = Arc::clone(&backend);
+    let backend_export = Arc::clone(&backend);
+    let backend_thread_search = Arc::clone(&backend);
+    let backend_draft = Arc::clone(&backend);
+    let backend_upload = Arc::clone(&backend);
+    let backend_attachment = Arc::clone(&backend);
+    let backend_review = Arc::clone(&backend);
+    let backend_summaries = Arc::clone(&backend);
+    let backend_describe = Arc::clone(&backend);
+    let backend_mentions = Arc::clone(&backend);
+    let watchers = Arc::new(WorkingTreeWatchers::new(Arc::clone(&backend)));
+    let symbol_indexes = Arc::new(SymbolIndexes::default());
 
     router
         .with_route_result("/local", {
             move |req: RouteRequest| {
                 let backend = Arc::clone(&backend_local);
-                async move { local_route(req, backend).await }
+                let watchers = Arc::clone(&watchers);
+                async move { local_route(req, backend, watchers).await }
             }
         })
         .with_route_result("/api/local/comment", {
@@ -134,6 +386,18 @@ pub fn add_local_routes<B: GitBackend + Send + Sync + 'static>(
                 async move { reply_comment_route(req, backend).await }
             }
         })
+        .with_route_result("/api/local/comment/update", {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_update);
+                async move { update_comment_route(req, backend).await }
+            }
+        })
+        .with_route_result("/api/local/comment/reattach", {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_reattach);
+                async move { reattach_comment_route(req, backend).await }
+            }
+        })
         .with_route_result("/api/local/comment/delete", {
             move |req: RouteRequest| {
                 let backend = Arc::clone(&backend_delete);
@@ -146,6 +410,77 @@ pub fn add_local_routes<B: GitBackend + Send + Sync + 'static>(
                 async move { set_state_route(req, backend).await }
             }
         })
+        .with_route_result("/api/local/comment/suggestion", {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_suggestion);
+                async move { apply_suggestion_route(req, backend).await }
+            }
+        })
+        .with_route_result("/api/local/comments/search", {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_thread_search);
+                async move { thread_search_route(req, backend).await }
+            }
+        })
+        .with_route_result("/api/local/draft", {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_draft);
+                async move { save_draft_route(req, backend).await }
+            }
+        })
+        .with_route_result("/api/local/attachment", {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_upload);
+                async move { upload_attachment_route(req, backend).await }
+            }
+        })
+        .with_route_result(
+            RoutePath::LiteralPrefix(local_attachments::ATTACHMENT_ROUTE_PREFIX.to_string()),
+            {
+                move |req: RouteRequest| {
+                    let backend = Arc::clone(&backend_attachment);
+                    async move { attachment_route(req, backend).await }
+                }
+            },
+        )
+        .with_route_result("/api/local/ai/review", {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_review);
+                async move { review_diff_route(req, backend).await }
+            }
+        })
+        .with_route_result(local_summaries::SUMMARIZE_ROUTE, {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_summaries);
+                async move { summarize_files_route(req, backend).await }
+            }
+        })
+        .with_route_result(local_describe::DESCRIBE_ROUTE, {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_describe);
+                async move { describe_diff_route(req, backend).await }
+            }
+        })
+        .with_route_result("/api/local/ai/cancel", |req: RouteRequest| async move {
+            cancel_ai_route(&req)
+        })
+        .with_route_result(
+            local_header::BUDGET_OVERRIDE_ROUTE,
+            |req: RouteRequest| async move { override_budget_route(&req) },
+        )
+        .with_route_result(local_agents::AGENTS_ROUTE, |req: RouteRequest| async move {
+            ai_agents_route(req).await
+        })
+        .with_route_result(
+            checklist::LOCAL_CHECKLIST_ROUTE,
+            |req: RouteRequest| async move { checklist_route(&req) },
+        )
+        .with_route_result(autocomplete::LOCAL_MENTIONS_ROUTE, {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_mentions);
+                async move { mentions_route(req, backend).await }
+            }
+        })
         .with_route_result("/api/local/reply/view", {
             move |req: RouteRequest| {
                 let backend = Arc::clone(&backend_reply_view);
@@ -164,6 +499,61 @@ pub fn add_local_routes<B: GitBackend + Send + Sync + 'static>(
                 async move { file_diff_route(req, backend).await }
             }
         })
+        .with_route_result("/api/local/files", {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_files);
+                async move { files_route(req, backend).await }
+            }
+        })
+        .with_route_result("/api/local/compare", {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_compare);
+                async move { compare_route(req, backend).await }
+            }
+        })
+        .with_route_result("/api/local/search", {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_search);
+                async move { search_route(req, backend).await }
+            }
+        })
+        .with_route_result("/api/local/symbols", {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_symbols);
+                let symbol_indexes = Arc::clone(&symbol_indexes);
+                async move { symbols_route(req, backend, symbol_indexes).await }
+            }
+        })
+        .with_route_result(local_staging::STAGING_ROUTE, {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_staging);
+                async move { staging_route(req, backend).await }
+            }
+        })
+        .with_route_result(local_commit::COMMIT_ROUTE, {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_commit);
+                async move { commit_route(req, backend).await }
+            }
+        })
+        .with_route_result(local_conflicts::RESOLVE_ROUTE, {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_conflict);
+                async move { resolve_conflict_route(req, backend).await }
+            }
+        })
+        .with_route_result("/api/local/export", {
+            move |req: RouteRequest| {
+                let backend = Arc::clone(&backend_export);
+                async move { export_route(req, backend).await }
+            }
+        })
+        .with_route_result("/api/local/import", {
+            move |req: RouteRequest| {
+                let provider = Arc::clone(&provider);
+                async move { import_route(req, provider).await }
+            }
+        })
 }
 
 /// Handle the `/local` route for viewing local git diffs.
@@ -174,13 +564,26 @@ pub fn add_local_routes<B: GitBackend + Send + Sync + 'static>(
 /// - `three_dot` - Use merge-base semantics (e.g., `main...feature`)
 /// - `commit` - Single commit SHA
 /// - `commits` - Comma-separated commit SHAs
-/// - `mode` - Multi-commit mode: `separate` (default) or `squashed`
+/// - `mode` - `separate` to review commit by commit or `squashed` for one combined
+///   diff (default: `separate` for `commits`, `squashed` for ranges)
 /// - `staged` - Only show staged changes
 /// - `against` - What to diff working tree against (default: HEAD)
-/// - `untracked` - Include untracked files (default: true)
-async fn local_route<B: GitBackend + 'static>(
+/// - `untracked` - Include untracked files (default: from config, else true)
+/// - `author` / `path` / `merges=hide` - When reviewing commit by commit, only
+///   show commits by an author, changing files under a path, or that aren't
+///   merge commits
+/// - `sort` - Order of the files: `path` (default), `changes`, `status` or
+///   `directory`
+/// - `context` - Number of context lines around changes (default: 3)
+/// - `conflicts` - `true` to show the conflicts of a merge or rebase in
+///   progress instead of a diff
+///
+/// Working tree diffs are refreshed as their files change, see
+/// [`WorkingTreeWatchers`].
+async fn local_route<B: GitBackend + Send + Sync + 'static>(
     req: RouteRequest,
     backend: Arc<B>,
+    watchers: Arc<WorkingTreeWatchers<B>>,
 ) -> Result<Container, LocalRouteError> {
     if !matches!(req.method, Method::Get) {
         return Err(LocalRouteError::UnsupportedMethod);
@@ -188,42 +591,511 @@ async fn local_route<B: GitBackend + 'static>(
 
     // Determine repository path
     let repo_path = get_repo_path(&req)?;
+    let config = settings::load_config(Some(&repo_path));
 
-    // Create provider for this repository
-    let provider = LocalDiffProvider::from_path(Arc::clone(&backend), &repo_path)
-        .map_err(|e| LocalRouteError::InvalidRepoPath(e.to_string()))?;
+    if req
+        .query
+        .get("conflicts")
+        .is_some_and(|value| value == "true")
+    {
+        let (operation, conflicts) = run_blocking({
+            let repo_path = repo_path.clone();
+            move || {
+                let provider = LocalDiffProvider::from_path(backend, &repo_path)
+                    .map_err(|e| LocalRouteError::InvalidRepoPath(e.to_string()))?;
+                Ok((provider.operation_in_progress()?, provider.conflicts()?))
+            }
+        })
+        .await?;
+        repos::record_recent_repo(&repo_path);
+
+        return Ok(container! {
+            div padding=20 gap=20 {
+                (settings::theme_stylesheet(config.appearance.theme))
+                (local_conflicts::render_conflicts(&repo_path.to_string_lossy(), operation, &conflicts))
+            }
+        }
+        .into());
+    }
+
+    // Parse diff specification from query params, with configured defaults
+    let mut query = req.query.clone();
+    if !config.diff.include_untracked {
+        query
+            .entry("untracked".to_string())
+            .or_insert_with(|| "false".to_string());
+    }
+    let spec = DiffSpec::from_query(&query)?;
+    let commit_filter = CommitFilter::from_query(&query);
+    let file_order = FileOrder::from_query(&query);
+
+    let mut review = run_blocking({
+        let repo_path = repo_path.clone();
+        let spec = spec.clone();
+        let commit_filter = commit_filter.clone();
+        let context_lines = context_lines(&req);
+        move || {
+            load_local_review(
+                backend,
+                &repo_path,
+                &spec,
+                &commit_filter,
+                file_order,
+                Some(DIFF_PAGE_FILES),
+                context_lines,
+            )
+        }
+    })
+    .await?;
+    let show_all = shows_all_comments(&req);
+    let hidden = if show_all {
+        0
+    } else {
+        let scope = diff_scope(&review.info.spec, &review.info.commits);
+        retain_scoped_threads(&mut review.comments, &scope)
+    };
+
+    watchers.watch(
+        &repo_path,
+        &spec,
+        show_all,
+        review.context_lines,
+        &review.diffs,
+    );
+    repos::record_recent_repo(&repo_path);
+    record_review(&repo_path, &spec, &review);
+
+    let saved_replies = settings::load_saved_replies();
+    let checklist = load_checklist(&repo_path, &config);
+
+    Ok(render_local_view(
+        &review,
+        &repo_path,
+        &config,
+        &checklist,
+        &saved_replies,
+        file_order,
+        show_all,
+        hidden,
+    ))
+}
+
+/// Remember that `spec`'s diff was just reviewed, for resuming it from
+/// `/repos`.
+///
+/// Any errors are logged but don't cause the function to fail.
+fn record_review(repo_path: &Path, spec: &DiffSpec, review: &LocalReview) {
+    let session = ReviewSession {
+        spec: spec.to_query_string(),
+        description: review.info.description.clone(),
+        files: review.paths.clone(),
+        threads: review.comments.len(),
+        viewed_at: Utc::now(),
+    };
+
+    LocalCommentStore::new(repo_path)
+        .and_then(|store| chadreview_local_comment::record_review_session(&store, session))
+        .unwrap_or_else(|e| {
+            log::warn!(
+                "Failed to record review history of {}: {e}",
+                repo_path.display()
+            );
+        });
+}
+
+/// Everything needed to present a local review: the diff, its comment
+/// threads and the reviewer's viewed state.
+#[derive(Debug, Clone)]
+pub struct LocalReview {
+    /// Description of what is being diffed.
+    pub info: LocalDiffInfo,
+    /// Changed files.
+    pub diffs: Vec<DiffFile>,
+    /// How many changed files are left to load after `diffs`, when only
+    /// the first page of a very large diff is loaded.
+    pub more_files: usize,
+    /// Paths of every changed file, including those left to load.
+    pub paths: Vec<String>,
+    /// Number of context lines around changes, `None` for git's default.
+    pub context_lines: Option<u32>,
+    /// Each commit's changes, when the diff is reviewed commit by commit,
+    /// narrowed down by `commit_filter`.
+    pub commit_diffs: Vec<CommitDiff>,
+    /// Which commits are shown when the diff is reviewed commit by commit.
+    pub commit_filter: CommitFilter,
+    /// Top-level directories changed by the commits, offered as path
+    /// filters when the diff is reviewed commit by commit.
+    pub commit_paths: Vec<String>,
+    /// All comment threads in the repository.
+    pub comments: Vec<LocalComment>,
+    /// IDs of the line threads whose line is no longer in the diff.
+    pub outdated: HashSet<Uuid>,
+    /// Paths of files marked as viewed.
+    pub viewed_paths: HashSet<String>,
+    /// IDs of replies marked as viewed.
+    pub viewed_reply_ids: HashSet<Uuid>,
+    /// Unsubmitted comment form text, by form element ID.
+    pub drafts: HashMap<String, String>,
+    /// Cached AI summaries of the changed files, by path.
+    pub summaries: HashMap<String, String>,
+    /// AI executions run in the repository.
+    pub usage: UsageLedger,
+    /// Review checklist items checked on this diff.
+    pub checked_items: HashSet<String>,
+    /// Which files have staged changes, when the diff is of the working
+    /// tree.
+    pub staging: Option<Staging>,
+    /// The merge or rebase in progress and how many files it left
+    /// conflicted, when the diff is of the working tree.
+    pub operation: Option<(RepoOperation, usize)>,
+    /// Worktrees of the repository, the main one first.
+    pub worktrees: Vec<Worktree>,
+}
+
+/// Load the diff and comments for a local review, showing only the commits
+/// passing `commit_filter` when it is reviewed commit by commit.
+///
+/// Files are sorted in `file_order`. With `file_limit`, only that many
+/// files of a squashed diff are loaded, the first page of it, leaving the
+/// rest to [`LocalReview::more_files`]. Diffs show `context_lines` lines
+/// around changes, or git's default of 3.
+///
+/// Shared by the `/local` route and other frontends (e.g. the TUI).
+///
+/// # Errors
+///
+/// Returns an error if the repository cannot be opened, the diff cannot be
+/// computed, or the comment store cannot be read or updated.
+pub fn load_local_review<B: GitBackend>(
+    backend: Arc<B>,
+    repo_path: &Path,
+    spec: &DiffSpec,
+    commit_filter: &CommitFilter,
+    file_order: FileOrder,
+    file_limit: Option<usize>,
+    context_lines: Option<u32>,
+) -> Result<LocalReview, LocalRouteError> {
+    load_review(
+        backend,
+        repo_path,
+        spec,
+        commit_filter,
+        file_order,
+        file_limit,
+        context_lines,
+        true,
+    )
+}
+
+/// Load the diff of `spec` and the threads made on it for a read-only view
+/// of the review, such as the one served in share mode.
+///
+/// Unlike [`load_local_review`], nothing is written to the comment store:
+/// threads that aren't anchored yet are left so.
+///
+/// # Errors
+///
+/// Returns an error if the repository cannot be opened, the diff cannot be
+/// computed, or the comment store cannot be read.
+pub fn load_shared_review<B: GitBackend>(
+    backend: Arc<B>,
+    repo_path: &Path,
+    spec: &DiffSpec,
+) -> Result<LocalReview, LocalRouteError> {
+    let mut review = load_review(
+        backend,
+        repo_path,
+        spec,
+        &CommitFilter::default(),
+        FileOrder::default(),
+        None,
+        None,
+        false,
+    )?;
+    let scope = diff_scope(spec, &review.info.commits);
+    retain_scoped_threads(&mut review.comments, &scope);
+    Ok(review)
+}
 
-    // Parse diff specification from query params
-    let spec = DiffSpec::from_query(&req.query)?;
+/// Load the review of [`load_local_review`], recording the anchors of the
+/// diff's line threads in the store if `record_anchors`.
+#[allow(clippy::too_many_arguments)]
+fn load_review<B: GitBackend>(
+    backend: Arc<B>,
+    repo_path: &Path,
+    spec: &DiffSpec,
+    commit_filter: &CommitFilter,
+    file_order: FileOrder,
+    file_limit: Option<usize>,
+    context_lines: Option<u32>,
+    record_anchors: bool,
+) -> Result<LocalReview, LocalRouteError> {
+    // Create provider for this repository
+    let config = settings::load_config(Some(repo_path));
+    let provider = LocalDiffProvider::from_path(backend, repo_path)
+        .map_err(|e| LocalRouteError::InvalidRepoPath(e.to_string()))?
+        .with_context_lines(context_lines)
+        .with_submodules(config.diff.recurse_submodules);
 
     // Get diff info and files
-    let info = provider.get_diff_info(&spec)?;
-    let diffs = provider.get_diff(&spec)?;
+    let info = provider.get_diff_info(spec)?;
+    let commit_diffs = provider.get_commit_diffs(spec)?;
+    let commit_paths = top_level_dirs(commit_diffs.iter().flat_map(|c| &c.files));
+    let mut commit_diffs = filter_commit_diffs(commit_diffs, commit_filter);
+    for section in &mut commit_diffs {
+        sort_diff_files(&mut section.files, file_order);
+    }
+    let (diffs, paths) = if spec.is_by_commit() {
+        let mut diffs: Vec<_> = commit_diffs.iter().flat_map(|c| c.files.clone()).collect();
+        sort_diff_files(&mut diffs, file_order);
+        let paths = diffs.iter().map(|file| file.filename.clone()).collect();
+        (diffs, paths)
+    } else if let Some(limit) = file_limit {
+        let page = provider.get_diff_page(spec, 0, limit, file_order)?;
+        (page.files, page.paths)
+    } else {
+        let mut diffs = provider.get_diff(spec)?;
+        sort_diff_files(&mut diffs, file_order);
+        let paths = diffs.iter().map(|file| file.filename.clone()).collect();
+        (diffs, paths)
+    };
+    let more_files = paths.len() - diffs.len();
 
     // Load comments for this repository
-    let store = LocalCommentStore::new(&repo_path)?;
-    let thread_indices = store.list_threads()?;
-
-    // Load full threads for display
-    let mut comments = Vec::new();
-    for idx in &thread_indices {
-        if let Ok(thread) = store.load_thread(idx.id) {
-            comments.push(thread);
-        }
+    let store = LocalCommentStore::new(repo_path)?;
+    let mut comments = load_threads(&store)?;
+    let thread_files = line_thread_files(&provider, spec, &comments, &diffs, &paths)?;
+    // Threads made on other diffs would be anchored to whatever this diff
+    // has at their line
+    let scope = diff_scope(spec, &info.commits);
+    if record_anchors {
+        chadreview_local_comment::record_anchors(
+            &store,
+            comments.iter_mut().filter(|t| is_scoped(t, &scope)),
+            &thread_files,
+        )?;
     }
+    let outdated = comments
+        .iter()
+        .filter(|c| chadreview_local_comment::is_outdated(c, &thread_files))
+        .map(|c| c.id)
+        .collect();
 
     // Load viewed files and replies
     let viewed_paths = store.get_viewed_file_paths()?;
     let viewed_reply_ids = store.get_viewed_reply_ids()?;
+    let drafts = store.get_draft_bodies()?;
+    let summaries = chadreview_local_comment::load_file_summaries(&store, &diffs)?;
+    let usage = store.load_usage()?;
+    let checked_items =
+        chadreview_local_comment::load_checked_items(&store, &spec.to_query_string())?;
+    let staging = match spec {
+        DiffSpec::WorkingTree {
+            against,
+            staged_only,
+            ..
+        } => Some(Staging {
+            staged_only: *staged_only,
+            staged_paths: provider.staged_paths(against)?,
+        }),
+        _ => None,
+    };
+    let operation = match (&staging, provider.operation_in_progress()?) {
+        (Some(_), Some(operation)) => Some((operation, provider.conflicts()?.len())),
+        _ => None,
+    };
+    let worktrees = provider.worktrees()?;
+
+    Ok(LocalReview {
+        info,
+        diffs,
+        more_files,
+        paths,
+        context_lines,
+        commit_diffs,
+        commit_filter: commit_filter.clone(),
+        commit_paths,
+        comments,
+        outdated,
+        viewed_paths,
+        viewed_reply_ids,
+        drafts,
+        summaries,
+        usage,
+        checked_items,
+        staging,
+        operation,
+        worktrees,
+    })
+}
 
-    Ok(render_local_view(
-        &info,
-        &diffs,
-        &comments,
-        &repo_path,
-        &viewed_paths,
-        &viewed_reply_ids,
-    ))
+/// The files of `spec`'s diff holding line threads of `threads`, to tell
+/// whether they're outdated.
+///
+/// Those among `diffs`, the loaded files, are reused; those further down
+/// `paths`, the whole diff, are loaded on their own, so threads on files
+/// past the first page of a very large diff aren't taken for outdated.
+fn line_thread_files<B: GitBackend>(
+    provider: &LocalDiffProvider<B>,
+    spec: &DiffSpec,
+    threads: &[LocalComment],
+    diffs: &[DiffFile],
+    paths: &[String],
+) -> Result<Vec<DiffFile>, LocalRouteError> {
+    let thread_paths: BTreeSet<&str> = threads
+        .iter()
+        .filter_map(|thread| match &thread.comment_type {
+            LocalCommentType::LineLevelComment { path, .. } => Some(path.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut files: Vec<DiffFile> = diffs
+        .iter()
+        .filter(|file| thread_paths.contains(file.filename.as_str()))
+        .cloned()
+        .collect();
+    let unloaded: Vec<&str> = thread_paths
+        .into_iter()
+        .filter(|path| {
+            paths.iter().any(|p| p == path) && !files.iter().any(|file| file.filename == *path)
+        })
+        .collect();
+    if !unloaded.is_empty() {
+        files.extend(provider.get_diff_files(spec, &unloaded)?);
+    }
+
+    Ok(files)
+}
+
+/// The distinct top-level directories of `files`, sorted. Files at the
+/// repository root have none.
+fn top_level_dirs<'a>(files: impl Iterator<Item = &'a DiffFile>) -> Vec<String> {
+    let dirs: BTreeSet<_> = files
+        .filter_map(|file| file.filename.split_once('/'))
+        .map(|(dir, _)| dir.to_string())
+        .collect();
+
+    dirs.into_iter().collect()
+}
+
+/// Load every comment thread in the store, skipping unreadable ones.
+pub(crate) fn load_threads(
+    store: &LocalCommentStore,
+) -> Result<Vec<LocalComment>, LocalRouteError> {
+    let thread_indices = store.list_threads()?;
+
+    Ok(thread_indices
+        .iter()
+        .filter_map(|idx| store.load_thread(idx.id).ok())
+        .collect())
+}
+
+/// Load the threads shown with `spec`'s diff: every thread with
+/// `show_all`, otherwise those made on the diff.
+fn load_scoped_threads<B: GitBackend>(
+    store: &LocalCommentStore,
+    provider: &LocalDiffProvider<B>,
+    spec: &DiffSpec,
+    show_all: bool,
+) -> Result<Vec<LocalComment>, LocalRouteError> {
+    let mut threads = load_threads(store)?;
+    if !show_all {
+        let scope = diff_scope(spec, &provider.get_commits(spec)?);
+        retain_scoped_threads(&mut threads, &scope);
+    }
+    Ok(threads)
+}
+
+/// The scope of threads made on `spec`'s diff, made of `commits`.
+fn diff_scope(spec: &DiffSpec, commits: &[CommitInfo]) -> DiffScope {
+    match spec {
+        DiffSpec::WorkingTree { against, .. } => DiffScope::WorkingTree {
+            against: against.clone(),
+        },
+        _ => DiffScope::Commits {
+            shas: commits.iter().map(|c| c.sha.clone()).collect(),
+        },
+    }
+}
+
+/// Drop the threads made on diffs other than `scope`. Threads without a
+/// scope are kept. Returns how many were dropped.
+fn retain_scoped_threads(threads: &mut Vec<LocalComment>, scope: &DiffScope) -> usize {
+    let before = threads.len();
+    threads.retain(|t| is_scoped(t, scope));
+    before - threads.len()
+}
+
+/// Whether `thread` was made on the diff of `scope`, or has no scope.
+fn is_scoped(thread: &LocalComment, scope: &DiffScope) -> bool {
+    thread.diff_scope.as_ref().is_none_or(|s| s.overlaps(scope))
+}
+
+/// Whether the request asks for every thread in the repository
+/// (`comments=all`) rather than those made on the diff being viewed.
+fn shows_all_comments(req: &RouteRequest) -> bool {
+    req.query.get("comments").is_some_and(|v| v == "all")
+}
+
+/// Run `f`, which works on the repository, on the blocking thread pool.
+///
+/// Git backends are synchronous, so a big diff, or a backend talking to a
+/// git service over the network, would otherwise stall every request the
+/// executor serves until it is loaded. Every route opening a
+/// [`LocalDiffProvider`] does so in here.
+pub(crate) async fn run_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, LocalRouteError> + Send + 'static,
+) -> Result<T, LocalRouteError> {
+    switchy::unsync::task::spawn_blocking(f)
+        .await
+        .map_err(|e| LocalRouteError::ProviderError(format!("Git task failed: {e}")))?
+}
+
+/// Open the diff provider of `repo_path`, showing `context_lines` lines
+/// around changes, and the changes inside submodules if the repository's
+/// config asks for it.
+fn diff_provider<B: GitBackend>(
+    backend: Arc<B>,
+    repo_path: &Path,
+    context_lines: Option<u32>,
+) -> Result<LocalDiffProvider<B>, LocalRouteError> {
+    let config = settings::load_config(Some(repo_path));
+    Ok(LocalDiffProvider::from_path(backend, repo_path)
+        .map_err(|e| LocalRouteError::ProviderError(e.to_string()))?
+        .with_context_lines(context_lines)
+        .with_submodules(config.diff.recurse_submodules))
+}
+
+/// The file at `path` in `spec`'s diff.
+fn find_diff_file<B: GitBackend>(
+    provider: &LocalDiffProvider<B>,
+    spec: &DiffSpec,
+    path: &str,
+) -> Result<DiffFile, LocalRouteError> {
+    provider
+        .get_diff_file(spec, path)?
+        .ok_or_else(|| LocalRouteError::MissingParameter(format!("File not found: {path}")))
+}
+
+/// Number of context lines around changes the request asks for
+/// (`context=N`), `None` for git's default of 3. Invalid values are
+/// ignored.
+fn context_lines(req: &RouteRequest) -> Option<u32> {
+    req.query.get("context").and_then(|v| v.parse().ok())
+}
+
+/// Query string of the diff, comment filter and context lines being
+/// viewed, for URLs that act on the same view.
+fn view_query(spec: &DiffSpec, show_all: bool, context_lines: Option<u32>) -> String {
+    let mut query = spec.to_query_string();
+    if show_all {
+        query.push_str("&comments=all");
+    }
+    if let Some(lines) = context_lines {
+        write!(query, "&context={lines}").unwrap();
+    }
+    query
 }
 
 /// Handle POST `/api/local/comment` - Create a new comment.
@@ -238,15 +1110,29 @@ async fn local_route<B: GitBackend + 'static>(
 /// - `line` - Line number (for line_level)
 /// - `side` - Line side: "old" or "new" (for line_level)
 /// - `ai_agent` - Optional AI agent in format "provider:agent" (e.g., "opencode:code")
+///
+/// The diff spec params of the diff the comment is made on (same as
+/// `/local`) scope the new thread to that diff.
 async fn create_comment_route<B: GitBackend + 'static>(
     req: RouteRequest,
-    _backend: Arc<B>,
+    backend: Arc<B>,
 ) -> Result<Container, LocalRouteError> {
     if !matches!(req.method, Method::Post) {
         return Err(LocalRouteError::UnsupportedMethod);
     }
 
     let repo_path = get_repo_path(&req)?;
+    let spec = parse_diff_spec(&req)?;
+    let (provider, scope) = run_blocking({
+        let repo_path = repo_path.clone();
+        move || {
+            let provider = LocalDiffProvider::from_path(backend, &repo_path)
+                .map_err(|e| LocalRouteError::ProviderError(e.to_string()))?;
+            let scope = diff_scope(&spec, &provider.get_commits(&spec)?);
+            Ok((provider, scope))
+        }
+    })
+    .await?;
 
     // Parse the form body
     let form: CreateLocalCommentForm = req.parse_form()?;
@@ -259,7 +1145,7 @@ async fn create_comment_route<B: GitBackend + 'static>(
     let author = LocalUser::default(); // TODO: Get from git config
 
     // Create the comment
-    let mut comment = LocalComment::new(author, form.body, comment_type);
+    let mut comment = LocalComment::new(author, form.body, comment_type).with_diff_scope(scope);
     if let Some(action) = ai_action {
         comment = comment.with_ai_action(action);
     }
@@ -268,6 +1154,8 @@ async fn create_comment_route<B: GitBackend + 'static>(
     let store = LocalCommentStore::new(&repo_path)?;
     store.save_thread(&comment)?;
 
+    chadreview_hooks::dispatch(&repo_path, HookEvent::ThreadCreated, &comment);
+
     // If AI action is specified, trigger execution
     if comment.ai_action.is_some() {
         // Clone what we need for the spawned task
@@ -276,13 +1164,13 @@ async fn create_comment_route<B: GitBackend + 'static>(
 
         // Spawn async AI execution (thread_id == comment_id for root comments)
         switchy::unsync::task::spawn(async move {
-            execute_ai_action(repo, comment_id, comment_id).await;
+            execute_ai_action(repo, comment_id, comment_id, None, provider).await;
         });
     }
 
     // Return the rendered comment as a full thread with reply form
     // New comments have no replies yet, so empty set
-    let viewed_reply_ids = std::collections::HashSet::new();
+    let viewed_reply_ids = HashSet::new();
     let repo_path_str = repo_path.to_string_lossy().to_string();
     Ok(local_comments::render_local_comment_with_reply(
         &comment,
@@ -302,7 +1190,7 @@ async fn create_comment_route<B: GitBackend + 'static>(
 /// - `ai_agent` - Optional AI agent in format "provider:agent"
 async fn reply_comment_route<B: GitBackend + 'static>(
     req: RouteRequest,
-    _backend: Arc<B>,
+    backend: Arc<B>,
 ) -> Result<Container, LocalRouteError> {
     if !matches!(req.method, Method::Post) {
         return Err(LocalRouteError::UnsupportedMethod);
@@ -334,9 +1222,17 @@ async fn reply_comment_route<B: GitBackend + 'static>(
         let thread_id = form.thread_id;
         let reply_id = reply.id;
         let repo = repo_path.clone();
+        let provider = run_blocking({
+            let repo_path = repo_path.clone();
+            move || {
+                LocalDiffProvider::from_path(backend, &repo_path)
+                    .map_err(|e| LocalRouteError::ProviderError(e.to_string()))
+            }
+        })
+        .await?;
 
         switchy::unsync::task::spawn(async move {
-            execute_ai_action(repo, thread_id, reply_id).await;
+            execute_ai_action(repo, thread_id, reply_id, None, provider).await;
         });
     }
 
@@ -351,6 +1247,164 @@ async fn reply_comment_route<B: GitBackend + 'static>(
     ))
 }
 
+/// Handle PUT `/api/local/comment/update` - Edit a comment's body.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - `thread_id` - The root thread ID
+/// - `comment_id` - Optional: The comment to edit, a reply at any depth. If not
+///                  provided or same as thread_id, edits the thread's root comment.
+///
+/// Form body:
+/// - `body` - New comment text (required)
+///
+/// Returns the re-rendered thread for root comments and the re-rendered reply
+/// otherwise.
+async fn update_comment_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    _backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Put) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(&req)?;
+    let thread_id = parse_uuid_param(&req, "thread_id")?;
+    let comment_id = req
+        .query
+        .get("comment_id")
+        .and_then(|s| Uuid::parse_str(s).ok())
+        .unwrap_or(thread_id);
+
+    let form: UpdateCommentForm = req.parse_form()?;
+
+    let store = LocalCommentStore::new(&repo_path)?;
+    let comment = store.edit_comment(thread_id, comment_id, form.body)?;
+    log::info!("Edited comment {comment_id} in thread {thread_id}");
+
+    let repo_path_str = repo_path.to_string_lossy().to_string();
+    if thread_id == comment_id {
+        let viewed_reply_ids = store.get_viewed_reply_ids()?;
+        Ok(local_comments::render_local_comment_with_reply(
+            &comment,
+            &repo_path_str,
+            &viewed_reply_ids,
+        ))
+    } else {
+        Ok(local_comments::render_local_comment_item(
+            &comment,
+            thread_id,
+            &repo_path_str,
+            store.is_reply_viewed(comment_id),
+        ))
+    }
+}
+
+/// Handle PUT `/api/local/comment/reattach` - Move an outdated line thread
+/// to another line of its file.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - `thread_id` - The line thread to move
+/// - Diff spec params (same as `/local`)
+///
+/// Form body:
+/// - `line` - The new line: `42` for a new-side line, `old:42` for an
+///            old-side one. It must be part of the diff.
+///
+/// Returns the outcome, shown next to the thread's re-attach form.
+async fn reattach_comment_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Put) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(&req)?;
+    let thread_id = parse_uuid_param(&req, "thread_id")?;
+    let spec = parse_diff_spec(&req)?;
+    let form: ReattachForm = req.parse_form()?;
+
+    let store = LocalCommentStore::new(&repo_path)?;
+    let thread = store.load_thread(thread_id)?;
+    let LocalCommentType::LineLevelComment { path, .. } = &thread.comment_type else {
+        return Err(LocalCommentStoreError::NotLineComment(thread_id).into());
+    };
+
+    let diffs: Vec<DiffFile> = run_blocking({
+        let repo_path = repo_path.clone();
+        let spec = spec.clone();
+        move || {
+            let provider = LocalDiffProvider::from_path(backend, &repo_path)
+                .map_err(|e| LocalRouteError::ProviderError(e.to_string()))?;
+            Ok(if spec.is_by_commit() {
+                provider
+                    .get_commit_diffs(&spec)?
+                    .into_iter()
+                    .flat_map(|c| c.files)
+                    .collect()
+            } else {
+                provider.get_diff(&spec)?
+            })
+        }
+    })
+    .await?;
+
+    let outcome = match parse_line_reference(&form.line) {
+        None => Err(format!("Not a line number: {}", form.line.trim())),
+        Some(line) => match chadreview_local_comment::line_anchor(&diffs, path, line) {
+            None => Err(format!(
+                "{} is not part of this diff",
+                describe_line(path, line)
+            )),
+            Some(anchor) => {
+                store.reattach_thread(thread_id, line, Some(anchor))?;
+                log::info!(
+                    "Re-attached thread {thread_id} to {}",
+                    describe_line(path, line)
+                );
+                Ok(format!("Moved to {}", describe_line(path, line)))
+            }
+        },
+    };
+
+    let repo_path_str = repo_path.to_string_lossy().to_string();
+    Ok(local_outdated::render_reattach_result(
+        thread_id,
+        &repo_path_str,
+        &spec.to_query_string(),
+        outcome.as_deref().map_err(String::as_str),
+    )
+    .into())
+}
+
+/// Parse a line reference: `42` for a new-side line, `old:42` (or `new:42`)
+/// for a specific side.
+fn parse_line_reference(reference: &str) -> Option<LineNumber> {
+    let reference = reference.trim();
+    if let Some(line) = reference.strip_prefix("old:") {
+        return line
+            .trim()
+            .parse()
+            .ok()
+            .map(|line| LineNumber::Old { line });
+    }
+    let line = reference.strip_prefix("new:").unwrap_or(reference);
+    line.trim()
+        .parse()
+        .ok()
+        .map(|line| LineNumber::New { line })
+}
+
+/// Describe a line of a file (e.g. `src/lib.rs:42`).
+pub(crate) fn describe_line(path: &str, line: LineNumber) -> String {
+    match line {
+        LineNumber::New { line } => format!("{path}:{line}"),
+        LineNumber::Old { line } => format!("{path}:{line} (old)"),
+    }
+}
+
 /// Handle DELETE `/api/local/comment/delete` - Delete a comment or thread.
 ///
 /// Query parameters:
@@ -422,21 +1476,75 @@ async fn set_state_route<B: GitBackend + 'static>(
         })
         .unwrap_or(ThreadState::Open);
 
+    let thread = set_thread_state(&repo_path, thread_id, new_state)?;
+
+    // Re-render the thread with the new state
     let store = LocalCommentStore::new(&repo_path)?;
+    let repo_path_str = repo_path.to_string_lossy().to_string();
+    let viewed_reply_ids = store.get_viewed_reply_ids()?;
+    Ok(local_comments::render_local_comment_with_reply(
+        &thread,
+        &repo_path_str,
+        &viewed_reply_ids,
+    ))
+}
 
-    // Load the thread
-    let mut thread = store.load_thread(thread_id)?;
+/// Handle POST `/api/local/comment/suggestion` - Apply a suggested change.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - `thread_id` - The root thread ID
+/// - `index` - Optional: Which ```` ```suggestion ```` block to apply (default: 0)
+///
+/// Replaces the commented line in the working tree with the suggestion,
+/// resolves the thread and returns the re-rendered thread.
+async fn apply_suggestion_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
 
-    // Set new state
-    thread.state = new_state;
-    thread.updated_at = Utc::now();
+    let repo_path = get_repo_path(&req)?;
+    let thread_id = parse_uuid_param(&req, "thread_id")?;
+    let index = req
+        .query
+        .get("index")
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(0);
 
-    // Save the updated thread
-    store.save_thread(&thread)?;
+    let store = LocalCommentStore::new(&repo_path)?;
+    let thread = store.load_thread(thread_id)?;
 
-    log::info!("Thread {thread_id} state changed to: {:?}", thread.state);
+    let LocalCommentType::LineLevelComment {
+        path,
+        line: LineNumber::New { line },
+    } = &thread.comment_type
+    else {
+        return Err(LocalRouteError::NoSuggestion(thread_id));
+    };
+    let suggestion = chadreview_markdown::extract_suggestions(&thread.body)
+        .into_iter()
+        .nth(index)
+        .ok_or(LocalRouteError::NoSuggestion(thread_id))?;
+
+    run_blocking({
+        let repo_path = repo_path.clone();
+        let path = path.clone();
+        let line = *line;
+        move || {
+            LocalDiffProvider::from_path(backend, &repo_path)
+                .and_then(|provider| provider.apply_suggestion(&path, line, &suggestion))
+                .map_err(|e| LocalRouteError::ProviderError(e.to_string()))
+        }
+    })
+    .await?;
+
+    log::info!("Applied suggestion from thread {thread_id} to {path}:{line}");
+
+    let thread = set_thread_state(&repo_path, thread_id, ThreadState::Resolved)?;
 
-    // Re-render the thread with the new state
     let repo_path_str = repo_path.to_string_lossy().to_string();
     let viewed_reply_ids = store.get_viewed_reply_ids()?;
     Ok(local_comments::render_local_comment_with_reply(
@@ -446,228 +1554,1433 @@ async fn set_state_route<B: GitBackend + 'static>(
     ))
 }
 
-/// Handle POST/DELETE `/api/local/reply/view` - Mark reply as viewed/unviewed.
+/// Change the state of a comment thread, firing the `thread-resolved` hook
+/// when it becomes resolved.
+///
+/// # Errors
+///
+/// Returns an error if the thread cannot be loaded or saved.
+pub fn set_thread_state(
+    repo_path: &Path,
+    thread_id: Uuid,
+    state: ThreadState,
+) -> Result<LocalComment, LocalRouteError> {
+    let store = LocalCommentStore::new(repo_path)?;
+
+    // Load the thread
+    let mut thread = store.load_thread(thread_id)?;
+    let was_resolved = thread.state == ThreadState::Resolved;
+
+    // Set new state
+    thread.state = state;
+    thread.updated_at = Utc::now();
+
+    // Save the updated thread
+    store.save_thread(&thread)?;
+
+    log::info!("Thread {thread_id} state changed to: {:?}", thread.state);
+
+    if !was_resolved && thread.state == ThreadState::Resolved {
+        chadreview_hooks::dispatch(repo_path, HookEvent::ThreadResolved, &thread);
+    }
+
+    Ok(thread)
+}
+
+/// Handle POST `/api/local/draft` - Save the draft of a comment form.
 ///
 /// Query parameters:
 /// - `repo` - Repository path
-/// - `thread_id` - The thread containing the reply
-/// - `reply_id` - The specific reply to mark viewed/unviewed
 ///
-/// POST: Mark as viewed, returns re-rendered reply (collapsed)
-/// DELETE: Mark as unviewed, returns re-rendered reply (expanded)
-async fn reply_view_route<B: GitBackend + 'static>(
+/// Form body:
+/// - `key` - Element ID of the comment form (required)
+/// - `body` - Draft text; empty discards the draft
+async fn save_draft_route<B: GitBackend + 'static>(
     req: RouteRequest,
     _backend: Arc<B>,
 ) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
     let repo_path = get_repo_path(&req)?;
-    let thread_id = parse_uuid_param(&req, "thread_id")?;
-    let reply_id = parse_uuid_param(&req, "reply_id")?;
+    let form: DraftForm = req.parse_form()?;
 
     let store = LocalCommentStore::new(&repo_path)?;
+    store.save_draft(&form.key, &form.body)?;
 
-    // Load the thread to find the reply
-    let thread = store.load_thread(thread_id)?;
+    Ok(container! { div {} }.into())
+}
 
-    // Find the reply in the thread
-    let reply = find_reply_in_thread(&thread, reply_id)
-        .ok_or(LocalRouteError::CommentNotFound(reply_id))?;
+/// Handle POST `/api/local/attachment` - Store an image attached to a
+/// comment.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - `content_type` - The image's MIME type (e.g. `image/png`)
+/// - `name` - Optional file name, used as the image's alt text
+///
+/// The request body is the image. Returns the markdown showing it, to be
+/// inserted into the comment.
+async fn upload_attachment_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    _backend: Arc<B>,
+) -> Result<Content, LocalRouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
 
-    match req.method {
-        Method::Post => {
-            // Mark as viewed
-            store.mark_reply_viewed(reply_id)?;
-            log::info!("Marked reply {reply_id} as viewed");
-        }
-        Method::Delete => {
-            // Mark as unviewed
-            store.mark_reply_unviewed(reply_id)?;
-            log::info!("Marked reply {reply_id} as unviewed");
-        }
-        _ => return Err(LocalRouteError::UnsupportedMethod),
+    let repo_path = get_repo_path(&req)?;
+    let content_type = req
+        .query
+        .get("content_type")
+        .ok_or_else(|| LocalRouteError::MissingParameter("content_type".to_string()))?;
+    let name = req.query.get("name").map_or("", String::as_str);
+    let data = req.body.as_deref().map_or(&[][..], |body| &body[..]);
+
+    let store = LocalCommentStore::new(&repo_path)?;
+    let id = chadreview_local_comment::save_attachment(&*store, content_type, data)?;
+    let markdown = local_attachments::attachment_markdown(&repo_path.to_string_lossy(), id, name);
+
+    Ok(Content::Raw {
+        data: markdown.into_bytes().into(),
+        content_type: "text/plain; charset=utf-8".to_string(),
+    })
+}
+
+/// Handle GET `/api/local/attachment/{id}` - Serve an image attached to a
+/// comment.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+async fn attachment_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    _backend: Arc<B>,
+) -> Result<Content, LocalRouteError> {
+    if !matches!(req.method, Method::Get) {
+        return Err(LocalRouteError::UnsupportedMethod);
     }
 
-    // Re-render the reply with the new state
-    let repo_path_str = repo_path.to_string_lossy().to_string();
-    let is_viewed = store.is_reply_viewed(reply_id);
-    Ok(local_comments::render_local_comment_item(
-        &reply,
-        thread_id,
-        &repo_path_str,
-        is_viewed,
-    ))
+    let repo_path = get_repo_path(&req)?;
+    let id = req
+        .path
+        .strip_prefix(local_attachments::ATTACHMENT_ROUTE_PREFIX)
+        .unwrap_or_default();
+    let id = Uuid::parse_str(id).map_err(|_| LocalRouteError::InvalidUuid(id.to_string()))?;
+
+    let store = LocalCommentStore::new(&repo_path)?;
+    let attachment = chadreview_local_comment::load_attachment(&*store, id)?;
+
+    Ok(Content::Raw {
+        data: attachment.data.into(),
+        content_type: attachment.content_type.to_string(),
+    })
 }
 
-/// Find a reply within a thread by ID.
-fn find_reply_in_thread(thread: &LocalComment, reply_id: Uuid) -> Option<LocalComment> {
-    for reply in &thread.replies {
-        if reply.id == reply_id {
-            return Some(reply.clone());
-        }
-        // Check nested replies (if any)
-        if let Some(found) = find_reply_in_thread(reply, reply_id) {
-            return Some(found);
-        }
+/// Handle GET `/api/local/ai/agents` - List the AI agents comments can be
+/// addressed to, as a JSON array of `{id, name, description, default_model}`.
+///
+/// Query parameters:
+/// - `repo` - Repository path, whose own agents are included
+async fn ai_agents_route(req: RouteRequest) -> Result<Content, LocalRouteError> {
+    if !matches!(req.method, Method::Get) {
+        return Err(LocalRouteError::UnsupportedMethod);
     }
-    None
+
+    let repo_path = get_repo_path(&req)?;
+    let agents = list_ai_agents(&repo_path).await;
+
+    Ok(Content::Raw {
+        data: serde_json::Value::Array(agents)
+            .to_string()
+            .into_bytes()
+            .into(),
+        content_type: "application/json".to_string(),
+    })
+}
+
+/// Handle GET `/api/local/mentions` - Suggest commit authors to
+/// `@`-mention in comments, most commits first, like `git shortlog -sne`.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - `q` - What was typed after the `@`
+async fn mentions_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Content, LocalRouteError> {
+    if !matches!(req.method, Method::Get) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(&req)?;
+    let query = req.query.get("q").map_or("", String::as_str);
+    let authors = run_blocking(move || {
+        LocalDiffProvider::from_path(backend, &repo_path)
+            .and_then(|provider| provider.list_authors())
+            .map_err(|e| LocalRouteError::ProviderError(e.to_string()))
+    })
+    .await?;
+
+    Ok(suggestions_response(
+        authors.into_iter().map(|author| Suggestion {
+            label: format!("{} <{}>", author.name, author.email),
+            value: author.name,
+        }),
+        query,
+    ))
+}
+
+#[cfg(feature = "_ai-integration")]
+async fn list_ai_agents(repo_path: &Path) -> Vec<serde_json::Value> {
+    crate::ai::list_agents(repo_path)
+        .await
+        .into_iter()
+        .map(|agent| {
+            serde_json::json!({
+                "id": agent.id,
+                "name": agent.name,
+                "description": agent.description,
+                "default_model": agent.default_model,
+            })
+        })
+        .collect()
+}
+
+/// Without AI integration there are no providers to ask, so offer the
+/// default agents.
+#[cfg(not(feature = "_ai-integration"))]
+#[allow(clippy::unused_async)]
+async fn list_ai_agents(_repo_path: &Path) -> Vec<serde_json::Value> {
+    local_comments::DEFAULT_AGENTS
+        .iter()
+        .map(|(id, description)| {
+            serde_json::json!({
+                "id": id,
+                "name": id,
+                "description": description,
+                "default_model": null,
+            })
+        })
+        .collect()
+}
+
+/// Handle POST `/api/local/ai/review` - Review the whole diff with an AI
+/// agent. Returns the general thread tracking the review, whose findings
+/// are added as threads on the lines they cite when it completes.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - The diff spec params of the diff to review (same as `/local`)
+///
+/// Form body:
+/// - `ai_agent` - The review agent in format "provider:agent"
+async fn review_diff_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(&req)?;
+    let spec = parse_diff_spec(&req)?;
+    let form: ReviewDiffForm = req.parse_form()?;
+    let ai_action = parse_ai_action_from_string(form.ai_agent.as_deref())
+        .ok_or_else(|| LocalRouteError::MissingParameter("ai_agent".to_string()))?;
+
+    let (provider, scope, diffs) = run_blocking({
+        let repo_path = repo_path.clone();
+        move || {
+            let provider = LocalDiffProvider::from_path(backend, &repo_path)
+                .map_err(|e| LocalRouteError::ProviderError(e.to_string()))?;
+            let scope = diff_scope(&spec, &provider.get_commits(&spec)?);
+            let diffs = provider.get_diff(&spec)?;
+            Ok((provider, scope, diffs))
+        }
+    })
+    .await?;
+
+    let comment = LocalComment::new(
+        LocalUser::default(),
+        format!(
+            "Review this diff ({} files) and comment on each finding.",
+            diffs.len()
+        ),
+        LocalCommentType::General,
+    )
+    .with_diff_scope(scope)
+    .with_ai_action(ai_action);
+
+    let store = LocalCommentStore::new(&repo_path)?;
+    store.save_thread(&comment)?;
+    chadreview_hooks::dispatch(&repo_path, HookEvent::ThreadCreated, &comment);
+
+    let comment_id = comment.id;
+    let repo = repo_path.clone();
+    switchy::unsync::task::spawn(async move {
+        execute_ai_action(repo, comment_id, comment_id, Some(diffs), provider).await;
+    });
+
+    let repo_path_str = repo_path.to_string_lossy().to_string();
+    Ok(local_comments::render_local_comment_with_reply(
+        &comment,
+        &repo_path_str,
+        &HashSet::new(),
+    ))
+}
+
+/// Handle POST `/api/local/ai/summaries` - Summarize the changed files of
+/// the diff that don't have a cached summary yet with an AI agent. Returns
+/// the summary form's status; each summary is pushed above its file as it
+/// is generated.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - The diff spec params of the diff to summarize (same as `/local`)
+///
+/// Form body:
+/// - `ai_agent` - The summary agent in format "provider:agent"
+async fn summarize_files_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(&req)?;
+    let spec = parse_diff_spec(&req)?;
+    let form: SummarizeFilesForm = req.parse_form()?;
+    let ai_action = parse_ai_action_from_string(form.ai_agent.as_deref())
+        .ok_or_else(|| LocalRouteError::MissingParameter("ai_agent".to_string()))?;
+
+    // Summarize the files as shown, commit by commit if reviewed that way
+    let diffs: Vec<DiffFile> = run_blocking({
+        let repo_path = repo_path.clone();
+        move || {
+            let provider = LocalDiffProvider::from_path(backend, &repo_path)
+                .map_err(|e| LocalRouteError::ProviderError(e.to_string()))?;
+            Ok(if spec.is_by_commit() {
+                provider
+                    .get_commit_diffs(&spec)?
+                    .into_iter()
+                    .flat_map(|c| c.files)
+                    .collect()
+            } else {
+                provider.get_diff(&spec)?
+            })
+        }
+    })
+    .await?;
+
+    let store = LocalCommentStore::new(&repo_path)?;
+    let cached = chadreview_local_comment::load_file_summaries(&store, &diffs)?;
+    let uncached: Vec<_> = diffs
+        .into_iter()
+        .filter(|file| !cached.contains_key(&file.filename))
+        .collect();
+
+    let batches = chadreview_local_comment::summary_batches(&uncached);
+    let files: usize = batches.iter().map(Vec::len).sum();
+    if files == 0 {
+        return Ok(local_summaries::render_summary_status(
+            "Every file is summarized.",
+        ));
+    }
+
+    switchy::unsync::task::spawn(async move {
+        summarize_files(repo_path, ai_action, batches).await;
+    });
+
+    Ok(local_summaries::render_summary_status(&format!(
+        "Summarizing {files} files..."
+    )))
+}
+
+/// Handle POST `/api/local/ai/describe` - Ask an AI agent for a commit
+/// message or PR description of the combined diff. Returns the proposed
+/// description, or why it couldn't be written.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - The diff spec params of the diff to describe (same as `/local`)
+///
+/// Form body:
+/// - `ai_agent` - The describing agent in format "provider:agent"
+/// - `kind` - `commit` for a commit message (default) or `pr` for a PR
+///   description
+async fn describe_diff_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(&req)?;
+    let spec = parse_diff_spec(&req)?;
+    let form: DescribeDiffForm = req.parse_form()?;
+    let ai_action = parse_ai_action_from_string(form.ai_agent.as_deref())
+        .ok_or_else(|| LocalRouteError::MissingParameter("ai_agent".to_string()))?;
+
+    let diffs = run_blocking({
+        let repo_path = repo_path.clone();
+        move || {
+            let provider = LocalDiffProvider::from_path(backend, &repo_path)
+                .map_err(|e| LocalRouteError::ProviderError(e.to_string()))?;
+            Ok(provider.get_diff(&spec)?)
+        }
+    })
+    .await?;
+
+    let description = describe_diff(&repo_path, &ai_action, form.kind, &diffs).await;
+    if let Err(e) = &description {
+        log::warn!("Failed to describe diff with {}: {e}", ai_action.agent);
+    }
+
+    Ok(local_describe::render_description(Some((
+        form.kind,
+        description.as_deref().map_err(String::as_str),
+    )))
+    .into())
+}
+
+/// Handle POST `/api/local/ai/cancel` - Cancel the running AI action of a
+/// comment, killing its process. Returns the comment's AI status.
+///
+/// Query parameters:
+/// - `comment_id` - The comment holding the AI action
+fn cancel_ai_route(req: &RouteRequest) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let comment_id = parse_uuid_param(req, "comment_id")?;
+    let status =
+        cancel_ai_execution(comment_id).ok_or(LocalRouteError::AiNotRunning(comment_id))?;
+    log::info!("Cancelled AI execution for comment {comment_id}");
+
+    Ok(local_comments::render_ai_status_container(
+        comment_id, &status,
+    ))
+}
+
+/// Cancel the running AI action of comment `comment_id`, returning the
+/// status its execution records once the process is killed.
+#[cfg(feature = "_ai-integration")]
+fn cancel_ai_execution(comment_id: Uuid) -> Option<AiExecutionStatus> {
+    crate::ai::cancel_execution(comment_id).then(|| AiExecutionStatus::Failed {
+        finished_at: Utc::now(),
+        error: chadreview_ai_provider::AiProviderError::Cancelled.to_string(),
+    })
+}
+
+/// Simulated executions run to completion.
+#[cfg(not(feature = "_ai-integration"))]
+const fn cancel_ai_execution(_comment_id: Uuid) -> Option<AiExecutionStatus> {
+    None
+}
+
+/// Handle POST `/api/local/ai/budget` - Let AI actions in a repository run
+/// past its budget until the app restarts. Returns the repository's AI usage.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// Handle POST `/api/local/checklist` - Check or uncheck an item of the
+/// review checklist of the diff given by the query parameters, returning
+/// the re-rendered checklist.
+fn checklist_route(req: &RouteRequest) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(req)?;
+    let spec = parse_diff_spec(req)?;
+    let form = req.parse_form::<ChecklistForm>()?;
+    let store = LocalCommentStore::new(&repo_path)?;
+    let spec_query = spec.to_query_string();
+
+    chadreview_local_comment::set_checklist_item(
+        &store,
+        &spec_query,
+        &form.item,
+        form.checked == "true",
+    )?;
+
+    let config = settings::load_config(Some(&repo_path));
+    let items = load_checklist(&repo_path, &config);
+    let checked_items = chadreview_local_comment::load_checked_items(&store, &spec_query)?;
+
+    Ok(checklist::render_checklist(
+        &items,
+        &checked_items,
+        &checklist::local_checklist_url(&repo_path.to_string_lossy(), &spec_query),
+    )
+    .into())
+}
+
+/// Load the review checklist of the repository at `repo_path`, falling back
+/// to none if its checklist file can't be read.
+fn load_checklist(repo_path: &Path, config: &Config) -> Vec<String> {
+    chadreview_config::load_checklist(Some(repo_path), config).unwrap_or_else(|e| {
+        log::warn!(
+            "Failed to load review checklist of {}: {e}",
+            repo_path.display()
+        );
+        Vec::new()
+    })
+}
+
+fn override_budget_route(req: &RouteRequest) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(req)?;
+    override_budget(&repo_path);
+    log::info!("Overrode the AI budget of {}", repo_path.display());
+
+    let store = LocalCommentStore::new(&repo_path)?;
+    let config = settings::load_config(Some(&repo_path));
+
+    Ok(local_header::render_ai_usage(
+        &repo_path.to_string_lossy(),
+        &store.load_usage()?,
+        config.ai.budget,
+        budget_overridden(&repo_path),
+    ))
+}
+
+#[cfg(feature = "_ai-integration")]
+fn override_budget(repo_path: &Path) {
+    crate::ai::override_budget(repo_path);
+}
+
+/// Simulated executions cost nothing, so there is no budget to override.
+#[cfg(not(feature = "_ai-integration"))]
+const fn override_budget(_repo_path: &Path) {}
+
+#[cfg(feature = "_ai-integration")]
+fn budget_overridden(repo_path: &Path) -> bool {
+    crate::ai::budget_overridden(repo_path)
+}
+
+#[cfg(not(feature = "_ai-integration"))]
+const fn budget_overridden(_repo_path: &Path) -> bool {
+    false
+}
+
+/// Handle POST/DELETE `/api/local/reply/view` - Mark reply as viewed/unviewed.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - `thread_id` - The thread containing the reply
+/// - `reply_id` - The specific reply to mark viewed/unviewed
+///
+/// POST: Mark as viewed, returns re-rendered reply (collapsed)
+/// DELETE: Mark as unviewed, returns re-rendered reply (expanded)
+async fn reply_view_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    _backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    let repo_path = get_repo_path(&req)?;
+    let thread_id = parse_uuid_param(&req, "thread_id")?;
+    let reply_id = parse_uuid_param(&req, "reply_id")?;
+
+    let store = LocalCommentStore::new(&repo_path)?;
+
+    // Load the thread to find the reply
+    let thread = store.load_thread(thread_id)?;
+
+    // Find the reply in the thread
+    let reply = find_reply_in_thread(&thread, reply_id)
+        .ok_or(LocalRouteError::CommentNotFound(reply_id))?;
+
+    match req.method {
+        Method::Post => {
+            // Mark as viewed
+            store.mark_reply_viewed(reply_id)?;
+            log::info!("Marked reply {reply_id} as viewed");
+        }
+        Method::Delete => {
+            // Mark as unviewed
+            store.mark_reply_unviewed(reply_id)?;
+            log::info!("Marked reply {reply_id} as unviewed");
+        }
+        _ => return Err(LocalRouteError::UnsupportedMethod),
+    }
+
+    // Re-render the reply with the new state
+    let repo_path_str = repo_path.to_string_lossy().to_string();
+    let is_viewed = store.is_reply_viewed(reply_id);
+    Ok(local_comments::render_local_comment_item(
+        &reply,
+        thread_id,
+        &repo_path_str,
+        is_viewed,
+    ))
+}
+
+/// Find a reply within a thread by ID.
+fn find_reply_in_thread(thread: &LocalComment, reply_id: Uuid) -> Option<LocalComment> {
+    for reply in &thread.replies {
+        if reply.id == reply_id {
+            return Some(reply.clone());
+        }
+        // Check nested replies (if any)
+        if let Some(found) = find_reply_in_thread(reply, reply_id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// Handle POST/DELETE `/api/local/file/view` - Mark file as viewed/unviewed.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - `path` - File path to mark
+/// - Diff spec params, `comments` and `context` (same as `/local`)
+///
+/// POST: Mark as viewed, returns collapsed file header
+/// DELETE: Mark as unviewed, returns expanded file with content
+async fn file_view_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    let repo_path = get_repo_path(&req)?;
+    let file_path = req
+        .query
+        .get("path")
+        .ok_or_else(|| LocalRouteError::MissingParameter("path".to_string()))?
+        .clone();
+
+    let store = Arc::new(LocalCommentStore::new(&repo_path)?);
+
+    match req.method {
+        Method::Post => {
+            // Mark as viewed -> return collapsed header
+            store.mark_file_viewed(&file_path)?;
+            log::info!("Marked file as viewed: {file_path}");
+
+            // We need the diff file info to render the header
+            // Load the diff to find this file
+            let spec = parse_diff_spec(&req)?;
+            let diff_file = run_blocking({
+                let repo_path = repo_path.clone();
+                let spec = spec.clone();
+                let context_lines = context_lines(&req);
+                move || {
+                    let provider = diff_provider(backend, &repo_path, context_lines)?;
+                    find_diff_file(&provider, &spec, &file_path)
+                }
+            })
+            .await?;
+
+            let repo_path_str = repo_path.to_string_lossy().to_string();
+            let spec_query = view_query(&spec, shows_all_comments(&req), context_lines(&req));
+            Ok(diff_viewer::render_file_collapsed(&diff_file, &repo_path_str, &spec_query).into())
+        }
+        Method::Delete => {
+            // Mark as unviewed -> return expanded file with content
+            store.mark_file_unviewed(&file_path)?;
+            log::info!("Marked file as unviewed: {file_path}");
+
+            // Load diff and comments to render the full file
+            let spec = parse_diff_spec(&req)?;
+            let show_all = shows_all_comments(&req);
+            let (diff_file, comments) = run_blocking({
+                let store = Arc::clone(&store);
+                let repo_path = repo_path.clone();
+                let spec = spec.clone();
+                let context_lines = context_lines(&req);
+                move || {
+                    let provider = diff_provider(backend, &repo_path, context_lines)?;
+                    let diff_file = find_diff_file(&provider, &spec, &file_path)?;
+                    let comments = load_scoped_threads(&store, &provider, &spec, show_all)?;
+                    Ok((diff_file, comments))
+                }
+            })
+            .await?;
+
+            // Load viewed reply IDs
+            let viewed_reply_ids = store.get_viewed_reply_ids()?;
+
+            let repo_path_str = repo_path.to_string_lossy().to_string();
+            Ok(diff_viewer::render_file_expanded(
+                &diff_file,
+                &comments,
+                &repo_path_str,
+                &view_query(&spec, show_all, context_lines(&req)),
+                false,
+                &viewed_reply_ids,
+            )
+            .into())
+        }
+        _ => Err(LocalRouteError::UnsupportedMethod),
+    }
+}
+
+/// Handle GET `/api/local/file/diff` - Get rendered diff for a file (lazy loading).
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - `path` - File path to render
+/// - Diff spec params, `comments` and `context` (same as `/local`)
+///
+/// Returns the expanded file container (header + content).
+async fn file_diff_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Get) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(&req)?;
+    let file_path = req
+        .query
+        .get("path")
+        .ok_or_else(|| LocalRouteError::MissingParameter("path".to_string()))?
+        .clone();
+
+    let spec = parse_diff_spec(&req)?;
+    let show_all = shows_all_comments(&req);
+
+    let (diff_file, comments, is_viewed, viewed_reply_ids) = run_blocking({
+        let repo_path = repo_path.clone();
+        let spec = spec.clone();
+        let context_lines = context_lines(&req);
+        move || {
+            let store = LocalCommentStore::new(&repo_path)?;
+
+            // Load diff
+            let provider = diff_provider(backend, &repo_path, context_lines)?;
+            let diff_file = find_diff_file(&provider, &spec, &file_path)?;
+
+            // Load comments
+            let comments = load_scoped_threads(&store, &provider, &spec, show_all)?;
+
+            // Check if file is viewed (for the checkbox state)
+            let is_viewed = store.is_file_viewed(&file_path);
+
+            // Load viewed reply IDs
+            let viewed_reply_ids = store.get_viewed_reply_ids()?;
+
+            Ok((diff_file, comments, is_viewed, viewed_reply_ids))
+        }
+    })
+    .await?;
+
+    let repo_path_str = repo_path.to_string_lossy().to_string();
+    Ok(diff_viewer::render_file_expanded(
+        &diff_file,
+        &comments,
+        &repo_path_str,
+        &view_query(&spec, show_all, context_lines(&req)),
+        is_viewed,
+        &viewed_reply_ids,
+    )
+    .into())
+}
+
+/// Handle GET `/api/local/files` - Render the next page of a very large
+/// diff, whose first page is loaded with the `/local` view.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - `offset` - Index of the first file of the page
+/// - Diff spec params, `comments` and `context` (same as `/local`)
+///
+/// Returns the page's files, followed by the button loading the next page
+/// if files are left.
+async fn files_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Get) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(&req)?;
+    let offset = req
+        .query
+        .get("offset")
+        .ok_or_else(|| LocalRouteError::MissingParameter("offset".to_string()))?
+        .parse::<usize>()
+        .map_err(|_| LocalRouteError::InvalidParameter("offset".to_string()))?;
+
+    let spec = parse_diff_spec(&req)?;
+    let show_all = shows_all_comments(&req);
+    let file_order = FileOrder::from_query(&req.query);
+
+    let (page, comments, viewed_paths, viewed_reply_ids, summaries) = run_blocking({
+        let repo_path = repo_path.clone();
+        let spec = spec.clone();
+        let context_lines = context_lines(&req);
+        move || {
+            let store = LocalCommentStore::new(&repo_path)?;
+            let provider = diff_provider(backend, &repo_path, context_lines)?;
+            let page = provider.get_diff_page(&spec, offset, DIFF_PAGE_FILES, file_order)?;
+
+            let scope = diff_scope(&spec, &provider.get_commits(&spec)?);
+            let mut comments = load_threads(&store)?;
+            chadreview_local_comment::record_anchors(
+                &store,
+                comments.iter_mut().filter(|t| is_scoped(t, &scope)),
+                &page.files,
+            )?;
+            if !show_all {
+                retain_scoped_threads(&mut comments, &scope);
+            }
+            let viewed_paths = store.get_viewed_file_paths()?;
+            let viewed_reply_ids = store.get_viewed_reply_ids()?;
+            let summaries = chadreview_local_comment::load_file_summaries(&store, &page.files)?;
+            Ok((page, comments, viewed_paths, viewed_reply_ids, summaries))
+        }
+    })
+    .await?;
+    let generated =
+        settings::load_generated_files(Some(&repo_path), &settings::load_config(Some(&repo_path)));
+    let generated_paths: HashSet<String> = page
+        .files
+        .iter()
+        .filter(|file| generated.is_generated(&file.filename))
+        .map(|file| file.filename.clone())
+        .collect();
+
+    let repo_path_str = repo_path.to_string_lossy().to_string();
+    let spec_query = view_query(&spec, show_all, context_lines(&req));
+    let total = page.paths.len();
+    let loaded = offset + page.files.len();
+
+    Ok(container! {
+        div gap=24 {
+            @for file in &page.files {
+                (diff_viewer::render_local_file(file, &comments, &repo_path_str, &spec_query, &viewed_paths, &generated_paths, &viewed_reply_ids, &summaries, None))
+            }
+            @if loaded < total {
+                (diff_viewer::render_load_more_files(&repo_path_str, &spec_query, loaded, total - loaded))
+            }
+        }
+    }
+    .into())
+}
+
+/// Files of a diff re-rendered after they changed.
+pub(crate) struct ChangedFiles {
+    /// The changed files still in the diff, rendered like on the page.
+    pub files: Vec<Container>,
+    /// Paths of the changed files no longer in the diff.
+    pub removed: Vec<String>,
+}
+
+/// Recompute `spec`'s diff of `repo_path` and render its files in `paths`,
+/// the same way [`file_diff_route`] does, or collapsed if viewed or
+/// generated.
+///
+/// # Errors
+///
+/// Returns an error if the diff cannot be computed or the comment store
+/// cannot be read.
+pub(crate) fn render_changed_files<B: GitBackend>(
+    backend: Arc<B>,
+    repo_path: &Path,
+    spec: &DiffSpec,
+    show_all: bool,
+    context_lines: Option<u32>,
+    paths: &HashSet<String>,
+) -> Result<ChangedFiles, LocalRouteError> {
+    let store = LocalCommentStore::new(repo_path)?;
+    let provider = diff_provider(backend, repo_path, context_lines)?;
+    let diffs = provider.get_diff(spec)?;
+    let comments = load_scoped_threads(&store, &provider, spec, show_all)?;
+    let viewed_reply_ids = store.get_viewed_reply_ids()?;
+    let generated =
+        settings::load_generated_files(Some(repo_path), &settings::load_config(Some(repo_path)));
+
+    let repo_path_str = repo_path.to_string_lossy().to_string();
+    let spec_query = view_query(spec, show_all, context_lines);
+
+    let files = diffs
+        .iter()
+        .filter(|file| paths.contains(&file.filename))
+        .map(|file| {
+            if store.is_file_viewed(&file.filename) {
+                diff_viewer::render_file_collapsed(file, &repo_path_str, &spec_query).into()
+            } else if generated.is_generated(&file.filename) {
+                diff_viewer::render_file_generated(file, &repo_path_str, &spec_query).into()
+            } else {
+                diff_viewer::render_file_expanded(
+                    file,
+                    &comments,
+                    &repo_path_str,
+                    &spec_query,
+                    false,
+                    &viewed_reply_ids,
+                )
+                .into()
+            }
+        })
+        .collect();
+    let removed = paths
+        .iter()
+        .filter(|path| !diffs.iter().any(|file| &&file.filename == path))
+        .cloned()
+        .collect();
+
+    Ok(ChangedFiles { files, removed })
+}
+
+/// Handle POST `/api/local/staging` - Stage, unstage or discard the changes
+/// of a file of a working tree diff, or stage one of its hunks.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - Diff spec params, `comments` and `context` (same as `/local`)
+///
+/// Returns the file's re-rendered staging bar, with the outcome of the
+/// action.
+async fn staging_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(&req)?;
+    let form: StagingForm = req.parse_form()?;
+    let spec = parse_diff_spec(&req)?;
+    let DiffSpec::WorkingTree {
+        against,
+        staged_only,
+        ..
+    } = &spec
+    else {
+        return Err(LocalRouteError::InvalidParameter(
+            "staging needs a working tree diff".to_string(),
+        ));
+    };
+    let path = form.path.clone();
+
+    let (outcome, staging, file) = run_blocking({
+        let repo_path = repo_path.clone();
+        let spec = spec.clone();
+        let against = against.clone();
+        let staged_only = *staged_only;
+        let context_lines = context_lines(&req);
+        move || {
+            let provider = diff_provider(backend, &repo_path, context_lines)?;
+
+            let result = match (form.action.as_str(), form.hunk) {
+                ("stage", _) => provider
+                    .stage_path(&spec, &form.path)
+                    .map(|()| "Staged".to_string()),
+                ("unstage", _) => provider
+                    .unstage_path(&spec, &form.path)
+                    .map(|()| "Unstaged".to_string()),
+                ("discard", _) => provider
+                    .discard_changes(&spec, &form.path, form.delete_added)
+                    .map(|()| "Discarded".to_string()),
+                ("hunk", Some(hunk)) => provider
+                    .stage_hunk(&spec, &form.path, hunk)
+                    .map(|()| format!("Staged hunk {}", hunk + 1)),
+                ("hunk", None) => {
+                    return Err(LocalRouteError::MissingParameter("hunk".to_string()));
+                }
+                (action, _) => {
+                    return Err(LocalRouteError::InvalidParameter(format!(
+                        "unknown staging action: {action}"
+                    )));
+                }
+            };
+            let outcome = result.map_err(|e| {
+                log::warn!("Failed to {} {}: {e}", form.action, form.path);
+                e.to_string()
+            });
+
+            let staging = Staging {
+                staged_only,
+                staged_paths: provider.staged_paths(&against)?,
+            };
+            let file = provider
+                .get_diff(&spec)?
+                .into_iter()
+                .find(|file| file.filename == form.path);
+            Ok((outcome, staging, file))
+        }
+    })
+    .await?;
+
+    Ok(local_staging::render_file_staging(
+        &path,
+        file.as_ref(),
+        &repo_path.to_string_lossy(),
+        &view_query(&spec, shows_all_comments(&req), context_lines(&req)),
+        &staging,
+        Some(outcome.as_deref().map_err(String::as_str)),
+    )
+    .into())
+}
+
+/// Handle POST `/api/local/commit` - Commit the staged changes, or ask an
+/// AI agent to suggest a commit message for them.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+///
+/// Form body:
+/// - `action` - `commit`, or `suggest` for a suggested message
+/// - `message` - The commit message
+/// - `amend` - Whether to amend the last commit
+/// - `sign_off` - Whether to add a `Signed-off-by` trailer
+/// - `ai_agent` - The suggesting agent in format "provider:agent"
+///
+/// Returns the re-rendered commit form, with the outcome of the action. A
+/// suggested message replaces the form's, and committing clears it.
+async fn commit_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(&req)?;
+    let form: CommitForm = req.parse_form()?;
+    let provider = run_blocking({
+        let repo_path = repo_path.clone();
+        move || {
+            LocalDiffProvider::from_path(backend, &repo_path)
+                .map_err(|e| LocalRouteError::ProviderError(e.to_string()))
+        }
+    })
+    .await?;
+    let mut draft = CommitDraft {
+        message: form.message,
+        amend: form.amend,
+        sign_off: form.sign_off,
+    };
+
+    let outcome = match form.action.as_str() {
+        "commit" => match run_blocking({
+            let message = draft.message.clone();
+            let (amend, sign_off) = (draft.amend, draft.sign_off);
+            move || Ok(provider.commit(&message, amend, sign_off))
+        })
+        .await?
+        {
+            Ok(commit) => {
+                draft = CommitDraft {
+                    sign_off: draft.sign_off,
+                    ..CommitDraft::default()
+                };
+                Ok(format!("Committed {} {}", commit.short_sha, commit.summary))
+            }
+            Err(e) => {
+                log::warn!("Failed to commit: {e}");
+                Err(e.to_string())
+            }
+        },
+        "suggest" => {
+            let ai_action = parse_ai_action_from_string(form.ai_agent.as_deref())
+                .ok_or_else(|| LocalRouteError::MissingParameter("ai_agent".to_string()))?;
+            let amend = draft.amend;
+            let diffs = run_blocking(move || {
+                // An amended commit replaces the last one, so its message
+                // covers the last commit's changes too
+                let spec = DiffSpec::WorkingTree {
+                    against: provider.commit_base(amend)?,
+                    staged_only: true,
+                    include_untracked: false,
+                };
+                Ok(provider.get_diff(&spec)?)
+            })
+            .await?;
+
+            if diffs.is_empty() {
+                Err("Nothing is staged to describe".to_string())
+            } else {
+                match describe_diff(&repo_path, &ai_action, DescriptionKind::Commit, &diffs).await {
+                    Ok(message) => {
+                        draft.message = message;
+                        Ok(format!("Message suggested by {}", ai_action.agent))
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "Failed to suggest a commit message with {}: {e}",
+                            ai_action.agent
+                        );
+                        Err(e)
+                    }
+                }
+            }
+        }
+        action => {
+            return Err(LocalRouteError::InvalidParameter(format!(
+                "unknown commit action: {action}"
+            )));
+        }
+    };
+
+    Ok(local_commit::render_commit_form(
+        &repo_path.to_string_lossy(),
+        form.ai_agent.as_deref(),
+        &draft,
+        Some(outcome.as_deref().map_err(String::as_str)),
+    )
+    .into())
+}
+
+/// Handle POST `/api/local/conflicts/resolve` - Mark the conflicts of a
+/// file resolved by staging it.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+///
+/// Form body:
+/// - `path` - The conflicted file
+///
+/// Returns the file's re-rendered resolve button, with the outcome.
+async fn resolve_conflict_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(&req)?;
+    let form: ResolveForm = req.parse_form()?;
+
+    let outcome = run_blocking({
+        let repo_path = repo_path.clone();
+        let path = form.path.clone();
+        move || {
+            let provider = LocalDiffProvider::from_path(backend, &repo_path)
+                .map_err(|e| LocalRouteError::ProviderError(e.to_string()))?;
+            Ok(provider
+                .mark_resolved(&path)
+                .map(|()| "Resolved".to_string())
+                .map_err(|e| {
+                    log::warn!("Failed to mark {path} resolved: {e}");
+                    e.to_string()
+                }))
+        }
+    })
+    .await?;
+
+    Ok(local_conflicts::render_resolve(
+        &repo_path.to_string_lossy(),
+        &form.path,
+        Some(outcome.as_deref().map_err(String::as_str)),
+    )
+    .into())
+}
+
+/// Handle fetching a branch from another remote (e.g. a fork).
+///
+/// Renders a link to the `/local` view comparing the fetched branch against
+/// the requested base, or the reason the fetch failed.
+async fn compare_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
+
+    let repo_path = get_repo_path(&req)?;
+    let form: CompareForm = req.parse_form()?;
+    let base = form
+        .base
+        .as_deref()
+        .map(str::trim)
+        .filter(|base| !base.is_empty())
+        .unwrap_or("HEAD");
+
+    let result = run_blocking({
+        let repo_path = repo_path.clone();
+        let (url, branch, base) = (
+            form.url.trim().to_string(),
+            form.branch.trim().to_string(),
+            base.to_string(),
+        );
+        move || {
+            Ok(LocalDiffProvider::from_path(backend, &repo_path)
+                .and_then(|provider| provider.fetch_compare(&url, &branch, &base)))
+        }
+    })
+    .await?;
+
+    Ok(match result {
+        Ok(spec) => {
+            let url = format!(
+                "/local?repo={}&{}",
+                urlencoding::encode(&repo_path.to_string_lossy()),
+                spec.to_query_string()
+            );
+            local_header::render_compare_result(Some(Ok(&url)))
+        }
+        Err(e) => {
+            log::warn!("Failed to fetch {} from {}: {e}", form.branch, form.url);
+            local_header::render_compare_result(Some(Err(&e.to_string())))
+        }
+    }
+    .into())
+}
+
+/// Handle searching a local diff.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+/// - Diff spec params (same as `/local`)
+///
+/// The query comes from the `q` and `scope` query params on `GET`, or from
+/// the form body on `POST`. A `scope` of `files` also searches the changed
+/// files' full contents, not just the diff's hunks.
+async fn search_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    let form = match req.method {
+        Method::Get => SearchForm {
+            q: req
+                .query
+                .get("q")
+                .ok_or_else(|| LocalRouteError::MissingParameter("q".to_string()))?
+                .clone(),
+            scope: req.query.get("scope").cloned(),
+        },
+        Method::Post => req.parse_form()?,
+        _ => return Err(LocalRouteError::UnsupportedMethod),
+    };
+
+    let query = form.q.trim();
+    let include_files = form.scope.as_deref() == Some("files");
+
+    let repo_path = get_repo_path(&req)?;
+    let spec = parse_diff_spec(&req)?;
+    let mut matches = run_blocking({
+        let query = query.to_string();
+        move || {
+            let provider = LocalDiffProvider::from_path(backend, &repo_path)
+                .map_err(|e| LocalRouteError::ProviderError(e.to_string()))?;
+            // Ask for one extra match to tell whether the results were cut off
+            Ok(provider.search(&spec, &query, include_files, SEARCH_LIMIT + 1)?)
+        }
+    })
+    .await?;
+    let limit_reached = matches.len() > SEARCH_LIMIT;
+    matches.truncate(SEARCH_LIMIT);
+
+    Ok(local_search::render_search_results(Some(query), &matches, limit_reached).into())
+}
+
+/// Handle `/api/local/symbols` - Find the definitions and references of a
+/// symbol in the repository.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+///
+/// The symbol comes from the `name` query param on `GET`, or from the form
+/// body on `POST`.
+async fn symbols_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    backend: Arc<B>,
+    symbol_indexes: Arc<SymbolIndexes>,
+) -> Result<Container, LocalRouteError> {
+    let form = match req.method {
+        Method::Get => SymbolForm {
+            name: req
+                .query
+                .get("name")
+                .ok_or_else(|| LocalRouteError::MissingParameter("name".to_string()))?
+                .clone(),
+        },
+        Method::Post => req.parse_form()?,
+        _ => return Err(LocalRouteError::UnsupportedMethod),
+    };
+    let name = form.name.trim();
+
+    let repo_path = get_repo_path(&req)?;
+    let lookup = run_blocking({
+        let name = name.to_string();
+        move || {
+            LocalDiffProvider::from_path(backend, &repo_path)
+                .and_then(|provider| {
+                    provider.lookup_symbol(&symbol_indexes, &name, SYMBOL_REFERENCE_LIMIT)
+                })
+                .map_err(|e| LocalRouteError::ProviderError(e.to_string()))
+        }
+    })
+    .await?;
+
+    Ok(local_symbols::render_symbol_results(Some(name), &lookup).into())
+}
+
+/// Handle GET/POST `/api/local/comments/search` - Filter comment threads.
+///
+/// Query parameters:
+/// - `repo` - Repository path
+///
+/// Form body (POST):
+/// - `q` - Text to find in the threads' comments
+/// - `path` - Part of the file path
+/// - `author` - Part of a participant's name
+/// - `state`/`set_state` - "open", "resolved" or "saved_for_later"
+/// - `ai`/`set_ai` - "yes" or "no" for threads with or without an AI action
+///
+/// Returns the filter panel with the matching threads. `GET` clears the
+/// filter.
+async fn thread_search_route<B: GitBackend + 'static>(
+    req: RouteRequest,
+    _backend: Arc<B>,
+) -> Result<Container, LocalRouteError> {
+    let form: ThreadFilterForm = match req.method {
+        Method::Get => ThreadFilterForm::default(),
+        Method::Post => req.parse_form()?,
+        _ => return Err(LocalRouteError::UnsupportedMethod),
+    };
+    let filter = form.into_filter();
+
+    let repo_path = get_repo_path(&req)?;
+    let store = LocalCommentStore::new(&repo_path)?;
+    let threads = if filter.is_empty() {
+        None
+    } else {
+        Some(chadreview_local_comment::filter_threads(&store, &filter)?)
+    };
+
+    let repo_path_str = repo_path.to_string_lossy().to_string();
+    Ok(
+        local_thread_filter::render_thread_filter(&repo_path_str, &filter, threads.as_deref())
+            .into(),
+    )
 }
 
-/// Handle POST/DELETE `/api/local/file/view` - Mark file as viewed/unviewed.
+/// Handle POST `/api/local/export` - Export the review as markdown.
+///
+/// Compiles every comment thread into one markdown document, with code
+/// snippets taken from the diff, and fires the `review-exported` hook.
 ///
 /// Query parameters:
 /// - `repo` - Repository path
-/// - `path` - File path to mark
-///
-/// POST: Mark as viewed, returns collapsed file header
-/// DELETE: Mark as unviewed, returns expanded file with content
-async fn file_view_route<B: GitBackend + 'static>(
+/// - Diff spec parameters (same as `/local`), used for the title and snippets
+async fn export_route<B: GitBackend + 'static>(
     req: RouteRequest,
     backend: Arc<B>,
 ) -> Result<Container, LocalRouteError> {
-    let repo_path = get_repo_path(&req)?;
-    let file_path = req
-        .query
-        .get("path")
-        .ok_or_else(|| LocalRouteError::MissingParameter("path".to_string()))?
-        .clone();
-
-    let store = LocalCommentStore::new(&repo_path)?;
-
-    match req.method {
-        Method::Post => {
-            // Mark as viewed -> return collapsed header
-            store.mark_file_viewed(&file_path)?;
-            log::info!("Marked file as viewed: {file_path}");
+    // POST only, as every export fires the `review-exported` hooks
+    if !matches!(req.method, Method::Post) {
+        return Err(LocalRouteError::UnsupportedMethod);
+    }
 
-            // We need the diff file info to render the header
-            // Load the diff to find this file
-            let provider = LocalDiffProvider::from_path(Arc::clone(&backend), &repo_path)
+    let repo_path = get_repo_path(&req)?;
+    let spec = parse_diff_spec(&req)?;
+    let (info, diffs, threads) = run_blocking({
+        let repo_path = repo_path.clone();
+        move || {
+            let provider = LocalDiffProvider::from_path(backend, &repo_path)
                 .map_err(|e| LocalRouteError::ProviderError(e.to_string()))?;
-            let spec = parse_diff_spec(&req)?;
+            let info = provider.get_diff_info(&spec)?;
             let diffs = provider.get_diff(&spec)?;
 
-            let diff_file = diffs
-                .iter()
-                .find(|f| f.filename == file_path)
-                .ok_or_else(|| {
-                    LocalRouteError::MissingParameter(format!("File not found: {file_path}"))
-                })?;
-
-            let repo_path_str = repo_path.to_string_lossy().to_string();
-            Ok(diff_viewer::render_file_collapsed(diff_file, &repo_path_str).into())
+            let store = LocalCommentStore::new(&repo_path)?;
+            let threads = load_threads(&store)?;
+            Ok((info, diffs, threads))
         }
-        Method::Delete => {
-            // Mark as unviewed -> return expanded file with content
-            store.mark_file_unviewed(&file_path)?;
-            log::info!("Marked file as unviewed: {file_path}");
-
-            // Load diff and comments to render the full file
-            let provider = LocalDiffProvider::from_path(Arc::clone(&backend), &repo_path)
-                .map_err(|e| LocalRouteError::ProviderError(e.to_string()))?;
-            let spec = parse_diff_spec(&req)?;
-            let diffs = provider.get_diff(&spec)?;
-
-            let diff_file = diffs
-                .iter()
-                .find(|f| f.filename == file_path)
-                .ok_or_else(|| {
-                    LocalRouteError::MissingParameter(format!("File not found: {file_path}"))
-                })?;
+    })
+    .await?;
 
-            // Load comments
-            let thread_indices = store.list_threads()?;
-            let mut comments = Vec::new();
-            for idx in &thread_indices {
-                if let Ok(thread) = store.load_thread(idx.id) {
-                    comments.push(thread);
-                }
-            }
+    let markdown = chadreview_local_comment::export_markdown(&info.description, &threads, &diffs);
+    let summary = chadreview_local_comment::export_summary(&threads);
 
-            // Load viewed reply IDs
-            let viewed_reply_ids = store.get_viewed_reply_ids()?;
+    chadreview_hooks::dispatch(
+        &repo_path,
+        HookEvent::ReviewExported,
+        &serde_json::json!({
+            "description": info.description,
+            "summary": summary,
+            "thread_count": threads.len(),
+            "markdown": markdown,
+        }),
+    );
 
-            let repo_path_str = repo_path.to_string_lossy().to_string();
-            Ok(diff_viewer::render_file_expanded(
-                diff_file,
-                &comments,
-                &repo_path_str,
-                false,
-                &viewed_reply_ids,
-            )
-            .into())
-        }
-        _ => Err(LocalRouteError::UnsupportedMethod),
-    }
+    Ok(local_export::render_export(&markdown, &summary).into())
 }
 
-/// Handle GET `/api/local/file/diff` - Get rendered diff for a file (lazy loading).
+/// Handle POST `/api/local/import` - Import a PR's comments as local threads.
+///
+/// Threads land on the same files and lines as on the PR. Importing a PR
+/// again only adds replies that are new since the last import.
 ///
 /// Query parameters:
 /// - `repo` - Repository path
-/// - `path` - File path to render
+/// - Diff spec parameters (same as `/local`), used to link back to the diff
 ///
-/// Returns the expanded file container (header + content).
-async fn file_diff_route<B: GitBackend + 'static>(
+/// Form body:
+/// - `pr` - The PR, as `owner/repo#123` or its URL
+async fn import_route(
     req: RouteRequest,
-    backend: Arc<B>,
+    provider: Arc<dyn GitProvider>,
 ) -> Result<Container, LocalRouteError> {
-    if !matches!(req.method, Method::Get) {
+    if !matches!(req.method, Method::Post) {
         return Err(LocalRouteError::UnsupportedMethod);
     }
 
+    let form: ImportForm = req.parse_form()?;
     let repo_path = get_repo_path(&req)?;
-    let file_path = req
-        .query
-        .get("path")
-        .ok_or_else(|| LocalRouteError::MissingParameter("path".to_string()))?
-        .clone();
-
-    let store = LocalCommentStore::new(&repo_path)?;
-
-    // Load diff
-    let provider = LocalDiffProvider::from_path(Arc::clone(&backend), &repo_path)
-        .map_err(|e| LocalRouteError::ProviderError(e.to_string()))?;
-    let spec = parse_diff_spec(&req)?;
-    let diffs = provider.get_diff(&spec)?;
+    let spec_query = parse_diff_spec(&req)?.to_query_string();
+    let repo_path_str = repo_path.to_string_lossy();
 
-    let diff_file = diffs
-        .iter()
-        .find(|f| f.filename == file_path)
-        .ok_or_else(|| LocalRouteError::MissingParameter(format!("File not found: {file_path}")))?;
+    let Some((owner, repo, number)) = parse_pr_reference(&form.pr) else {
+        return Ok(local_import::render_import_result(
+            &repo_path_str,
+            &spec_query,
+            Err("Enter the PR as owner/repo#123 or its URL."),
+        )
+        .into());
+    };
 
-    // Load comments
-    let thread_indices = store.list_threads()?;
-    let mut comments = Vec::new();
-    for idx in &thread_indices {
-        if let Ok(thread) = store.load_thread(idx.id) {
-            comments.push(thread);
+    let result = match provider.get_comments(&owner, &repo, number).await {
+        Ok(comments) => {
+            let store = LocalCommentStore::new(&repo_path)?;
+            let summary = chadreview_local_comment::import_pr_comments(&store, &comments)?;
+            Ok(format!("Imported {owner}/{repo}#{number}: {summary}."))
         }
-    }
-
-    // Check if file is viewed (for the checkbox state)
-    let is_viewed = store.is_file_viewed(&file_path);
-
-    // Load viewed reply IDs
-    let viewed_reply_ids = store.get_viewed_reply_ids()?;
+        Err(e) => Err(format!(
+            "Failed to fetch comments for {owner}/{repo}#{number}: {e}"
+        )),
+    };
 
-    let repo_path_str = repo_path.to_string_lossy().to_string();
-    Ok(diff_viewer::render_file_expanded(
-        diff_file,
-        &comments,
+    Ok(local_import::render_import_result(
         &repo_path_str,
-        is_viewed,
-        &viewed_reply_ids,
+        &spec_query,
+        result.as_deref().map_err(String::as_str),
     )
     .into())
 }
 
 // Helper functions
 
+/// The repository of a request: its `repo` param, else the configured
+/// default repository, else the current directory.
+///
+/// The path is canonicalized when it exists, so a repository is watched and
+/// remembered once however its path was written.
 fn get_repo_path(req: &RouteRequest) -> Result<PathBuf, LocalRouteError> {
-    if let Some(path) = req.query.get("repo") {
-        Ok(PathBuf::from(path))
+    let path = if let Some(path) = req.query.get("repo") {
+        PathBuf::from(path)
+    } else if let Some(path) = settings::load_config(None).repos.default {
+        path
     } else {
-        std::env::current_dir().map_err(|e| LocalRouteError::InvalidRepoPath(e.to_string()))
-    }
+        std::env::current_dir().map_err(|e| LocalRouteError::InvalidRepoPath(e.to_string()))?
+    };
+
+    Ok(path.canonicalize().unwrap_or(path))
 }
 
 fn parse_uuid_param(req: &RouteRequest, name: &str) -> Result<Uuid, LocalRouteError> {
@@ -752,15 +3065,25 @@ fn parse_ai_action_from_string(agent_str: Option<&str>) -> Option<AiAction> {
     })
 }
 
-/// Execute AI action for a comment using OpenCode provider.
+/// Execute AI action for a comment using the installed provider it names.
 ///
 /// # Arguments
 /// * `repo_path` - Path to the repository
 /// * `thread_id` - The root thread ID (for loading/saving)
 /// * `comment_id` - The comment with the AI action (same as thread_id for root comments)
-#[cfg(feature = "ai-integration-opencode")]
-async fn execute_ai_action(repo_path: PathBuf, thread_id: Uuid, comment_id: Uuid) {
-    log::info!("Starting AI execution for comment {comment_id} (OpenCode)");
+/// * `review_diffs` - The diff to review, for a whole-diff review whose
+///   findings are added as threads of their own
+/// * `diff_provider` - Provider of the repository's diffs, to find the
+///   changes made by agents that can edit files
+#[cfg(feature = "_ai-integration")]
+async fn execute_ai_action<B: GitBackend>(
+    repo_path: PathBuf,
+    thread_id: Uuid,
+    comment_id: Uuid,
+    review_diffs: Option<Vec<DiffFile>>,
+    diff_provider: LocalDiffProvider<B>,
+) {
+    log::info!("Starting AI execution for comment {comment_id}");
 
     // Load the comment and its AI action
     let store = match LocalCommentStore::new(&repo_path) {
@@ -802,8 +3125,47 @@ async fn execute_ai_action(repo_path: PathBuf, thread_id: Uuid, comment_id: Uuid
         }
     };
 
+    let provider = match crate::ai::providers().get(&ai_action.provider) {
+        Ok(provider) => provider,
+        Err(e) => {
+            log::error!("AI execution failed for comment {comment_id}: {e}");
+            let failed_status = AiExecutionStatus::Failed {
+                finished_at: Utc::now(),
+                error: format!("{e}"),
+            };
+            store
+                .update_reply_ai_status(thread_id, comment_id, failed_status.clone())
+                .inspect_err(|e| log::warn!("Failed to update AI status to failed: {e}"))
+                .ok();
+            push_ai_status_update(comment_id, &failed_status).await;
+            return;
+        }
+    };
+
+    if let Some((spent, budget)) = crate::ai::exceeded_budget(&repo_path, &store) {
+        log::warn!("AI budget reached, not running the AI action of comment {comment_id}");
+        let over_budget_status = AiExecutionStatus::OverBudget {
+            blocked_at: Utc::now(),
+            spent,
+            budget,
+        };
+        store
+            .update_reply_ai_status(thread_id, comment_id, over_budget_status.clone())
+            .inspect_err(|e| log::warn!("Failed to update AI status to over budget: {e}"))
+            .ok();
+        push_ai_status_update(comment_id, &over_budget_status).await;
+        return;
+    }
+
+    let snapshot =
+        snapshot_before_ai(provider.as_ref(), &ai_action, &repo_path, &diff_provider).await;
+
     if session_id.is_some() {
-        log::info!("Continuing OpenCode session: {:?}", session_id);
+        log::info!(
+            "Continuing {} session: {:?}",
+            ai_action.provider,
+            session_id
+        );
     }
 
     // Update status to Running
@@ -819,11 +3181,21 @@ async fn execute_ai_action(repo_path: PathBuf, thread_id: Uuid, comment_id: Uuid
     }
     push_ai_status_update(comment_id, &running_status).await;
 
-    // Build AI context from comment
-    let context = build_ai_context(&repo_path, &comment);
+    // Build AI context from comment, or from the whole diff for a review
+    let context = match &review_diffs {
+        Some(diffs) => AiContext::new(
+            repo_path.clone(),
+            "local diff".to_string(),
+            chadreview_local_comment::REVIEW_REQUEST.to_string(),
+        )
+        .with_diff(chadreview_local_comment::format_review_diff(diffs)),
+        None => build_ai_context(&repo_path, &thread, &comment),
+    };
+    let context = crate::ai::with_prompt_template(context, &ai_action);
 
     // Create progress channel
-    let (progress_tx, mut progress_rx) = switchy::unsync::sync::mpsc::unbounded::<ProgressEntry>();
+    let (progress_tx, mut progress_rx) = switchy::unsync::sync::mpsc::unbounded::<AiProgress>();
+    let ai_author = LocalUser::ai(&ai_action.agent);
 
     // Spawn task to forward progress updates to SSE
     let store_for_progress = match LocalCommentStore::new(&repo_path) {
@@ -835,9 +3207,17 @@ async fn execute_ai_action(repo_path: PathBuf, thread_id: Uuid, comment_id: Uuid
     };
     let progress_thread_id = thread_id;
     let progress_comment_id = comment_id;
-    switchy::unsync::task::spawn(async move {
+    let progress_author = ai_author.name.clone();
+    let progress_handle = switchy::unsync::task::spawn(async move {
         let mut progress_entries = Vec::new();
-        while let Ok(entry) = progress_rx.recv_async().await {
+        while let Ok(progress) = progress_rx.recv_async().await {
+            let entry = match progress {
+                AiProgress::Step(entry) => entry,
+                AiProgress::Text(text) => {
+                    push_partial_reply(progress_thread_id, Some((&progress_author, &text))).await;
+                    continue;
+                }
+            };
             progress_entries.push(entry);
             let status = AiExecutionStatus::Running {
                 started_at,
@@ -857,11 +3237,22 @@ async fn execute_ai_action(repo_path: PathBuf, thread_id: Uuid, comment_id: Uuid
         }
     });
 
-    // Execute via OpenCode provider
-    let provider = OpenCodeProvider::new();
+    let abort = crate::ai::start_execution(comment_id);
     let result = provider
-        .execute(&context, &ai_action, session_id.as_deref(), progress_tx)
+        .execute(
+            &context,
+            &ai_action,
+            session_id.as_deref(),
+            progress_tx,
+            abort,
+        )
         .await;
+    crate::ai::finish_execution(comment_id);
+
+    // Let the last progress updates land before the final ones
+    if let Err(e) = progress_handle.await {
+        log::warn!("AI progress task failed for comment {comment_id}: {e}");
+    }
 
     let finished_at = Utc::now();
 
@@ -875,21 +3266,34 @@ async fn execute_ai_action(repo_path: PathBuf, thread_id: Uuid, comment_id: Uuid
                     log::debug!("Updated thread {thread_id} with session ID: {new_session_id}");
                 }
             }
+            if let Some(details) = &response.execution_details {
+                crate::ai::record_usage(&store, Some(comment_id), details);
+            }
 
-            // Create reply with AI response
-            let ai_author = LocalUser {
-                name: format!("AI ({})", ai_action.agent),
-                email: "ai@local".to_string(),
+            // Create reply with AI response, or with the review's summary
+            // once its findings are added as threads
+            let content = match &review_diffs {
+                Some(diffs) => add_review_threads(
+                    &repo_path,
+                    &store,
+                    &comment,
+                    &ai_author,
+                    diffs,
+                    &response.content,
+                ),
+                None => response.content.clone(),
             };
-
-            let response_comment = LocalComment::new(
+            let mut response_comment = LocalComment::new(
                 ai_author,
-                response.content.clone(),
+                content,
                 LocalCommentType::Reply {
                     root_comment_id: thread_id,
                     in_reply_to: comment_id,
                 },
             );
+            if let Some(changes) = snapshot.and_then(|before| ai_changes(&diff_provider, &before)) {
+                response_comment = response_comment.with_ai_changes(changes);
+            }
 
             if let Err(e) = store.add_reply(thread_id, response_comment.clone()) {
                 log::error!("Failed to add AI response: {e}");
@@ -901,11 +3305,13 @@ async fn execute_ai_action(repo_path: PathBuf, thread_id: Uuid, comment_id: Uuid
                     .update_reply_ai_status(thread_id, comment_id, failed_status.clone())
                     .inspect_err(|e| log::warn!("Failed to update AI status to failed: {e}"))
                     .ok();
+                push_partial_reply(thread_id, None).await;
                 push_ai_status_update(comment_id, &failed_status).await;
                 return;
             }
 
-            // Reload the thread to get all replies including the new one
+            // Reload the thread to get all replies including the new one,
+            // which also empties the partial reply
             let repo_path_str = repo_path.to_string_lossy().to_string();
             if let Ok(updated_thread) = store.get_comment(thread_id) {
                 let viewed_reply_ids = store.get_viewed_reply_ids().unwrap_or_default();
@@ -929,6 +3335,8 @@ async fn execute_ai_action(repo_path: PathBuf, thread_id: Uuid, comment_id: Uuid
                 .inspect_err(|e| log::error!("Failed to update AI status to completed: {e}"))
                 .ok();
             push_ai_status_update(comment_id, &completed_status).await;
+            dispatch_ai_completed_hook(&repo_path, thread_id, comment_id, &completed_status);
+            dispatch_ai_blocker_hook(&repo_path, thread_id, comment_id, &response_comment);
             log::info!("AI execution completed for comment {comment_id}");
         }
         Err(e) => {
@@ -941,22 +3349,272 @@ async fn execute_ai_action(repo_path: PathBuf, thread_id: Uuid, comment_id: Uuid
                 .update_reply_ai_status(thread_id, comment_id, failed_status.clone())
                 .inspect_err(|e| log::warn!("Failed to update AI status to failed: {e}"))
                 .ok();
+            push_partial_reply(thread_id, None).await;
             push_ai_status_update(comment_id, &failed_status).await;
+            dispatch_ai_completed_hook(&repo_path, thread_id, comment_id, &failed_status);
+        }
+    }
+}
+
+/// Snapshot the working tree before `ai_action` runs, if its agent can edit
+/// files, to show the changes it made with [`ai_changes`].
+///
+/// Agents that can't be listed or snapshots that fail are logged and get no
+/// snapshot.
+#[cfg(feature = "_ai-integration")]
+async fn snapshot_before_ai<B: GitBackend>(
+    provider: &dyn chadreview_ai_provider::AiProvider,
+    ai_action: &AiAction,
+    repo_path: &Path,
+    diff_provider: &LocalDiffProvider<B>,
+) -> Option<chadreview_git_backend::DiffResult> {
+    let agent_id = format!("{}:{}", ai_action.provider, ai_action.agent);
+    let can_write = provider
+        .list_agents(repo_path)
+        .await
+        .inspect_err(|e| log::warn!("Failed to list {} agents: {e}", ai_action.provider))
+        .ok()?
+        .iter()
+        .any(|agent| agent.id == agent_id && agent.capabilities.can_write);
+    if !can_write {
+        return None;
+    }
+
+    diff_provider
+        .snapshot_working_tree()
+        .inspect_err(|e| log::warn!("Failed to snapshot the working tree: {e}"))
+        .ok()
+}
+
+/// The changes made to the working tree since the `before` snapshot, as a
+/// unified diff, or `None` if there are none.
+#[cfg(feature = "_ai-integration")]
+fn ai_changes<B: GitBackend>(
+    diff_provider: &LocalDiffProvider<B>,
+    before: &chadreview_git_backend::DiffResult,
+) -> Option<String> {
+    let after = diff_provider
+        .snapshot_working_tree()
+        .inspect_err(|e| log::warn!("Failed to snapshot the working tree: {e}"))
+        .ok()?;
+
+    chadreview_local_diff::working_tree_changes(before, &after)
+}
+
+/// Add a thread for each finding of the AI review `response` on `diffs`,
+/// requested by `comment`, and return the review's summary.
+#[cfg(feature = "_ai-integration")]
+fn add_review_threads(
+    repo_path: &Path,
+    store: &LocalCommentStore,
+    comment: &LocalComment,
+    author: &LocalUser,
+    diffs: &[DiffFile],
+    response: &str,
+) -> String {
+    let review = chadreview_local_comment::parse_review_response(response);
+    let mut added = 0;
+
+    for mut thread in chadreview_local_comment::finding_threads(&review.findings, diffs, author) {
+        thread.diff_scope.clone_from(&comment.diff_scope);
+        match store.save_thread(&thread) {
+            Ok(()) => {
+                chadreview_hooks::dispatch(repo_path, HookEvent::ThreadCreated, &thread);
+                added += 1;
+            }
+            Err(e) => log::error!("Failed to add review finding: {e}"),
+        }
+    }
+    log::info!("AI review of comment {} added {added} threads", comment.id);
+
+    let outcome = match added {
+        0 => "No findings to comment on.".to_string(),
+        1 => "Added 1 comment to the diff.".to_string(),
+        n => format!("Added {n} comments to the diff."),
+    };
+    if review.summary.is_empty() {
+        outcome
+    } else {
+        format!("{}\n\n{outcome}", review.summary)
+    }
+}
+
+/// Summarize each batch of files with `ai_action`, caching each file's
+/// summary and pushing it above the file.
+///
+/// Batches the agent fails on are logged and skipped.
+#[cfg(feature = "_ai-integration")]
+async fn summarize_files(repo_path: PathBuf, ai_action: AiAction, batches: Vec<Vec<DiffFile>>) {
+    let provider = match crate::ai::providers().get(&ai_action.provider) {
+        Ok(provider) => provider,
+        Err(e) => {
+            log::error!("Failed to summarize files: {e}");
+            push_summary_status(&format!("Failed to summarize files: {e}")).await;
+            return;
+        }
+    };
+    let store = match LocalCommentStore::new(&repo_path) {
+        Ok(s) => s,
+        Err(e) => {
+            log::error!("Failed to open comment store: {e}");
+            return;
+        }
+    };
+
+    let total: usize = batches.iter().map(Vec::len).sum();
+    let mut summarized = 0;
+
+    for batch in batches {
+        if let Some((spent, budget)) = crate::ai::exceeded_budget(&repo_path, &store) {
+            log::warn!("AI budget reached, not summarizing the remaining files");
+            push_summary_status(&format!(
+                "Summarized {summarized} of {total} files before reaching the AI budget of ${budget:.2} (${spent:.2} spent)."
+            ))
+            .await;
+            return;
+        }
+
+        let context = AiContext::new(
+            repo_path.clone(),
+            "local diff".to_string(),
+            chadreview_local_comment::SUMMARY_REQUEST.to_string(),
+        )
+        .with_diff(chadreview_local_comment::format_review_diff(&batch));
+        let context = crate::ai::with_prompt_template(context, &ai_action);
+        let (progress_tx, _progress_rx) = switchy::unsync::sync::mpsc::unbounded::<AiProgress>();
+
+        let response = match provider
+            .execute(
+                &context,
+                &ai_action,
+                None,
+                progress_tx,
+                chadreview_ai_provider::AbortSignal::never(),
+            )
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                log::error!("Failed to summarize {} files: {e}", batch.len());
+                continue;
+            }
+        };
+        if let Some(details) = &response.execution_details {
+            crate::ai::record_usage(&store, None, details);
+        }
+
+        let summaries = chadreview_local_comment::parse_file_summaries(&response.content);
+        for file in &batch {
+            let Some(summary) = summaries.get(&file.filename) else {
+                log::warn!("No summary of {} in the AI response", file.filename);
+                continue;
+            };
+            chadreview_local_comment::save_file_summary(&store, file, summary)
+                .inspect_err(|e| log::warn!("Failed to cache summary of {}: {e}", file.filename))
+                .ok();
+            push_file_summary(&file.filename, summary).await;
+            summarized += 1;
         }
+        push_summary_status(&format!("Summarized {summarized} of {total} files...")).await;
+    }
+
+    log::info!(
+        "Summarized {summarized} of {total} files with {}",
+        ai_action.agent
+    );
+    push_summary_status(&format!("Summarized {summarized} of {total} files.")).await;
+}
+
+/// Summarizing files needs AI integration.
+#[cfg(not(feature = "_ai-integration"))]
+async fn summarize_files(_repo_path: PathBuf, ai_action: AiAction, _batches: Vec<Vec<DiffFile>>) {
+    log::warn!(
+        "AI integration not enabled. Cannot summarize files with {}:{}",
+        ai_action.provider,
+        ai_action.agent
+    );
+    push_summary_status("AI integration is not enabled in this build.").await;
+}
+
+/// Ask `ai_action` for a description of `kind` of `diffs`.
+#[cfg(feature = "_ai-integration")]
+async fn describe_diff(
+    repo_path: &Path,
+    ai_action: &AiAction,
+    kind: DescriptionKind,
+    diffs: &[DiffFile],
+) -> Result<String, String> {
+    let provider = crate::ai::providers()
+        .get(&ai_action.provider)
+        .map_err(|e| e.to_string())?;
+    let store = LocalCommentStore::new(repo_path).map_err(|e| e.to_string())?;
+    if let Some((spent, budget)) = crate::ai::exceeded_budget(repo_path, &store) {
+        return Err(format!(
+            "AI budget of ${budget:.2} reached (${spent:.2} spent). Override the budget in the header to run AI actions."
+        ));
     }
+
+    let context = AiContext::new(
+        repo_path.to_path_buf(),
+        "local diff".to_string(),
+        chadreview_local_comment::description_request(kind).to_string(),
+    )
+    .with_diff(chadreview_local_comment::format_review_diff(diffs));
+    let context = crate::ai::with_prompt_template(context, ai_action);
+    let (progress_tx, _progress_rx) = switchy::unsync::sync::mpsc::unbounded::<AiProgress>();
+
+    provider
+        .execute(
+            &context,
+            ai_action,
+            None,
+            progress_tx,
+            chadreview_ai_provider::AbortSignal::never(),
+        )
+        .await
+        .map(|response| {
+            if let Some(details) = &response.execution_details {
+                crate::ai::record_usage(&store, None, details);
+            }
+            chadreview_local_comment::parse_description(&response.content)
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// Describing a diff needs AI integration.
+#[cfg(not(feature = "_ai-integration"))]
+#[allow(clippy::unused_async)]
+async fn describe_diff(
+    _repo_path: &Path,
+    _ai_action: &AiAction,
+    _kind: DescriptionKind,
+    _diffs: &[DiffFile],
+) -> Result<String, String> {
+    Err("AI integration is not enabled in this build.".to_string())
 }
 
-/// Build AI context from a comment.
-#[cfg(feature = "ai-integration-opencode")]
-fn build_ai_context(repo_path: &PathBuf, comment: &LocalComment) -> AiContext {
+/// Build AI context from a comment of `thread`, along with the messages
+/// posted before it, cut to fit the history token budget.
+#[cfg(feature = "_ai-integration")]
+fn build_ai_context(
+    repo_path: &PathBuf,
+    thread: &LocalComment,
+    comment: &LocalComment,
+) -> AiContext {
+    let history = chadreview_ai_provider::thread_history(thread, comment);
     let mut context = AiContext::new(
         repo_path.clone(),
         "local diff".to_string(), // TODO: Get actual diff description
         comment.body.clone(),
-    );
-
-    // Add file/line context if available
-    match &comment.comment_type {
+    )
+    .with_thread_history(chadreview_ai_provider::truncate_thread_history(
+        history,
+        chadreview_ai_provider::DEFAULT_HISTORY_TOKENS,
+    ));
+
+    // Add file/line context if available, which replies share with their
+    // thread's root comment
+    match &thread.comment_type {
         LocalCommentType::FileLevelComment { path } => {
             context = context.with_file_path(path.clone());
         }
@@ -970,14 +3628,24 @@ fn build_ai_context(repo_path: &PathBuf, comment: &LocalComment) -> AiContext {
     context
 }
 
-/// Execute AI action for a comment (simulation fallback when OpenCode not available).
+/// Execute AI action for a comment (simulation fallback when AI integration
+/// is not enabled).
 ///
 /// # Arguments
 /// * `repo_path` - Path to the repository
 /// * `thread_id` - The root thread ID (for loading/saving)
 /// * `comment_id` - The comment with the AI action (same as thread_id for root comments)
-#[cfg(not(feature = "ai-integration-opencode"))]
-async fn execute_ai_action(repo_path: PathBuf, thread_id: Uuid, comment_id: Uuid) {
+/// * `_review_diffs` - The diff to review, ignored by the simulation
+/// * `_diff_provider` - Provider of the repository's diffs, unused since
+///   simulated actions don't edit files
+#[cfg(not(feature = "_ai-integration"))]
+async fn execute_ai_action<B: GitBackend>(
+    repo_path: PathBuf,
+    thread_id: Uuid,
+    comment_id: Uuid,
+    _review_diffs: Option<Vec<DiffFile>>,
+    _diff_provider: LocalDiffProvider<B>,
+) {
     log::info!("Starting AI execution for comment {comment_id} (simulated)");
 
     // Load the comment and its AI action
@@ -1032,7 +3700,7 @@ async fn execute_ai_action(repo_path: PathBuf, thread_id: Uuid, comment_id: Uuid
         let progress = AiExecutionStatus::Running {
             started_at,
             progress: (1..=i)
-                .map(|n| ProgressEntry {
+                .map(|n| chadreview_local_comment_models::ProgressEntry {
                     tool: format!("tool_{n}"),
                     title: format!("Simulated step {n}"),
                     timestamp: Utc::now(),
@@ -1063,10 +3731,7 @@ async fn execute_ai_action(repo_path: PathBuf, thread_id: Uuid, comment_id: Uuid
         ai_action.provider, ai_action.agent, duration
     );
 
-    let ai_author = LocalUser {
-        name: format!("AI ({})", ai_action.agent),
-        email: "ai@local".to_string(),
-    };
+    let ai_author = LocalUser::ai(&ai_action.agent);
 
     let response_comment = LocalComment::new(
         ai_author,
@@ -1124,28 +3789,156 @@ async fn execute_ai_action(repo_path: PathBuf, thread_id: Uuid, comment_id: Uuid
         .inspect_err(|e| log::error!("Failed to update AI status to completed: {e}"))
         .ok();
     push_ai_status_update(comment_id, &completed_status).await;
+    dispatch_ai_completed_hook(&repo_path, thread_id, comment_id, &completed_status);
+    dispatch_ai_blocker_hook(&repo_path, thread_id, comment_id, &response_comment);
     log::info!("AI execution completed for comment {comment_id} (simulated)");
 }
 
+/// Fire the `ai-completed` hook for a finished AI execution.
+fn dispatch_ai_completed_hook(
+    repo_path: &Path,
+    thread_id: Uuid,
+    comment_id: Uuid,
+    status: &AiExecutionStatus,
+) {
+    chadreview_hooks::dispatch(
+        repo_path,
+        HookEvent::AiCompleted,
+        &serde_json::json!({
+            "thread_id": thread_id,
+            "comment_id": comment_id,
+            "status": status,
+        }),
+    );
+}
+
+/// Fire the `ai-blocker-found` hook if an AI response flags blocker-severity issues.
+fn dispatch_ai_blocker_hook(
+    repo_path: &Path,
+    thread_id: Uuid,
+    comment_id: Uuid,
+    response: &LocalComment,
+) {
+    let findings = blocker_findings(&response.body);
+    if findings.is_empty() {
+        return;
+    }
+
+    chadreview_hooks::dispatch(
+        repo_path,
+        HookEvent::AiBlockerFound,
+        &serde_json::json!({
+            "thread_id": thread_id,
+            "comment_id": comment_id,
+            "response_comment_id": response.id,
+            "findings": findings,
+            "excerpt": findings.join("\n"),
+        }),
+    );
+}
+
+/// Lines of an AI response that mark a finding as blocker severity.
+///
+/// Recognizes `[blocker]`, `**blocker**` and `severity: blocker` markers.
+fn blocker_findings(content: &str) -> Vec<&str> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            let lower = line.to_ascii_lowercase();
+            lower.contains("[blocker]")
+                || lower.contains("**blocker**")
+                || lower.contains("severity: blocker")
+        })
+        .collect()
+}
+
 /// Render the local diff view with comments.
+#[allow(clippy::too_many_arguments)]
 fn render_local_view(
-    info: &chadreview_local_diff_models::LocalDiffInfo,
-    diffs: &[chadreview_pr_models::DiffFile],
-    comments: &[LocalComment],
-    repo_path: &std::path::Path,
-    viewed_paths: &std::collections::HashSet<String>,
-    viewed_reply_ids: &std::collections::HashSet<Uuid>,
+    review: &LocalReview,
+    repo_path: &Path,
+    config: &Config,
+    checklist: &[String],
+    saved_replies: &SavedReplies,
+    file_order: FileOrder,
+    show_all: bool,
+    hidden: usize,
 ) -> Container {
+    let LocalReview {
+        info,
+        diffs,
+        more_files,
+        paths: _,
+        context_lines,
+        commit_diffs,
+        commit_filter,
+        commit_paths,
+        comments,
+        outdated,
+        viewed_paths,
+        viewed_reply_ids,
+        drafts,
+        summaries,
+        usage,
+        checked_items,
+        staging,
+        operation,
+        worktrees,
+    } = review;
     let repo_path_str = repo_path.to_string_lossy();
+    let mentions_url = autocomplete::local_mentions_url(&repo_path_str);
+    let generated = settings::load_generated_files(Some(repo_path), config);
+    let generated_paths: HashSet<String> = diffs
+        .iter()
+        .chain(commit_diffs.iter().flat_map(|section| &section.files))
+        .filter(|file| generated.is_generated(&file.filename))
+        .map(|file| file.filename.clone())
+        .collect();
 
     let general_comments: Vec<_> = comments
         .iter()
         .filter(|c| matches!(c.comment_type, LocalCommentType::General))
         .collect();
+    let outdated_comments: Vec<_> = comments
+        .iter()
+        .filter(|c| outdated.contains(&c.id))
+        .collect();
+    let commits: Vec<_> = commit_diffs.iter().map(|c| c.commit.clone()).collect();
+    let spec_query = view_query(&info.spec, show_all, *context_lines);
+    let saved_replies: Vec<_> = saved_replies
+        .replies
+        .iter()
+        .map(|reply| (reply.name.as_str(), reply.body.as_str()))
+        .collect();
 
     container! {
         div padding=20 gap=20 {
+            (settings::theme_stylesheet(config.appearance.theme))
+            (chadreview_markdown::markdown_stylesheet())
+            (permalink::render_permalink_script())
+            (copy::render_copy_script())
+            (local_drafts::render_draft_script(&repo_path_str, drafts))
+            (local_saved_replies::render_saved_replies_script(&saved_replies))
+            (local_attachments::render_attachment_script(&repo_path_str))
+            (markdown_preview::render_markdown_preview_script())
+            (autocomplete::render_autocomplete_script(&[('@', mentions_url.as_str()), (':', autocomplete::EMOJI_ROUTE)]))
+            (local_agents::render_agent_picker_script(&repo_path_str))
             (local_header::render_local_diff_header(info))
+            @if let Some((operation, conflicted)) = operation {
+                (local_conflicts::render_operation_banner(&repo_path_str, *operation, *conflicted))
+            }
+            (checklist::render_checklist(checklist, checked_items, &checklist::local_checklist_url(&repo_path_str, &info.spec.to_query_string())))
+            (local_header::render_ai_usage(&repo_path_str, usage, config.ai.budget, budget_overridden(repo_path)))
+            (local_describe::render_describe_form(&repo_path_str, &spec_query, config.ai.default_agent.as_deref()))
+            @if staging.is_some() {
+                (local_commit::render_commit_section(&repo_path_str, config.ai.default_agent.as_deref()))
+            }
+            (local_commits::render_mode_toggle(&repo_path_str, &info.spec))
+            (local_header::render_comment_scope_toggle(&repo_path_str, &info.spec.to_query_string(), show_all, hidden))
+            (local_header::render_file_order_menu(&repo_path_str, &spec_query, file_order))
+            (local_header::render_worktree_menu(worktrees, &spec_query))
+            (local_header::render_compare_form(&repo_path_str))
 
             // General comments section - always render container so hx-target works
             div id="general-comments-section" gap=12 {
@@ -1163,18 +3956,51 @@ fn render_local_view(
             }
 
             // Comment form for general comments
-            (render_comment_form(&repo_path_str))
+            (render_comment_form(&repo_path_str, &spec_query, config.ai.default_agent.as_deref()))
+            (render_review_form(&repo_path_str, &spec_query, config.ai.default_agent.as_deref()))
+            (local_summaries::render_summarize_form(&repo_path_str, &spec_query, config.ai.default_agent.as_deref()))
+
+            div direction=row gap=12 align-items=center {
+                (local_export::render_export_button(&repo_path_str, &info.spec.to_query_string()))
+                (local_import::render_import_form(&repo_path_str, &info.spec.to_query_string()))
+            }
+
+            (local_search::render_search_box(&repo_path_str, &info.spec.to_query_string()))
+            (local_symbols::render_symbol_box(&repo_path_str))
+            (local_thread_filter::render_thread_filter(&repo_path_str, &ThreadFilter::default(), None))
+
+            // Line comments whose line is no longer in the diff
+            (local_outdated::render_outdated_section(&outdated_comments, &repo_path_str, &spec_query, viewed_reply_ids))
 
             // Diff view with inline comments (file-level and line-level)
-            (diff_viewer::render_local(diffs, comments, &repo_path_str, viewed_paths, viewed_reply_ids))
+            @if !info.spec.is_by_commit() {
+                (diff_viewer::render_local(diffs, comments, &repo_path_str, &spec_query, viewed_paths, &generated_paths, viewed_reply_ids, summaries, staging.as_ref(), *more_files))
+            } @else {
+                (local_commits::render_commit_filters(&repo_path_str, &info.spec, commit_filter, &info.commits, commit_paths, commits.len()))
+                (local_commits::render_commit_nav(&commits))
+                @for (index, section) in commit_diffs.iter().enumerate() {
+                    div id=(local_commits::commit_section_id(&section.commit.sha)) gap=12 {
+                        (local_commits::render_commit_section_header(&commits, index))
+                        (diff_viewer::render_local(&section.files, comments, &repo_path_str, &spec_query, viewed_paths, &generated_paths, viewed_reply_ids, summaries, None, 0))
+                    }
+                }
+            }
         }
     }
     .into()
 }
 
-/// Render the comment creation form for general comments.
-fn render_comment_form(repo_path: &str) -> Container {
-    let api_url = format!("/api/local/comment?repo={}", urlencoding::encode(repo_path));
+/// Render the comment creation form for general comments on the diff of
+/// `spec_query`.
+fn render_comment_form(
+    repo_path: &str,
+    spec_query: &str,
+    default_agent: Option<&str>,
+) -> Container {
+    let api_url = format!(
+        "/api/local/comment?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
 
     container! {
         div
@@ -1188,6 +4014,7 @@ fn render_comment_form(repo_path: &str) -> Container {
                 "Add General Comment"
             }
             form
+                id=(local_drafts::GENERAL_COMMENT_FORM_ID)
                 hx-post=(api_url)
                 hx-swap="beforeend"
                 hx-target="#general-comments-section"
@@ -1205,7 +4032,7 @@ fn render_comment_form(repo_path: &str) -> Container {
                     font-size=14;
 
                 div margin-top=8 {
-                    (local_comments::render_ai_action_selector("ai_agent", local_comments::DEFAULT_AGENTS))
+                    (local_comments::render_ai_action_selector("ai_agent", default_agent))
                 }
 
                 button
@@ -1227,3 +4054,46 @@ fn render_comment_form(repo_path: &str) -> Container {
     }
     .into()
 }
+
+/// Render the form starting an AI review of the whole diff of `spec_query`,
+/// whose findings become comment threads.
+fn render_review_form(repo_path: &str, spec_query: &str, default_agent: Option<&str>) -> Container {
+    let api_url = format!(
+        "/api/local/ai/review?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
+
+    container! {
+        form
+            id="ai-review-form"
+            hx-post=(api_url)
+            hx-swap="beforeend"
+            hx-target="#general-comments-section"
+            direction=row
+            align-items=center
+            gap=12
+            padding=16
+            background="#f6f8fa"
+            border="1px solid #d0d7de"
+            border-radius=6
+        {
+            div flex=1 {
+                (local_comments::render_ai_action_selector("ai_agent", default_agent))
+            }
+            button
+                type=submit
+                background="#0969da"
+                color="#ffffff"
+                padding-x=16
+                padding-y=8
+                border-radius=6
+                font-weight=600
+                font-size=14
+                cursor=pointer
+            {
+                "Review this diff"
+            }
+        }
+    }
+    .into()
+}
@@ -4,10 +4,32 @@
 
 use std::sync::Arc;
 
+use chadreview_app::auth;
+use chadreview_app::maintenance::{Maintenance, MaintenanceConfig};
 use chadreview_app::routes;
+use chadreview_config::Config;
 use chadreview_github::GitHubProvider;
+use hyperchad::router::Router;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = <chadreview_app::cli::Cli as clap::Parser>::parse();
+    if let Some(command) = cli.command {
+        // Logging stays uninitialized for the terminal UI so log output
+        // doesn't draw over it
+        #[cfg(feature = "tui")]
+        let terminal_ui = matches!(command, chadreview_app::cli::Command::Tui(_));
+        #[cfg(not(feature = "tui"))]
+        let terminal_ui = false;
+        if !terminal_ui {
+            moosicbox_logging::init(None, None).expect("Failed to initialize logging");
+        }
+        #[cfg(feature = "local-git")]
+        command.run(Arc::new(chadreview_git_backend_git2::Git2Backend))?;
+        #[cfg(not(feature = "local-git"))]
+        command.run()?;
+        return Ok(());
+    }
+
     moosicbox_logging::init(None, None).expect("Failed to initialize logging");
 
     println!("ChadReview - GitHub PR Review Tool");
@@ -18,47 +40,192 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap_or(3000);
     let host = std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
 
-    let mut github_provider = GitHubProvider::new();
-    if let Ok(token) = std::env::var("GITHUB_TOKEN") {
-        github_provider = github_provider.with_token(token);
+    #[cfg(feature = "local-git")]
+    if let Some(config) = chadreview_app::share_routes::ShareConfig::from_env()? {
+        let git_backend = Arc::new(chadreview_git_backend_git2::Git2Backend);
+        let share_path = config.share_path();
+        println!(
+            "Read-only share mode: serving {} ({})",
+            config.repo_path.display(),
+            config.spec.description()
+        );
+        let router = chadreview_app::share_routes::create_share_router(git_backend, config);
+        println!("Share link: http://{host}:{port}{share_path}");
+        if host == "127.0.0.1" {
+            println!("Set HOST=0.0.0.0 to make the share link reachable from your network.");
+        }
+        return run_server(router, &host, port, None);
     }
 
-    let provider: Arc<dyn chadreview_git_provider::GitProvider> = Arc::new(github_provider);
+    let config = chadreview_app::settings::load_config(None);
+    let (provider, github_provider) = create_provider(&config);
+    set_markdown_base_urls(&config);
 
-    // Get relay URL if provided (actual connection will happen when first PR is viewed)
-    let relay_url = std::env::var("RELAY_URL").ok();
+    // Relay connection will happen when the first PR is viewed
+    let relay_url = config.relay.url.clone();
 
     if relay_url.is_some() {
         println!(
-            "RELAY_URL configured. Real-time webhook updates will be enabled when viewing PRs."
+            "Relay URL configured. Real-time webhook updates will be enabled when viewing PRs."
         );
     } else {
-        println!("RELAY_URL not set. Real-time updates will be disabled.");
-        println!("Set RELAY_URL environment variable to enable real-time webhook updates.");
+        println!("No relay URL configured. Real-time updates will be disabled.");
+        println!(
+            "Set RELAY_URL or the relay URL in /settings to enable real-time webhook updates."
+        );
     }
 
-    #[allow(unused_mut)]
-    let mut router = routes::create_router(&provider, relay_url);
+    if config.relay.webhook_secret.is_some() {
+        println!(
+            "Direct webhooks enabled. Point GitHub webhooks at http://{host}:{port}/webhook (or your public address)."
+        );
+    }
+
+    #[cfg(feature = "_ai-integration")]
+    {
+        let providers = chadreview_app::ai::init_providers(&config);
+        if providers.is_empty() {
+            println!("No AI providers installed. AI actions on comments will fail.");
+        } else {
+            println!(
+                "AI providers installed: {}",
+                providers.provider_names().join(", ")
+            );
+        }
+    }
+
+    let maintenance = Maintenance::new(MaintenanceConfig::from_env(relay_url));
+
+    let mut router = routes::create_router(&provider, config.relay.clone());
+    router = chadreview_app::maintenance::add_maintenance_routes(router, Arc::clone(&maintenance));
+    router = chadreview_app::settings::add_settings_routes(router);
+    if let Some(github_provider) = github_provider {
+        router = auth::add_auth_routes(router, &auth::Auth::from_env(github_provider));
+    }
 
     // Add local git routes if the feature is enabled
     #[cfg(feature = "local-git")]
     {
         let git_backend = Arc::new(chadreview_git_backend_git2::Git2Backend);
-        router = chadreview_app::local_routes::add_local_routes(router, git_backend);
+        router = chadreview_app::local_routes::add_local_routes(
+            router,
+            git_backend,
+            Arc::clone(&provider),
+        );
+        router = chadreview_app::repos::add_repo_routes(router);
         println!("Local git diff support enabled.");
     }
 
     println!("Router created with routes:");
-    println!("  GET  /pr?owner=<owner>&repo=<repo>&number=<number>");
+    println!("  GET  /pr?owner=<owner>&repo=<repo>&number=<number>[&tab=commits|&commit=<sha>]");
     println!("  POST /api/pr/comment?owner=<owner>&repo=<repo>&number=<number>");
+    println!("  POST /api/pr/review?owner=<owner>&repo=<repo>&number=<number>");
+    println!("  POST /api/pr/review/{{start,discard}}?owner=<owner>&repo=<repo>&number=<number>");
+    println!(
+        "  POST|DELETE /api/pr/{{labels,assignees,reviewers}}?owner=<owner>&repo=<repo>&number=<number>"
+    );
+    println!(
+        "  POST|DELETE /api/pr/file/view?owner=<owner>&repo=<repo>&number=<number>&sha=<sha>&path=<path>"
+    );
+    println!(
+        "  GET /api/pr/file/diff?owner=<owner>&repo=<repo>&number=<number>&sha=<sha>&path=<path>"
+    );
+    println!(
+        "  GET|POST /api/pr/edit?owner=<owner>&repo=<repo>&number=<number>[&part=title|description&edit=true]"
+    );
+    println!("  POST /api/pr/merge?owner=<owner>&repo=<repo>&number=<number>");
+    println!("  POST /api/pr/checklist?owner=<owner>&repo=<repo>&number=<number>");
     println!("  PUT  /api/comment/update?id=<id>");
     println!("  DELETE /api/comment/delete?id=<id>");
+    println!("  POST /api/comment/reaction?owner=<owner>&repo=<repo>&id=<id>");
+    println!("  POST /api/markdown/preview");
+    println!("  GET  /api/mentions?owner=<owner>&repo=<repo>&number=<number>&q=<query>");
+    println!("  GET  /api/emoji?q=<query>");
+    println!("  GET  /debug/maintenance");
+    if config.relay.webhook_secret.is_some() {
+        println!("  POST /webhook");
+    }
+    println!("  GET  /login");
+    println!("  POST /logout");
+    println!("  GET|POST /settings");
     #[cfg(feature = "local-git")]
     println!("  GET  /local[?repo=<path>&base=<ref>&head=<ref>&...]");
+    #[cfg(feature = "local-git")]
+    println!("  GET|POST /repos (also at /)");
+    #[cfg(feature = "local-git")]
+    println!("\nRun with SHARE=1 to serve a read-only share link for a local review.");
+    println!("\nRun `chadreview --help` to open a PR or local diff in the browser.");
+    #[cfg(feature = "tui")]
+    println!("\nRun `chadreview tui --help` to review local changes in the terminal.");
+    #[cfg(feature = "sqlite")]
+    println!("\nRun `chadreview migrate-comments` to move local comments into SQLite.");
 
+    run_server(router, &host, port, Some(maintenance))
+}
+
+/// Pick the git hosting provider from the config.
+///
+/// Uses Gitea/Forgejo when a Gitea URL is configured (with the `gitea`
+/// feature), otherwise GitHub, authenticated with the configured token or
+/// the token saved by signing in at `/login`. The GitHub provider is also
+/// returned on its own so `/login` can swap its token.
+fn create_provider(
+    config: &Config,
+) -> (
+    Arc<dyn chadreview_git_provider::GitProvider>,
+    Option<Arc<GitHubProvider>>,
+) {
+    #[cfg(feature = "gitea")]
+    if let Some(base_url) = &config.providers.gitea_url {
+        println!("Using Gitea/Forgejo instance at {base_url}");
+        let mut gitea_provider = chadreview_gitea::GiteaProvider::new(base_url.clone());
+        if let Some(token) = &config.providers.gitea_token {
+            gitea_provider = gitea_provider.with_token(token.clone());
+        }
+        return (Arc::new(gitea_provider), None);
+    }
+
+    let mut github_provider = GitHubProvider::new();
+    if let Some(token) = config
+        .providers
+        .github_token
+        .clone()
+        .or_else(auth::token_store::load_token)
+    {
+        github_provider = github_provider.with_token(token);
+    }
+
+    let github_provider = Arc::new(github_provider);
+    (Arc::clone(&github_provider), Some(github_provider))
+}
+
+/// Link references in rendered markdown to the configured self-hosted
+/// instances.
+fn set_markdown_base_urls(config: &Config) {
+    use chadreview_app_ui::markdown::set_base_url;
+    use chadreview_markdown::ReferenceProvider;
+
+    if let Some(url) = &config.providers.github_url {
+        set_base_url(ReferenceProvider::GitHub, url.clone());
+    }
+    if let Some(url) = &config.providers.gitea_url {
+        set_base_url(ReferenceProvider::Gitea, url.clone());
+    }
+}
+
+fn run_server(
+    router: Router,
+    host: &str,
+    port: u16,
+    maintenance: Option<Arc<Maintenance>>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let runtime = switchy::unsync::runtime::Runtime::new();
     let handle = runtime.handle();
 
+    if let Some(maintenance) = maintenance {
+        handle.spawn(maintenance.run_forever());
+    }
+
     #[allow(unused_mut)]
     let mut builder = hyperchad::app::AppBuilder::new()
         .with_title("ChadReview - GitHub PR Review Tool".to_string())
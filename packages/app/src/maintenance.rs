@@ -0,0 +1,344 @@
+//! Background maintenance for long-running instances.
+//!
+//! A scheduler task periodically archives old resolved comment threads,
+//! fails AI executions that were interrupted, evicts the diff caches of
+//! repositories that are no longer viewed, and checks that the relay server
+//! is reachable. The outcome of each task's last run is shown at
+//! `/debug/maintenance`.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use hyperchad::{
+    router::{Container, RouteRequest, Router},
+    template::container,
+};
+use switchy::http::models::Method;
+
+#[cfg(all(feature = "local-git", feature = "_ai-integration"))]
+use crate::ai::is_running;
+
+/// How long to wait for the relay server during a health check.
+const RELAY_HEALTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Error type for maintenance routes.
+#[derive(Debug, thiserror::Error)]
+pub enum MaintenanceError {
+    /// The request method is not supported for this route.
+    #[error("Unsupported method")]
+    UnsupportedMethod,
+}
+
+/// Maintenance schedule and task settings.
+#[derive(Debug, Clone)]
+pub struct MaintenanceConfig {
+    /// Time between maintenance runs.
+    pub interval: Duration,
+    /// Archive resolved threads with no activity for this long (`None`
+    /// disables archival).
+    pub archive_resolved_after: Option<Duration>,
+    /// Fail AI executions pending or running for longer than this.
+    pub stale_ai_after: Duration,
+    /// Evict the diff caches of repositories not viewed for this long.
+    pub evict_caches_after: Duration,
+    /// Relay server to health check.
+    pub relay_url: Option<String>,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(24 * 60 * 60),
+            archive_resolved_after: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+            stale_ai_after: Duration::from_secs(60 * 60),
+            evict_caches_after: Duration::from_secs(24 * 60 * 60),
+            relay_url: None,
+        }
+    }
+}
+
+impl MaintenanceConfig {
+    /// Read maintenance settings from the environment.
+    ///
+    /// - `MAINTENANCE_INTERVAL_HOURS` - Time between runs (default: 24)
+    /// - `ARCHIVE_RESOLVED_AFTER_DAYS` - Archive resolved threads after this
+    ///   many days without activity, `0` to disable (default: 30)
+    /// - `STALE_AI_AFTER_MINUTES` - Fail AI executions running longer than
+    ///   this (default: 60)
+    /// - `EVICT_CACHES_AFTER_HOURS` - Evict the diff caches of repositories
+    ///   not viewed for this many hours (default: 24)
+    #[must_use]
+    pub fn from_env(relay_url: Option<String>) -> Self {
+        let defaults = Self::default();
+        let env_u64 = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+
+        Self {
+            interval: env_u64("MAINTENANCE_INTERVAL_HOURS")
+                .filter(|hours| *hours > 0)
+                .map_or(defaults.interval, |hours| {
+                    Duration::from_secs(hours * 60 * 60)
+                }),
+            archive_resolved_after: env_u64("ARCHIVE_RESOLVED_AFTER_DAYS")
+                .map_or(defaults.archive_resolved_after, |days| {
+                    (days > 0).then(|| Duration::from_secs(days * 24 * 60 * 60))
+                }),
+            stale_ai_after: env_u64("STALE_AI_AFTER_MINUTES")
+                .map_or(defaults.stale_ai_after, |minutes| {
+                    Duration::from_secs(minutes * 60)
+                }),
+            evict_caches_after: env_u64("EVICT_CACHES_AFTER_HOURS")
+                .map_or(defaults.evict_caches_after, |hours| {
+                    Duration::from_secs(hours * 60 * 60)
+                }),
+            relay_url,
+        }
+    }
+}
+
+/// A maintenance task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MaintenanceTask {
+    /// Archive old resolved comment threads.
+    CommentArchival,
+    /// Fail AI executions that were interrupted.
+    StaleAiCleanup,
+    /// Evict the diff caches of repositories that are no longer viewed.
+    CacheEviction,
+    /// Check that the relay server accepts connections.
+    RelayHealth,
+}
+
+impl MaintenanceTask {
+    /// Human-readable task name.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::CommentArchival => "Comment archival",
+            Self::StaleAiCleanup => "Stale AI cleanup",
+            Self::CacheEviction => "Cache eviction",
+            Self::RelayHealth => "Relay health check",
+        }
+    }
+}
+
+/// Outcome of a task's most recent run.
+#[derive(Debug, Clone)]
+pub struct TaskRun {
+    /// When the run finished.
+    pub finished_at: DateTime<Utc>,
+    /// How long the run took.
+    pub duration: Duration,
+    /// Summary of what was done, or why the task failed.
+    pub result: Result<String, String>,
+}
+
+/// Runs maintenance tasks on a schedule and records their results.
+#[derive(Debug)]
+pub struct Maintenance {
+    config: MaintenanceConfig,
+    runs: RwLock<BTreeMap<MaintenanceTask, TaskRun>>,
+}
+
+impl Maintenance {
+    /// Create a scheduler. Nothing runs until [`Self::run_forever`] is spawned.
+    #[must_use]
+    pub fn new(config: MaintenanceConfig) -> Arc<Self> {
+        Arc::new(Self {
+            config,
+            runs: RwLock::new(BTreeMap::new()),
+        })
+    }
+
+    /// Run all tasks now, then again after every interval.
+    pub async fn run_forever(self: Arc<Self>) {
+        loop {
+            self.run_once().await;
+            switchy::unsync::time::sleep(self.config.interval).await;
+        }
+    }
+
+    /// Run every enabled task once.
+    pub async fn run_once(&self) {
+        #[cfg(feature = "local-git")]
+        {
+            if let Some(after) = self.config.archive_resolved_after {
+                self.run_task(MaintenanceTask::CommentArchival, move || {
+                    archive_resolved_threads(after)
+                })
+                .await;
+            }
+
+            let stale_after = self.config.stale_ai_after;
+            self.run_task(MaintenanceTask::StaleAiCleanup, move || {
+                fail_stale_ai_executions(stale_after)
+            })
+            .await;
+
+            let idle = self.config.evict_caches_after;
+            self.run_task(MaintenanceTask::CacheEviction, move || {
+                let evicted = chadreview_local_diff::evict_idle_caches(idle);
+                Ok(format!("Evicted the caches of {evicted} idle repositories"))
+            })
+            .await;
+        }
+
+        if let Some(relay_url) = &self.config.relay_url {
+            let start = Instant::now();
+            let result =
+                chadreview_relay_client::RelayClient::check_health(relay_url, RELAY_HEALTH_TIMEOUT)
+                    .await
+                    .map(|()| "Relay server reachable".to_string())
+                    .map_err(|e| e.to_string());
+            self.record(MaintenanceTask::RelayHealth, start, result);
+        }
+    }
+
+    /// The most recent run of each task that has run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the status lock is poisoned.
+    #[must_use]
+    pub fn last_runs(&self) -> BTreeMap<MaintenanceTask, TaskRun> {
+        self.runs.read().unwrap().clone()
+    }
+
+    /// Run `f`, which does blocking IO, on the blocking thread pool and
+    /// record its result.
+    #[cfg(feature = "local-git")]
+    async fn run_task(
+        &self,
+        task: MaintenanceTask,
+        f: impl FnOnce() -> Result<String, String> + Send + 'static,
+    ) {
+        let start = Instant::now();
+        let result = switchy::unsync::task::spawn_blocking(f)
+            .await
+            .unwrap_or_else(|e| Err(format!("Task failed: {e}")));
+        self.record(task, start, result);
+    }
+
+    fn record(&self, task: MaintenanceTask, start: Instant, result: Result<String, String>) {
+        match &result {
+            Ok(summary) => log::info!("Maintenance: {}: {summary}", task.name()),
+            Err(e) => log::warn!("Maintenance: {} failed: {e}", task.name()),
+        }
+
+        let run = TaskRun {
+            finished_at: Utc::now(),
+            duration: start.elapsed(),
+            result,
+        };
+        if let Ok(mut runs) = self.runs.write() {
+            runs.insert(task, run);
+        }
+    }
+}
+
+#[cfg(feature = "local-git")]
+fn cutoff(age: Duration) -> Result<DateTime<Utc>, String> {
+    chrono::Duration::from_std(age)
+        .map(|age| Utc::now() - age)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "local-git")]
+fn archive_resolved_threads(after: Duration) -> Result<String, String> {
+    use chadreview_local_comment::LocalCommentStore;
+
+    let cutoff = cutoff(after)?;
+    let mut archived = 0;
+    for store in LocalCommentStore::open_all().map_err(|e| e.to_string())? {
+        archived += store
+            .archive_resolved_threads(cutoff)
+            .map_err(|e| format!("{}: {e}", store.storage_path().display()))?;
+    }
+
+    Ok(format!("Archived {archived} resolved threads"))
+}
+
+/// Without AI integration, no AI actions execute in this process.
+#[cfg(all(feature = "local-git", not(feature = "_ai-integration")))]
+const fn is_running(_comment_id: switchy::uuid::Uuid) -> bool {
+    false
+}
+
+#[cfg(feature = "local-git")]
+fn fail_stale_ai_executions(after: Duration) -> Result<String, String> {
+    use chadreview_local_comment::LocalCommentStore;
+
+    let cutoff = cutoff(after)?;
+    let mut failed = 0;
+    for store in LocalCommentStore::open_all().map_err(|e| e.to_string())? {
+        failed += store
+            .fail_stale_ai_executions(cutoff, &is_running)
+            .map_err(|e| format!("{}: {e}", store.storage_path().display()))?;
+    }
+
+    Ok(format!(
+        "Marked {failed} interrupted AI executions as failed"
+    ))
+}
+
+/// Add the `/debug/maintenance` status route to an existing router.
+#[must_use]
+pub fn add_maintenance_routes(router: Router, maintenance: Arc<Maintenance>) -> Router {
+    router.with_route_result("/debug/maintenance", move |req: RouteRequest| {
+        let maintenance = Arc::clone(&maintenance);
+        async move { maintenance_route(&req, &maintenance) }
+    })
+}
+
+fn maintenance_route(
+    req: &RouteRequest,
+    maintenance: &Maintenance,
+) -> Result<Container, MaintenanceError> {
+    if !matches!(req.method, Method::Get) {
+        return Err(MaintenanceError::UnsupportedMethod);
+    }
+
+    let runs = maintenance.last_runs();
+    let interval_hours = maintenance.config.interval.as_secs() / 3600;
+
+    Ok(container! {
+        div padding=20 gap=12 {
+            h1 font-size=24 font-weight=600 color="#24292f" { "Maintenance" }
+            span font-size=14 color="#57606a" {
+                "Runs every " (interval_hours.to_string()) " hours."
+            }
+            @if runs.is_empty() {
+                span font-size=14 color="#57606a" { "No tasks have run yet." }
+            }
+            @for (task, run) in &runs {
+                div
+                    padding=12
+                    border="1px solid #d0d7de"
+                    border-radius=6
+                    gap=4
+                {
+                    div direction=row gap=8 align-items=center {
+                        span font-weight=600 font-size=14 color="#24292f" { (task.name()) }
+                        @match &run.result {
+                            Ok(_) => { span font-size=12 color="#1a7f37" { "OK" } }
+                            Err(_) => { span font-size=12 color="#cf222e" { "Failed" } }
+                        }
+                    }
+                    span font-size=13 color="#24292f" {
+                        @match &run.result {
+                            Ok(summary) => { (summary) }
+                            Err(e) => { (e) }
+                        }
+                    }
+                    span font-size=12 color="#57606a" {
+                        (run.finished_at.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                        " · took "
+                        (format!("{:.1?}", run.duration))
+                    }
+                }
+            }
+        }
+    }
+    .into())
+}
@@ -0,0 +1,393 @@
+//! The `/repos` picker for switching between local repositories.
+//!
+//! One instance serves every checkout: the picker lists the repositories
+//! from the config file (`repos.default` and `repos.paths`) followed by the
+//! ones opened recently at `/local`, each linking to its diff. Comments are
+//! stored per repository, keyed by its canonical path, so each one keeps its
+//! own threads however its path was written.
+//!
+//! Under each repository, the diffs reviewed in it recently link back to
+//! the same diff with how many of their files have been viewed, so a review
+//! can be resumed where it was left. The picker is also the landing page at
+//! `/`.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use chadreview_config::{Config, RecentRepos};
+use chadreview_local_comment::{LocalCommentStore, load_review_history};
+use chadreview_local_comment_models::ReviewSession;
+use hyperchad::router::{Container, RouteRequest, Router};
+use hyperchad_template::{Containers, Selector, container};
+use switchy::http::models::Method;
+
+use crate::settings::{self, theme_stylesheet};
+
+const REPOS_ID: &str = "repos";
+
+/// Most recently reviewed diffs shown under a repository.
+const MAX_SESSIONS_SHOWN: usize = 5;
+
+/// Error type for the `/repos` routes.
+#[derive(Debug, thiserror::Error)]
+pub enum ReposError {
+    /// The request method is not supported for this route.
+    #[error("Unsupported method")]
+    UnsupportedMethod,
+    /// Form parse error.
+    #[error("Invalid form body: {0}")]
+    InvalidBody(#[from] hyperchad::router::ParseError),
+}
+
+/// Form data for adding or forgetting a recent repository.
+#[derive(Debug, Default, serde::Deserialize)]
+struct RepoForm {
+    /// Path of a repository to add to the recent ones.
+    open: Option<String>,
+    /// Path of a recent repository to forget, instead of adding one.
+    remove: Option<String>,
+}
+
+/// Where a repository in the picker comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RepoSource {
+    /// `repos.default` in the config file.
+    Default,
+    /// `repos.paths` in the config file.
+    Configured,
+    /// Opened at `/local` recently.
+    Recent,
+}
+
+impl RepoSource {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Configured => "configured",
+            Self::Recent => "recent",
+        }
+    }
+}
+
+/// A repository listed in the picker.
+#[derive(Debug, Clone)]
+struct RepoEntry {
+    path: PathBuf,
+    source: RepoSource,
+}
+
+/// Add the `/repos` routes, and the picker as the landing page at `/`, to
+/// an existing router.
+#[must_use]
+pub fn add_repo_routes(router: Router) -> Router {
+    router
+        .with_route_result("/", |req: RouteRequest| async move { repos_route(&req) })
+        .with_route_result(
+            "/repos",
+            |req: RouteRequest| async move { repos_route(&req) },
+        )
+}
+
+/// Remember `repo_path` as the most recently opened repository.
+///
+/// Any errors are logged but don't cause the function to fail.
+pub fn record_recent_repo(repo_path: &Path) {
+    let mut recent = match RecentRepos::load() {
+        Ok(recent) => recent,
+        Err(e) => {
+            log::warn!("Failed to load recent repositories: {e}");
+            return;
+        }
+    };
+
+    if recent.record(repo_path) {
+        recent
+            .save()
+            .unwrap_or_else(|e| log::warn!("Failed to save recent repositories: {e}"));
+    }
+}
+
+fn repos_route(req: &RouteRequest) -> Result<Container, ReposError> {
+    match req.method {
+        Method::Get => {
+            let config = settings::load_config(None);
+            Ok(container! {
+                div padding=20 gap=16 max-width=800 {
+                    (theme_stylesheet(config.appearance.theme))
+                    h1 font-size=24 font-weight=600 color="#24292f" { "Repositories" }
+                    (render_repos(&config, None))
+                }
+            }
+            .into())
+        }
+        Method::Post => {
+            let form: RepoForm = req.parse_form()?;
+            let config = settings::load_config(None);
+
+            // Don't overwrite a recent repositories file that failed to parse
+            let mut recent = match RecentRepos::load() {
+                Ok(recent) => recent,
+                Err(e) => {
+                    let error = format!("Failed to read recent repositories: {e}");
+                    return Ok(render_repos(&config, Some(Err(&error))).into());
+                }
+            };
+
+            let status = form.apply(&mut recent).and_then(|message| {
+                recent
+                    .save()
+                    .map(|()| message)
+                    .map_err(|e| format!("Failed to save recent repositories: {e}"))
+            });
+
+            Ok(render_repos(&config, Some(status.as_deref())).into())
+        }
+        _ => Err(ReposError::UnsupportedMethod),
+    }
+}
+
+impl RepoForm {
+    fn apply(self, recent: &mut RecentRepos) -> Result<String, String> {
+        if let Some(path) = self.remove {
+            let path = PathBuf::from(path);
+            return if recent.remove(&path) {
+                Ok(format!("Forgot {}.", path.display()))
+            } else {
+                Err(format!("{} isn't a recent repository.", path.display()))
+            };
+        }
+
+        let path = self
+            .open
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty())
+            .ok_or_else(|| "Enter a repository path.".to_string())?;
+        let path = PathBuf::from(path)
+            .canonicalize()
+            .map_err(|e| format!("Can't open {path}: {e}"))?;
+        if !path.join(".git").exists() {
+            return Err(format!("{} isn't a git repository.", path.display()));
+        }
+
+        recent.record(&path);
+        Ok(format!("Added {}.", path.display()))
+    }
+}
+
+/// The configured repositories followed by the recent ones, each listed
+/// once.
+fn repo_entries(config: &Config) -> Vec<RepoEntry> {
+    let recent = RecentRepos::load().unwrap_or_else(|e| {
+        log::warn!("Failed to load recent repositories: {e}");
+        RecentRepos::default()
+    });
+
+    let mut entries: Vec<RepoEntry> = vec![];
+    let sources = config
+        .repos
+        .default
+        .iter()
+        .map(|path| (path, RepoSource::Default))
+        .chain(
+            config
+                .repos
+                .paths
+                .iter()
+                .map(|path| (path, RepoSource::Configured)),
+        )
+        .chain(recent.paths.iter().map(|path| (path, RepoSource::Recent)));
+    for (path, source) in sources {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if entries.iter().any(|entry| entry.path == canonical) {
+            continue;
+        }
+        entries.push(RepoEntry {
+            path: canonical,
+            source,
+        });
+    }
+
+    entries
+}
+
+fn render_repos(config: &Config, status: Option<Result<&str, &str>>) -> Containers {
+    let entries = repo_entries(config);
+
+    container! {
+        div id=(REPOS_ID) gap=12 {
+            span font-size=13 color="#57606a" {
+                "Repositories from the config file (edit them at /settings) and the ones opened recently. Each repository keeps its own comments."
+            }
+            @match status {
+                Some(Ok(message)) => {
+                    span font-size=14 color="#1a7f37" { (message) }
+                }
+                Some(Err(message)) => {
+                    span font-size=14 color="#cf222e" { (message) }
+                }
+                None => {}
+            }
+            @if entries.is_empty() {
+                span font-size=14 color="#57606a" { "No repositories yet." }
+            }
+            @for entry in &entries {
+                (render_repo(entry))
+            }
+            form
+                hx-post="/repos"
+                hx-target=(Selector::Id(REPOS_ID.to_string()))
+                hx-swap="outerHTML"
+                direction=row
+                gap=8
+                align-items=center
+            {
+                input
+                    type=text
+                    name="open"
+                    placeholder="/path/to/repository"
+                    flex=1
+                    padding=8
+                    border="1px solid #d0d7de"
+                    border-radius=6
+                    font-size=14
+                    font-family="monospace";
+                button
+                    type=submit
+                    background="#1f883d"
+                    color="#ffffff"
+                    padding-x=16
+                    padding-y=8
+                    border-radius=6
+                    font-weight=600
+                    font-size=14
+                    cursor=pointer
+                {
+                    "Add"
+                }
+            }
+        }
+    }
+}
+
+fn render_repo(entry: &RepoEntry) -> Containers {
+    let path = entry.path.display().to_string();
+    let repo_param = urlencoding::encode(&path).into_owned();
+    let exists = entry.path.exists();
+
+    let store = exists
+        .then(|| LocalCommentStore::new(&entry.path))
+        .transpose()
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to open the comment store of {path}: {e}");
+            None
+        });
+    let threads = store
+        .as_ref()
+        .and_then(|store| store.list_threads().ok())
+        .map_or(0, |threads| threads.len());
+    let sessions = store
+        .as_ref()
+        .and_then(|store| {
+            load_review_history(store)
+                .inspect_err(|e| log::warn!("Failed to load review history of {path}: {e}"))
+                .ok()
+        })
+        .map(|history| history.sessions)
+        .unwrap_or_default();
+    let viewed_paths = store
+        .as_ref()
+        .and_then(|store| store.get_viewed_file_paths().ok())
+        .unwrap_or_default();
+
+    container! {
+        form
+            hx-post="/repos"
+            hx-target=(Selector::Id(REPOS_ID.to_string()))
+            hx-swap="outerHTML"
+            gap=8
+            padding=12
+            border="1px solid #d0d7de"
+            border-radius=6
+        {
+            div direction=row gap=12 align-items=center {
+                div flex=1 gap=4 {
+                    @if exists {
+                        anchor
+                            href=(format!("/local?repo={repo_param}"))
+                            color="#0969da"
+                            font-size=14
+                            font-weight=600
+                            font-family="monospace"
+                        {
+                            (path)
+                        }
+                    } @else {
+                        span font-size=14 font-family="monospace" color="#57606a" { (path) }
+                    }
+                    span font-size=12 color="#57606a" {
+                        @if exists {
+                            (format!("{} · {threads} comment thread{}", entry.source.label(), if threads == 1 { "" } else { "s" }))
+                        } @else {
+                            (format!("{} · not found", entry.source.label()))
+                        }
+                    }
+                }
+                @if entry.source == RepoSource::Recent {
+                    button
+                        type=submit
+                        name="remove"
+                        value=(path)
+                        background="#ffffff"
+                        border="1px solid #d0d7de"
+                        color="#cf222e"
+                        padding-x=12
+                        padding-y=4
+                        border-radius=6
+                        font-size=14
+                        cursor=pointer
+                    {
+                        "Forget"
+                    }
+                }
+            }
+            @for session in sessions.iter().take(MAX_SESSIONS_SHOWN) {
+                (render_session(&repo_param, session, &viewed_paths))
+            }
+        }
+    }
+}
+
+/// A recently reviewed diff, linking back to it.
+fn render_session(
+    repo_param: &str,
+    session: &ReviewSession,
+    viewed_paths: &HashSet<String>,
+) -> Containers {
+    let (viewed, files) = session.progress(viewed_paths);
+    let percent = if files == 0 {
+        100
+    } else {
+        viewed * 100 / files
+    };
+
+    container! {
+        div direction=row gap=12 align-items=center padding-left=12 {
+            anchor
+                href=(format!("/local?repo={repo_param}&{}", session.spec))
+                flex=1
+                color="#0969da"
+                font-size=13
+            {
+                (session.description)
+            }
+            span font-size=12 color="#57606a" {
+                (format!(
+                    "{viewed}/{files} files viewed ({percent}%) · {} thread{} · {}",
+                    session.threads,
+                    if session.threads == 1 { "" } else { "s" },
+                    session.viewed_at.format("%Y-%m-%d %H:%M UTC"),
+                ))
+            }
+        }
+    }
+}
@@ -2,21 +2,32 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 #![allow(clippy::multiple_crate_versions)]
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use chadreview_app_ui::{
+    autocomplete, checklist,
     comment_thread::{
         comment_class, comment_thread_id, render_comment_item, render_comment_thread,
-        render_reply_form,
+        render_reactions, render_reply_form,
     },
-    diff_viewer::render_line_comments,
-    general_comments,
+    commits_tab::{self, PrTab},
+    diff_viewer::{copy, permalink, render_line_comments},
+    footer, general_comments, markdown_preview, merge_panel, pr_header, review_form,
 };
+use chadreview_config::{Config, RelayConfig, Theme};
 use chadreview_diff_models::{LineNumber, ParseLineNumberError};
 use chadreview_git_provider::GitProvider;
-use chadreview_pr_models::{CommentType, CreateComment};
-use chadreview_relay_client::RelayClient;
-use chadreview_relay_models::PrKey;
+use chadreview_hooks::HookEvent;
+use chadreview_pr::{
+    PendingReviewKey, PendingReviewStore, PendingReviewStoreError, PrReviewStateStore,
+    PrReviewStateStoreError,
+};
+use chadreview_pr_models::{
+    Comment, CommentType, CreateComment, MergeStrategy, ReactionContent, ReviewVerdict, User,
+};
+use chadreview_relay_client::{EventCallback, RelayClient};
+use chadreview_relay_models::{PrKey, ReviewAction, WebhookEvent};
 use hyperchad::{
     renderer::Content,
     router::{Container, RouteRequest, Router},
@@ -24,6 +35,11 @@ use hyperchad::{
 use hyperchad_template::{Containers, Selector, container};
 use switchy::http::models::Method;
 
+use crate::autocomplete::{self as suggestions, Suggestion};
+use crate::live::{self, PrLiveUpdates};
+use crate::settings;
+use crate::webhook::{self, DirectWebhooks};
+
 #[derive(Debug, thiserror::Error)]
 pub enum RouteError {
     #[error("Missing query param: '{0}'")]
@@ -42,6 +58,16 @@ pub enum RouteError {
     Json(#[from] serde_json::Error),
     #[error("Invalid body: {0}")]
     InvalidBody(#[from] hyperchad::router::ParseError),
+    #[error("Pending review error: {0}")]
+    PendingReview(#[from] PendingReviewStoreError),
+    #[error("Pending reviews are unavailable")]
+    PendingReviewsUnavailable,
+    #[error("Review state error: {0}")]
+    ReviewState(#[from] PrReviewStateStoreError),
+    #[error("Review state is unavailable")]
+    ReviewStateUnavailable,
+    #[error("File not in diff: {0}")]
+    FileNotInDiff(String),
 }
 
 #[derive(serde::Deserialize)]
@@ -49,23 +75,213 @@ struct UpdateBody {
     body: String,
 }
 
-pub fn create_router(provider: &Arc<dyn GitProvider>, relay_url: Option<String>) -> Router {
-    Router::new()
+/// Comma-separated usernames or label names.
+#[derive(serde::Deserialize)]
+struct NamesBody {
+    names: String,
+}
+
+#[derive(serde::Deserialize)]
+struct ReactionBody {
+    content: ReactionContent,
+}
+
+/// Fields of the PR to change; missing ones are left as they are.
+#[derive(serde::Deserialize)]
+struct EditPrBody {
+    title: Option<String>,
+    body: Option<String>,
+    draft: Option<String>,
+    /// Render the submitted description instead of saving it.
+    preview: Option<String>,
+}
+
+/// A checklist item to check or uncheck.
+#[derive(serde::Deserialize)]
+struct ChecklistBody {
+    item: String,
+    checked: String,
+}
+
+#[derive(serde::Deserialize)]
+struct MergeBody {
+    strategy: MergeStrategy,
+}
+
+#[derive(serde::Deserialize)]
+struct SubmitReviewBody {
+    verdict: ReviewVerdict,
+    #[serde(default)]
+    body: String,
+}
+
+/// Create the router for the PR routes, along with `/webhook` when direct
+/// webhooks are enabled.
+pub fn create_router(provider: &Arc<dyn GitProvider>, relay: RelayConfig) -> Router {
+    let pending_reviews = match PendingReviewStore::new() {
+        Ok(store) => Some(Arc::new(store)),
+        Err(e) => {
+            log::warn!("Pending reviews disabled: {e}");
+            None
+        }
+    };
+    let review_state = match PrReviewStateStore::new() {
+        Ok(store) => Some(Arc::new(store)),
+        Err(e) => {
+            log::warn!("Viewed file tracking disabled: {e}");
+            None
+        }
+    };
+
+    let direct_webhooks = relay
+        .webhook_secret
+        .clone()
+        .map(|secret| Arc::new(DirectWebhooks::new(secret)));
+
+    let router = Router::new()
         .with_route_result("/pr", {
             let provider = provider.clone();
+            let direct_webhooks = direct_webhooks.clone();
+            let pending_reviews = pending_reviews.clone();
+            let review_state = review_state.clone();
             move |req: RouteRequest| {
                 let provider = provider.clone();
-                let relay_url = relay_url.clone();
-                async move { pr_route(req, provider, relay_url).await }
+                let relay = relay.clone();
+                let direct_webhooks = direct_webhooks.clone();
+                let pending_reviews = pending_reviews.clone();
+                let review_state = review_state.clone();
+                async move {
+                    pr_route(
+                        req,
+                        provider,
+                        relay,
+                        direct_webhooks,
+                        pending_reviews,
+                        review_state,
+                    )
+                    .await
+                }
             }
         })
         .with_route_result("/api/pr/comment", {
             let provider = provider.clone();
+            let pending_reviews = pending_reviews.clone();
             move |req: RouteRequest| {
                 let provider = provider.clone();
-                async move { create_comment_route(req, provider).await }
+                let pending_reviews = pending_reviews.clone();
+                async move { create_comment_route(req, provider, pending_reviews).await }
             }
         })
+        .with_route_result("/api/pr/review", {
+            let provider = provider.clone();
+            let pending_reviews = pending_reviews.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                let pending_reviews = pending_reviews.clone();
+                async move { submit_review_route(req, provider, pending_reviews).await }
+            }
+        })
+        .with_route_result("/api/pr/review/start", {
+            let provider = provider.clone();
+            let pending_reviews = pending_reviews.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                let pending_reviews = pending_reviews.clone();
+                async move { start_review_route(&req, &*provider, pending_reviews.as_deref()) }
+            }
+        })
+        .with_route_result("/api/pr/review/discard", {
+            let provider = provider.clone();
+            let pending_reviews = pending_reviews.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                let pending_reviews = pending_reviews.clone();
+                async move { discard_review_route(&req, &*provider, pending_reviews.as_deref()) }
+            }
+        })
+        .with_route_result("/api/pr/review/pending", {
+            let provider = provider.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                let pending_reviews = pending_reviews.clone();
+                async move {
+                    delete_pending_comment_route(&req, &*provider, pending_reviews.as_deref())
+                }
+            }
+        })
+        .with_route_result("/api/pr/labels", {
+            let provider = provider.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                async move { labels_route(req, provider).await }
+            }
+        })
+        .with_route_result("/api/pr/assignees", {
+            let provider = provider.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                async move { assignees_route(req, provider).await }
+            }
+        })
+        .with_route_result("/api/pr/reviewers", {
+            let provider = provider.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                async move { reviewers_route(req, provider).await }
+            }
+        })
+        .with_route_result("/api/pr/file/view", {
+            let provider = provider.clone();
+            let review_state = review_state.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                let review_state = review_state.clone();
+                async move { file_view_route(req, provider, review_state).await }
+            }
+        })
+        .with_route_result("/api/pr/file/diff", {
+            let provider = provider.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                async move { file_diff_route(req, provider).await }
+            }
+        })
+        .with_route_result(checklist::CHECKLIST_ROUTE, {
+            let provider = provider.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                let review_state = review_state.clone();
+                async move { checklist_route(&req, &*provider, review_state.as_deref()) }
+            }
+        })
+        .with_route_result("/api/pr/edit", {
+            let provider = provider.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                async move { edit_pr_route(req, provider).await }
+            }
+        })
+        .with_route_result("/api/pr/merge", {
+            let provider = provider.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                async move { merge_route(req, provider).await }
+            }
+        })
+        .with_route_result(autocomplete::EMOJI_ROUTE, |req: RouteRequest| async move {
+            emoji_route(&req)
+        })
+        .with_route_result(autocomplete::MENTIONS_ROUTE, {
+            let provider = provider.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                async move { mentions_route(req, provider).await }
+            }
+        })
+        .with_route_result(
+            markdown_preview::MARKDOWN_PREVIEW_ROUTE,
+            |req: RouteRequest| async move { markdown_preview_route(&req) },
+        )
         .with_route_result("/api/comment/update", {
             let provider = provider.clone();
             move |req: RouteRequest| {
@@ -87,6 +303,13 @@ pub fn create_router(provider: &Arc<dyn GitProvider>, relay_url: Option<String>)
                 async move { resolve_comment_route(req, provider).await }
             }
         })
+        .with_route_result("/api/comment/reaction", {
+            let provider = provider.clone();
+            move |req: RouteRequest| {
+                let provider = provider.clone();
+                async move { reaction_route(req, provider).await }
+            }
+        })
         .with_route_result("/api/comment/expand", {
             let provider = provider.clone();
             move |req: RouteRequest| {
@@ -100,13 +323,21 @@ pub fn create_router(provider: &Arc<dyn GitProvider>, relay_url: Option<String>)
                 let provider = provider.clone();
                 async move { collapse_comment_route(req, provider).await }
             }
-        })
+        });
+
+    match direct_webhooks {
+        Some(direct_webhooks) => webhook::add_webhook_routes(router, direct_webhooks),
+        None => router,
+    }
 }
 
 async fn pr_route(
     req: RouteRequest,
     provider: Arc<dyn GitProvider>,
-    relay_url: Option<String>,
+    relay: RelayConfig,
+    direct_webhooks: Option<Arc<DirectWebhooks>>,
+    pending_reviews: Option<Arc<PendingReviewStore>>,
+    review_state: Option<Arc<PrReviewStateStore>>,
 ) -> Result<Container, RouteError> {
     if !matches!(req.method, Method::Get) {
         return Err(RouteError::UnsupportedMethod);
@@ -126,42 +357,87 @@ async fn pr_route(
         .ok_or(RouteError::MissingQueryParam("number"))?
         .parse::<u64>()?;
 
+    let tab = PrTab::from_query(
+        req.query.get("tab").map(String::as_str),
+        req.query.get("commit").map(String::as_str),
+    );
+
     let pr = provider.get_pr(owner, repo, number).await?;
-    let diffs = provider.get_diff(owner, repo, number).await?;
+    let commits = provider
+        .get_commits(owner, repo, number)
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to fetch commits for {owner}/{repo} #{number}: {e}");
+            Vec::new()
+        });
+    let diffs = match &tab {
+        PrTab::Files => provider.get_diff(owner, repo, number).await?,
+        PrTab::Commit(sha) => provider.get_commit_diff(owner, repo, sha).await?,
+        PrTab::Commits => Vec::new(),
+    };
     let comments = provider.get_comments(owner, repo, number).await?;
+    let checks = provider
+        .get_checks(owner, repo, &pr.head_sha)
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to fetch checks for {owner}/{repo} #{number}: {e}");
+            Vec::new()
+        });
+    let review_key = pending_review_key(&*provider, owner, repo, number);
+    let pending_review = match &pending_reviews {
+        Some(store) => store.get(&review_key)?,
+        None => None,
+    };
+    let viewed_paths = match &review_state {
+        Some(store) => store
+            .viewed_paths(&review_key, &pr.head_sha)
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to load viewed files for {owner}/{repo} #{number}: {e}");
+                HashSet::new()
+            }),
+        None => HashSet::new(),
+    };
+    let config = settings::load_config(None);
+    let checklist_items = load_checklist(&config);
+    let checked_items = match &review_state {
+        Some(store) => store.checked_items(&review_key).unwrap_or_else(|e| {
+            log::warn!("Failed to load checklist for {owner}/{repo} #{number}: {e}");
+            HashSet::new()
+        }),
+        None => HashSet::new(),
+    };
+    let generated = settings::load_generated_files(None, &config);
+    let generated_paths: HashSet<String> = diffs
+        .iter()
+        .filter(|file| generated.is_generated(&file.filename))
+        .map(|file| file.filename.clone())
+        .collect();
 
     // Subscribe to PR webhook events for real-time updates
-    if let Some(url) = relay_url {
+    let pr_key = PrKey {
+        owner: owner.clone(),
+        repo: repo.clone(),
+        number,
+    };
+
+    let live = Arc::new(PrLiveUpdates::new(
+        provider.clone(),
+        review_state.clone(),
+        pr_key.clone(),
+    ));
+
+    if let Some(direct_webhooks) = &direct_webhooks {
+        direct_webhooks.subscribe(pr_key.clone(), pr_event_callback(&pr_key, &live));
+    }
+
+    if let Some(url) = relay.url {
         let instance_id = RelayClient::get_or_create_instance_id();
 
         // Connect to relay server (lazily, on first PR view)
-        match RelayClient::connect_async(&url, instance_id).await {
+        match RelayClient::connect_async(&url, instance_id, relay.token).await {
             Ok(client) => {
-                let pr_key = PrKey {
-                    owner: owner.clone(),
-                    repo: repo.clone(),
-                    number,
-                };
-
-                let owner_clone = owner.clone();
-                let repo_clone = repo.clone();
-
-                // Create callback that will be invoked when webhook events arrive
-                let callback = Arc::new(move |event| {
-                    log::info!(
-                        "Received webhook event for PR {owner_clone}/{repo_clone} #{number}: {event:?}"
-                    );
-
-                    // TODO: In a future enhancement, this callback should:
-                    // 1. Refetch the updated PR data from the GitHub provider
-                    // 2. Trigger HyperChad SSE updates to push changes to all connected clients
-                    // 3. Update the UI with the new data (new comments, PR state changes, etc.)
-                    //
-                    // For now, the callback just logs the event. The real-time update mechanism
-                    // would need to integrate with HyperChad's state management and SSE system.
-                });
-
                 // Subscribe to this PR's events
+                let callback = pr_event_callback(&pr_key, &live);
                 if let Err(e) = client.subscribe(pr_key, callback).await {
                     log::warn!("Failed to subscribe to PR webhook events: {e}");
                 } else {
@@ -174,12 +450,94 @@ async fn pr_route(
         }
     }
 
-    Ok(render_pr_view(&pr, &diffs, &comments, owner, repo, number))
+    Ok(render_pr_view(
+        &pr,
+        &checks,
+        provider.rate_limit().as_ref(),
+        &tab,
+        &commits,
+        &diffs,
+        &comments,
+        (&viewed_paths, &generated_paths),
+        (&checklist_items, &checked_items),
+        pending_review.as_ref(),
+        config.appearance.theme,
+        owner,
+        repo,
+        number,
+    ))
+}
+
+/// Create the callback invoked when webhook events arrive for a PR, from
+/// the relay server or delivered directly, which applies them to the open
+/// views of the PR through `live`.
+fn pr_event_callback(pr_key: &PrKey, live: &Arc<PrLiveUpdates>) -> EventCallback {
+    let live = Arc::clone(live);
+    let PrKey {
+        owner,
+        repo,
+        number,
+    } = pr_key.clone();
+
+    Arc::new(move |event: WebhookEvent| {
+        log::info!("Received webhook event for PR {owner}/{repo} #{number}: {event:?}");
+
+        if let Some(data) = review_received(&owner, &repo, number, &event) {
+            chadreview_hooks::dispatch_global(HookEvent::PrReviewReceived, &data);
+        }
+
+        let live = Arc::clone(&live);
+        let (owner, repo) = (owner.clone(), repo.clone());
+        switchy::unsync::task::spawn(async move {
+            if let Err(e) = live.apply(&event).await {
+                log::warn!("Failed to apply webhook event to PR {owner}/{repo} #{number}: {e}");
+            }
+        });
+    })
+}
+
+/// Hook data for a review submitted on a PR, or `None` for any other event.
+///
+/// Each comment of a review also arrives as an event of its own, so only
+/// the submission is reported, once per review.
+fn review_received(
+    owner: &str,
+    repo: &str,
+    number: u64,
+    event: &WebhookEvent,
+) -> Option<serde_json::Value> {
+    let WebhookEvent::PullRequestReview {
+        action: ReviewAction::Submitted,
+        review,
+        ..
+    } = event
+    else {
+        return None;
+    };
+
+    Some(serde_json::json!({
+        "owner": owner,
+        "repo": repo,
+        "number": number,
+        "author": review.user.login,
+        "body": review.body.as_deref().unwrap_or_default(),
+        "state": review.state,
+    }))
+}
+
+fn pending_review_key(
+    provider: &dyn GitProvider,
+    owner: &str,
+    repo: &str,
+    number: u64,
+) -> PendingReviewKey {
+    PendingReviewKey::new(provider.provider_name(), owner, repo, number)
 }
 
 async fn create_comment_route(
     req: RouteRequest,
     provider: Arc<dyn GitProvider>,
+    pending_reviews: Option<Arc<PendingReviewStore>>,
 ) -> Result<Content, RouteError> {
     if !matches!(req.method, Method::Post) {
         return Err(RouteError::UnsupportedMethod);
@@ -202,6 +560,32 @@ async fn create_comment_route(
     let create_comment: CreateComment = req.parse_form().map_err(RouteError::InvalidBody)?;
     let comment_type = create_comment.comment_type.clone();
 
+    // While a review is pending, line and file comments are held back and
+    // published together when it is submitted
+    if matches!(
+        comment_type,
+        CommentType::LineLevelComment { .. } | CommentType::FileLevelComment { .. }
+    ) && let Some(store) = &pending_reviews
+    {
+        let key = pending_review_key(&*provider, owner, repo, number);
+        if store.get(&key)?.is_some() {
+            let pending = store.add_comment(&key, create_comment)?;
+            let review = store.get(&key)?;
+
+            let mut content = Content::builder();
+            content.primary(review_form::render_inline_pending_comment(
+                &pending, owner, repo, number,
+            ));
+            content.fragment(review_form::render_review_form(
+                owner,
+                repo,
+                number,
+                review.as_ref(),
+            ));
+            return Ok(content.build());
+        }
+    }
+
     let comment = provider
         .create_comment(owner, repo, number, create_comment)
         .await?;
@@ -219,6 +603,7 @@ async fn create_comment_route(
                     *root_comment_id,
                     &comment,
                     1,
+                    provider.provider_name(),
                     owner,
                     repo,
                     number,
@@ -232,7 +617,13 @@ async fn create_comment_route(
                 ));
             } else {
                 content.primary(render_comment_thread(
-                    comment.id, &comment, 0, owner, repo, number,
+                    comment.id,
+                    &comment,
+                    0,
+                    provider.provider_name(),
+                    owner,
+                    repo,
+                    number,
                 ));
             }
         }
@@ -246,6 +637,7 @@ async fn create_comment_route(
                 std::iter::once(&comment),
                 &path,
                 line,
+                provider.provider_name(),
                 owner,
                 repo,
                 number,
@@ -256,6 +648,586 @@ async fn create_comment_route(
     Ok(content.build())
 }
 
+async fn submit_review_route(
+    req: RouteRequest,
+    provider: Arc<dyn GitProvider>,
+    pending_reviews: Option<Arc<PendingReviewStore>>,
+) -> Result<Container, RouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(RouteError::UnsupportedMethod);
+    }
+
+    let owner = req
+        .query
+        .get("owner")
+        .ok_or(RouteError::MissingQueryParam("owner"))?;
+    let repo = req
+        .query
+        .get("repo")
+        .ok_or(RouteError::MissingQueryParam("repo"))?;
+    let number = req
+        .query
+        .get("number")
+        .ok_or(RouteError::MissingQueryParam("number"))?
+        .parse::<u64>()?;
+
+    let submit: SubmitReviewBody = req.parse_form().map_err(RouteError::InvalidBody)?;
+
+    let key = pending_review_key(&*provider, owner, repo, number);
+    let pending_comments = match &pending_reviews {
+        Some(store) => store
+            .get(&key)?
+            .map(|review| review.comments.into_iter().map(|c| c.comment).collect())
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+
+    let review = provider
+        .submit_review(
+            owner,
+            repo,
+            number,
+            submit.verdict,
+            submit.body,
+            pending_comments,
+        )
+        .await?;
+
+    if let Some(store) = &pending_reviews {
+        store.discard(&key)?;
+    }
+
+    Ok(review_form::render_review_submitted(&review).into())
+}
+
+fn start_review_route(
+    req: &RouteRequest,
+    provider: &dyn GitProvider,
+    pending_reviews: Option<&PendingReviewStore>,
+) -> Result<Container, RouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(RouteError::UnsupportedMethod);
+    }
+
+    let (owner, repo, number) = pr_query(req)?;
+    let store = pending_reviews.ok_or(RouteError::PendingReviewsUnavailable)?;
+    let review = store.start(&pending_review_key(provider, owner, repo, number))?;
+
+    Ok(review_form::render_review_form(owner, repo, number, Some(&review)).into())
+}
+
+fn discard_review_route(
+    req: &RouteRequest,
+    provider: &dyn GitProvider,
+    pending_reviews: Option<&PendingReviewStore>,
+) -> Result<Container, RouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(RouteError::UnsupportedMethod);
+    }
+
+    let (owner, repo, number) = pr_query(req)?;
+    let store = pending_reviews.ok_or(RouteError::PendingReviewsUnavailable)?;
+    store.discard(&pending_review_key(provider, owner, repo, number))?;
+
+    Ok(review_form::render_review_form(owner, repo, number, None).into())
+}
+
+fn delete_pending_comment_route(
+    req: &RouteRequest,
+    provider: &dyn GitProvider,
+    pending_reviews: Option<&PendingReviewStore>,
+) -> Result<Content, RouteError> {
+    if !matches!(req.method, Method::Delete) {
+        return Err(RouteError::UnsupportedMethod);
+    }
+
+    let (owner, repo, number) = pr_query(req)?;
+    let id = req
+        .query
+        .get("id")
+        .ok_or(RouteError::MissingQueryParam("id"))?
+        .parse::<u64>()?;
+    let store = pending_reviews.ok_or(RouteError::PendingReviewsUnavailable)?;
+    let key = pending_review_key(provider, owner, repo, number);
+
+    store.remove_comment(&key, id)?;
+    let review = store.get(&key)?;
+
+    let mut content = Content::builder();
+    content.primary(review_form::render_review_form(
+        owner,
+        repo,
+        number,
+        review.as_ref(),
+    ));
+    content.delete_selector(Selector::Id(review_form::pending_comment_id(id)));
+
+    Ok(content.build())
+}
+
+/// Read the `owner`, `repo` and `number` query parameters.
+/// Add labels (POST with [`NamesBody`]) or remove the `name` label (DELETE),
+/// returning the updated labels section.
+async fn labels_route(
+    req: RouteRequest,
+    provider: Arc<dyn GitProvider>,
+) -> Result<Container, RouteError> {
+    let (owner, repo, number) = pr_query(&req)?;
+
+    let labels = match req.method {
+        Method::Post => {
+            let names = parse_names(&req.parse_form::<NamesBody>()?.names);
+            if names.is_empty() {
+                provider.get_pr(owner, repo, number).await?.labels
+            } else {
+                provider.add_labels(owner, repo, number, names).await?
+            }
+        }
+        Method::Delete => {
+            let name = req
+                .query
+                .get("name")
+                .ok_or(RouteError::MissingQueryParam("name"))?;
+            provider.remove_label(owner, repo, number, name).await?
+        }
+        _ => return Err(RouteError::UnsupportedMethod),
+    };
+
+    Ok(pr_header::render_labels(owner, repo, number, &labels))
+}
+
+/// Add assignees (POST with [`NamesBody`]) or unassign `name` (DELETE),
+/// returning the updated assignees section.
+async fn assignees_route(
+    req: RouteRequest,
+    provider: Arc<dyn GitProvider>,
+) -> Result<Container, RouteError> {
+    let (owner, repo, number) = pr_query(&req)?;
+
+    let mut assignees: Vec<String> = provider
+        .get_pr(owner, repo, number)
+        .await?
+        .assignees
+        .into_iter()
+        .map(|user| user.username)
+        .collect();
+
+    match req.method {
+        Method::Post => {
+            for name in parse_names(&req.parse_form::<NamesBody>()?.names) {
+                if !assignees.contains(&name) {
+                    assignees.push(name);
+                }
+            }
+        }
+        Method::Delete => {
+            let name = req
+                .query
+                .get("name")
+                .ok_or(RouteError::MissingQueryParam("name"))?;
+            assignees.retain(|assignee| assignee != name);
+        }
+        _ => return Err(RouteError::UnsupportedMethod),
+    }
+
+    let assignees = provider
+        .set_assignees(owner, repo, number, assignees)
+        .await?;
+
+    Ok(pr_header::render_assignees(owner, repo, number, &assignees))
+}
+
+/// Request reviews (POST with [`NamesBody`]) or withdraw the request for
+/// `name` (DELETE), returning the updated reviewers section.
+async fn reviewers_route(
+    req: RouteRequest,
+    provider: Arc<dyn GitProvider>,
+) -> Result<Container, RouteError> {
+    let (owner, repo, number) = pr_query(&req)?;
+
+    let reviewers = match req.method {
+        Method::Post => {
+            let names = parse_names(&req.parse_form::<NamesBody>()?.names);
+            if names.is_empty() {
+                provider.get_pr(owner, repo, number).await?.reviewers
+            } else {
+                provider
+                    .request_reviewers(owner, repo, number, names)
+                    .await?
+            }
+        }
+        Method::Delete => {
+            let name = req
+                .query
+                .get("name")
+                .ok_or(RouteError::MissingQueryParam("name"))?;
+            provider
+                .remove_reviewers(owner, repo, number, vec![name.clone()])
+                .await?
+        }
+        _ => return Err(RouteError::UnsupportedMethod),
+    };
+
+    Ok(pr_header::render_reviewers(owner, repo, number, &reviewers))
+}
+
+/// Edit the PR's title, description or draft state.
+///
+/// GET returns the `part` (`title` or `description`) as a form when `edit`
+/// is set, or as it's normally shown otherwise. POST saves the submitted
+/// [`EditPrBody`] and returns the updated part, along with the merge panel
+/// when the draft state changed.
+async fn edit_pr_route(
+    req: RouteRequest,
+    provider: Arc<dyn GitProvider>,
+) -> Result<Content, RouteError> {
+    let (owner, repo, number) = pr_query(&req)?;
+    let mut content = Content::builder();
+
+    match req.method {
+        Method::Get => {
+            let part = req
+                .query
+                .get("part")
+                .ok_or(RouteError::MissingQueryParam("part"))?;
+            let edit = req
+                .query
+                .get("edit")
+                .map(|s| s.parse::<bool>())
+                .transpose()?
+                .unwrap_or(false);
+            let pr = provider.get_pr(owner, repo, number).await?;
+
+            content.primary(match (part.as_str(), edit) {
+                ("description", true) => {
+                    pr_header::render_description_form(&pr, &pr.description, false)
+                }
+                ("description", false) => pr_header::render_description(&pr),
+                (_, true) => pr_header::render_title_form(&pr),
+                (_, false) => pr_header::render_header_main(&pr),
+            });
+        }
+        Method::Post => {
+            let edit: EditPrBody = req.parse_form()?;
+
+            if edit.preview.is_some() {
+                let pr = provider.get_pr(owner, repo, number).await?;
+                let body = edit.body.unwrap_or_default();
+                content.primary(pr_header::render_description_form(&pr, &body, true));
+                return Ok(content.build());
+            }
+
+            let draft = edit.draft.map(|s| s.parse::<bool>()).transpose()?;
+            let edits_description = edit.body.is_some();
+            let pr = provider
+                .update_pr(owner, repo, number, edit.title, edit.body, draft)
+                .await?;
+
+            if edits_description {
+                content.primary(pr_header::render_description(&pr));
+                return Ok(content.build());
+            }
+
+            content.primary(pr_header::render_header_main(&pr));
+            if draft.is_some() {
+                let checks = provider
+                    .get_checks(owner, repo, &pr.head_sha)
+                    .await
+                    .unwrap_or_else(|e| {
+                        log::warn!("Failed to fetch checks for {owner}/{repo} #{number}: {e}");
+                        Vec::new()
+                    });
+                content.fragment(merge_panel::render_merge_panel(&pr, &checks));
+            }
+        }
+        _ => return Err(RouteError::UnsupportedMethod),
+    }
+
+    Ok(content.build())
+}
+
+/// Merge the pull request with the submitted [`MergeBody`] strategy,
+/// replacing the merge panel with a confirmation.
+async fn merge_route(
+    req: RouteRequest,
+    provider: Arc<dyn GitProvider>,
+) -> Result<Container, RouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(RouteError::UnsupportedMethod);
+    }
+
+    let (owner, repo, number) = pr_query(&req)?;
+    let strategy = req.parse_form::<MergeBody>()?.strategy;
+
+    provider.merge_pr(owner, repo, number, strategy).await?;
+
+    Ok(merge_panel::render_merged(strategy).into())
+}
+
+/// Handle POST/DELETE `/api/pr/file/view` - Mark a file as viewed/unviewed
+/// at the PR's head commit (`sha`).
+///
+/// POST: Mark as viewed, returns the file collapsed
+/// DELETE: Mark as unviewed, returns the file expanded with its comments
+async fn file_view_route(
+    req: RouteRequest,
+    provider: Arc<dyn GitProvider>,
+    review_state: Option<Arc<PrReviewStateStore>>,
+) -> Result<Container, RouteError> {
+    let viewed = match req.method {
+        Method::Post => true,
+        Method::Delete => false,
+        _ => return Err(RouteError::UnsupportedMethod),
+    };
+
+    let (owner, repo, number) = pr_query(&req)?;
+    let sha = req
+        .query
+        .get("sha")
+        .ok_or(RouteError::MissingQueryParam("sha"))?;
+    let path = req
+        .query
+        .get("path")
+        .ok_or(RouteError::MissingQueryParam("path"))?;
+    let store = review_state.ok_or(RouteError::ReviewStateUnavailable)?;
+
+    store.set_file_viewed(
+        &pending_review_key(&*provider, owner, repo, number),
+        sha,
+        path,
+        viewed,
+    )?;
+    log::info!(
+        "Marked {path} as {} in {owner}/{repo} #{number}",
+        if viewed { "viewed" } else { "unviewed" }
+    );
+
+    let diffs = provider.get_diff(owner, repo, number).await?;
+    let file = diffs
+        .iter()
+        .find(|file| &file.filename == path)
+        .ok_or_else(|| RouteError::FileNotInDiff(path.clone()))?;
+    let comments = if viewed {
+        Vec::new()
+    } else {
+        provider.get_comments(owner, repo, number).await?
+    };
+
+    Ok(chadreview_app_ui::diff_viewer::render_file(
+        sha,
+        file,
+        &comments,
+        viewed,
+        provider.provider_name(),
+        owner,
+        repo,
+        number,
+    )
+    .into())
+}
+
+/// Handle GET `/api/pr/file/diff` - Render a file of the PR's diff at its
+/// head commit (`sha`) expanded with its comments, for showing generated
+/// files that are collapsed by default.
+async fn file_diff_route(
+    req: RouteRequest,
+    provider: Arc<dyn GitProvider>,
+) -> Result<Container, RouteError> {
+    if !matches!(req.method, Method::Get) {
+        return Err(RouteError::UnsupportedMethod);
+    }
+
+    let (owner, repo, number) = pr_query(&req)?;
+    let sha = req
+        .query
+        .get("sha")
+        .ok_or(RouteError::MissingQueryParam("sha"))?;
+    let path = req
+        .query
+        .get("path")
+        .ok_or(RouteError::MissingQueryParam("path"))?;
+
+    let diffs = provider.get_diff(owner, repo, number).await?;
+    let file = diffs
+        .iter()
+        .find(|file| &file.filename == path)
+        .ok_or_else(|| RouteError::FileNotInDiff(path.clone()))?;
+    let comments = provider.get_comments(owner, repo, number).await?;
+
+    Ok(chadreview_app_ui::diff_viewer::render_file(
+        sha,
+        file,
+        &comments,
+        false,
+        provider.provider_name(),
+        owner,
+        repo,
+        number,
+    )
+    .into())
+}
+
+/// Handle POST `/api/pr/checklist` - Check or uncheck an item of the PR's
+/// review checklist, returning the re-rendered checklist.
+fn checklist_route(
+    req: &RouteRequest,
+    provider: &dyn GitProvider,
+    review_state: Option<&PrReviewStateStore>,
+) -> Result<Container, RouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(RouteError::UnsupportedMethod);
+    }
+
+    let (owner, repo, number) = pr_query(req)?;
+    let body = req.parse_form::<ChecklistBody>()?;
+    let checked = body.checked.parse::<bool>()?;
+    let store = review_state.ok_or(RouteError::ReviewStateUnavailable)?;
+    let key = pending_review_key(provider, owner, repo, number);
+
+    store.set_checklist_item(&key, &body.item, checked)?;
+
+    let items = load_checklist(&settings::load_config(None));
+    let checked_items = store.checked_items(&key)?;
+
+    Ok(checklist::render_checklist(
+        &items,
+        &checked_items,
+        &checklist::pr_checklist_url(owner, repo, number),
+    )
+    .into())
+}
+
+/// Load the review checklist for PRs. PRs aren't checked out, so only the
+/// configured checklist applies.
+fn load_checklist(config: &Config) -> Vec<String> {
+    chadreview_config::load_checklist(None, config).unwrap_or_else(|e| {
+        log::warn!("Failed to load review checklist: {e}");
+        Vec::new()
+    })
+}
+
+/// Split a comma-separated list, dropping blank entries.
+fn parse_names(names: &str) -> Vec<String> {
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn pr_query(req: &RouteRequest) -> Result<(&str, &str, u64), RouteError> {
+    let owner = req
+        .query
+        .get("owner")
+        .map(String::as_str)
+        .ok_or(RouteError::MissingQueryParam("owner"))?;
+    let repo = req
+        .query
+        .get("repo")
+        .map(String::as_str)
+        .ok_or(RouteError::MissingQueryParam("repo"))?;
+    let number = req
+        .query
+        .get("number")
+        .ok_or(RouteError::MissingQueryParam("number"))?
+        .parse::<u64>()?;
+
+    Ok((owner, repo, number))
+}
+
+/// Suggest users to `@`-mention in the PR's comments matching the `q`
+/// query parameter: the PR's author, assignees, reviewers and commenters,
+/// followed by the repository's collaborators.
+async fn mentions_route(
+    req: RouteRequest,
+    provider: Arc<dyn GitProvider>,
+) -> Result<Content, RouteError> {
+    if !matches!(req.method, Method::Get) {
+        return Err(RouteError::UnsupportedMethod);
+    }
+
+    let (owner, repo, number) = pr_query(&req)?;
+    let query = req.query.get("q").map_or("", String::as_str);
+
+    let pr = provider.get_pr(owner, repo, number).await?;
+    let comments = provider
+        .get_comments(owner, repo, number)
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to fetch comments for {owner}/{repo} #{number}: {e}");
+            Vec::new()
+        });
+    let collaborators = provider
+        .get_collaborators(owner, repo)
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to fetch collaborators of {owner}/{repo}: {e}");
+            Vec::new()
+        });
+
+    let mut users = vec![pr.author];
+    users.extend(pr.assignees);
+    users.extend(pr.reviewers);
+    collect_comment_authors(&comments, &mut users);
+    users.extend(collaborators);
+
+    Ok(suggestions::suggestions_response(
+        users.into_iter().map(|user| Suggestion {
+            label: user.username.clone(),
+            value: user.username,
+        }),
+        query,
+    ))
+}
+
+/// Suggest `:emoji:` shortcodes matching the `q` query parameter, once at
+/// least one character of the shortcode has been typed.
+fn emoji_route(req: &RouteRequest) -> Result<Content, RouteError> {
+    if !matches!(req.method, Method::Get) {
+        return Err(RouteError::UnsupportedMethod);
+    }
+
+    let query = req.query.get("q").map_or("", String::as_str);
+    let matches = if query.is_empty() {
+        Vec::new()
+    } else {
+        chadreview_markdown::search_emoji(query, suggestions::MAX_SUGGESTIONS)
+    };
+
+    Ok(suggestions::suggestions_response(
+        matches.into_iter().map(|(shortcode, emoji)| Suggestion {
+            value: format!("{shortcode}:"),
+            label: format!("{emoji} :{shortcode}:"),
+        }),
+        query,
+    ))
+}
+
+fn collect_comment_authors(comments: &[Comment], users: &mut Vec<User>) {
+    for comment in comments {
+        users.push(comment.author.clone());
+        collect_comment_authors(&comment.replies, users);
+    }
+}
+
+/// Render the posted comment `body` ([`UpdateBody`]) to HTML for the
+/// preview tab of comment forms.
+fn markdown_preview_route(req: &RouteRequest) -> Result<Content, RouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(RouteError::UnsupportedMethod);
+    }
+
+    let body = req.parse_form::<UpdateBody>()?.body;
+
+    Ok(Content::Raw {
+        data: chadreview_markdown::render_markdown(&body)
+            .into_bytes()
+            .into(),
+        content_type: "text/html; charset=utf-8".to_string(),
+    })
+}
+
 async fn update_comment_route(
     req: RouteRequest,
     provider: Arc<dyn GitProvider>,
@@ -294,8 +1266,25 @@ async fn update_comment_route(
     let comment = provider
         .update_comment(owner, repo, number, comment_id, update.body)
         .await?;
+    live::push_comment_updated(
+        &comment,
+        root,
+        provider.provider_name(),
+        owner,
+        repo,
+        number,
+    )
+    .await;
 
-    Ok(render_comment_item(&comment, root, owner, repo, number).into())
+    Ok(render_comment_item(
+        &comment,
+        root,
+        provider.provider_name(),
+        owner,
+        repo,
+        number,
+    )
+    .into())
 }
 
 async fn delete_comment_route(
@@ -333,21 +1322,17 @@ async fn delete_comment_route(
 
     let mut response = Content::builder();
 
-    if root {
-        let path = req
-            .query
-            .get("path")
-            .ok_or(RouteError::MissingQueryParam("path"))?;
-        let line = req
-            .query
-            .get("line")
-            .ok_or(RouteError::MissingQueryParam("line"))?
-            .parse::<LineNumber>()?;
-        let commit_sha = req
-            .query
-            .get("commit_sha")
-            .ok_or(RouteError::MissingQueryParam("commit_sha"))?;
-
+    // Only line comments have a slot on the diff to move their replies to;
+    // general and file-level comments are removed along with their thread
+    let line = req
+        .query
+        .get("line")
+        .map(|line| line.parse::<LineNumber>())
+        .transpose()?;
+    if root
+        && let (Some(path), Some(line), Some(commit_sha)) =
+            (req.query.get("path"), line, req.query.get("commit_sha"))
+    {
         let mut comment = provider
             .get_comment(owner, repo, number, comment_id, true)
             .await?;
@@ -361,6 +1346,7 @@ async fn delete_comment_route(
                 std::iter::once(&root),
                 path,
                 line,
+                provider.provider_name(),
                 owner,
                 repo,
                 number,
@@ -373,6 +1359,7 @@ async fn delete_comment_route(
     provider
         .delete_comment(owner, repo, number, comment_id)
         .await?;
+    live::push_comment_deleted(comment_id).await;
 
     Ok(response.build())
 }
@@ -409,15 +1396,64 @@ async fn resolve_comment_route(
         .ok_or(RouteError::MissingQueryParam("resolved"))?
         .parse::<bool>()?;
 
-    provider
-        .resolve_comment(owner, repo, number, comment_id, resolved)
-        .await?;
+    // The thread ID is known when the comment was rendered from a review
+    // thread, saving a lookup
+    match (req.query.get("thread_id"), resolved) {
+        (Some(thread_id), true) => provider.resolve_thread(thread_id).await?,
+        (Some(thread_id), false) => provider.unresolve_thread(thread_id).await?,
+        (None, _) => {
+            provider
+                .resolve_comment(owner, repo, number, comment_id, resolved)
+                .await?;
+        }
+    }
 
     let comment = provider
         .get_comment(owner, repo, number, comment_id, true)
         .await?;
 
-    Ok(render_comment_thread(comment_id, &comment, 0, owner, repo, number).into())
+    Ok(render_comment_thread(
+        comment_id,
+        &comment,
+        0,
+        provider.provider_name(),
+        owner,
+        repo,
+        number,
+    )
+    .into())
+}
+
+/// Toggle the viewer's [`ReactionBody`] reaction on comment `id`, returning
+/// the updated reaction pills.
+async fn reaction_route(
+    req: RouteRequest,
+    provider: Arc<dyn GitProvider>,
+) -> Result<Container, RouteError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(RouteError::UnsupportedMethod);
+    }
+
+    let owner = req
+        .query
+        .get("owner")
+        .ok_or(RouteError::MissingQueryParam("owner"))?;
+    let repo = req
+        .query
+        .get("repo")
+        .ok_or(RouteError::MissingQueryParam("repo"))?;
+    let comment_id = req
+        .query
+        .get("id")
+        .ok_or(RouteError::MissingQueryParam("id"))?
+        .parse::<u64>()?;
+    let content = req.parse_form::<ReactionBody>()?.content;
+
+    let reactions = provider
+        .toggle_reaction(owner, repo, comment_id, content)
+        .await?;
+
+    Ok(render_reactions(comment_id, &reactions, owner, repo).into())
 }
 
 async fn expand_comment_route(
@@ -452,7 +1488,16 @@ async fn expand_comment_route(
         .await?;
 
     // Render with collapse ignored (temporarily expanded)
-    Ok(render_comment_thread_expanded(comment_id, &comment, 0, owner, repo, number).into())
+    Ok(render_comment_thread_expanded(
+        comment_id,
+        &comment,
+        0,
+        provider.provider_name(),
+        owner,
+        repo,
+        number,
+    )
+    .into())
 }
 
 async fn collapse_comment_route(
@@ -487,13 +1532,23 @@ async fn collapse_comment_route(
         .await?;
 
     // Render with normal logic (respects resolved flag for collapsing)
-    Ok(render_comment_thread(comment_id, &comment, 0, owner, repo, number).into())
+    Ok(render_comment_thread(
+        comment_id,
+        &comment,
+        0,
+        provider.provider_name(),
+        owner,
+        repo,
+        number,
+    )
+    .into())
 }
 
 fn render_comment_thread_expanded(
     root_comment_id: u64,
     comment: &chadreview_pr_models::Comment,
     depth: usize,
+    provider: &str,
     owner: &str,
     repo: &str,
     number: u64,
@@ -537,31 +1592,148 @@ fn render_comment_thread_expanded(
                     }
                 }
             }
-            (render_comment_item(comment, is_root, owner, repo, number))
+            (render_comment_item(comment, is_root, provider, owner, repo, number))
             (render_reply_form(root_comment_id, comment.id, owner, repo, number))
             @for reply in &comment.replies {
-                (render_comment_thread_expanded(root_comment_id, reply, depth + 1, owner, repo, number))
+                (render_comment_thread_expanded(root_comment_id, reply, depth + 1, provider, owner, repo, number))
             }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn render_pr_view(
     pr: &chadreview_pr_models::PullRequest,
+    checks: &[chadreview_pr_models::Check],
+    rate_limit: Option<&chadreview_pr_models::RateLimitStatus>,
+    tab: &PrTab,
+    commits: &[chadreview_pr_models::Commit],
     diffs: &[chadreview_pr_models::DiffFile],
     comments: &[chadreview_pr_models::Comment],
+    (viewed_paths, generated_paths): (&HashSet<String>, &HashSet<String>),
+    (checklist_items, checked_items): (&[String], &HashSet<String>),
+    pending_review: Option<&chadreview_pr_models::PendingReview>,
+    theme: Theme,
     owner: &str,
     repo: &str,
     number: u64,
 ) -> Container {
     use hyperchad::template::container;
 
+    let viewed_files = diffs
+        .iter()
+        .filter(|file| viewed_paths.contains(&file.filename))
+        .count();
+    let mentions_url = autocomplete::pr_mentions_url(owner, repo, number);
+    let checklist_url = checklist::pr_checklist_url(owner, repo, number);
+
     container! {
         div class="pr-view" {
-            (chadreview_app_ui::pr_header::render_pr_header(pr))
-            (general_comments::render_general_comments_section(comments, owner, repo, number))
-            (chadreview_app_ui::diff_viewer::render(&pr.head_sha, diffs, comments, owner, repo, number))
+            (settings::theme_stylesheet(theme))
+            (chadreview_markdown::markdown_stylesheet())
+            (permalink::render_permalink_script())
+            (copy::render_copy_script())
+            (markdown_preview::render_markdown_preview_script())
+            (autocomplete::render_autocomplete_script(&[('@', mentions_url.as_str()), (':', autocomplete::EMOJI_ROUTE)]))
+            (pr_header::render_pr_header(pr, checks, (viewed_files, diffs.len())))
+            (checklist::render_checklist(checklist_items, checked_items, &checklist_url))
+            (general_comments::render_general_comments_section(comments, &pr.provider, owner, repo, number))
+            (commits_tab::render_pr_tabs(owner, repo, number, tab, commits.len()))
+            @match tab {
+                PrTab::Files => {
+                    (chadreview_app_ui::diff_viewer::render(&pr.head_sha, diffs, comments, viewed_paths, generated_paths, &pr.provider, owner, repo, number))
+                }
+                PrTab::Commits => {
+                    (commits_tab::render_commit_list(commits, owner, repo, number))
+                }
+                PrTab::Commit(sha) => {
+                    (commits_tab::render_commit_banner(sha, commits.iter().find(|c| &c.sha == sha), owner, repo, number))
+                    (chadreview_app_ui::diff_viewer::render_readonly(diffs))
+                }
+            }
+            (review_form::render_review_form(owner, repo, number, pending_review))
+            (merge_panel::render_merge_panel(pr, checks))
+            (footer::render_footer(rate_limit))
         }
     }
     .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(json: serde_json::Value) -> WebhookEvent {
+        let user = serde_json::json!({
+            "id": 1,
+            "login": "alice",
+            "avatar_url": "",
+            "html_url": "",
+        });
+        let mut event = serde_json::json!({
+            "pull_request": {
+                "number": 7,
+                "title": "Fix",
+                "state": "open",
+                "head": { "ref": "fix", "sha": "b" },
+                "base": { "ref": "main", "sha": "a" },
+            },
+            "repository": { "name": "repo", "owner": user, "full_name": "alice/repo" },
+        });
+        let serde_json::Value::Object(fields) = json else {
+            unreachable!()
+        };
+        for (key, mut value) in fields {
+            if let Some(object) = value.as_object_mut() {
+                object.insert("user".to_string(), user.clone());
+            }
+            event[key] = value;
+        }
+        serde_json::from_value(event).unwrap()
+    }
+
+    #[test]
+    fn test_review_with_comments_is_received_once() {
+        let mut events: Vec<_> = (0..3)
+            .map(|id| {
+                event(serde_json::json!({
+                    "type": "pull_request_review_comment",
+                    "action": "created",
+                    "comment": {
+                        "id": id,
+                        "body": "Nit",
+                        "path": "src/lib.rs",
+                        "commit_id": "b",
+                        "original_commit_id": "b",
+                        "line": 1,
+                        "original_line": 1,
+                        "side": "RIGHT",
+                        "created_at": "2024-01-01T00:00:00Z",
+                        "updated_at": "2024-01-01T00:00:00Z",
+                        "in_reply_to_id": null,
+                    },
+                }))
+            })
+            .collect();
+        events.push(event(serde_json::json!({
+            "type": "pull_request_review",
+            "action": "submitted",
+            "review": {
+                "id": 10,
+                "body": "Looks good",
+                "state": "approved",
+                "commit_id": "b",
+                "submitted_at": "2024-01-01T00:00:00Z",
+            },
+        })));
+
+        let received: Vec<_> = events
+            .iter()
+            .filter_map(|event| review_received("alice", "repo", 7, event))
+            .collect();
+
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0]["author"], "alice");
+        assert_eq!(received[0]["body"], "Looks good");
+    }
+}
@@ -0,0 +1,624 @@
+//! The `/settings` page for editing the global config file.
+//!
+//! Saving only touches `chadreview.toml` in the config directory.
+//! Repository config files and environment variables still override what is
+//! saved here, and provider and relay settings take effect on the next
+//! start. Saved replies are kept in their own file and edited in their own
+//! section.
+
+use std::path::Path;
+
+use chadreview_config::{Config, GeneratedFiles, SavedReplies, SavedReply, Theme};
+use hyperchad::router::{Container, RouteRequest, Router};
+use hyperchad_template::{Containers, Selector, container};
+use switchy::http::models::Method;
+
+const SETTINGS_ID: &str = "settings";
+const SAVED_REPLIES_ID: &str = "saved-replies";
+
+/// Error type for settings routes.
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    /// The request method is not supported for this route.
+    #[error("Unsupported method")]
+    UnsupportedMethod,
+    /// Form parse error.
+    #[error("Invalid form body: {0}")]
+    InvalidBody(#[from] hyperchad::router::ParseError),
+}
+
+/// Form data for saving settings. Fields left out of the form are not
+/// changed.
+#[derive(Debug, Default, serde::Deserialize)]
+struct SettingsForm {
+    default_agent: Option<String>,
+    /// Dollars; empty removes the budget.
+    ai_budget: Option<String>,
+    theme: Option<String>,
+    include_untracked: Option<String>,
+    recurse_submodules: Option<String>,
+    relay_url: Option<String>,
+    /// Tokens are never shown, so an empty token keeps the saved one.
+    relay_token: Option<String>,
+    webhook_secret: Option<String>,
+    github_token: Option<String>,
+    github_url: Option<String>,
+    gitea_url: Option<String>,
+    gitea_token: Option<String>,
+    default_repo: Option<String>,
+    /// One repository path per line.
+    repo_paths: Option<String>,
+}
+
+/// Form data for adding or removing a saved reply.
+#[derive(Debug, Default, serde::Deserialize)]
+struct SavedReplyForm {
+    name: Option<String>,
+    body: Option<String>,
+    /// Name of the reply to remove, instead of adding one.
+    remove: Option<String>,
+}
+
+impl SavedReplyForm {
+    fn apply(self, replies: &mut SavedReplies) -> Result<&'static str, String> {
+        if let Some(name) = self.remove {
+            return if replies.remove(&name) {
+                Ok("Saved reply removed.")
+            } else {
+                Err(format!("No saved reply named {name}"))
+            };
+        }
+
+        let name = self.name.as_deref().map(str::trim).unwrap_or_default();
+        let body = self.body.unwrap_or_default();
+        if name.is_empty() || body.trim().is_empty() {
+            return Err("A saved reply needs a name and a text".to_string());
+        }
+        replies.upsert(SavedReply::new(name, body));
+
+        Ok("Saved reply saved.")
+    }
+}
+
+impl SettingsForm {
+    fn apply(self, config: &mut Config) -> Result<(), String> {
+        let non_empty = |value: String| {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+
+        if let Some(theme) = self.theme {
+            config.appearance.theme = theme.parse::<Theme>().map_err(|e| e.to_string())?;
+        }
+        if let Some(include_untracked) = self.include_untracked {
+            config.diff.include_untracked = include_untracked == "true";
+        }
+        if let Some(recurse_submodules) = self.recurse_submodules {
+            config.diff.recurse_submodules = recurse_submodules == "true";
+        }
+        if let Some(agent) = self.default_agent {
+            config.ai.default_agent = non_empty(agent);
+        }
+        if let Some(budget) = self.ai_budget {
+            config.ai.budget = non_empty(budget)
+                .map(|budget| {
+                    budget
+                        .trim_start_matches('$')
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|budget| budget.is_finite() && *budget >= 0.0)
+                        .ok_or_else(|| format!("Invalid AI budget: {budget}"))
+                })
+                .transpose()?;
+        }
+        if let Some(url) = self.relay_url {
+            config.relay.url = non_empty(url);
+        }
+        if let Some(token) = self.relay_token.and_then(non_empty) {
+            config.relay.token = Some(token);
+        }
+        if let Some(secret) = self.webhook_secret.and_then(non_empty) {
+            config.relay.webhook_secret = Some(secret);
+        }
+        if let Some(url) = self.github_url {
+            config.providers.github_url = non_empty(url);
+        }
+        if let Some(url) = self.gitea_url {
+            config.providers.gitea_url = non_empty(url);
+        }
+        if let Some(token) = self.github_token.and_then(non_empty) {
+            config.providers.github_token = Some(token);
+        }
+        if let Some(token) = self.gitea_token.and_then(non_empty) {
+            config.providers.gitea_token = Some(token);
+        }
+        if let Some(path) = self.default_repo {
+            config.repos.default = non_empty(path).map(Into::into);
+        }
+        if let Some(paths) = self.repo_paths {
+            config.repos.paths = paths
+                .lines()
+                .filter_map(|line| non_empty(line.to_string()))
+                .map(Into::into)
+                .collect();
+        }
+
+        Ok(())
+    }
+}
+
+/// Load the effective config, falling back to the defaults if a config file
+/// is broken so a typo doesn't take every page down.
+#[must_use]
+pub fn load_config(repo_path: Option<&Path>) -> Config {
+    Config::load(repo_path).unwrap_or_else(|e| {
+        log::warn!("Failed to load config, using defaults: {e}");
+        Config::default()
+    })
+}
+
+/// Load the generated file patterns for diffs of `repo_path`, falling back
+/// to the configured ones if a repository file can't be read.
+#[must_use]
+pub fn load_generated_files(repo_path: Option<&Path>, config: &Config) -> GeneratedFiles {
+    GeneratedFiles::load(repo_path, config).unwrap_or_else(|e| {
+        log::warn!("Failed to load generated file patterns: {e}");
+        GeneratedFiles::load(None, config).unwrap_or_default()
+    })
+}
+
+/// Load the saved replies, falling back to none if the file is broken.
+#[must_use]
+pub fn load_saved_replies() -> SavedReplies {
+    SavedReplies::load().unwrap_or_else(|e| {
+        log::warn!("Failed to load saved replies: {e}");
+        SavedReplies { replies: vec![] }
+    })
+}
+
+/// Stylesheet applying the configured color theme to a page.
+///
+/// The UI's colors are written for a light background, so the dark theme
+/// inverts the page and flips images back.
+#[must_use]
+pub fn theme_stylesheet(theme: Theme) -> Containers {
+    match theme {
+        Theme::Light => vec![],
+        Theme::Dark => container! {
+            div {
+                raw {
+                    "<style>html { filter: invert(1) hue-rotate(180deg); background: #ffffff; } img, video { filter: invert(1) hue-rotate(180deg); }</style>"
+                }
+            }
+        },
+    }
+}
+
+/// Add the `/settings` routes to an existing router.
+#[must_use]
+pub fn add_settings_routes(router: Router) -> Router {
+    router
+        .with_route_result("/settings", |req: RouteRequest| async move {
+            settings_route(&req)
+        })
+        .with_route_result("/settings/saved-replies", |req: RouteRequest| async move {
+            saved_replies_route(&req)
+        })
+}
+
+fn settings_route(req: &RouteRequest) -> Result<Container, SettingsError> {
+    match req.method {
+        Method::Get => {
+            let (config, error) = match Config::load_global() {
+                Ok(config) => (config, None),
+                Err(e) => (
+                    Config::default(),
+                    Some(format!("Failed to read the config file: {e}")),
+                ),
+            };
+
+            let (replies, replies_error) = match SavedReplies::load() {
+                Ok(replies) => (replies, None),
+                Err(e) => (
+                    SavedReplies { replies: vec![] },
+                    Some(format!("Failed to read the saved replies file: {e}")),
+                ),
+            };
+
+            Ok(container! {
+                div padding=20 gap=16 max-width=800 {
+                    (theme_stylesheet(config.appearance.theme))
+                    h1 font-size=24 font-weight=600 color="#24292f" { "Settings" }
+                    (render_settings(&config, error.as_deref().map(Err)))
+                    (render_saved_replies(&replies, replies_error.as_deref().map(Err)))
+                }
+            }
+            .into())
+        }
+        Method::Post => {
+            let form: SettingsForm = req.parse_form()?;
+
+            // Don't overwrite a config file that failed to parse
+            let mut config = match Config::load_global() {
+                Ok(config) => config,
+                Err(e) => {
+                    let error = format!("Failed to read the config file: {e}");
+                    return Ok(render_settings(&Config::default(), Some(Err(&error))).into());
+                }
+            };
+
+            let status = form
+                .apply(&mut config)
+                .and_then(|()| config.save_global().map_err(|e| e.to_string()))
+                .map(|()| "Settings saved.")
+                .map_err(|e| format!("Failed to save settings: {e}"));
+
+            Ok(render_settings(&config, Some(status.as_deref())).into())
+        }
+        _ => Err(SettingsError::UnsupportedMethod),
+    }
+}
+
+fn saved_replies_route(req: &RouteRequest) -> Result<Container, SettingsError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(SettingsError::UnsupportedMethod);
+    }
+
+    let form: SavedReplyForm = req.parse_form()?;
+
+    // Don't overwrite a saved replies file that failed to parse
+    let mut replies = match SavedReplies::load() {
+        Ok(replies) => replies,
+        Err(e) => {
+            let error = format!("Failed to read the saved replies file: {e}");
+            return Ok(
+                render_saved_replies(&SavedReplies { replies: vec![] }, Some(Err(&error))).into(),
+            );
+        }
+    };
+
+    let status = form.apply(&mut replies).and_then(|message| {
+        replies
+            .save()
+            .map(|()| message)
+            .map_err(|e| format!("Failed to save saved replies: {e}"))
+    });
+
+    Ok(render_saved_replies(&replies, Some(status.as_deref())).into())
+}
+
+fn render_settings(config: &Config, status: Option<Result<&str, &str>>) -> Containers {
+    let config_path = Config::global_path().map_or_else(
+        || "No config directory found".to_string(),
+        |path| path.display().to_string(),
+    );
+    let env_overrides = Config::env_overrides();
+    let repo_paths = config
+        .repos
+        .paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    container! {
+        div id=(SETTINGS_ID) gap=16 {
+            span font-size=13 color="#57606a" {
+                (format!("Saved to {config_path}. A repository's .chadreview/config.toml overrides these settings for that repository."))
+            }
+            @if !env_overrides.is_empty() {
+                span font-size=13 color="#9a6700" {
+                    (format!("Overridden by environment variables: {}", env_overrides.join(", ")))
+                }
+            }
+            @match status {
+                Some(Ok(message)) => {
+                    span font-size=14 color="#1a7f37" { (message) }
+                }
+                Some(Err(message)) => {
+                    span font-size=14 color="#cf222e" { (message) }
+                }
+                None => {}
+            }
+
+            (render_section("Appearance", container! {
+                form
+                    hx-post="/settings"
+                    hx-target=(Selector::Id(SETTINGS_ID.to_string()))
+                    hx-swap="outerHTML"
+                    direction=row
+                    gap=8
+                    align-items=center
+                {
+                    span font-size=14 color="#24292f" { "Theme" }
+                    (render_choice_button("theme", Theme::Light.as_str(), "Light", config.appearance.theme == Theme::Light))
+                    (render_choice_button("theme", Theme::Dark.as_str(), "Dark", config.appearance.theme == Theme::Dark))
+                }
+            }))
+
+            (render_section("Local diffs", container! {
+                form
+                    hx-post="/settings"
+                    hx-target=(Selector::Id(SETTINGS_ID.to_string()))
+                    hx-swap="outerHTML"
+                    direction=row
+                    gap=8
+                    align-items=center
+                {
+                    span font-size=14 color="#24292f" { "Include untracked files" }
+                    (render_choice_button("include_untracked", "true", "On", config.diff.include_untracked))
+                    (render_choice_button("include_untracked", "false", "Off", !config.diff.include_untracked))
+                }
+                form
+                    hx-post="/settings"
+                    hx-target=(Selector::Id(SETTINGS_ID.to_string()))
+                    hx-swap="outerHTML"
+                    direction=row
+                    gap=8
+                    align-items=center
+                {
+                    span font-size=14 color="#24292f" { "Include submodule changes" }
+                    (render_choice_button("recurse_submodules", "true", "On", config.diff.recurse_submodules))
+                    (render_choice_button("recurse_submodules", "false", "Off", !config.diff.recurse_submodules))
+                }
+            }))
+
+            form
+                hx-post="/settings"
+                hx-target=(Selector::Id(SETTINGS_ID.to_string()))
+                hx-swap="outerHTML"
+                gap=16
+            {
+                (render_section("AI", container! {
+                    (render_text_field("default_agent", "Default agent", config.ai.default_agent.as_deref(), "e.g. opencode:code"))
+                    (render_text_field("ai_budget", "Budget per repository (USD)", config.ai.budget.map(|budget| budget.to_string()).as_deref(), "Leave empty for no budget"))
+                }))
+                (render_section("Providers", container! {
+                    (render_secret_field("github_token", "GitHub token", config.providers.github_token.is_some()))
+                    (render_text_field("github_url", "GitHub Enterprise URL", config.providers.github_url.as_deref(), "Leave empty for github.com"))
+                    (render_text_field("gitea_url", "Gitea/Forgejo URL", config.providers.gitea_url.as_deref(), "Leave empty to use GitHub"))
+                    (render_secret_field("gitea_token", "Gitea/Forgejo token", config.providers.gitea_token.is_some()))
+                }))
+                (render_section("Relay", container! {
+                    (render_text_field("relay_url", "Relay URL", config.relay.url.as_deref(), "Leave empty to disable real-time updates"))
+                    (render_secret_field("relay_token", "Relay token", config.relay.token.is_some()))
+                    (render_secret_field("webhook_secret", "Direct webhook secret", config.relay.webhook_secret.is_some()))
+                }))
+                (render_section("Repositories", container! {
+                    (render_text_field(
+                        "default_repo",
+                        "Default repository",
+                        config.repos.default.as_ref().map(|path| path.to_string_lossy()).as_deref(),
+                        "Opened by /local when no repo is given (default: current directory)",
+                    ))
+                    div gap=4 {
+                        span font-size=14 color="#24292f" { "Other repositories (one path per line)" }
+                        textarea
+                            name="repo_paths"
+                            padding=8
+                            border="1px solid #d0d7de"
+                            border-radius=6
+                            font-size=14
+                            font-family="monospace"
+                            height=80
+                        {
+                            (repo_paths)
+                        }
+                    }
+                    (render_repo_links(config))
+                }))
+                button
+                    type=submit
+                    background="#1f883d"
+                    color="#ffffff"
+                    padding-x=16
+                    padding-y=8
+                    border-radius=6
+                    font-weight=600
+                    font-size=14
+                    cursor=pointer
+                {
+                    "Save"
+                }
+            }
+        }
+    }
+}
+
+fn render_saved_replies(replies: &SavedReplies, status: Option<Result<&str, &str>>) -> Containers {
+    let replies_path = SavedReplies::path().map_or_else(
+        || "No config directory found".to_string(),
+        |path| path.display().to_string(),
+    );
+
+    container! {
+        div id=(SAVED_REPLIES_ID) {
+            (render_section("Saved replies", container! {
+                span font-size=13 color="#57606a" {
+                    (format!("Inserted from the picker in comment forms. {{file}} and {{line}} are replaced with the commented file and line. Saved to {replies_path}."))
+                }
+                @match status {
+                    Some(Ok(message)) => {
+                        span font-size=14 color="#1a7f37" { (message) }
+                    }
+                    Some(Err(message)) => {
+                        span font-size=14 color="#cf222e" { (message) }
+                    }
+                    None => {}
+                }
+                @for reply in &replies.replies {
+                    form
+                        hx-post="/settings/saved-replies"
+                        hx-target=(Selector::Id(SAVED_REPLIES_ID.to_string()))
+                        hx-swap="outerHTML"
+                        direction=row
+                        gap=8
+                        align-items=center
+                    {
+                        span font-size=14 font-weight=600 color="#24292f" min-width=120 { (reply.name) }
+                        span font-size=13 color="#57606a" flex=1 white-space=preserve-wrap { (reply.body) }
+                        button
+                            type=submit
+                            name="remove"
+                            value=(reply.name)
+                            background="#ffffff"
+                            border="1px solid #d0d7de"
+                            color="#cf222e"
+                            padding-x=12
+                            padding-y=4
+                            border-radius=6
+                            font-size=14
+                            cursor=pointer
+                        {
+                            "Remove"
+                        }
+                    }
+                }
+                form
+                    hx-post="/settings/saved-replies"
+                    hx-target=(Selector::Id(SAVED_REPLIES_ID.to_string()))
+                    hx-swap="outerHTML"
+                    gap=8
+                {
+                    (render_text_field("name", "Name", None, "e.g. Nit (saving an existing name replaces it)"))
+                    div gap=4 {
+                        span font-size=14 color="#24292f" { "Text" }
+                        textarea
+                            name="body"
+                            placeholder="e.g. Please add a test for {file}:{line}."
+                            padding=8
+                            border="1px solid #d0d7de"
+                            border-radius=6
+                            font-size=14
+                            height=60;
+                    }
+                    button
+                        type=submit
+                        background="#1f883d"
+                        color="#ffffff"
+                        padding-x=16
+                        padding-y=8
+                        border-radius=6
+                        font-weight=600
+                        font-size=14
+                        cursor=pointer
+                    {
+                        "Add saved reply"
+                    }
+                }
+            }))
+        }
+    }
+}
+
+fn render_section(title: &str, content: Containers) -> Containers {
+    container! {
+        div gap=8 padding=12 border="1px solid #d0d7de" border-radius=6 {
+            h2 font-size=16 font-weight=600 color="#24292f" { (title) }
+            (content)
+        }
+    }
+}
+
+fn render_text_field(
+    name: &str,
+    label: &str,
+    value: Option<&str>,
+    placeholder: &str,
+) -> Containers {
+    container! {
+        div gap=4 {
+            span font-size=14 color="#24292f" { (label) }
+            input
+                type=text
+                name=(name)
+                value=(value.unwrap_or_default())
+                placeholder=(placeholder)
+                padding=8
+                border="1px solid #d0d7de"
+                border-radius=6
+                font-size=14;
+        }
+    }
+}
+
+fn render_secret_field(name: &str, label: &str, is_set: bool) -> Containers {
+    let placeholder = if is_set {
+        "Saved (leave empty to keep)"
+    } else {
+        "Not set"
+    };
+
+    render_text_field(name, label, None, placeholder)
+}
+
+fn render_choice_button(name: &str, value: &str, label: &str, selected: bool) -> Containers {
+    container! {
+        @if selected {
+            button
+                type=submit
+                name=(name)
+                value=(value)
+                background="#ddf4ff"
+                border="1px solid #0969da"
+                color="#0969da"
+                padding-x=12
+                padding-y=4
+                border-radius=6
+                font-size=14
+                font-weight=600
+                cursor=pointer
+            {
+                (label)
+            }
+        } @else {
+            button
+                type=submit
+                name=(name)
+                value=(value)
+                background="#ffffff"
+                border="1px solid #d0d7de"
+                color="#24292f"
+                padding-x=12
+                padding-y=4
+                border-radius=6
+                font-size=14
+                cursor=pointer
+            {
+                (label)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "local-git")]
+fn render_repo_links(config: &Config) -> Containers {
+    let repos = config.repos.default.iter().chain(
+        config
+            .repos
+            .paths
+            .iter()
+            .filter(|path| Some(*path) != config.repos.default.as_ref()),
+    );
+
+    container! {
+        div direction=row gap=12 {
+            @for path in repos {
+                anchor
+                    href=(format!("/local?repo={}", urlencoding::encode(&path.to_string_lossy())))
+                    color="#0969da"
+                    font-size=13
+                    font-family="monospace"
+                {
+                    (path.display().to_string())
+                }
+            }
+            anchor href="/repos" color="#0969da" font-size=13 { "All repositories" }
+        }
+    }
+}
+
+#[cfg(not(feature = "local-git"))]
+fn render_repo_links(_config: &Config) -> Containers {
+    vec![]
+}
@@ -0,0 +1,161 @@
+//! Read-only share mode for a local review.
+//!
+//! Serves a single, fixed local review at `/share?token=<token>` so it can be
+//! looked over from another machine. The share router only contains that
+//! route: comment mutation, AI execution and the `/local` repository
+//! override are not reachable from it.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chadreview_app_ui::local_share;
+use chadreview_git_backend::GitBackend;
+use chadreview_local_diff_models::{DiffSpec, DiffSpecError};
+use hyperchad::{
+    router::{Container, RouteRequest, Router},
+    template::container,
+};
+use subtle::ConstantTimeEq;
+use switchy::http::models::Method;
+use switchy::uuid::Uuid;
+
+use crate::local_routes::{LocalRouteError, load_shared_review, run_blocking};
+
+/// Error type for share mode.
+#[derive(Debug, thiserror::Error)]
+pub enum ShareError {
+    /// The request method is not supported for this route.
+    #[error("Unsupported method")]
+    UnsupportedMethod,
+    /// The share token is missing or does not match.
+    #[error("Invalid share token")]
+    InvalidToken,
+    /// Invalid `SHARE_DIFF` value.
+    #[error("Invalid diff spec: {0}")]
+    DiffSpec(#[from] DiffSpecError),
+    /// The current directory could not be determined.
+    #[error("Invalid repository path: {0}")]
+    InvalidRepoPath(#[from] std::io::Error),
+    /// The review could not be loaded.
+    #[error(transparent)]
+    Review(#[from] LocalRouteError),
+}
+
+/// What is shared and how access is guarded.
+#[derive(Debug, Clone)]
+pub struct ShareConfig {
+    /// Token required in the `token` query parameter.
+    pub token: String,
+    /// Repository being reviewed.
+    pub repo_path: PathBuf,
+    /// Diff being reviewed.
+    pub spec: DiffSpec,
+}
+
+impl ShareConfig {
+    /// Read share mode settings from the environment.
+    ///
+    /// Returns `None` unless `SHARE` is set to `1` or `true`. Other
+    /// variables:
+    /// - `SHARE_TOKEN` - Access token (a random token is generated if unset)
+    /// - `SHARE_REPO` - Repository path (defaults to CWD)
+    /// - `SHARE_DIFF` - Diff in `/local` query syntax, e.g. `base=main&head=feature`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `SHARE_DIFF` is invalid or the current directory
+    /// cannot be determined.
+    pub fn from_env() -> Result<Option<Self>, ShareError> {
+        let enabled = std::env::var("SHARE")
+            .is_ok_and(|value| value == "1" || value.eq_ignore_ascii_case("true"));
+        if !enabled {
+            return Ok(None);
+        }
+
+        let token = std::env::var("SHARE_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty())
+            .unwrap_or_else(|| Uuid::new_v4().simple().to_string());
+        let repo_path = match std::env::var("SHARE_REPO") {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => std::env::current_dir()?,
+        };
+        let spec = DiffSpec::from_query(&parse_query(
+            &std::env::var("SHARE_DIFF").unwrap_or_default(),
+        ))?;
+
+        Ok(Some(Self {
+            token,
+            repo_path,
+            spec,
+        }))
+    }
+
+    /// Path (including the token) that the shared review is served at.
+    #[must_use]
+    pub fn share_path(&self) -> String {
+        format!("/share?token={}", urlencoding::encode(&self.token))
+    }
+
+    fn is_authorized(&self, req: &RouteRequest) -> bool {
+        req.query
+            .get("token")
+            .is_some_and(|token| token.as_bytes().ct_eq(self.token.as_bytes()).into())
+    }
+}
+
+/// Create a router that serves only the read-only shared review.
+#[must_use]
+pub fn create_share_router<B: GitBackend + Send + Sync + 'static>(
+    backend: Arc<B>,
+    config: ShareConfig,
+) -> Router {
+    let config = Arc::new(config);
+
+    Router::new().with_route_result("/share", move |req: RouteRequest| {
+        let backend = Arc::clone(&backend);
+        let config = Arc::clone(&config);
+        async move { share_route(&req, backend, config).await }
+    })
+}
+
+/// Handle the `/share` route.
+///
+/// Only the `token` query parameter is read; the repository and diff come
+/// from the [`ShareConfig`].
+async fn share_route<B: GitBackend + 'static>(
+    req: &RouteRequest,
+    backend: Arc<B>,
+    config: Arc<ShareConfig>,
+) -> Result<Container, ShareError> {
+    if !matches!(req.method, Method::Get) {
+        return Err(ShareError::UnsupportedMethod);
+    }
+    if !config.is_authorized(req) {
+        return Err(ShareError::InvalidToken);
+    }
+
+    let review =
+        run_blocking(move || load_shared_review(backend, &config.repo_path, &config.spec)).await?;
+
+    Ok(container! {
+        div padding=20 gap=20 {
+            (chadreview_markdown::markdown_stylesheet())
+            (local_share::render_shared_review(&review.info, &review.diffs, &review.comments))
+        }
+    }
+    .into())
+}
+
+/// Parse a `key=value&key=value` string into query parameters.
+fn parse_query(query: &str) -> BTreeMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, "true"));
+            (key.to_string(), value.to_string())
+        })
+        .collect()
+}
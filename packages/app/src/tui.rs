@@ -0,0 +1,182 @@
+//! `chadreview tui` - terminal frontend for local reviews.
+//!
+//! Uses the same loading and thread state logic as the `/local` routes.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chadreview_git_backend::GitBackend;
+use chadreview_local_comment::LocalCommentStore;
+use chadreview_local_comment_models::ThreadState;
+use chadreview_local_diff_models::{CommitFilter, DiffSpec, DiffSpecError, FileOrder};
+use chadreview_tui::{ReviewSnapshot, ReviewSource, TuiError};
+use switchy::uuid::Uuid;
+
+use crate::local_routes::{LocalRouteError, load_local_review, set_thread_state};
+
+/// Error type for the `tui` command.
+#[derive(Debug, thiserror::Error)]
+pub enum TuiCommandError {
+    /// Invalid diff arguments.
+    #[error("Invalid diff spec: {0}")]
+    DiffSpec(#[from] DiffSpecError),
+    /// The current directory could not be determined.
+    #[error("Invalid repository path: {0}")]
+    InvalidRepoPath(#[from] std::io::Error),
+    /// Error running the terminal frontend.
+    #[error(transparent)]
+    Tui(#[from] TuiError),
+}
+
+/// Arguments for `chadreview tui`.
+///
+/// The diff options mirror the `/local` route's query parameters.
+#[derive(Debug, clap::Args)]
+pub struct TuiArgs {
+    /// Path to the repository (defaults to the current directory)
+    #[arg(long)]
+    repo: Option<PathBuf>,
+
+    /// Base ref of a range diff (requires --head)
+    #[arg(long, requires = "head")]
+    base: Option<String>,
+
+    /// Head ref of a range diff (requires --base)
+    #[arg(long, requires = "base")]
+    head: Option<String>,
+
+    /// Diff against the merge base of --base and --head
+    #[arg(long)]
+    three_dot: bool,
+
+    /// Show a single commit
+    #[arg(long)]
+    commit: Option<String>,
+
+    /// Show multiple commits (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    commits: Vec<String>,
+
+    /// Squash multiple commits into one diff
+    #[arg(long)]
+    squashed: bool,
+
+    /// Only show staged changes
+    #[arg(long)]
+    staged: bool,
+
+    /// What to diff the working tree against
+    #[arg(long)]
+    against: Option<String>,
+
+    /// Exclude untracked files
+    #[arg(long)]
+    no_untracked: bool,
+}
+
+impl TuiArgs {
+    /// Build the diff spec using the same parsing as the `/local` route.
+    fn diff_spec(&self) -> Result<DiffSpec, DiffSpecError> {
+        let mut query = BTreeMap::new();
+
+        let values = [
+            ("base", self.base.clone()),
+            ("head", self.head.clone()),
+            ("commit", self.commit.clone()),
+            (
+                "commits",
+                (!self.commits.is_empty()).then(|| self.commits.join(",")),
+            ),
+            ("against", self.against.clone()),
+        ];
+        let flags = [
+            ("three_dot", self.three_dot),
+            ("staged", self.staged),
+            ("untracked", !self.no_untracked),
+        ];
+
+        for (key, value) in values {
+            if let Some(value) = value {
+                query.insert(key.to_string(), value);
+            }
+        }
+        for (key, enabled) in flags {
+            query.insert(key.to_string(), enabled.to_string());
+        }
+        if self.squashed {
+            query.insert("mode".to_string(), "squashed".to_string());
+        }
+
+        DiffSpec::from_query(&query)
+    }
+}
+
+/// Review data source backed by a local repository.
+struct LocalReviewSource<B: GitBackend> {
+    backend: Arc<B>,
+    repo_path: PathBuf,
+    spec: DiffSpec,
+}
+
+impl<B: GitBackend> ReviewSource for LocalReviewSource<B> {
+    type Error = LocalRouteError;
+
+    fn load(&self) -> Result<ReviewSnapshot, Self::Error> {
+        let review = load_local_review(
+            Arc::clone(&self.backend),
+            &self.repo_path,
+            &self.spec,
+            &CommitFilter::default(),
+            FileOrder::default(),
+            None,
+            None,
+        )?;
+
+        Ok(ReviewSnapshot {
+            info: review.info,
+            diffs: review.diffs,
+            threads: review.comments,
+            viewed_paths: review.viewed_paths,
+        })
+    }
+
+    fn set_file_viewed(&self, path: &str, viewed: bool) -> Result<(), Self::Error> {
+        let store = LocalCommentStore::new(&self.repo_path)?;
+
+        if viewed {
+            store.mark_file_viewed(path)?;
+        } else {
+            store.mark_file_unviewed(path)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_thread_state(&self, thread_id: Uuid, state: ThreadState) -> Result<(), Self::Error> {
+        set_thread_state(&self.repo_path, thread_id, state).map(|_| ())
+    }
+}
+
+/// Run the terminal frontend on the diff of `args`.
+///
+/// # Errors
+///
+/// Returns an error if the arguments describe an invalid diff or the
+/// terminal frontend fails.
+pub fn run<B: GitBackend>(backend: Arc<B>, args: &TuiArgs) -> Result<(), TuiCommandError> {
+    let repo_path = match &args.repo {
+        Some(path) => path.clone(),
+        None => std::env::current_dir()?,
+    };
+
+    let source = LocalReviewSource {
+        backend,
+        repo_path,
+        spec: args.diff_spec()?,
+    };
+
+    chadreview_tui::run(&source)?;
+
+    Ok(())
+}
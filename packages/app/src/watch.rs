@@ -0,0 +1,217 @@
+//! Live refresh of working tree diffs while files are edited.
+//!
+//! Viewing a working tree diff at `/local` starts watching its repository.
+//! When files of the diff change on disk, the diff is recomputed and just
+//! those files are re-rendered and pushed over SSE, replacing them on the
+//! page, or removed once they no longer differ. Files that start differing
+//! after the page was loaded appear on the next load.
+//!
+//! Each repository is watched once, for the working tree diff of it that
+//! was viewed last.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use chadreview_git_backend::GitBackend;
+use chadreview_local_diff_models::DiffSpec;
+use chadreview_pr_models::DiffFile;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use switchy::unsync::sync::mpsc;
+
+use crate::local_routes::{render_changed_files, run_blocking};
+use crate::sse::push_diff_files;
+
+/// How long to wait for more changes after one, so saving several files or
+/// an editor's write-and-rename make a single refresh.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// The working tree diff being viewed in a watched repository.
+#[derive(Debug, Clone)]
+struct WatchedView {
+    spec: DiffSpec,
+    show_all: bool,
+    /// Number of context lines around changes, `None` for git's default.
+    context_lines: Option<u32>,
+    /// Paths of the files in the diff, as last rendered.
+    paths: HashSet<String>,
+}
+
+struct WatchedRepo {
+    view: Mutex<WatchedView>,
+    /// Stops watching when dropped.
+    _watcher: RecommendedWatcher,
+}
+
+/// The repositories whose working tree diffs are refreshed as they change.
+pub struct WorkingTreeWatchers<B: GitBackend> {
+    backend: Arc<B>,
+    repos: Mutex<HashMap<PathBuf, Arc<WatchedRepo>>>,
+}
+
+impl<B: GitBackend + Send + Sync + 'static> WorkingTreeWatchers<B> {
+    /// Watch repositories, recomputing their diffs with `backend`.
+    #[must_use]
+    pub fn new(backend: Arc<B>) -> Self {
+        Self {
+            backend,
+            repos: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Refresh `spec`'s view of `repo_path` as its `diffs` change. Does
+    /// nothing unless `spec` is a working tree diff.
+    ///
+    /// A repository that is already watched switches to this view.
+    pub fn watch(
+        &self,
+        repo_path: &Path,
+        spec: &DiffSpec,
+        show_all: bool,
+        context_lines: Option<u32>,
+        diffs: &[DiffFile],
+    ) {
+        if !matches!(spec, DiffSpec::WorkingTree { .. }) {
+            return;
+        }
+
+        let view = WatchedView {
+            spec: spec.clone(),
+            show_all,
+            context_lines,
+            paths: diffs.iter().map(|file| file.filename.clone()).collect(),
+        };
+
+        let mut repos = self.repos.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(repo) = repos.get(repo_path) {
+            *repo.view.lock().unwrap_or_else(PoisonError::into_inner) = view;
+            return;
+        }
+
+        let root = match repo_path.canonicalize() {
+            Ok(root) => root,
+            Err(e) => {
+                log::warn!("Not watching {}: {e}", repo_path.display());
+                return;
+            }
+        };
+
+        let (tx, rx) = mpsc::unbounded::<PathBuf>();
+        let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            match res {
+                Ok(event) => {
+                    for path in event.paths {
+                        let _ = tx.send(path);
+                    }
+                }
+                Err(e) => log::warn!("Working tree watcher error: {e}"),
+            }
+        })
+        .and_then(|mut watcher| {
+            watcher.watch(&root, RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+        let watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                log::warn!("Failed to watch {}: {e}", root.display());
+                return;
+            }
+        };
+
+        log::info!("Watching {} for working tree changes", root.display());
+        let repo = Arc::new(WatchedRepo {
+            view: Mutex::new(view),
+            _watcher: watcher,
+        });
+        repos.insert(repo_path.to_path_buf(), Arc::clone(&repo));
+
+        let backend = Arc::clone(&self.backend);
+        let repo_path = repo_path.to_path_buf();
+        switchy::unsync::task::spawn(async move {
+            while let Ok(path) = rx.recv_async().await {
+                let mut changed = HashSet::from([path]);
+                switchy::unsync::time::sleep(DEBOUNCE).await;
+                while let Ok(path) = rx.try_recv() {
+                    changed.insert(path);
+                }
+
+                let changed = relative_paths(&root, changed);
+                refresh(Arc::clone(&backend), &repo_path, &repo, &changed).await;
+            }
+        });
+    }
+}
+
+/// Re-render the files of the watched view among `changed`, pushing them
+/// to the page.
+async fn refresh<B: GitBackend + 'static>(
+    backend: Arc<B>,
+    repo_path: &Path,
+    repo: &WatchedRepo,
+    changed: &HashSet<String>,
+) {
+    let view = repo
+        .view
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+
+    // Files not on the page have nothing to replace, which also skips the
+    // build output and `.git` churn inside the repository
+    let paths: HashSet<String> = changed.intersection(&view.paths).cloned().collect();
+    if paths.is_empty() {
+        return;
+    }
+
+    let changed_files = match run_blocking({
+        let repo_path = repo_path.to_path_buf();
+        let spec = view.spec.clone();
+        let (show_all, context_lines) = (view.show_all, view.context_lines);
+        move || render_changed_files(backend, &repo_path, &spec, show_all, context_lines, &paths)
+    })
+    .await
+    {
+        Ok(changed_files) => changed_files,
+        Err(e) => {
+            log::warn!(
+                "Failed to refresh working tree diff of {}: {e}",
+                repo_path.display()
+            );
+            return;
+        }
+    };
+
+    {
+        let mut current = repo.view.lock().unwrap_or_else(PoisonError::into_inner);
+        if current.spec == view.spec {
+            for path in &changed_files.removed {
+                current.paths.remove(path);
+            }
+        }
+    }
+
+    log::debug!(
+        "Working tree changed in {}: refreshing {} file(s), removing {}",
+        repo_path.display(),
+        changed_files.files.len(),
+        changed_files.removed.len()
+    );
+    push_diff_files(changed_files.files, &changed_files.removed).await;
+}
+
+/// `paths` relative to the repository `root`, with `/` separators as in
+/// diffs. Paths outside of it are dropped.
+fn relative_paths(root: &Path, paths: HashSet<PathBuf>) -> HashSet<String> {
+    paths
+        .iter()
+        .filter_map(|path| path.strip_prefix(root).ok())
+        .map(|path| {
+            path.components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect()
+}
@@ -0,0 +1,143 @@
+//! Direct webhook mode, for instances GitHub can reach on a public address
+//! or through a tunnel.
+//!
+//! GitHub delivers webhooks straight to the `/webhook` route instead of to
+//! a relay server, and they are parsed and verified the same way the relay
+//! does. Events go to the PRs viewed since the instance started, just like
+//! events from the relay.
+
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use chadreview_relay_client::EventCallback;
+use chadreview_relay_models::{PrKey, WebhookEvent, webhook};
+use hyperchad::{
+    renderer::Content,
+    router::{RouteRequest, Router},
+};
+use switchy::http::models::Method;
+
+/// Error type for the `/webhook` route.
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    /// The request method is not supported for this route.
+    #[error("Unsupported method")]
+    UnsupportedMethod,
+    /// The delivery's signature didn't verify.
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(&'static str),
+    /// The delivery's body isn't a valid payload of its event type.
+    #[error("Failed to parse webhook: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// Webhooks delivered to this instance, and the PRs subscribed to them.
+pub struct DirectWebhooks {
+    secret: String,
+    subscriptions: RwLock<HashMap<PrKey, EventCallback>>,
+}
+
+impl DirectWebhooks {
+    /// Accept webhooks signed with `secret`.
+    #[must_use]
+    pub fn new(secret: String) -> Self {
+        Self {
+            secret,
+            subscriptions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Call `callback` with the events of a PR, replacing any callback it
+    /// had.
+    pub fn subscribe(&self, pr_key: PrKey, callback: EventCallback) {
+        self.subscriptions
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(pr_key, callback);
+    }
+
+    /// Send `event` to the subscribed PRs it is about, returning how many
+    /// there were.
+    ///
+    /// Commit statuses don't name their PRs, so they go to every subscribed
+    /// PR of their repository, the same as from the relay.
+    pub fn dispatch(&self, event: &WebhookEvent) -> usize {
+        let repository = event.repository();
+        let numbers = event.pr_numbers();
+        let is_status = matches!(event, WebhookEvent::Status { .. });
+
+        let callbacks: Vec<EventCallback> = self
+            .subscriptions
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|(pr_key, _)| {
+                pr_key.owner.eq_ignore_ascii_case(&repository.owner.login)
+                    && pr_key.repo.eq_ignore_ascii_case(&repository.name)
+                    && (is_status || numbers.contains(&pr_key.number))
+            })
+            .map(|(_, callback)| Arc::clone(callback))
+            .collect();
+
+        for callback in &callbacks {
+            callback(event.clone());
+        }
+
+        callbacks.len()
+    }
+}
+
+/// Add the `/webhook` route GitHub delivers webhooks to.
+#[must_use]
+pub fn add_webhook_routes(router: Router, webhooks: Arc<DirectWebhooks>) -> Router {
+    router.with_route_result("/webhook", move |req: RouteRequest| {
+        let webhooks = Arc::clone(&webhooks);
+        async move { webhook_route(&req, &webhooks) }
+    })
+}
+
+/// Handle POST `/webhook` - A GitHub webhook delivery.
+///
+/// Deliveries of events the relay doesn't handle, such as the `ping` sent
+/// when the webhook is created, are accepted and ignored.
+fn webhook_route(req: &RouteRequest, webhooks: &DirectWebhooks) -> Result<Content, WebhookError> {
+    if !matches!(req.method, Method::Post) {
+        return Err(WebhookError::UnsupportedMethod);
+    }
+
+    let body = req.body.as_deref().map_or(&[][..], |body| &body[..]);
+    webhook::verify_signature(
+        header(req, webhook::SIGNATURE_HEADER),
+        body,
+        &webhooks.secret,
+    )
+    .map_err(|e| {
+        log::warn!("Rejected webhook delivery: {e}");
+        WebhookError::InvalidSignature(e)
+    })?;
+
+    let event_type = header(req, webhook::EVENT_HEADER).unwrap_or_default();
+    match webhook::parse_webhook_event(event_type, body)? {
+        Some(event) => {
+            let delivered = webhooks.dispatch(&event);
+            log::info!(
+                "Received {event_type:?} webhook for {}, delivered to {delivered} PR(s)",
+                event.repository().full_name
+            );
+        }
+        None => log::debug!("Ignoring unsupported webhook event: {event_type:?}"),
+    }
+
+    Ok(Content::Raw {
+        data: Vec::<u8>::new().into(),
+        content_type: "text/plain; charset=utf-8".to_string(),
+    })
+}
+
+/// A request header, looked up case-insensitively.
+fn header<'a>(req: &'a RouteRequest, name: &str) -> Option<&'a str> {
+    req.headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
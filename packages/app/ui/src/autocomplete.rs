@@ -0,0 +1,167 @@
+//! Autocomplete dropdowns in comment forms.
+//!
+//! Typing a trigger character (like `@`) at the start of a word in a
+//! comment body fetches suggestions for the word from the trigger's URL,
+//! with the word as the `q` query parameter. The URL responds with a JSON
+//! array of `{value, label}` objects; picking one replaces the word with
+//! the trigger followed by its `value`.
+
+use hyperchad_template::{Containers, container};
+
+/// Route suggesting users to mention in a PR's comments.
+pub const MENTIONS_ROUTE: &str = "/api/mentions";
+/// Route suggesting commit authors to mention in local comments.
+pub const LOCAL_MENTIONS_ROUTE: &str = "/api/local/mentions";
+/// Route suggesting `:emoji:` shortcodes.
+pub const EMOJI_ROUTE: &str = "/api/emoji";
+
+/// Script showing the dropdowns. `__SOURCES__` is replaced with an object
+/// mapping each trigger character to its URL.
+const AUTOCOMPLETE_SCRIPT: &str = r##"<script>
+(() => {
+    const sources = __SOURCES__;
+    const triggers = Object.keys(sources).map((t) => t.replace(/[\\^$.*+?()[\]{}|-]/g, "\\$&")).join("");
+    const wordBeforeCaret = new RegExp("(^|\\s)([" + triggers + "])([\\w+-]*)$");
+    let menu = null;
+    let state = null;
+    let request = 0;
+    const close = () => {
+        menu?.remove();
+        menu = null;
+        state = null;
+    };
+    const highlight = (index) => {
+        state.index = (index + state.items.length) % state.items.length;
+        [...menu.children].forEach((item, i) => {
+            item.style.background = i === state.index ? "#ddf4ff" : "#ffffff";
+        });
+    };
+    const pick = (index) => {
+        const { textarea, start, trigger, items } = state;
+        const end = textarea.selectionStart;
+        const text = trigger + items[index].value + " ";
+        textarea.value = textarea.value.slice(0, start) + text + textarea.value.slice(end);
+        textarea.selectionStart = textarea.selectionEnd = start + text.length;
+        close();
+        textarea.focus();
+        // Let the draft autosave see the change
+        textarea.dispatchEvent(new Event("input", { bubbles: true }));
+    };
+    const show = (textarea, start, trigger, items) => {
+        close();
+        if (items.length === 0) return;
+        state = { textarea, start, trigger, items, index: 0 };
+        menu = document.createElement("div");
+        menu.className = "autocomplete-menu";
+        menu.style.cssText = "position: absolute; z-index: 100; min-width: 200px; max-height: 240px; overflow-y: auto; background: #ffffff; border: 1px solid #d0d7de; border-radius: 6px; box-shadow: 0 8px 24px rgba(140, 149, 159, 0.2); font-size: 13px;";
+        items.forEach((suggestion, i) => {
+            const item = document.createElement("div");
+            item.textContent = suggestion.label;
+            item.style.cssText = "padding: 6px 12px; cursor: pointer; color: #24292f;";
+            item.addEventListener("mouseenter", () => highlight(i));
+            item.addEventListener("mousedown", (event) => {
+                event.preventDefault();
+                pick(i);
+            });
+            menu.append(item);
+        });
+        const rect = textarea.getBoundingClientRect();
+        menu.style.left = rect.left + window.scrollX + "px";
+        menu.style.top = rect.bottom + window.scrollY + 4 + "px";
+        document.body.append(menu);
+        highlight(0);
+    };
+    const update = async (textarea) => {
+        const before = textarea.value.slice(0, textarea.selectionStart);
+        const match = before.match(wordBeforeCaret);
+        if (!match) {
+            close();
+            return;
+        }
+        const [, , trigger, query] = match;
+        const start = before.length - query.length - 1;
+        const url = sources[trigger];
+        const id = ++request;
+        const response = await fetch(url + (url.includes("?") ? "&" : "?") + "q=" + encodeURIComponent(query));
+        if (id !== request || !response.ok) return;
+        show(textarea, start, trigger, await response.json());
+    };
+    const isBody = (target) => target instanceof HTMLTextAreaElement && target.name === "body";
+    document.addEventListener("input", (event) => {
+        if (isBody(event.target) && event.isTrusted) update(event.target);
+    });
+    document.addEventListener("keydown", (event) => {
+        if (!state || event.target !== state.textarea) return;
+        if (event.key === "ArrowDown" || event.key === "ArrowUp") {
+            highlight(state.index + (event.key === "ArrowDown" ? 1 : -1));
+        } else if (event.key === "Enter" || event.key === "Tab") {
+            pick(state.index);
+        } else if (event.key === "Escape") {
+            close();
+        } else {
+            return;
+        }
+        event.preventDefault();
+    });
+    document.addEventListener("focusout", (event) => {
+        if (state && event.target === state.textarea) close();
+    });
+})();
+</script>"##;
+
+/// Render the script showing autocomplete dropdowns in comment forms, for
+/// each `(trigger, url)` source. Include it once per page.
+#[must_use]
+pub fn render_autocomplete_script(sources: &[(char, &str)]) -> Containers {
+    let sources = sources
+        .iter()
+        .map(|(trigger, url)| format!("{}: {}", js_string(&trigger.to_string()), js_string(url)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let script = AUTOCOMPLETE_SCRIPT.replace("__SOURCES__", &format!("{{{sources}}}"));
+
+    container! {
+        div {
+            raw { (script) }
+        }
+    }
+}
+
+/// URL of the [`MENTIONS_ROUTE`] suggestions for a PR.
+#[must_use]
+pub fn pr_mentions_url(owner: &str, repo: &str, number: u64) -> String {
+    format!(
+        "{MENTIONS_ROUTE}?owner={}&repo={}&number={number}",
+        urlencoding::encode(owner),
+        urlencoding::encode(repo),
+    )
+}
+
+/// URL of the [`LOCAL_MENTIONS_ROUTE`] suggestions for the repository at
+/// `repo_path`.
+#[must_use]
+pub fn local_mentions_url(repo_path: &str) -> String {
+    format!(
+        "{LOCAL_MENTIONS_ROUTE}?repo={}",
+        urlencoding::encode(repo_path)
+    )
+}
+
+/// Quote `value` as a JavaScript string literal that is safe to embed in a
+/// `<script>` element.
+fn js_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '<' => quoted.push_str("\\u003c"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,105 @@
+//! Review checklist shown at the top of `/local` and `/pr`.
+//!
+//! Each item toggles by posting its text and new `checked` state to the
+//! checklist's URL, which answers with the re-rendered checklist.
+
+use std::collections::HashSet;
+
+use hyperchad::transformer::models::Selector;
+use hyperchad_template::{Containers, container};
+
+/// Element ID of the checklist, replaced whenever an item is toggled.
+pub const CHECKLIST_ID: &str = "review-checklist";
+
+/// Route toggling items of a PR's checklist.
+pub const CHECKLIST_ROUTE: &str = "/api/pr/checklist";
+/// Route toggling items of a local diff's checklist.
+pub const LOCAL_CHECKLIST_ROUTE: &str = "/api/local/checklist";
+
+/// Render the checklist `items`, with the ones in `checked` checked off.
+/// Renders nothing when there are no items.
+#[must_use]
+pub fn render_checklist(
+    items: &[String],
+    checked: &HashSet<String>,
+    toggle_url: &str,
+) -> Containers {
+    if items.is_empty() {
+        return vec![];
+    }
+
+    let done = items.iter().filter(|item| checked.contains(*item)).count();
+    let complete = done == items.len();
+
+    container! {
+        div
+            id=(CHECKLIST_ID)
+            padding=12
+            background=(if complete { "#dafbe1" } else { "#f6f8fa" })
+            border="1px solid #d0d7de"
+            border-radius=6
+            gap=8
+        {
+            div direction=row align-items=center gap=8 {
+                span font-size=14 font-weight=600 color="#24292f" {
+                    "Review checklist"
+                }
+                span font-size=12 color="#57606a" {
+                    (done) " of " (items.len()) " done"
+                }
+            }
+            @for item in items {
+                (render_item(item, checked.contains(item), toggle_url))
+            }
+        }
+    }
+}
+
+fn render_item(item: &str, checked: bool, toggle_url: &str) -> Containers {
+    container! {
+        form
+            hx-post=(toggle_url)
+            hx-target=(Selector::Id(CHECKLIST_ID.to_string()))
+            hx-swap="outerHTML"
+        {
+            input type=hidden name="item" value=(item);
+            button
+                type=submit
+                name="checked"
+                value=(if checked { "false" } else { "true" })
+                direction=row
+                align-items=center
+                gap=8
+                background="transparent"
+                font-size=14
+                color=(if checked { "#57606a" } else { "#24292f" })
+                cursor=pointer
+            {
+                span font-size=16 {
+                    @if checked { "☑" } @else { "☐" }
+                }
+                span { (item) }
+            }
+        }
+    }
+}
+
+/// URL toggling items of a PR's checklist.
+#[must_use]
+pub fn pr_checklist_url(owner: &str, repo: &str, number: u64) -> String {
+    format!(
+        "{CHECKLIST_ROUTE}?owner={}&repo={}&number={number}",
+        urlencoding::encode(owner),
+        urlencoding::encode(repo),
+    )
+}
+
+/// URL toggling items of the checklist of the local diff described by
+/// `spec_query`.
+#[must_use]
+pub fn local_checklist_url(repo_path: &str, spec_query: &str) -> String {
+    format!(
+        "{LOCAL_CHECKLIST_ROUTE}?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    )
+}
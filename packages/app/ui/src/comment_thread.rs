@@ -1,10 +1,11 @@
 use chadreview_diff_models::LineNumber;
-use chadreview_pr_models::{Comment, CommentType};
-use hyperchad::markdown::markdown_to_container;
+use chadreview_markdown::ReferenceProvider;
+use chadreview_pr_models::{Comment, CommentType, Reaction, ReactionContent};
 use hyperchad::template::{Containers, container};
 use hyperchad::transformer::models::Selector;
 
 use crate::diff_viewer::comment_thread_container_id;
+use crate::markdown::{markdown_to_container, repo_markdown_to_container};
 
 #[must_use]
 pub fn comment_id(comment_id: u64) -> String {
@@ -21,11 +22,17 @@ pub fn comment_thread_id(comment_id: u64) -> String {
     format!("comment-thread-{comment_id}")
 }
 
+#[must_use]
+pub fn comment_reactions_id(comment_id: u64) -> String {
+    format!("comment-{comment_id}-reactions")
+}
+
 #[must_use]
 pub fn render_comment_thread(
     root_comment_id: u64,
     comment: &Comment,
     depth: usize,
+    provider: &str,
     owner: &str,
     repo: &str,
     number: u64,
@@ -46,10 +53,10 @@ pub fn render_comment_thread(
             @if should_collapse {
                 (render_collapsed_thread_header(comment, owner, repo, number))
             } @else {
-                (render_comment_item(comment, is_root, owner, repo, number))
+                (render_comment_item(comment, is_root, provider, owner, repo, number))
                 (render_reply_form(root_comment_id, comment.id, owner, repo, number))
                 @for reply in &comment.replies {
-                    (render_comment_thread(root_comment_id, reply, depth + 1, owner, repo, number))
+                    (render_comment_thread(root_comment_id, reply, depth + 1, provider, owner, repo, number))
                 }
             }
         }
@@ -60,6 +67,7 @@ pub fn render_comment_thread(
 pub fn render_comment_item(
     comment: &Comment,
     root: bool,
+    provider: &str,
     owner: &str,
     repo: &str,
     number: u64,
@@ -102,8 +110,9 @@ pub fn render_comment_item(
                 color="#24292f"
                 font-size=14
             {
-                (markdown_to_container(&comment.body))
+                (comment_markdown(&comment.body, provider, owner, repo))
             }
+            (render_reactions(comment.id, &comment.reactions, owner, repo))
             (render_edit_form(comment, root, owner, repo, number))
             div direction=row gap=12 {
                 (render_reply_button(comment))
@@ -115,6 +124,78 @@ pub fn render_comment_item(
     }
 }
 
+/// Render a comment's reaction pills, followed by the emojis nobody has
+/// used yet. Clicking either toggles the viewer's reaction.
+#[must_use]
+pub fn render_reactions(
+    comment_id: u64,
+    reactions: &[Reaction],
+    owner: &str,
+    repo: &str,
+) -> Containers {
+    let api_url = format!(
+        "/api/comment/reaction?owner={}&repo={}&id={comment_id}",
+        urlencoding::encode(owner),
+        urlencoding::encode(repo),
+    );
+    let unused = ReactionContent::ALL
+        .into_iter()
+        .filter(|content| !reactions.iter().any(|r| r.content == *content))
+        .collect::<Vec<_>>();
+
+    container! {
+        form
+            id=(comment_reactions_id(comment_id))
+            hx-post=(api_url)
+            hx-swap="outerHTML"
+            hx-target=(Selector::Id(comment_reactions_id(comment_id)))
+            direction=row
+            align-items=center
+            gap=6
+        {
+            @for reaction in reactions {
+                @let background = if reaction.viewer_reacted { "#ddf4ff" } else { "#f6f8fa" };
+                @let border = if reaction.viewer_reacted { "1, #54aeff" } else { "1, #d0d7de" };
+                button
+                    type=submit
+                    name="content"
+                    value=(reaction.content.as_str())
+                    background=(background)
+                    border=(border)
+                    border-radius=12
+                    padding-x=8
+                    padding-y=2
+                    font-size=12
+                    cursor=pointer
+                {
+                    (format!("{} {}", reaction.content.emoji(), reaction.count))
+                }
+            }
+            @for content in unused {
+                button
+                    type=submit
+                    name="content"
+                    value=(content.as_str())
+                    padding-x=4
+                    font-size=12
+                    cursor=pointer
+                {
+                    (content.emoji())
+                }
+            }
+        }
+    }
+}
+
+/// Render a comment's body as markdown, linking references to the PR's
+/// repository on `provider`.
+fn comment_markdown(body: &str, provider: &str, owner: &str, repo: &str) -> Containers {
+    ReferenceProvider::from_name(provider).map_or_else(
+        || markdown_to_container(body),
+        |provider| repo_markdown_to_container(body, provider, owner, repo),
+    )
+}
+
 fn format_timestamp(dt: &chrono::DateTime<chrono::Utc>) -> String {
     dt.format("%b %d, %Y").to_string()
 }
@@ -264,9 +345,12 @@ pub fn render_edit_form(
     number: u64,
 ) -> Containers {
     let form_id = format!("edit-form-{}", comment.id);
+    let body_id = format!("comment-{}-body", comment.id);
     let target_id = format!("comment-{}", comment.id);
     let api_url = format!(
         "/api/comment/update?owner={owner}&repo={repo}&number={number}&id={comment_id}&root={root}",
+        owner = urlencoding::encode(owner),
+        repo = urlencoding::encode(repo),
         comment_id = comment.id
     );
 
@@ -304,7 +388,7 @@ pub fn render_edit_form(
                     padding-y=8
                     border-radius=6
                     cursor=pointer
-                    fx-click=fx { element_by_id(form_id).no_display() }
+                    fx-click=fx { element_by_id(form_id).no_display(); element_by_id(body_id).display() }
                 {
                     "Cancel"
                 }
@@ -398,22 +482,51 @@ pub fn render_delete_button(
     } else {
         Selector::Id(comment_id(comment.id))
     };
+    let confirm_id = format!("delete-confirm-{}", comment.id);
+    let item_id = comment_id(comment.id);
 
     container! {
-        form
-            hx-delete=(api_url)
-            hx-target=(target)
-        {
+        div direction=row align-items=center gap=8 {
             button
-                type=submit
+                type=button
                 color=#cf222e
                 padding-x=8
                 padding-y=4
                 cursor=pointer
                 font-size=12
+                fx-click=fx { element_by_id(confirm_id).display() }
             {
                 "Delete"
             }
+            div id=(confirm_id) hidden direction=row align-items=center gap=8 {
+                span font-size=12 color="#57606a" { "Delete this comment?" }
+                button
+                    type=button
+                    background=#cf222e
+                    color="#ffffff"
+                    padding-x=8
+                    padding-y=4
+                    border-radius=6
+                    cursor=pointer
+                    font-size=12
+                    hx-delete=(api_url)
+                    hx-target=(target)
+                    fx-click=fx { element_by_id(item_id).no_display() }
+                {
+                    "Yes, delete"
+                }
+                button
+                    type=button
+                    color="#57606a"
+                    padding-x=8
+                    padding-y=4
+                    cursor=pointer
+                    font-size=12
+                    fx-click=fx { element_by_id(confirm_id).no_display() }
+                {
+                    "Cancel"
+                }
+            }
         }
     }
 }
@@ -430,7 +543,7 @@ pub fn render_resolve_button(
         return container! { div {} };
     }
 
-    let api_url = format!(
+    let mut api_url = format!(
         "/api/comment/resolve?owner={}&repo={}&number={}&id={}&resolved={}",
         urlencoding::encode(owner),
         urlencoding::encode(repo),
@@ -438,6 +551,10 @@ pub fn render_resolve_button(
         comment.id,
         !comment.resolved
     );
+    if let Some(thread_id) = &comment.thread_id {
+        api_url.push_str("&thread_id=");
+        api_url.push_str(&urlencoding::encode(thread_id));
+    }
 
     let button_text = if comment.resolved {
         "Unresolve"
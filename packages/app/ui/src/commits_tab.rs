@@ -0,0 +1,167 @@
+use chadreview_pr_models::Commit;
+use hyperchad::template::{Containers, container};
+
+/// Which part of the pull request the page shows below the header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrTab {
+    /// The combined diff of every commit.
+    Files,
+    /// The list of commits.
+    Commits,
+    /// The diff of a single commit.
+    Commit(String),
+}
+
+impl PrTab {
+    /// Pick the tab from the `/pr` route's `tab` and `commit` query params.
+    #[must_use]
+    pub fn from_query(tab: Option<&str>, commit: Option<&str>) -> Self {
+        match (tab, commit) {
+            (_, Some(sha)) => Self::Commit(sha.to_string()),
+            (Some("commits"), None) => Self::Commits,
+            _ => Self::Files,
+        }
+    }
+}
+
+fn pr_url(owner: &str, repo: &str, number: u64) -> String {
+    format!(
+        "/pr?owner={}&repo={}&number={number}",
+        urlencoding::encode(owner),
+        urlencoding::encode(repo),
+    )
+}
+
+fn short_sha(sha: &str) -> &str {
+    sha.get(..7).unwrap_or(sha)
+}
+
+fn summary(message: &str) -> &str {
+    message.lines().next().unwrap_or_default()
+}
+
+/// Render the "Files changed" / "Commits" tab bar.
+#[must_use]
+pub fn render_pr_tabs(
+    owner: &str,
+    repo: &str,
+    number: u64,
+    active: &PrTab,
+    commit_count: usize,
+) -> Containers {
+    let url = pr_url(owner, repo, number);
+    let commits_active = matches!(active, PrTab::Commits | PrTab::Commit(_));
+
+    container! {
+        div direction=row gap=16 padding-x=20 border-bottom="1, #d0d7de" {
+            (render_tab("Files changed", &url, !commits_active))
+            (render_tab(&format!("Commits ({commit_count})"), &format!("{url}&tab=commits"), commits_active))
+        }
+    }
+}
+
+fn render_tab(label: &str, href: &str, active: bool) -> Containers {
+    container! {
+        @if active {
+            anchor
+                href=(href)
+                padding-y=8
+                border-bottom="2, #fd8c73"
+                color="#24292f"
+                font-size=14
+                font-weight=600
+            {
+                (label)
+            }
+        } @else {
+            anchor href=(href) padding-y=8 color="#57606a" font-size=14 {
+                (label)
+            }
+        }
+    }
+}
+
+/// Render the pull request's commits, each linking to its own diff.
+#[must_use]
+pub fn render_commit_list(commits: &[Commit], owner: &str, repo: &str, number: u64) -> Containers {
+    if commits.is_empty() {
+        return container! {
+            div padding=20 color="#57606a" {
+                "No commits in this pull request."
+            }
+        };
+    }
+
+    let url = pr_url(owner, repo, number);
+
+    container! {
+        section padding=20 gap=4 {
+            @for commit in commits {
+                div
+                    direction=row
+                    gap=12
+                    padding=8
+                    background="#f6f8fa"
+                    border-radius=6
+                    align-items=center
+                {
+                    anchor
+                        href=(format!("{url}&commit={}", commit.sha))
+                        font-family="monospace"
+                        color="#0969da"
+                        font-size=13
+                        min-width=70
+                    {
+                        (short_sha(&commit.sha))
+                    }
+                    span color="#1f2328" font-size=14 flex=1 {
+                        (summary(&commit.message))
+                    }
+                    span color="#57606a" font-size=12 {
+                        (format!(
+                            "{} committed {}",
+                            commit.author.username,
+                            commit.committed_at.format("%Y-%m-%d %H:%M UTC")
+                        ))
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Render the banner above a single commit's diff, with a link back to the
+/// full pull request diff.
+#[must_use]
+pub fn render_commit_banner(
+    sha: &str,
+    commit: Option<&Commit>,
+    owner: &str,
+    repo: &str,
+    number: u64,
+) -> Containers {
+    let title = commit.map_or("Viewing changes from a single commit", |commit| {
+        summary(&commit.message)
+    });
+
+    container! {
+        div
+            direction=row
+            gap=12
+            margin-x=20
+            margin-top=16
+            padding=12
+            background="#ddf4ff"
+            border="1, #54aeff"
+            border-radius=6
+            align-items=center
+            font-size=14
+        {
+            span font-family="monospace" color="#0969da" { (short_sha(sha)) }
+            span flex=1 color="#1f2328" { (title) }
+            anchor href=(pr_url(owner, repo, number)) color="#0969da" {
+                "Show all changes"
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Quick copy actions in the diff viewer.
+//!
+//! Files can have their path or permalink copied and hunks their permalink
+//! or patch. The copied text is rendered by the server into a textarea next
+//! to each button, so [`render_copy_script`] only has to put it on the
+//! clipboard, and without the script the button still reveals the text to
+//! copy by hand.
+
+use chadreview_diff_models::LineNumber;
+use chadreview_pr_models::{DiffFile, DiffHunk};
+use hyperchad::template::{Containers, container};
+
+use super::permalink::permalink_fragment;
+
+/// Script copying the text of a clicked copy button, resolving permalinks
+/// against the page's URL. Shows the text selected for copying by hand if
+/// the clipboard can't be written.
+const COPY_SCRIPT: &str = r#"<script>
+(() => {
+    document.addEventListener("click", async (event) => {
+        const button = event.target.closest && event.target.closest(".copy-button");
+        if (!button) return;
+        const action = button.closest(".copy-action");
+        const text = action && action.querySelector(".copy-text");
+        if (!text) return;
+        event.preventDefault();
+        const value = action.classList.contains("copy-link")
+            ? new URL(text.value, location.href).href
+            : text.value;
+        const label = button.dataset.label || button.textContent;
+        button.dataset.label = label;
+        try {
+            await navigator.clipboard.writeText(value);
+            action.open = false;
+            button.textContent = "Copied";
+        } catch {
+            text.value = value;
+            action.open = true;
+            text.select();
+            button.textContent = "Press Ctrl+C to copy";
+        }
+        setTimeout(() => { button.textContent = label; }, 1500);
+    });
+})();
+</script>"#;
+
+/// Render the script making copy buttons write to the clipboard. Include it
+/// once per page.
+#[must_use]
+pub fn render_copy_script() -> Containers {
+    container! {
+        div {
+            raw { (COPY_SCRIPT) }
+        }
+    }
+}
+
+/// Render the actions copying the path of `file` and its permalink on the
+/// page at `page_url`.
+#[must_use]
+pub fn render_file_copy_actions(file: &DiffFile, page_url: &str) -> Containers {
+    let permalink = format!("{page_url}#{}", permalink_fragment(&file.filename, None));
+
+    container! {
+        div direction=row align-items=center gap=4 {
+            (render_copy_action("Copy path", &file.filename, false))
+            (render_copy_action("Copy link", &permalink, true))
+        }
+    }
+}
+
+/// Render the actions copying `hunk` of `file` as a fenced patch and, when
+/// the page is linkable at `page_url`, the permalink to the hunk's first
+/// line.
+#[must_use]
+pub fn render_hunk_copy_actions(
+    file: &DiffFile,
+    hunk: &DiffHunk,
+    page_url: Option<&str>,
+) -> Containers {
+    let line = if hunk.new_lines == 0 {
+        LineNumber::Old {
+            line: hunk.old_start,
+        }
+    } else {
+        LineNumber::New {
+            line: hunk.new_start,
+        }
+    };
+    let permalink =
+        page_url.map(|url| format!("{url}#{}", permalink_fragment(&file.filename, Some(line))));
+
+    container! {
+        div direction=row align-items=center gap=4 {
+            @if let Some(permalink) = &permalink {
+                (render_copy_action("Copy link", permalink, true))
+            }
+            (render_copy_action("Copy patch", &hunk_patch(file, hunk), false))
+        }
+    }
+}
+
+/// Render a button copying `text`, expanding to show it when clicked
+/// without the script. `is_link` marks `text` as a URL to resolve against
+/// the page's.
+fn render_copy_action(label: &str, text: &str, is_link: bool) -> Containers {
+    container! {
+        details class=(if is_link { "copy-action copy-link" } else { "copy-action" }) {
+            summary
+                class="copy-button"
+                padding-x=8
+                padding-y=2
+                cursor=pointer
+                background="#ffffff"
+                border="1px solid #d0d7de"
+                border-radius=4
+                font-size=12
+                font-weight=400
+                color="#57606a"
+                user-select=none
+            {
+                (label)
+            }
+            textarea
+                class="copy-text"
+                margin-top=4
+                padding=8
+                border="1px solid #d0d7de"
+                border-radius=6
+                font-size=12
+                font-family="monospace"
+                height=120
+            {
+                (text)
+            }
+        }
+    }
+}
+
+/// `hunk` of `file` as a unified diff wrapped in a `diff` code fence, ready
+/// to paste into a chat or an issue.
+fn hunk_patch(file: &DiffFile, hunk: &DiffHunk) -> String {
+    let patch = file.hunk_patch(hunk);
+    let fence = code_fence(&patch);
+    format!("{fence}diff\n{patch}{fence}\n")
+}
+
+/// A backtick fence longer than any backtick run in `code`.
+fn code_fence(code: &str) -> String {
+    let longest = code
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+
+    "`".repeat(longest.max(2) + 1)
+}
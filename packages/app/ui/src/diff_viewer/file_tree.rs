@@ -0,0 +1,125 @@
+//! File tree sidebar for navigating a diff.
+//!
+//! Files are grouped by directory and link to their diff with an in-page
+//! anchor, so clicking a file scrolls to it.
+
+use std::collections::{BTreeMap, HashSet};
+
+use chadreview_pr_models::{DiffFile, FileStatus};
+use hyperchad::template::{Containers, container};
+
+/// Element ID of a file's diff, used as the tree's scroll anchor.
+#[must_use]
+pub fn file_anchor_id(path: &str) -> String {
+    format!(
+        "file-{}",
+        path.to_ascii_lowercase()
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "-")
+    )
+}
+
+/// Group files by their parent directory, sorted by directory and then by
+/// file name. Files at the repository root are grouped under `""`.
+#[must_use]
+pub fn group_by_directory(diffs: &[DiffFile]) -> Vec<(&str, Vec<&DiffFile>)> {
+    let mut groups: BTreeMap<&str, Vec<&DiffFile>> = BTreeMap::new();
+
+    for file in diffs {
+        let directory = file.filename.rsplit_once('/').map_or("", |(dir, _)| dir);
+        groups.entry(directory).or_default().push(file);
+    }
+
+    groups
+        .into_iter()
+        .map(|(directory, mut files)| {
+            files.sort_by(|a, b| a.filename.cmp(&b.filename));
+            (directory, files)
+        })
+        .collect()
+}
+
+/// Render the collapsible file tree, marking files in `viewed_paths`.
+#[must_use]
+pub fn render_file_tree(diffs: &[DiffFile], viewed_paths: &HashSet<String>) -> Containers {
+    let viewed = diffs
+        .iter()
+        .filter(|file| viewed_paths.contains(&file.filename))
+        .count();
+
+    container! {
+        details
+            open
+            width=280
+            flex-shrink=0
+            border="1px solid #d0d7de"
+            border-radius=6
+            background="#f6f8fa"
+        {
+            summary
+                cursor=pointer
+                font-weight=600
+                font-size=14
+                padding=8
+                color="#1f2328"
+            {
+                (format!("Files ({viewed}/{} viewed)", diffs.len()))
+            }
+            div direction=column gap=8 padding=8 {
+                @for (directory, files) in group_by_directory(diffs) {
+                    div direction=column gap=2 {
+                        @if !directory.is_empty() {
+                            span
+                                font-family="monospace"
+                                font-size=12
+                                color="#57606a"
+                                padding-y=2
+                            {
+                                (format!("{directory}/"))
+                            }
+                        }
+                        @for file in files {
+                            (render_tree_file(file, viewed_paths.contains(&file.filename)))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn render_tree_file(file: &DiffFile, viewed: bool) -> Containers {
+    let name = file
+        .filename
+        .rsplit_once('/')
+        .map_or(file.filename.as_str(), |(_, name)| name);
+    let (status_marker, status_color) = match file.status {
+        FileStatus::Added => ("A", "#1a7f37"),
+        FileStatus::Modified => ("M", "#0969da"),
+        FileStatus::Deleted => ("D", "#cf222e"),
+        FileStatus::Renamed => ("R", "#8250df"),
+    };
+    let name_color = if viewed { "#57606a" } else { "#1f2328" };
+
+    container! {
+        anchor
+            href=(format!("#{}", file_anchor_id(&file.filename)))
+            direction=row
+            align-items=center
+            gap=6
+            padding-x=8
+            padding-y=2
+            border-radius=6
+            font-size=13
+        {
+            span font-family="monospace" font-size=11 font-weight=600 color=(status_color) {
+                (status_marker)
+            }
+            span flex=1 color=(name_color) { (name) }
+            span color="#1a7f37" font-size=11 { "+" (file.additions) }
+            span color="#cf222e" font-size=11 { "-" (file.deletions) }
+            @if viewed {
+                span color="#1a7f37" font-size=12 { "✓" }
+            }
+        }
+    }
+}
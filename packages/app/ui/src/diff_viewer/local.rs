@@ -9,12 +9,17 @@ use chadreview_local_comment_models::{
 };
 use chadreview_pr_models::{DiffFile, DiffLine, FileStatus, LineType};
 use hyperchad::template::{Containers, LayoutOverflow, container};
+use hyperchad::transformer::models::Selector;
 
 use super::{
-    render_diff_marker_inline, render_file_stats, render_hunk_header_row,
-    render_line_numbers_inline,
+    copy, file_tree, permalink, render_diff_marker_inline, render_encoding_notice,
+    render_file_stats, render_hunk_body, render_hunk_header_row, render_mode_change,
+    render_show_generated, render_unchanged_region_controls,
 };
-use crate::local_comments;
+use crate::{local_comments, local_staging, local_summaries};
+
+/// Element ID of the button loading the next page of a very large diff.
+pub const LOAD_MORE_FILES_ID: &str = "load-more-files";
 
 /// Render diffs with local comment support.
 ///
@@ -22,14 +27,33 @@ use crate::local_comments;
 /// rather than on a remote Git provider.
 ///
 /// Files in `viewed_paths` will be rendered collapsed (header only) with
-/// lazy loading for the content.
+/// lazy loading for the content, as will files in `generated_paths` along
+/// with a "Show anyway" button.
+///
+/// `spec_query` is the query string of the diff (and comment filter) being
+/// viewed, carried by the comment forms and file URLs so they act on the
+/// same diff.
+///
+/// Each file is preceded by its AI summary from `summaries`, keyed by path,
+/// or an empty slot the summary is pushed into once generated. Files of a
+/// working tree diff, which comes with its `staging`, are also preceded by
+/// their staging actions.
+///
+/// `diffs` can be the first page of a very large diff, with `more_files`
+/// files left, loaded page by page with [`render_load_more_files`].
 #[must_use]
+#[allow(clippy::too_many_arguments)]
 pub fn render_local(
     diffs: &[DiffFile],
     comments: &[LocalComment],
     repo_path: &str,
+    spec_query: &str,
     viewed_paths: &std::collections::HashSet<String>,
+    generated_paths: &std::collections::HashSet<String>,
     viewed_reply_ids: &std::collections::HashSet<switchy::uuid::Uuid>,
+    summaries: &std::collections::HashMap<String, String>,
+    staging: Option<&local_staging::Staging>,
+    more_files: usize,
 ) -> Containers {
     if diffs.is_empty() {
         return container! {
@@ -40,37 +64,123 @@ pub fn render_local(
     }
 
     container! {
-        section padding=20 gap=24 {
-            // Header with title and collapse/expand buttons
-            div direction=row align-items=center justify-content=space-between margin-bottom=16 {
-                h2 font-size=20 font-weight=600 color="#24292f" {
-                    "Files changed"
+        section padding=20 direction=row gap=16 {
+            (file_tree::render_file_tree(diffs, viewed_paths))
+            div flex=1 gap=24 {
+                // Header with title and collapse/expand buttons
+                div direction=row align-items=center justify-content=space-between margin-bottom=16 {
+                    h2 font-size=20 font-weight=600 color="#24292f" {
+                        "Files changed"
+                    }
+                    div direction=row gap=8 overflow-x=(LayoutOverflow::Wrap { grid: false }) {
+                        // Collapse/Expand everything (files + all comments + replies)
+                        (local_comments::render_collapse_everything_controls())
+                        // Collapse/Expand all files
+                        (local_comments::render_collapse_all_files_controls())
+                        // Collapse/Expand all file comments
+                        (local_comments::render_collapse_all_file_comments_controls())
+                        // Fold/Unfold all unchanged regions
+                        (render_unchanged_region_controls())
+                    }
                 }
-                div direction=row gap=8 overflow-x=(LayoutOverflow::Wrap { grid: false }) {
-                    // Collapse/Expand everything (files + all comments + replies)
-                    (local_comments::render_collapse_everything_controls())
-                    // Collapse/Expand all files
-                    (local_comments::render_collapse_all_files_controls())
-                    // Collapse/Expand all file comments
-                    (local_comments::render_collapse_all_file_comments_controls())
+                @for diff_file in diffs {
+                    (render_local_file(diff_file, comments, repo_path, spec_query, viewed_paths, generated_paths, viewed_reply_ids, summaries, staging))
                 }
-            }
-            @for diff_file in diffs {
-                @let is_viewed = viewed_paths.contains(&diff_file.filename);
-                @if is_viewed {
-                    (render_file_collapsed(diff_file, repo_path))
-                } @else {
-                    (render_file_expanded(diff_file, comments, repo_path, false, viewed_reply_ids))
+                @if more_files > 0 {
+                    (render_load_more_files(repo_path, spec_query, diffs.len(), more_files))
                 }
             }
         }
     }
 }
 
+/// Render a file of [`render_local`]: its summary slot, its staging actions
+/// with `staging`, and the file, collapsed if viewed or generated.
+#[must_use]
+#[allow(clippy::too_many_arguments)]
+pub fn render_local_file(
+    diff_file: &DiffFile,
+    comments: &[LocalComment],
+    repo_path: &str,
+    spec_query: &str,
+    viewed_paths: &std::collections::HashSet<String>,
+    generated_paths: &std::collections::HashSet<String>,
+    viewed_reply_ids: &std::collections::HashSet<switchy::uuid::Uuid>,
+    summaries: &std::collections::HashMap<String, String>,
+    staging: Option<&local_staging::Staging>,
+) -> Containers {
+    let is_viewed = viewed_paths.contains(&diff_file.filename);
+    let summary = summaries.get(&diff_file.filename).map(String::as_str);
+
+    container! {
+        div gap=8 {
+            (local_summaries::render_file_summary(&diff_file.filename, summary))
+            @if let Some(staging) = staging {
+                (local_staging::render_file_staging(&diff_file.filename, Some(diff_file), repo_path, spec_query, staging, None))
+            }
+            @if is_viewed {
+                (render_file_collapsed(diff_file, repo_path, spec_query))
+            } @else if generated_paths.contains(&diff_file.filename) {
+                (render_file_generated(diff_file, repo_path, spec_query))
+            } @else {
+                (render_file_expanded(diff_file, comments, repo_path, spec_query, false, viewed_reply_ids))
+            }
+        }
+    }
+}
+
+/// Render the button loading the next page of a very large diff, from
+/// file `offset` on, with `remaining` files left. The page replaces the
+/// button, followed by another one if files are still left.
+#[must_use]
+pub fn render_load_more_files(
+    repo_path: &str,
+    spec_query: &str,
+    offset: usize,
+    remaining: usize,
+) -> Containers {
+    let url = format!(
+        "/api/local/files?repo={}&offset={offset}&{spec_query}",
+        urlencoding::encode(repo_path),
+    );
+
+    container! {
+        div
+            id=(LOAD_MORE_FILES_ID)
+            direction=row
+            align-items=center
+            gap=12
+            padding=12
+            font-size=13
+            color="#57606a"
+        {
+            span {
+                (remaining) @if remaining == 1 { " more file" } @else { " more files" } " in this diff."
+            }
+            button
+                type=button
+                padding-x=8
+                padding-y=2
+                cursor=pointer
+                background="#ffffff"
+                border="1px solid #d0d7de"
+                border-radius=4
+                font-size=12
+                color="#0969da"
+                hx-get=(url)
+                hx-target=(Selector::Id(LOAD_MORE_FILES_ID.to_string()))
+                hx-swap="outerHTML"
+            {
+                "Load more files"
+            }
+        }
+    }
+}
+
 /// Generate a unique ID for a file container.
 #[must_use]
 pub fn file_container_id(path: &str) -> String {
-    format!("file-{}", local_comments::classify_name(path))
+    file_tree::file_anchor_id(path)
 }
 
 /// Generate a unique ID for a file content container.
@@ -95,8 +205,30 @@ pub fn file_expand_btn_id(path: &str) -> String {
 ///
 /// The content is not rendered and will be lazy-loaded when expanded.
 #[must_use]
-pub fn render_file_collapsed(file: &DiffFile, repo_path: &str) -> Containers {
+pub fn render_file_collapsed(file: &DiffFile, repo_path: &str, spec_query: &str) -> Containers {
+    let container_id = file_container_id(&file.filename);
+
+    container! {
+        div
+            id=(container_id)
+            border="1px solid #d0d7de"
+            border-radius=6
+        {
+            (render_file_header_local(file, repo_path, spec_query, true, true, true))
+        }
+    }
+}
+
+/// Render a collapsed generated file: its header and a "Show anyway" button,
+/// both lazy-loading the content like [`render_file_collapsed`].
+#[must_use]
+pub fn render_file_generated(file: &DiffFile, repo_path: &str, spec_query: &str) -> Containers {
     let container_id = file_container_id(&file.filename);
+    let expand_url = format!(
+        "/api/local/file/diff?repo={}&path={}&{spec_query}",
+        urlencoding::encode(repo_path),
+        urlencoding::encode(&file.filename),
+    );
 
     container! {
         div
@@ -104,7 +236,8 @@ pub fn render_file_collapsed(file: &DiffFile, repo_path: &str) -> Containers {
             border="1px solid #d0d7de"
             border-radius=6
         {
-            (render_file_header_local(file, repo_path, true, true, true))
+            (render_file_header_local(file, repo_path, spec_query, false, true, true))
+            (render_show_generated(&expand_url, &container_id))
         }
     }
 }
@@ -115,11 +248,13 @@ pub fn render_file_expanded(
     file: &DiffFile,
     comments: &[LocalComment],
     repo_path: &str,
+    spec_query: &str,
     is_viewed: bool,
     viewed_reply_ids: &std::collections::HashSet<switchy::uuid::Uuid>,
 ) -> Containers {
     let container_id = file_container_id(&file.filename);
     let content_id = file_content_id(&file.filename);
+    let page_url = local_page_url(repo_path, spec_query);
 
     container! {
         div
@@ -128,7 +263,7 @@ pub fn render_file_expanded(
             border-radius=6
         {
             // Header (always visible)
-            (render_file_header_local(file, repo_path, is_viewed, false, false))
+            (render_file_header_local(file, repo_path, spec_query, is_viewed, false, false))
             // Content container (collapsible via fx-click)
             div id=(content_id) class="file-content" {
                 table width=100% {
@@ -174,18 +309,16 @@ pub fn render_file_expanded(
                     tbody {
                         tr {
                             td columns=3 {
-                                (local_comments::render_local_file_comment_form(repo_path, &file.filename))
+                                (local_comments::render_local_file_comment_form(repo_path, spec_query, &file.filename))
                             }
                         }
                     }
                     // Hunks
-                    @for hunk in &file.hunks {
-                        (render_hunk_header_row(hunk))
-                        tbody font-family="monospace" font-size=12 {
-                            @for line in &hunk.lines {
-                                (render_line_row_local(&file.filename, line, comments, repo_path, viewed_reply_ids))
-                            }
-                        }
+                    @for (index, hunk) in file.hunks.iter().enumerate() {
+                        (render_hunk_header_row(file, index, hunk, Some(&page_url)))
+                        (render_hunk_body(&file.filename, index, hunk, |line| {
+                            render_line_row_local(&file.filename, line, comments, repo_path, spec_query, viewed_reply_ids)
+                        }))
                     }
                 }
             }
@@ -193,11 +326,21 @@ pub fn render_file_expanded(
     }
 }
 
-/// Render file header with collapse/expand, viewed checkbox, and comment button.
+/// URL of the local diff page of `spec_query`, which file and line
+/// permalinks are relative to.
+fn local_page_url(repo_path: &str, spec_query: &str) -> String {
+    format!(
+        "/local?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    )
+}
+
+/// Render file header with collapse/expand, viewed checkbox, comment button and copy actions.
 ///
 /// # Arguments
 /// * `file` - The diff file
 /// * `repo_path` - Repository path (for API calls)
+/// * `spec_query` - Query string of the diff being viewed (for API calls)
 /// * `is_viewed` - Whether the file is marked as viewed
 /// * `is_collapsed` - Whether the file content is currently collapsed
 /// * `lazy_load` - If true, expand button uses hx-get; if false, uses client-side fx-click
@@ -205,12 +348,11 @@ pub fn render_file_expanded(
 fn render_file_header_local(
     file: &DiffFile,
     repo_path: &str,
+    spec_query: &str,
     is_viewed: bool,
     is_collapsed: bool,
     lazy_load: bool,
 ) -> Containers {
-    use hyperchad::transformer::models::Selector;
-
     let (status_text, status_color) = match file.status {
         FileStatus::Added => ("Added", "#1a7f37"),
         FileStatus::Modified => ("Modified", "#0969da"),
@@ -225,14 +367,14 @@ fn render_file_header_local(
 
     // URL for lazy loading diff content (when expanding a collapsed file)
     let expand_url = format!(
-        "/api/local/file/diff?repo={}&path={}",
+        "/api/local/file/diff?repo={}&path={}&{spec_query}",
         urlencoding::encode(repo_path),
         urlencoding::encode(&file.filename),
     );
 
     // URL for marking file as viewed/unviewed
     let view_url = format!(
-        "/api/local/file/view?repo={}&path={}",
+        "/api/local/file/view?repo={}&path={}&{spec_query}",
         urlencoding::encode(repo_path),
         urlencoding::encode(&file.filename),
     );
@@ -381,10 +523,13 @@ fn render_file_header_local(
                 {
                     (status_text)
                 }
+                (render_mode_change(file))
+                (render_encoding_notice(file))
 
-                // Filename
+                // Filename, linking to the file's permalink
                 div overflow-x=(LayoutOverflow::Wrap { grid: true }) {
-                    span
+                    anchor
+                        href=(format!("#{}", permalink::permalink_fragment(&file.filename, None)))
                         font-family="monospace"
                         font-size=14
                         font-weight=600
@@ -397,6 +542,7 @@ fn render_file_header_local(
                 }
             }
             div direction=row align-items=center gap=12 {
+                (copy::render_file_copy_actions(file, &local_page_url(repo_path, spec_query)))
                 @if !is_collapsed {
                     (local_comments::render_file_comment_controls(&file.filename))
                     (local_comments::render_local_file_comment_button(&file.filename))
@@ -413,6 +559,7 @@ fn render_line_row_local(
     diff_line: &DiffLine,
     comments: &[LocalComment],
     repo_path: &str,
+    spec_query: &str,
     viewed_reply_ids: &std::collections::HashSet<switchy::uuid::Uuid>,
 ) -> Containers {
     let line = diff_line_to_local_line_number(diff_line);
@@ -426,7 +573,7 @@ fn render_line_row_local(
 
     container! {
         tr {
-            (render_line_numbers_inline(diff_line))
+            (permalink::render_line_numbers_linked(file_path, diff_line))
 
             td {
                 div
@@ -472,7 +619,7 @@ fn render_line_row_local(
         tr {
             td columns=3 {
                 (local_comments::render_local_line_comments(comments, file_path, line, repo_path, viewed_reply_ids))
-                (local_comments::render_local_create_comment_form(repo_path, file_path, line))
+                (local_comments::render_local_create_comment_form(repo_path, spec_query, file_path, line))
             }
         }
     }
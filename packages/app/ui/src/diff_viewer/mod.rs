@@ -1,24 +1,42 @@
 #![allow(clippy::implicit_hasher)]
 
+use std::collections::HashSet;
+
 use chadreview_diff_models::LineNumber;
 use chadreview_pr_models::{
-    Comment, CommentType, DiffFile, DiffHunk, DiffLine, FileStatus, LineType,
+    Comment, CommentType, DiffFile, DiffHunk, DiffLine, FileStatus, HunkSegment, LineType,
 };
 use hyperchad::template::{Containers, LayoutOverflow, container};
+use hyperchad::transformer::models::Selector;
 
 use crate::comment_thread::{
     add_comment_button_id, render_add_comment_button, render_comment_thread,
     render_create_comment_form,
 };
 
+pub mod copy;
+pub mod file_tree;
 #[cfg(feature = "local-git")]
 pub mod local;
+pub mod permalink;
+
+/// Runs of more context lines than this are unchanged regions, which can be
+/// folded away individually or all at once.
+pub const MAX_UNCHANGED_LINES: usize = 4;
 
+/// Render a pull request's diff with comment UI.
+///
+/// Files in `viewed_paths` are rendered collapsed (header only), and files
+/// in `generated_paths` collapsed with a "Show anyway" button.
 #[must_use]
+#[allow(clippy::too_many_arguments)]
 pub fn render(
     commit_sha: &str,
     diffs: &[DiffFile],
     comments: &[Comment],
+    viewed_paths: &HashSet<String>,
+    generated_paths: &HashSet<String>,
+    provider: &str,
     owner: &str,
     repo: &str,
     number: u64,
@@ -32,12 +50,23 @@ pub fn render(
     }
 
     container! {
-        section padding=20 gap=24 {
-            h2 font-size=20 font-weight=600 color="#24292f" margin-bottom=16 {
-                "Files changed"
-            }
-            @for diff_file in diffs {
-                (render_file(commit_sha, diff_file, comments, owner, repo, number))
+        section padding=20 direction=row gap=16 {
+            (file_tree::render_file_tree(diffs, viewed_paths))
+            div flex=1 gap=24 {
+                div direction=row align-items=center justify-content=space-between margin-bottom=16 {
+                    h2 font-size=20 font-weight=600 color="#24292f" {
+                        "Files changed"
+                    }
+                    (render_unchanged_region_controls())
+                }
+                @for diff_file in diffs {
+                    @let viewed = viewed_paths.contains(&diff_file.filename);
+                    @if !viewed && generated_paths.contains(&diff_file.filename) {
+                        (render_generated_file(commit_sha, diff_file, owner, repo, number))
+                    } @else {
+                        (render_file(commit_sha, diff_file, comments, viewed, provider, owner, repo, number))
+                    }
+                }
             }
         }
     }
@@ -57,77 +86,217 @@ pub fn render_readonly(diffs: &[DiffFile]) -> Containers {
     }
 
     container! {
-        section padding=20 gap=24 {
-            h2 font-size=20 font-weight=600 color="#24292f" margin-bottom=16 {
-                "Files changed"
-            }
-            @for diff_file in diffs {
-                (render_file_readonly(diff_file))
+        section padding=20 direction=row gap=16 {
+            (file_tree::render_file_tree(diffs, &HashSet::new()))
+            div flex=1 gap=24 {
+                h2 font-size=20 font-weight=600 color="#24292f" margin-bottom=16 {
+                    "Files changed"
+                }
+                @for diff_file in diffs {
+                    (render_file_readonly(diff_file))
+                }
             }
         }
     }
 }
 
-fn render_file(
+/// Render one file of a pull request's diff. Viewed files are collapsed to
+/// their header, which keeps the toggle for marking them unviewed.
+#[must_use]
+#[allow(clippy::too_many_arguments)]
+pub fn render_file(
     commit_sha: &str,
     file: &DiffFile,
     comments: &[Comment],
+    viewed: bool,
+    provider: &str,
     owner: &str,
     repo: &str,
     number: u64,
 ) -> Containers {
+    let container_id = file_tree::file_anchor_id(&file.filename);
+    let view_url = format!(
+        "/api/pr/file/view?owner={}&repo={}&number={number}&sha={}&path={}",
+        urlencoding::encode(owner),
+        urlencoding::encode(repo),
+        urlencoding::encode(commit_sha),
+        urlencoding::encode(&file.filename),
+    );
+    let page_url = pr_page_url(owner, repo, number);
+    let mut header_controls = copy::render_file_copy_actions(file, &page_url);
+    header_controls.extend(render_view_toggle(&view_url, &container_id, viewed));
+
+    if viewed {
+        return container! {
+            div id=(container_id) border="1px solid #d0d7de" border-radius=6 {
+                table width=100% {
+                    (render_file_header(file, header_controls))
+                }
+            }
+        };
+    }
+
     container! {
-        div border="1px solid #d0d7de" border-radius=6 {
+        div id=(container_id) border="1px solid #d0d7de" border-radius=6 {
             table width=100% {
-                (render_file_header(file))
-                (render_file_level_comments(comments, &file.filename, owner, repo, number))
-                @for hunk in &file.hunks {
-                    (render_hunk_header_row(hunk))
-                    tbody font-family="monospace" font-size=12 {
-                        @for line in &hunk.lines {
-                            (render_line_row(&file.filename, line))
-                            tr {
-                                td columns=3 {
-                                    @let line_number = line.into();
-                                    @let line_comments = comments
-                                        .iter()
-                                        .filter(|c| {
-                                            matches!(
-                                                &c.comment_type,
-                                                CommentType::LineLevelComment {
-                                                    path,
-                                                    line: l,
-                                                    ..
-                                                } if path == &file.filename
-                                                    && (line.new_line_number.is_some_and(|n| *l == LineNumber::New { line: n })
-                                                        || line.old_line_number.is_some_and(|n| *l == LineNumber::Old { line: n }))
-                                            )
-                                        });
-                                    (render_line_comments(commit_sha, line_comments, &file.filename, line_number, owner, repo, number))
-                                    (render_create_comment_form(owner, repo, number, commit_sha, &file.filename, line_number))
-                                }
-                            }
-                        }
-                    }
+                (render_file_header(file, header_controls))
+                (render_file_level_comments(comments, &file.filename, provider, owner, repo, number))
+                @for (index, hunk) in file.hunks.iter().enumerate() {
+                    (render_hunk_header_row(file, index, hunk, Some(&page_url)))
+                    (render_hunk_body(&file.filename, index, hunk, |line| {
+                        render_line_with_comments(commit_sha, file, line, comments, provider, owner, repo, number)
+                    }))
                 }
             }
         }
     }
 }
 
+/// URL of a pull request's page, which file and line permalinks are
+/// relative to.
+fn pr_page_url(owner: &str, repo: &str, number: u64) -> String {
+    format!(
+        "/pr?owner={}&repo={}&number={number}",
+        urlencoding::encode(owner),
+        urlencoding::encode(repo),
+    )
+}
+
+/// Render a line of a pull request's diff followed by its comments and the
+/// form for adding one.
+#[allow(clippy::too_many_arguments)]
+fn render_line_with_comments(
+    commit_sha: &str,
+    file: &DiffFile,
+    line: &DiffLine,
+    comments: &[Comment],
+    provider: &str,
+    owner: &str,
+    repo: &str,
+    number: u64,
+) -> Containers {
+    let line_number = line.into();
+    let line_comments = comments.iter().filter(|c| {
+        matches!(
+            &c.comment_type,
+            CommentType::LineLevelComment {
+                path,
+                line: l,
+                ..
+            } if path == &file.filename
+                && (line.new_line_number.is_some_and(|n| *l == LineNumber::New { line: n })
+                    || line.old_line_number.is_some_and(|n| *l == LineNumber::Old { line: n }))
+        )
+    });
+
+    container! {
+        (render_line_row(&file.filename, line))
+        tr {
+            td columns=3 {
+                (render_line_comments(commit_sha, line_comments, &file.filename, line_number, provider, owner, repo, number))
+                (render_create_comment_form(owner, repo, number, commit_sha, &file.filename, line_number))
+            }
+        }
+    }
+}
+
+/// Render a generated file of a pull request's diff collapsed to its header,
+/// with a "Show anyway" button loading the file from `/api/pr/file/diff`.
+#[must_use]
+pub fn render_generated_file(
+    commit_sha: &str,
+    file: &DiffFile,
+    owner: &str,
+    repo: &str,
+    number: u64,
+) -> Containers {
+    let container_id = file_tree::file_anchor_id(&file.filename);
+    let query = format!(
+        "owner={}&repo={}&number={number}&sha={}&path={}",
+        urlencoding::encode(owner),
+        urlencoding::encode(repo),
+        urlencoding::encode(commit_sha),
+        urlencoding::encode(&file.filename),
+    );
+    let mut header_controls = render_generated_badge();
+    header_controls.extend(copy::render_file_copy_actions(
+        file,
+        &pr_page_url(owner, repo, number),
+    ));
+    header_controls.extend(render_view_toggle(
+        &format!("/api/pr/file/view?{query}"),
+        &container_id,
+        false,
+    ));
+
+    container! {
+        div id=(container_id) border="1px solid #d0d7de" border-radius=6 {
+            table width=100% {
+                (render_file_header(file, header_controls))
+            }
+            (render_show_generated(&format!("/api/pr/file/diff?{query}"), &container_id))
+        }
+    }
+}
+
+/// Render the badge marking a file as generated.
+fn render_generated_badge() -> Containers {
+    container! {
+        span
+            padding-y=2
+            padding-x=8
+            border="1px solid #d0d7de"
+            border-radius=4
+            font-size=12
+            color="#57606a"
+        {
+            "Generated"
+        }
+    }
+}
+
+/// Render the note under a collapsed generated file, with the "Show anyway"
+/// button replacing the file's container with the response of `show_url`.
+fn render_show_generated(show_url: &str, container_id: &str) -> Containers {
+    container! {
+        div
+            padding=12
+            direction=row
+            align-items=center
+            gap=12
+            font-size=13
+            color="#57606a"
+        {
+            span { "Changes to generated files are hidden by default." }
+            button
+                type=button
+                padding-x=8
+                padding-y=2
+                cursor=pointer
+                background="#ffffff"
+                border="1px solid #d0d7de"
+                border-radius=4
+                font-size=12
+                color="#0969da"
+                hx-get=(show_url)
+                hx-target=(Selector::Id(container_id.to_string()))
+                hx-swap="outerHTML"
+            {
+                "Show anyway"
+            }
+        }
+    }
+}
+
 /// Render a single file in read-only mode (no comment UI).
 fn render_file_readonly(file: &DiffFile) -> Containers {
     container! {
-        div border="1px solid #d0d7de" border-radius=6 {
+        div id=(file_tree::file_anchor_id(&file.filename)) border="1px solid #d0d7de" border-radius=6 {
             table width=100% {
-                (render_file_header(file))
-                @for hunk in &file.hunks {
-                    (render_hunk_header_row(hunk))
-                    tbody font-family="monospace" font-size=12 {
-                        @for line in &hunk.lines {
-                            (render_line_row_readonly(line))
-                        }
-                    }
+                (render_file_header(file, Vec::new()))
+                @for (index, hunk) in file.hunks.iter().enumerate() {
+                    (render_hunk_header_row(file, index, hunk, None))
+                    (render_hunk_body(&file.filename, index, hunk, render_line_row_readonly))
                 }
             }
         }
@@ -183,7 +352,49 @@ fn render_line_row_readonly(diff_line: &DiffLine) -> Containers {
     }
 }
 
-fn render_file_header(file: &DiffFile) -> Containers {
+/// Render the "Mark as viewed" / "✓ Viewed" button, which replaces the
+/// file's container with the file re-rendered in its new state.
+fn render_view_toggle(view_url: &str, container_id: &str, viewed: bool) -> Containers {
+    container! {
+        @if viewed {
+            button
+                type=button
+                padding-x=8
+                padding-y=2
+                cursor=pointer
+                background="#ddf4ff"
+                border="1px solid #0969da"
+                border-radius=4
+                font-size=12
+                color="#0969da"
+                hx-delete=(view_url)
+                hx-target=(Selector::Id(container_id.to_string()))
+                hx-swap="outerHTML"
+            {
+                "✓ Viewed"
+            }
+        } @else {
+            button
+                type=button
+                padding-x=8
+                padding-y=2
+                cursor=pointer
+                background="#ffffff"
+                border="1px solid #d0d7de"
+                border-radius=4
+                font-size=12
+                color="#57606a"
+                hx-post=(view_url)
+                hx-target=(Selector::Id(container_id.to_string()))
+                hx-swap="outerHTML"
+            {
+                "Mark as viewed"
+            }
+        }
+    }
+}
+
+fn render_file_header(file: &DiffFile, view_toggle: Containers) -> Containers {
     let (status_text, status_color) = match file.status {
         FileStatus::Added => ("Added", "#1a7f37"),
         FileStatus::Modified => ("Modified", "#0969da"),
@@ -218,6 +429,8 @@ fn render_file_header(file: &DiffFile) -> Containers {
                         {
                             (status_text)
                         }
+                        (render_mode_change(file))
+                        (render_encoding_notice(file))
                         div overflow-x=(LayoutOverflow::Wrap { grid: true }) {
                             span
                                 font-family="monospace"
@@ -231,7 +444,10 @@ fn render_file_header(file: &DiffFile) -> Containers {
                             }
                         }
                     }
-                    (render_file_stats(file))
+                    div direction=row align-items=center gap=12 {
+                        (view_toggle)
+                        (render_file_stats(file))
+                    }
                 }
             }
         }
@@ -250,7 +466,7 @@ fn render_line_row(file_path: &str, diff_line: &DiffLine) -> Containers {
 
     container! {
         tr {
-            (render_line_numbers_inline(diff_line))
+            (permalink::render_line_numbers_linked(file_path, diff_line))
 
             td {
                 div
@@ -338,7 +554,33 @@ pub(crate) const fn render_diff_marker_inline(line: &DiffLine) -> &'static str {
     }
 }
 
-pub(crate) fn render_hunk_header_row(hunk: &DiffHunk) -> Containers {
+/// Class shared by the elements of a file's `index`th hunk, suffixed with
+/// `-lines`, `-expander` or `-region` for its parts.
+fn hunk_class(path: &str, index: usize) -> String {
+    format!("hunk-{}-{index}", file_tree::file_anchor_id(path))
+}
+
+/// Render a hunk's `@@` header, with buttons collapsing and expanding the
+/// hunk's lines and its copy actions. Expanding a hunk folds its unchanged
+/// regions.
+pub(crate) fn render_hunk_header_row(
+    file: &DiffFile,
+    index: usize,
+    hunk: &DiffHunk,
+    page_url: Option<&str>,
+) -> Containers {
+    let class = hunk_class(&file.filename, index);
+    let collapse_id = format!("{class}-collapse-btn");
+    let expand_id = format!("{class}-expand-btn");
+    let lines_class = format!(".{class}-lines");
+    let expander_class = format!(".{class}-expander");
+    let region_class = format!(".{class}-region");
+
+    let collapse_id_1 = collapse_id.clone();
+    let expand_id_1 = expand_id.clone();
+    let lines_class_1 = lines_class.clone();
+    let expander_class_1 = expander_class.clone();
+
     container! {
         thead {
             tr
@@ -356,7 +598,40 @@ pub(crate) fn render_hunk_header_row(hunk: &DiffHunk) -> Containers {
                     columns=2
                     width=1%
                 {
-                    div min-height=24 { "..." }
+                    div min-height=24 direction=row align-items=center {
+                        button
+                            id=(collapse_id.clone())
+                            type=button
+                            cursor=pointer
+                            background="transparent"
+                            color="#57606a"
+                            fx-click=fx {
+                                element(lines_class_1).no_display();
+                                element(expander_class_1).no_display();
+                                element(region_class).no_display();
+                                element_by_id(collapse_id_1).no_display();
+                                element_by_id(expand_id_1).display()
+                            }
+                        {
+                            "▼"
+                        }
+                        button
+                            id=(expand_id.clone())
+                            type=button
+                            cursor=pointer
+                            background="transparent"
+                            color="#57606a"
+                            hidden=(true)
+                            fx-click=fx {
+                                element(lines_class).display();
+                                element(expander_class).display();
+                                element_by_id(expand_id).no_display();
+                                element_by_id(collapse_id).display()
+                            }
+                        {
+                            "▶"
+                        }
+                    }
                 }
                 th
                     padding-x=12
@@ -366,8 +641,163 @@ pub(crate) fn render_hunk_header_row(hunk: &DiffHunk) -> Containers {
                     user-select=none
                     text-align=start
                 {
-                    "@@ -"(hunk.old_start)","(hunk.old_lines)" +"(hunk.new_start)","(hunk.new_lines)" @@"
+                    div direction=row align-items=center justify-content=space-between gap=12 {
+                        span {
+                            "@@ -"(hunk.old_start)","(hunk.old_lines)" +"(hunk.new_start)","(hunk.new_lines)" @@"
+                        }
+                        (copy::render_hunk_copy_actions(file, hunk, page_url))
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Render a hunk's lines with `render_line`. Unchanged regions are preceded
+/// by a hidden "Show unchanged lines" row, shown in their place when they
+/// are folded.
+pub(crate) fn render_hunk_body(
+    path: &str,
+    index: usize,
+    hunk: &DiffHunk,
+    render_line: impl Fn(&DiffLine) -> Containers,
+) -> Containers {
+    let class = hunk_class(path, index);
+
+    container! {
+        @for (segment_index, segment) in hunk.segments(MAX_UNCHANGED_LINES).into_iter().enumerate() {
+            @match segment {
+                HunkSegment::Lines(lines) => {
+                    tbody class=(format!("{class}-lines")) font-family="monospace" font-size=12 {
+                        @for line in lines {
+                            (render_line(line))
+                        }
+                    }
+                }
+                HunkSegment::Unchanged(lines) => {
+                    @let expander_id = format!("{class}-expander-{segment_index}");
+                    @let region_id = format!("{class}-region-{segment_index}");
+                    @let expander_id_1 = expander_id.clone();
+                    @let region_id_1 = region_id.clone();
+                    tbody
+                        id=(expander_id)
+                        class=(format!("unchanged-expander {class}-expander"))
+                        hidden=(true)
+                    {
+                        tr background="#f6f8fa" {
+                            td columns=3 padding-x=12 padding-y=4 {
+                                button
+                                    type=button
+                                    cursor=pointer
+                                    background="transparent"
+                                    font-size=12
+                                    color="#0969da"
+                                    fx-click=fx {
+                                        element_by_id(region_id_1).display();
+                                        element_by_id(expander_id_1).no_display()
+                                    }
+                                {
+                                    "⋯ Show " (lines.len()) " unchanged lines"
+                                }
+                            }
+                        }
+                    }
+                    tbody
+                        id=(region_id)
+                        class=(format!("unchanged-region {class}-region"))
+                        font-family="monospace"
+                        font-size=12
+                    {
+                        @for line in lines {
+                            (render_line(line))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Render "Collapse unchanged regions" / "Show unchanged regions" buttons,
+/// folding every unchanged region of the diff or unfolding them.
+#[must_use]
+pub fn render_unchanged_region_controls() -> Containers {
+    container! {
+        div direction=row gap=8 {
+            button
+                type=button
+                padding-x=12
+                padding-y=6
+                cursor=pointer
+                background="#ffffff"
+                border="1px solid #d0d7de"
+                border-radius=6
+                font-size=12
+                color="#24292f"
+                fx-click=fx {
+                    element(".unchanged-region").no_display();
+                    element(".unchanged-expander").display()
                 }
+            {
+                "Collapse unchanged regions"
+            }
+            button
+                type=button
+                padding-x=12
+                padding-y=6
+                cursor=pointer
+                background="#ffffff"
+                border="1px solid #d0d7de"
+                border-radius=6
+                font-size=12
+                color="#24292f"
+                fx-click=fx {
+                    element(".unchanged-region").display();
+                    element(".unchanged-expander").no_display()
+                }
+            {
+                "Show unchanged regions"
+            }
+        }
+    }
+}
+
+/// Render a badge telling the file's mode changed, like when it became
+/// executable, which a file with no other changes shows with an empty
+/// diff. Nothing if its mode didn't change.
+pub(crate) fn render_mode_change(file: &DiffFile) -> Containers {
+    container! {
+        @if let Some(mode_change) = file.mode_change {
+            span
+                padding-y=2
+                padding-x=8
+                border-radius=4
+                border="1px solid #d0d7de"
+                font-size=12
+                font-family="monospace"
+                color="#57606a"
+            {
+                "mode changed " (mode_change.to_string())
+            }
+        }
+    }
+}
+
+/// Render a badge telling some of the file's lines aren't valid UTF-8 and
+/// are shown decoded as Latin-1, so they may not read as the file's author
+/// intended. Nothing for UTF-8 files.
+pub(crate) fn render_encoding_notice(file: &DiffFile) -> Containers {
+    container! {
+        @if file.decoded_lossy {
+            span
+                padding-y=2
+                padding-x=8
+                border-radius=4
+                border="1px solid #d4a72c"
+                font-size=12
+                color="#9a6700"
+            {
+                "non-UTF-8 lines shown as Latin-1"
             }
         }
     }
@@ -392,11 +822,13 @@ pub fn comment_thread_container_id(comment_id: u64) -> String {
 }
 
 #[must_use]
+#[allow(clippy::too_many_arguments)]
 pub fn render_line_comments<'a>(
     commit_sha: &str,
     comments: impl Iterator<Item = &'a Comment>,
     file_path: &str,
     line: LineNumber,
+    provider: &str,
     owner: &str,
     repo: &str,
     number: u64,
@@ -411,7 +843,7 @@ pub fn render_line_comments<'a>(
         div id=(comment_thread_container_id(root_comment.id)) padding=8 direction=column gap=8 {
             div direction=column gap=8 {
                 @for comment in line_comments {
-                    (render_comment_thread(comment.id, comment, 0, owner, repo, number))
+                    (render_comment_thread(comment.id, comment, 0, provider, owner, repo, number))
                 }
                 (render_create_comment_form(owner, repo, number, commit_sha, file_path, line))
             }
@@ -422,6 +854,7 @@ pub fn render_line_comments<'a>(
 fn render_file_level_comments(
     comments: &[Comment],
     file_path: &str,
+    provider: &str,
     owner: &str,
     repo: &str,
     number: u64,
@@ -441,7 +874,7 @@ fn render_file_level_comments(
                 td {
                     div direction=column gap=12 padding=12 background="#f6f8fa" margin-bottom=12 {
                         @for comment in file_comments {
-                            (render_comment_thread(comment.id, comment, 0, owner, repo, number))
+                            (render_comment_thread(comment.id, comment, 0, provider, owner, repo, number))
                         }
                     }
                 }
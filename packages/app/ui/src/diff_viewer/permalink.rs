@@ -0,0 +1,155 @@
+//! Permalinks to files and lines in the diff viewer.
+//!
+//! A file is linked as `#src/main.rs` and a line as `#src/main.rs:n42` (new
+//! line 42) or `#src/main.rs:o10` (old line 10), using the same `n`/`o`
+//! notation as [`LineNumber`]. The fragments are resolved to element IDs by
+//! [`render_permalink_script`], which also expands collapsed files so links to
+//! viewed files still land on the right line.
+
+use chadreview_diff_models::LineNumber;
+use chadreview_pr_models::DiffLine;
+use hyperchad::template::{Containers, container};
+
+use super::file_tree::file_anchor_id;
+
+/// Script that scrolls to and highlights the file or line named by the URL
+/// fragment, on load and whenever the fragment changes.
+///
+/// The element IDs are derived from the path the same way as
+/// [`file_anchor_id`] and [`line_anchor_id`].
+const PERMALINK_SCRIPT: &str = r#"<script>
+(() => {
+    const slug = (path) => path.replace(/[^A-Za-z0-9]/gu, "-").toLowerCase();
+    let highlighted = null;
+    const highlight = (element) => {
+        if (highlighted) highlighted.style.outline = "";
+        highlighted = element;
+        element.style.outline = "2px solid #d4a72c";
+        element.scrollIntoView({ block: "center" });
+    };
+    const visible = (element) => element && element.getClientRects().length > 0;
+    const resolve = () => {
+        let fragment;
+        try {
+            fragment = decodeURIComponent(location.hash.slice(1));
+        } catch {
+            return;
+        }
+        if (!fragment) return;
+        const line = fragment.match(/^(.+):([no]\d+)$/);
+        const path = line && document.getElementById("file-" + slug(line[1])) ? line[1] : fragment;
+        const file = document.getElementById("file-" + slug(path));
+        if (!file) return;
+        if (!line || path !== line[1]) {
+            highlight(file);
+            return;
+        }
+        const id = "file-" + slug(path) + "-" + line[2];
+        const target = () => document.getElementById(id);
+        if (visible(target())) {
+            highlight(target().parentElement);
+            return;
+        }
+        // Expand a collapsed file and wait for its lines to show up
+        const content = document.getElementById("file-content-" + slug(path));
+        const expand = document.getElementById("file-expand-btn-" + slug(path));
+        if (!expand || (visible(content) && !target())) {
+            highlight(file);
+            return;
+        }
+        const observer = new MutationObserver(() => {
+            if (visible(target())) {
+                observer.disconnect();
+                highlight(target().parentElement);
+            }
+        });
+        observer.observe(document.body, { childList: true, subtree: true, attributes: true });
+        setTimeout(() => observer.disconnect(), 10000);
+        expand.click();
+    };
+    window.addEventListener("hashchange", resolve);
+    if (document.readyState === "loading") {
+        document.addEventListener("DOMContentLoaded", resolve);
+    } else {
+        resolve();
+    }
+})();
+</script>"#;
+
+/// URL fragment (without the `#`) linking to a file, or to a line in it.
+#[must_use]
+pub fn permalink_fragment(path: &str, line: Option<LineNumber>) -> String {
+    line.map_or_else(|| path.to_string(), |line| format!("{path}:{line}"))
+}
+
+/// Element ID of a line's number cell, the scroll target of its permalink.
+///
+/// Lines with both an old and a new number have two IDs, one per cell, so
+/// either form of the link resolves.
+#[must_use]
+pub fn line_anchor_id(path: &str, line: LineNumber) -> String {
+    format!("{}-{line}", file_anchor_id(path))
+}
+
+/// Render the script resolving permalink fragments. Include it once per
+/// page.
+#[must_use]
+pub fn render_permalink_script() -> Containers {
+    container! {
+        div {
+            raw { (PERMALINK_SCRIPT) }
+        }
+    }
+}
+
+/// Render the old and new line numbers as permalinks to the line.
+pub(crate) fn render_line_numbers_linked(file_path: &str, diff_line: &DiffLine) -> Containers {
+    let old = diff_line
+        .old_line_number
+        .map(|line| LineNumber::Old { line });
+    let new = diff_line
+        .new_line_number
+        .map(|line| LineNumber::New { line });
+
+    container! {
+        (render_line_number_cell(file_path, old))
+        (render_line_number_cell(file_path, new))
+    }
+}
+
+/// Render one line number cell. The cell carries the line's anchor ID, so a
+/// line with both numbers can be linked by either of them.
+fn render_line_number_cell(file_path: &str, line: Option<LineNumber>) -> Containers {
+    let Some(line) = line else {
+        return container! {
+            td
+                background="#f6f8fa"
+                border-right="1px solid #d0d7de"
+                padding-y=4
+                padding-x=8
+                width=1%
+            {}
+        };
+    };
+
+    container! {
+        td
+            id=(line_anchor_id(file_path, line))
+            background="#f6f8fa"
+            border-right="1px solid #d0d7de"
+            padding-y=4
+            padding-x=8
+            font-size=12
+            text-align=end
+            user-select=none
+            width=1%
+        {
+            anchor
+                href=(format!("#{}", permalink_fragment(file_path, Some(line))))
+                color="#57606a"
+            {
+                (line.number())
+            }
+        }
+    }
+}
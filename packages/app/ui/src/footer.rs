@@ -0,0 +1,41 @@
+use chadreview_pr_models::RateLimitStatus;
+use hyperchad::template::{Containers, container};
+
+/// Render the page footer, showing how much API quota is left when the
+/// provider reports it.
+#[must_use]
+pub fn render_footer(rate_limit: Option<&RateLimitStatus>) -> Containers {
+    let Some(rate_limit) = rate_limit else {
+        return container! { div id="app-footer" {} };
+    };
+
+    let quota_color = if rate_limit.is_low() {
+        "#cf222e"
+    } else {
+        "#57606a"
+    };
+
+    container! {
+        div
+            id="app-footer"
+            direction=row
+            justify-content=end
+            gap=8
+            padding-y=12
+            margin-top=16
+            border-top="1, #d0d7de"
+            font-size=12
+            color="#57606a"
+        {
+            span color=(quota_color) {
+                (format!(
+                    "API quota: {} of {} remaining",
+                    rate_limit.remaining, rate_limit.limit
+                ))
+            }
+            span {
+                (format!("resets at {}", rate_limit.reset_at.format("%H:%M UTC")))
+            }
+        }
+    }
+}
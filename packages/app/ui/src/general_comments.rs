@@ -7,20 +7,19 @@ use hyperchad::transformer::models::Selector;
 
 use crate::comment_thread;
 
+/// Element ID of the general comments count, replaced as comments come and go.
+pub const GENERAL_COMMENTS_SUMMARY_ID: &str = "general-comments-summary";
+/// Element ID of the general comment threads, replaced as comments come and go.
+pub const GENERAL_COMMENTS_LIST_ID: &str = "general-comments-list";
+
 #[must_use]
 pub fn render_general_comments_section(
     comments: &[Comment],
+    provider: &str,
     owner: &str,
     repo: &str,
     number: u64,
 ) -> Containers {
-    let general_comments: Vec<&Comment> = comments
-        .iter()
-        .filter(|c| matches!(c.comment_type, CommentType::General))
-        .collect();
-
-    let count = general_comments.len();
-
     container! {
         div
             border="1, #d0d7de"
@@ -31,38 +30,71 @@ pub fn render_general_comments_section(
             background=#ffffff
         {
             details open {
-                summary
-                    cursor=pointer
-                    font-weight=600
-                    font-size=16
-                    padding=8
-                    user-select=none
-                {
-                    (format!("General Comments ({})", count))
-                }
-                div
-                    id="general-comments-list"
-                    direction=column
-                    gap=16
-                    margin-top=16
-                {
-                    @for comment in general_comments {
-                        (comment_thread::render_comment_thread(
-                            0,
-                            comment,
-                            0,
-                            owner,
-                            repo,
-                            number,
-                        ))
-                    }
-                }
+                (render_general_comments_summary(comments))
+                (render_general_comments_list(comments, provider, owner, repo, number))
                 (render_create_general_comment_form(owner, repo, number))
             }
         }
     }
 }
 
+/// Render the section's heading with the number of general comments.
+#[must_use]
+pub fn render_general_comments_summary(comments: &[Comment]) -> Containers {
+    let count = comments
+        .iter()
+        .filter(|c| matches!(c.comment_type, CommentType::General))
+        .count();
+
+    container! {
+        summary
+            id=(GENERAL_COMMENTS_SUMMARY_ID)
+            cursor=pointer
+            font-weight=600
+            font-size=16
+            padding=8
+            user-select=none
+        {
+            (format!("General Comments ({})", count))
+        }
+    }
+}
+
+/// Render the threads of the general comments among `comments`.
+#[must_use]
+pub fn render_general_comments_list(
+    comments: &[Comment],
+    provider: &str,
+    owner: &str,
+    repo: &str,
+    number: u64,
+) -> Containers {
+    let general_comments = comments
+        .iter()
+        .filter(|c| matches!(c.comment_type, CommentType::General));
+
+    container! {
+        div
+            id=(GENERAL_COMMENTS_LIST_ID)
+            direction=column
+            gap=16
+            margin-top=16
+        {
+            @for comment in general_comments {
+                (comment_thread::render_comment_thread(
+                    0,
+                    comment,
+                    0,
+                    provider,
+                    owner,
+                    repo,
+                    number,
+                ))
+            }
+        }
+    }
+}
+
 #[must_use]
 pub fn render_create_general_comment_form(owner: &str, repo: &str, number: u64) -> Containers {
     let api_url = format!("/api/pr/comment?owner={owner}&repo={repo}&number={number}");
@@ -71,7 +103,7 @@ pub fn render_create_general_comment_form(owner: &str, repo: &str, number: u64)
         form
             hx-post=(api_url)
             hx-swap=beforeend
-            hx-target=(Selector::Id(String::from("general-comments-list")))
+            hx-target=(Selector::Id(GENERAL_COMMENTS_LIST_ID.to_string()))
         {
             div
                 margin-top=16
@@ -2,12 +2,54 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 #![allow(clippy::multiple_crate_versions)]
 
+pub mod autocomplete;
+pub mod checklist;
 pub mod comment_thread;
+pub mod commits_tab;
 pub mod diff_viewer;
+pub mod footer;
 pub mod general_comments;
+pub mod markdown;
+pub mod markdown_preview;
+pub mod merge_panel;
 pub mod pr_header;
+pub mod review_form;
 
+#[cfg(feature = "local-git")]
+pub mod local_agents;
+#[cfg(feature = "local-git")]
+pub mod local_attachments;
 #[cfg(feature = "local-git")]
 pub mod local_comments;
 #[cfg(feature = "local-git")]
+pub mod local_commit;
+#[cfg(feature = "local-git")]
+pub mod local_commits;
+#[cfg(feature = "local-git")]
+pub mod local_conflicts;
+#[cfg(feature = "local-git")]
+pub mod local_describe;
+#[cfg(feature = "local-git")]
+pub mod local_drafts;
+#[cfg(feature = "local-git")]
+pub mod local_export;
+#[cfg(feature = "local-git")]
 pub mod local_header;
+#[cfg(feature = "local-git")]
+pub mod local_import;
+#[cfg(feature = "local-git")]
+pub mod local_outdated;
+#[cfg(feature = "local-git")]
+pub mod local_saved_replies;
+#[cfg(feature = "local-git")]
+pub mod local_search;
+#[cfg(feature = "local-git")]
+pub mod local_share;
+#[cfg(feature = "local-git")]
+pub mod local_staging;
+#[cfg(feature = "local-git")]
+pub mod local_summaries;
+#[cfg(feature = "local-git")]
+pub mod local_symbols;
+#[cfg(feature = "local-git")]
+pub mod local_thread_filter;
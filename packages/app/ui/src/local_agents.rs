@@ -0,0 +1,101 @@
+//! AI agent picker for local comment forms.
+//!
+//! Comment forms render the agent as a free-text `ai_agent` input so they
+//! work without scripts. This script replaces each of them with a dropdown
+//! of the agents the installed providers offer for the repository, fetched
+//! once from [`AGENTS_ROUTE`], showing each agent's description and default
+//! model.
+
+use hyperchad_template::{Containers, container};
+use serde_json::Value;
+
+use crate::local_drafts::script_json;
+
+/// Route listing the available agents as JSON.
+pub const AGENTS_ROUTE: &str = "/api/local/ai/agents";
+
+/// Script replacing agent inputs with dropdowns. `__REPO__` is replaced
+/// with the repository path and `__ROUTE__` with [`AGENTS_ROUTE`], as JSON.
+///
+/// Inputs added after the page loads (new comment forms, edited comments)
+/// are picked up as they're inserted.
+const AGENT_PICKER_SCRIPT: &str = r#"<script>
+(() => {
+    const params = new URLSearchParams({ repo: __REPO__ });
+    let agents = null;
+    const loadAgents = () => {
+        agents ??= fetch(__ROUTE__ + "?" + params)
+            .then((response) => response.ok ? response.json() : [])
+            .catch(() => []);
+        return agents;
+    };
+    const modelText = (agent) => agent && agent.default_model
+        ? "Default model: " + agent.default_model
+        : "";
+    const upgrade = async (input) => {
+        if (input.dataset.agentPicker) return;
+        input.dataset.agentPicker = "pending";
+        const list = await loadAgents();
+        if (list.length === 0 || !input.isConnected) return;
+
+        const select = document.createElement("select");
+        select.name = input.name;
+        select.className = "ai-agent-picker";
+        select.style.cssText = "flex: 1; padding: 8px; border: 1px solid #d0d7de; border-radius: 6px; font-size: 14px; background: #ffffff;";
+        select.add(new Option("No AI action", ""));
+        for (const agent of list) {
+            const option = new Option(agent.name + " — " + agent.description, agent.id);
+            option.title = agent.description;
+            select.add(option);
+        }
+        // Keep agents typed in or saved before that aren't offered any more
+        if (input.value && !list.some((agent) => agent.id === input.value)) {
+            select.add(new Option(input.value, input.value));
+        }
+        select.value = input.value;
+
+        const model = document.createElement("span");
+        model.className = "ai-agent-model";
+        model.style.cssText = "font-size: 12px; color: #57606a;";
+        const showModel = () => {
+            model.textContent = modelText(list.find((agent) => agent.id === select.value));
+        };
+        select.addEventListener("change", showModel);
+        showModel();
+
+        input.replaceWith(select, model);
+    };
+    const upgradeAll = (root) => {
+        if (!(root instanceof Element)) return;
+        const selector = 'input[name="ai_agent"]';
+        if (root.matches(selector)) upgrade(root);
+        root.querySelectorAll(selector).forEach(upgrade);
+    };
+    const start = () => {
+        upgradeAll(document.body);
+        new MutationObserver((mutations) => {
+            for (const mutation of mutations) mutation.addedNodes.forEach(upgradeAll);
+        }).observe(document.body, { childList: true, subtree: true });
+    };
+    if (document.readyState === "loading") {
+        document.addEventListener("DOMContentLoaded", start);
+    } else {
+        start();
+    }
+})();
+</script>"#;
+
+/// Render the script replacing agent inputs with dropdowns of the agents
+/// available for the repository at `repo_path`. Include it once per page.
+#[must_use]
+pub fn render_agent_picker_script(repo_path: &str) -> Containers {
+    let script = AGENT_PICKER_SCRIPT
+        .replace("__REPO__", &script_json(&Value::from(repo_path)))
+        .replace("__ROUTE__", &script_json(&Value::from(AGENTS_ROUTE)));
+
+    container! {
+        div {
+            raw { (script) }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Image attachments in local comment forms.
+//!
+//! Images pasted or dropped into a comment form, or picked with its
+//! "Attach image" button, are uploaded to the comment store and inserted
+//! into the comment as markdown images served from
+//! [`ATTACHMENT_ROUTE_PREFIX`].
+
+use hyperchad_template::{Containers, container};
+use serde_json::Value;
+use switchy::uuid::Uuid;
+
+use crate::local_drafts::script_json;
+
+/// Path prefix attachments are served from, followed by the attachment ID.
+pub const ATTACHMENT_ROUTE_PREFIX: &str = "/api/local/attachment/";
+
+/// Script uploading images from comment forms. `__REPO__` is replaced with
+/// the repository path, as JSON.
+const ATTACHMENT_SCRIPT: &str = r#"<script>
+(() => {
+    const repo = __REPO__;
+    const prefixes = ["local-comment-form-", "local-file-comment-form-", "local-reply-form-", "local-edit-form-", "general-comment-form"];
+    const commentForm = (element) => {
+        const form = element instanceof Element ? element.closest("form") : null;
+        return form && prefixes.some((prefix) => form.id.startsWith(prefix)) ? form : null;
+    };
+    const bodyOf = (form) => form.querySelector('textarea[name="body"]');
+    const insert = (textarea, text) => {
+        const start = textarea.selectionStart ?? textarea.value.length;
+        const end = textarea.selectionEnd ?? start;
+        textarea.value = textarea.value.slice(0, start) + text + textarea.value.slice(end);
+        textarea.selectionStart = textarea.selectionEnd = start + text.length;
+        // Let the draft autosave see the change
+        textarea.dispatchEvent(new Event("input", { bubbles: true }));
+    };
+    const upload = async (textarea, file) => {
+        const params = new URLSearchParams({ repo, content_type: file.type, name: file.name });
+        const response = await fetch("/api/local/attachment?" + params, { method: "POST", body: file });
+        if (!response.ok) {
+            alert("Failed to attach " + file.name + ": " + await response.text());
+            return;
+        }
+        insert(textarea, await response.text() + "\n");
+    };
+    const uploadAll = (textarea, files) => {
+        const images = [...files].filter((file) => file.type.startsWith("image/"));
+        images.reduce((previous, file) => previous.then(() => upload(textarea, file)), Promise.resolve());
+        return images.length > 0;
+    };
+    const addButton = (form) => {
+        const textarea = bodyOf(form);
+        if (!textarea || form.querySelector(".attach-image")) return;
+        const input = document.createElement("input");
+        input.type = "file";
+        input.accept = "image/*";
+        input.multiple = true;
+        input.hidden = true;
+        input.addEventListener("change", () => {
+            uploadAll(textarea, input.files);
+            input.value = "";
+        });
+        const button = document.createElement("button");
+        button.type = "button";
+        button.className = "attach-image";
+        button.textContent = "Attach image";
+        button.style.cssText = "align-self: flex-start; padding: 4px 8px; border: 1px solid #d0d7de; border-radius: 6px; font-size: 12px; color: #57606a; background: #ffffff; cursor: pointer;";
+        button.addEventListener("click", () => input.click());
+        textarea.after(button, input);
+    };
+    document.addEventListener("paste", (event) => {
+        const form = commentForm(event.target);
+        if (form && event.target === bodyOf(form) && uploadAll(event.target, event.clipboardData.files)) {
+            event.preventDefault();
+        }
+    });
+    document.addEventListener("dragover", (event) => {
+        if (commentForm(event.target)) event.preventDefault();
+    });
+    document.addEventListener("drop", (event) => {
+        const form = commentForm(event.target);
+        if (form && bodyOf(form) && uploadAll(bodyOf(form), event.dataTransfer.files)) {
+            event.preventDefault();
+        }
+    });
+    document.addEventListener("focusin", (event) => {
+        const form = commentForm(event.target);
+        if (form) addButton(form);
+    });
+    const addAll = () => {
+        for (const form of document.querySelectorAll("form[id]")) {
+            if (commentForm(form)) addButton(form);
+        }
+    };
+    if (document.readyState === "loading") {
+        document.addEventListener("DOMContentLoaded", addAll);
+    } else {
+        addAll();
+    }
+})();
+</script>"#;
+
+/// URL the attachment `id` of the repository at `repo_path` is served from.
+#[must_use]
+pub fn attachment_url(repo_path: &str, id: Uuid) -> String {
+    format!(
+        "{ATTACHMENT_ROUTE_PREFIX}{id}?repo={}",
+        urlencoding::encode(repo_path)
+    )
+}
+
+/// Markdown showing the attachment `id`, with the uploaded file's `name`
+/// as its alt text.
+#[must_use]
+pub fn attachment_markdown(repo_path: &str, id: Uuid, name: &str) -> String {
+    let alt: String = name
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | '\n' | '\r'))
+        .collect();
+    let alt = if alt.trim().is_empty() {
+        "image"
+    } else {
+        alt.trim()
+    };
+
+    format!("![{alt}]({})", attachment_url(repo_path, id))
+}
+
+/// Render the script uploading images attached in comment forms. Include
+/// it once per page.
+#[must_use]
+pub fn render_attachment_script(repo_path: &str) -> Containers {
+    let script = ATTACHMENT_SCRIPT.replace("__REPO__", &script_json(&Value::from(repo_path)));
+
+    container! {
+        div {
+            raw { (script) }
+        }
+    }
+}
@@ -7,15 +7,16 @@
 use std::collections::HashSet;
 
 use chadreview_local_comment_models::{
-    AiExecutionStatus, ExecutionDetails, LineNumber, LocalComment, LocalCommentType, ProgressEntry,
-    ThreadState,
+    AiExecutionStatus, CommentRevision, ExecutionDetails, LineNumber, LocalComment,
+    LocalCommentType, ProgressEntry, ThreadState,
 };
-use hyperchad::markdown::markdown_to_container;
 use hyperchad::template::container;
 use hyperchad::transformer::models::Selector;
 use hyperchad_router::Container;
 use switchy::uuid::Uuid;
 
+use crate::markdown::markdown_to_container;
+
 // =============================================================================
 // ID Generation Functions
 // =============================================================================
@@ -35,6 +36,13 @@ pub fn ai_status_str_id(comment_id: Uuid) -> String {
     format!("ai-status-{comment_id}")
 }
 
+/// Generate ID for the slot streaming an AI response into a thread before
+/// it's saved as a reply.
+#[must_use]
+pub fn ai_partial_reply_str_id(thread_id: Uuid) -> String {
+    format!("ai-partial-reply-{thread_id}")
+}
+
 /// Generate ID for the "+" button on a line.
 #[must_use]
 pub fn local_add_comment_button_id(file_path: &str, line: LineNumber) -> String {
@@ -107,6 +115,12 @@ pub fn local_comment_body_id(comment_id: Uuid) -> String {
     format!("local-comment-body-{comment_id}")
 }
 
+/// Generate ID for the edit form of a comment.
+#[must_use]
+pub fn local_edit_form_id(comment_id: Uuid) -> String {
+    format!("local-edit-form-{comment_id}")
+}
+
 /// Generate ID for the collapse button (▼) on a reply.
 #[must_use]
 pub fn local_reply_collapse_btn_id(comment_id: Uuid) -> String {
@@ -491,15 +505,20 @@ pub fn render_local_add_comment_button(file_path: &str, line: LineNumber) -> Con
 /// Render the comment form for a specific line.
 ///
 /// This form is hidden by default and shown when the user clicks the "+" button.
+/// `spec_query` is the query string of the diff the comment is made on.
 #[must_use]
 pub fn render_local_create_comment_form(
     repo_path: &str,
+    spec_query: &str,
     file_path: &str,
     line: LineNumber,
 ) -> Container {
     let form_id = local_comment_form_id(file_path, line);
     let container_id = local_line_comments_container_id(file_path, line);
-    let api_url = format!("/api/local/comment?repo={}", urlencoding::encode(repo_path));
+    let api_url = format!(
+        "/api/local/comment?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
 
     let (side, line_num) = match line {
         LineNumber::New { line } => ("new", line),
@@ -539,7 +558,7 @@ pub fn render_local_create_comment_form(
                     font-size=14;
 
                 div margin-top=8 {
-                    (render_ai_action_selector("ai_agent", DEFAULT_AGENTS))
+                    (render_ai_action_selector("ai_agent", None))
                 }
 
                 div direction=row gap=8 margin-top=8 {
@@ -639,12 +658,20 @@ pub fn render_local_file_comment_button(file_path: &str) -> Container {
     .into()
 }
 
-/// Render the file-level comment form.
+/// Render the file-level comment form. `spec_query` is the query string of
+/// the diff the comment is made on.
 #[must_use]
-pub fn render_local_file_comment_form(repo_path: &str, file_path: &str) -> Container {
+pub fn render_local_file_comment_form(
+    repo_path: &str,
+    spec_query: &str,
+    file_path: &str,
+) -> Container {
     let form_id = local_file_comment_form_id(file_path);
     let container_id = local_file_comments_container_id(file_path);
-    let api_url = format!("/api/local/comment?repo={}", urlencoding::encode(repo_path));
+    let api_url = format!(
+        "/api/local/comment?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
 
     container! {
         form
@@ -677,7 +704,7 @@ pub fn render_local_file_comment_form(repo_path: &str, file_path: &str) -> Conta
                     background="#ffffff";
 
                 div margin-top=8 {
-                    (render_ai_action_selector("ai_agent", DEFAULT_AGENTS))
+                    (render_ai_action_selector("ai_agent", None))
                 }
 
                 div direction=row gap=8 margin-top=8 {
@@ -750,14 +777,28 @@ pub fn render_local_file_comments(
 // =============================================================================
 
 /// Render the reply form for a comment thread.
+///
+/// The thread's file and line are included for saved replies to fill in
+/// their placeholders; the reply route ignores them.
 #[must_use]
-pub fn render_local_reply_form(repo_path: &str, thread_id: Uuid) -> Container {
+pub fn render_local_reply_form(
+    repo_path: &str,
+    thread_id: Uuid,
+    thread_type: &LocalCommentType,
+) -> Container {
     let form_id = local_reply_form_id(thread_id);
     let thread_container_id = local_comment_thread_id(thread_id);
     let api_url = format!(
         "/api/local/comment/reply?repo={}",
         urlencoding::encode(repo_path)
     );
+    let (path, line) = match thread_type {
+        LocalCommentType::LineLevelComment { path, line } => {
+            (Some(path.as_str()), Some(line.number()))
+        }
+        LocalCommentType::FileLevelComment { path } => (Some(path.as_str()), None),
+        _ => (None, None),
+    };
 
     container! {
         form
@@ -777,6 +818,12 @@ pub fn render_local_reply_form(repo_path: &str, thread_id: Uuid) -> Container {
                 margin-top=8
             {
                 input type=hidden name="thread_id" value=(thread_id);
+                @if let Some(path) = path {
+                    input type=hidden name="path" value=(path);
+                }
+                @if let Some(line) = line {
+                    input type=hidden name="line" value=(line);
+                }
 
                 textarea
                     name="body"
@@ -789,7 +836,7 @@ pub fn render_local_reply_form(repo_path: &str, thread_id: Uuid) -> Container {
                     background="#ffffff";
 
                 div margin-top=8 {
-                    (render_ai_action_selector("ai_agent", DEFAULT_AGENTS))
+                    (render_ai_action_selector("ai_agent", None))
                 }
 
                 div direction=row gap=8 margin-top=8 {
@@ -846,6 +893,217 @@ pub fn render_local_reply_button(comment_id: Uuid) -> Container {
     .into()
 }
 
+/// Render an edit button for a comment, showing its edit form.
+#[must_use]
+pub fn render_local_edit_button(comment_id: Uuid) -> Container {
+    let form_id = local_edit_form_id(comment_id);
+
+    container! {
+        button
+            type=button
+            color="#0969da"
+            padding-x=8
+            padding-y=4
+            cursor=pointer
+            font-size=12
+            fx-click=fx { element_by_id(form_id).display() }
+        {
+            "Edit"
+        }
+    }
+    .into()
+}
+
+/// Render the (initially hidden) form editing a comment's body.
+///
+/// Saving replaces the whole thread for root comments (`thread_id` ==
+/// `comment.id`) and just the reply otherwise.
+#[must_use]
+pub fn render_local_edit_form(
+    thread_id: Uuid,
+    comment: &LocalComment,
+    repo_path: &str,
+) -> Container {
+    let form_id = local_edit_form_id(comment.id);
+    let api_url = format!(
+        "/api/local/comment/update?repo={}&thread_id={}&comment_id={}",
+        urlencoding::encode(repo_path),
+        thread_id,
+        comment.id
+    );
+    let target_id = if thread_id == comment.id {
+        local_comment_thread_id(thread_id)
+    } else {
+        format!("comment-{}", comment.id)
+    };
+
+    container! {
+        form
+            id=(form_id)
+            hidden
+            hx-put=(api_url)
+            hx-target=(Selector::Id(target_id))
+            hx-swap="outerHTML"
+        {
+            div
+                padding=12
+                background="#f6f8fa"
+                border="1px solid #d0d7de"
+                border-radius=6
+                gap=8
+                margin-top=8
+            {
+                textarea
+                    name="body"
+                    height=80
+                    padding=8
+                    border="1px solid #d0d7de"
+                    border-radius=6
+                    font-size=14
+                    background="#ffffff"
+                {
+                    (comment.body)
+                }
+
+                div direction=row gap=8 margin-top=8 {
+                    button
+                        type=submit
+                        background="#1a7f37"
+                        color="#ffffff"
+                        padding-x=16
+                        padding-y=8
+                        border-radius=6
+                        font-weight=600
+                        font-size=14
+                        cursor=pointer
+                    {
+                        "Save"
+                    }
+                    button
+                        type=button
+                        color="#57606a"
+                        padding-x=16
+                        padding-y=8
+                        border-radius=6
+                        cursor=pointer
+                        font-size=14
+                        fx-click=fx { element_by_id(form_id).no_display() }
+                    {
+                        "Cancel"
+                    }
+                }
+            }
+        }
+    }
+    .into()
+}
+
+/// Render the "Show edits" disclosure listing a comment's previous bodies,
+/// newest first.
+#[must_use]
+pub fn render_comment_revisions(revisions: &[CommentRevision]) -> Container {
+    let label = if revisions.len() == 1 {
+        "Show 1 edit".to_string()
+    } else {
+        format!("Show {} edits", revisions.len())
+    };
+
+    container! {
+        details margin-top=4 {
+            summary
+                cursor=pointer
+                font-size=12
+                color="#57606a"
+            {
+                (label)
+            }
+            div gap=8 margin-top=8 {
+                @for revision in revisions.iter().rev() {
+                    div
+                        padding=8
+                        background="#f6f8fa"
+                        border="1px solid #d0d7de"
+                        border-radius=6
+                        gap=4
+                    {
+                        span font-size=12 color="#57606a" {
+                            "Written " (format_time_ago(revision.written_at))
+                        }
+                        div color="#57606a" font-size=14 {
+                            (markdown_to_container(&revision.body))
+                        }
+                    }
+                }
+            }
+        }
+    }
+    .into()
+}
+
+/// Render the "Changes made" disclosure showing the unified diff of the
+/// changes an AI action made to the working tree.
+#[must_use]
+pub fn render_ai_changes(changes: &str) -> Container {
+    let files = changes
+        .lines()
+        .filter(|line| line.starts_with("diff --git "))
+        .count();
+    let label = if files == 1 {
+        "Changes made (1 file)".to_string()
+    } else {
+        format!("Changes made ({files} files)")
+    };
+
+    container! {
+        details margin-top=4 {
+            summary
+                cursor=pointer
+                font-size=12
+                font-weight=600
+                color="#0969da"
+            {
+                (label)
+            }
+            div
+                margin-top=8
+                border="1px solid #d0d7de"
+                border-radius=6
+                font-family="monospace"
+                font-size=12
+            {
+                @for line in changes.lines() {
+                    div
+                        background=(change_line_background(line))
+                        color=(if line.starts_with("diff --git ") || line.starts_with("@@") { "#57606a" } else { "#24292f" })
+                        padding-x=8
+                        white-space=preserve-wrap
+                    {
+                        (line)
+                    }
+                }
+            }
+        }
+    }
+    .into()
+}
+
+/// Background of a line of a unified diff, by its diff marker.
+fn change_line_background(line: &str) -> &'static str {
+    if line.starts_with("diff --git ")
+        || line.starts_with("--- ")
+        || line.starts_with("+++ ")
+        || line.starts_with("@@")
+    {
+        return "#f6f8fa";
+    }
+
+    match line.chars().next() {
+        Some('+') => "#e6ffec",
+        Some('-') => "#ffebe9",
+        _ => "#ffffff",
+    }
+}
+
 /// Render a delete button for a comment.
 ///
 /// For root comments (`thread_id` == `comment_id`), deletes the entire thread.
@@ -886,6 +1144,53 @@ pub fn render_local_delete_button(thread_id: Uuid, comment_id: Uuid, repo_path:
     .into()
 }
 
+/// Whether a thread's root comment has a suggested change that can be
+/// applied to the working tree.
+///
+/// Suggestions can only be applied to lines on the new side of the diff,
+/// and are not offered once the thread is resolved.
+#[must_use]
+pub fn has_applicable_suggestion(comment: &LocalComment) -> bool {
+    matches!(
+        comment.comment_type,
+        LocalCommentType::LineLevelComment {
+            line: LineNumber::New { .. },
+            ..
+        }
+    ) && comment.state != ThreadState::Resolved
+        && !chadreview_markdown::extract_suggestions(&comment.body).is_empty()
+}
+
+/// Render an "Apply suggestion" button for a thread.
+///
+/// Applying the suggestion edits the working tree file and resolves the
+/// thread, which is re-rendered in place.
+#[must_use]
+pub fn render_local_apply_suggestion_button(thread_id: Uuid, repo_path: &str) -> Container {
+    let apply_url = format!(
+        "/api/local/comment/suggestion?repo={}&thread_id={thread_id}",
+        urlencoding::encode(repo_path),
+    );
+
+    container! {
+        button
+            type=button
+            color="#1a7f37"
+            padding-x=8
+            padding-y=4
+            cursor=pointer
+            font-size=12
+            font-weight=500
+            hx-post=(apply_url)
+            hx-target=(Selector::Id(local_comment_thread_id(thread_id)))
+            hx-swap="outerHTML"
+        {
+            "Apply suggestion"
+        }
+    }
+    .into()
+}
+
 /// Render state action buttons for a comment thread.
 ///
 /// Shows different buttons based on current state:
@@ -1215,6 +1520,9 @@ pub fn render_local_comment_with_reply(
                     (&comment.author.name)
                 }
                 span font-size=12 color="#57606a" { (time_ago) }
+                @if comment.edited_at.is_some() {
+                    span font-size=12 color="#57606a" { "(edited)" }
+                }
                 @match comment.state {
                     ThreadState::Resolved => {
                         span
@@ -1268,6 +1576,11 @@ pub fn render_local_comment_with_reply(
                     (markdown_to_container(&comment.body))
                 }
 
+                // Previous bodies of an edited comment
+                @if !comment.revisions.is_empty() {
+                    (render_comment_revisions(&comment.revisions))
+                }
+
                 // AI action badge (if present)
                 @if let Some(ref ai_action) = comment.ai_action {
                     div direction=row align-items=center gap=4 margin-top=4 {
@@ -1290,17 +1603,24 @@ pub fn render_local_comment_with_reply(
                     (render_ai_status_container(comment.id, status))
                 }
 
-                // Action buttons - Reply and Delete
+                // Edit form
+                (render_local_edit_form(comment.id, comment, repo_path))
+
+                // Action buttons - Reply, Edit, Delete and Apply suggestion
                 div direction=row gap=12 margin-top=4 {
                     (render_local_reply_button(comment.id))
+                    (render_local_edit_button(comment.id))
                     (render_local_delete_button(comment.id, comment.id, repo_path))
+                    @if has_applicable_suggestion(comment) {
+                        (render_local_apply_suggestion_button(comment.id, repo_path))
+                    }
                 }
 
                 // Replies
                 (render_thread_replies(comment.id, &comment.replies, repo_path, viewed_reply_ids))
 
                 // Reply form
-                (render_local_reply_form(repo_path, comment.id))
+                (render_local_reply_form(repo_path, comment.id, &comment.comment_type))
             }
         }
     }
@@ -1327,11 +1647,52 @@ pub fn render_thread_replies(
                 @let is_viewed = viewed_reply_ids.contains(&reply.id);
                 (render_local_comment_item(reply, thread_id, repo_path, is_viewed))
             }
+            (render_partial_reply(thread_id, None))
         }
     }
     .into()
 }
 
+/// Render the slot streaming an AI response into thread `thread_id`, with
+/// the `(author, text)` generated so far, or empty if no response is being
+/// generated.
+///
+/// The slot is replaced via SSE as text arrives, and emptied when the
+/// response is saved as a reply (re-rendering the replies) or fails.
+///
+/// # Panics
+///
+/// Panics if the container macro produces an empty iterator (should never happen).
+#[must_use]
+pub fn render_partial_reply(thread_id: Uuid, partial: Option<(&str, &str)>) -> Container {
+    let slot_id = ai_partial_reply_str_id(thread_id);
+
+    container! {
+        div id=(slot_id) {
+            @if let Some((author, text)) = partial {
+                div
+                    background="#ffffff"
+                    border="1px dashed #54aeff"
+                    border-radius=6
+                    margin-bottom=8
+                    margin-left=20
+                {
+                    div padding=12 direction=row align-items=center gap=8 {
+                        span font-weight=600 font-size=14 color="#24292f" { (author) }
+                        span font-size=12 color="#0969da" { "writing..." }
+                    }
+                    div padding=12 padding-top=0 color="#24292f" font-size=14 {
+                        (markdown_to_container(text))
+                    }
+                }
+            }
+        }
+    }
+    .into_iter()
+    .next()
+    .unwrap()
+}
+
 /// Render a single reply comment item with collapse/expand and viewed functionality.
 ///
 /// Root comments are rendered by `render_local_comment_with_reply` which includes
@@ -1431,6 +1792,9 @@ pub fn render_local_comment_item(
                     (&comment.author.name)
                 }
                 span font-size=12 color="#57606a" { (time_ago) }
+                @if comment.edited_at.is_some() {
+                    span font-size=12 color="#57606a" { "(edited)" }
+                }
 
                 // Spacer
                 div flex=1 {}
@@ -1453,6 +1817,16 @@ pub fn render_local_comment_item(
                     (markdown_to_container(&comment.body))
                 }
 
+                // Previous bodies of an edited comment
+                @if !comment.revisions.is_empty() {
+                    (render_comment_revisions(&comment.revisions))
+                }
+
+                // Changes the AI action made to the working tree
+                @if let Some(ref changes) = comment.ai_changes {
+                    (render_ai_changes(changes))
+                }
+
                 // AI action badge (if present)
                 @if let Some(ref ai_action) = comment.ai_action {
                     div direction=row align-items=center gap=4 margin-top=4 {
@@ -1475,9 +1849,13 @@ pub fn render_local_comment_item(
                     (render_ai_status_container(comment.id, status))
                 }
 
-                // Action buttons - Reply and Delete
+                // Edit form
+                (render_local_edit_form(thread_id, comment, repo_path))
+
+                // Action buttons - Reply, Edit and Delete
                 div direction=row gap=12 margin-top=4 {
                     (render_local_reply_button(thread_id))
+                    (render_local_edit_button(comment.id))
                     (render_local_delete_button(thread_id, comment.id, repo_path))
                 }
             }
@@ -1502,7 +1880,7 @@ pub fn render_local_comment_item(
 pub fn render_ai_status_container(comment_id: Uuid, status: &AiExecutionStatus) -> Container {
     container! {
         div id=(ai_status_str_id(comment_id)) padding=8 margin-top=8 {
-            (render_ai_status_inner(status))
+            (render_ai_status_inner(comment_id, status))
         }
     }
     .into_iter()
@@ -1515,13 +1893,13 @@ pub fn render_ai_status_container(comment_id: Uuid, status: &AiExecutionStatus)
 /// This is separated from the container so we can push just the inner content
 /// during updates while maintaining the same container `str_id`.
 #[must_use]
-pub fn render_ai_status_inner(status: &AiExecutionStatus) -> Container {
+pub fn render_ai_status_inner(comment_id: Uuid, status: &AiExecutionStatus) -> Container {
     match status {
         AiExecutionStatus::Pending => render_status_pending(),
         AiExecutionStatus::Running {
             started_at,
             progress,
-        } => render_status_running(started_at, progress),
+        } => render_status_running(comment_id, started_at, progress),
         AiExecutionStatus::Completed {
             finished_at,
             execution_details,
@@ -1530,6 +1908,9 @@ pub fn render_ai_status_inner(status: &AiExecutionStatus) -> Container {
         AiExecutionStatus::Failed { finished_at, error } => {
             render_status_failed(finished_at, error)
         }
+        AiExecutionStatus::OverBudget { spent, budget, .. } => {
+            render_status_over_budget(*spent, *budget)
+        }
     }
 }
 
@@ -1552,12 +1933,14 @@ fn render_status_pending() -> Container {
 }
 
 fn render_status_running(
+    comment_id: Uuid,
     started_at: &chrono::DateTime<chrono::Utc>,
     progress: &[ProgressEntry],
 ) -> Container {
     let elapsed = chrono::Utc::now()
         .signed_duration_since(*started_at)
         .num_seconds();
+    let cancel_url = format!("/api/local/ai/cancel?comment_id={comment_id}");
 
     container! {
         div
@@ -1572,6 +1955,22 @@ fn render_status_running(
                 span font-size=14 color="#0969da" font-weight=600 {
                     "AI working... (" (elapsed) "s)"
                 }
+                button
+                    type=button
+                    color="#cf222e"
+                    background="#ffffff"
+                    border="1px solid #d0d7de"
+                    border-radius=6
+                    padding-x=8
+                    padding-y=4
+                    cursor=pointer
+                    font-size=12
+                    hx-post=(cancel_url)
+                    hx-target=(Selector::Id(ai_status_str_id(comment_id)))
+                    hx-swap="outerHTML"
+                {
+                    "Cancel"
+                }
             }
             @if !progress.is_empty() {
                 div margin-top=8 gap=4 {
@@ -1651,6 +2050,26 @@ fn render_status_failed(_finished_at: &chrono::DateTime<chrono::Utc>, error: &st
     .into()
 }
 
+fn render_status_over_budget(spent: f64, budget: f64) -> Container {
+    container! {
+        div
+            direction=row
+            align-items=center
+            gap=8
+            padding=12
+            background="#fff8c5"
+            border="1px solid #d4a72c"
+            border-radius=6
+        {
+            span font-size=16 { "💸" }
+            span font-size=14 color="#6e5a00" {
+                (format!("Not run: AI budget of ${budget:.2} reached (${spent:.2} spent). Override the budget in the header to run AI actions."))
+            }
+        }
+    }
+    .into()
+}
+
 /// Render execution details ("How I worked on this" section).
 #[must_use]
 pub fn render_execution_details(details: &ExecutionDetails) -> Container {
@@ -1712,18 +2131,19 @@ pub fn render_execution_details(details: &ExecutionDetails) -> Container {
 /// Render the AI action selector.
 ///
 /// This is used in the comment form to allow users to select which AI agent
-/// to use for processing their comment.
+/// to use for processing their comment. `default_agent` prefills the input.
+///
+/// The input is replaced with a dropdown of the available agents by
+/// [`crate::local_agents::render_agent_picker_script`].
 #[must_use]
-pub fn render_ai_action_selector(
-    field_name: &str,
-    _available_agents: &[(&str, &str)], // (value, display_name) - not used yet
-) -> Container {
+pub fn render_ai_action_selector(field_name: &str, default_agent: Option<&str>) -> Container {
     container! {
         div direction=row align-items=center gap=8 {
             span font-size=14 color="#57606a" { "AI Agent:" }
             input
                 type=text
                 name=(field_name)
+                value=(default_agent.unwrap_or_default())
                 placeholder="e.g. opencode:code or leave empty"
                 padding=8
                 border="1px solid #d0d7de"
@@ -1735,7 +2155,8 @@ pub fn render_ai_action_selector(
     .into()
 }
 
-/// Default available agents for the AI action selector.
+/// Agents offered by the AI action selector when no provider is installed,
+/// as `(id, description)` pairs.
 pub const DEFAULT_AGENTS: &[(&str, &str)] = &[
     ("opencode:code", "OpenCode - Code (default)"),
     ("opencode:plan", "OpenCode - Plan (research, no edits)"),
@@ -1746,7 +2167,7 @@ pub const DEFAULT_AGENTS: &[(&str, &str)] = &[
 // =============================================================================
 
 /// Format a timestamp as a human-readable "time ago" string.
-fn format_time_ago(timestamp: chrono::DateTime<chrono::Utc>) -> String {
+pub(crate) fn format_time_ago(timestamp: chrono::DateTime<chrono::Utc>) -> String {
     let now = chrono::Utc::now();
     let duration = now.signed_duration_since(timestamp);
 
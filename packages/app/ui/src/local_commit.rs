@@ -0,0 +1,139 @@
+//! Committing the staged changes of a working tree diff.
+//!
+//! Renders the form ending a self-review with the commit: a message written
+//! by hand or suggested by an agent from the staged changes, whether to
+//! amend the last commit instead, and whether to sign it off. Each action
+//! answers with the re-rendered form.
+
+use hyperchad::transformer::models::Selector;
+use hyperchad_template::{Containers, container};
+
+use crate::local_comments;
+
+/// Route committing the staged changes, or suggesting a commit message.
+pub const COMMIT_ROUTE: &str = "/api/local/commit";
+
+/// Element ID of the commit form, replaced after each action.
+pub const COMMIT_FORM_ID: &str = "commit-form";
+
+/// The commit being written in the commit form.
+#[derive(Debug, Clone, Default)]
+pub struct CommitDraft {
+    /// The commit message.
+    pub message: String,
+    /// Whether to amend the last commit instead of adding one.
+    pub amend: bool,
+    /// Whether to add a `Signed-off-by` trailer.
+    pub sign_off: bool,
+}
+
+/// Render the collapsible commit form of the repository at `repo_path`.
+/// Include it once per page.
+#[must_use]
+pub fn render_commit_section(repo_path: &str, default_agent: Option<&str>) -> Containers {
+    container! {
+        details
+            padding=12
+            background="#f6f8fa"
+            border="1px solid #d0d7de"
+            border-radius=6
+        {
+            summary cursor=pointer font-weight=600 font-size=14 color="#1f2328" {
+                "Commit staged changes"
+            }
+            (render_commit_form(repo_path, default_agent, &CommitDraft::default(), None))
+        }
+    }
+}
+
+/// Render the commit form holding `draft`, and the outcome of the last
+/// action, if any.
+#[must_use]
+pub fn render_commit_form(
+    repo_path: &str,
+    default_agent: Option<&str>,
+    draft: &CommitDraft,
+    outcome: Option<Result<&str, &str>>,
+) -> Containers {
+    let url = format!("{COMMIT_ROUTE}?repo={}", urlencoding::encode(repo_path));
+
+    container! {
+        form
+            id=(COMMIT_FORM_ID)
+            hx-post=(url)
+            hx-target=(Selector::Id(COMMIT_FORM_ID.to_string()))
+            hx-swap="outerHTML"
+            gap=8
+            margin-top=12
+            font-size=14
+        {
+            textarea
+                name="message"
+                placeholder="Commit message"
+                padding=8
+                border="1px solid #d0d7de"
+                border-radius=6
+                font-size=12
+                font-family="monospace"
+                height=160
+            {
+                (draft.message)
+            }
+            div direction=row align-items=center gap=12 {
+                div flex=1 {
+                    (local_comments::render_ai_action_selector("ai_agent", default_agent))
+                }
+                button
+                    type=submit
+                    name="action"
+                    value="suggest"
+                    background="#ffffff"
+                    color="#24292f"
+                    border="1px solid #d0d7de"
+                    padding-x=16
+                    padding-y=8
+                    border-radius=6
+                    font-size=14
+                    cursor=pointer
+                {
+                    "Suggest message"
+                }
+            }
+            div direction=row align-items=center gap=16 {
+                div direction=row align-items=center gap=4 {
+                    input type=checkbox name="amend" value="true" checked=(draft.amend);
+                    span color="#57606a" { "Amend last commit" }
+                }
+                div direction=row align-items=center gap=4 {
+                    input type=checkbox name="sign_off" value="true" checked=(draft.sign_off);
+                    span color="#57606a" { "Sign off" }
+                }
+                div flex=1 {}
+                button
+                    type=submit
+                    name="action"
+                    value="commit"
+                    background="#1f883d"
+                    color="#ffffff"
+                    padding-x=16
+                    padding-y=8
+                    border-radius=6
+                    font-weight=600
+                    font-size=14
+                    cursor=pointer
+                {
+                    "Commit"
+                }
+            }
+            @match outcome {
+                Some(Ok(message)) => {
+                    span color="#1a7f37" { (message) }
+                }
+                Some(Err(message)) => {
+                    span color="#cf222e" { (message) }
+                }
+                None => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,321 @@
+//! Commit-by-commit navigation for multi-commit local diffs.
+//!
+//! When a range or a list of commits is reviewed one commit at a time, each
+//! commit's changes get their own section with a header, and the sections
+//! are linked to each other with previous/next links and a jump list.
+//! Filter chips above the jump list narrow the commits down by author or
+//! path, and can hide merge commits. Signed commits are badged with the
+//! outcome of verifying their signature.
+
+use std::fmt::Write as _;
+
+use chadreview_git_backend_models::{CommitInfo, CommitSignature, SignatureVerification};
+use chadreview_local_diff_models::{CommitFilter, DiffSpec, MultiCommitMode};
+use hyperchad::template::{Containers, LayoutOverflow, container};
+
+/// Element ID of the commit jump list.
+pub const COMMIT_NAV_ID: &str = "commit-nav";
+
+/// Element ID of a commit's section, used as its scroll anchor.
+#[must_use]
+pub fn commit_section_id(sha: &str) -> String {
+    format!("commit-{sha}")
+}
+
+/// The same diff shown the other way: commit by commit if it's combined,
+/// combined if it's commit by commit. `None` for diffs of a single commit or
+/// the working tree.
+#[must_use]
+pub fn toggled_mode(spec: &DiffSpec) -> Option<DiffSpec> {
+    let toggle = |mode: &MultiCommitMode| match mode {
+        MultiCommitMode::Separate => MultiCommitMode::Squashed,
+        MultiCommitMode::Squashed => MultiCommitMode::Separate,
+    };
+
+    match spec {
+        DiffSpec::Range {
+            base,
+            head,
+            three_dot,
+            mode,
+        } => Some(DiffSpec::Range {
+            base: base.clone(),
+            head: head.clone(),
+            three_dot: *three_dot,
+            mode: toggle(mode),
+        }),
+        DiffSpec::Commits { shas, mode } if shas.len() > 1 => Some(DiffSpec::Commits {
+            shas: shas.clone(),
+            mode: toggle(mode),
+        }),
+        DiffSpec::Commits { .. } | DiffSpec::WorkingTree { .. } | DiffSpec::Commit { .. } => None,
+    }
+}
+
+/// Render the link switching between the combined and commit-by-commit
+/// views of a diff.
+#[must_use]
+pub fn render_mode_toggle(repo_path: &str, spec: &DiffSpec) -> Containers {
+    let Some(toggled) = toggled_mode(spec) else {
+        return vec![];
+    };
+    let url = format!(
+        "/local?repo={}&{}",
+        urlencoding::encode(repo_path),
+        toggled.to_query_string()
+    );
+    let label = if toggled.is_by_commit() {
+        "Review commit by commit"
+    } else {
+        "Show combined diff"
+    };
+
+    container! {
+        anchor href=(url) color="#0969da" font-size=14 font-weight=600 {
+            (label)
+        }
+    }
+}
+
+/// Render the chips filtering the commits of `spec`'s diff: one per author
+/// of `commits`, one per directory in `paths`, and a toggle hiding merge
+/// commits when there are any. Clicking an active chip clears it.
+///
+/// `shown` is how many of `commits` pass `filter`.
+#[must_use]
+pub fn render_commit_filters(
+    repo_path: &str,
+    spec: &DiffSpec,
+    filter: &CommitFilter,
+    commits: &[CommitInfo],
+    paths: &[String],
+    shown: usize,
+) -> Containers {
+    let mut authors: Vec<(&str, usize)> = vec![];
+    for commit in commits {
+        match authors
+            .iter_mut()
+            .find(|(name, _)| *name == commit.author_name)
+        {
+            Some((_, count)) => *count += 1,
+            None => authors.push((&commit.author_name, 1)),
+        }
+    }
+    let has_merges = commits.iter().any(|commit| commit.parent_shas.len() > 1);
+
+    container! {
+        div
+            direction=column
+            gap=8
+            padding=12
+            background="#ffffff"
+            border="1px solid #d0d7de"
+            border-radius=6
+            font-size=13
+        {
+            div direction=row gap=8 align-items=center {
+                span font-weight=600 color="#1f2328" {
+                    (format!("Showing {shown} of {} commits", commits.len()))
+                }
+                @if !filter.is_empty() {
+                    anchor href=(filter_url(repo_path, spec, &CommitFilter::default())) color="#0969da" {
+                        "Clear filters"
+                    }
+                }
+            }
+            div direction=row gap=6 align-items=center overflow-x=(LayoutOverflow::Wrap { grid: false }) {
+                span color="#57606a" min-width=60 { "Authors" }
+                @for (name, count) in &authors {
+                    @let active = filter.author.as_deref() == Some(*name);
+                    @let author = if active { None } else { Some((*name).to_string()) };
+                    (render_chip(
+                        &format!("{name} ({count})"),
+                        active,
+                        &filter_url(repo_path, spec, &CommitFilter { author, ..filter.clone() }),
+                    ))
+                }
+            }
+            @if !paths.is_empty() {
+                div direction=row gap=6 align-items=center overflow-x=(LayoutOverflow::Wrap { grid: false }) {
+                    span color="#57606a" min-width=60 { "Paths" }
+                    @for path in paths {
+                        @let active = filter.path.as_deref() == Some(path.as_str());
+                        @let prefix = if active { None } else { Some(path.clone()) };
+                        (render_chip(
+                            &format!("{path}/"),
+                            active,
+                            &filter_url(repo_path, spec, &CommitFilter { path: prefix, ..filter.clone() }),
+                        ))
+                    }
+                }
+            }
+            @if has_merges {
+                div direction=row gap=6 align-items=center {
+                    (render_chip(
+                        "Hide merge commits",
+                        filter.hide_merges,
+                        &filter_url(repo_path, spec, &CommitFilter { hide_merges: !filter.hide_merges, ..filter.clone() }),
+                    ))
+                }
+            }
+        }
+    }
+}
+
+fn render_chip(label: &str, active: bool, url: &str) -> Containers {
+    container! {
+        anchor
+            href=(url)
+            padding-x=10
+            padding-y=2
+            border-radius=12
+            border=(if active { "1px solid #0969da" } else { "1px solid #d0d7de" })
+            background=(if active { "#ddf4ff" } else { "#f6f8fa" })
+            color=(if active { "#0969da" } else { "#1f2328" })
+        {
+            (label)
+        }
+    }
+}
+
+/// URL of `spec`'s diff reviewed commit by commit with `filter` applied.
+fn filter_url(repo_path: &str, spec: &DiffSpec, filter: &CommitFilter) -> String {
+    let mut url = format!(
+        "/local?repo={}&{}",
+        urlencoding::encode(repo_path),
+        spec.to_query_string()
+    );
+    if let Some(author) = &filter.author {
+        write!(url, "&author={}", urlencoding::encode(author)).unwrap();
+    }
+    if let Some(path) = &filter.path {
+        write!(url, "&path={}", urlencoding::encode(path)).unwrap();
+    }
+    if filter.hide_merges {
+        url.push_str("&merges=hide");
+    }
+    url
+}
+
+/// Render the jump list of commits, oldest first.
+#[must_use]
+pub fn render_commit_nav(commits: &[CommitInfo]) -> Containers {
+    container! {
+        div
+            id=(COMMIT_NAV_ID)
+            direction=column
+            gap=4
+            padding=12
+            background="#f6f8fa"
+            border="1px solid #d0d7de"
+            border-radius=6
+        {
+            span font-weight=600 font-size=14 color="#1f2328" margin-bottom=4 {
+                (format!("Reviewing {} commits one at a time", commits.len()))
+            }
+            @for (index, commit) in commits.iter().enumerate() {
+                anchor
+                    href=(format!("#{}", commit_section_id(&commit.sha)))
+                    direction=row
+                    gap=12
+                    padding-x=8
+                    padding-y=2
+                    border-radius=6
+                    font-size=13
+                {
+                    span color="#57606a" min-width=40 { (format!("{}.", index + 1)) }
+                    span font-family="monospace" color="#0969da" min-width=70 {
+                        (commit.short_sha.clone())
+                    }
+                    span color="#1f2328" flex=1 { (commit.summary.clone()) }
+                    @if let Some(signature) = &commit.signature {
+                        (render_signature_badge(signature))
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Render the header of the section for `commits[index]`, with its message,
+/// author and SHA, and links to the neighboring commits.
+#[must_use]
+pub fn render_commit_section_header(commits: &[CommitInfo], index: usize) -> Containers {
+    let Some(commit) = commits.get(index) else {
+        return vec![];
+    };
+    let previous = index.checked_sub(1).and_then(|i| commits.get(i));
+    let next = commits.get(index + 1);
+    let body = commit
+        .message
+        .split_once('\n')
+        .map_or("", |(_, body)| body.trim());
+    let committed_at = chrono::DateTime::from_timestamp(commit.timestamp, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default();
+
+    container! {
+        div
+            direction=column
+            gap=8
+            padding=12
+            background="#ddf4ff"
+            border="1px solid #54aeff"
+            border-radius=6
+        {
+            div direction=row gap=12 align-items=center font-size=13 {
+                span color="#57606a" { (format!("Commit {} of {}", index + 1, commits.len())) }
+                div flex=1 {}
+                @if let Some(previous) = previous {
+                    anchor href=(format!("#{}", commit_section_id(&previous.sha))) color="#0969da" {
+                        "← Previous"
+                    }
+                }
+                anchor href=(format!("#{COMMIT_NAV_ID}")) color="#0969da" { "All commits" }
+                @if let Some(next) = next {
+                    anchor href=(format!("#{}", commit_section_id(&next.sha))) color="#0969da" {
+                        "Next →"
+                    }
+                }
+            }
+            span font-size=16 font-weight=600 color="#1f2328" { (commit.summary.clone()) }
+            @if !body.is_empty() {
+                div font-size=14 color="#1f2328" white-space=preserve-wrap { (body) }
+            }
+            div direction=row gap=12 font-size=13 color="#57606a" {
+                span font-family="monospace" color="#0969da" { (commit.short_sha.clone()) }
+                span { (format!("{} <{}>", commit.author_name, commit.author_email)) }
+                span { (committed_at) }
+                @if let Some(signature) = &commit.signature {
+                    (render_signature_badge(signature))
+                    @if let Some(signer) = &signature.signer {
+                        span { (format!("Signed by {signer}")) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Render the badge showing how a commit's `signature` verified.
+#[must_use]
+pub fn render_signature_badge(signature: &CommitSignature) -> Containers {
+    let (label, color, border) = match signature.verification {
+        SignatureVerification::Good => ("Verified", "#1a7f37", "1px solid #1a7f37"),
+        SignatureVerification::Bad => ("Bad signature", "#cf222e", "1px solid #cf222e"),
+        SignatureVerification::UnknownKey => ("Unverified", "#9a6700", "1px solid #9a6700"),
+        SignatureVerification::Unchecked => ("Signed", "#57606a", "1px solid #d0d7de"),
+    };
+
+    container! {
+        span
+            padding-x=6
+            border=(border)
+            border-radius=10
+            font-size=12
+            color=(color)
+        {
+            (format!("{label} ({})", signature.kind.label()))
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Conflicts of a merge or rebase stopped partway through.
+//!
+//! The conflicts mode of `/local` shows each conflicted file as three panes,
+//! the base, ours and theirs versions from its index stages, with the lines
+//! each side changed from the base highlighted. A file is marked resolved
+//! once its conflict markers are gone, which stages it.
+
+use std::collections::HashSet;
+
+use chadreview_git_backend_models::{ConflictFile, RepoOperation};
+use hyperchad::transformer::models::Selector;
+use hyperchad_template::{Containers, container};
+
+use crate::local_comments;
+
+/// Route marking the conflicts of a file resolved.
+pub const RESOLVE_ROUTE: &str = "/api/local/conflicts/resolve";
+
+/// URL of the conflicts mode of the `/local` view of `repo_path`.
+#[must_use]
+pub fn conflicts_url(repo_path: &str) -> String {
+    format!(
+        "/local?repo={}&conflicts=true",
+        urlencoding::encode(repo_path)
+    )
+}
+
+/// Element ID of the resolve button of the conflicted file `path`.
+#[must_use]
+pub fn resolve_id(path: &str) -> String {
+    format!("conflict-resolve-{}", local_comments::classify_name(path))
+}
+
+/// Render the banner telling a merge or rebase is in progress, linking to
+/// the conflicts mode while `conflicted` files are left to resolve.
+#[must_use]
+pub fn render_operation_banner(
+    repo_path: &str,
+    operation: RepoOperation,
+    conflicted: usize,
+) -> Containers {
+    container! {
+        div
+            direction=row
+            align-items=center
+            gap=12
+            padding=12
+            background="#fff8c5"
+            border="1px solid #d4a72c"
+            border-radius=6
+            font-size=14
+        {
+            span font-weight=600 color="#1f2328" {
+                (operation.label()) " in progress"
+            }
+            @if conflicted == 0 {
+                span color="#57606a" { "All conflicts are resolved." }
+            } @else {
+                span color="#57606a" {
+                    (conflicted) @if conflicted == 1 { " file has" } @else { " files have" } " conflicts."
+                }
+                anchor href=(conflicts_url(repo_path)) color="#0969da" font-weight=600 {
+                    "Show conflicts"
+                }
+            }
+        }
+    }
+}
+
+/// Render the conflicts mode: every conflicted file with its base, ours
+/// and theirs panes.
+#[must_use]
+pub fn render_conflicts(
+    repo_path: &str,
+    operation: Option<RepoOperation>,
+    conflicts: &[ConflictFile],
+) -> Containers {
+    let back_url = format!("/local?repo={}", urlencoding::encode(repo_path));
+
+    container! {
+        div gap=16 {
+            div direction=row align-items=center justify-content=space-between {
+                h1 font-size=20 font-weight=600 color="#1f2328" {
+                    @match operation {
+                        Some(operation) => { (operation.label()) " conflicts" }
+                        None => { "Conflicts" }
+                    }
+                }
+                anchor href=(back_url) color="#0969da" font-size=14 font-weight=600 {
+                    "Back to working tree diff"
+                }
+            }
+            @if operation == Some(RepoOperation::Rebase) {
+                span font-size=13 color="#57606a" {
+                    "While rebasing, ours is the branch being rebased onto and theirs the commit being replayed."
+                }
+            }
+            @if conflicts.is_empty() {
+                span font-size=14 color="#57606a" {
+                    "No files have unresolved conflicts."
+                }
+            }
+            @for file in conflicts {
+                (render_conflict_file(repo_path, file))
+            }
+        }
+    }
+}
+
+/// Render a conflicted file's header, with its resolve button, and its
+/// three panes.
+fn render_conflict_file(repo_path: &str, file: &ConflictFile) -> Containers {
+    let base_lines: HashSet<&str> = file
+        .base
+        .as_deref()
+        .map(|base| base.lines().collect())
+        .unwrap_or_default();
+
+    container! {
+        div border="1px solid #d0d7de" border-radius=6 {
+            div
+                direction=row
+                align-items=center
+                justify-content=space-between
+                padding-x=16
+                padding-y=8
+                background="#f6f8fa"
+                border-bottom="1px solid #d0d7de"
+            {
+                span font-family="monospace" font-size=14 font-weight=600 color="#1f2328" {
+                    (file.path)
+                }
+                (render_resolve(repo_path, &file.path, None))
+            }
+            @if file.binary {
+                span padding=16 font-size=14 color="#57606a" {
+                    "Binary file, resolve it outside the review."
+                }
+            } @else {
+                div direction=row {
+                    (render_pane("Base", file.base.as_deref(), None))
+                    (render_pane("Ours", file.ours.as_deref(), Some(&base_lines)))
+                    (render_pane("Theirs", file.theirs.as_deref(), Some(&base_lines)))
+                }
+            }
+        }
+    }
+}
+
+/// Render one version of a conflicted file, highlighting the lines missing
+/// from `base_lines` when given.
+fn render_pane(
+    title: &str,
+    content: Option<&str>,
+    base_lines: Option<&HashSet<&str>>,
+) -> Containers {
+    container! {
+        div flex=1 min-width=0 border-left="1px solid #d0d7de" {
+            div padding-x=8 padding-y=4 font-size=12 font-weight=600 color="#57606a" {
+                (title)
+            }
+            @match content {
+                Some(content) => {
+                    div font-family="monospace" font-size=12 {
+                        @for line in content.lines() {
+                            div
+                                padding-x=8
+                                white-space=preserve-wrap
+                                background=(line_background(line, base_lines))
+                            {
+                                (line)
+                            }
+                        }
+                    }
+                }
+                None => {
+                    span padding-x=8 padding-y=4 font-size=12 color="#57606a" {
+                        "Not present"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Background of a line of a pane, highlighting lines missing from
+/// `base_lines`.
+fn line_background(line: &str, base_lines: Option<&HashSet<&str>>) -> &'static str {
+    if base_lines.is_some_and(|base| !base.contains(line)) {
+        "#fff8c5"
+    } else {
+        "#ffffff"
+    }
+}
+
+/// Render the button marking file `path` resolved, and the outcome of the
+/// last attempt, if any. Once resolved, only the outcome is left.
+#[must_use]
+pub fn render_resolve(
+    repo_path: &str,
+    path: &str,
+    outcome: Option<Result<&str, &str>>,
+) -> Containers {
+    let id = resolve_id(path);
+    let url = format!("{RESOLVE_ROUTE}?repo={}", urlencoding::encode(repo_path));
+
+    container! {
+        div id=(id.clone()) direction=row align-items=center gap=8 font-size=12 {
+            @match outcome {
+                Some(Ok(message)) => {
+                    span color="#1a7f37" { (message) }
+                }
+                Some(Err(message)) => {
+                    span color="#cf222e" { (message) }
+                }
+                None => {}
+            }
+            @if !matches!(outcome, Some(Ok(_))) {
+                form
+                    hx-post=(url)
+                    hx-target=(Selector::Id(id))
+                    hx-swap="outerHTML"
+                {
+                    input type=hidden name="path" value=(path);
+                    button
+                        type=submit
+                        padding-x=8
+                        padding-y=2
+                        cursor=pointer
+                        background="#ffffff"
+                        border="1px solid #d0d7de"
+                        border-radius=4
+                        font-size=12
+                        color="#24292f"
+                    {
+                        "Mark resolved"
+                    }
+                }
+            }
+        }
+    }
+}
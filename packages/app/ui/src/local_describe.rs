@@ -0,0 +1,160 @@
+//! AI-written commit messages and PR descriptions of a local diff.
+//!
+//! Renders the forms asking an agent to describe the diff and the proposed
+//! description, in a text box with a button copying it to the clipboard.
+
+use chadreview_local_comment_models::DescriptionKind;
+use hyperchad::transformer::models::Selector;
+use hyperchad_template::{Containers, container};
+use serde_json::Value;
+
+use crate::local_comments;
+use crate::local_drafts::script_json;
+
+/// Route asking an agent to describe the diff.
+pub const DESCRIBE_ROUTE: &str = "/api/local/ai/describe";
+
+/// Element ID of the proposed description, replaced when one is written.
+pub const DESCRIPTION_ID: &str = "ai-description";
+
+/// Element ID of the text box holding the proposed description.
+const DESCRIPTION_TEXT_ID: &str = "ai-description-text";
+
+/// Script copying the proposed description when its copy button is
+/// clicked. `__TEXT_ID__` is replaced with the ID of its text box, as JSON.
+///
+/// Buttons are matched by class since descriptions are swapped in after the
+/// page loads.
+const COPY_SCRIPT: &str = r#"<script>
+(() => {
+    document.addEventListener("click", async (event) => {
+        const button = event.target.closest(".copy-description");
+        const text = document.getElementById(__TEXT_ID__);
+        if (!button || !text) return;
+        try {
+            await navigator.clipboard.writeText(text.value);
+            button.textContent = "Copied";
+        } catch {
+            text.select();
+            button.textContent = "Press Ctrl+C to copy";
+        }
+    });
+})();
+</script>"#;
+
+/// Render the forms asking an agent for a commit message or a PR
+/// description of the diff of `spec_query`, and the slot the proposed
+/// description is shown in. Include it once per page.
+#[must_use]
+pub fn render_describe_form(
+    repo_path: &str,
+    spec_query: &str,
+    default_agent: Option<&str>,
+) -> Containers {
+    let api_url = format!(
+        "{DESCRIBE_ROUTE}?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
+    let script = COPY_SCRIPT.replace(
+        "__TEXT_ID__",
+        &script_json(&Value::from(DESCRIPTION_TEXT_ID)),
+    );
+
+    container! {
+        details
+            padding=12
+            background="#f6f8fa"
+            border="1px solid #d0d7de"
+            border-radius=6
+        {
+            summary cursor=pointer font-weight=600 font-size=14 color="#1f2328" {
+                "Describe this diff"
+            }
+            div gap=8 margin-top=12 {
+                @for kind in [DescriptionKind::Commit, DescriptionKind::Pr] {
+                    form
+                        hx-post=(api_url)
+                        hx-target=(Selector::Id(DESCRIPTION_ID.to_string()))
+                        hx-swap="outerHTML"
+                        direction=row
+                        align-items=center
+                        gap=12
+                    {
+                        input type=hidden name="kind" value=(kind.as_str());
+                        div flex=1 {
+                            (local_comments::render_ai_action_selector("ai_agent", default_agent))
+                        }
+                        button
+                            type=submit
+                            background="#0969da"
+                            color="#ffffff"
+                            padding-x=16
+                            padding-y=8
+                            border-radius=6
+                            font-weight=600
+                            font-size=14
+                            cursor=pointer
+                        {
+                            "Write " (kind.label().to_lowercase())
+                        }
+                    }
+                }
+            }
+            (render_description(None))
+            div {
+                raw { (script) }
+            }
+        }
+    }
+}
+
+/// Render the proposed description of the diff.
+///
+/// `Some((kind, Ok(text)))` shows `text` with a copy button,
+/// `Some((kind, Err(message)))` shows why it couldn't be written and `None`
+/// renders an empty placeholder.
+#[must_use]
+pub fn render_description(result: Option<(DescriptionKind, Result<&str, &str>)>) -> Containers {
+    container! {
+        div id=(DESCRIPTION_ID) margin-top=8 gap=8 font-size=14 {
+            @match result {
+                Some((kind, Ok(text))) => {
+                    div direction=row align-items=center justify-content=space-between {
+                        span font-weight=600 color="#1f2328" { (kind.label()) }
+                        button
+                            type=button
+                            class="copy-description"
+                            background="#ffffff"
+                            color="#24292f"
+                            border="1px solid #d0d7de"
+                            padding-x=12
+                            padding-y=4
+                            border-radius=6
+                            font-size=13
+                            cursor=pointer
+                        {
+                            "Copy"
+                        }
+                    }
+                    textarea
+                        id=(DESCRIPTION_TEXT_ID)
+                        padding=8
+                        border="1px solid #d0d7de"
+                        border-radius=6
+                        font-size=12
+                        font-family="monospace"
+                        height=240
+                    {
+                        (text)
+                    }
+                }
+                Some((kind, Err(message))) => {
+                    span color="#cf222e" {
+                        "Failed to write " (kind.label().to_lowercase()) ": " (message)
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+}
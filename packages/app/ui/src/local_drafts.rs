@@ -0,0 +1,99 @@
+//! Autosaved drafts of local comment forms.
+//!
+//! Text typed into a comment form is saved as a draft, keyed by the form's
+//! element ID (which is unique per file, line or thread), until the form is
+//! submitted. Drafts are put back into their forms when the page loads, so
+//! a long comment survives navigating away.
+
+use std::collections::HashMap;
+
+use hyperchad_template::{Containers, container};
+use serde_json::Value;
+
+/// Element ID of the general comment form.
+pub const GENERAL_COMMENT_FORM_ID: &str = "general-comment-form";
+
+/// Script saving and restoring drafts. `__REPO__` and `__DRAFTS__` are
+/// replaced with the repository path and the saved drafts, as JSON.
+///
+/// Only new comment and reply forms have drafts; edit forms start out with
+/// the comment's body.
+const DRAFT_SCRIPT: &str = r#"<script>
+(() => {
+    const repo = __REPO__;
+    const drafts = __DRAFTS__;
+    const prefixes = ["local-comment-form-", "local-file-comment-form-", "local-reply-form-", "general-comment-form"];
+    const draftForm = (element) => {
+        const form = element instanceof Element ? element.closest("form") : null;
+        return form && prefixes.some((prefix) => form.id.startsWith(prefix)) ? form : null;
+    };
+    const bodyOf = (form) => form.querySelector('textarea[name="body"]');
+    const timers = {};
+    const save = (key, body) => {
+        if (body) drafts[key] = body; else delete drafts[key];
+        fetch("/api/local/draft?repo=" + encodeURIComponent(repo), {
+            method: "POST",
+            headers: { "Content-Type": "application/x-www-form-urlencoded" },
+            body: new URLSearchParams({ key, body }),
+        });
+    };
+    const restore = (form) => {
+        const textarea = bodyOf(form);
+        if (textarea && !textarea.value && drafts[form.id]) textarea.value = drafts[form.id];
+    };
+    document.addEventListener("input", (event) => {
+        const form = draftForm(event.target);
+        if (!form || event.target !== bodyOf(form)) return;
+        clearTimeout(timers[form.id]);
+        timers[form.id] = setTimeout(() => save(form.id, event.target.value), 1000);
+    });
+    document.addEventListener("submit", (event) => {
+        const form = draftForm(event.target);
+        if (!form) return;
+        clearTimeout(timers[form.id]);
+        save(form.id, "");
+    }, true);
+    // Forms re-rendered with their thread get their draft back when focused
+    document.addEventListener("focusin", (event) => {
+        const form = draftForm(event.target);
+        if (form) restore(form);
+    });
+    const restoreAll = () => {
+        for (const key of Object.keys(drafts)) {
+            const form = document.getElementById(key);
+            if (form) restore(form);
+        }
+    };
+    if (document.readyState === "loading") {
+        document.addEventListener("DOMContentLoaded", restoreAll);
+    } else {
+        restoreAll();
+    }
+})();
+</script>"#;
+
+/// Render the script autosaving comment form drafts, with the repository's
+/// saved `drafts` (form ID to body). Include it once per page.
+#[must_use]
+#[allow(clippy::implicit_hasher)]
+pub fn render_draft_script(repo_path: &str, drafts: &HashMap<String, String>) -> Containers {
+    let drafts: serde_json::Map<_, _> = drafts
+        .iter()
+        .map(|(key, body)| (key.clone(), Value::from(body.as_str())))
+        .collect();
+    let script = DRAFT_SCRIPT
+        .replace("__REPO__", &script_json(&Value::from(repo_path)))
+        .replace("__DRAFTS__", &script_json(&Value::Object(drafts)));
+
+    container! {
+        div {
+            raw { (script) }
+        }
+    }
+}
+
+/// Serialize `value` as JSON that can't close the `<script>` element it's
+/// embedded in.
+pub(crate) fn script_json(value: &Value) -> String {
+    value.to_string().replace("</", "<\\/")
+}
@@ -0,0 +1,76 @@
+//! Markdown export of a local review.
+//!
+//! Renders the "Export review" action and the exported document, shown in a
+//! text box ready to be copied into a PR or sent to a teammate.
+
+use hyperchad::transformer::models::Selector;
+use hyperchad_template::{Containers, container};
+
+/// Element ID of the export panel, replaced when the review is exported.
+pub const EXPORT_ID: &str = "review-export";
+
+/// Render the "Export review" button.
+///
+/// `spec_query` is the diff spec's query string, so code snippets come from
+/// the diff being viewed.
+#[must_use]
+pub fn render_export_button(repo_path: &str, spec_query: &str) -> Containers {
+    let api_url = format!(
+        "/api/local/export?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
+
+    container! {
+        div id=(EXPORT_ID) direction=row {
+            button
+                type=button
+                hx-post=(api_url)
+                hx-target=(Selector::Id(EXPORT_ID.to_string()))
+                hx-swap="outerHTML"
+                background="#ffffff"
+                color="#24292f"
+                border="1px solid #d0d7de"
+                padding-x=16
+                padding-y=8
+                border-radius=6
+                font-size=14
+                cursor=pointer
+            {
+                "Export review"
+            }
+        }
+    }
+}
+
+/// Render an exported review: its summary and the markdown document.
+#[must_use]
+pub fn render_export(markdown: &str, summary: &str) -> Containers {
+    container! {
+        div
+            id=(EXPORT_ID)
+            gap=8
+            padding=12
+            background="#f6f8fa"
+            border="1px solid #d0d7de"
+            border-radius=6
+        {
+            div direction=row align-items=center gap=12 {
+                span font-weight=600 font-size=14 color="#1f2328" { "Review export" }
+                span font-size=13 color="#57606a" { (summary) }
+            }
+            span font-size=13 color="#57606a" {
+                "Copy the markdown below into a PR description or send it to a teammate."
+            }
+            textarea
+                padding=8
+                border="1px solid #d0d7de"
+                border-radius=6
+                font-size=12
+                font-family="monospace"
+                height=320
+            {
+                (markdown)
+            }
+        }
+    }
+}
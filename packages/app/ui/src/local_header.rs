@@ -1,9 +1,15 @@
 //! Local diff header component.
 //!
 //! Renders header information for local git diff views, including
-//! repository path, diff specification, and commit information.
+//! repository path, diff specification, commit information, AI usage, the
+//! file sort menu, the worktree picker, and the form for comparing a branch
+//! from another remote.
 
-use chadreview_local_diff_models::LocalDiffInfo;
+use chadreview_git_backend_models::Worktree;
+use chadreview_local_comment_models::UsageLedger;
+use chadreview_local_diff_models::{FileOrder, LocalDiffInfo};
+use hyperchad::transformer::models::Selector;
+use hyperchad_router::Container;
 use hyperchad_template::{Containers, container};
 
 use crate::local_comments;
@@ -215,3 +221,295 @@ fn render_commits_section(commits: &[chadreview_git_backend_models::CommitInfo])
         }
     }
 }
+
+/// Route overriding the AI budget of a repository.
+pub const BUDGET_OVERRIDE_ROUTE: &str = "/api/local/ai/budget";
+
+/// Element ID of the AI usage summary.
+pub const AI_USAGE_ID: &str = "ai-usage";
+
+/// Render the AI spend and tokens recorded in the repository, against its
+/// `budget` if it has one.
+///
+/// Once the budget is reached, a button overrides it so AI actions run
+/// again until the app restarts. Renders an empty placeholder while nothing
+/// was spent and there is no budget.
+///
+/// # Panics
+///
+/// Panics if the container macro produces an empty iterator (should never happen).
+#[must_use]
+pub fn render_ai_usage(
+    repo_path: &str,
+    ledger: &UsageLedger,
+    budget: Option<f64>,
+    overridden: bool,
+) -> Container {
+    let spent = ledger.total_cost();
+    let tokens = ledger.total_tokens();
+    let reached = budget.is_some_and(|budget| spent >= budget);
+    let override_url = format!(
+        "{BUDGET_OVERRIDE_ROUTE}?repo={}",
+        urlencoding::encode(repo_path)
+    );
+    let spend = budget.map_or_else(
+        || format!("${spent:.2}"),
+        |budget| format!("${spent:.2} of ${budget:.2}"),
+    );
+
+    container! {
+        div id=(AI_USAGE_ID) direction=row align-items=center gap=8 font-size=14 {
+            @if !ledger.entries.is_empty() || budget.is_some() {
+                span color=(if reached { "#cf222e" } else { "#57606a" }) {
+                    (format!(
+                        "AI usage: {spend} ({} input / {} output tokens)",
+                        tokens.input, tokens.output
+                    ))
+                }
+                @if reached && overridden {
+                    span color="#9a6700" { "Budget overridden" }
+                } @else if reached {
+                    button
+                        type=button
+                        hx-post=(override_url)
+                        hx-target=(Selector::Id(AI_USAGE_ID.to_string()))
+                        hx-swap="outerHTML"
+                        background="#ffffff"
+                        color="#cf222e"
+                        border="1px solid #d0d7de"
+                        padding-x=8
+                        padding-y=4
+                        border-radius=6
+                        font-size=13
+                        cursor=pointer
+                    {
+                        "Override budget"
+                    }
+                }
+            }
+        }
+    }
+    .into_iter()
+    .next()
+    .unwrap()
+}
+
+/// ID of the container that shows the result of a fork compare.
+pub const COMPARE_RESULT_ID: &str = "compare-result";
+
+/// Render the form for comparing a branch from another remote (e.g. a fork)
+/// against a local ref.
+#[must_use]
+pub fn render_compare_form(repo_path: &str) -> Containers {
+    let api_url = format!("/api/local/compare?repo={}", urlencoding::encode(repo_path));
+
+    container! {
+        details
+            padding=12
+            background="#f6f8fa"
+            border="1px solid #d0d7de"
+            border-radius=6
+        {
+            summary cursor=pointer font-weight=600 font-size=14 color="#1f2328" {
+                "Compare a branch from another remote"
+            }
+            form
+                hx-post=(api_url)
+                hx-target=(Selector::Id(COMPARE_RESULT_ID.to_string()))
+                hx-swap="outerHTML"
+                direction=row
+                gap=8
+                margin-top=12
+                overflow-x=(hyperchad_template::LayoutOverflow::Wrap { grid: false })
+            {
+                input
+                    type=text
+                    name="url"
+                    placeholder="https://github.com/user/fork.git"
+                    padding=8
+                    border="1px solid #d0d7de"
+                    border-radius=6
+                    font-size=14
+                    flex=2;
+                input
+                    type=text
+                    name="branch"
+                    placeholder="branch"
+                    padding=8
+                    border="1px solid #d0d7de"
+                    border-radius=6
+                    font-size=14
+                    flex=1;
+                input
+                    type=text
+                    name="base"
+                    placeholder="base (default: HEAD)"
+                    padding=8
+                    border="1px solid #d0d7de"
+                    border-radius=6
+                    font-size=14
+                    flex=1;
+                button
+                    type=submit
+                    background="#0969da"
+                    color="#ffffff"
+                    padding-x=16
+                    padding-y=8
+                    border-radius=6
+                    font-weight=600
+                    font-size=14
+                    cursor=pointer
+                {
+                    "Fetch & compare"
+                }
+            }
+            (render_compare_result(None))
+        }
+    }
+}
+
+/// Render the outcome of a fork compare.
+///
+/// `Some(Ok(url))` links to the comparison, `Some(Err(message))` shows why
+/// the fetch failed and `None` renders an empty placeholder.
+#[must_use]
+pub fn render_compare_result(result: Option<Result<&str, &str>>) -> Containers {
+    container! {
+        div id=(COMPARE_RESULT_ID) margin-top=8 font-size=14 {
+            @match result {
+                Some(Ok(url)) => {
+                    anchor color="#0969da" font-weight=600 href=(url) {
+                        "Fetched. Open the comparison →"
+                    }
+                }
+                Some(Err(message)) => {
+                    span color="#cf222e" { (message) }
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+/// Render the switch between showing the threads made on this diff and
+/// every thread in the repository, or nothing if no threads are hidden.
+///
+/// `spec_query` is the diff spec's query string and `hidden` how many
+/// threads were made on other diffs.
+#[must_use]
+pub fn render_comment_scope_toggle(
+    repo_path: &str,
+    spec_query: &str,
+    show_all: bool,
+    hidden: usize,
+) -> Containers {
+    if !show_all && hidden == 0 {
+        return vec![];
+    }
+
+    let url = format!(
+        "/local?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
+    let (status, label, url) = if show_all {
+        (
+            "Showing comments from all diffs in this repository.".to_string(),
+            "Only comments for this diff",
+            url,
+        )
+    } else {
+        let noun = if hidden == 1 { "comment" } else { "comments" };
+        (
+            format!("{hidden} {noun} from other diffs hidden."),
+            "Show all repo comments",
+            format!("{url}&comments=all"),
+        )
+    };
+
+    container! {
+        div direction=row gap=8 align-items=center font-size=14 {
+            span color="#57606a" { (status) }
+            anchor href=(url) color="#0969da" font-weight=600 { (label) }
+        }
+    }
+}
+
+/// Render the menu picking the order of the diff's files, linking to the
+/// diff of `spec_query` with each [`FileOrder`].
+#[must_use]
+pub fn render_file_order_menu(repo_path: &str, spec_query: &str, order: FileOrder) -> Containers {
+    let url = format!(
+        "/local?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
+
+    container! {
+        details
+            font-size=14
+            background="#f6f8fa"
+            border="1px solid #d0d7de"
+            border-radius=6
+        {
+            summary cursor=pointer padding=8 color="#1f2328" {
+                "Sort files: " (order.label())
+            }
+            div direction=column gap=4 padding=8 {
+                @for option in FileOrder::ALL {
+                    @if option == order {
+                        span font-weight=600 color="#1f2328" { "✓ " (option.label()) }
+                    } @else {
+                        anchor href=(format!("{url}&sort={}", option.as_str())) color="#0969da" {
+                            (option.label())
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Render the menu switching the view to the same diff in another worktree
+/// of the repository. Nothing is rendered without linked worktrees.
+#[must_use]
+pub fn render_worktree_menu(worktrees: &[Worktree], spec_query: &str) -> Containers {
+    if worktrees.len() < 2 {
+        return vec![];
+    }
+    let current = worktrees.iter().find(|worktree| worktree.is_current);
+
+    container! {
+        details
+            font-size=14
+            background="#f6f8fa"
+            border="1px solid #d0d7de"
+            border-radius=6
+        {
+            summary cursor=pointer padding=8 color="#1f2328" {
+                "Worktree: " (current.map_or_else(|| "other".to_string(), worktree_label))
+            }
+            div direction=column gap=4 padding=8 {
+                @for worktree in worktrees {
+                    @let label = format!("{} ({})", worktree.path.display(), worktree_label(worktree));
+                    @if worktree.is_current {
+                        span font-weight=600 color="#1f2328" { "✓ " (label) }
+                    } @else {
+                        @let url = format!("/local?repo={}&{spec_query}", urlencoding::encode(&worktree.path.to_string_lossy()));
+                        anchor href=(url) color="#0969da" {
+                            (label)
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The branch checked out in `worktree`, or its short commit SHA when
+/// detached.
+fn worktree_label(worktree: &Worktree) -> String {
+    match (&worktree.branch, &worktree.head) {
+        (Some(branch), _) => branch.clone(),
+        (None, Some(head)) => format!("detached at {}", &head[..head.len().min(7)]),
+        (None, None) => "no commits".to_string(),
+    }
+}
@@ -0,0 +1,84 @@
+//! Import of hosted PR comments into a local review.
+//!
+//! Renders the form taking a PR reference and the outcome of the import.
+
+use hyperchad::transformer::models::Selector;
+use hyperchad_template::{Containers, container};
+
+/// Element ID of the import form, replaced with the outcome of an import.
+pub const IMPORT_ID: &str = "pr-comment-import";
+
+/// Render the form importing a PR's review comments.
+///
+/// `spec_query` is the diff spec's query string, used to link back to the
+/// same diff once the import is done.
+#[must_use]
+pub fn render_import_form(repo_path: &str, spec_query: &str) -> Containers {
+    let api_url = format!(
+        "/api/local/import?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
+
+    container! {
+        form
+            id=(IMPORT_ID)
+            hx-post=(api_url)
+            hx-target=(Selector::Id(IMPORT_ID.to_string()))
+            hx-swap="outerHTML"
+            direction=row
+            gap=8
+            align-items=center
+        {
+            input
+                type=text
+                name="pr"
+                placeholder="owner/repo#123 or PR URL"
+                padding=8
+                border="1px solid #d0d7de"
+                border-radius=6
+                font-size=14
+                width=280;
+            button
+                type=submit
+                background="#ffffff"
+                color="#24292f"
+                border="1px solid #d0d7de"
+                padding-x=16
+                padding-y=8
+                border-radius=6
+                font-size=14
+                cursor=pointer
+            {
+                "Import PR comments"
+            }
+        }
+    }
+}
+
+/// Render the outcome of an import, with a link reloading the diff to show
+/// the imported threads.
+#[must_use]
+pub fn render_import_result(
+    repo_path: &str,
+    spec_query: &str,
+    result: Result<&str, &str>,
+) -> Containers {
+    let reload_url = format!(
+        "/local?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
+
+    container! {
+        div id=(IMPORT_ID) direction=row gap=12 align-items=center font-size=14 {
+            @match result {
+                Ok(message) => {
+                    span color="#1a7f37" { (message) }
+                    anchor href=(reload_url) color="#0969da" font-weight=600 { "Reload" }
+                }
+                Err(message) => {
+                    span color="#cf222e" { (message) }
+                }
+            }
+        }
+    }
+}
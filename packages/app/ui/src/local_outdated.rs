@@ -0,0 +1,186 @@
+//! Outdated line comments of a local review.
+//!
+//! Line threads whose line is no longer part of the diff can't be shown
+//! inline, so they're listed in their own section with the hunk excerpt
+//! they were made on and a form re-attaching them to another line.
+
+use std::collections::HashSet;
+
+use chadreview_local_comment_models::{LineAnchor, LocalComment};
+use hyperchad::transformer::models::Selector;
+use hyperchad_template::{Containers, container};
+use switchy::uuid::Uuid;
+
+use crate::local_comments;
+use crate::local_share::thread_location;
+
+/// Element ID of the outcome of re-attaching a thread.
+#[must_use]
+pub fn reattach_result_id(thread_id: Uuid) -> String {
+    format!("outdated-reattach-result-{thread_id}")
+}
+
+/// Render the section listing outdated threads, or nothing if there are
+/// none.
+///
+/// `spec_query` is the diff spec's query string, used to find the line a
+/// thread is re-attached to in the same diff.
+#[must_use]
+pub fn render_outdated_section(
+    threads: &[&LocalComment],
+    repo_path: &str,
+    spec_query: &str,
+    viewed_reply_ids: &HashSet<Uuid>,
+) -> Containers {
+    if threads.is_empty() {
+        return vec![];
+    }
+
+    container! {
+        div id="outdated-comments-section" gap=12 {
+            div direction=row align-items=center gap=8 {
+                h2 font-size=18 font-weight=600 color="#24292f" { "Outdated" }
+                span
+                    background="#fff8c5"
+                    color="#9a6700"
+                    padding-x=8
+                    padding-y=2
+                    border-radius=12
+                    font-size=12
+                    font-weight=600
+                {
+                    (threads.len())
+                }
+            }
+            span font-size=14 color="#57606a" {
+                "These comments are on lines that are no longer part of this diff."
+            }
+            @for thread in threads {
+                div
+                    border="1px solid #d4a72c"
+                    border-radius=6
+                    padding=12
+                    gap=8
+                {
+                    span font-size=14 font-weight=600 color="#24292f" {
+                        (thread_location(&thread.comment_type))
+                    }
+                    (render_excerpt(thread.anchor.as_ref()))
+                    (render_reattach_form(thread.id, repo_path, spec_query))
+                    (local_comments::render_local_comment_with_reply(thread, repo_path, viewed_reply_ids))
+                }
+            }
+        }
+    }
+}
+
+/// Render the outcome of re-attaching a thread, with a link reloading the
+/// diff to show it on its new line.
+#[must_use]
+pub fn render_reattach_result(
+    thread_id: Uuid,
+    repo_path: &str,
+    spec_query: &str,
+    result: Result<&str, &str>,
+) -> Containers {
+    let reload_url = format!(
+        "/local?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
+
+    container! {
+        div id=(reattach_result_id(thread_id)) direction=row gap=12 align-items=center font-size=14 {
+            @match result {
+                Ok(message) => {
+                    span color="#1a7f37" { (message) }
+                    anchor href=(reload_url) color="#0969da" font-weight=600 { "Reload" }
+                }
+                Err(message) => {
+                    span color="#cf222e" { (message) }
+                }
+            }
+        }
+    }
+}
+
+/// Render the hunk excerpt a thread was made on.
+fn render_excerpt(anchor: Option<&LineAnchor>) -> Containers {
+    let Some(anchor) = anchor else {
+        return container! {
+            span font-size=12 color="#57606a" {
+                "No excerpt was recorded for this comment."
+            }
+        };
+    };
+
+    container! {
+        div
+            border="1px solid #d0d7de"
+            border-radius=6
+            font-family="monospace"
+            font-size=12
+        {
+            div background="#f6f8fa" color="#57606a" padding-x=8 padding-y=4 {
+                (anchor.hunk_header)
+            }
+            @for line in &anchor.excerpt {
+                div background=(excerpt_line_background(line)) padding-x=8 white-space=preserve-wrap {
+                    (line)
+                }
+            }
+        }
+    }
+}
+
+/// Background of an excerpt line, by its diff marker.
+fn excerpt_line_background(line: &str) -> &'static str {
+    match line.chars().next() {
+        Some('+') => "#e6ffec",
+        Some('-') => "#ffebe9",
+        _ => "#ffffff",
+    }
+}
+
+/// Render the form moving a thread to another line of its file.
+fn render_reattach_form(thread_id: Uuid, repo_path: &str, spec_query: &str) -> Containers {
+    let api_url = format!(
+        "/api/local/comment/reattach?repo={}&thread_id={thread_id}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
+    let result_id = reattach_result_id(thread_id);
+
+    container! {
+        form
+            hx-put=(api_url)
+            hx-target=(Selector::Id(result_id.clone()))
+            hx-swap="outerHTML"
+            direction=row
+            gap=8
+            align-items=center
+        {
+            input
+                type=text
+                name="line"
+                placeholder="Line (e.g. 42, or old:42)"
+                padding=6
+                border="1px solid #d0d7de"
+                border-radius=6
+                font-size=14
+                width=200;
+            button
+                type=submit
+                background="#ffffff"
+                color="#24292f"
+                border="1px solid #d0d7de"
+                padding-x=12
+                padding-y=6
+                border-radius=6
+                font-size=14
+                cursor=pointer
+            {
+                "Re-attach"
+            }
+            div id=(result_id) {}
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Saved replies picker for local comment forms.
+//!
+//! Adds a "Saved replies" dropdown to every comment and reply form that
+//! inserts the chosen text at the cursor. `{file}` and `{line}` in a saved
+//! reply are filled in from the form's `path` and `line` fields, and left
+//! empty in forms without them (such as general comments).
+
+use hyperchad_template::{Containers, container};
+use serde_json::Value;
+
+use crate::local_drafts::script_json;
+
+/// Script adding the picker to comment forms. `__REPLIES__` is replaced
+/// with the saved replies as a JSON array of `[name, body]` pairs.
+///
+/// Forms are picked up when first focused, so forms rendered after the page
+/// loads (new threads, re-rendered replies) get a picker too.
+const SAVED_REPLIES_SCRIPT: &str = r#"<script>
+(() => {
+    const replies = __REPLIES__;
+    const prefixes = ["local-comment-form-", "local-file-comment-form-", "local-reply-form-", "general-comment-form"];
+    const commentForm = (element) => {
+        const form = element instanceof Element ? element.closest("form") : null;
+        return form && prefixes.some((prefix) => form.id.startsWith(prefix)) ? form : null;
+    };
+    const field = (form, name) => form.querySelector(`input[name="${name}"]`)?.value ?? "";
+    const expand = (form, body) => body
+        .replaceAll("{file}", field(form, "path"))
+        .replaceAll("{line}", field(form, "line"));
+    const insert = (textarea, text) => {
+        const start = textarea.selectionStart ?? textarea.value.length;
+        const end = textarea.selectionEnd ?? start;
+        textarea.value = textarea.value.slice(0, start) + text + textarea.value.slice(end);
+        textarea.focus();
+        textarea.selectionStart = textarea.selectionEnd = start + text.length;
+        // Let the draft autosave see the change
+        textarea.dispatchEvent(new Event("input", { bubbles: true }));
+    };
+    const addPicker = (form) => {
+        const textarea = form.querySelector('textarea[name="body"]');
+        if (!textarea || form.querySelector("select.saved-replies")) return;
+        const select = document.createElement("select");
+        select.className = "saved-replies";
+        select.style.cssText = "align-self: flex-start; padding: 4px 8px; border: 1px solid #d0d7de; border-radius: 6px; font-size: 12px; color: #57606a; background: #ffffff;";
+        select.add(new Option("Saved replies…", ""));
+        replies.forEach(([name], index) => select.add(new Option(name, String(index))));
+        select.addEventListener("change", () => {
+            const index = select.value;
+            select.value = "";
+            if (index !== "") insert(textarea, expand(form, replies[Number(index)][1]));
+        });
+        textarea.before(select);
+    };
+    document.addEventListener("focusin", (event) => {
+        const form = commentForm(event.target);
+        if (form) addPicker(form);
+    });
+    const addAll = () => {
+        for (const form of document.querySelectorAll("form[id]")) {
+            if (commentForm(form)) addPicker(form);
+        }
+    };
+    if (document.readyState === "loading") {
+        document.addEventListener("DOMContentLoaded", addAll);
+    } else {
+        addAll();
+    }
+})();
+</script>"#;
+
+/// Render the script adding the saved replies picker, with `replies` as
+/// `(name, body)` pairs. Include it once per page; nothing is rendered if
+/// there are no saved replies.
+#[must_use]
+pub fn render_saved_replies_script(replies: &[(&str, &str)]) -> Containers {
+    if replies.is_empty() {
+        return vec![];
+    }
+
+    let replies: Vec<Value> = replies
+        .iter()
+        .map(|(name, body)| Value::from(vec![*name, *body]))
+        .collect();
+    let script = SAVED_REPLIES_SCRIPT.replace("__REPLIES__", &script_json(&Value::Array(replies)));
+
+    container! {
+        div {
+            raw { (script) }
+        }
+    }
+}
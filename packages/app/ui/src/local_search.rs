@@ -0,0 +1,158 @@
+//! Full-text search within a local diff.
+//!
+//! Renders the search box above the diff and the list of matches, each
+//! linking to its line with a permalink.
+
+use chadreview_diff_models::{LineNumber, MatchSource, SearchMatch};
+use hyperchad::transformer::models::Selector;
+use hyperchad_template::{Containers, container};
+
+use crate::diff_viewer::permalink::permalink_fragment;
+
+/// Element ID of the search results, replaced on every search.
+pub const SEARCH_RESULTS_ID: &str = "diff-search-results";
+
+/// Render the search box for a local diff.
+///
+/// `spec_query` is the diff spec's query string, so the search runs
+/// against the same diff being viewed.
+#[must_use]
+pub fn render_search_box(repo_path: &str, spec_query: &str) -> Containers {
+    let api_url = format!(
+        "/api/local/search?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
+
+    container! {
+        div
+            padding=12
+            background="#f6f8fa"
+            border="1px solid #d0d7de"
+            border-radius=6
+        {
+            form
+                hx-post=(api_url)
+                hx-target=(Selector::Id(SEARCH_RESULTS_ID.to_string()))
+                hx-swap="outerHTML"
+                direction=row
+                gap=8
+                align-items=center
+            {
+                input
+                    type=text
+                    name="q"
+                    placeholder="Search the diff"
+                    padding=8
+                    border="1px solid #d0d7de"
+                    border-radius=6
+                    font-size=14
+                    flex=1;
+                button
+                    type=submit
+                    name="scope"
+                    value="diff"
+                    background="#0969da"
+                    color="#ffffff"
+                    padding-x=16
+                    padding-y=8
+                    border-radius=6
+                    font-weight=600
+                    font-size=14
+                    cursor=pointer
+                {
+                    "Search diff"
+                }
+                button
+                    type=submit
+                    name="scope"
+                    value="files"
+                    background="#ffffff"
+                    color="#24292f"
+                    border="1px solid #d0d7de"
+                    padding-x=16
+                    padding-y=8
+                    border-radius=6
+                    font-size=14
+                    cursor=pointer
+                {
+                    "Search full files"
+                }
+            }
+            (render_search_results(None, &[], false))
+        }
+    }
+}
+
+/// Render the matches for `query`.
+///
+/// `None` renders an empty placeholder. `limit_reached` notes that more
+/// matches exist than were returned.
+#[must_use]
+pub fn render_search_results(
+    query: Option<&str>,
+    matches: &[SearchMatch],
+    limit_reached: bool,
+) -> Containers {
+    container! {
+        div id=(SEARCH_RESULTS_ID) direction=column gap=2 font-size=13 {
+            @match query {
+                Some(query) => {
+                    @if matches.is_empty() {
+                        div margin-top=8 color="#57606a" {
+                            (format!("No matches for \"{query}\""))
+                        }
+                    } @else {
+                        div margin-top=8 margin-bottom=4 color="#57606a" {
+                            @if limit_reached {
+                                (format!("Showing the first {} matches for \"{query}\"", matches.len()))
+                            } @else {
+                                (format!("{} matches for \"{query}\"", matches.len()))
+                            }
+                        }
+                        @for search_match in matches {
+                            (render_search_match(search_match))
+                        }
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+fn render_search_match(search_match: &SearchMatch) -> Containers {
+    let (marker, color, background) = match search_match.source {
+        MatchSource::Addition => ("+", "#1a7f37", "#e6ffec"),
+        MatchSource::Deletion => ("-", "#cf222e", "#ffebe9"),
+        MatchSource::Context => (" ", "#57606a", "#ffffff"),
+        MatchSource::File => ("…", "#57606a", "#ffffff"),
+    };
+    let line = match search_match.source {
+        MatchSource::Deletion => LineNumber::Old {
+            line: search_match.line_number,
+        },
+        MatchSource::Addition | MatchSource::Context | MatchSource::File => LineNumber::New {
+            line: search_match.line_number,
+        },
+    };
+
+    container! {
+        anchor
+            href=(format!("#{}", permalink_fragment(&search_match.filename, Some(line))))
+            direction=row
+            gap=8
+            padding-x=8
+            padding-y=2
+            border-radius=6
+            background=(background)
+        {
+            span font-family="monospace" color="#0969da" {
+                (format!("{}:{}", search_match.filename, search_match.line_number))
+            }
+            span font-family="monospace" color=(color) font-weight=600 { (marker) }
+            span font-family="monospace" color="#1f2328" flex=1 white-space=preserve-wrap {
+                (search_match.text.trim_end())
+            }
+        }
+    }
+}
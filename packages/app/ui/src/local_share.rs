@@ -0,0 +1,121 @@
+//! Read-only local review UI components.
+//!
+//! Renders a local diff and its comment threads for a shared review link.
+//! Nothing rendered here posts back to the server: there are no comment
+//! forms, state buttons or AI controls.
+
+use chadreview_local_comment_models::{LineNumber, LocalComment, LocalCommentType, ThreadState};
+use chadreview_local_diff_models::LocalDiffInfo;
+use chadreview_pr_models::DiffFile;
+use hyperchad::template::{Containers, container};
+
+use crate::diff_viewer;
+use crate::local_comments::format_time_ago;
+use crate::local_header;
+use crate::markdown::markdown_to_container;
+
+/// Render a local review in read-only mode.
+///
+/// Shows the diff header, a banner explaining the view is read-only, every
+/// comment thread (with replies) and the diff itself.
+#[must_use]
+pub fn render_shared_review(
+    info: &LocalDiffInfo,
+    diffs: &[DiffFile],
+    comments: &[LocalComment],
+) -> Containers {
+    container! {
+        (local_header::render_local_diff_header(info))
+
+        div
+            padding=12
+            background="#ddf4ff"
+            border="1px solid #54aeff"
+            border-radius=6
+            color="#0969da"
+            font-size=14
+        {
+            "You are viewing a read-only shared review. Comments cannot be added or changed."
+        }
+
+        @if !comments.is_empty() {
+            section gap=12 {
+                h2 font-size=18 font-weight=600 color="#24292f" {
+                    "Comments (" (comments.len().to_string()) ")"
+                }
+                @for comment in comments {
+                    (render_shared_thread(comment))
+                }
+            }
+        }
+
+        (diff_viewer::render_readonly(diffs))
+    }
+}
+
+/// Render a single comment thread without any actions.
+#[must_use]
+pub fn render_shared_thread(comment: &LocalComment) -> Containers {
+    let bg_color = match comment.state {
+        ThreadState::Open => "#ffffff",
+        ThreadState::Resolved => "#f0f6fc",
+        ThreadState::SavedForLater => "#fff8e5",
+    };
+
+    container! {
+        div
+            background=(bg_color)
+            border="1px solid #d0d7de"
+            border-radius=6
+            padding=12
+            gap=8
+        {
+            div direction=row align-items=center gap=8 font-size=12 color="#57606a" {
+                span font-family="monospace" { (thread_location(&comment.comment_type)) }
+                @match comment.state {
+                    ThreadState::Resolved => {
+                        span font-weight=600 color="#8250df" { "Resolved" }
+                    }
+                    ThreadState::SavedForLater => {
+                        span font-weight=600 color="#bf8700" { "Later" }
+                    }
+                    ThreadState::Open => {}
+                }
+            }
+            (render_shared_comment(comment))
+            @for reply in &comment.replies {
+                div margin-left=20 padding-top=8 border-top="1px solid #d0d7de" {
+                    (render_shared_comment(reply))
+                }
+            }
+        }
+    }
+}
+
+fn render_shared_comment(comment: &LocalComment) -> Containers {
+    container! {
+        div gap=4 {
+            div direction=row align-items=center gap=8 {
+                span font-weight=600 font-size=14 color="#24292f" {
+                    (&comment.author.name)
+                }
+                span font-size=12 color="#57606a" { (format_time_ago(comment.created_at)) }
+            }
+            div color="#24292f" font-size=14 {
+                (markdown_to_container(&comment.body))
+            }
+        }
+    }
+}
+
+/// Describe where a thread is anchored (e.g. `src/lib.rs:42`).
+pub(crate) fn thread_location(comment_type: &LocalCommentType) -> String {
+    match comment_type {
+        LocalCommentType::LineLevelComment { path, line } => match line {
+            LineNumber::New { line } => format!("{path}:{line}"),
+            LineNumber::Old { line } => format!("{path}:{line} (old)"),
+        },
+        LocalCommentType::FileLevelComment { path } => path.clone(),
+        LocalCommentType::General | LocalCommentType::Reply { .. } => "General".to_string(),
+    }
+}
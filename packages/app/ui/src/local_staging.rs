@@ -0,0 +1,235 @@
+//! Staging actions of a working tree diff.
+//!
+//! Each file of a working tree diff gets a bar above it staging or
+//! unstaging the whole file, or staging one of its hunks, so the changes can
+//! be split into commits while reviewing them. The bar also discards the
+//! file's changes, after asking for confirmation. Each action answers with
+//! the re-rendered bar.
+
+use std::collections::HashSet;
+
+use chadreview_pr_models::{DiffFile, FileStatus};
+use hyperchad::transformer::models::Selector;
+use hyperchad_template::{Containers, container};
+
+use crate::local_comments;
+
+/// Route staging, unstaging and discarding files and hunks.
+pub const STAGING_ROUTE: &str = "/api/local/staging";
+
+/// How the files of a working tree diff are staged.
+#[derive(Debug, Clone, Default)]
+pub struct Staging {
+    /// Whether the diff only shows the staged changes.
+    pub staged_only: bool,
+    /// Paths of the files with staged changes.
+    pub staged_paths: HashSet<String>,
+}
+
+/// Element ID of the staging bar of file `path`.
+#[must_use]
+pub fn file_staging_id(path: &str) -> String {
+    format!("file-staging-{}", local_comments::classify_name(path))
+}
+
+/// Render the staging bar of file `path`, and the outcome of the last
+/// action on it, if any. `file` is the file's diff, `None` once it no longer
+/// has changes, which leaves only the outcome.
+///
+/// A file is unstaged as a whole. Its hunks can be staged one by one until
+/// any of its changes are staged, since they are shown against the file's
+/// last commit rather than its index entry. Files that aren't valid UTF-8
+/// are only staged as a whole, as their hunks are re-encoded for display.
+#[must_use]
+pub fn render_file_staging(
+    path: &str,
+    file: Option<&DiffFile>,
+    repo_path: &str,
+    spec_query: &str,
+    staging: &Staging,
+    outcome: Option<Result<&str, &str>>,
+) -> Containers {
+    let id = file_staging_id(path);
+    let staged = staging.staged_paths.contains(path);
+    let url = format!(
+        "{STAGING_ROUTE}?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
+
+    let Some(file) = file else {
+        return container! {
+            div id=(id) font-size=12 {
+                (render_outcome(outcome))
+            }
+        };
+    };
+    let hunks = &file.hunks;
+
+    container! {
+        div id=(id.clone()) direction=row align-items=center gap=8 font-size=12 {
+            span
+                padding-y=2
+                padding-x=8
+                border-radius=4
+                border=(if staged { "1px solid #1a7f37" } else { "1px solid #d0d7de" })
+                color=(if staged { "#1a7f37" } else { "#57606a" })
+            {
+                @if staged { "Staged changes" } @else { "Not staged" }
+            }
+            form
+                hx-post=(url.clone())
+                hx-target=(Selector::Id(id.clone()))
+                hx-swap="outerHTML"
+                direction=row
+                gap=8
+            {
+                input type=hidden name="path" value=(path);
+                @if !staging.staged_only {
+                    (render_staging_button("stage", "Stage file"))
+                }
+                @if staged {
+                    (render_staging_button("unstage", "Unstage file"))
+                }
+            }
+            @if !staging.staged_only && !staged && !file.decoded_lossy && !hunks.is_empty() {
+                details {
+                    summary cursor=pointer color="#0969da" {
+                        "Stage hunk"
+                    }
+                    div gap=4 margin-top=4 {
+                        @for (index, hunk) in hunks.iter().enumerate() {
+                            form
+                                hx-post=(url.clone())
+                                hx-target=(Selector::Id(id.clone()))
+                                hx-swap="outerHTML"
+                            {
+                                input type=hidden name="path" value=(path);
+                                input type=hidden name="hunk" value=(index);
+                                button
+                                    type=submit
+                                    name="action"
+                                    value="hunk"
+                                    cursor=pointer
+                                    background="transparent"
+                                    font-family="monospace"
+                                    font-size=12
+                                    color="#0969da"
+                                {
+                                    "@@ -"(hunk.old_start)","(hunk.old_lines)" +"(hunk.new_start)","(hunk.new_lines)" @@"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            (render_discard(file, &url, &id))
+            (render_outcome(outcome))
+        }
+    }
+}
+
+/// Render the "Discard changes" button and the confirmation it shows.
+/// Discarding a new file deletes it, which the confirmation spells out and
+/// the form has to ask for explicitly.
+fn render_discard(file: &DiffFile, url: &str, staging_id: &str) -> Containers {
+    let confirm_id = format!("{staging_id}-discard-confirm");
+    let added = file.status == FileStatus::Added;
+
+    container! {
+        button
+            type=button
+            padding-x=8
+            padding-y=2
+            cursor=pointer
+            background="transparent"
+            font-size=12
+            color="#cf222e"
+            fx-click=fx { element_by_id(confirm_id).display() }
+        {
+            "Discard changes"
+        }
+        form
+            id=(confirm_id.clone())
+            hidden=(true)
+            hx-post=(url)
+            hx-target=(Selector::Id(staging_id.to_string()))
+            hx-swap="outerHTML"
+            direction=row
+            align-items=center
+            gap=8
+        {
+            input type=hidden name="path" value=(file.filename);
+            @if added {
+                input type=hidden name="delete_added" value="true";
+            }
+            span color="#57606a" {
+                @if added {
+                    "This file is new, discarding deletes it. This can't be undone."
+                } @else {
+                    "Discard all changes to this file, staged ones included? This can't be undone."
+                }
+            }
+            button
+                type=submit
+                name="action"
+                value="discard"
+                padding-x=8
+                padding-y=2
+                cursor=pointer
+                background="#cf222e"
+                border-radius=4
+                font-size=12
+                color="#ffffff"
+            {
+                @if added { "Yes, delete" } @else { "Yes, discard" }
+            }
+            button
+                type=button
+                padding-x=8
+                padding-y=2
+                cursor=pointer
+                background="transparent"
+                font-size=12
+                color="#57606a"
+                fx-click=fx { element_by_id(confirm_id).no_display() }
+            {
+                "Cancel"
+            }
+        }
+    }
+}
+
+/// Render the outcome of the last action, if any.
+fn render_outcome(outcome: Option<Result<&str, &str>>) -> Containers {
+    container! {
+        @match outcome {
+            Some(Ok(message)) => {
+                span color="#57606a" { (message) }
+            }
+            Some(Err(message)) => {
+                span color="#cf222e" { (message) }
+            }
+            None => {}
+        }
+    }
+}
+
+fn render_staging_button(action: &str, label: &str) -> Containers {
+    container! {
+        button
+            type=submit
+            name="action"
+            value=(action)
+            padding-x=8
+            padding-y=2
+            cursor=pointer
+            background="#ffffff"
+            border="1px solid #d0d7de"
+            border-radius=4
+            font-size=12
+            color="#24292f"
+        {
+            (label)
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! AI summaries of changed files in a local diff.
+//!
+//! Each file of the diff gets a slot above it for its summary, filled in as
+//! summaries are generated, and a form asks an agent to summarize the files
+//! that don't have one yet.
+
+use hyperchad::transformer::models::Selector;
+use hyperchad_router::Container;
+use hyperchad_template::{Containers, container};
+
+use crate::local_comments;
+use crate::markdown::markdown_to_container;
+
+/// Route starting the summaries of a diff's files.
+pub const SUMMARIZE_ROUTE: &str = "/api/local/ai/summaries";
+
+/// Element ID of the outcome of starting the summaries.
+pub const SUMMARY_STATUS_ID: &str = "ai-summary-status";
+
+/// Element ID of the summary slot of file `path`.
+#[must_use]
+pub fn file_summary_id(path: &str) -> String {
+    format!("file-summary-{}", local_comments::classify_name(path))
+}
+
+/// Render the summary slot of file `path`, as a collapsible banner holding
+/// `summary`, or empty if the file has no summary yet.
+///
+/// # Panics
+///
+/// Panics if the container macro produces an empty iterator (should never happen).
+#[must_use]
+pub fn render_file_summary(path: &str, summary: Option<&str>) -> Container {
+    container! {
+        div id=(file_summary_id(path)) {
+            @if let Some(text) = summary {
+                details
+                    open
+                    padding-x=12
+                    padding-y=8
+                    background="#ddf4ff"
+                    border="1px solid #54aeff"
+                    border-radius=6
+                {
+                    summary
+                        cursor=pointer
+                        font-size=12
+                        font-weight=600
+                        color="#0969da"
+                    {
+                        "AI summary"
+                    }
+                    div margin-top=4 font-size=14 color="#24292f" {
+                        (markdown_to_container(text))
+                    }
+                }
+            }
+        }
+    }
+    .into_iter()
+    .next()
+    .unwrap()
+}
+
+/// Render the outcome of starting the summaries.
+///
+/// # Panics
+///
+/// Panics if the container macro produces an empty iterator (should never happen).
+#[must_use]
+pub fn render_summary_status(message: &str) -> Container {
+    container! {
+        span id=(SUMMARY_STATUS_ID) font-size=14 color="#57606a" {
+            (message)
+        }
+    }
+    .into_iter()
+    .next()
+    .unwrap()
+}
+
+/// Render the form asking an agent to summarize the files of the diff of
+/// `spec_query` that don't have a summary yet.
+#[must_use]
+pub fn render_summarize_form(
+    repo_path: &str,
+    spec_query: &str,
+    default_agent: Option<&str>,
+) -> Containers {
+    let api_url = format!(
+        "{SUMMARIZE_ROUTE}?repo={}&{spec_query}",
+        urlencoding::encode(repo_path)
+    );
+
+    container! {
+        form
+            id="ai-summary-form"
+            hx-post=(api_url)
+            hx-swap="outerHTML"
+            hx-target=(Selector::Id(SUMMARY_STATUS_ID.to_string()))
+            direction=row
+            align-items=center
+            gap=12
+            padding=16
+            background="#f6f8fa"
+            border="1px solid #d0d7de"
+            border-radius=6
+        {
+            div flex=1 {
+                (local_comments::render_ai_action_selector("ai_agent", default_agent))
+            }
+            (render_summary_status(""))
+            button
+                type=submit
+                background="#0969da"
+                color="#ffffff"
+                padding-x=16
+                padding-y=8
+                border-radius=6
+                font-weight=600
+                font-size=14
+                cursor=pointer
+            {
+                "Summarize files"
+            }
+        }
+    }
+}
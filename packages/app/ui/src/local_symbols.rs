@@ -0,0 +1,158 @@
+//! Symbol lookup from a local diff.
+//!
+//! Renders the "Find definition/references" box above the diff and its
+//! results. Double-clicking an identifier in the diff looks it up, so how a
+//! changed function is used can be checked without leaving the review.
+
+use chadreview_diff_models::LineNumber;
+use chadreview_local_diff_models::{SymbolLocation, SymbolLookup};
+use hyperchad::transformer::models::Selector;
+use hyperchad_template::{Containers, container};
+
+use crate::diff_viewer::permalink::permalink_fragment;
+
+/// Element ID of the lookup results, replaced on every lookup.
+pub const SYMBOL_RESULTS_ID: &str = "symbol-results";
+
+/// Element ID of the lookup form, also submitted by [`SYMBOL_SCRIPT`].
+const SYMBOL_FORM_ID: &str = "symbol-lookup-form";
+
+/// Element ID of the lookup form's name input.
+const SYMBOL_NAME_ID: &str = "symbol-lookup-name";
+
+/// Script looking up identifiers double-clicked in the diff with the lookup
+/// form.
+const SYMBOL_SCRIPT: &str = r#"<script>
+(() => {
+    document.addEventListener("dblclick", (event) => {
+        if (!event.target.closest || !event.target.closest("[id^='file-']")) return;
+        const name = String(window.getSelection()).trim();
+        if (!/^[\p{L}_][\p{L}\p{N}_]*$/u.test(name)) return;
+        const form = document.getElementById("symbol-lookup-form");
+        const input = document.getElementById("symbol-lookup-name");
+        if (!form || !input) return;
+        input.value = name;
+        form.requestSubmit();
+        form.scrollIntoView({ block: "center" });
+    });
+})();
+</script>"#;
+
+/// Render the symbol lookup box for a local diff.
+#[must_use]
+pub fn render_symbol_box(repo_path: &str) -> Containers {
+    let api_url = format!("/api/local/symbols?repo={}", urlencoding::encode(repo_path));
+
+    container! {
+        div
+            padding=12
+            background="#f6f8fa"
+            border="1px solid #d0d7de"
+            border-radius=6
+        {
+            form
+                id=(SYMBOL_FORM_ID)
+                hx-post=(api_url)
+                hx-target=(Selector::Id(SYMBOL_RESULTS_ID.to_string()))
+                hx-swap="outerHTML"
+                direction=row
+                gap=8
+                align-items=center
+            {
+                input
+                    id=(SYMBOL_NAME_ID)
+                    type=text
+                    name="name"
+                    placeholder="Symbol name, or double-click one in the diff"
+                    padding=8
+                    border="1px solid #d0d7de"
+                    border-radius=6
+                    font-size=14
+                    font-family="monospace"
+                    flex=1;
+                button
+                    type=submit
+                    background="#ffffff"
+                    color="#24292f"
+                    border="1px solid #d0d7de"
+                    padding-x=16
+                    padding-y=8
+                    border-radius=6
+                    font-size=14
+                    cursor=pointer
+                {
+                    "Find definition/references"
+                }
+            }
+            (render_symbol_results(None, &SymbolLookup::default()))
+        }
+        div {
+            raw { (SYMBOL_SCRIPT) }
+        }
+    }
+}
+
+/// Render the definitions and references found for `name`.
+///
+/// `None` renders an empty placeholder.
+#[must_use]
+pub fn render_symbol_results(name: Option<&str>, lookup: &SymbolLookup) -> Containers {
+    container! {
+        div id=(SYMBOL_RESULTS_ID) direction=column gap=2 font-size=13 {
+            @if let Some(name) = name {
+                @if lookup.definitions.is_empty() && lookup.references.is_empty() {
+                    div margin-top=8 color="#57606a" {
+                        (format!("No definitions or references of \"{name}\""))
+                    }
+                } @else {
+                    div margin-top=8 margin-bottom=4 font-weight=600 color="#1f2328" {
+                        (format!("Definitions of \"{name}\" ({})", lookup.definitions.len()))
+                    }
+                    @for location in &lookup.definitions {
+                        (render_location(location))
+                    }
+                    div margin-top=8 margin-bottom=4 font-weight=600 color="#1f2328" {
+                        @if lookup.references_truncated {
+                            (format!("First {} references", lookup.references.len()))
+                        } @else {
+                            (format!("References ({})", lookup.references.len()))
+                        }
+                    }
+                    @for location in &lookup.references {
+                        (render_location(location))
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Render a definition or reference, linking to its line in case the file
+/// is part of the diff.
+fn render_location(location: &SymbolLocation) -> Containers {
+    let line = LineNumber::New {
+        line: location.line,
+    };
+
+    container! {
+        anchor
+            href=(format!("#{}", permalink_fragment(&location.path, Some(line))))
+            direction=row
+            gap=8
+            padding-x=8
+            padding-y=2
+            border-radius=6
+            background="#ffffff"
+        {
+            span font-family="monospace" color="#0969da" {
+                (format!("{}:{}", location.path, location.line))
+            }
+            @if let Some(kind) = &location.kind {
+                span color="#57606a" { (kind) }
+            }
+            span font-family="monospace" color="#1f2328" flex=1 white-space=preserve-wrap {
+                (location.text.trim())
+            }
+        }
+    }
+}
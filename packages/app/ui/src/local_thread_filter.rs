@@ -0,0 +1,245 @@
+//! Search and filters over the comment threads of a local review.
+//!
+//! Renders the filter panel (text, file and author fields plus state and
+//! AI toggles) and the threads matching the current filter, each linking
+//! to where the thread is shown.
+
+use chadreview_local_comment_models::{LocalComment, ThreadFilter, ThreadState};
+use hyperchad::transformer::models::Selector;
+use hyperchad_template::{Containers, container};
+
+use crate::local_comments::{format_time_ago, local_comment_thread_id};
+use crate::local_share::thread_location;
+
+/// Element ID of the filter panel, replaced on every search.
+pub const THREAD_FILTER_ID: &str = "thread-filter";
+
+/// Longest excerpt of a thread's body shown in the results.
+const BODY_EXCERPT_CHARS: usize = 100;
+
+/// The state toggles: the value submitted and its label.
+const STATE_OPTIONS: &[(&str, &str)] = &[
+    ("", "All"),
+    ("open", "Open"),
+    ("resolved", "Resolved"),
+    ("saved_for_later", "Saved for later"),
+];
+
+/// The AI action toggles: the value submitted and its label.
+const AI_OPTIONS: &[(&str, &str)] = &[("", "Any"), ("yes", "With AI"), ("no", "Without AI")];
+
+/// Render the thread filter panel with the current `filter` and the
+/// `threads` matching it.
+///
+/// `threads` is `None` before any filter is applied, which renders just the
+/// panel.
+#[must_use]
+pub fn render_thread_filter(
+    repo_path: &str,
+    filter: &ThreadFilter,
+    threads: Option<&[LocalComment]>,
+) -> Containers {
+    let api_url = format!(
+        "/api/local/comments/search?repo={}",
+        urlencoding::encode(repo_path)
+    );
+    let state = filter.state.map_or("", state_value);
+    let ai = match filter.has_ai_action {
+        Some(true) => "yes",
+        Some(false) => "no",
+        None => "",
+    };
+
+    container! {
+        div
+            id=(THREAD_FILTER_ID)
+            padding=12
+            background="#f6f8fa"
+            border="1px solid #d0d7de"
+            border-radius=6
+            gap=8
+        {
+            form
+                hx-post=(api_url)
+                hx-target=(Selector::Id(THREAD_FILTER_ID.to_string()))
+                hx-swap="outerHTML"
+                gap=8
+            {
+                input type=hidden name="state" value=(state);
+                input type=hidden name="ai" value=(ai);
+                div direction=row gap=8 align-items=center {
+                    (render_text_field("q", "Search comments", filter.query.as_deref()))
+                    (render_text_field("path", "File", filter.path.as_deref()))
+                    (render_text_field("author", "Author", filter.author.as_deref()))
+                    button
+                        type=submit
+                        background="#0969da"
+                        color="#ffffff"
+                        padding-x=16
+                        padding-y=8
+                        border-radius=6
+                        font-weight=600
+                        font-size=14
+                        cursor=pointer
+                    {
+                        "Filter threads"
+                    }
+                    @if !filter.is_empty() {
+                        button
+                            type=button
+                            hx-get=(api_url)
+                            hx-target=(Selector::Id(THREAD_FILTER_ID.to_string()))
+                            hx-swap="outerHTML"
+                            background="#ffffff"
+                            color="#24292f"
+                            border="1px solid #d0d7de"
+                            padding-x=16
+                            padding-y=8
+                            border-radius=6
+                            font-size=14
+                            cursor=pointer
+                        {
+                            "Clear"
+                        }
+                    }
+                }
+                div direction=row gap=16 align-items=center {
+                    (render_toggles("set_state", STATE_OPTIONS, state))
+                    (render_toggles("set_ai", AI_OPTIONS, ai))
+                }
+            }
+            @if let Some(threads) = threads {
+                (render_results(threads))
+            }
+        }
+    }
+}
+
+fn render_text_field(name: &str, placeholder: &str, value: Option<&str>) -> Containers {
+    container! {
+        input
+            type=text
+            name=(name)
+            placeholder=(placeholder)
+            value=(value.unwrap_or_default())
+            padding=8
+            border="1px solid #d0d7de"
+            border-radius=6
+            font-size=14
+            flex=1;
+    }
+}
+
+/// Render a group of toggles submitting `name`, with the `current` value
+/// highlighted.
+fn render_toggles(name: &str, options: &[(&str, &str)], current: &str) -> Containers {
+    container! {
+        div direction=row gap=4 {
+            @for (value, label) in options {
+                @if *value == current {
+                    button
+                        type=submit
+                        name=(name)
+                        value=(*value)
+                        background="#ddf4ff"
+                        color="#0969da"
+                        border="1px solid #0969da"
+                        padding-x=10
+                        padding-y=4
+                        border-radius=12
+                        font-size=12
+                        font-weight=600
+                        cursor=pointer
+                    {
+                        (*label)
+                    }
+                } @else {
+                    button
+                        type=submit
+                        name=(name)
+                        value=(*value)
+                        background="#ffffff"
+                        color="#57606a"
+                        border="1px solid #d0d7de"
+                        padding-x=10
+                        padding-y=4
+                        border-radius=12
+                        font-size=12
+                        cursor=pointer
+                    {
+                        (*label)
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn render_results(threads: &[LocalComment]) -> Containers {
+    let count = if threads.len() == 1 {
+        "1 matching thread".to_string()
+    } else {
+        format!("{} matching threads", threads.len())
+    };
+
+    container! {
+        div direction=column gap=2 font-size=13 {
+            div margin-top=4 margin-bottom=4 color="#57606a" { (count) }
+            @for thread in threads {
+                (render_result(thread))
+            }
+        }
+    }
+}
+
+/// Render one matching thread, linking to the thread on the page.
+fn render_result(thread: &LocalComment) -> Containers {
+    let (state, state_color) = match thread.state {
+        ThreadState::Open => ("Open", "#1a7f37"),
+        ThreadState::Resolved => ("Resolved", "#8250df"),
+        ThreadState::SavedForLater => ("Saved", "#9a6700"),
+    };
+    let first_line = thread.body.lines().next().unwrap_or_default();
+    let excerpt: String = first_line.chars().take(BODY_EXCERPT_CHARS).collect();
+    let replies = match thread.count_replies() {
+        0 => None,
+        1 => Some("1 reply".to_string()),
+        count => Some(format!("{count} replies")),
+    };
+
+    container! {
+        anchor
+            href=(format!("#{}", local_comment_thread_id(thread.id)))
+            direction=row
+            gap=8
+            padding-x=8
+            padding-y=4
+            border-radius=6
+            background="#ffffff"
+            align-items=center
+        {
+            span color=(state_color) font-weight=600 min-width=60 { (state) }
+            span font-family="monospace" color="#0969da" {
+                (thread_location(&thread.comment_type))
+            }
+            span color="#1f2328" flex=1 { (excerpt) }
+            span color="#57606a" { (thread.author.name.clone()) }
+            @if let Some(replies) = replies {
+                span color="#57606a" { (replies) }
+            }
+            @if thread.ai_action.is_some() {
+                span color="#8250df" { "AI" }
+            }
+            span color="#57606a" { (format_time_ago(thread.updated_at)) }
+        }
+    }
+}
+
+/// The value a state is submitted as.
+const fn state_value(state: ThreadState) -> &'static str {
+    match state {
+        ThreadState::Open => "open",
+        ThreadState::Resolved => "resolved",
+        ThreadState::SavedForLater => "saved_for_later",
+    }
+}
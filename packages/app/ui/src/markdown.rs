@@ -0,0 +1,92 @@
+//! Markdown rendering for UI components.
+//!
+//! Comment bodies and PR descriptions share one set of rendering options so
+//! every view supports the same markdown extensions.
+
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, RwLock},
+};
+
+use chadreview_markdown::{
+    MarkdownOptions, ReferenceLinks, ReferenceProvider, RepositoryContent,
+    markdown_to_container_with_options,
+};
+use hyperchad::template::Containers;
+
+static OPTIONS: LazyLock<MarkdownOptions> = LazyLock::new(|| {
+    MarkdownOptions::default()
+        .with_mermaid()
+        .with_math()
+        .with_suggestions()
+});
+
+/// Base URLs of self-hosted provider instances, keyed by provider.
+static BASE_URLS: LazyLock<RwLock<HashMap<ReferenceProvider, String>>> =
+    LazyLock::new(RwLock::default);
+
+/// Link references and repository content for `provider` to a self-hosted
+/// instance (e.g. GitHub Enterprise or a Gitea server) instead of its public
+/// instance.
+pub fn set_base_url(provider: ReferenceProvider, base_url: impl Into<String>) {
+    BASE_URLS
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .insert(provider, base_url.into());
+}
+
+fn base_url(provider: ReferenceProvider) -> String {
+    BASE_URLS
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .get(&provider)
+        .map_or_else(|| provider.default_base_url().to_string(), Clone::clone)
+}
+
+/// Render markdown into a container using the UI's markdown options.
+///
+/// Pages rendering markdown should include
+/// [`chadreview_markdown::markdown_stylesheet`] once.
+#[must_use]
+pub fn markdown_to_container(markdown: &str) -> Containers {
+    markdown_to_container_with_options(markdown, &OPTIONS)
+}
+
+/// Render markdown from a hosted repository, linking `#123`,
+/// `owner/repo#123`, commit SHA and `@username` references to the provider.
+#[must_use]
+pub fn repo_markdown_to_container(
+    markdown: &str,
+    provider: ReferenceProvider,
+    owner: &str,
+    repo: &str,
+) -> Containers {
+    markdown_to_container_with_options(markdown, &repo_options(provider, owner, repo))
+}
+
+/// Render markdown from a hosted repository like
+/// [`repo_markdown_to_container`], also pointing relative links and images
+/// (e.g. screenshots in a PR description) at the repository's files as of
+/// `git_ref`.
+#[must_use]
+pub fn repo_content_markdown_to_container(
+    markdown: &str,
+    provider: ReferenceProvider,
+    owner: &str,
+    repo: &str,
+    git_ref: &str,
+) -> Containers {
+    let content =
+        RepositoryContent::new(provider, owner, repo, git_ref).with_base_url(base_url(provider));
+    let options = repo_options(provider, owner, repo).with_repository_content(content);
+
+    markdown_to_container_with_options(markdown, &options)
+}
+
+fn repo_options(provider: ReferenceProvider, owner: &str, repo: &str) -> MarkdownOptions {
+    OPTIONS.clone().with_reference_links(
+        ReferenceLinks::new(provider)
+            .with_base_url(base_url(provider))
+            .with_repository(owner, repo),
+    )
+}
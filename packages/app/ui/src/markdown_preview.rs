@@ -0,0 +1,100 @@
+//! Write/Preview tabs in comment forms.
+//!
+//! Every form with a comment body gets a "Write" and a "Preview" tab above
+//! it. Previewing renders the body on the server at
+//! [`MARKDOWN_PREVIEW_ROUTE`], the same way a posted comment is rendered,
+//! so tables, code fences and emoji can be checked before posting.
+
+use hyperchad_template::{Containers, container};
+
+/// Route rendering a form's `body` to HTML for the preview tab.
+pub const MARKDOWN_PREVIEW_ROUTE: &str = "/api/markdown/preview";
+
+/// Script adding the tabs to comment forms. `__ROUTE__` is replaced with
+/// [`MARKDOWN_PREVIEW_ROUTE`].
+const PREVIEW_SCRIPT: &str = r#"<script>
+(() => {
+    const bodyOf = (form) => form.querySelector('textarea[name="body"]');
+    const commentForm = (element) => {
+        const form = element instanceof Element ? element.closest("form") : null;
+        // Forms with a preview of their own render it themselves
+        return form && bodyOf(form) && !form.querySelector('[name="preview"]') ? form : null;
+    };
+    const tabStyle = (active) => "padding: 4px 12px; border: 1px solid #d0d7de; border-radius: 6px; font-size: 12px; cursor: pointer; "
+        + (active ? "background: #ffffff; color: #24292f; font-weight: 600;" : "background: #f6f8fa; color: #57606a;");
+    const addTabs = (form) => {
+        const textarea = bodyOf(form);
+        if (form.querySelector(".markdown-preview-tabs")) return;
+        const tabs = document.createElement("div");
+        tabs.className = "markdown-preview-tabs";
+        tabs.style.cssText = "display: flex; gap: 4px;";
+        const write = document.createElement("button");
+        const preview = document.createElement("button");
+        const output = document.createElement("div");
+        output.className = "markdown-body";
+        output.hidden = true;
+        output.style.cssText = "min-height: 60px; padding: 8px; border: 1px solid #d0d7de; border-radius: 6px;";
+        const show = (previewing) => {
+            textarea.hidden = previewing;
+            output.hidden = !previewing;
+            write.style.cssText = tabStyle(!previewing);
+            preview.style.cssText = tabStyle(previewing);
+        };
+        write.type = "button";
+        write.textContent = "Write";
+        write.addEventListener("click", () => show(false));
+        preview.type = "button";
+        preview.textContent = "Preview";
+        preview.addEventListener("click", async () => {
+            show(true);
+            if (!textarea.value.trim()) {
+                output.textContent = "Nothing to preview";
+                return;
+            }
+            output.textContent = "Loading preview...";
+            const response = await fetch("__ROUTE__", {
+                method: "POST",
+                body: new URLSearchParams({ body: textarea.value }),
+            });
+            if (response.ok) {
+                output.innerHTML = await response.text();
+            } else {
+                output.textContent = "Failed to render preview: " + await response.text();
+            }
+        });
+        tabs.append(write, preview);
+        textarea.before(tabs);
+        textarea.after(output);
+        form.addEventListener("submit", () => show(false));
+        show(false);
+    };
+    document.addEventListener("focusin", (event) => {
+        const form = commentForm(event.target);
+        if (form) addTabs(form);
+    });
+    const addAll = () => {
+        for (const form of document.querySelectorAll("form")) {
+            if (commentForm(form)) addTabs(form);
+        }
+    };
+    if (document.readyState === "loading") {
+        document.addEventListener("DOMContentLoaded", addAll);
+    } else {
+        addAll();
+    }
+})();
+</script>"#;
+
+/// Render the script adding Write/Preview tabs to comment forms. Include it
+/// once per page, along with
+/// [`chadreview_markdown::markdown_stylesheet`].
+#[must_use]
+pub fn render_markdown_preview_script() -> Containers {
+    let script = PREVIEW_SCRIPT.replace("__ROUTE__", MARKDOWN_PREVIEW_ROUTE);
+
+    container! {
+        div {
+            raw { (script) }
+        }
+    }
+}
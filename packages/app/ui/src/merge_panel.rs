@@ -0,0 +1,166 @@
+use chadreview_pr_models::{
+    Check, CheckStatus, MergeStrategy, MergeableState, PrState, PullRequest,
+};
+use hyperchad::template::{Containers, container};
+use hyperchad::transformer::models::Selector;
+
+/// Element ID of the merge panel, replaced with the result once merged.
+pub const MERGE_PANEL_ID: &str = "merge-panel";
+
+/// Why a pull request can't be merged yet, or `None` if it can.
+#[must_use]
+pub fn merge_blocker(pr: &PullRequest, checks: &[Check]) -> Option<&'static str> {
+    if pr.state != PrState::Open {
+        return Some("This pull request is not open.");
+    }
+    if pr.draft {
+        return Some("This pull request is still a draft.");
+    }
+
+    match pr.mergeable_state {
+        MergeableState::Conflicting => {
+            return Some("This branch has conflicts that must be resolved.");
+        }
+        MergeableState::Blocked => {
+            return Some("Required checks or reviews have not passed.");
+        }
+        MergeableState::Unknown => {
+            // Without the provider's verdict, don't merge past failing or
+            // unfinished checks
+            if checks.iter().any(|c| c.status == CheckStatus::Failure) {
+                return Some("Some checks have failed.");
+            }
+            if checks.iter().any(|c| c.status == CheckStatus::Pending) {
+                return Some("Some checks haven't finished yet.");
+            }
+        }
+        MergeableState::Clean | MergeableState::Unstable | MergeableState::Behind => {}
+    }
+
+    None
+}
+
+/// Render the merge panel with its mergeability summary.
+///
+/// The strategy buttons are only shown when [`merge_blocker`] finds nothing
+/// preventing the merge.
+#[must_use]
+pub fn render_merge_panel(pr: &PullRequest, checks: &[Check]) -> Containers {
+    let blocker = merge_blocker(pr, checks);
+    let api_url = format!(
+        "/api/pr/merge?owner={}&repo={}&number={}",
+        urlencoding::encode(&pr.owner),
+        urlencoding::encode(&pr.repo),
+        pr.number,
+    );
+
+    let failed = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Failure)
+        .count();
+    let pending = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Pending)
+        .count();
+
+    let (status, status_color) = match (blocker, pr.mergeable_state) {
+        (Some(reason), _) => (reason, "#cf222e"),
+        (None, MergeableState::Unstable) => {
+            ("Some checks that aren't required have failed.", "#bf8700")
+        }
+        (None, MergeableState::Behind) => (
+            "This branch is out-of-date with the base branch.",
+            "#bf8700",
+        ),
+        (None, _) => (
+            "This branch has no conflicts with the base branch.",
+            "#1a7f37",
+        ),
+    };
+
+    container! {
+        div
+            id=(MERGE_PANEL_ID)
+            border="1, #d0d7de"
+            border-radius=6
+            padding=16
+            margin-top=16
+            background=#ffffff
+            direction=column
+            gap=8
+        {
+            span font-weight=600 font-size=16 { "Merge pull request" }
+            span font-size=14 color=(status_color) { (status) }
+            @if !checks.is_empty() {
+                span font-size=13 color="#57606a" {
+                    (format!(
+                        "{} checks: {} passed, {failed} failed, {pending} pending",
+                        checks.len(),
+                        checks.len() - failed - pending,
+                    ))
+                }
+            }
+            @if blocker.is_none() {
+                form
+                    hx-post=(api_url)
+                    hx-swap="outerHTML"
+                    hx-target=(Selector::Id(MERGE_PANEL_ID.to_string()))
+                    direction=row
+                    gap=8
+                {
+                    (render_strategy_button(MergeStrategy::Merge))
+                    (render_strategy_button(MergeStrategy::Squash))
+                    (render_strategy_button(MergeStrategy::Rebase))
+                }
+            }
+        }
+    }
+}
+
+fn render_strategy_button(strategy: MergeStrategy) -> Containers {
+    let value = match strategy {
+        MergeStrategy::Merge => "merge",
+        MergeStrategy::Squash => "squash",
+        MergeStrategy::Rebase => "rebase",
+    };
+    container! {
+        button
+            type=submit
+            name="strategy"
+            value=(value)
+            background=#1a7f37
+            color=#ffffff
+            padding-x=16
+            padding-y=8
+            border-radius=6
+            font-size=14
+            font-weight=600
+            cursor=pointer
+        {
+            (strategy.label())
+        }
+    }
+}
+
+/// Render the confirmation shown in place of the merge panel.
+#[must_use]
+pub fn render_merged(strategy: MergeStrategy) -> Containers {
+    let message = match strategy {
+        MergeStrategy::Merge => "Pull request merged",
+        MergeStrategy::Squash => "Pull request squashed and merged",
+        MergeStrategy::Rebase => "Pull request rebased and merged",
+    };
+
+    container! {
+        div
+            id=(MERGE_PANEL_ID)
+            border="1, #d0d7de"
+            border-radius=6
+            padding=16
+            margin-top=16
+            background=#f6f8fa
+        {
+            span font-weight=600 font-size=14 color="#8250df" { (message) }
+        }
+    }
+}
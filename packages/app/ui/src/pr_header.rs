@@ -1,36 +1,56 @@
-use chadreview_pr_models::{PrState, PullRequest};
-use hyperchad::markdown::markdown_to_container;
+use chadreview_markdown::ReferenceProvider;
+use chadreview_pr_models::{Check, CheckStatus, Label, PrState, PullRequest, User};
 use hyperchad::router::Container;
 use hyperchad::template::container;
-use hyperchad_template::LayoutOverflow;
+use hyperchad::transformer::models::Selector;
+use hyperchad_template::{Containers, LayoutOverflow};
 
+use crate::markdown::{markdown_to_container, repo_content_markdown_to_container};
+
+/// Render the PR header. `viewed_files` is the number of changed files
+/// marked as viewed out of the total, shown as a progress bar.
 #[must_use]
-pub fn render_pr_header(pr: &PullRequest) -> Container {
-    let draft_badge = render_draft_badge(pr.draft);
-    let labels_section = render_labels(&pr.labels);
-    let people_section = render_people(&pr.assignees, &pr.reviewers);
+pub fn render_pr_header(
+    pr: &PullRequest,
+    checks: &[Check],
+    viewed_files: (usize, usize),
+) -> Container {
+    let labels_section = render_labels(&pr.owner, &pr.repo, pr.number, &pr.labels);
+    let people_section = render_people(pr);
 
     container! {
         header padding=20 {
-            (render_header_main(pr, &draft_badge))
+            (render_header_main(pr))
             (render_metadata(pr))
             (labels_section)
             (people_section)
-            (render_description(&pr.description))
+            (render_checks(checks))
+            (render_review_progress(viewed_files.0, viewed_files.1))
+            (render_description(pr))
         }
     }
     .into()
 }
 
-fn render_header_main(pr: &PullRequest, draft_badge: &Container) -> Container {
+/// Element ID of the title and state badges, replaced when the PR changes.
+pub const PR_HEADER_MAIN_ID: &str = "pr-header-main";
+/// Element ID of the description, replaced when the PR is edited.
+pub const PR_DESCRIPTION_ID: &str = "pr-description";
+
+/// Render the PR's title, number and state badges.
+#[must_use]
+pub fn render_header_main(pr: &PullRequest) -> Container {
+    let draft_badge = render_draft_badge(pr.draft);
     let (state_text, state_bg, state_color) = match pr.state {
         PrState::Open => ("Open", "#1a7f37", "#ffffff"),
         PrState::Closed => ("Closed", "#cf222e", "#ffffff"),
         PrState::Merged => ("Merged", "#8250df", "#ffffff"),
     };
 
+    let edit_url = pr_api_url("edit", &pr.owner, &pr.repo, pr.number);
+
     container! {
-        div margin-bottom=16 {
+        div id=(PR_HEADER_MAIN_ID) margin-bottom=16 {
             div direction=row align-items=center gap=12 margin-bottom=12 {
                 h1 font-size=32 font-weight=600 color="#24292f" margin=0 {
                     (pr.title)
@@ -38,6 +58,8 @@ fn render_header_main(pr: &PullRequest, draft_badge: &Container) -> Container {
                 span font-size=32 font-weight=300 color="#57606a" {
                     "#" (pr.number)
                 }
+                div flex=1 {}
+                (render_edit_button(&format!("{edit_url}&part=title&edit=true"), PR_HEADER_MAIN_ID))
             }
             div direction=row align-items=center gap=8 {
                 span
@@ -52,12 +74,124 @@ fn render_header_main(pr: &PullRequest, draft_badge: &Container) -> Container {
                     (state_text)
                 }
                 (draft_badge)
+                @if pr.state == PrState::Open {
+                    form
+                        hx-post=(edit_url)
+                        hx-target=(Selector::Id(PR_HEADER_MAIN_ID.to_string()))
+                        hx-swap="outerHTML"
+                    {
+                        button
+                            type=submit
+                            name="draft"
+                            value=(if pr.draft { "false" } else { "true" })
+                            background="#f6f8fa"
+                            border="1px solid #d0d7de"
+                            color="#24292f"
+                            padding-x=12
+                            padding-y=4
+                            border-radius=6
+                            font-size=12
+                            cursor=pointer
+                        {
+                            @if pr.draft { "Ready for review" } @else { "Convert to draft" }
+                        }
+                    }
+                }
             }
         }
     }
     .into()
 }
 
+/// Render a form editing the PR's title in place of its header.
+#[must_use]
+pub fn render_title_form(pr: &PullRequest) -> Container {
+    let edit_url = pr_api_url("edit", &pr.owner, &pr.repo, pr.number);
+
+    container! {
+        div id=(PR_HEADER_MAIN_ID) margin-bottom=16 {
+            form
+                hx-post=(edit_url)
+                hx-target=(Selector::Id(PR_HEADER_MAIN_ID.to_string()))
+                hx-swap="outerHTML"
+                direction=row
+                align-items=center
+                gap=8
+            {
+                input
+                    type=text
+                    name="title"
+                    value=(pr.title)
+                    flex=1
+                    padding=8
+                    border="1px solid #d0d7de"
+                    border-radius=6
+                    font-size=20;
+                (render_save_button())
+                (render_cancel_button(&format!("{edit_url}&part=title"), PR_HEADER_MAIN_ID))
+            }
+        }
+    }
+    .into()
+}
+
+fn render_edit_button(url: &str, target_id: &str) -> Container {
+    container! {
+        button
+            type=button
+            color="#0969da"
+            padding-x=8
+            padding-y=4
+            font-size=12
+            cursor=pointer
+            hx-get=(url)
+            hx-target=(Selector::Id(target_id.to_string()))
+            hx-swap="outerHTML"
+        {
+            "Edit"
+        }
+    }
+    .into()
+}
+
+fn render_save_button() -> Container {
+    container! {
+        button
+            type=submit
+            background="#1a7f37"
+            color="#ffffff"
+            padding-x=16
+            padding-y=8
+            border-radius=6
+            font-weight=600
+            cursor=pointer
+        {
+            "Save"
+        }
+    }
+    .into()
+}
+
+/// A button restoring the section at `target_id` by fetching it from `url`.
+fn render_cancel_button(url: &str, target_id: &str) -> Container {
+    container! {
+        button
+            type=button
+            color="#57606a"
+            padding-x=16
+            padding-y=8
+            border-radius=6
+            cursor=pointer
+            hx-get=(url)
+            hx-target=(Selector::Id(target_id.to_string()))
+            hx-swap="outerHTML"
+        {
+            "Cancel"
+        }
+    }
+    .into()
+}
+
 fn render_draft_badge(draft: bool) -> Container {
     if draft {
         container! {
@@ -135,16 +269,41 @@ fn render_metadata(pr: &PullRequest) -> Container {
     .into()
 }
 
-fn render_labels(labels: &[chadreview_pr_models::Label]) -> Container {
-    if labels.is_empty() {
-        return container! { div {} }.into();
-    }
+/// Element ID of the labels section, replaced when labels change.
+pub const PR_LABELS_ID: &str = "pr-labels";
+/// Element ID of the assignees section, replaced when assignees change.
+pub const PR_ASSIGNEES_ID: &str = "pr-assignees";
+/// Element ID of the reviewers section, replaced when review requests change.
+pub const PR_REVIEWERS_ID: &str = "pr-reviewers";
+
+fn pr_api_url(path: &str, owner: &str, repo: &str, number: u64) -> String {
+    format!(
+        "/api/pr/{path}?owner={}&repo={}&number={number}",
+        urlencoding::encode(owner),
+        urlencoding::encode(repo),
+    )
+}
+
+/// Render the PR's labels as removable chips with a form to add more.
+#[must_use]
+pub fn render_labels(owner: &str, repo: &str, number: u64, labels: &[Label]) -> Container {
+    let api_url = pr_api_url("labels", owner, repo, number);
 
     container! {
-        div direction=row align-items=center gap=8 margin-bottom=16 {
+        div
+            id=(PR_LABELS_ID)
+            direction=row
+            align-items=center
+            gap=8
+            margin-bottom=16
+            overflow-x=(LayoutOverflow::Wrap { grid: false })
+        {
             span color="#57606a" font-weight=600 { "Labels:" }
             @for label in labels {
-                span
+                div
+                    direction=row
+                    align-items=center
+                    gap=4
                     padding-y=4
                     padding-x=10
                     border-radius=12
@@ -154,85 +313,312 @@ fn render_labels(labels: &[chadreview_pr_models::Label]) -> Container {
                     color="#ffffff"
                 {
                     (label.name)
+                    (render_remove_button(
+                        &format!("{api_url}&name={}", urlencoding::encode(&label.name)),
+                        PR_LABELS_ID,
+                        "#ffffff",
+                    ))
                 }
             }
+            (render_add_form(&api_url, PR_LABELS_ID, "Add labels..."))
         }
     }
     .into()
 }
 
-fn render_people(
-    assignees: &[chadreview_pr_models::User],
-    reviewers: &[chadreview_pr_models::User],
-) -> Container {
-    if assignees.is_empty() && reviewers.is_empty() {
-        return container! { div {} }.into();
+fn render_people(pr: &PullRequest) -> Container {
+    container! {
+        div
+            direction=row
+            gap=24
+            margin-bottom=16
+            overflow-x=(LayoutOverflow::Wrap { grid: false })
+        {
+            (render_assignees(&pr.owner, &pr.repo, pr.number, &pr.assignees))
+            (render_reviewers(&pr.owner, &pr.repo, pr.number, &pr.reviewers))
+        }
     }
+    .into()
+}
+
+/// Render the PR's assignees as removable chips with a form to add more.
+#[must_use]
+pub fn render_assignees(owner: &str, repo: &str, number: u64, assignees: &[User]) -> Container {
+    render_users(
+        "Assignees:",
+        &pr_api_url("assignees", owner, repo, number),
+        PR_ASSIGNEES_ID,
+        assignees,
+    )
+}
 
-    let assignees_section = render_assignees(assignees);
-    let reviewers_section = render_reviewers(reviewers);
+/// Render the PR's requested reviewers as removable chips with a form to
+/// request more.
+#[must_use]
+pub fn render_reviewers(owner: &str, repo: &str, number: u64, reviewers: &[User]) -> Container {
+    render_users(
+        "Reviewers:",
+        &pr_api_url("reviewers", owner, repo, number),
+        PR_REVIEWERS_ID,
+        reviewers,
+    )
+}
 
+fn render_users(title: &str, api_url: &str, id: &str, users: &[User]) -> Container {
     container! {
-        div direction=row gap=24 margin-bottom=16 {
-            (assignees_section)
-            (reviewers_section)
+        div id=(id) direction=row align-items=center gap=8 {
+            span color="#57606a" font-weight=600 { (title) }
+            @for user in users {
+                div direction=row align-items=center gap=4 {
+                    image src=(user.avatar_url) width=24 height=24 border-radius=12 {}
+                    anchor href=(user.html_url) color="#0969da" font-weight=600 {
+                        (user.username)
+                    }
+                    (render_remove_button(
+                        &format!("{api_url}&name={}", urlencoding::encode(&user.username)),
+                        id,
+                        "#57606a",
+                    ))
+                }
+            }
+            (render_add_form(api_url, id, "Add usernames..."))
         }
     }
     .into()
 }
 
-fn render_assignees(assignees: &[chadreview_pr_models::User]) -> Container {
-    if assignees.is_empty() {
+fn render_remove_button(url: &str, target_id: &str, color: &str) -> Container {
+    container! {
+        button
+            type=button
+            color=(color)
+            cursor=pointer
+            font-size=12
+            hx-delete=(url)
+            hx-target=(Selector::Id(target_id.to_string()))
+            hx-swap="outerHTML"
+        {
+            "×"
+        }
+    }
+    .into()
+}
+
+/// A one-line form submitting comma-separated `names` to `url`.
+fn render_add_form(url: &str, target_id: &str, placeholder: &str) -> Container {
+    container! {
+        form
+            hx-post=(url)
+            hx-target=(Selector::Id(target_id.to_string()))
+            hx-swap="outerHTML"
+            direction=row
+            gap=4
+        {
+            input
+                type=text
+                name="names"
+                placeholder=(placeholder)
+                padding-x=6
+                padding-y=2
+                border="1px solid #d0d7de"
+                border-radius=6
+                font-size=12
+                width=120;
+            button
+                type=submit
+                color="#0969da"
+                padding-x=6
+                cursor=pointer
+                font-size=12
+                font-weight=500
+            {
+                "+"
+            }
+        }
+    }
+    .into()
+}
+
+const fn check_status_color(status: CheckStatus) -> &'static str {
+    match status {
+        CheckStatus::Success => "#1a7f37",
+        CheckStatus::Failure => "#cf222e",
+        CheckStatus::Pending => "#bf8700",
+        CheckStatus::Neutral | CheckStatus::Skipped => "#57606a",
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn render_review_progress(viewed: usize, total: usize) -> Container {
+    if total == 0 {
         return container! { div {} }.into();
     }
 
+    let fraction = viewed as f32 / total as f32;
+    let bar_color = if viewed == total {
+        "#1a7f37"
+    } else {
+        "#0969da"
+    };
+
     container! {
-        div direction=row align-items=center gap=8 {
-            span color="#57606a" font-weight=600 { "Assignees:" }
-            @for assignee in assignees {
-                div direction=row align-items=center gap=4 {
-                    image src=(assignee.avatar_url) width=24 height=24 border-radius=12 {}
-                    anchor href=(assignee.html_url) color="#0969da" font-weight=600 {
-                        (assignee.username)
-                    }
-                }
+        div direction=row align-items=center gap=12 margin-bottom=16 {
+            div flex=1 height=8 border-radius=4 background="#eaeef2" {
+                div
+                    width=calc(100% * fraction)
+                    height=8
+                    border-radius=4
+                    background=(bar_color)
+                {}
+            }
+            span font-size=13 color="#57606a" {
+                (format!("{viewed} / {total} files viewed"))
             }
         }
     }
     .into()
 }
 
-fn render_reviewers(reviewers: &[chadreview_pr_models::User]) -> Container {
-    if reviewers.is_empty() {
+fn render_checks(checks: &[Check]) -> Container {
+    if checks.is_empty() {
         return container! { div {} }.into();
     }
 
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let failed = count(CheckStatus::Failure);
+    let pending = count(CheckStatus::Pending);
+    let passed = count(CheckStatus::Success);
+
+    let (summary, summary_color) = if failed > 0 {
+        (
+            format!("{failed} of {} checks failed", checks.len()),
+            "#cf222e",
+        )
+    } else if pending > 0 {
+        (
+            format!("{pending} of {} checks pending", checks.len()),
+            "#bf8700",
+        )
+    } else {
+        (format!("All checks passed ({passed} passed)"), "#1a7f37")
+    };
+
     container! {
-        div direction=row align-items=center gap=8 {
-            span color="#57606a" font-weight=600 { "Reviewers:" }
-            @for reviewer in reviewers {
-                div direction=row align-items=center gap=4 {
-                    image src=(reviewer.avatar_url) width=24 height=24 border-radius=12 {}
-                    anchor href=(reviewer.html_url) color="#0969da" font-weight=600 {
-                        (reviewer.username)
+        div
+            border="1px solid #d0d7de"
+            border-radius=6
+            margin-bottom=16
+        {
+            div padding=12 background="#f6f8fa" border-bottom="1px solid #d0d7de" {
+                span font-weight=600 font-size=14 color=(summary_color) { (summary) }
+            }
+            @for check in checks {
+                div
+                    direction=row
+                    align-items=center
+                    gap=8
+                    padding-x=12
+                    padding-y=6
+                    font-size=13
+                {
+                    span font-weight=600 color=(check_status_color(check.status)) {
+                        (check.status.label())
+                    }
+                    span font-weight=600 color="#24292f" { (check.name) }
+                    @if let Some(description) = &check.description {
+                        span color="#57606a" { (description) }
                     }
+                    div flex=1 {}
+                    @if let Some(url) = &check.details_url {
+                        anchor href=(url) color="#0969da" { "Details" }
+                    }
+                }
+            }
+        }
+    }
+    .into()
+}
+
+/// Render the PR's description as markdown.
+#[must_use]
+pub fn render_description(pr: &PullRequest) -> Container {
+    let edit_url = pr_api_url("edit", &pr.owner, &pr.repo, pr.number);
+
+    container! {
+        section id=(PR_DESCRIPTION_ID) margin-top=20 padding-top=20 border-top="1px solid #d0d7de" {
+            div direction=row align-items=center margin-bottom=12 {
+                h3 flex=1 font-size=16 font-weight=600 color="#24292f" {
+                    "Description"
                 }
+                (render_edit_button(&format!("{edit_url}&part=description&edit=true"), PR_DESCRIPTION_ID))
+            }
+            div color="#24292f" {
+                (description_markdown(pr, &pr.description))
             }
         }
     }
     .into()
 }
 
-fn render_description(description: &str) -> Container {
+/// Render a form editing the PR's description, starting from `body`. When
+/// `preview` is set, the body's rendered markdown is shown below it.
+#[must_use]
+pub fn render_description_form(pr: &PullRequest, body: &str, preview: bool) -> Container {
+    let edit_url = pr_api_url("edit", &pr.owner, &pr.repo, pr.number);
+
     container! {
-        section margin-top=20 padding-top=20 border-top="1px solid #d0d7de" {
+        section id=(PR_DESCRIPTION_ID) margin-top=20 padding-top=20 border-top="1px solid #d0d7de" {
             h3 font-size=16 font-weight=600 color="#24292f" margin-bottom=12 {
                 "Description"
             }
-            div color="#24292f" {
-                (markdown_to_container(description))
+            form
+                hx-post=(edit_url)
+                hx-target=(Selector::Id(PR_DESCRIPTION_ID.to_string()))
+                hx-swap="outerHTML"
+                direction=column
+                gap=8
+            {
+                textarea name="body" height=200 { (body) }
+                div direction=row gap=8 {
+                    (render_save_button())
+                    button
+                        type=submit
+                        name="preview"
+                        value="true"
+                        color="#24292f"
+                        border="1px solid #d0d7de"
+                        padding-x=16
+                        padding-y=8
+                        border-radius=6
+                        cursor=pointer
+                    {
+                        "Preview"
+                    }
+                    (render_cancel_button(&format!("{edit_url}&part=description"), PR_DESCRIPTION_ID))
+                }
+            }
+            @if preview {
+                div
+                    margin-top=12
+                    padding=12
+                    border="1px solid #d0d7de"
+                    border-radius=6
+                    color="#24292f"
+                {
+                    (description_markdown(pr, body))
+                }
             }
         }
     }
     .into()
 }
+
+/// Render `body` as markdown, linking references to the PR's repository.
+fn description_markdown(pr: &PullRequest, body: &str) -> Containers {
+    ReferenceProvider::from_name(&pr.provider).map_or_else(
+        || markdown_to_container(body),
+        |provider| {
+            repo_content_markdown_to_container(body, provider, &pr.owner, &pr.repo, &pr.head_sha)
+        },
+    )
+}
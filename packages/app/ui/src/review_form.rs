@@ -0,0 +1,264 @@
+use chadreview_diff_models::LineNumber;
+use chadreview_pr_models::{CommentType, PendingComment, PendingReview, Review, ReviewVerdict};
+use hyperchad::template::{Containers, container};
+use hyperchad::transformer::models::Selector;
+
+/// Element ID of the review submission section, replaced with the result
+/// once a review is submitted.
+pub const REVIEW_FORM_ID: &str = "review-form";
+
+#[must_use]
+pub fn pending_comment_id(id: u64) -> String {
+    format!("pending-comment-{id}")
+}
+
+fn review_api_url(action: &str, owner: &str, repo: &str, number: u64) -> String {
+    format!(
+        "/api/pr/review{action}?owner={}&repo={}&number={number}",
+        urlencoding::encode(owner),
+        urlencoding::encode(repo),
+    )
+}
+
+/// Render the "Finish your review" section.
+///
+/// Without a pending review, the verdict is submitted on its own and a
+/// review can be started to batch comments. With one, its comments are
+/// listed and published together with the verdict.
+#[must_use]
+pub fn render_review_form(
+    owner: &str,
+    repo: &str,
+    number: u64,
+    pending: Option<&PendingReview>,
+) -> Containers {
+    let api_url = review_api_url("", owner, repo, number);
+    let title = pending.map_or_else(
+        || "Finish your review".to_string(),
+        |review| format!("Pending review ({} comments)", review.comments.len()),
+    );
+
+    container! {
+        div
+            id=(REVIEW_FORM_ID)
+            border="1, #d0d7de"
+            border-radius=6
+            padding=16
+            margin-top=16
+            background=#ffffff
+            direction=column
+            gap=8
+        {
+            div direction=row align-items=center gap=8 {
+                span font-weight=600 font-size=16 flex=1 { (title) }
+                @if pending.is_some() {
+                    (render_review_action_button("/discard", "Discard review", "#cf222e", owner, repo, number))
+                } @else {
+                    (render_review_action_button("/start", "Start a review", "#0969da", owner, repo, number))
+                }
+            }
+            @if let Some(review) = pending {
+                @if review.comments.is_empty() {
+                    span font-size=14 color="#57606a" {
+                        "New line and file comments will be added to this review until it is submitted."
+                    }
+                }
+                @for comment in &review.comments {
+                    (render_pending_comment(comment, owner, repo, number))
+                }
+            }
+            form
+                hx-post=(api_url)
+                hx-swap="outerHTML"
+                hx-target=(Selector::Id(REVIEW_FORM_ID.to_string()))
+                direction=column
+                gap=8
+            {
+                textarea
+                    name="body"
+                    placeholder="Leave a summary (required to request changes)..."
+                    height=80
+                    border="1, #d0d7de"
+                    border-radius=6
+                    padding=8
+                    font-size=14;
+                div direction=row gap=8 {
+                    (render_verdict_button(ReviewVerdict::Comment, "#f6f8fa", "#24292f"))
+                    (render_verdict_button(ReviewVerdict::Approve, "#1a7f37", "#ffffff"))
+                    (render_verdict_button(ReviewVerdict::RequestChanges, "#cf222e", "#ffffff"))
+                }
+            }
+        }
+    }
+}
+
+fn render_review_action_button(
+    action: &str,
+    label: &str,
+    color: &str,
+    owner: &str,
+    repo: &str,
+    number: u64,
+) -> Containers {
+    container! {
+        form
+            hx-post=(review_api_url(action, owner, repo, number))
+            hx-swap="outerHTML"
+            hx-target=(Selector::Id(REVIEW_FORM_ID.to_string()))
+        {
+            button
+                type=submit
+                color=(color)
+                padding-x=8
+                padding-y=4
+                cursor=pointer
+                font-size=12
+                font-weight=500
+            {
+                (label)
+            }
+        }
+    }
+}
+
+fn render_verdict_button(verdict: ReviewVerdict, background: &str, color: &str) -> Containers {
+    let value = match verdict {
+        ReviewVerdict::Approve => "approve",
+        ReviewVerdict::RequestChanges => "request_changes",
+        ReviewVerdict::Comment => "comment",
+    };
+
+    container! {
+        button
+            type=submit
+            name="verdict"
+            value=(value)
+            background=(background)
+            color=(color)
+            border="1, #d0d7de"
+            padding-x=16
+            padding-y=8
+            border-radius=6
+            font-size=14
+            font-weight=600
+            cursor=pointer
+        {
+            (verdict.label())
+        }
+    }
+}
+
+/// Render a just-added pending comment in place of the diff's comment form.
+///
+/// It has the [`pending_comment_id`] so it can be removed when the comment
+/// is deleted from the review.
+#[must_use]
+pub fn render_inline_pending_comment(
+    pending: &PendingComment,
+    owner: &str,
+    repo: &str,
+    number: u64,
+) -> Containers {
+    container! {
+        div id=(pending_comment_id(pending.id)) {
+            (render_pending_comment(pending, owner, repo, number))
+        }
+    }
+}
+
+/// Render a comment that will be published when the pending review is
+/// submitted.
+#[must_use]
+pub fn render_pending_comment(
+    pending: &PendingComment,
+    owner: &str,
+    repo: &str,
+    number: u64,
+) -> Containers {
+    let location = match &pending.comment.comment_type {
+        CommentType::LineLevelComment { path, line, .. } => match line {
+            LineNumber::New { line } => format!("{path}:{line}"),
+            LineNumber::Old { line } => format!("{path}:{line} (old)"),
+        },
+        CommentType::FileLevelComment { path } => path.clone(),
+        CommentType::General | CommentType::Reply { .. } => String::new(),
+    };
+    let delete_url = format!(
+        "{}&id={}",
+        review_api_url("/pending", owner, repo, number),
+        pending.id
+    );
+
+    container! {
+        div
+            class="pending-comment"
+            padding=12
+            background="#fff8c5"
+            border="1, #d4a72c"
+            border-radius=6
+            direction=column
+            gap=4
+        {
+            div direction=row align-items=center gap=8 {
+                span
+                    padding-x=6
+                    padding-y=2
+                    border-radius=12
+                    background="#d4a72c"
+                    color="#ffffff"
+                    font-size=11
+                    font-weight=600
+                {
+                    "Pending"
+                }
+                span font-family="monospace" font-size=12 color="#57606a" flex=1 { (location) }
+                form
+                    hx-delete=(delete_url)
+                    hx-swap="outerHTML"
+                    hx-target=(Selector::Id(REVIEW_FORM_ID.to_string()))
+                {
+                    button
+                        type=submit
+                        color=#cf222e
+                        padding-x=8
+                        padding-y=4
+                        cursor=pointer
+                        font-size=12
+                    {
+                        "Delete"
+                    }
+                }
+            }
+            span font-size=14 color="#24292f" { (pending.comment.body) }
+        }
+    }
+}
+
+/// Render the confirmation shown in place of the review form.
+#[must_use]
+pub fn render_review_submitted(review: &Review) -> Containers {
+    let (message, color) = match review.verdict {
+        ReviewVerdict::Approve => ("Approved these changes", "#1a7f37"),
+        ReviewVerdict::RequestChanges => ("Requested changes", "#cf222e"),
+        ReviewVerdict::Comment => ("Submitted review comments", "#57606a"),
+    };
+
+    container! {
+        div
+            id=(REVIEW_FORM_ID)
+            border="1, #d0d7de"
+            border-radius=6
+            padding=16
+            margin-top=16
+            background=#f6f8fa
+            direction=row
+            align-items=center
+            gap=8
+        {
+            span font-weight=600 font-size=14 color=(color) { (message) }
+            span font-size=14 color="#57606a" {
+                (format!("as {}", review.author.username))
+            }
+        }
+    }
+}
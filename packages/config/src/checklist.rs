@@ -0,0 +1,132 @@
+//! Review checklists.
+//!
+//! A repository can keep its checklist in `.chadreview/checklist.md` as a
+//! markdown list, so it is versioned with the code it applies to:
+//!
+//! ```markdown
+//! # Before merging
+//!
+//! - [ ] Tests added
+//! - [ ] Docs updated
+//! ```
+//!
+//! Only the list items are used; headings and other text are ignored.
+//! Without the file, the `[review] checklist` config setting is used.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::{Config, ConfigError};
+
+/// Path of the per-repository checklist, relative to the repository root.
+pub const CHECKLIST_PATH: &str = ".chadreview/checklist.md";
+
+/// Load the checklist for reviews of `repo_path`: its
+/// `.chadreview/checklist.md` if there is one, otherwise the one in
+/// `config`.
+///
+/// # Errors
+///
+/// Returns an error if the checklist file exists but cannot be read.
+pub fn load_checklist(
+    repo_path: Option<&Path>,
+    config: &Config,
+) -> Result<Vec<String>, ConfigError> {
+    if let Some(repo_path) = repo_path {
+        match fs::read_to_string(repo_path.join(CHECKLIST_PATH)) {
+            Ok(markdown) => return Ok(parse_checklist(&markdown)),
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(config.review.checklist.clone())
+}
+
+/// Get the items of the markdown lists in `markdown`, in order, without
+/// their task list checkboxes. Duplicate items are only listed once.
+#[must_use]
+pub fn parse_checklist(markdown: &str) -> Vec<String> {
+    let mut items: Vec<String> = vec![];
+
+    for line in markdown.lines() {
+        let Some(item) = list_item(line.trim()) else {
+            continue;
+        };
+        if !item.is_empty() && !items.iter().any(|existing| existing == item) {
+            items.push(item.to_string());
+        }
+    }
+
+    items
+}
+
+/// Get the text of a list item line, if `line` is one.
+fn list_item(line: &str) -> Option<&str> {
+    let rest = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))
+        .or_else(|| {
+            let (number, rest) = line.split_once(". ")?;
+            (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit())).then_some(rest)
+        })?;
+
+    let rest = rest.trim_start();
+    let rest = ["[ ]", "[x]", "[X]"]
+        .iter()
+        .find_map(|checkbox| rest.strip_prefix(checkbox))
+        .unwrap_or(rest);
+
+    Some(rest.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checklist_items() {
+        let items = parse_checklist(
+            "# Before merging\n\
+             \n\
+             Check these off:\n\
+             \n\
+             - [ ] Tests added\n\
+             * [x] Docs updated\n\
+             1. Changelog entry\n\
+             - Tests added\n\
+             -\n",
+        );
+
+        assert_eq!(
+            items,
+            vec!["Tests added", "Docs updated", "Changelog entry"]
+        );
+    }
+
+    #[test]
+    fn test_checklist_file_takes_precedence_over_config() {
+        let repo =
+            std::env::temp_dir().join(format!("chadreview-checklist-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&repo);
+        let mut config = Config::default();
+        config.review.checklist = vec!["From config".to_string()];
+
+        assert_eq!(
+            load_checklist(Some(&repo), &config).unwrap(),
+            vec!["From config"]
+        );
+
+        fs::create_dir_all(repo.join(".chadreview")).unwrap();
+        fs::write(repo.join(CHECKLIST_PATH), "- From file\n").unwrap();
+        assert_eq!(
+            load_checklist(Some(&repo), &config).unwrap(),
+            vec!["From file"]
+        );
+        assert_eq!(load_checklist(None, &config).unwrap(), vec!["From config"]);
+
+        let _ = fs::remove_dir_all(&repo);
+    }
+}
@@ -0,0 +1,271 @@
+//! Generated files, collapsed in diffs.
+//!
+//! Lockfiles, snapshots and minified assets are rarely worth reading line by
+//! line, so diffs show them collapsed with a "Show anyway" button. Which
+//! files count as generated is decided by, in increasing precedence:
+//!
+//! 1. [`DEFAULT_GENERATED_PATTERNS`]
+//! 2. The `[diff] generated` config setting
+//! 3. `linguist-generated` attributes in the repository's `.gitattributes`
+//! 4. The repository's `.chadreview-ignore`, one pattern per line
+//!
+//! Patterns use `.gitignore` syntax: a pattern without a slash matches a
+//! file or directory name at any depth, `*` and `?` match within a path
+//! component, `**` matches across them, a trailing `/` only matches
+//! directories and a leading `!` marks matching files as not generated. The
+//! last matching pattern wins.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::{Config, ConfigError};
+
+/// Path of the per-repository generated file patterns, relative to the
+/// repository root.
+pub const GENERATED_IGNORE_PATH: &str = ".chadreview-ignore";
+
+/// Path of the git attributes file, relative to the repository root.
+const GITATTRIBUTES_PATH: &str = ".gitattributes";
+
+/// Files treated as generated unless configured otherwise.
+pub const DEFAULT_GENERATED_PATTERNS: &[&str] = &[
+    "*.lock",
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "*.snap",
+    "*.min.js",
+    "*.min.css",
+];
+
+/// A parsed generated file pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    glob: Vec<char>,
+    /// Matched against the whole path rather than any component.
+    anchored: bool,
+    dir_only: bool,
+    negated: bool,
+}
+
+impl Pattern {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, line) = line
+            .strip_prefix('!')
+            .map_or((false, line), |line| (true, line));
+        let dir_only = line.ends_with('/');
+        let line = line.trim_end_matches('/');
+        let anchored = line.contains('/');
+        let glob: Vec<char> = line.trim_start_matches('/').chars().collect();
+
+        (!glob.is_empty()).then_some(Self {
+            glob,
+            anchored,
+            dir_only,
+            negated,
+        })
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        let components: Vec<&str> = path.split('/').collect();
+        // Directory patterns can't match the file itself
+        let candidates = if self.dir_only {
+            components.len() - 1
+        } else {
+            components.len()
+        };
+
+        if self.anchored {
+            (1..=candidates).any(|n| {
+                let prefix: Vec<char> = components[..n].join("/").chars().collect();
+                glob_match(&self.glob, &prefix)
+            })
+        } else {
+            components[..candidates].iter().any(|component| {
+                let component: Vec<char> = component.chars().collect();
+                glob_match(&self.glob, &component)
+            })
+        }
+    }
+}
+
+/// Match `text` against a glob where `*` and `?` stop at `/` and `**`
+/// doesn't.
+fn glob_match(glob: &[char], text: &[char]) -> bool {
+    match glob.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => {
+            if let Some(('*', rest)) = rest.split_first() {
+                // `**/` also matches no directories at all
+                if let Some(('/', after)) = rest.split_first()
+                    && glob_match(after, text)
+                {
+                    return true;
+                }
+                return (0..=text.len()).any(|i| glob_match(rest, &text[i..]));
+            }
+            for i in 0..=text.len() {
+                if glob_match(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some(('?', rest)) => {
+            matches!(text.split_first(), Some((c, text)) if *c != '/' && glob_match(rest, text))
+        }
+        Some((c, rest)) => {
+            matches!(text.split_first(), Some((t, text)) if t == c && glob_match(rest, text))
+        }
+    }
+}
+
+/// Patterns deciding which files of a diff are generated.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeneratedFiles {
+    patterns: Vec<Pattern>,
+}
+
+impl GeneratedFiles {
+    /// Build a matcher from patterns in `.gitignore` syntax, later ones
+    /// taking precedence. Blank lines and `#` comments are skipped.
+    #[must_use]
+    pub fn new<S: AsRef<str>>(patterns: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            patterns: patterns
+                .into_iter()
+                .filter_map(|pattern| Pattern::parse(pattern.as_ref()))
+                .collect(),
+        }
+    }
+
+    /// Load the patterns for diffs of `repo_path`: the defaults, those in
+    /// `config`, and the repository's `.gitattributes` and
+    /// `.chadreview-ignore` when viewing a local repository.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the repository's files exists but cannot
+    /// be read.
+    pub fn load(repo_path: Option<&Path>, config: &Config) -> Result<Self, ConfigError> {
+        let mut patterns: Vec<String> = DEFAULT_GENERATED_PATTERNS
+            .iter()
+            .map(ToString::to_string)
+            .chain(config.diff.generated.iter().cloned())
+            .collect();
+
+        if let Some(repo_path) = repo_path {
+            if let Some(attributes) = read_optional(&repo_path.join(GITATTRIBUTES_PATH))? {
+                patterns.extend(parse_gitattributes(&attributes));
+            }
+            if let Some(ignore) = read_optional(&repo_path.join(GENERATED_IGNORE_PATH))? {
+                patterns.extend(ignore.lines().map(ToString::to_string));
+            }
+        }
+
+        Ok(Self::new(patterns))
+    }
+
+    /// Whether the file at `path`, relative to the repository root, is
+    /// generated.
+    #[must_use]
+    pub fn is_generated(&self, path: &str) -> bool {
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| pattern.matches(path))
+            .is_some_and(|pattern| !pattern.negated)
+    }
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>, ConfigError> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Get the patterns `.gitattributes` marks `linguist-generated`, negated
+/// for the ones it marks as not generated.
+fn parse_gitattributes(attributes: &str) -> Vec<String> {
+    attributes
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pattern = fields.next().filter(|p| !p.starts_with('#'))?;
+            fields.find_map(|attribute| match attribute {
+                "linguist-generated" | "linguist-generated=true" => Some(pattern.to_string()),
+                "-linguist-generated" | "linguist-generated=false" => Some(format!("!{pattern}")),
+                _ => None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_patterns() {
+        let generated = GeneratedFiles::new(DEFAULT_GENERATED_PATTERNS);
+
+        assert!(generated.is_generated("Cargo.lock"));
+        assert!(generated.is_generated("web/package-lock.json"));
+        assert!(generated.is_generated("tests/snapshots/render.snap"));
+        assert!(generated.is_generated("static/app.min.js"));
+        assert!(!generated.is_generated("src/lib.rs"));
+        assert!(!generated.is_generated("static/app.js"));
+    }
+
+    #[test]
+    fn test_pattern_syntax() {
+        let generated = GeneratedFiles::new([
+            "# Generated code",
+            "/gen/",
+            "docs/**/*.html",
+            "dist",
+            "*.pb.?",
+            "!dist/keep.js",
+        ]);
+
+        assert!(generated.is_generated("gen/api.rs"));
+        assert!(!generated.is_generated("src/gen/api.rs"));
+        assert!(!generated.is_generated("gen"));
+        assert!(generated.is_generated("docs/index.html"));
+        assert!(generated.is_generated("docs/api/v1/index.html"));
+        assert!(generated.is_generated("web/dist/app.js"));
+        assert!(generated.is_generated("proto/user.pb.c"));
+        assert!(!generated.is_generated("proto/user.pb.rs"));
+        assert!(!generated.is_generated("dist/keep.js"));
+    }
+
+    #[test]
+    fn test_parse_gitattributes() {
+        let patterns = parse_gitattributes(
+            "*.rs text\n\
+             # comment linguist-generated\n\
+             api/*.ts linguist-generated=true\n\
+             Cargo.lock -linguist-generated\n",
+        );
+
+        assert_eq!(patterns, vec!["api/*.ts", "!Cargo.lock"]);
+        let generated = GeneratedFiles::new(
+            DEFAULT_GENERATED_PATTERNS
+                .iter()
+                .copied()
+                .chain(patterns.iter().map(String::as_str)),
+        );
+        assert!(generated.is_generated("api/client.ts"));
+        assert!(!generated.is_generated("Cargo.lock"));
+    }
+}
@@ -0,0 +1,529 @@
+#![cfg_attr(feature = "fail-on-warnings", deny(warnings))]
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![allow(clippy::multiple_crate_versions)]
+
+//! Configuration file support for `ChadReview`.
+//!
+//! Settings are read from `<config dir>/chadreview/chadreview.toml`, overlaid
+//! with the repository's `.chadreview/config.toml` when viewing a local
+//! repository. Environment variables that predate the config file (such as
+//! `GITHUB_TOKEN` and `RELAY_URL`) still take precedence over both.
+//!
+//! Saved replies live in their own file next to it; see [`SavedReplies`].
+//! Recently opened repositories are remembered in the data directory; see
+//! [`RecentRepos`]. The review checklist can also come from the repository's
+//! `.chadreview/checklist.md`; see [`load_checklist`]. Generated files are
+//! matched with [`GeneratedFiles`].
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+mod checklist;
+mod generated;
+mod recent_repos;
+mod saved_replies;
+
+pub use checklist::{CHECKLIST_PATH, load_checklist, parse_checklist};
+pub use generated::{DEFAULT_GENERATED_PATTERNS, GENERATED_IGNORE_PATH, GeneratedFiles};
+pub use recent_repos::{MAX_RECENT_REPOS, RecentRepos};
+pub use saved_replies::{FILE_PLACEHOLDER, LINE_PLACEHOLDER, SavedReplies, SavedReply};
+
+/// Path of the per-repository config file, relative to the repository root.
+pub const REPO_CONFIG_PATH: &str = ".chadreview/config.toml";
+
+/// Path of the global config file, relative to the config directory.
+const GLOBAL_CONFIG_PATH: &str = "chadreview/chadreview.toml";
+
+/// Environment variables that override config file settings.
+pub const GITHUB_TOKEN_ENV: &str = "GITHUB_TOKEN";
+pub const GITEA_URL_ENV: &str = "GITEA_URL";
+pub const GITEA_TOKEN_ENV: &str = "GITEA_TOKEN";
+pub const RELAY_URL_ENV: &str = "RELAY_URL";
+pub const RELAY_TOKEN_ENV: &str = "RELAY_TOKEN";
+pub const GITHUB_WEBHOOK_SECRET_ENV: &str = "GITHUB_WEBHOOK_SECRET";
+pub const OPENCODE_BINARY_ENV: &str = "OPENCODE_BINARY";
+
+const ENV_OVERRIDES: &[&str] = &[
+    GITHUB_TOKEN_ENV,
+    GITEA_URL_ENV,
+    GITEA_TOKEN_ENV,
+    RELAY_URL_ENV,
+    RELAY_TOKEN_ENV,
+    GITHUB_WEBHOOK_SECRET_ENV,
+    OPENCODE_BINARY_ENV,
+];
+
+/// Errors that can occur when loading or saving config.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// Failed to read or write a config file.
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Failed to parse a config file.
+    #[error("Failed to parse config: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    /// Failed to serialize the config.
+    #[error("Failed to serialize config: {0}")]
+    Serialize(#[from] toml::ser::Error),
+
+    /// Could not determine the config directory.
+    #[error("Could not determine XDG config directory")]
+    NoConfigDir,
+
+    /// Could not determine the data directory.
+    #[error("Could not determine XDG data directory")]
+    NoDataDir,
+}
+
+/// Parsed `chadreview.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub ai: AiConfig,
+    pub appearance: AppearanceConfig,
+    pub comments: CommentsConfig,
+    pub diff: DiffConfig,
+    pub relay: RelayConfig,
+    pub providers: ProvidersConfig,
+    pub repos: ReposConfig,
+    pub review: ReviewConfig,
+}
+
+/// AI integration settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiConfig {
+    /// Agent prefilled in comment forms, in `provider:agent` format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_agent: Option<String>,
+    /// Providers to install, by name. Empty installs every provider the
+    /// build supports.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<String>,
+    /// Path of the `opencode` binary, if it isn't `opencode` on the `PATH`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opencode_binary: Option<String>,
+    /// Most to spend on AI executions in a repository, in dollars. New
+    /// executions are blocked once the repository's recorded spend reaches
+    /// it, unless the budget is overridden.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<f64>,
+}
+
+/// Color theme of the web UI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl Theme {
+    /// The theme name as used in config files.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Light => "light",
+            Self::Dark => "dark",
+        }
+    }
+}
+
+/// Error returned when parsing an unknown theme name.
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown theme: {0}")]
+pub struct ParseThemeError(String);
+
+impl FromStr for Theme {
+    type Err = ParseThemeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "light" => Ok(Self::Light),
+            "dark" => Ok(Self::Dark),
+            _ => Err(ParseThemeError(s.to_string())),
+        }
+    }
+}
+
+/// Appearance settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppearanceConfig {
+    pub theme: Theme,
+}
+
+/// Local comment storage settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommentsConfig {
+    /// Store local comment threads as git notes under
+    /// `refs/notes/chadreview` in the repository, so they can be pushed and
+    /// fetched with it.
+    pub git_notes: bool,
+}
+
+/// Defaults for local diffs, used when the URL doesn't say otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiffConfig {
+    /// Include untracked files in working tree diffs.
+    pub include_untracked: bool,
+    /// Show the changes inside submodules in working tree diffs, for
+    /// repositories developed across submodules.
+    pub recurse_submodules: bool,
+    /// Patterns of generated files to collapse, in `.gitignore` syntax, on
+    /// top of [`DEFAULT_GENERATED_PATTERNS`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub generated: Vec<String>,
+}
+
+impl Default for DiffConfig {
+    fn default() -> Self {
+        Self {
+            include_untracked: true,
+            recurse_submodules: false,
+            generated: vec![],
+        }
+    }
+}
+
+/// Webhook relay settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RelayConfig {
+    /// URL of the relay server for real-time webhook updates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Token this instance authenticates to the relay server with, for
+    /// servers requiring one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Secret of GitHub webhooks delivered straight to this instance's
+    /// `/webhook` endpoint. Setting it enables the endpoint, for instances
+    /// GitHub can reach without a relay server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_secret: Option<String>,
+}
+
+/// Git hosting provider credentials.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProvidersConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_token: Option<String>,
+    /// Web URL of a GitHub Enterprise instance, used to link references in
+    /// rendered markdown. Defaults to `https://github.com`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_url: Option<String>,
+    /// Base URL of a Gitea/Forgejo instance. When set, it is used instead of
+    /// GitHub.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gitea_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gitea_token: Option<String>,
+}
+
+/// Local repository settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReposConfig {
+    /// Repository opened by `/local` when no `repo` is given, instead of the
+    /// current directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<PathBuf>,
+    /// Repositories listed on the settings page and the `/repos` picker for
+    /// quick access.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<PathBuf>,
+}
+
+/// Review settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReviewConfig {
+    /// Items to check off before a diff or PR is done, shown at the top of
+    /// `/local` and `/pr`. A repository's `.chadreview/checklist.md` takes
+    /// precedence over it.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub checklist: Vec<String>,
+}
+
+impl Config {
+    /// Path of the global config file
+    /// (`$XDG_CONFIG_HOME/chadreview/chadreview.toml`).
+    #[must_use]
+    pub fn global_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(GLOBAL_CONFIG_PATH))
+    }
+
+    /// Load the effective config: the global config file, overlaid with
+    /// `repo_path`'s config file (if given) and then with environment
+    /// variables.
+    ///
+    /// Missing config files are treated as empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a config file exists but cannot be read or parsed.
+    pub fn load(repo_path: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut table = Self::global_path()
+            .map_or_else(|| Ok(toml::Table::new()), |path| read_table(&path))?;
+
+        if let Some(repo_path) = repo_path {
+            merge_tables(&mut table, read_table(&repo_path.join(REPO_CONFIG_PATH))?);
+        }
+
+        let config: Self = table.try_into()?;
+        Ok(config.with_overrides(|name| std::env::var(name).ok()))
+    }
+
+    /// Load only the global config file, without repository or environment
+    /// overrides. This is what the settings page edits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config file exists but cannot be read or parsed.
+    pub fn load_global() -> Result<Self, ConfigError> {
+        Self::global_path().map_or_else(|| Ok(Self::default()), |path| Self::load_file(&path))
+    }
+
+    /// Save this config as the global config file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config directory cannot be determined or the
+    /// file cannot be written.
+    pub fn save_global(&self) -> Result<(), ConfigError> {
+        let path = Self::global_path().ok_or(ConfigError::NoConfigDir)?;
+        self.save_file(&path)
+    }
+
+    fn load_file(path: &Path) -> Result<Self, ConfigError> {
+        Ok(read_table(path)?.try_into()?)
+    }
+
+    fn save_file(&self, path: &Path) -> Result<(), ConfigError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string_pretty(self)?)?;
+
+        // The file may hold provider tokens
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+
+        Ok(())
+    }
+
+    /// Apply environment variable overrides, looking each one up with `var`.
+    #[must_use]
+    pub fn with_overrides(mut self, var: impl Fn(&str) -> Option<String>) -> Self {
+        if let Some(token) = var(GITHUB_TOKEN_ENV) {
+            self.providers.github_token = Some(token);
+        }
+        if let Some(url) = var(GITEA_URL_ENV) {
+            self.providers.gitea_url = Some(url);
+        }
+        if let Some(token) = var(GITEA_TOKEN_ENV) {
+            self.providers.gitea_token = Some(token);
+        }
+        if let Some(url) = var(RELAY_URL_ENV) {
+            self.relay.url = Some(url);
+        }
+        if let Some(token) = var(RELAY_TOKEN_ENV) {
+            self.relay.token = Some(token);
+        }
+        if let Some(secret) = var(GITHUB_WEBHOOK_SECRET_ENV) {
+            self.relay.webhook_secret = Some(secret);
+        }
+        if let Some(binary) = var(OPENCODE_BINARY_ENV) {
+            self.ai.opencode_binary = Some(binary);
+        }
+        self
+    }
+
+    /// Names of the environment variables currently overriding config file
+    /// settings.
+    #[must_use]
+    pub fn env_overrides() -> Vec<&'static str> {
+        ENV_OVERRIDES
+            .iter()
+            .copied()
+            .filter(|name| std::env::var_os(name).is_some())
+            .collect()
+    }
+}
+
+fn read_table(path: &Path) -> Result<toml::Table, ConfigError> {
+    if !path.exists() {
+        return Ok(toml::Table::new());
+    }
+
+    let content = fs::read_to_string(path)?;
+    Ok(content.parse()?)
+}
+
+/// Overlay `overlay` onto `base`, merging nested tables key by key.
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+                merge_tables(base, overlay);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Config {
+        content.parse::<toml::Table>().unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = parse("");
+
+        assert_eq!(config, Config::default());
+        assert_eq!(config.appearance.theme, Theme::Light);
+        assert!(config.diff.include_untracked);
+        assert!(!config.comments.git_notes);
+        assert!(!config.diff.recurse_submodules);
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = parse(
+            r#"
+            [ai]
+            default_agent = "opencode:plan"
+            providers = ["opencode"]
+            budget = 5.0
+
+            [appearance]
+            theme = "dark"
+
+            [comments]
+            git_notes = true
+
+            [diff]
+            include_untracked = false
+            recurse_submodules = true
+            generated = ["vendor/"]
+
+            [relay]
+            url = "wss://relay.example.com"
+            token = "relay_secret"
+
+            [providers]
+            github_token = "ghp_secret"
+            github_url = "https://github.example.com"
+
+            [repos]
+            default = "/src/chadreview"
+            paths = ["/src/chadreview", "/src/hyperchad"]
+
+            [review]
+            checklist = ["Tests added", "Docs updated"]
+            "#,
+        );
+
+        assert_eq!(config.ai.default_agent.as_deref(), Some("opencode:plan"));
+        assert_eq!(config.ai.providers, vec!["opencode"]);
+        assert_eq!(config.ai.budget, Some(5.0));
+        assert_eq!(config.appearance.theme, Theme::Dark);
+        assert!(config.comments.git_notes);
+        assert!(!config.diff.include_untracked);
+        assert!(config.diff.recurse_submodules);
+        assert_eq!(config.diff.generated, vec!["vendor/"]);
+        assert_eq!(
+            config.relay.url.as_deref(),
+            Some("wss://relay.example.com")
+        );
+        assert_eq!(config.relay.token.as_deref(), Some("relay_secret"));
+        assert_eq!(config.providers.github_token.as_deref(), Some("ghp_secret"));
+        assert_eq!(
+            config.providers.github_url.as_deref(),
+            Some("https://github.example.com")
+        );
+        assert_eq!(config.repos.paths.len(), 2);
+        assert_eq!(config.review.checklist, vec!["Tests added", "Docs updated"]);
+    }
+
+    #[test]
+    fn test_repo_config_overrides_global_per_key() {
+        let mut table: toml::Table = r#"
+            [ai]
+            default_agent = "opencode:code"
+
+            [appearance]
+            theme = "dark"
+            "#
+        .parse()
+        .unwrap();
+        let repo: toml::Table = r#"
+            [ai]
+            default_agent = "opencode:plan"
+            "#
+        .parse()
+        .unwrap();
+
+        merge_tables(&mut table, repo);
+        let config: Config = table.try_into().unwrap();
+
+        assert_eq!(config.ai.default_agent.as_deref(), Some("opencode:plan"));
+        assert_eq!(config.appearance.theme, Theme::Dark);
+    }
+
+    #[test]
+    fn test_env_overrides_config() {
+        let config = parse("[relay]\nurl = \"wss://from-file\"").with_overrides(|name| {
+            (name == RELAY_URL_ENV || name == GITHUB_TOKEN_ENV).then(|| format!("{name}-value"))
+        });
+
+        assert_eq!(config.relay.url.as_deref(), Some("RELAY_URL-value"));
+        assert_eq!(
+            config.providers.github_token.as_deref(),
+            Some("GITHUB_TOKEN-value")
+        );
+        assert_eq!(config.providers.gitea_url, None);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let path = std::env::temp_dir()
+            .join(format!("chadreview-config-test-{}", std::process::id()))
+            .join("chadreview.toml");
+        let _ = fs::remove_file(&path);
+
+        let mut config = Config::default();
+        config.appearance.theme = Theme::Dark;
+        config.repos.default = Some(PathBuf::from("/src/chadreview"));
+        config.save_file(&path).unwrap();
+
+        assert_eq!(Config::load_file(&path).unwrap(), config);
+    }
+
+    #[test]
+    fn test_parse_theme() {
+        assert_eq!("dark".parse::<Theme>().unwrap(), Theme::Dark);
+        assert_eq!(Theme::Light.as_str(), "light");
+        assert!("solarized".parse::<Theme>().is_err());
+    }
+}
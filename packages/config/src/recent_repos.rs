@@ -0,0 +1,142 @@
+//! Recently opened local repositories.
+//!
+//! Every repository viewed at `/local` is recorded in
+//! `<data dir>/chadreview/recent_repos.toml`, most recent first, so the
+//! repository picker can offer it again without it being listed in the
+//! config file.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ConfigError;
+
+/// Path of the recent repositories file, relative to the data directory.
+const RECENT_REPOS_PATH: &str = "chadreview/recent_repos.toml";
+
+/// Most repositories remembered; older ones are forgotten.
+pub const MAX_RECENT_REPOS: usize = 20;
+
+/// Parsed `recent_repos.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecentRepos {
+    /// Repository paths, most recently opened first.
+    pub paths: Vec<PathBuf>,
+}
+
+impl RecentRepos {
+    /// Path of the recent repositories file
+    /// (`$XDG_DATA_HOME/chadreview/recent_repos.toml`).
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(RECENT_REPOS_PATH))
+    }
+
+    /// Load the recent repositories, or none if the file doesn't exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::path().map_or_else(|| Ok(Self::default()), |path| Self::load_file(&path))
+    }
+
+    /// Save the recent repositories.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data directory cannot be determined or the
+    /// file cannot be written.
+    pub fn save(&self) -> Result<(), ConfigError> {
+        let path = Self::path().ok_or(ConfigError::NoDataDir)?;
+        self.save_file(&path)
+    }
+
+    /// Move `path` to the front, forgetting the oldest repositories beyond
+    /// [`MAX_RECENT_REPOS`]. Returns whether anything changed.
+    pub fn record(&mut self, path: &Path) -> bool {
+        if self.paths.first().is_some_and(|first| first == path) {
+            return false;
+        }
+
+        self.paths.retain(|recent| recent != path);
+        self.paths.insert(0, path.to_path_buf());
+        self.paths.truncate(MAX_RECENT_REPOS);
+        true
+    }
+
+    /// Forget `path`. Returns whether it was remembered.
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let len = self.paths.len();
+        self.paths.retain(|recent| recent != path);
+        self.paths.len() != len
+    }
+
+    fn load_file(path: &Path) -> Result<Self, ConfigError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn save_file(&self, path: &Path) -> Result<(), ConfigError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_moves_to_front() {
+        let mut recent = RecentRepos::default();
+
+        assert!(recent.record(Path::new("/src/a")));
+        assert!(recent.record(Path::new("/src/b")));
+        assert!(recent.record(Path::new("/src/a")));
+        assert!(!recent.record(Path::new("/src/a")));
+
+        assert_eq!(
+            recent.paths,
+            vec![PathBuf::from("/src/a"), PathBuf::from("/src/b")]
+        );
+    }
+
+    #[test]
+    fn test_record_forgets_oldest() {
+        let mut recent = RecentRepos::default();
+        for i in 0..=MAX_RECENT_REPOS {
+            recent.record(&PathBuf::from(format!("/src/{i}")));
+        }
+
+        assert_eq!(recent.paths.len(), MAX_RECENT_REPOS);
+        assert_eq!(
+            recent.paths[0],
+            PathBuf::from(format!("/src/{MAX_RECENT_REPOS}"))
+        );
+        assert!(!recent.paths.contains(&PathBuf::from("/src/0")));
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let path = std::env::temp_dir()
+            .join(format!("chadreview-config-test-{}", std::process::id()))
+            .join("recent_repos.toml");
+        let _ = fs::remove_file(&path);
+
+        let mut recent = RecentRepos::default();
+        recent.record(Path::new("/src/chadreview"));
+        recent.save_file(&path).unwrap();
+
+        assert_eq!(RecentRepos::load_file(&path).unwrap(), recent);
+    }
+}
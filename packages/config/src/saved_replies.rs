@@ -0,0 +1,197 @@
+//! Saved replies: canned comment texts inserted from comment and reply
+//! forms.
+//!
+//! They're kept in `<config dir>/chadreview/saved_replies.toml`, apart from
+//! `chadreview.toml` so they can be shared without sharing tokens. A body
+//! may contain [`FILE_PLACEHOLDER`] and [`LINE_PLACEHOLDER`], filled in with
+//! the file and line of the form the reply is inserted into.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ConfigError;
+
+/// Path of the saved replies file, relative to the config directory.
+const SAVED_REPLIES_PATH: &str = "chadreview/saved_replies.toml";
+
+/// Placeholder replaced with the path of the commented file.
+pub const FILE_PLACEHOLDER: &str = "{file}";
+
+/// Placeholder replaced with the commented line number.
+pub const LINE_PLACEHOLDER: &str = "{line}";
+
+/// A canned comment text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedReply {
+    /// Name shown in the picker.
+    pub name: String,
+    /// Text inserted into the comment.
+    pub body: String,
+}
+
+impl SavedReply {
+    #[must_use]
+    pub fn new(name: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            body: body.into(),
+        }
+    }
+
+    /// The body with its placeholders replaced by `file` and `line`.
+    /// Placeholders without a value are replaced with nothing.
+    #[must_use]
+    pub fn expand(&self, file: Option<&str>, line: Option<u64>) -> String {
+        self.body
+            .replace(FILE_PLACEHOLDER, file.unwrap_or_default())
+            .replace(
+                LINE_PLACEHOLDER,
+                &line.map(|line| line.to_string()).unwrap_or_default(),
+            )
+    }
+}
+
+/// Parsed `saved_replies.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedReplies {
+    pub replies: Vec<SavedReply>,
+}
+
+impl Default for SavedReplies {
+    fn default() -> Self {
+        Self {
+            replies: vec![
+                SavedReply::new("Nit", "nit: "),
+                SavedReply::new("Needs a test", "Please add a test covering this."),
+                SavedReply::new("Explain", "Could you explain why {file}:{line} is needed?"),
+                SavedReply::new(
+                    "Follow-up",
+                    "Fine for now, but let's track a follow-up for this.",
+                ),
+                SavedReply::new("Looks good", "Looks good to me."),
+            ],
+        }
+    }
+}
+
+impl SavedReplies {
+    /// Path of the saved replies file
+    /// (`$XDG_CONFIG_HOME/chadreview/saved_replies.toml`).
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(SAVED_REPLIES_PATH))
+    }
+
+    /// Load the saved replies, or the defaults if the file doesn't exist
+    /// yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::path().map_or_else(|| Ok(Self::default()), |path| Self::load_file(&path))
+    }
+
+    /// Save the replies to the saved replies file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config directory cannot be determined or the
+    /// file cannot be written.
+    pub fn save(&self) -> Result<(), ConfigError> {
+        let path = Self::path().ok_or(ConfigError::NoConfigDir)?;
+        self.save_file(&path)
+    }
+
+    /// Add a reply, replacing any existing reply with the same name.
+    pub fn upsert(&mut self, reply: SavedReply) {
+        match self.replies.iter_mut().find(|r| r.name == reply.name) {
+            Some(existing) => *existing = reply,
+            None => self.replies.push(reply),
+        }
+    }
+
+    /// Remove the reply named `name`. Returns whether there was one.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.replies.len();
+        self.replies.retain(|reply| reply.name != name);
+        self.replies.len() != len
+    }
+
+    fn load_file(path: &Path) -> Result<Self, ConfigError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn save_file(&self, path: &Path) -> Result<(), ConfigError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_placeholders() {
+        let reply = SavedReply::new("Explain", "Why is {file}:{line} needed?");
+
+        assert_eq!(
+            reply.expand(Some("src/lib.rs"), Some(42)),
+            "Why is src/lib.rs:42 needed?"
+        );
+        assert_eq!(reply.expand(None, None), "Why is : needed?");
+    }
+
+    #[test]
+    fn test_upsert_and_remove() {
+        let mut replies = SavedReplies { replies: vec![] };
+
+        replies.upsert(SavedReply::new("Nit", "nit:"));
+        replies.upsert(SavedReply::new("Nit", "nit (non-blocking):"));
+        assert_eq!(
+            replies.replies,
+            vec![SavedReply::new("Nit", "nit (non-blocking):")]
+        );
+
+        assert!(replies.remove("Nit"));
+        assert!(!replies.remove("Nit"));
+        assert!(replies.replies.is_empty());
+    }
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let path = std::env::temp_dir().join("chadreview-missing-saved-replies.toml");
+
+        assert_eq!(
+            SavedReplies::load_file(&path).unwrap(),
+            SavedReplies::default()
+        );
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let path = std::env::temp_dir()
+            .join(format!("chadreview-config-test-{}", std::process::id()))
+            .join("saved_replies.toml");
+        let _ = fs::remove_file(&path);
+
+        let replies = SavedReplies {
+            replies: vec![SavedReply::new("Typo", "Typo in {file}, line {line}.")],
+        };
+        replies.save_file(&path).unwrap();
+
+        assert_eq!(SavedReplies::load_file(&path).unwrap(), replies);
+    }
+}
@@ -94,6 +94,34 @@ impl std::fmt::Display for LineNumber {
     }
 }
 
+/// Where a search match was found.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchSource {
+    /// An added line of the diff.
+    Addition,
+    /// A removed line of the diff.
+    Deletion,
+    /// An unchanged context line of the diff.
+    Context,
+    /// A line of the file's full contents outside the diff.
+    File,
+}
+
+/// A single line matching a search query.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// Path of the file containing the match.
+    pub filename: String,
+    /// Line number in the new version of the file, or in the old version
+    /// for deleted lines.
+    pub line_number: u64,
+    /// Where the match was found.
+    pub source: MatchSource,
+    /// The matching line, without its diff marker.
+    pub text: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -8,5 +8,7 @@
 //! data models with syntax highlighting support.
 
 pub mod parser;
+pub mod search;
 
-pub use parser::parse_unified_diff;
+pub use parser::{FilePatch, highlight_to_html, parse_unified_diff, split_diff};
+pub use search::{search_diff, search_text};
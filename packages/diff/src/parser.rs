@@ -59,7 +59,10 @@ pub fn parse_unified_diff(
         status,
         additions,
         deletions,
+        mode_change: None,
         hunks,
+        decoded_lossy: false,
+        submodule: None,
     })
 }
 
@@ -150,7 +153,14 @@ fn parse_hunk(
 }
 
 /// Convert syntax-highlighted content to HTML.
-fn highlight_to_html(
+///
+/// Highlights `content` as a line of `filename`, for building diff lines
+/// from already structured hunks.
+///
+/// # Errors
+///
+/// Returns an error if highlighting fails.
+pub fn highlight_to_html(
     highlighter: &SyntaxHighlighter,
     filename: &str,
     content: &str,
@@ -203,10 +213,98 @@ pub fn count_additions_deletions(patch: &str) -> (u64, u64) {
     (additions, deletions)
 }
 
+/// A single file's section of a multi-file git diff.
+#[derive(Debug, Clone)]
+pub struct FilePatch {
+    /// Path of the file after the change.
+    pub filename: String,
+    pub status: FileStatus,
+    /// Whether git reported the file as binary, in which case `patch` is
+    /// empty.
+    pub binary: bool,
+    /// The file's hunks, starting at the first `@@` header.
+    pub patch: String,
+}
+
+/// Split the output of `git diff` into per-file patches, ready for
+/// [`parse_unified_diff`].
+#[must_use]
+pub fn split_diff(diff: &str) -> Vec<FilePatch> {
+    let mut files: Vec<FilePatch> = Vec::new();
+
+    for line in diff.lines() {
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            // `a/<old> b/<new>`; refined below by `+++`/`rename to` when present
+            let filename = paths
+                .rsplit_once(" b/")
+                .map_or(paths, |(_, new)| new)
+                .to_string();
+            files.push(FilePatch {
+                filename,
+                status: FileStatus::Modified,
+                binary: false,
+                patch: String::new(),
+            });
+            continue;
+        }
+
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+
+        if file.patch.is_empty() && !line.starts_with("@@") {
+            if line.starts_with("new file mode") {
+                file.status = FileStatus::Added;
+            } else if line.starts_with("deleted file mode") {
+                file.status = FileStatus::Deleted;
+            } else if let Some(path) = line.strip_prefix("rename to ") {
+                file.status = FileStatus::Renamed;
+                path.clone_into(&mut file.filename);
+            } else if let Some(path) = line.strip_prefix("+++ b/") {
+                path.clone_into(&mut file.filename);
+            } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+                file.binary = true;
+            }
+            continue;
+        }
+
+        file.patch.push_str(line);
+        file.patch.push('\n');
+    }
+
+    files
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_split_diff_added_and_deleted() {
+        let diff = "\
+diff --git a/a.txt b/a.txt
+new file mode 100644
+--- /dev/null
++++ b/a.txt
+@@ -0,0 +1 @@
++a
+diff --git a/b.txt b/b.txt
+deleted file mode 100644
+--- a/b.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-b
+";
+        let files = split_diff(diff);
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].filename, "a.txt");
+        assert_eq!(files[0].status, FileStatus::Added);
+        assert_eq!(files[0].patch, "@@ -0,0 +1 @@\n+a\n");
+        assert_eq!(files[1].filename, "b.txt");
+        assert_eq!(files[1].status, FileStatus::Deleted);
+    }
+
     #[test]
     fn test_parse_hunk_header() {
         let header = "@@ -10,5 +12,7 @@ fn main() {";
@@ -0,0 +1,139 @@
+//! Case-insensitive text search over parsed diffs and file contents.
+
+use chadreview_diff_models::{MatchSource, SearchMatch};
+use chadreview_pr_models::diff::{DiffFile, LineType};
+
+/// Find diff lines containing `query`, ignoring case, in file order.
+///
+/// Stops after `limit` matches. An empty query matches nothing.
+#[must_use]
+pub fn search_diff(diffs: &[DiffFile], query: &str, limit: usize) -> Vec<SearchMatch> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    diffs
+        .iter()
+        .flat_map(|file| {
+            file.hunks
+                .iter()
+                .flat_map(|hunk| &hunk.lines)
+                .map(move |line| (file, line))
+        })
+        .filter(|(_, line)| line.content.to_lowercase().contains(&query))
+        .filter_map(|(file, line)| {
+            let (line_number, source) = match line.line_type {
+                LineType::Addition => (line.new_line_number?, MatchSource::Addition),
+                LineType::Deletion => (line.old_line_number?, MatchSource::Deletion),
+                LineType::Context => (line.new_line_number?, MatchSource::Context),
+            };
+
+            Some(SearchMatch {
+                filename: file.filename.clone(),
+                line_number,
+                source,
+                text: line.content.clone(),
+            })
+        })
+        .take(limit)
+        .collect()
+}
+
+/// Find lines of `content` containing `query`, ignoring case.
+///
+/// Lines in `skip_lines` (e.g. those already matched in the diff) are left
+/// out. Stops after `limit` matches.
+#[must_use]
+pub fn search_text(
+    filename: &str,
+    content: &str,
+    query: &str,
+    skip_lines: &[u64],
+    limit: usize,
+) -> Vec<SearchMatch> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    content
+        .lines()
+        .zip(1..)
+        .filter(|(line, number)| {
+            !skip_lines.contains(number) && line.to_lowercase().contains(&query)
+        })
+        .map(|(line, line_number)| SearchMatch {
+            filename: filename.to_string(),
+            line_number,
+            source: MatchSource::File,
+            text: line.to_string(),
+        })
+        .take(limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chadreview_pr_models::diff::{DiffHunk, DiffLine, FileStatus};
+
+    fn line(line_type: LineType, old: Option<u64>, new: Option<u64>, content: &str) -> DiffLine {
+        DiffLine {
+            line_type,
+            old_line_number: old,
+            new_line_number: new,
+            content: content.to_string(),
+            highlighted_html: content.to_string(),
+        }
+    }
+
+    fn diff() -> Vec<DiffFile> {
+        vec![DiffFile {
+            filename: "src/lib.rs".to_string(),
+            status: FileStatus::Modified,
+            additions: 1,
+            deletions: 1,
+            mode_change: None,
+            hunks: vec![DiffHunk {
+                old_start: 1,
+                old_lines: 2,
+                new_start: 1,
+                new_lines: 2,
+                lines: vec![
+                    line(LineType::Context, Some(1), Some(1), "fn main() {"),
+                    line(LineType::Deletion, Some(2), None, "    println!(\"Old\");"),
+                    line(LineType::Addition, None, Some(2), "    println!(\"new\");"),
+                ],
+            }],
+            decoded_lossy: false,
+            submodule: None,
+        }]
+    }
+
+    #[test]
+    fn test_search_diff_ignores_case() {
+        let matches = search_diff(&diff(), "PRINTLN", 10);
+
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].line_number, 2);
+        assert_eq!(matches[0].source, MatchSource::Deletion);
+        assert_eq!(matches[1].source, MatchSource::Addition);
+    }
+
+    #[test]
+    fn test_search_diff_respects_limit_and_empty_query() {
+        assert_eq!(search_diff(&diff(), "println", 1).len(), 1);
+        assert!(search_diff(&diff(), "", 10).is_empty());
+    }
+
+    #[test]
+    fn test_search_text_skips_lines() {
+        let content = "use foo;\nfn main() {\n    foo();\n}\n";
+        let matches = search_text("src/main.rs", content, "foo", &[3], 10);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 1);
+        assert_eq!(matches[0].source, MatchSource::File);
+    }
+}
@@ -7,6 +7,8 @@
 //! This crate defines the data types returned by git backend operations,
 //! abstracting over the specific git implementation (git2, CLI, etc.).
 
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
 /// Result of a diff operation containing all changed files.
@@ -14,6 +16,41 @@ use serde::{Deserialize, Serialize};
 pub struct DiffResult {
     /// List of files with changes.
     pub files: Vec<FileDiff>,
+    /// Changes inside submodules, when a working tree diff recurses into
+    /// them.
+    pub submodules: Vec<SubmoduleDiff>,
+}
+
+impl DiffResult {
+    /// Merge the changes inside submodules into [`Self::files`], with their
+    /// paths prefixed with the submodule's path, which is kept in
+    /// [`FileDiff::submodule`].
+    #[must_use]
+    pub fn flatten(self) -> Self {
+        let mut files = self.files;
+        for SubmoduleDiff { path, diff } in self.submodules {
+            let prefix = |file_path: String| format!("{path}/{file_path}");
+            files.extend(diff.flatten().files.into_iter().map(|mut file| {
+                file.old_path = file.old_path.map(prefix);
+                file.new_path = file.new_path.map(prefix);
+                file.submodule = Some(file.submodule.map_or_else(|| path.clone(), prefix));
+                file
+            }));
+        }
+        Self {
+            files,
+            submodules: vec![],
+        }
+    }
+}
+
+/// The changes inside a submodule of a working tree diff.
+#[derive(Debug, Clone)]
+pub struct SubmoduleDiff {
+    /// Path of the submodule in its parent repository.
+    pub path: String,
+    /// Working tree diff of the submodule against its checked out commit.
+    pub diff: DiffResult,
 }
 
 /// A single file's diff information.
@@ -25,10 +62,212 @@ pub struct FileDiff {
     pub new_path: Option<String>,
     /// Status of the file change.
     pub status: DiffStatus,
-    /// Unified diff patch text (None for binary files).
-    pub patch: Option<String>,
+    /// Git mode of the file in the old tree, e.g. `0o100644` (None for
+    /// added files).
+    pub old_mode: Option<u32>,
+    /// Git mode of the file in the new tree, e.g. `0o100755` (None for
+    /// deleted files).
+    pub new_mode: Option<u32>,
+    /// Changed regions of the file (empty for binary files).
+    pub hunks: Vec<Hunk>,
     /// Whether this is a binary file.
     pub binary: bool,
+    /// Whether some lines of its hunks weren't valid UTF-8 and were decoded
+    /// as Latin-1, so their text doesn't match the file's bytes.
+    pub decoded_lossy: bool,
+    /// Path of the submodule the file is in, for files of a flattened
+    /// working tree diff that recursed into submodules. The file belongs to
+    /// the submodule's repository rather than this one.
+    pub submodule: Option<String>,
+}
+
+impl FileDiff {
+    /// Path of the file, in the new tree unless it was deleted.
+    #[must_use]
+    pub fn path(&self) -> Option<&str> {
+        self.new_path.as_deref().or(self.old_path.as_deref())
+    }
+
+    /// The old and new modes of the file, if it exists on both sides and
+    /// its mode changed, like when it became executable.
+    #[must_use]
+    pub const fn mode_change(&self) -> Option<(u32, u32)> {
+        match (self.old_mode, self.new_mode) {
+            (Some(old_mode), Some(new_mode)) if old_mode != new_mode => Some((old_mode, new_mode)),
+            _ => None,
+        }
+    }
+
+    /// Numbers of added and deleted lines.
+    #[must_use]
+    pub fn line_changes(&self) -> (u64, u64) {
+        self.hunks.iter().flat_map(|hunk| &hunk.lines).fold(
+            (0, 0),
+            |(additions, deletions), line| match line.origin {
+                LineOrigin::Context => (additions, deletions),
+                LineOrigin::Addition => (additions + 1, deletions),
+                LineOrigin::Deletion => (additions, deletions + 1),
+            },
+        )
+    }
+
+    /// The file's hunks as unified diff text, without file headers (None
+    /// for binary files).
+    #[must_use]
+    pub fn patch(&self) -> Option<String> {
+        if self.binary {
+            return None;
+        }
+        Some(self.hunks.iter().map(ToString::to_string).collect())
+    }
+}
+
+/// A changed file's line counts, which backends can compute without
+/// loading its hunks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStat {
+    /// Path in the old tree (None for added files).
+    pub old_path: Option<String>,
+    /// Path in the new tree (None for deleted files).
+    pub new_path: Option<String>,
+    /// Status of the file change.
+    pub status: DiffStatus,
+    /// Number of added lines (0 for binary files).
+    pub additions: u64,
+    /// Number of deleted lines (0 for binary files).
+    pub deletions: u64,
+}
+
+impl FileStat {
+    /// Path of the file, in the new tree unless it was deleted.
+    #[must_use]
+    pub fn path(&self) -> Option<&str> {
+        self.new_path.as_deref().or(self.old_path.as_deref())
+    }
+}
+
+impl From<&FileDiff> for FileStat {
+    fn from(file: &FileDiff) -> Self {
+        let (additions, deletions) = file.line_changes();
+        Self {
+            old_path: file.old_path.clone(),
+            new_path: file.new_path.clone(),
+            status: file.status,
+            additions,
+            deletions,
+        }
+    }
+}
+
+/// A changed region of a file, with its surrounding context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// First line of the region in the old file (0 if it is empty).
+    pub old_start: u32,
+    /// Number of lines of the region in the old file.
+    pub old_lines: u32,
+    /// First line of the region in the new file (0 if it is empty).
+    pub new_start: u32,
+    /// Number of lines of the region in the new file.
+    pub new_lines: u32,
+    /// Text git shows after the line ranges of the header, usually the
+    /// enclosing function (may be empty).
+    pub section: String,
+    /// Lines of the region.
+    pub lines: Vec<Line>,
+}
+
+impl Hunk {
+    /// The hunk undone: added lines become deleted ones and the other way
+    /// around.
+    #[must_use]
+    pub fn reversed(&self) -> Self {
+        Self {
+            old_start: self.new_start,
+            old_lines: self.new_lines,
+            new_start: self.old_start,
+            new_lines: self.old_lines,
+            section: self.section.clone(),
+            lines: self
+                .lines
+                .iter()
+                .map(|line| Line {
+                    origin: match line.origin {
+                        LineOrigin::Context => LineOrigin::Context,
+                        LineOrigin::Addition => LineOrigin::Deletion,
+                        LineOrigin::Deletion => LineOrigin::Addition,
+                    },
+                    old_lineno: line.new_lineno,
+                    new_lineno: line.old_lineno,
+                    content: line.content.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Formats the hunk as unified diff text, its `@@` header first, like git.
+impl std::fmt::Display for Hunk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn range(start: u32, lines: u32) -> String {
+            if lines == 1 {
+                start.to_string()
+            } else {
+                format!("{start},{lines}")
+            }
+        }
+
+        write!(
+            f,
+            "@@ -{} +{} @@",
+            range(self.old_start, self.old_lines),
+            range(self.new_start, self.new_lines)
+        )?;
+        if !self.section.is_empty() {
+            write!(f, " {}", self.section)?;
+        }
+        writeln!(f)?;
+        for line in &self.lines {
+            writeln!(f, "{}{}", line.origin.as_char(), line.content)?;
+        }
+        Ok(())
+    }
+}
+
+/// A line of a hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    /// Whether the line is context, added or deleted.
+    pub origin: LineOrigin,
+    /// Line number in the old file (None for added lines).
+    pub old_lineno: Option<u32>,
+    /// Line number in the new file (None for deleted lines).
+    pub new_lineno: Option<u32>,
+    /// Content of the line, without its trailing newline.
+    pub content: String,
+}
+
+/// Whether a hunk line is context, added or deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineOrigin {
+    /// Unchanged line around the changes.
+    Context,
+    /// Line added in the new file.
+    Addition,
+    /// Line deleted from the old file.
+    Deletion,
+}
+
+impl LineOrigin {
+    /// The line's marker in unified diff text.
+    #[must_use]
+    pub const fn as_char(self) -> char {
+        match self {
+            Self::Context => ' ',
+            Self::Addition => '+',
+            Self::Deletion => '-',
+        }
+    }
 }
 
 /// Status of a file in a diff.
@@ -67,6 +306,157 @@ pub struct CommitInfo {
     pub timestamp: i64,
     /// Parent commit SHAs.
     pub parent_shas: Vec<String>,
+    /// The commit's signature, `None` for unsigned commits.
+    #[serde(default)]
+    pub signature: Option<CommitSignature>,
+}
+
+/// The signature of a signed commit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitSignature {
+    /// How the commit is signed.
+    pub kind: SignatureKind,
+    /// Whether the signature was checked, and how that went.
+    pub verification: SignatureVerification,
+    /// Who signed the commit, as reported by the verification: the key's
+    /// user ID for GPG, the matched principal for SSH.
+    pub signer: Option<String>,
+}
+
+/// How a commit is signed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureKind {
+    /// `OpenPGP` signature (`gpg.format=openpgp`).
+    Gpg,
+    /// SSH signature (`gpg.format=ssh`).
+    Ssh,
+    /// X.509 signature (`gpg.format=x509`).
+    X509,
+}
+
+impl SignatureKind {
+    /// Detect the kind of an armored signature from its header.
+    #[must_use]
+    pub fn from_armor(signature: &str) -> Option<Self> {
+        let signature = signature.trim_start();
+        if signature.starts_with("-----BEGIN PGP SIGNATURE-----") {
+            Some(Self::Gpg)
+        } else if signature.starts_with("-----BEGIN SSH SIGNATURE-----") {
+            Some(Self::Ssh)
+        } else if signature.starts_with("-----BEGIN SIGNED MESSAGE-----") {
+            Some(Self::X509)
+        } else {
+            None
+        }
+    }
+
+    /// Human-readable name of the signature kind.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Gpg => "GPG",
+            Self::Ssh => "SSH",
+            Self::X509 => "X.509",
+        }
+    }
+}
+
+/// Outcome of verifying a commit signature, like `git verify-commit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureVerification {
+    /// The signature is good.
+    Good,
+    /// The signature doesn't match the commit.
+    Bad,
+    /// The signer's key is unknown, expired or revoked, or not allowed to
+    /// sign.
+    UnknownKey,
+    /// The signature hasn't been checked, or couldn't be.
+    Unchecked,
+}
+
+/// Someone who authored commits in a repository, as listed by
+/// `git shortlog -sne`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Author {
+    /// Author name.
+    pub name: String,
+    /// Author email.
+    pub email: String,
+    /// Number of commits reachable from HEAD they authored.
+    pub commits: usize,
+}
+
+/// The name and email new commits are made with, from the `user.name` and
+/// `user.email` config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identity {
+    /// User name.
+    pub name: String,
+    /// User email.
+    pub email: String,
+}
+
+/// A checkout of a repository, the main one or one added with
+/// `git worktree add`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Worktree {
+    /// Path of the worktree's working directory.
+    pub path: PathBuf,
+    /// Branch checked out, or `None` when HEAD is detached.
+    pub branch: Option<String>,
+    /// SHA of the checked out commit, or `None` when HEAD is unborn.
+    pub head: Option<String>,
+    /// Whether this is the repository's main worktree.
+    pub is_main: bool,
+    /// Whether the repository was opened at this worktree.
+    pub is_current: bool,
+}
+
+/// An operation stopped partway through, e.g. on conflicts, waiting to be
+/// continued or aborted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RepoOperation {
+    /// `git merge`.
+    Merge,
+    /// `git rebase`.
+    Rebase,
+    /// `git cherry-pick`.
+    CherryPick,
+    /// `git revert`.
+    Revert,
+}
+
+impl RepoOperation {
+    /// Human-readable name of the operation.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Merge => "Merge",
+            Self::Rebase => "Rebase",
+            Self::CherryPick => "Cherry-pick",
+            Self::Revert => "Revert",
+        }
+    }
+}
+
+/// A file with conflicting changes, from the stages of its index entry.
+///
+/// Each side is `None` when the file doesn't exist on it, e.g. `base` for a
+/// file both sides added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictFile {
+    /// File path relative to the repository root.
+    pub path: String,
+    /// Content in the common ancestor (stage 1).
+    pub base: Option<String>,
+    /// Content on the checked out side (stage 2). During a rebase this is
+    /// the branch being rebased onto.
+    pub ours: Option<String>,
+    /// Content on the side being merged in (stage 3).
+    pub theirs: Option<String>,
+    /// Whether any side is binary, in which case its content is left out.
+    pub binary: bool,
 }
 
 /// Result of resolving a git reference.
@@ -95,6 +485,33 @@ pub enum RefType {
     Remote,
 }
 
+/// Ref namespace that branches fetched from other remotes (e.g. forks) are
+/// stored under.
+pub const FETCHED_REF_PREFIX: &str = "refs/chadreview/fetched";
+
+/// Local ref name for `branch` fetched from the remote at `url`.
+///
+/// The URL is reduced to a ref-safe slug, so
+/// `fetched_ref_name("https://github.com/alice/repo.git", "fix")` is
+/// `refs/chadreview/fetched/github-com-alice-repo/fix`.
+#[must_use]
+pub fn fetched_ref_name(url: &str, branch: &str) -> String {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let url = url.strip_suffix(".git").unwrap_or(url);
+
+    let mut slug = String::with_capacity(url.len());
+    for c in url.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+
+    format!("{FETCHED_REF_PREFIX}/{slug}/{branch}")
+}
+
 /// Errors from git backend operations.
 #[derive(Debug, thiserror::Error)]
 pub enum GitBackendError {
@@ -140,6 +557,24 @@ pub enum GitBackendError {
         message: String,
     },
 
+    /// Fetching from a remote failed.
+    #[error("Failed to fetch {branch} from {url}: {message}")]
+    FetchFailed {
+        /// The remote URL.
+        url: String,
+        /// The branch that was requested.
+        branch: String,
+        /// Error message from the underlying git implementation.
+        message: String,
+    },
+
+    /// A patch doesn't apply to the index.
+    #[error("Patch does not apply: {message}")]
+    PatchFailed {
+        /// Error message from the underlying git implementation.
+        message: String,
+    },
+
     /// I/O error.
     #[error("I/O error: {message}")]
     IoError {
@@ -156,8 +591,17 @@ impl From<std::io::Error> for GitBackendError {
     }
 }
 
+/// Options for how the hunks of a diff are generated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Number of unchanged lines shown around each change (git's default
+    /// of 3 if None).
+    pub context_lines: Option<u32>,
+}
+
 /// Options for working tree diff operations.
 #[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct WorkingTreeDiffOptions {
     /// Only include staged changes (git diff --cached).
     pub staged_only: bool,
@@ -165,4 +609,9 @@ pub struct WorkingTreeDiffOptions {
     pub include_untracked: bool,
     /// Include ignored files.
     pub include_ignored: bool,
+    /// Also diff the working trees of checked out submodules, returned as
+    /// [`DiffResult::submodules`].
+    pub recurse_submodules: bool,
+    /// How the hunks are generated.
+    pub diff: DiffOptions,
 }
@@ -5,9 +5,38 @@
 use std::path::Path;
 
 use chadreview_git_backend_models::{
-    CommitInfo, DiffResult, GitBackendError, ResolvedRef, WorkingTreeDiffOptions,
+    Author, CommitInfo, CommitSignature, ConflictFile, DiffOptions, DiffResult, FileDiff, FileStat,
+    GitBackendError, Identity, RepoOperation, ResolvedRef, WorkingTreeDiffOptions, Worktree,
 };
 
+/// A diff whose files are listed upfront, but whose hunks are only loaded
+/// for the files asked for, for diffs too large to load at once.
+pub trait LazyDiff {
+    /// Number of changed files.
+    fn len(&self) -> usize;
+
+    /// Whether no files changed.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Line counts of every file, in diff order, without loading their
+    /// hunks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file's changes can't be computed.
+    fn stats(&self) -> Result<Vec<FileStat>, GitBackendError>;
+
+    /// The file at `index`, in diff order, with its hunks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no such file or its hunks can't be
+    /// loaded.
+    fn file(&self, index: usize) -> Result<FileDiff, GitBackendError>;
+}
+
 /// Factory trait for opening git repositories.
 ///
 /// This is the main abstraction point for testing - mock implementations
@@ -46,6 +75,10 @@ pub trait GitBackend: Send + Sync {
 /// Note: This trait only requires `Send`, not `Sync`, because `git2::Repository`
 /// is not thread-safe. Operations should be performed on a single thread or
 /// protected by external synchronization.
+///
+/// Operations block until done, which can take a while on big diffs or for
+/// backends reaching a git service over the network. Async callers should
+/// run them on a blocking thread pool rather than on their executor.
 pub trait GitRepository: Send {
     // === Reference Resolution ===
 
@@ -74,6 +107,17 @@ pub trait GitRepository: Send {
     /// Returns an error if either commit doesn't exist or has no common ancestor.
     fn merge_base(&self, commit1: &str, commit2: &str) -> Result<String, GitBackendError>;
 
+    /// A fingerprint of the repository's refs and HEAD.
+    ///
+    /// It changes whenever a ref is created, deleted or moved, such as by a
+    /// commit, checkout or fetch, so refs resolved under one fingerprint are
+    /// current for as long as it stays the same.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ref storage cannot be read.
+    fn refs_fingerprint(&self) -> Result<u64, GitBackendError>;
+
     // === Commit Information ===
 
     /// Get commit information for a SHA.
@@ -87,6 +131,22 @@ pub trait GitRepository: Send {
     /// Returns `GitBackendError::CommitNotFound` if the commit doesn't exist.
     fn get_commit(&self, sha: &str) -> Result<CommitInfo, GitBackendError>;
 
+    /// Verify the signature of a commit like `git verify-commit`, with the
+    /// repository's signing configuration (`gpg.program`,
+    /// `gpg.ssh.allowedSignersFile`, ...).
+    ///
+    /// Returns `None` for an unsigned commit. The verification is
+    /// `Unchecked` when the signing tools can't be run.
+    ///
+    /// # Arguments
+    ///
+    /// * `sha` - The commit SHA (full or partial).
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitBackendError::RefNotFound` if the commit doesn't exist.
+    fn verify_signature(&self, sha: &str) -> Result<Option<CommitSignature>, GitBackendError>;
+
     /// List commits in a range (base..head), newest first.
     ///
     /// # Arguments
@@ -107,11 +167,40 @@ pub trait GitRepository: Send {
     ///
     /// * `old_sha` - The "old" commit SHA.
     /// * `new_sha` - The "new" commit SHA.
+    /// * `options` - How the hunks are generated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either commit doesn't exist.
+    fn diff_commits(
+        &self,
+        old_sha: &str,
+        new_sha: &str,
+        options: DiffOptions,
+    ) -> Result<DiffResult, GitBackendError>;
+
+    /// Compute diff between two commits lazily, for diffs too large to load
+    /// at once.
+    ///
+    /// Only the list of changed files is computed upfront. Each file's hunks
+    /// are loaded when [`LazyDiff::file`] asks for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `old_sha` - The "old" commit SHA, or a tree SHA like git's empty
+    ///   tree.
+    /// * `new_sha` - The "new" commit SHA.
+    /// * `options` - How the hunks are generated.
     ///
     /// # Errors
     ///
     /// Returns an error if either commit doesn't exist.
-    fn diff_commits(&self, old_sha: &str, new_sha: &str) -> Result<DiffResult, GitBackendError>;
+    fn diff_commits_lazy(
+        &self,
+        old_sha: &str,
+        new_sha: &str,
+        options: DiffOptions,
+    ) -> Result<Box<dyn LazyDiff + '_>, GitBackendError>;
 
     /// Diff a single commit against its parent(s).
     ///
@@ -120,11 +209,12 @@ pub trait GitRepository: Send {
     /// # Arguments
     ///
     /// * `sha` - The commit SHA.
+    /// * `options` - How the hunks are generated.
     ///
     /// # Errors
     ///
     /// Returns an error if the commit doesn't exist.
-    fn diff_commit(&self, sha: &str) -> Result<DiffResult, GitBackendError>;
+    fn diff_commit(&self, sha: &str, options: DiffOptions) -> Result<DiffResult, GitBackendError>;
 
     /// Diff working tree against a reference.
     ///
@@ -142,6 +232,164 @@ pub trait GitRepository: Send {
         options: WorkingTreeDiffOptions,
     ) -> Result<DiffResult, GitBackendError>;
 
+    /// Diff working tree against a reference lazily, like
+    /// [`Self::diff_commits_lazy`]. Changes inside submodules follow the
+    /// repository's own, with their paths prefixed with the submodule's
+    /// path as by [`DiffResult::flatten`].
+    ///
+    /// # Arguments
+    ///
+    /// * `against` - Reference to diff against (e.g., "HEAD").
+    /// * `options` - Options controlling what to include in the diff.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reference doesn't exist.
+    fn diff_working_tree_lazy(
+        &self,
+        against: &str,
+        options: WorkingTreeDiffOptions,
+    ) -> Result<Box<dyn LazyDiff + '_>, GitBackendError>;
+
+    // === Index Operations ===
+
+    /// Stage the working tree state of a file, like `git add -- <path>`. A
+    /// file deleted from the working tree is removed from the index.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - File path relative to the repository root.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is inside a submodule, or the index
+    /// cannot be read or written.
+    fn stage_path(&self, path: &str) -> Result<(), GitBackendError>;
+
+    /// Unstage a file, resetting its index entry to HEAD like
+    /// `git reset -- <path>`. A file not in HEAD is removed from the index.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - File path relative to the repository root.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is inside a submodule, or the index
+    /// cannot be read or written.
+    fn unstage_path(&self, path: &str) -> Result<(), GitBackendError>;
+
+    /// Restore a file in both the index and the working tree to its state
+    /// in a reference, like `git checkout <against> -- <path>`, discarding
+    /// its changes. A file that isn't in `against` is removed from both.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - File path relative to the repository root.
+    /// * `against` - Reference to restore the file from (e.g., "HEAD").
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reference doesn't exist, the file is inside
+    /// a submodule, or it can't be restored.
+    fn checkout_path(&self, path: &str, against: &str) -> Result<(), GitBackendError>;
+
+    /// Apply a unified diff patch to the index without touching the working
+    /// tree, like `git apply --cached`.
+    ///
+    /// # Arguments
+    ///
+    /// * `patch` - The patch, with `---`/`+++` file headers.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitBackendError::PatchFailed` if the patch can't be parsed
+    /// or doesn't apply to the index.
+    fn apply_patch_to_index(&self, patch: &str) -> Result<(), GitBackendError>;
+
+    /// List the files with unresolved conflicts in the index, sorted by
+    /// path, with the content of each side of the conflict.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index or its blobs cannot be read.
+    fn list_conflicts(&self) -> Result<Vec<ConflictFile>, GitBackendError>;
+
+    /// Commit the index on top of HEAD, like `git commit`, or replace HEAD
+    /// with it, like `git commit --amend`. An amended commit keeps its
+    /// author and gets the current identity as committer.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The commit message.
+    /// * `amend` - Whether to replace HEAD instead of adding a commit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no identity is configured, nothing is staged
+    /// (when not amending), there is no commit to amend, or the commit
+    /// cannot be written. Implementations that can't sign commits or run
+    /// commit hooks return an error when the repository asks for them,
+    /// rather than skip them.
+    fn commit(&self, message: &str, amend: bool) -> Result<CommitInfo, GitBackendError>;
+
+    // === Remote Operations ===
+
+    /// Fetch a branch from a remote URL without configuring a remote.
+    ///
+    /// The branch is stored at `fetched_ref_name(url, branch)` so it can be
+    /// diffed like any local ref. Fetching again moves the ref to the
+    /// remote's latest commit.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - URL (or path) of the remote repository, e.g. a fork.
+    /// * `branch` - Branch name on the remote.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitBackendError::InvalidDiffSpec` if the branch name is
+    /// invalid, or `GitBackendError::FetchFailed` if the fetch fails.
+    fn fetch_branch(&self, url: &str, branch: &str) -> Result<ResolvedRef, GitBackendError>;
+
+    // === Notes ===
+    //
+    // Notes are named by a key rather than an object: each is attached to
+    // the ID `key` would have as a blob, which doesn't need to exist in the
+    // repository. Notes refs are pushed and fetched like any other ref.
+
+    /// Read the note named `key` under `notes_ref`.
+    ///
+    /// Returns `None` if there is no such note, or no `notes_ref` at all.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the notes can't be read.
+    fn read_note(&self, notes_ref: &str, key: &str) -> Result<Option<String>, GitBackendError>;
+
+    /// Write the note named `key` under `notes_ref`, replacing any note
+    /// already there, in a new commit on `notes_ref`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the note can't be written.
+    fn write_note(&self, notes_ref: &str, key: &str, note: &str) -> Result<(), GitBackendError>;
+
+    /// Remove the note named `key` from `notes_ref`. Returns whether there
+    /// was one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the note can't be removed.
+    fn remove_note(&self, notes_ref: &str, key: &str) -> Result<bool, GitBackendError>;
+
+    /// List the contents of every note under `notes_ref`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the notes can't be read.
+    fn list_notes(&self, notes_ref: &str) -> Result<Vec<String>, GitBackendError>;
+
     // === Repository Information ===
 
     /// Get the current HEAD SHA.
@@ -151,11 +399,51 @@ pub trait GitRepository: Send {
     /// Returns an error if HEAD is unborn (empty repository).
     fn head(&self) -> Result<String, GitBackendError>;
 
+    /// Get the operation in progress, such as a merge or rebase stopped on
+    /// conflicts, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository state cannot be read.
+    fn operation_in_progress(&self) -> Result<Option<RepoOperation>, GitBackendError>;
+
     /// Get the repository working directory.
     ///
     /// Returns `None` for bare repositories.
     fn workdir(&self) -> Option<&Path>;
 
+    /// List the worktrees of the repository, the main one first and then
+    /// the linked ones by path, like `git worktree list`. Linked worktrees
+    /// whose directory is gone are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the worktrees cannot be read.
+    fn list_worktrees(&self) -> Result<Vec<Worktree>, GitBackendError>;
+
+    /// List the authors of the commits reachable from HEAD, most commits
+    /// first, like `git shortlog -sne HEAD`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if HEAD is unborn or the history cannot be walked.
+    fn list_authors(&self) -> Result<Vec<Author>, GitBackendError>;
+
+    /// Get the identity new commits are made with.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `user.name` or `user.email` isn't configured.
+    fn identity(&self) -> Result<Identity, GitBackendError>;
+
+    /// List the paths of the files tracked in the index, relative to the
+    /// repository root and sorted. Submodules are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index cannot be read.
+    fn list_files(&self) -> Result<Vec<String>, GitBackendError>;
+
     /// Check if the working tree has uncommitted changes.
     ///
     /// This includes staged, unstaged, and untracked files.
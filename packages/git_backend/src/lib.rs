@@ -9,5 +9,5 @@
 
 mod backend;
 
-pub use backend::{GitBackend, GitRepository};
+pub use backend::{GitBackend, GitRepository, LazyDiff};
 pub use chadreview_git_backend_models::*;
@@ -7,14 +7,22 @@
 //! This crate provides a production-ready git backend using the `git2` crate,
 //! which wraps the `libgit2` C library.
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{DefaultHasher, Hash as _, Hasher as _};
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
 
-use chadreview_git_backend::{GitBackend, GitRepository};
+use chadreview_git_backend::{GitBackend, GitRepository, LazyDiff};
 use chadreview_git_backend_models::{
-    CommitInfo, DiffResult, DiffStatus, FileDiff, GitBackendError, RefType, ResolvedRef,
-    WorkingTreeDiffOptions,
+    Author, CommitInfo, CommitSignature, ConflictFile, DiffOptions, DiffResult, DiffStatus,
+    FETCHED_REF_PREFIX, FileDiff, FileStat, GitBackendError, Hunk, Identity, Line, LineOrigin,
+    RefType, RepoOperation, ResolvedRef, SignatureKind, SignatureVerification, SubmoduleDiff,
+    WorkingTreeDiffOptions, Worktree, fetched_ref_name,
 };
-use git2::{DiffOptions, Repository, StatusOptions};
+use git2::{Repository, RepositoryState, StatusOptions};
 
 /// git2-based implementation of `GitBackend`.
 #[derive(Debug, Clone, Default)]
@@ -44,6 +52,12 @@ impl GitBackend for Git2Backend {
     }
 }
 
+/// Hooks `git commit` runs, which committing through git2 would skip.
+const COMMIT_HOOKS: [&str; 3] = ["pre-commit", "prepare-commit-msg", "commit-msg"];
+
+/// File mode of submodule entries.
+const GITLINK_MODE: u32 = 0o160_000;
+
 /// git2-based implementation of `GitRepository`.
 struct Git2Repository {
     repo: Repository,
@@ -56,6 +70,149 @@ impl Git2Repository {
         Self { repo, workdir }
     }
 
+    /// The repository's index, reloaded if it changed on disk since it was
+    /// last read, e.g. by a merge or rebase run from elsewhere.
+    fn index(&self) -> Result<git2::Index, GitBackendError> {
+        let mut index = self.repo.index().map_err(|e| GitBackendError::GitError {
+            message: format!("Failed to read index: {e}"),
+        })?;
+        index.read(false).map_err(|e| GitBackendError::GitError {
+            message: format!("Failed to read index: {e}"),
+        })?;
+        Ok(index)
+    }
+
+    /// Fail if `path` is inside a submodule. The submodule's files belong
+    /// to its own repository, so this one's index and trees don't have them
+    /// and acting on them here would stage or delete the wrong thing.
+    fn check_outside_submodules(&self, path: &str) -> Result<(), GitBackendError> {
+        let index = self.index()?;
+        let submodule = Path::new(path)
+            .ancestors()
+            .skip(1)
+            .take_while(|ancestor| !ancestor.as_os_str().is_empty())
+            .find(|ancestor| {
+                index
+                    .get_path(ancestor, 0)
+                    .is_some_and(|entry| entry.mode == GITLINK_MODE)
+            });
+
+        match submodule {
+            Some(submodule) => Err(GitBackendError::GitError {
+                message: format!(
+                    "{path} is inside submodule {}, which has a repository of its own",
+                    submodule.display()
+                ),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn write_index(index: &mut git2::Index) -> Result<(), GitBackendError> {
+        index.write().map_err(|e| GitBackendError::GitError {
+            message: format!("Failed to write index: {e}"),
+        })
+    }
+
+    fn signature(&self) -> Result<git2::Signature<'static>, GitBackendError> {
+        self.repo
+            .signature()
+            .map_err(|e| GitBackendError::GitError {
+                message: format!("No identity configured (set user.name and user.email): {e}"),
+            })
+    }
+
+    fn resolve_to_tree(&self, spec: &str) -> Result<git2::Tree<'_>, GitBackendError> {
+        self.repo
+            .revparse_single(spec)
+            .and_then(|obj| obj.peel_to_tree())
+            .map_err(|_| GitBackendError::RefNotFound {
+                ref_name: spec.to_string(),
+            })
+    }
+
+    /// The directory shared by all worktrees of the repository. git2 doesn't
+    /// expose it, so it's read from a linked worktree's `commondir` file.
+    fn common_dir(&self) -> PathBuf {
+        let git_dir = self.repo.path();
+        std::fs::read_to_string(git_dir.join("commondir")).map_or_else(
+            |_| git_dir.to_path_buf(),
+            |common_dir| git_dir.join(common_dir.trim()),
+        )
+    }
+
+    /// Why committing here would skip part of what `git commit` does:
+    /// signing the commit or running the repository's commit hooks.
+    fn commit_blocker(&self) -> Result<Option<String>, GitBackendError> {
+        let config = self.repo.config().map_err(|e| GitBackendError::GitError {
+            message: format!("Failed to read config: {e}"),
+        })?;
+        if config.get_bool("commit.gpgsign").unwrap_or(false) {
+            return Ok(Some("commit.gpgsign is set".to_string()));
+        }
+
+        // A relative core.hooksPath is relative to where hooks run
+        let hooks_dir = config.get_path("core.hooksPath").map_or_else(
+            |_| self.common_dir().join("hooks"),
+            |path| {
+                self.workdir
+                    .as_deref()
+                    .unwrap_or_else(|| self.repo.path())
+                    .join(path)
+            },
+        );
+        Ok(COMMIT_HOOKS
+            .iter()
+            .find(|hook| is_executable(&hooks_dir.join(hook)))
+            .map(|hook| format!("the repository has a {hook} hook")))
+    }
+
+    /// Content of the blob of an index stage entry, or `None` for a binary
+    /// blob.
+    fn stage_content(&self, entry: &git2::IndexEntry) -> Result<Option<String>, GitBackendError> {
+        let blob = self
+            .repo
+            .find_blob(entry.id)
+            .map_err(|e| GitBackendError::GitError {
+                message: format!("Failed to read blob {}: {e}", entry.id),
+            })?;
+
+        if blob.is_binary() {
+            return Ok(None);
+        }
+        Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+    }
+
+    /// Describe the worktree at `path` that `repo` was opened at. It is the
+    /// current one when `path` is `current`, which is canonicalized.
+    fn worktree_info(
+        repo: &Repository,
+        path: &Path,
+        is_main: bool,
+        current: Option<&Path>,
+    ) -> Worktree {
+        let head = repo.head().ok();
+        let branch = head
+            .as_ref()
+            .filter(|head| head.is_branch())
+            .and_then(git2::Reference::shorthand)
+            .map(str::to_string);
+        let sha = head
+            .as_ref()
+            .and_then(git2::Reference::target)
+            .map(|oid| oid.to_string());
+        let is_current =
+            current.is_some_and(|current| path.canonicalize().is_ok_and(|path| path == current));
+
+        Worktree {
+            path: path.to_path_buf(),
+            branch,
+            head: sha,
+            is_main,
+            is_current,
+        }
+    }
+
     fn resolve_to_commit(&self, spec: &str) -> Result<git2::Commit<'_>, GitBackendError> {
         let obj = self
             .repo
@@ -80,6 +237,11 @@ impl Git2Repository {
         let author_email = author.email().unwrap_or("").to_string();
         let timestamp = commit.time().seconds();
         let parent_shas = commit.parent_ids().map(|id| id.to_string()).collect();
+        let signature = Self::signature_kind(commit).map(|kind| CommitSignature {
+            kind,
+            verification: SignatureVerification::Unchecked,
+            signer: None,
+        });
 
         CommitInfo {
             sha,
@@ -90,125 +252,478 @@ impl Git2Repository {
             author_email,
             timestamp,
             parent_shas,
+            signature,
         }
     }
 
-    fn diff_to_result(diff: &git2::Diff<'_>) -> DiffResult {
-        let mut files = Vec::new();
+    /// Kind of the signature of `commit`, or `None` if it isn't signed.
+    fn signature_kind(commit: &git2::Commit<'_>) -> Option<SignatureKind> {
+        let signature = commit.header_field_bytes("gpgsig").ok()?;
+        SignatureKind::from_armor(&String::from_utf8_lossy(&signature))
+    }
 
-        for (delta_idx, delta) in diff.deltas().enumerate() {
-            let old_path = delta
-                .old_file()
-                .path()
-                .map(|p| p.to_string_lossy().to_string());
-            let new_path = delta
-                .new_file()
-                .path()
-                .map(|p| p.to_string_lossy().to_string());
-
-            let status = match delta.status() {
-                git2::Delta::Added => DiffStatus::Added,
-                git2::Delta::Deleted => DiffStatus::Deleted,
-                git2::Delta::Renamed => DiffStatus::Renamed,
-                git2::Delta::Copied => DiffStatus::Copied,
-                git2::Delta::Untracked | git2::Delta::Ignored => DiffStatus::Untracked,
-                git2::Delta::Modified
-                | git2::Delta::Unmodified
-                | git2::Delta::Typechange
-                | git2::Delta::Unreadable
-                | git2::Delta::Conflicted => DiffStatus::Modified,
-            };
+    /// The diff between the trees of commits `old_sha` and `new_sha`,
+    /// without any file content loaded yet.
+    fn diff_trees(
+        &self,
+        old_sha: &str,
+        new_sha: &str,
+        options: DiffOptions,
+    ) -> Result<git2::Diff<'_>, GitBackendError> {
+        let old_tree = self.resolve_to_tree(old_sha)?;
+        let new_tree = self.resolve_to_tree(new_sha)?;
+
+        self.repo
+            .diff_tree_to_tree(
+                Some(&old_tree),
+                Some(&new_tree),
+                Some(&mut git_diff_options(options)),
+            )
+            .map_err(|e| GitBackendError::GitError {
+                message: e.to_string(),
+            })
+    }
 
-            let binary = delta.flags().is_binary();
+    /// The diff of the working tree, or only the index with
+    /// `options.staged_only`, against `against`, leaving out submodules'
+    /// changes.
+    fn working_tree_diff(
+        &self,
+        against: &str,
+        options: &WorkingTreeDiffOptions,
+    ) -> Result<git2::Diff<'_>, GitBackendError> {
+        let tree = self.resolve_to_tree(against)?;
 
-            // Get patch text
-            let patch = if binary {
-                None
-            } else {
-                Self::get_patch_text(diff, delta_idx)
+        let mut diff_opts = git_diff_options(options.diff);
+        if options.include_untracked {
+            diff_opts.include_untracked(true);
+            diff_opts.show_untracked_content(true);
+            diff_opts.recurse_untracked_dirs(true);
+        }
+        if options.include_ignored {
+            diff_opts.include_ignored(true);
+        }
+
+        if options.staged_only {
+            self.repo
+                .diff_tree_to_index(Some(&tree), None, Some(&mut diff_opts))
+        } else {
+            self.repo
+                .diff_tree_to_workdir_with_index(Some(&tree), Some(&mut diff_opts))
+        }
+        .map_err(|e| GitBackendError::GitError {
+            message: e.to_string(),
+        })
+    }
+
+    /// Convert `diff` to a [`DiffResult`], loading each file's hunks once
+    /// from its own patch so large diffs stay linear in their size.
+    fn diff_to_result(diff: &git2::Diff<'_>) -> Result<DiffResult, GitBackendError> {
+        let files = (0..diff.deltas().len())
+            .map(|delta_idx| Self::file_diff(diff, delta_idx))
+            .collect::<Result<_, _>>()?;
+
+        Ok(DiffResult {
+            files,
+            submodules: vec![],
+        })
+    }
+
+    /// Working tree diffs of the submodules with changes, each against the
+    /// commit checked out in it. Submodules that aren't checked out are
+    /// skipped.
+    fn submodule_diffs(
+        &self,
+        options: &WorkingTreeDiffOptions,
+    ) -> Result<Vec<SubmoduleDiff>, GitBackendError> {
+        let submodules = self
+            .repo
+            .submodules()
+            .map_err(|e| GitBackendError::GitError {
+                message: format!("Failed to list submodules: {e}"),
+            })?;
+
+        let mut diffs = vec![];
+        for submodule in submodules {
+            let Ok(repo) = submodule.open() else {
+                continue;
             };
+            if repo.head().is_err() {
+                continue;
+            }
 
-            files.push(FileDiff {
-                old_path,
-                new_path,
-                status,
-                patch,
-                binary,
-            });
+            let diff = Self::new(repo).diff_working_tree("HEAD", options.clone())?;
+            if !diff.files.is_empty() || !diff.submodules.is_empty() {
+                diffs.push(SubmoduleDiff {
+                    path: submodule.path().to_string_lossy().into_owned(),
+                    diff,
+                });
+            }
         }
+        Ok(diffs)
+    }
+
+    /// The file of `diff` at `delta_idx`, with its hunks.
+    fn file_diff(diff: &git2::Diff<'_>, delta_idx: usize) -> Result<FileDiff, GitBackendError> {
+        let (delta, patch) = Self::delta_patch(diff, delta_idx)?;
+        let (old_path, new_path) = delta_paths(&delta);
+
+        // Whether the file is binary is only known once its content is
+        // loaded by the patch
+        let binary = patch.as_ref().map_or_else(
+            || delta.flags().is_binary(),
+            |patch| patch.delta().flags().is_binary(),
+        );
+        let (hunks, decoded_lossy) = match &patch {
+            Some(patch) if !binary => {
+                Self::patch_hunks(patch).map_err(|e| GitBackendError::GitError {
+                    message: e.to_string(),
+                })?
+            }
+            _ => (Vec::new(), false),
+        };
+
+        Ok(FileDiff {
+            old_path,
+            new_path,
+            status: delta_status(delta.status()),
+            old_mode: file_mode(&delta.old_file()),
+            new_mode: file_mode(&delta.new_file()),
+            hunks,
+            binary,
+            decoded_lossy,
+            submodule: None,
+        })
+    }
+
+    /// The line counts of the file of `diff` at `delta_idx`, without
+    /// decoding its lines.
+    fn file_stat(diff: &git2::Diff<'_>, delta_idx: usize) -> Result<FileStat, GitBackendError> {
+        let (delta, patch) = Self::delta_patch(diff, delta_idx)?;
+        let (old_path, new_path) = delta_paths(&delta);
+
+        let (additions, deletions) = match &patch {
+            Some(patch) if !patch.delta().flags().is_binary() => {
+                let (_, additions, deletions) =
+                    patch.line_stats().map_err(|e| GitBackendError::GitError {
+                        message: e.to_string(),
+                    })?;
+                (additions as u64, deletions as u64)
+            }
+            _ => (0, 0),
+        };
+
+        Ok(FileStat {
+            old_path,
+            new_path,
+            status: delta_status(delta.status()),
+            additions,
+            deletions,
+        })
+    }
+
+    /// The delta of `diff` at `delta_idx` and its patch, which loads the
+    /// file's content.
+    fn delta_patch<'diff, 'repo>(
+        diff: &'diff git2::Diff<'repo>,
+        delta_idx: usize,
+    ) -> Result<(git2::DiffDelta<'diff>, Option<git2::Patch<'repo>>), GitBackendError> {
+        let delta = diff
+            .get_delta(delta_idx)
+            .ok_or_else(|| GitBackendError::GitError {
+                message: format!("Diff has no file {delta_idx}"),
+            })?;
+
+        let patch =
+            git2::Patch::from_diff(diff, delta_idx).map_err(|e| GitBackendError::GitError {
+                message: format!(
+                    "Failed to load diff of {}: {e}",
+                    delta
+                        .new_file()
+                        .path()
+                        .or_else(|| delta.old_file().path())
+                        .unwrap_or_else(|| Path::new(""))
+                        .display()
+                ),
+            })?;
 
-        DiffResult { files }
+        Ok((delta, patch))
     }
 
-    fn get_patch_text(diff: &git2::Diff<'_>, delta_idx: usize) -> Option<String> {
-        let mut patch_text = String::new();
+    /// The hunks of `patch`, leaving out the "No newline at end of file"
+    /// markers, and whether some of its lines weren't valid UTF-8, as
+    /// decoded with [`decode_lines`].
+    fn patch_hunks(patch: &git2::Patch<'_>) -> Result<(Vec<Hunk>, bool), git2::Error> {
+        let mut hunks = Vec::with_capacity(patch.num_hunks());
+        let mut contents = Vec::new();
+
+        for hunk_idx in 0..patch.num_hunks() {
+            let (hunk, line_count) = patch.hunk(hunk_idx)?;
+            let header = String::from_utf8_lossy(hunk.header());
+            let section = header
+                .splitn(3, "@@")
+                .nth(2)
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+
+            let mut lines = Vec::with_capacity(line_count);
+            for line_idx in 0..line_count {
+                let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+                let origin = match line.origin() {
+                    ' ' => LineOrigin::Context,
+                    '+' => LineOrigin::Addition,
+                    '-' => LineOrigin::Deletion,
+                    _ => continue,
+                };
+                contents.push(line.content().to_vec());
+
+                lines.push(Line {
+                    origin,
+                    old_lineno: line.old_lineno(),
+                    new_lineno: line.new_lineno(),
+                    content: String::new(),
+                });
+            }
 
-        let result = diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
-            // Check if this is for our delta
-            let current_idx = diff.deltas().position(|d| {
-                d.old_file().id() == delta.old_file().id()
-                    && d.new_file().id() == delta.new_file().id()
+            hunks.push(Hunk {
+                old_start: hunk.old_start(),
+                old_lines: hunk.old_lines(),
+                new_start: hunk.new_start(),
+                new_lines: hunk.new_lines(),
+                section,
+                lines,
             });
+        }
 
-            if current_idx != Some(delta_idx) {
-                return true;
+        let (decoded, decoded_lossy) = decode_lines(contents);
+        let lines = hunks.iter_mut().flat_map(|hunk| &mut hunk.lines);
+        for (line, mut content) in lines.zip(decoded) {
+            if content.ends_with('\n') {
+                content.pop();
             }
+            line.content = content;
+        }
 
-            let origin = line.origin();
-            match origin {
-                '+' | '-' | ' ' => {
-                    patch_text.push(origin);
-                    if let Ok(content) = std::str::from_utf8(line.content()) {
-                        patch_text.push_str(content);
-                    }
-                }
-                'H' => {
-                    // Hunk header
-                    if let Ok(content) = std::str::from_utf8(line.content()) {
-                        patch_text.push_str(content);
-                    }
-                }
-                _ => {}
+        Ok((hunks, decoded_lossy))
+    }
+
+    /// The object ID the note named `key` is attached to.
+    fn note_id(key: &str) -> Result<git2::Oid, GitBackendError> {
+        git2::Oid::hash_object(git2::ObjectType::Blob, key.as_bytes()).map_err(|e| {
+            GitBackendError::GitError {
+                message: format!("Failed to hash note key: {e}"),
             }
-            true
-        });
+        })
+    }
+
+    /// The signature notes are written with: the configured user, or a
+    /// placeholder if there is none.
+    fn note_signature(&self) -> Result<git2::Signature<'static>, GitBackendError> {
+        self.repo
+            .signature()
+            .or_else(|_| git2::Signature::now("chadreview", "chadreview@localhost"))
+            .map_err(|e| GitBackendError::GitError {
+                message: format!("Failed to create signature: {e}"),
+            })
+    }
+}
+
+/// Decode the lines of a file's content, and whether any of them wasn't
+/// valid UTF-8.
+///
+/// Each line is decoded on its own, so a file mixing encodings keeps its
+/// UTF-8 lines as they are. The others are decoded as Latin-1, where each
+/// byte is the character of the same code point, so they stay readable
+/// instead of being filled with replacement characters.
+fn decode_lines(lines: Vec<Vec<u8>>) -> (Vec<String>, bool) {
+    let mut lossy = false;
+    let decoded = lines
+        .into_iter()
+        .map(|line| {
+            String::from_utf8(line).unwrap_or_else(|e| {
+                lossy = true;
+                e.into_bytes().into_iter().map(char::from).collect()
+            })
+        })
+        .collect();
+    (decoded, lossy)
+}
+
+/// How long after a ref file or directory changed that its timestamp
+/// isn't trusted to tell it apart from a later change, as a quick second
+/// update may leave it the same.
+const RACY_REFS_WINDOW: Duration = Duration::from_secs(2);
+
+/// Fingerprint the refs of the repository with git directory `git_dir`,
+/// hashing the content of the ref files and directories changed since
+/// `racy_since`.
+///
+/// Every ref transaction renames a lock file into place, changing the
+/// timestamp of the ref file and of its directory, so the timestamps and
+/// sizes of `HEAD`, `packed-refs` and the directories under `refs/` tell
+/// whether any ref changed without reading every ref.
+fn refs_fingerprint_since(
+    git_dir: &Path,
+    common_dir: &Path,
+    racy_since: SystemTime,
+) -> std::io::Result<u64> {
+    let mut hasher = DefaultHasher::new();
+    hash_ref_file(&git_dir.join("HEAD"), racy_since, &mut hasher)?;
+    hash_ref_file(&common_dir.join("packed-refs"), racy_since, &mut hasher)?;
+    hash_ref_dir(&common_dir.join("refs"), racy_since, &mut hasher)?;
+    Ok(hasher.finish())
+}
+
+/// Hash the timestamp and size of the ref file at `path` into `hasher`,
+/// and its content too if it changed since `racy_since`.
+fn hash_ref_file(
+    path: &Path,
+    racy_since: SystemTime,
+    hasher: &mut DefaultHasher,
+) -> std::io::Result<()> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let modified = metadata.modified()?;
+    (modified, metadata.len()).hash(hasher);
+    if modified >= racy_since {
+        // A ref may be deleted while we read it
+        fs::read(path).unwrap_or_default().hash(hasher);
+    }
+    Ok(())
+}
 
-        if result.is_err() || patch_text.is_empty() {
-            return None;
+/// Hash the timestamps of `dir` and the directories under it into
+/// `hasher`, in a stable order, along with the paths and content of the
+/// files of those changed since `racy_since`.
+fn hash_ref_dir(
+    dir: &Path,
+    racy_since: SystemTime,
+    hasher: &mut DefaultHasher,
+) -> std::io::Result<()> {
+    let modified = fs::metadata(dir)?.modified()?;
+    modified.hash(hasher);
+    let racy = modified >= racy_since;
+
+    let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(fs::DirEntry::file_name);
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            hash_ref_dir(&path, racy_since, hasher)?;
+        } else if racy {
+            path.hash(hasher);
+            // A ref may be deleted while we walk the directory
+            fs::read(&path).unwrap_or_default().hash(hasher);
         }
+    }
+    Ok(())
+}
+
+/// The git2 options generating diffs with `options`.
+fn git_diff_options(options: DiffOptions) -> git2::DiffOptions {
+    let mut git_options = git2::DiffOptions::new();
+    if let Some(context_lines) = options.context_lines {
+        git_options.context_lines(context_lines);
+    }
+    git_options
+}
+
+/// Git mode of `file`, or `None` if it doesn't exist on its side of the
+/// diff.
+fn file_mode(file: &git2::DiffFile<'_>) -> Option<u32> {
+    Some(u32::from(file.mode())).filter(|&mode| mode != 0)
+}
+
+/// The old and new paths of `delta` (None on the side the file doesn't
+/// exist on).
+fn delta_paths(delta: &git2::DiffDelta<'_>) -> (Option<String>, Option<String>) {
+    let path = |file: git2::DiffFile<'_>| file.path().map(|p| p.to_string_lossy().to_string());
+    (path(delta.old_file()), path(delta.new_file()))
+}
+
+const fn delta_status(status: git2::Delta) -> DiffStatus {
+    match status {
+        git2::Delta::Added => DiffStatus::Added,
+        git2::Delta::Deleted => DiffStatus::Deleted,
+        git2::Delta::Renamed => DiffStatus::Renamed,
+        git2::Delta::Copied => DiffStatus::Copied,
+        git2::Delta::Untracked | git2::Delta::Ignored => DiffStatus::Untracked,
+        git2::Delta::Modified
+        | git2::Delta::Unmodified
+        | git2::Delta::Typechange
+        | git2::Delta::Unreadable
+        | git2::Delta::Conflicted => DiffStatus::Modified,
+    }
+}
+
+/// A diff whose files' hunks are loaded when asked for, followed by the
+/// already loaded changes inside submodules, as returned by
+/// [`GitRepository::diff_commits_lazy`] and
+/// [`GitRepository::diff_working_tree_lazy`].
+struct Git2LazyDiff<'repo> {
+    diff: git2::Diff<'repo>,
+    submodule_files: Vec<FileDiff>,
+}
+
+impl LazyDiff for Git2LazyDiff<'_> {
+    fn len(&self) -> usize {
+        self.diff.deltas().len() + self.submodule_files.len()
+    }
 
-        Some(patch_text)
+    fn stats(&self) -> Result<Vec<FileStat>, GitBackendError> {
+        let mut stats = (0..self.diff.deltas().len())
+            .map(|delta_idx| Git2Repository::file_stat(&self.diff, delta_idx))
+            .collect::<Result<Vec<_>, _>>()?;
+        stats.extend(self.submodule_files.iter().map(FileStat::from));
+        Ok(stats)
+    }
+
+    fn file(&self, index: usize) -> Result<FileDiff, GitBackendError> {
+        let deltas = self.diff.deltas().len();
+        if index < deltas {
+            return Git2Repository::file_diff(&self.diff, index);
+        }
+        self.submodule_files
+            .get(index - deltas)
+            .cloned()
+            .ok_or_else(|| GitBackendError::GitError {
+                message: format!("Diff has no file {index}"),
+            })
     }
 }
 
 impl GitRepository for Git2Repository {
     fn resolve_ref(&self, ref_name: &str) -> Result<ResolvedRef, GitBackendError> {
-        let commit = self.resolve_to_commit(ref_name)?;
+        // A single lookup finds the reference a short name means, in the
+        // same order revparse tries them, instead of probing each namespace
+        let reference = self.repo.resolve_reference_from_short_name(ref_name).ok();
+        let commit = match &reference {
+            Some(reference) => {
+                reference
+                    .peel_to_commit()
+                    .map_err(|_| GitBackendError::RefNotFound {
+                        ref_name: ref_name.to_string(),
+                    })?
+            }
+            None => self.resolve_to_commit(ref_name)?,
+        };
         let sha = commit.id().to_string();
 
-        // Try to determine ref type
         let ref_type = if ref_name == "HEAD" {
             RefType::Head
-        } else if self
-            .repo
-            .find_branch(ref_name, git2::BranchType::Local)
-            .is_ok()
-        {
-            RefType::Branch
-        } else if self
-            .repo
-            .find_branch(ref_name, git2::BranchType::Remote)
-            .is_ok()
-        {
+        } else if ref_name.starts_with(FETCHED_REF_PREFIX) {
             RefType::Remote
-        } else if self
-            .repo
-            .find_reference(&format!("refs/tags/{ref_name}"))
-            .is_ok()
-        {
-            RefType::Tag
         } else {
-            RefType::Commit
+            match &reference {
+                Some(reference) if reference.is_branch() => RefType::Branch,
+                Some(reference) if reference.is_remote() => RefType::Remote,
+                Some(reference) if reference.is_tag() => RefType::Tag,
+                _ => RefType::Commit,
+            }
         };
 
         Ok(ResolvedRef {
@@ -232,11 +747,51 @@ impl GitRepository for Git2Repository {
         Ok(merge_base.to_string())
     }
 
+    fn refs_fingerprint(&self) -> Result<u64, GitBackendError> {
+        Ok(refs_fingerprint_since(
+            self.repo.path(),
+            &self.common_dir(),
+            SystemTime::now()
+                .checked_sub(RACY_REFS_WINDOW)
+                .unwrap_or(SystemTime::UNIX_EPOCH),
+        )?)
+    }
+
     fn get_commit(&self, sha: &str) -> Result<CommitInfo, GitBackendError> {
         let commit = self.resolve_to_commit(sha)?;
         Ok(Self::commit_to_info(&commit))
     }
 
+    fn verify_signature(&self, sha: &str) -> Result<Option<CommitSignature>, GitBackendError> {
+        let commit = self.resolve_to_commit(sha)?;
+        let Some(kind) = Self::signature_kind(&commit) else {
+            return Ok(None);
+        };
+
+        // libgit2 can't verify signatures, so leave it to the git CLI and
+        // the signing tools it is configured with
+        let output = Command::new("git")
+            .arg("--git-dir")
+            .arg(self.repo.path())
+            .args(["verify-commit", "--raw"])
+            .arg(commit.id().to_string())
+            .output();
+        let (verification, signer) = match output {
+            Ok(output) => parse_verification(
+                kind,
+                output.status.success(),
+                &String::from_utf8_lossy(&output.stderr),
+            ),
+            Err(_) => (SignatureVerification::Unchecked, None),
+        };
+
+        Ok(Some(CommitSignature {
+            kind,
+            verification,
+            signer,
+        }))
+    }
+
     fn list_commits(&self, base: &str, head: &str) -> Result<Vec<CommitInfo>, GitBackendError> {
         let base_oid = self.resolve_to_commit(base)?.id();
         let head_oid = self.resolve_to_commit(head)?.id();
@@ -276,29 +831,28 @@ impl GitRepository for Git2Repository {
         Ok(commits)
     }
 
-    fn diff_commits(&self, old_sha: &str, new_sha: &str) -> Result<DiffResult, GitBackendError> {
-        let old_commit = self.resolve_to_commit(old_sha)?;
-        let new_commit = self.resolve_to_commit(new_sha)?;
-
-        let old_tree = old_commit.tree().map_err(|e| GitBackendError::GitError {
-            message: e.to_string(),
-        })?;
-
-        let new_tree = new_commit.tree().map_err(|e| GitBackendError::GitError {
-            message: e.to_string(),
-        })?;
-
-        let diff = self
-            .repo
-            .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)
-            .map_err(|e| GitBackendError::GitError {
-                message: e.to_string(),
-            })?;
+    fn diff_commits(
+        &self,
+        old_sha: &str,
+        new_sha: &str,
+        options: DiffOptions,
+    ) -> Result<DiffResult, GitBackendError> {
+        Self::diff_to_result(&self.diff_trees(old_sha, new_sha, options)?)
+    }
 
-        Ok(Self::diff_to_result(&diff))
+    fn diff_commits_lazy(
+        &self,
+        old_sha: &str,
+        new_sha: &str,
+        options: DiffOptions,
+    ) -> Result<Box<dyn LazyDiff + '_>, GitBackendError> {
+        Ok(Box::new(Git2LazyDiff {
+            diff: self.diff_trees(old_sha, new_sha, options)?,
+            submodule_files: vec![],
+        }))
     }
 
-    fn diff_commit(&self, sha: &str) -> Result<DiffResult, GitBackendError> {
+    fn diff_commit(&self, sha: &str, options: DiffOptions) -> Result<DiffResult, GitBackendError> {
         let commit = self.resolve_to_commit(sha)?;
         let tree = commit.tree().map_err(|e| GitBackendError::GitError {
             message: e.to_string(),
@@ -317,12 +871,16 @@ impl GitRepository for Git2Repository {
 
         let diff = self
             .repo
-            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+            .diff_tree_to_tree(
+                parent_tree.as_ref(),
+                Some(&tree),
+                Some(&mut git_diff_options(options)),
+            )
             .map_err(|e| GitBackendError::GitError {
                 message: e.to_string(),
             })?;
 
-        Ok(Self::diff_to_result(&diff))
+        Self::diff_to_result(&diff)
     }
 
     fn diff_working_tree(
@@ -330,79 +888,633 @@ impl GitRepository for Git2Repository {
         against: &str,
         options: WorkingTreeDiffOptions,
     ) -> Result<DiffResult, GitBackendError> {
-        let commit = self.resolve_to_commit(against)?;
-        let tree = commit.tree().map_err(|e| GitBackendError::GitError {
-            message: e.to_string(),
-        })?;
-
-        let mut diff_opts = DiffOptions::new();
-        if options.include_untracked {
-            diff_opts.include_untracked(true);
-            diff_opts.show_untracked_content(true);
-            diff_opts.recurse_untracked_dirs(true);
-        }
-        if options.include_ignored {
-            diff_opts.include_ignored(true);
+        let mut result = Self::diff_to_result(&self.working_tree_diff(against, &options)?)?;
+        if options.recurse_submodules {
+            result.submodules = self.submodule_diffs(&options)?;
         }
+        Ok(result)
+    }
 
-        let diff = if options.staged_only {
-            self.repo
-                .diff_tree_to_index(Some(&tree), None, Some(&mut diff_opts))
+    fn diff_working_tree_lazy(
+        &self,
+        against: &str,
+        options: WorkingTreeDiffOptions,
+    ) -> Result<Box<dyn LazyDiff + '_>, GitBackendError> {
+        let diff = self.working_tree_diff(against, &options)?;
+        let submodule_files = if options.recurse_submodules {
+            DiffResult {
+                files: vec![],
+                submodules: self.submodule_diffs(&options)?,
+            }
+            .flatten()
+            .files
         } else {
-            self.repo
-                .diff_tree_to_workdir_with_index(Some(&tree), Some(&mut diff_opts))
+            vec![]
+        };
+
+        Ok(Box::new(Git2LazyDiff {
+            diff,
+            submodule_files,
+        }))
+    }
+
+    fn stage_path(&self, path: &str) -> Result<(), GitBackendError> {
+        self.check_outside_submodules(path)?;
+        let mut index = self.index()?;
+        let in_workdir = self
+            .workdir
+            .as_ref()
+            .is_some_and(|workdir| workdir.join(path).symlink_metadata().is_ok());
+
+        if in_workdir {
+            index.add_path(Path::new(path))
+        } else {
+            index.remove_path(Path::new(path))
         }
         .map_err(|e| GitBackendError::GitError {
-            message: e.to_string(),
+            message: format!("Failed to stage {path}: {e}"),
         })?;
 
-        Ok(Self::diff_to_result(&diff))
+        Self::write_index(&mut index)
     }
 
-    fn head(&self) -> Result<String, GitBackendError> {
-        let head = self.repo.head().map_err(|e| GitBackendError::GitError {
-            message: format!("Failed to get HEAD: {e}"),
-        })?;
+    fn unstage_path(&self, path: &str) -> Result<(), GitBackendError> {
+        self.check_outside_submodules(path)?;
+        let head = self.repo.head().ok().and_then(|h| h.peel_to_commit().ok());
 
-        let commit = head
-            .peel_to_commit()
+        if let Some(head) = head {
+            return self
+                .repo
+                .reset_default(Some(head.as_object()), [path])
+                .map_err(|e| GitBackendError::GitError {
+                    message: format!("Failed to unstage {path}: {e}"),
+                });
+        }
+
+        // Nothing is committed yet, so unstaging leaves the file untracked
+        let mut index = self.index()?;
+        index
+            .remove_path(Path::new(path))
             .map_err(|e| GitBackendError::GitError {
-                message: format!("HEAD is not a commit: {e}"),
+                message: format!("Failed to unstage {path}: {e}"),
             })?;
-
-        Ok(commit.id().to_string())
+        Self::write_index(&mut index)
     }
 
-    fn workdir(&self) -> Option<&Path> {
-        self.workdir.as_deref()
-    }
+    fn checkout_path(&self, path: &str, against: &str) -> Result<(), GitBackendError> {
+        self.check_outside_submodules(path)?;
+        let commit = self.resolve_to_commit(against)?;
+        let tree = commit.tree().map_err(|e| GitBackendError::GitError {
+            message: e.to_string(),
+        })?;
 
-    fn is_dirty(&self) -> Result<bool, GitBackendError> {
-        let mut opts = StatusOptions::new();
-        opts.include_untracked(true);
-        opts.include_ignored(false);
+        if tree.get_path(Path::new(path)).is_ok() {
+            let mut checkout = git2::build::CheckoutBuilder::new();
+            checkout
+                .force()
+                .update_index(true)
+                .path(escape_pathspec(path));
+            return self
+                .repo
+                .checkout_tree(commit.as_object(), Some(&mut checkout))
+                .map_err(|e| GitBackendError::GitError {
+                    message: format!("Failed to check out {path}: {e}"),
+                });
+        }
 
-        let statuses =
-            self.repo
-                .statuses(Some(&mut opts))
+        let mut index = self.index()?;
+        if index.get_path(Path::new(path), 0).is_some() {
+            index
+                .remove_path(Path::new(path))
                 .map_err(|e| GitBackendError::GitError {
-                    message: format!("Failed to get status: {e}"),
+                    message: format!("Failed to remove {path} from the index: {e}"),
                 })?;
+            Self::write_index(&mut index)?;
+        }
+        if let Some(workdir) = &self.workdir {
+            match std::fs::remove_file(workdir.join(path)) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(GitBackendError::IoError {
+                        message: format!("Failed to remove {path}: {e}"),
+                    });
+                }
+            }
+        }
 
-        Ok(!statuses.is_empty())
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
+    fn apply_patch_to_index(&self, patch: &str) -> Result<(), GitBackendError> {
+        let patch = with_git_header(patch);
+        let diff = git2::Diff::from_buffer(patch.as_bytes()).map_err(|e| {
+            GitBackendError::PatchFailed {
+                message: e.message().to_string(),
+            }
+        })?;
 
-    fn create_test_repo() -> (tempfile::TempDir, Repository) {
-        let dir = tempfile::tempdir().unwrap();
-        let repo = Repository::init(dir.path()).unwrap();
+        self.repo
+            .apply(&diff, git2::ApplyLocation::Index, None)
+            .map_err(|e| GitBackendError::PatchFailed {
+                message: e.message().to_string(),
+            })
+    }
 
-        // Configure user for commits
+    fn list_conflicts(&self) -> Result<Vec<ConflictFile>, GitBackendError> {
+        let index = self.index()?;
+        let conflicts = index.conflicts().map_err(|e| GitBackendError::GitError {
+            message: format!("Failed to read conflicts: {e}"),
+        })?;
+
+        let mut files = Vec::new();
+        for conflict in conflicts {
+            let conflict = conflict.map_err(|e| GitBackendError::GitError {
+                message: format!("Failed to read conflicts: {e}"),
+            })?;
+            let Some(path) = [&conflict.our, &conflict.their, &conflict.ancestor]
+                .into_iter()
+                .flatten()
+                .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+                .next()
+            else {
+                continue;
+            };
+
+            let mut binary = false;
+            let mut side = |entry: Option<&git2::IndexEntry>| -> Result<_, GitBackendError> {
+                let Some(entry) = entry else {
+                    return Ok(None);
+                };
+                let content = self.stage_content(entry)?;
+                binary |= content.is_none();
+                Ok(content)
+            };
+            let base = side(conflict.ancestor.as_ref())?;
+            let ours = side(conflict.our.as_ref())?;
+            let theirs = side(conflict.their.as_ref())?;
+
+            files.push(ConflictFile {
+                path,
+                base,
+                ours,
+                theirs,
+                binary,
+            });
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(files)
+    }
+
+    fn commit(&self, message: &str, amend: bool) -> Result<CommitInfo, GitBackendError> {
+        if let Some(reason) = self.commit_blocker()? {
+            return Err(GitBackendError::GitError {
+                message: format!("Can't commit here because {reason}; commit with git instead"),
+            });
+        }
+
+        let signature = self.signature()?;
+        let tree_id = self
+            .index()?
+            .write_tree()
+            .map_err(|e| GitBackendError::GitError {
+                message: format!("Failed to write tree: {e}"),
+            })?;
+        let tree = self
+            .repo
+            .find_tree(tree_id)
+            .map_err(|e| GitBackendError::GitError {
+                message: e.to_string(),
+            })?;
+        let head = self.repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+
+        let oid = if amend {
+            let head = head.ok_or_else(|| GitBackendError::GitError {
+                message: "No commit to amend".to_string(),
+            })?;
+            head.amend(
+                Some("HEAD"),
+                None,
+                Some(&signature),
+                None,
+                Some(message),
+                Some(&tree),
+            )
+        } else {
+            if head
+                .as_ref()
+                .map_or(tree.is_empty(), |h| h.tree_id() == tree_id)
+            {
+                return Err(GitBackendError::GitError {
+                    message: "Nothing staged to commit".to_string(),
+                });
+            }
+            let parents: Vec<_> = head.iter().collect();
+            self.repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &parents,
+            )
+        }
+        .map_err(|e| GitBackendError::GitError {
+            message: format!("Failed to commit: {e}"),
+        })?;
+
+        let commit = self
+            .repo
+            .find_commit(oid)
+            .map_err(|e| GitBackendError::CommitNotFound {
+                sha: format!("{oid}: {e}"),
+            })?;
+        Ok(Self::commit_to_info(&commit))
+    }
+
+    fn fetch_branch(&self, url: &str, branch: &str) -> Result<ResolvedRef, GitBackendError> {
+        let local_ref = fetched_ref_name(url, branch);
+        if !git2::Reference::is_valid_name(&format!("refs/heads/{branch}"))
+            || !git2::Reference::is_valid_name(&local_ref)
+        {
+            return Err(GitBackendError::InvalidDiffSpec {
+                message: format!("Invalid branch name: {branch}"),
+            });
+        }
+
+        let fetch_error = |e: git2::Error| GitBackendError::FetchFailed {
+            url: url.to_string(),
+            branch: branch.to_string(),
+            message: e.message().to_string(),
+        };
+
+        // An anonymous remote is never written to the repository config
+        let mut remote = self.repo.remote_anonymous(url).map_err(fetch_error)?;
+        let refspec = format!("+refs/heads/{branch}:{local_ref}");
+        remote
+            .fetch(&[refspec.as_str()], None, None)
+            .map_err(fetch_error)?;
+
+        // Fetching a branch the remote doesn't have succeeds without
+        // creating the local ref
+        let sha =
+            self.repo
+                .refname_to_id(&local_ref)
+                .map_err(|_| GitBackendError::FetchFailed {
+                    url: url.to_string(),
+                    branch: branch.to_string(),
+                    message: "branch not found on remote".to_string(),
+                })?;
+
+        Ok(ResolvedRef {
+            sha: sha.to_string(),
+            name: local_ref,
+            ref_type: RefType::Remote,
+        })
+    }
+
+    fn read_note(&self, notes_ref: &str, key: &str) -> Result<Option<String>, GitBackendError> {
+        match self.repo.find_note(Some(notes_ref), Self::note_id(key)?) {
+            Ok(note) => Ok(Some(
+                String::from_utf8_lossy(note.message_bytes()).into_owned(),
+            )),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(GitBackendError::GitError {
+                message: format!("Failed to read note: {e}"),
+            }),
+        }
+    }
+
+    fn write_note(&self, notes_ref: &str, key: &str, note: &str) -> Result<(), GitBackendError> {
+        let sig = self.note_signature()?;
+        self.repo
+            .note(&sig, &sig, Some(notes_ref), Self::note_id(key)?, note, true)
+            .map_err(|e| GitBackendError::GitError {
+                message: format!("Failed to write note: {e}"),
+            })?;
+        Ok(())
+    }
+
+    fn remove_note(&self, notes_ref: &str, key: &str) -> Result<bool, GitBackendError> {
+        let sig = self.note_signature()?;
+        match self
+            .repo
+            .note_delete(Self::note_id(key)?, Some(notes_ref), &sig, &sig)
+        {
+            Ok(()) => Ok(true),
+            Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(false),
+            Err(e) => Err(GitBackendError::GitError {
+                message: format!("Failed to remove note: {e}"),
+            }),
+        }
+    }
+
+    fn list_notes(&self, notes_ref: &str) -> Result<Vec<String>, GitBackendError> {
+        let notes_error = |e: git2::Error| GitBackendError::GitError {
+            message: format!("Failed to list notes: {e}"),
+        };
+
+        let notes = match self.repo.notes(Some(notes_ref)) {
+            Ok(notes) => notes,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(vec![]),
+            Err(e) => return Err(notes_error(e)),
+        };
+
+        let mut contents = Vec::new();
+        for entry in notes {
+            let (blob_id, _) = entry.map_err(notes_error)?;
+            let blob = self.repo.find_blob(blob_id).map_err(notes_error)?;
+            contents.push(String::from_utf8_lossy(blob.content()).into_owned());
+        }
+
+        Ok(contents)
+    }
+
+    fn head(&self) -> Result<String, GitBackendError> {
+        let head = self.repo.head().map_err(|e| GitBackendError::GitError {
+            message: format!("Failed to get HEAD: {e}"),
+        })?;
+
+        let commit = head
+            .peel_to_commit()
+            .map_err(|e| GitBackendError::GitError {
+                message: format!("HEAD is not a commit: {e}"),
+            })?;
+
+        Ok(commit.id().to_string())
+    }
+
+    fn operation_in_progress(&self) -> Result<Option<RepoOperation>, GitBackendError> {
+        Ok(match self.repo.state() {
+            RepositoryState::Merge => Some(RepoOperation::Merge),
+            RepositoryState::Rebase
+            | RepositoryState::RebaseInteractive
+            | RepositoryState::RebaseMerge
+            | RepositoryState::ApplyMailboxOrRebase => Some(RepoOperation::Rebase),
+            RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+                Some(RepoOperation::CherryPick)
+            }
+            RepositoryState::Revert | RepositoryState::RevertSequence => {
+                Some(RepoOperation::Revert)
+            }
+            RepositoryState::Clean | RepositoryState::Bisect | RepositoryState::ApplyMailbox => {
+                None
+            }
+        })
+    }
+
+    fn workdir(&self) -> Option<&Path> {
+        self.workdir.as_deref()
+    }
+
+    fn list_worktrees(&self) -> Result<Vec<Worktree>, GitBackendError> {
+        // The main worktree is found from the common directory, wherever the
+        // repository was opened
+        let main = Repository::open(self.common_dir()).map_err(|e| GitBackendError::GitError {
+            message: format!("Failed to open main worktree: {e}"),
+        })?;
+        let current = self
+            .workdir
+            .as_deref()
+            .and_then(|workdir| workdir.canonicalize().ok());
+
+        let mut worktrees = Vec::new();
+        if let Some(workdir) = main.workdir() {
+            worktrees.push(Self::worktree_info(
+                &main,
+                workdir,
+                true,
+                current.as_deref(),
+            ));
+        }
+
+        let names = main.worktrees().map_err(|e| GitBackendError::GitError {
+            message: format!("Failed to list worktrees: {e}"),
+        })?;
+        let mut linked = Vec::new();
+        for name in names.iter().flatten() {
+            let Ok(worktree) = main.find_worktree(name) else {
+                continue;
+            };
+            // Worktrees whose directory was removed without pruning them
+            if worktree.validate().is_err() {
+                continue;
+            }
+            let Ok(repo) = Repository::open_from_worktree(&worktree) else {
+                continue;
+            };
+            linked.push(Self::worktree_info(
+                &repo,
+                worktree.path(),
+                false,
+                current.as_deref(),
+            ));
+        }
+        linked.sort_by(|a, b| a.path.cmp(&b.path));
+        worktrees.extend(linked);
+
+        Ok(worktrees)
+    }
+
+    fn list_authors(&self) -> Result<Vec<Author>, GitBackendError> {
+        let mut revwalk = self.repo.revwalk().map_err(|e| GitBackendError::GitError {
+            message: e.to_string(),
+        })?;
+
+        revwalk.push_head().map_err(|e| GitBackendError::GitError {
+            message: format!("Failed to get HEAD: {e}"),
+        })?;
+
+        let mut counts: HashMap<(String, String), usize> = HashMap::new();
+        for oid_result in revwalk {
+            let oid = oid_result.map_err(|e| GitBackendError::GitError {
+                message: e.to_string(),
+            })?;
+
+            let commit =
+                self.repo
+                    .find_commit(oid)
+                    .map_err(|e| GitBackendError::CommitNotFound {
+                        sha: format!("{oid}: {e}"),
+                    })?;
+            let author = commit.author();
+            let key = (
+                author.name().unwrap_or("").to_string(),
+                author.email().unwrap_or("").to_string(),
+            );
+            *counts.entry(key).or_default() += 1;
+        }
+
+        let mut authors: Vec<_> = counts
+            .into_iter()
+            .map(|((name, email), commits)| Author {
+                name,
+                email,
+                commits,
+            })
+            .collect();
+        authors.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.name.cmp(&b.name)));
+
+        Ok(authors)
+    }
+
+    fn identity(&self) -> Result<Identity, GitBackendError> {
+        let signature = self.signature()?;
+        Ok(Identity {
+            name: signature.name().unwrap_or("").to_string(),
+            email: signature.email().unwrap_or("").to_string(),
+        })
+    }
+
+    fn list_files(&self) -> Result<Vec<String>, GitBackendError> {
+        let index = self.index()?;
+
+        let mut files: Vec<String> = index
+            .iter()
+            .filter(|entry| entry.mode != GITLINK_MODE)
+            .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            .collect();
+        // Conflicted files have an entry per stage
+        files.dedup();
+
+        Ok(files)
+    }
+
+    fn is_dirty(&self) -> Result<bool, GitBackendError> {
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(true);
+        opts.include_ignored(false);
+
+        let statuses =
+            self.repo
+                .statuses(Some(&mut opts))
+                .map_err(|e| GitBackendError::GitError {
+                    message: format!("Failed to get status: {e}"),
+                })?;
+
+        Ok(!statuses.is_empty())
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Prefix a plain unified diff with the `diff --git` header libgit2 needs to
+/// parse it.
+fn with_git_header(patch: &str) -> Cow<'_, str> {
+    if patch.starts_with("diff --git ") {
+        return Cow::Borrowed(patch);
+    }
+
+    patch
+        .lines()
+        .find_map(|line| {
+            line.strip_prefix("+++ b/")
+                .or_else(|| line.strip_prefix("--- a/"))
+        })
+        .map_or(Cow::Borrowed(patch), |filename| {
+            Cow::Owned(format!("diff --git a/{filename} b/{filename}\n{patch}"))
+        })
+}
+
+/// Escape a path so a checkout pathspec matches it literally rather than as
+/// a glob or negation.
+fn escape_pathspec(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for (i, c) in path.chars().enumerate() {
+        if matches!(c, '*' | '?' | '[' | '\\') || (i == 0 && c == '!') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Parse the output of `git verify-commit --raw` for a signature of `kind`
+/// into its verification and signer.
+///
+/// GPG and X.509 signatures are verified by `gpg`/`gpgsm`, which report
+/// `[GNUPG:]` status lines. SSH ones are verified by `ssh-keygen`, which
+/// names the principal matched in the allowed signers file.
+fn parse_verification(
+    kind: SignatureKind,
+    success: bool,
+    output: &str,
+) -> (SignatureVerification, Option<String>) {
+    if kind == SignatureKind::Ssh {
+        let signer = output
+            .lines()
+            .find_map(|line| line.strip_prefix("Good \"git\" signature for "))
+            .and_then(|rest| rest.split_once(" with "))
+            .map(|(principal, _)| principal.to_string());
+        let verification = if success {
+            SignatureVerification::Good
+        } else if output.contains("No principal matched") || output.contains("allowedSignersFile") {
+            SignatureVerification::UnknownKey
+        } else if output.contains("cannot run") {
+            SignatureVerification::Unchecked
+        } else {
+            SignatureVerification::Bad
+        };
+        return (verification, signer);
+    }
+
+    let mut verification = SignatureVerification::Unchecked;
+    let mut signer = None;
+    for line in output.lines() {
+        let Some(status) = line.strip_prefix("[GNUPG:] ") else {
+            continue;
+        };
+        let mut fields = status.splitn(3, ' ');
+        let keyword = fields.next().unwrap_or_default();
+        let user_id = fields.nth(1).map(str::to_string);
+
+        match keyword {
+            "GOODSIG" => {
+                verification = if success {
+                    SignatureVerification::Good
+                } else {
+                    // Good, but from a key below `gpg.minTrustLevel`
+                    SignatureVerification::UnknownKey
+                };
+                signer = user_id;
+            }
+            "BADSIG" => {
+                return (SignatureVerification::Bad, user_id);
+            }
+            "EXPSIG" | "EXPKEYSIG" | "REVKEYSIG" => {
+                return (SignatureVerification::UnknownKey, user_id);
+            }
+            "ERRSIG" | "NO_PUBKEY" => {
+                verification = SignatureVerification::UnknownKey;
+            }
+            _ => {}
+        }
+    }
+
+    (verification, signer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_repo() -> (tempfile::TempDir, Repository) {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        // Configure user for commits
         let mut config = repo.config().unwrap();
         config.set_str("user.name", "Test User").unwrap();
         config.set_str("user.email", "test@example.com").unwrap();
@@ -481,7 +1593,7 @@ mod tests {
         let backend = Git2Backend::new();
         let git_repo = backend.open(dir.path()).unwrap();
 
-        let result = git_repo.diff_commits(&first_sha, &second_sha);
+        let result = git_repo.diff_commits(&first_sha, &second_sha, DiffOptions::default());
         assert!(result.is_ok());
 
         let diff = result.unwrap();
@@ -490,6 +1602,344 @@ mod tests {
         assert_eq!(diff.files[0].status, DiffStatus::Modified);
     }
 
+    #[test]
+    fn test_diff_hunks() {
+        let (dir, repo) = create_test_repo();
+        let first = create_commit(&repo, "Initial", &[("file.txt", "one\ntwo\nthree\n")]);
+        let second = create_commit(&repo, "Edit", &[("file.txt", "one\n2\nthree\nfour\n")]);
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        let diff = git_repo
+            .diff_commits(
+                &first.to_string(),
+                &second.to_string(),
+                DiffOptions::default(),
+            )
+            .unwrap();
+
+        let file = &diff.files[0];
+        assert_eq!(file.hunks.len(), 1);
+        let hunk = &file.hunks[0];
+        assert_eq!(
+            (
+                hunk.old_start,
+                hunk.old_lines,
+                hunk.new_start,
+                hunk.new_lines
+            ),
+            (1, 3, 1, 4)
+        );
+
+        let lines: Vec<_> = hunk
+            .lines
+            .iter()
+            .map(|line| {
+                (
+                    line.origin,
+                    line.old_lineno,
+                    line.new_lineno,
+                    line.content.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (LineOrigin::Context, Some(1), Some(1), "one"),
+                (LineOrigin::Deletion, Some(2), None, "two"),
+                (LineOrigin::Addition, None, Some(2), "2"),
+                (LineOrigin::Context, Some(3), Some(3), "three"),
+                (LineOrigin::Addition, None, Some(4), "four"),
+            ]
+        );
+        assert_eq!(
+            file.patch().as_deref(),
+            Some("@@ -1,3 +1,4 @@\n one\n-two\n+2\n three\n+four\n")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_diff_mode_change() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let (dir, repo) = create_test_repo();
+        let first = create_commit(&repo, "Initial", &[("run.sh", "echo hi\n")]);
+        let script = dir.path().join("run.sh");
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let second = create_commit(&repo, "Make executable", &[("run.sh", "echo hi\n")]);
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        let diff = git_repo
+            .diff_commits(
+                &first.to_string(),
+                &second.to_string(),
+                DiffOptions::default(),
+            )
+            .unwrap();
+
+        assert_eq!(diff.files.len(), 1);
+        let file = &diff.files[0];
+        assert!(file.hunks.is_empty());
+        assert_eq!(file.mode_change(), Some((0o100_644, 0o100_755)));
+    }
+
+    #[test]
+    fn test_diff_latin1() {
+        let (dir, repo) = create_test_repo();
+        create_commit(&repo, "Initial", &[("notes.txt", "cafe\n")]);
+        // The UTF-8 line of the mixed file stays UTF-8
+        fs::write(
+            dir.path().join("notes.txt"),
+            b"caf\xe9\nna\xefve\n\xc3\xa9t\xc3\xa9\n",
+        )
+        .unwrap();
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        let diff = git_repo
+            .diff_working_tree("HEAD", WorkingTreeDiffOptions::default())
+            .unwrap();
+
+        let file = &diff.files[0];
+        assert!(file.decoded_lossy);
+        let added: Vec<_> = file.hunks[0]
+            .lines
+            .iter()
+            .filter(|line| line.origin == LineOrigin::Addition)
+            .map(|line| line.content.as_str())
+            .collect();
+        assert_eq!(added, vec!["café", "naïve", "été"]);
+    }
+
+    #[test]
+    fn test_diff_working_tree_submodules() {
+        let (dir, repo) = create_test_repo();
+        create_commit(&repo, "Initial", &[("README.md", "# Test\n")]);
+
+        let lib_repo = Repository::init(dir.path().join("lib")).unwrap();
+        let mut config = lib_repo.config().unwrap();
+        config.set_str("user.name", "Test User").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        create_commit(&lib_repo, "Initial", &[("lib.rs", "fn lib() {}\n")]);
+
+        let mut submodule = repo
+            .submodule("https://example.com/lib.git", Path::new("lib"), false)
+            .unwrap();
+        submodule.add_finalize().unwrap();
+        create_commit(&repo, "Add lib", &[]);
+        fs::write(dir.path().join("lib/lib.rs"), "fn lib() {}\nfn more() {}\n").unwrap();
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+
+        let diff = git_repo
+            .diff_working_tree("HEAD", WorkingTreeDiffOptions::default())
+            .unwrap();
+        assert!(diff.submodules.is_empty());
+
+        let options = WorkingTreeDiffOptions {
+            recurse_submodules: true,
+            ..WorkingTreeDiffOptions::default()
+        };
+        let diff = git_repo.diff_working_tree("HEAD", options.clone()).unwrap();
+        assert_eq!(diff.submodules.len(), 1);
+        assert_eq!(diff.submodules[0].path, "lib");
+        assert_eq!(diff.submodules[0].diff.files[0].path(), Some("lib.rs"));
+
+        let flat = diff.flatten();
+        assert!(flat.submodules.is_empty());
+        let lib_file = flat
+            .files
+            .iter()
+            .find(|file| file.path() == Some("lib/lib.rs"))
+            .unwrap();
+        assert_eq!(lib_file.submodule.as_deref(), Some("lib"));
+
+        let lazy = git_repo.diff_working_tree_lazy("HEAD", options).unwrap();
+        assert_eq!(lazy.len(), flat.files.len());
+        let stats = lazy.stats().unwrap();
+        let index = stats
+            .iter()
+            .position(|stat| stat.path() == Some("lib/lib.rs"))
+            .unwrap();
+        assert_eq!((stats[index].additions, stats[index].deletions), (1, 0));
+        assert_eq!(lazy.file(index).unwrap().path(), Some("lib/lib.rs"));
+
+        // The submodule's files aren't this repository's to stage or restore
+        assert!(git_repo.stage_path("lib/lib.rs").is_err());
+        assert!(git_repo.checkout_path("lib/lib.rs", "HEAD").is_err());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("lib/lib.rs")).unwrap(),
+            "fn lib() {}\nfn more() {}\n"
+        );
+    }
+
+    #[test]
+    fn test_decode_lines() {
+        assert_eq!(
+            decode_lines(vec!["naïve\n".as_bytes().to_vec()]),
+            (vec!["naïve\n".to_string()], false)
+        );
+        assert_eq!(
+            decode_lines(vec![b"caf\xe9".to_vec(), "é".as_bytes().to_vec()]),
+            (vec!["café".to_string(), "é".to_string()], true)
+        );
+    }
+
+    #[test]
+    fn test_diff_commits_lazy() {
+        let (dir, repo) = create_test_repo();
+        let first = create_commit(&repo, "Initial", &[("a.txt", "a"), ("b.txt", "b")]);
+        let second = create_commit(
+            &repo,
+            "Edit",
+            &[("a.txt", "a2\nmore"), ("b.txt", "b2"), ("c.txt", "c")],
+        );
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        let (first, second) = (first.to_string(), second.to_string());
+
+        let diff = git_repo
+            .diff_commits_lazy(&first, &second, DiffOptions::default())
+            .unwrap();
+        assert_eq!(diff.len(), 3);
+        let stats = diff.stats().unwrap();
+        let paths: Vec<_> = stats.iter().map(|stat| stat.path().unwrap()).collect();
+        assert_eq!(paths, vec!["a.txt", "b.txt", "c.txt"]);
+        assert_eq!((stats[0].additions, stats[0].deletions), (2, 1));
+        assert_eq!(stats[2].status, DiffStatus::Added);
+
+        let file = diff.file(2).unwrap();
+        assert_eq!(file.new_path.as_deref(), Some("c.txt"));
+        assert_eq!(file.hunks[0].lines[0].content, "c");
+        assert!(diff.file(3).is_err());
+
+        // A root commit's changes, against the empty tree
+        let root = git_repo
+            .diff_commits_lazy(
+                "4b825dc642cb6eb9a060e54bf8d69288fbee4904",
+                &first,
+                DiffOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(root.len(), 2);
+
+        let diff = git_repo
+            .diff_commits(&first, &second, DiffOptions::default())
+            .unwrap();
+        assert_eq!(diff.files.len(), 3);
+    }
+
+    #[test]
+    fn test_diff_context_lines() {
+        let (dir, repo) = create_test_repo();
+        let first = create_commit(&repo, "Initial", &[("file.txt", "1\n2\n3\n4\n5\n6\n7\n")]);
+        let second = create_commit(&repo, "Edit", &[("file.txt", "1\n2\n3\nfour\n5\n6\n7\n")]);
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        let hunk_lines = |context_lines| {
+            let options = DiffOptions { context_lines };
+            let diff = git_repo
+                .diff_commits(&first.to_string(), &second.to_string(), options)
+                .unwrap();
+            diff.files[0].hunks[0].lines.len()
+        };
+
+        assert_eq!(hunk_lines(None), 8);
+        assert_eq!(hunk_lines(Some(1)), 4);
+        assert_eq!(hunk_lines(Some(0)), 2);
+    }
+
+    /// A repository with two commits, the second editing every one of the
+    /// `files` files added by the first, and the SHAs of both.
+    fn create_many_files_repo(files: usize) -> (tempfile::TempDir, String, String) {
+        let (dir, repo) = create_test_repo();
+        let contents = |version: &str| -> Vec<(String, String)> {
+            (0..files)
+                .map(|i| {
+                    (
+                        format!("src/file_{i}.txt"),
+                        format!("{i}\nfirst\n{version}\nlast\n"),
+                    )
+                })
+                .collect()
+        };
+        let commit = |message: &str, files: &[(String, String)]| {
+            let files: Vec<(&str, &str)> = files
+                .iter()
+                .map(|(path, content)| (path.as_str(), content.as_str()))
+                .collect();
+            create_commit(&repo, message, &files)
+        };
+        let first = commit("Initial", &contents("old"));
+        let second = commit("Edit every file", &contents("new"));
+
+        (dir, first.to_string(), second.to_string())
+    }
+
+    #[test]
+    fn test_diff_many_files() {
+        const FILES: usize = 1000;
+
+        let (dir, first, second) = create_many_files_repo(FILES);
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+
+        let diff = git_repo
+            .diff_commits(&first, &second, DiffOptions::default())
+            .unwrap();
+
+        assert_eq!(diff.files.len(), FILES);
+        for file in &diff.files {
+            let index = file.new_path.as_deref().unwrap();
+            let index = index
+                .trim_start_matches("src/file_")
+                .trim_end_matches(".txt");
+            assert_eq!(file.hunks.len(), 1);
+            assert_eq!(file.hunks[0].lines[0].content, index);
+            assert_eq!(file.hunks[0].lines.len(), 5);
+        }
+    }
+
+    #[test]
+    fn test_diff_cost_grows_linearly_with_files() {
+        const FILES: usize = 1000;
+
+        let backend = Git2Backend::new();
+        // The fastest of a few runs, to keep other load on the machine out
+        // of the comparison
+        let time_diff = |files: usize| {
+            let (dir, first, second) = create_many_files_repo(files);
+            let git_repo = backend.open(dir.path()).unwrap();
+            (0..3)
+                .map(|_| {
+                    let started = std::time::Instant::now();
+                    git_repo
+                        .diff_commits(&first, &second, DiffOptions::default())
+                        .unwrap();
+                    started.elapsed()
+                })
+                .min()
+                .unwrap()
+        };
+
+        let small = time_diff(FILES / 4);
+        let large = time_diff(FILES);
+
+        // Four times the files take about four times as long when each file
+        // costs the same, and sixteen times when each one rescans the diff
+        assert!(
+            large < small * 10,
+            "diffing {FILES} files took {large:?}, {} files took {small:?}",
+            FILES / 4
+        );
+    }
+
     #[test]
     fn test_list_commits() {
         let (dir, repo) = create_test_repo();
@@ -515,6 +1965,30 @@ mod tests {
         assert_eq!(commits[1].summary, "Second");
     }
 
+    #[test]
+    fn test_list_authors() {
+        let (dir, repo) = create_test_repo();
+
+        create_commit(&repo, "First", &[("a.txt", "a")]);
+        create_commit(&repo, "Second", &[("b.txt", "b")]);
+
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Other User").unwrap();
+        config.set_str("user.email", "other@example.com").unwrap();
+        create_commit(&repo, "Third", &[("c.txt", "c")]);
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+
+        let authors = git_repo.list_authors().unwrap();
+        assert_eq!(authors.len(), 2);
+        assert_eq!(authors[0].name, "Test User");
+        assert_eq!(authors[0].email, "test@example.com");
+        assert_eq!(authors[0].commits, 2);
+        assert_eq!(authors[1].name, "Other User");
+        assert_eq!(authors[1].commits, 1);
+    }
+
     #[test]
     fn test_is_dirty() {
         let (dir, repo) = create_test_repo();
@@ -530,4 +2004,548 @@ mod tests {
         fs::write(dir.path().join("file.txt"), "modified").unwrap();
         assert!(git_repo.is_dirty().unwrap());
     }
+
+    #[test]
+    fn test_fetch_branch() {
+        let (fork_dir, fork) = create_test_repo();
+        create_commit(&fork, "Fork commit", &[("fork.txt", "fork")]);
+        let fork_head = fork.head().unwrap().peel_to_commit().unwrap();
+        fork.branch("feature", &fork_head, false).unwrap();
+
+        let (dir, repo) = create_test_repo();
+        create_commit(&repo, "Local commit", &[("local.txt", "local")]);
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+
+        let url = fork_dir.path().to_string_lossy().to_string();
+        let fetched = git_repo.fetch_branch(&url, "feature").unwrap();
+        assert_eq!(fetched.sha, fork_head.id().to_string());
+        assert_eq!(fetched.name, fetched_ref_name(&url, "feature"));
+        assert_eq!(fetched.ref_type, RefType::Remote);
+
+        // The fetched ref can be used like any other ref and no remote is configured
+        let resolved = git_repo.resolve_ref(&fetched.name).unwrap();
+        assert_eq!(resolved.sha, fetched.sha);
+        assert_eq!(resolved.ref_type, RefType::Remote);
+        assert!(repo.remotes().unwrap().is_empty());
+
+        let missing = git_repo.fetch_branch(&url, "missing");
+        assert!(matches!(missing, Err(GitBackendError::FetchFailed { .. })));
+
+        let invalid = git_repo.fetch_branch(&url, "bad..name");
+        assert!(matches!(
+            invalid,
+            Err(GitBackendError::InvalidDiffSpec { .. })
+        ));
+    }
+
+    #[test]
+    fn test_resolve_ref() {
+        let (dir, repo) = create_test_repo();
+        let first = create_commit(&repo, "Initial", &[("a.txt", "a")]);
+        let second = create_commit(&repo, "Second", &[("a.txt", "b")]);
+        let first_commit = repo.find_commit(first).unwrap();
+        repo.branch("feature", &first_commit, false).unwrap();
+        repo.tag_lightweight("v1", first_commit.as_object(), false)
+            .unwrap();
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+
+        let resolved = |name: &str| {
+            let resolved = git_repo.resolve_ref(name).unwrap();
+            (resolved.sha, resolved.ref_type)
+        };
+        assert_eq!(resolved("HEAD"), (second.to_string(), RefType::Head));
+        assert_eq!(resolved("feature"), (first.to_string(), RefType::Branch));
+        assert_eq!(resolved("v1"), (first.to_string(), RefType::Tag));
+        assert_eq!(resolved("HEAD~1"), (first.to_string(), RefType::Commit));
+        assert_eq!(
+            resolved(&second.to_string()[..7]),
+            (second.to_string(), RefType::Commit)
+        );
+        assert!(matches!(
+            git_repo.resolve_ref("missing"),
+            Err(GitBackendError::RefNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_refs_fingerprint() {
+        let (dir, repo) = create_test_repo();
+        let first = create_commit(&repo, "Initial", &[("a.txt", "a")]);
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        let before = git_repo.refs_fingerprint().unwrap();
+        assert_eq!(git_repo.refs_fingerprint().unwrap(), before);
+
+        repo.branch("feature/nested", &repo.find_commit(first).unwrap(), false)
+            .unwrap();
+        let branched = git_repo.refs_fingerprint().unwrap();
+        assert_ne!(branched, before);
+
+        // Moving a ref right away, within the same timestamp, is noticed too
+        let second = create_commit(&repo, "Second", &[("a.txt", "b")]);
+        repo.reference("refs/heads/feature/nested", second, true, "move")
+            .unwrap();
+        assert_ne!(git_repo.refs_fingerprint().unwrap(), branched);
+    }
+
+    #[test]
+    fn test_refs_fingerprint_many_refs() {
+        let (dir, repo) = create_test_repo();
+        let first = create_commit(&repo, "Initial", &[("a.txt", "a")]);
+        let second = create_commit(&repo, "Second", &[("a.txt", "b")]);
+        let commit = repo.find_commit(first).unwrap();
+        for i in 0..2000 {
+            repo.branch(&format!("topic/{}/branch-{i}", i % 20), &commit, false)
+                .unwrap();
+        }
+
+        let git_dir = repo.path();
+        let fingerprint =
+            |racy_since| refs_fingerprint_since(git_dir, git_dir, racy_since).unwrap();
+        let settled = SystemTime::now();
+        let before = fingerprint(settled);
+        let racy_before = fingerprint(SystemTime::UNIX_EPOCH);
+
+        // Rewriting a ref in place, keeping its timestamp, is only noticed
+        // within the racy window: outside of it the ref files aren't read
+        let ref_path = dir.path().join(".git/refs/heads/topic/7/branch-7");
+        let modified = fs::metadata(&ref_path).unwrap().modified().unwrap();
+        fs::write(&ref_path, format!("{second}\n")).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&ref_path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert_eq!(fingerprint(settled), before);
+        assert_ne!(fingerprint(SystemTime::UNIX_EPOCH), racy_before);
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        let moved_before = git_repo.refs_fingerprint().unwrap();
+        repo.reference("refs/heads/topic/3/branch-3", second, true, "move")
+            .unwrap();
+        assert_ne!(git_repo.refs_fingerprint().unwrap(), moved_before);
+    }
+
+    #[test]
+    fn test_list_files() {
+        let (dir, repo) = create_test_repo();
+        create_commit(
+            &repo,
+            "Initial",
+            &[("src/main.rs", "fn main() {}"), ("README.md", "# Test")],
+        );
+        fs::write(dir.path().join("untracked.txt"), "untracked").unwrap();
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+
+        assert_eq!(
+            git_repo.list_files().unwrap(),
+            vec!["README.md", "src/main.rs"]
+        );
+    }
+
+    #[test]
+    fn test_stage_and_unstage_path() {
+        let (dir, repo) = create_test_repo();
+        create_commit(
+            &repo,
+            "Initial",
+            &[("file.txt", "content\n"), ("gone.txt", "gone\n")],
+        );
+        fs::write(dir.path().join("file.txt"), "modified\n").unwrap();
+        fs::write(dir.path().join("new.txt"), "new\n").unwrap();
+        fs::remove_file(dir.path().join("gone.txt")).unwrap();
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        let staged = WorkingTreeDiffOptions {
+            staged_only: true,
+            ..WorkingTreeDiffOptions::default()
+        };
+        let staged_files = || {
+            git_repo
+                .diff_working_tree("HEAD", staged.clone())
+                .unwrap()
+                .files
+                .len()
+        };
+
+        for path in ["file.txt", "new.txt", "gone.txt"] {
+            git_repo.stage_path(path).unwrap();
+        }
+        assert_eq!(staged_files(), 3);
+
+        for path in ["file.txt", "new.txt", "gone.txt"] {
+            git_repo.unstage_path(path).unwrap();
+        }
+        assert_eq!(staged_files(), 0);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("file.txt")).unwrap(),
+            "modified\n"
+        );
+    }
+
+    #[test]
+    fn test_checkout_path() {
+        let (dir, repo) = create_test_repo();
+        create_commit(&repo, "Initial", &[("file.txt", "content\n")]);
+        fs::write(dir.path().join("file.txt"), "staged\n").unwrap();
+        fs::write(dir.path().join("new.txt"), "new\n").unwrap();
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        git_repo.stage_path("file.txt").unwrap();
+        git_repo.stage_path("new.txt").unwrap();
+        fs::write(dir.path().join("file.txt"), "unstaged\n").unwrap();
+
+        git_repo.checkout_path("file.txt", "HEAD").unwrap();
+        git_repo.checkout_path("new.txt", "HEAD").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("file.txt")).unwrap(),
+            "content\n"
+        );
+        assert!(!dir.path().join("new.txt").exists());
+        assert!(!git_repo.is_dirty().unwrap());
+    }
+
+    #[test]
+    fn test_checkout_path_matches_literally() {
+        let (dir, repo) = create_test_repo();
+        create_commit(
+            &repo,
+            "Initial",
+            &[("a*.txt", "glob\n"), ("ab.txt", "plain\n")],
+        );
+        fs::write(dir.path().join("a*.txt"), "changed\n").unwrap();
+        fs::write(dir.path().join("ab.txt"), "changed\n").unwrap();
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        git_repo.checkout_path("a*.txt", "HEAD").unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("a*.txt")).unwrap(),
+            "glob\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("ab.txt")).unwrap(),
+            "changed\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_patch_to_index() {
+        let (dir, repo) = create_test_repo();
+        create_commit(&repo, "Initial", &[("file.txt", "one\ntwo\nthree\n")]);
+        fs::write(dir.path().join("file.txt"), "one\n2\nthree\nfour\n").unwrap();
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+
+        // Stage only the first change
+        git_repo
+            .apply_patch_to_index(
+                "--- a/file.txt\n+++ b/file.txt\n@@ -1,3 +1,3 @@\n one\n-two\n+2\n three\n",
+            )
+            .unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.read(true).unwrap();
+        let entry = index.get_path(Path::new("file.txt"), 0).unwrap();
+        let blob = repo.find_blob(entry.id).unwrap();
+        assert_eq!(blob.content(), b"one\n2\nthree\n");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("file.txt")).unwrap(),
+            "one\n2\nthree\nfour\n"
+        );
+
+        let stale = git_repo.apply_patch_to_index(
+            "--- a/file.txt\n+++ b/file.txt\n@@ -1,3 +1,3 @@\n one\n-two\n+2\n three\n",
+        );
+        assert!(matches!(stale, Err(GitBackendError::PatchFailed { .. })));
+    }
+
+    #[test]
+    fn test_commit() {
+        let (dir, repo) = create_test_repo();
+        let initial = create_commit(&repo, "Initial", &[("file.txt", "content\n")]);
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        assert!(git_repo.commit("Nothing", false).is_err());
+
+        fs::write(dir.path().join("file.txt"), "changed\n").unwrap();
+        git_repo.stage_path("file.txt").unwrap();
+        let commit = git_repo.commit("Change file", false).unwrap();
+        assert_eq!(commit.summary, "Change file");
+        assert_eq!(commit.parent_shas, vec![initial.to_string()]);
+        assert_eq!(git_repo.head().unwrap(), commit.sha);
+        assert!(!git_repo.is_dirty().unwrap());
+
+        let amended = git_repo.commit("Change file again", true).unwrap();
+        assert_ne!(amended.sha, commit.sha);
+        assert_eq!(amended.parent_shas, vec![initial.to_string()]);
+        assert_eq!(git_repo.head().unwrap(), amended.sha);
+    }
+
+    #[test]
+    fn test_commit_refuses_hooks_and_signing() {
+        let (dir, repo) = create_test_repo();
+        create_commit(&repo, "Initial", &[("file.txt", "content\n")]);
+        fs::write(dir.path().join("file.txt"), "changed\n").unwrap();
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        git_repo.stage_path("file.txt").unwrap();
+
+        repo.config()
+            .unwrap()
+            .set_bool("commit.gpgsign", true)
+            .unwrap();
+        assert!(git_repo.commit("Change file", false).is_err());
+        repo.config()
+            .unwrap()
+            .set_bool("commit.gpgsign", false)
+            .unwrap();
+
+        let hook = repo.path().join("hooks/pre-commit");
+        fs::create_dir_all(hook.parent().unwrap()).unwrap();
+        fs::write(&hook, "#!/bin/sh\nexit 1\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+            fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        assert!(git_repo.commit("Change file", false).is_err());
+
+        fs::remove_file(&hook).unwrap();
+        assert!(git_repo.commit("Change file", false).is_ok());
+    }
+
+    #[test]
+    fn test_diff_working_tree_against_empty_tree() {
+        let (dir, repo) = create_test_repo();
+        create_commit(&repo, "Initial", &[("file.txt", "content\n")]);
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        let staged = WorkingTreeDiffOptions {
+            staged_only: true,
+            ..WorkingTreeDiffOptions::default()
+        };
+        let result = git_repo
+            .diff_working_tree("4b825dc642cb6eb9a060e54bf8d69288fbee4904", staged)
+            .unwrap();
+        assert_eq!(result.files.len(), 1);
+    }
+
+    #[test]
+    fn test_identity() {
+        let (dir, _repo) = create_test_repo();
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        assert_eq!(
+            git_repo.identity().unwrap(),
+            Identity {
+                name: "Test User".to_string(),
+                email: "test@example.com".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_conflicts() {
+        let (dir, repo) = create_test_repo();
+        let base = create_commit(&repo, "Initial", &[("file.txt", "base\n")]);
+        let main = repo.head().unwrap().name().unwrap().to_string();
+        create_commit(&repo, "Ours", &[("file.txt", "ours\n")]);
+
+        let base_commit = repo.find_commit(base).unwrap();
+        repo.branch("feature", &base_commit, false).unwrap();
+        repo.set_head("refs/heads/feature").unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+        create_commit(&repo, "Theirs", &[("file.txt", "theirs\n")]);
+        repo.set_head(&main).unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .unwrap();
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        assert_eq!(git_repo.operation_in_progress().unwrap(), None);
+        assert!(git_repo.list_conflicts().unwrap().is_empty());
+
+        let feature = repo
+            .find_annotated_commit(repo.refname_to_id("refs/heads/feature").unwrap())
+            .unwrap();
+        repo.merge(&[&feature], None, None).unwrap();
+
+        assert_eq!(
+            git_repo.operation_in_progress().unwrap(),
+            Some(RepoOperation::Merge)
+        );
+        assert_eq!(
+            git_repo.list_conflicts().unwrap(),
+            vec![ConflictFile {
+                path: "file.txt".to_string(),
+                base: Some("base\n".to_string()),
+                ours: Some("ours\n".to_string()),
+                theirs: Some("theirs\n".to_string()),
+                binary: false,
+            }]
+        );
+
+        fs::write(dir.path().join("file.txt"), "resolved\n").unwrap();
+        git_repo.stage_path("file.txt").unwrap();
+        assert!(git_repo.list_conflicts().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_list_worktrees() {
+        let (dir, repo) = create_test_repo();
+        create_commit(&repo, "Initial", &[("file.txt", "content\n")]);
+        let linked_dir = tempfile::tempdir().unwrap();
+        let linked_path = linked_dir.path().join("linked");
+        repo.worktree("linked", &linked_path, None).unwrap();
+
+        let backend = Git2Backend::new();
+        let worktrees = backend.open(dir.path()).unwrap().list_worktrees().unwrap();
+        assert_eq!(worktrees.len(), 2);
+        assert!(worktrees[0].is_main);
+        assert!(worktrees[0].is_current);
+        assert!(!worktrees[1].is_main);
+        assert!(!worktrees[1].is_current);
+        assert_eq!(worktrees[1].branch.as_deref(), Some("linked"));
+        assert_eq!(worktrees[0].head, worktrees[1].head);
+
+        let worktrees = backend
+            .open(&linked_path)
+            .unwrap()
+            .list_worktrees()
+            .unwrap();
+        assert!(!worktrees[0].is_current);
+        assert!(worktrees[1].is_current);
+    }
+
+    #[test]
+    fn test_unsigned_commit() {
+        let (dir, repo) = create_test_repo();
+        let oid = create_commit(&repo, "Initial", &[("file.txt", "content\n")]);
+
+        let backend = Git2Backend::new();
+        let git_repo = backend.open(dir.path()).unwrap();
+        let sha = oid.to_string();
+        assert_eq!(git_repo.get_commit(&sha).unwrap().signature, None);
+        assert_eq!(git_repo.verify_signature(&sha).unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_verification() {
+        let alice = Some("Alice <alice@example.com>".to_string());
+        assert_eq!(
+            parse_verification(
+                SignatureKind::Gpg,
+                true,
+                "[GNUPG:] NEWSIG\n[GNUPG:] GOODSIG 1234ABCD Alice <alice@example.com>\n"
+            ),
+            (SignatureVerification::Good, alice.clone())
+        );
+        assert_eq!(
+            parse_verification(
+                SignatureKind::Gpg,
+                false,
+                "[GNUPG:] BADSIG 1234ABCD Alice <alice@example.com>\n"
+            ),
+            (SignatureVerification::Bad, alice)
+        );
+        assert_eq!(
+            parse_verification(
+                SignatureKind::Gpg,
+                false,
+                "[GNUPG:] ERRSIG 1234ABCD 1 8 00 1700000000 9 -\n[GNUPG:] NO_PUBKEY 1234ABCD\n"
+            ),
+            (SignatureVerification::UnknownKey, None)
+        );
+        assert_eq!(
+            parse_verification(SignatureKind::Gpg, false, "error: cannot run gpg\n"),
+            (SignatureVerification::Unchecked, None)
+        );
+
+        assert_eq!(
+            parse_verification(
+                SignatureKind::Ssh,
+                true,
+                "Good \"git\" signature for alice@example.com with ED25519 key SHA256:abc\n"
+            ),
+            (
+                SignatureVerification::Good,
+                Some("alice@example.com".to_string())
+            )
+        );
+        assert_eq!(
+            parse_verification(
+                SignatureKind::Ssh,
+                false,
+                "error: gpg.ssh.allowedSignersFile needs to be configured\n"
+            ),
+            (SignatureVerification::UnknownKey, None)
+        );
+        assert_eq!(
+            parse_verification(
+                SignatureKind::Ssh,
+                false,
+                "Signature verification failed: incorrect signature\n"
+            ),
+            (SignatureVerification::Bad, None)
+        );
+    }
+
+    #[test]
+    fn test_fetched_ref_name() {
+        assert_eq!(
+            fetched_ref_name("https://github.com/alice/repo.git", "fix/typo"),
+            "refs/chadreview/fetched/github-com-alice-repo/fix/typo"
+        );
+        assert_eq!(
+            fetched_ref_name("git@gitlab.com:Bob/Repo.git", "main"),
+            "refs/chadreview/fetched/git-gitlab-com-bob-repo/main"
+        );
+    }
+
+    #[test]
+    fn test_notes() {
+        let (dir, _repo) = create_test_repo();
+        let repo = Git2Backend::new().open(dir.path()).unwrap();
+        let notes_ref = "refs/notes/test";
+
+        assert_eq!(repo.read_note(notes_ref, "a").unwrap(), None);
+        assert!(repo.list_notes(notes_ref).unwrap().is_empty());
+
+        repo.write_note(notes_ref, "a", "first").unwrap();
+        repo.write_note(notes_ref, "b", "second").unwrap();
+        repo.write_note(notes_ref, "a", "replaced").unwrap();
+        assert_eq!(
+            repo.read_note(notes_ref, "a").unwrap().as_deref(),
+            Some("replaced")
+        );
+
+        let mut notes = repo.list_notes(notes_ref).unwrap();
+        notes.sort();
+        assert_eq!(notes, vec!["replaced", "second"]);
+
+        assert!(repo.remove_note(notes_ref, "a").unwrap());
+        assert!(!repo.remove_note(notes_ref, "a").unwrap());
+        assert_eq!(repo.read_note(notes_ref, "a").unwrap(), None);
+        assert_eq!(repo.list_notes(notes_ref).unwrap(), vec!["second"]);
+    }
 }
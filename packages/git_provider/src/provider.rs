@@ -1,5 +1,8 @@
 use anyhow::Result;
-use chadreview_pr_models::{Comment, CreateComment, DiffFile, PullRequest};
+use chadreview_pr_models::{
+    Check, Comment, Commit, CreateComment, DiffFile, Label, MergeStrategy, PullRequest,
+    RateLimitStatus, Reaction, ReactionContent, Review, ReviewVerdict, User,
+};
 
 /// Abstract trait for git hosting provider implementations.
 ///
@@ -30,6 +33,39 @@ pub trait GitProvider: Send + Sync {
     /// List of changed files with their hunks and line-by-line diffs.
     async fn get_diff(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<DiffFile>>;
 
+    /// Fetches the commits of a pull request.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `number` - Pull request number
+    ///
+    /// # Returns
+    /// The pull request's commits, oldest first.
+    async fn get_commits(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<Commit>>;
+
+    /// Fetches the changes made by a single commit.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `sha` - Commit SHA, usually one of the pull request's commits
+    ///
+    /// # Returns
+    /// List of files changed by the commit relative to its parent.
+    async fn get_commit_diff(&self, owner: &str, repo: &str, sha: &str) -> Result<Vec<DiffFile>>;
+
+    /// Fetches CI checks and commit statuses for a commit.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `sha` - Commit SHA, usually the pull request's head commit
+    ///
+    /// # Returns
+    /// The latest result of each check, with links to their logs where available.
+    async fn get_checks(&self, owner: &str, repo: &str, sha: &str) -> Result<Vec<Check>>;
+
     /// Fetches all comments for a pull request.
     ///
     /// # Arguments
@@ -115,7 +151,22 @@ pub trait GitProvider: Send + Sync {
         comment_id: u64,
     ) -> Result<()>;
 
-    /// Marks a review comment thread as resolved or unresolved.
+    /// Marks a review thread as resolved.
+    ///
+    /// # Arguments
+    /// * `thread_id` - Provider ID of the review thread (see `Comment::thread_id`)
+    async fn resolve_thread(&self, thread_id: &str) -> Result<()>;
+
+    /// Marks a resolved review thread as unresolved.
+    ///
+    /// # Arguments
+    /// * `thread_id` - Provider ID of the review thread (see `Comment::thread_id`)
+    async fn unresolve_thread(&self, thread_id: &str) -> Result<()>;
+
+    /// Marks the review thread containing a comment as resolved or unresolved.
+    ///
+    /// Looks up the comment's thread and calls [`Self::resolve_thread`] or
+    /// [`Self::unresolve_thread`].
     ///
     /// # Arguments
     /// * `owner` - Repository owner username or organization
@@ -133,8 +184,271 @@ pub trait GitProvider: Send + Sync {
         number: u64,
         comment_id: u64,
         resolved: bool,
+    ) -> Result<()> {
+        let comment = self
+            .get_comment(owner, repo, number, comment_id, true)
+            .await?;
+        let thread_id = comment.thread_id.ok_or_else(|| {
+            anyhow::anyhow!("Comment {comment_id} is not part of a review thread")
+        })?;
+
+        if resolved {
+            self.resolve_thread(&thread_id).await
+        } else {
+            self.unresolve_thread(&thread_id).await
+        }
+    }
+
+    /// Fetches the emoji reactions on a comment.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `comment_id` - Comment ID to fetch reactions for
+    ///
+    /// # Returns
+    /// One entry per emoji that has at least one reaction.
+    async fn get_reactions(
+        &self,
+        owner: &str,
+        repo: &str,
+        comment_id: u64,
+    ) -> Result<Vec<Reaction>>;
+
+    /// Reacts to a comment as the authenticated user.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `comment_id` - Comment ID to react to
+    /// * `content` - Emoji to react with
+    ///
+    /// # Returns
+    /// The comment's reactions after the change.
+    async fn add_reaction(
+        &self,
+        owner: &str,
+        repo: &str,
+        comment_id: u64,
+        content: ReactionContent,
+    ) -> Result<Vec<Reaction>>;
+
+    /// Removes the authenticated user's reaction from a comment.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `comment_id` - Comment ID to remove the reaction from
+    /// * `content` - Emoji to remove
+    ///
+    /// # Returns
+    /// The comment's reactions after the change.
+    async fn remove_reaction(
+        &self,
+        owner: &str,
+        repo: &str,
+        comment_id: u64,
+        content: ReactionContent,
+    ) -> Result<Vec<Reaction>>;
+
+    /// Adds the authenticated user's reaction to a comment, or removes it if
+    /// they have already reacted with that emoji.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `comment_id` - Comment ID to toggle the reaction on
+    /// * `content` - Emoji to toggle
+    ///
+    /// # Returns
+    /// The comment's reactions after the change.
+    async fn toggle_reaction(
+        &self,
+        owner: &str,
+        repo: &str,
+        comment_id: u64,
+        content: ReactionContent,
+    ) -> Result<Vec<Reaction>> {
+        let reacted = self
+            .get_reactions(owner, repo, comment_id)
+            .await?
+            .iter()
+            .any(|reaction| reaction.content == content && reaction.viewer_reacted);
+
+        if reacted {
+            self.remove_reaction(owner, repo, comment_id, content).await
+        } else {
+            self.add_reaction(owner, repo, comment_id, content).await
+        }
+    }
+
+    /// Submits a review on a pull request.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `number` - Pull request number
+    /// * `verdict` - Whether to approve, request changes, or just comment
+    /// * `body` - Review summary text (may be empty when approving)
+    /// * `pending_comments` - Line-level and file-level comments to publish
+    ///   as part of the review
+    ///
+    /// # Returns
+    /// The submitted review.
+    async fn submit_review(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        verdict: ReviewVerdict,
+        body: String,
+        pending_comments: Vec<CreateComment>,
+    ) -> Result<Review>;
+
+    /// Merges a pull request.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `number` - Pull request number
+    /// * `strategy` - Whether to create a merge commit, squash, or rebase
+    ///
+    /// # Returns
+    /// Success, or an error if the provider refused the merge (e.g. because
+    /// of conflicts or failing required checks).
+    async fn merge_pr(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        strategy: MergeStrategy,
     ) -> Result<()>;
 
+    /// Edits a pull request's title and description, or converts it
+    /// between a draft and ready for review.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `number` - Pull request number
+    /// * `title` - New title, or `None` to keep the current one
+    /// * `body` - New description, or `None` to keep the current one
+    /// * `draft` - `Some(true)` to convert to a draft, `Some(false)` to mark
+    ///   as ready for review, or `None` to leave as is
+    ///
+    /// # Returns
+    /// The pull request after the change.
+    async fn update_pr(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        title: Option<String>,
+        body: Option<String>,
+        draft: Option<bool>,
+    ) -> Result<PullRequest>;
+
+    /// Requests reviews from users.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `number` - Pull request number
+    /// * `reviewers` - Usernames to request reviews from
+    ///
+    /// # Returns
+    /// All users whose review is now requested.
+    async fn request_reviewers(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        reviewers: Vec<String>,
+    ) -> Result<Vec<User>>;
+
+    /// Withdraws review requests.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `number` - Pull request number
+    /// * `reviewers` - Usernames whose review request should be removed
+    ///
+    /// # Returns
+    /// All users whose review is still requested.
+    async fn remove_reviewers(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        reviewers: Vec<String>,
+    ) -> Result<Vec<User>>;
+
+    /// Replaces the assignees of a pull request.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `number` - Pull request number
+    /// * `assignees` - Usernames to assign; an empty list unassigns everyone
+    ///
+    /// # Returns
+    /// The pull request's assignees after the change.
+    async fn set_assignees(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        assignees: Vec<String>,
+    ) -> Result<Vec<User>>;
+
+    /// Adds labels to a pull request.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `number` - Pull request number
+    /// * `labels` - Names of existing repository labels to add
+    ///
+    /// # Returns
+    /// All labels on the pull request after the change.
+    async fn add_labels(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        labels: Vec<String>,
+    ) -> Result<Vec<Label>>;
+
+    /// Removes a label from a pull request.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    /// * `number` - Pull request number
+    /// * `label` - Name of the label to remove
+    ///
+    /// # Returns
+    /// The labels remaining on the pull request.
+    async fn remove_label(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        label: &str,
+    ) -> Result<Vec<Label>>;
+
+    /// Lists the users who can be assigned to the repository's pull
+    /// requests, which are the ones worth suggesting as `@`-mentions.
+    ///
+    /// # Arguments
+    /// * `owner` - Repository owner username or organization
+    /// * `repo` - Repository name
+    ///
+    /// # Returns
+    /// The repository's collaborators.
+    async fn get_collaborators(&self, owner: &str, repo: &str) -> Result<Vec<User>>;
+
     /// Returns the provider name identifier.
     ///
     /// # Returns
@@ -156,4 +470,14 @@ pub trait GitProvider: Send + Sync {
     fn supports_line_comments(&self) -> bool {
         true
     }
+
+    /// Returns the API quota observed on the provider's latest responses.
+    ///
+    /// # Returns
+    /// The quota closest to running out, or `None` before any request has
+    /// been made or if the provider doesn't report rate limits. Defaults to
+    /// `None`.
+    fn rate_limit(&self) -> Option<RateLimitStatus> {
+        None
+    }
 }
@@ -0,0 +1,1524 @@
+use anyhow::Result;
+use chadreview_diff::parse_unified_diff;
+use chadreview_diff::parser::{count_additions_deletions, split_diff};
+use chadreview_diff_models::LineNumber;
+use chadreview_git_provider::GitProvider;
+use chadreview_pr_models::{
+    Check, CheckStatus, Comment, CommentType, Commit, CreateComment, DiffFile, Label,
+    MergeStrategy, MergeableState, PrState, PullRequest, Reaction, ReactionContent, Review,
+    ReviewVerdict, User,
+};
+use chadreview_syntax::SyntaxHighlighter;
+use reqwest::Method;
+
+/// Page size used for paginated list endpoints.
+const PAGE_LIMIT: usize = 50;
+
+/// Stop paginating after this many items.
+const MAX_ITEMS: usize = 3000;
+
+pub struct GiteaProvider {
+    http_client: reqwest::Client,
+    auth_token: Option<String>,
+    base_url: String,
+}
+
+impl GiteaProvider {
+    /// Create a new Gitea/Forgejo provider for the instance at `base_url`
+    /// (e.g. `https://codeberg.org`) without authentication.
+    ///
+    /// # Panics
+    ///
+    /// * If the `reqwest::Client` fails to build.
+    #[must_use]
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let http_client = reqwest::Client::builder()
+            .user_agent("ChadReview")
+            .build()
+            .unwrap();
+        Self {
+            http_client,
+            auth_token: None,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_token(mut self, token: String) -> Self {
+        self.auth_token = Some(token);
+        self
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!("{}/api/v1{path}", self.base_url)
+    }
+
+    fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        log::debug!("{method} {url}");
+        let mut request = self
+            .http_client
+            .request(method, url)
+            .header("Accept", "application/json");
+
+        if let Some(token) = &self.auth_token {
+            request = request.header("Authorization", format!("token {token}"));
+        }
+
+        request
+    }
+
+    async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let response = request.send().await?;
+        let status = response.status();
+
+        if !status.is_success() {
+            log::error!("Gitea API error: {}", response.text().await?);
+            anyhow::bail!("Gitea API error: {status}");
+        }
+
+        Ok(response)
+    }
+
+    async fn send_json(request: reqwest::RequestBuilder) -> Result<serde_json::Value> {
+        Ok(Self::send(request).await?.json().await?)
+    }
+
+    /// Fetch every item from a list endpoint that paginates with `page` and
+    /// `limit`.
+    async fn fetch_all_paginated(&self, url: &str) -> Result<Vec<serde_json::Value>> {
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let mut items = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let url = format!("{url}{separator}limit={PAGE_LIMIT}&page={page}");
+            let page_items: Vec<serde_json::Value> = Self::send(self.request(Method::GET, &url))
+                .await?
+                .json()
+                .await?;
+            let count = page_items.len();
+            items.extend(page_items);
+
+            if items.len() >= MAX_ITEMS {
+                log::warn!("Reached the {MAX_ITEMS} item limit fetching {url}");
+                break;
+            }
+            if count < PAGE_LIMIT {
+                break;
+            }
+            page += 1;
+        }
+
+        Ok(items)
+    }
+
+    /// Fetch every review comment on a pull request, unthreaded.
+    async fn get_review_comments(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+    ) -> Result<Vec<Comment>> {
+        let reviews = self
+            .fetch_all_paginated(
+                &self.api_url(&format!("/repos/{owner}/{repo}/pulls/{number}/reviews")),
+            )
+            .await?;
+
+        let mut comments = Vec::new();
+        for review in &reviews {
+            // Reviews without inline comments (approvals, plain comments)
+            if review["comments_count"].as_u64() == Some(0) {
+                continue;
+            }
+            let Some(review_id) = review["id"].as_u64() else {
+                continue;
+            };
+
+            let url = self.api_url(&format!(
+                "/repos/{owner}/{repo}/pulls/{number}/reviews/{review_id}/comments"
+            ));
+            let review_comments = Self::send_json(self.request(Method::GET, &url)).await?;
+            if let Some(review_comments) = review_comments.as_array() {
+                comments.extend(review_comments.iter().map(parse_review_comment));
+            }
+        }
+
+        Ok(comments)
+    }
+
+    /// Look up the IDs of repository labels by name, since Gitea's issue
+    /// label endpoints identify labels by ID.
+    async fn label_ids(&self, owner: &str, repo: &str, names: &[String]) -> Result<Vec<u64>> {
+        let labels = self
+            .fetch_all_paginated(&self.api_url(&format!("/repos/{owner}/{repo}/labels")))
+            .await?;
+
+        names
+            .iter()
+            .map(|name| {
+                labels
+                    .iter()
+                    .find(|label| label["name"].as_str() == Some(name))
+                    .and_then(|label| label["id"].as_u64())
+                    .ok_or_else(|| anyhow::anyhow!("Label not found: {name}"))
+            })
+            .collect()
+    }
+
+    /// Login of the authenticated user, or `None` without a token.
+    async fn viewer_login(&self) -> Result<Option<String>> {
+        if self.auth_token.is_none() {
+            return Ok(None);
+        }
+
+        let user = Self::send_json(self.request(Method::GET, &self.api_url("/user"))).await?;
+        Ok(user["login"].as_str().map(ToString::to_string))
+    }
+
+    /// Reactions endpoint of a comment. Gitea stores review comments as
+    /// issue comments, so this covers both.
+    fn reactions_url(&self, owner: &str, repo: &str, comment_id: u64) -> String {
+        self.api_url(&format!(
+            "/repos/{owner}/{repo}/issues/comments/{comment_id}/reactions"
+        ))
+    }
+
+    /// Post a single inline comment (see [`inline_comment`]) as a `COMMENT`
+    /// review and return it.
+    async fn create_review_comment(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        commit_sha: &str,
+        comment: serde_json::Value,
+    ) -> Result<Comment> {
+        let url = self.api_url(&format!("/repos/{owner}/{repo}/pulls/{number}/reviews"));
+        let mut review_body = serde_json::json!({
+            "event": "COMMENT",
+            "body": "",
+            "comments": [comment],
+        });
+        if !commit_sha.is_empty() {
+            review_body["commit_id"] = commit_sha.into();
+        }
+        log::debug!("POST url={url} body={review_body:?}");
+
+        let review = Self::send_json(self.request(Method::POST, &url).json(&review_body)).await?;
+        let review_id = review["id"]
+            .as_u64()
+            .ok_or_else(|| anyhow::anyhow!("No review id in response"))?;
+
+        let url = self.api_url(&format!(
+            "/repos/{owner}/{repo}/pulls/{number}/reviews/{review_id}/comments"
+        ));
+        let comments = Self::send_json(self.request(Method::GET, &url)).await?;
+        comments
+            .as_array()
+            .and_then(|comments| comments.last())
+            .map(parse_review_comment)
+            .ok_or_else(|| anyhow::anyhow!("Review {review_id} has no comments"))
+    }
+}
+
+#[async_trait::async_trait]
+impl GitProvider for GiteaProvider {
+    async fn get_pr(&self, owner: &str, repo: &str, number: u64) -> Result<PullRequest> {
+        let url = self.api_url(&format!("/repos/{owner}/{repo}/pulls/{number}"));
+        let pr_data = Self::send_json(self.request(Method::GET, &url)).await?;
+
+        Ok(parse_pull_request(owner, repo, &pr_data))
+    }
+
+    async fn get_diff(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<DiffFile>> {
+        let url = self.api_url(&format!("/repos/{owner}/{repo}/pulls/{number}.diff"));
+        let diff = Self::send(self.request(Method::GET, &url))
+            .await?
+            .text()
+            .await?;
+
+        parse_diff_text(&diff)
+    }
+
+    async fn get_commits(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<Commit>> {
+        let commits = self
+            .fetch_all_paginated(
+                &self.api_url(&format!("/repos/{owner}/{repo}/pulls/{number}/commits")),
+            )
+            .await?;
+
+        Ok(commits.iter().map(parse_commit).collect())
+    }
+
+    async fn get_commit_diff(&self, owner: &str, repo: &str, sha: &str) -> Result<Vec<DiffFile>> {
+        let url = self.api_url(&format!("/repos/{owner}/{repo}/git/commits/{sha}.diff"));
+        let diff = Self::send(self.request(Method::GET, &url))
+            .await?
+            .text()
+            .await?;
+
+        parse_diff_text(&diff)
+    }
+
+    async fn get_checks(&self, owner: &str, repo: &str, sha: &str) -> Result<Vec<Check>> {
+        // Gitea Actions and external CI both report commit statuses
+        let url = self.api_url(&format!("/repos/{owner}/{repo}/commits/{sha}/status"));
+        let combined_status = Self::send_json(self.request(Method::GET, &url)).await?;
+
+        Ok(combined_status["statuses"]
+            .as_array()
+            .map(|statuses| statuses.iter().map(parse_commit_status).collect())
+            .unwrap_or_default())
+    }
+
+    async fn get_comments(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<Comment>> {
+        let review_comments = self.get_review_comments(owner, repo, number).await?;
+        let issue_comments = self
+            .fetch_all_paginated(
+                &self.api_url(&format!("/repos/{owner}/{repo}/issues/{number}/comments")),
+            )
+            .await?;
+
+        let mut all_comments = thread_review_comments(review_comments);
+        all_comments.extend(issue_comments.iter().map(parse_issue_comment));
+
+        Ok(all_comments)
+    }
+
+    async fn get_comment(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        comment_id: u64,
+        include_replies: bool,
+    ) -> Result<Comment> {
+        if include_replies {
+            let all_comments = self.get_comments(owner, repo, number).await?;
+            return find_comment_in_tree(&all_comments, comment_id)
+                .ok_or_else(|| anyhow::anyhow!("Comment {comment_id} not found"));
+        }
+
+        // The issue comment endpoint also serves review comments, but without
+        // their path and line, so look for a review comment first
+        let review_comments = self.get_review_comments(owner, repo, number).await?;
+        if let Some(comment) = review_comments.into_iter().find(|c| c.id == comment_id) {
+            return Ok(comment);
+        }
+
+        let url = self.api_url(&format!(
+            "/repos/{owner}/{repo}/issues/comments/{comment_id}"
+        ));
+        let comment_data = Self::send_json(self.request(Method::GET, &url)).await?;
+        Ok(parse_issue_comment(&comment_data))
+    }
+
+    async fn create_comment(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        comment: CreateComment,
+    ) -> Result<Comment> {
+        match &comment.comment_type {
+            CommentType::General => {
+                let url = self.api_url(&format!("/repos/{owner}/{repo}/issues/{number}/comments"));
+                let body = serde_json::json!({
+                    "body": comment.body,
+                });
+                log::debug!("POST url={url} body={body:?}");
+
+                let comment_data =
+                    Self::send_json(self.request(Method::POST, &url).json(&body)).await?;
+                Ok(parse_issue_comment(&comment_data))
+            }
+            CommentType::LineLevelComment {
+                commit_sha,
+                path,
+                line,
+            } => {
+                let inline = inline_comment(&comment.body, path, *line);
+                self.create_review_comment(owner, repo, number, commit_sha, inline)
+                    .await
+            }
+            CommentType::Reply { in_reply_to, .. } => {
+                // Gitea threads review comments by their position, so a reply
+                // is a new comment on the same line
+                let review_comments = self.get_review_comments(owner, repo, number).await?;
+                let parent = review_comments
+                    .iter()
+                    .find(|c| c.id == *in_reply_to)
+                    .ok_or_else(|| anyhow::anyhow!("Comment {in_reply_to} not found"))?;
+
+                let CommentType::LineLevelComment {
+                    commit_sha,
+                    path,
+                    line,
+                } = &parent.comment_type
+                else {
+                    anyhow::bail!("Comment {in_reply_to} is not a line comment");
+                };
+
+                let inline = inline_comment(&comment.body, path, *line);
+                self.create_review_comment(owner, repo, number, commit_sha, inline)
+                    .await
+            }
+            CommentType::FileLevelComment { .. } => {
+                anyhow::bail!("Gitea does not support file-level comments")
+            }
+        }
+    }
+
+    async fn update_comment(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        comment_id: u64,
+        body: String,
+    ) -> Result<Comment> {
+        let url = self.api_url(&format!(
+            "/repos/{owner}/{repo}/issues/comments/{comment_id}"
+        ));
+        let request_body = serde_json::json!({
+            "body": body,
+        });
+
+        let comment_data =
+            Self::send_json(self.request(Method::PATCH, &url).json(&request_body)).await?;
+
+        // Review comments come back without their position
+        let review_comments = self.get_review_comments(owner, repo, number).await?;
+        Ok(review_comments
+            .into_iter()
+            .find(|c| c.id == comment_id)
+            .unwrap_or_else(|| parse_issue_comment(&comment_data)))
+    }
+
+    async fn delete_comment(
+        &self,
+        owner: &str,
+        repo: &str,
+        _number: u64,
+        comment_id: u64,
+    ) -> Result<()> {
+        let url = self.api_url(&format!(
+            "/repos/{owner}/{repo}/issues/comments/{comment_id}"
+        ));
+        Self::send(self.request(Method::DELETE, &url)).await?;
+        Ok(())
+    }
+
+    async fn resolve_thread(&self, _thread_id: &str) -> Result<()> {
+        anyhow::bail!("Gitea does not support resolving conversations through its API")
+    }
+
+    async fn unresolve_thread(&self, _thread_id: &str) -> Result<()> {
+        anyhow::bail!("Gitea does not support resolving conversations through its API")
+    }
+
+    async fn get_reactions(
+        &self,
+        owner: &str,
+        repo: &str,
+        comment_id: u64,
+    ) -> Result<Vec<Reaction>> {
+        let url = self.reactions_url(owner, repo, comment_id);
+        let reactions = Self::send_json(self.request(Method::GET, &url)).await?;
+        let viewer = self.viewer_login().await?;
+
+        Ok(summarize_reactions(
+            reactions.as_array().map_or(&[], Vec::as_slice),
+            viewer.as_deref(),
+        ))
+    }
+
+    async fn add_reaction(
+        &self,
+        owner: &str,
+        repo: &str,
+        comment_id: u64,
+        content: ReactionContent,
+    ) -> Result<Vec<Reaction>> {
+        let url = self.reactions_url(owner, repo, comment_id);
+        let body = serde_json::json!({ "content": content.as_str() });
+        Self::send(self.request(Method::POST, &url).json(&body)).await?;
+
+        self.get_reactions(owner, repo, comment_id).await
+    }
+
+    async fn remove_reaction(
+        &self,
+        owner: &str,
+        repo: &str,
+        comment_id: u64,
+        content: ReactionContent,
+    ) -> Result<Vec<Reaction>> {
+        let url = self.reactions_url(owner, repo, comment_id);
+        let body = serde_json::json!({ "content": content.as_str() });
+        Self::send(self.request(Method::DELETE, &url).json(&body)).await?;
+
+        self.get_reactions(owner, repo, comment_id).await
+    }
+
+    async fn submit_review(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        verdict: ReviewVerdict,
+        body: String,
+        pending_comments: Vec<CreateComment>,
+    ) -> Result<Review> {
+        let mut commit_id = None;
+        let mut comments = Vec::with_capacity(pending_comments.len());
+        for comment in &pending_comments {
+            let CommentType::LineLevelComment {
+                commit_sha,
+                path,
+                line,
+            } = &comment.comment_type
+            else {
+                anyhow::bail!("Only line-level comments can be part of a Gitea review");
+            };
+            if commit_id.is_none() && !commit_sha.is_empty() {
+                commit_id = Some(commit_sha.clone());
+            }
+            comments.push(inline_comment(&comment.body, path, *line));
+        }
+
+        let url = self.api_url(&format!("/repos/{owner}/{repo}/pulls/{number}/reviews"));
+        let mut request_body = serde_json::json!({
+            "event": review_event(verdict),
+            "body": body,
+            "comments": comments,
+        });
+        if let Some(commit_id) = commit_id {
+            request_body["commit_id"] = commit_id.into();
+        }
+        log::debug!("POST url={url} body={request_body:?}");
+
+        let review_data =
+            Self::send_json(self.request(Method::POST, &url).json(&request_body)).await?;
+        Ok(parse_review(&review_data, verdict))
+    }
+
+    async fn merge_pr(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        strategy: MergeStrategy,
+    ) -> Result<()> {
+        let url = self.api_url(&format!("/repos/{owner}/{repo}/pulls/{number}/merge"));
+        let merge_style = match strategy {
+            MergeStrategy::Merge => "merge",
+            MergeStrategy::Squash => "squash",
+            MergeStrategy::Rebase => "rebase",
+        };
+        let body = serde_json::json!({ "Do": merge_style });
+        Self::send(self.request(Method::POST, &url).json(&body)).await?;
+
+        Ok(())
+    }
+
+    async fn request_reviewers(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        reviewers: Vec<String>,
+    ) -> Result<Vec<User>> {
+        let url = self.api_url(&format!(
+            "/repos/{owner}/{repo}/pulls/{number}/requested_reviewers"
+        ));
+        let body = serde_json::json!({ "reviewers": reviewers });
+        Self::send(self.request(Method::POST, &url).json(&body)).await?;
+
+        // The response lists review requests rather than users
+        Ok(self.get_pr(owner, repo, number).await?.reviewers)
+    }
+
+    async fn remove_reviewers(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        reviewers: Vec<String>,
+    ) -> Result<Vec<User>> {
+        let url = self.api_url(&format!(
+            "/repos/{owner}/{repo}/pulls/{number}/requested_reviewers"
+        ));
+        let body = serde_json::json!({ "reviewers": reviewers });
+        Self::send(self.request(Method::DELETE, &url).json(&body)).await?;
+
+        Ok(self.get_pr(owner, repo, number).await?.reviewers)
+    }
+
+    async fn set_assignees(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        assignees: Vec<String>,
+    ) -> Result<Vec<User>> {
+        let url = self.api_url(&format!("/repos/{owner}/{repo}/issues/{number}"));
+        let body = serde_json::json!({ "assignees": assignees });
+        let issue_data = Self::send_json(self.request(Method::PATCH, &url).json(&body)).await?;
+
+        Ok(parse_users(&issue_data["assignees"]))
+    }
+
+    async fn add_labels(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        labels: Vec<String>,
+    ) -> Result<Vec<Label>> {
+        let ids = self.label_ids(owner, repo, &labels).await?;
+        let url = self.api_url(&format!("/repos/{owner}/{repo}/issues/{number}/labels"));
+        let body = serde_json::json!({ "labels": ids });
+        let labels_data = Self::send_json(self.request(Method::POST, &url).json(&body)).await?;
+
+        Ok(parse_labels(&labels_data))
+    }
+
+    async fn remove_label(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        label: &str,
+    ) -> Result<Vec<Label>> {
+        let ids = self.label_ids(owner, repo, &[label.to_string()]).await?;
+        let url = self.api_url(&format!(
+            "/repos/{owner}/{repo}/issues/{number}/labels/{}",
+            ids[0]
+        ));
+        Self::send(self.request(Method::DELETE, &url)).await?;
+
+        let url = self.api_url(&format!("/repos/{owner}/{repo}/issues/{number}/labels"));
+        let labels_data = Self::send_json(self.request(Method::GET, &url)).await?;
+
+        Ok(parse_labels(&labels_data))
+    }
+
+    async fn get_collaborators(&self, owner: &str, repo: &str) -> Result<Vec<User>> {
+        let url = self.api_url(&format!("/repos/{owner}/{repo}/assignees"));
+        let users = Self::send_json(self.request(Method::GET, &url)).await?;
+
+        Ok(parse_users(&users))
+    }
+
+    async fn update_pr(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        title: Option<String>,
+        body: Option<String>,
+        draft: Option<bool>,
+    ) -> Result<PullRequest> {
+        let url = self.api_url(&format!("/repos/{owner}/{repo}/pulls/{number}"));
+
+        // Drafts are marked in the title, so changing the draft state needs
+        // the current one
+        let title = match draft {
+            Some(draft) => {
+                let title = match title {
+                    Some(title) => title,
+                    None => self.get_pr(owner, repo, number).await?.title,
+                };
+                Some(with_wip_prefix(&title, draft))
+            }
+            None => title,
+        };
+
+        let mut fields = serde_json::Map::new();
+        if let Some(title) = title {
+            fields.insert("title".to_string(), title.into());
+        }
+        if let Some(body) = body {
+            fields.insert("body".to_string(), body.into());
+        }
+        let pr_data = Self::send_json(
+            self.request(Method::PATCH, &url)
+                .json(&serde_json::Value::Object(fields)),
+        )
+        .await?;
+
+        Ok(parse_pull_request(owner, repo, &pr_data))
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "gitea"
+    }
+
+    fn supports_drafts(&self) -> bool {
+        true
+    }
+}
+
+/// Build an inline comment for the create review endpoint.
+fn inline_comment(body: &str, path: &str, line: LineNumber) -> serde_json::Value {
+    let mut comment = serde_json::json!({
+        "body": body,
+        "path": path,
+    });
+    match line {
+        LineNumber::Old { line } => comment["old_position"] = line.into(),
+        LineNumber::New { line } => comment["new_position"] = line.into(),
+    }
+    comment
+}
+
+/// The create review endpoint's `event` for a verdict.
+const fn review_event(verdict: ReviewVerdict) -> &'static str {
+    match verdict {
+        ReviewVerdict::Approve => "APPROVED",
+        ReviewVerdict::RequestChanges => "REQUEST_CHANGES",
+        ReviewVerdict::Comment => "COMMENT",
+    }
+}
+
+/// Parse a review response, falling back to the submitted verdict for
+/// states that don't map to one (e.g. `PENDING`).
+fn parse_review(value: &serde_json::Value, verdict: ReviewVerdict) -> Review {
+    let verdict = match value["state"].as_str() {
+        Some("APPROVED") => ReviewVerdict::Approve,
+        Some("REQUEST_CHANGES") => ReviewVerdict::RequestChanges,
+        Some("COMMENT") => ReviewVerdict::Comment,
+        _ => verdict,
+    };
+
+    Review {
+        id: value["id"].as_u64().unwrap(),
+        author: parse_user(&value["user"]),
+        verdict,
+        body: value["body"].as_str().unwrap_or("").to_string(),
+        submitted_at: value["submitted_at"].as_str().map(parse_datetime),
+    }
+}
+
+fn parse_user(value: &serde_json::Value) -> User {
+    User {
+        id: value["id"].as_u64().unwrap_or_default().to_string(),
+        username: value["login"].as_str().unwrap_or("unknown").to_string(),
+        avatar_url: value["avatar_url"].as_str().unwrap_or("").to_string(),
+        html_url: value["html_url"].as_str().unwrap_or("").to_string(),
+    }
+}
+
+/// Parse a commit from the pull request commits API.
+fn parse_commit(value: &serde_json::Value) -> Commit {
+    let author = if value["author"].is_object() {
+        parse_user(&value["author"])
+    } else {
+        User {
+            id: String::new(),
+            username: value["commit"]["author"]["name"]
+                .as_str()
+                .unwrap_or("unknown")
+                .to_string(),
+            avatar_url: String::new(),
+            html_url: String::new(),
+        }
+    };
+    let committed_at = value["commit"]["committer"]["date"]
+        .as_str()
+        .or_else(|| value["commit"]["author"]["date"].as_str())
+        .or_else(|| value["created"].as_str())
+        .map_or_else(chrono::Utc::now, parse_datetime);
+
+    Commit {
+        sha: value["sha"].as_str().unwrap_or_default().to_string(),
+        message: value["commit"]["message"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        author,
+        committed_at,
+    }
+}
+
+/// Parse a raw `.diff` response into diffs, skipping binary files.
+fn parse_diff_text(diff: &str) -> Result<Vec<DiffFile>> {
+    let highlighter = SyntaxHighlighter::new();
+    let mut result = Vec::new();
+
+    for file in split_diff(diff) {
+        if file.binary {
+            log::debug!("Skipping {} - binary file", file.filename);
+            continue;
+        }
+
+        let (additions, deletions) = count_additions_deletions(&file.patch);
+        let parsed = parse_unified_diff(
+            &file.filename,
+            file.status,
+            additions,
+            deletions,
+            &file.patch,
+            &highlighter,
+        )
+        .map_err(|e| anyhow::anyhow!(e))?;
+        result.push(parsed);
+    }
+
+    Ok(result)
+}
+
+fn parse_users(value: &serde_json::Value) -> Vec<User> {
+    value
+        .as_array()
+        .map(|arr| arr.iter().map(parse_user).collect())
+        .unwrap_or_default()
+}
+
+fn parse_pr_state(value: &serde_json::Value) -> PrState {
+    match value["state"].as_str().unwrap() {
+        "closed" if value["merged"].as_bool().unwrap_or(false) => PrState::Merged,
+        "closed" => PrState::Closed,
+        _ => PrState::Open,
+    }
+}
+
+fn parse_pull_request(owner: &str, repo: &str, pr_data: &serde_json::Value) -> PullRequest {
+    let title = pr_data["title"].as_str().unwrap().to_string();
+    let draft = pr_data["draft"]
+        .as_bool()
+        .unwrap_or_else(|| is_wip_title(&title));
+
+    PullRequest {
+        number: pr_data["number"].as_u64().unwrap(),
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+        title,
+        description: pr_data["body"].as_str().unwrap_or("").to_string(),
+        author: parse_user(&pr_data["user"]),
+        state: parse_pr_state(pr_data),
+        draft,
+        base_branch: pr_data["base"]["ref"].as_str().unwrap().to_string(),
+        head_branch: pr_data["head"]["ref"].as_str().unwrap().to_string(),
+        labels: parse_labels(&pr_data["labels"]),
+        assignees: parse_users(&pr_data["assignees"]),
+        reviewers: parse_users(&pr_data["requested_reviewers"]),
+        head_sha: pr_data["head"]["sha"].as_str().unwrap().to_string(),
+        created_at: parse_datetime(pr_data["created_at"].as_str().unwrap()),
+        updated_at: parse_datetime(pr_data["updated_at"].as_str().unwrap()),
+        provider: "gitea".to_string(),
+        mergeable_state: match pr_data["mergeable"].as_bool() {
+            Some(true) => MergeableState::Clean,
+            Some(false) => MergeableState::Conflicting,
+            None => MergeableState::Unknown,
+        },
+    }
+}
+
+/// Whether a title marks the pull request as a work in progress, which is
+/// how Gitea versions without a `draft` field represent drafts.
+fn is_wip_title(title: &str) -> bool {
+    let title = title.trim_start().to_ascii_uppercase();
+    title.starts_with("WIP:") || title.starts_with("[WIP]")
+}
+
+/// `title` with a work in progress prefix if `draft`, and without one
+/// otherwise.
+fn with_wip_prefix(title: &str, draft: bool) -> String {
+    let trimmed = title.trim_start();
+    let unmarked = if is_wip_title(trimmed) {
+        let prefix_len = if trimmed.starts_with('[') { 5 } else { 4 };
+        trimmed[prefix_len..].trim_start()
+    } else {
+        trimmed
+    };
+
+    if draft {
+        format!("WIP: {unmarked}")
+    } else {
+        unmarked.to_string()
+    }
+}
+
+fn parse_labels(value: &serde_json::Value) -> Vec<Label> {
+    value
+        .as_array()
+        .map(|arr| {
+            arr.iter()
+                .map(|v| Label {
+                    name: v["name"].as_str().unwrap().to_string(),
+                    color: v["color"]
+                        .as_str()
+                        .unwrap_or("")
+                        .trim_start_matches('#')
+                        .to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Count reactions per emoji from the comment reactions endpoint.
+fn summarize_reactions(reactions: &[serde_json::Value], viewer: Option<&str>) -> Vec<Reaction> {
+    ReactionContent::ALL
+        .into_iter()
+        .filter_map(|content| {
+            let matching = reactions
+                .iter()
+                .filter(|reaction| reaction["content"].as_str() == Some(content.as_str()))
+                .collect::<Vec<_>>();
+            (!matching.is_empty()).then(|| Reaction {
+                content,
+                count: matching.len() as u64,
+                viewer_reacted: viewer.is_some_and(|viewer| {
+                    matching
+                        .iter()
+                        .any(|reaction| reaction["user"]["login"].as_str() == Some(viewer))
+                }),
+            })
+        })
+        .collect()
+}
+
+fn parse_commit_status(value: &serde_json::Value) -> Check {
+    let state = value["status"].as_str().or_else(|| value["state"].as_str());
+    let status = match state {
+        Some("success") => CheckStatus::Success,
+        Some("pending") => CheckStatus::Pending,
+        Some("warning") => CheckStatus::Neutral,
+        _ => CheckStatus::Failure,
+    };
+
+    Check {
+        name: value["context"].as_str().unwrap_or("").to_string(),
+        status,
+        description: value["description"]
+            .as_str()
+            .filter(|d| !d.is_empty())
+            .map(ToString::to_string),
+        details_url: value["target_url"]
+            .as_str()
+            .filter(|u| !u.is_empty())
+            .map(ToString::to_string),
+    }
+}
+
+fn parse_datetime(s: &str) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .unwrap()
+        .with_timezone(&chrono::Utc)
+}
+
+fn parse_review_comment(value: &serde_json::Value) -> Comment {
+    let commit_sha = value["commit_id"].as_str().unwrap_or("").to_string();
+    let path = value["path"].as_str().unwrap_or("").to_string();
+    let position = value["position"].as_u64().filter(|line| *line > 0);
+    let original_position = value["original_position"].as_u64().filter(|line| *line > 0);
+
+    let comment_type = match (position, original_position) {
+        (Some(line), _) => CommentType::LineLevelComment {
+            commit_sha,
+            path,
+            line: LineNumber::New { line },
+        },
+        (None, Some(line)) => CommentType::LineLevelComment {
+            commit_sha,
+            path,
+            line: LineNumber::Old { line },
+        },
+        (None, None) => CommentType::FileLevelComment { path },
+    };
+
+    Comment {
+        id: value["id"].as_u64().unwrap(),
+        author: parse_user(&value["user"]),
+        body: value["body"].as_str().unwrap_or("").to_string(),
+        created_at: parse_datetime(value["created_at"].as_str().unwrap()),
+        updated_at: parse_datetime(value["updated_at"].as_str().unwrap()),
+        comment_type,
+        replies: Vec::new(),
+        resolved: value["resolver"].is_object(),
+        thread_id: None,
+        reactions: Vec::new(),
+    }
+}
+
+fn parse_issue_comment(value: &serde_json::Value) -> Comment {
+    Comment {
+        id: value["id"].as_u64().unwrap(),
+        author: parse_user(&value["user"]),
+        body: value["body"].as_str().unwrap_or("").to_string(),
+        created_at: parse_datetime(value["created_at"].as_str().unwrap()),
+        updated_at: parse_datetime(value["updated_at"].as_str().unwrap()),
+        comment_type: CommentType::General,
+        replies: Vec::new(),
+        resolved: false,
+        thread_id: None,
+        reactions: Vec::new(),
+    }
+}
+
+/// Group review comments into threads.
+///
+/// Gitea has no reply relation between review comments; a conversation is
+/// every comment on the same path and line. The oldest comment becomes the
+/// root and the rest are its replies, and the thread is resolved if the
+/// root is.
+fn thread_review_comments(mut comments: Vec<Comment>) -> Vec<Comment> {
+    comments.sort_by_key(|c| (c.created_at, c.id));
+
+    let mut threads: Vec<Comment> = Vec::new();
+    for comment in comments {
+        let root = threads.iter_mut().find(|root| {
+            matches!(
+                (&root.comment_type, &comment.comment_type),
+                (
+                    CommentType::LineLevelComment { path: a, line: la, .. },
+                    CommentType::LineLevelComment { path: b, line: lb, .. },
+                ) if a == b && la == lb
+            )
+        });
+
+        match root {
+            Some(root) => root.replies.push(comment),
+            None => threads.push(comment),
+        }
+    }
+
+    threads
+}
+
+fn find_comment_in_tree(comments: &[Comment], comment_id: u64) -> Option<Comment> {
+    for comment in comments {
+        if comment.id == comment_id {
+            return Some(comment.clone());
+        }
+        if let Some(found) = find_comment_in_tree(&comment.replies, comment_id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chadreview_pr_models::FileStatus;
+    use wiremock::matchers::{body_partial_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn user_json(id: u64, login: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "login": login,
+            "avatar_url": "https://example.com/avatar.png",
+            "html_url": format!("https://gitea.example.com/{login}")
+        })
+    }
+
+    fn review_comment_json(
+        id: u64,
+        created_at: &str,
+        position: u64,
+        original_position: u64,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "body": format!("comment {id}"),
+            "user": user_json(1, "reviewer"),
+            "resolver": null,
+            "path": "src/main.rs",
+            "commit_id": "abc123",
+            "position": position,
+            "original_position": original_position,
+            "created_at": created_at,
+            "updated_at": created_at
+        })
+    }
+
+    #[tokio::test]
+    async fn test_get_pr_success() {
+        let mock_server = MockServer::start().await;
+
+        let pr_json = serde_json::json!({
+            "number": 7,
+            "title": "WIP: Add feature",
+            "body": "Description",
+            "state": "open",
+            "merged": false,
+            "user": user_json(1, "author"),
+            "base": { "ref": "main" },
+            "head": { "ref": "feature", "sha": "abc123" },
+            "labels": [{ "name": "bug", "color": "#ee0701" }],
+            "assignees": null,
+            "requested_reviewers": [user_json(2, "reviewer")],
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2025-01-02T00:00:00+01:00"
+        });
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/owner/repo/pulls/7"))
+            .and(header("Authorization", "token test-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&pr_json))
+            .mount(&mock_server)
+            .await;
+
+        let client = GiteaProvider::new(format!("{}/", mock_server.uri()))
+            .with_token("test-token".to_string());
+
+        let pr = client.get_pr("owner", "repo", 7).await.unwrap();
+
+        assert_eq!(pr.number, 7);
+        assert_eq!(pr.state, PrState::Open);
+        assert!(pr.draft);
+        assert_eq!(pr.author.username, "author");
+        assert_eq!(pr.labels[0].color, "ee0701");
+        assert!(pr.assignees.is_empty());
+        assert_eq!(pr.reviewers[0].username, "reviewer");
+        assert_eq!(pr.head_sha, "abc123");
+        assert_eq!(pr.provider, "gitea");
+    }
+
+    #[tokio::test]
+    async fn test_get_diff() {
+        let mock_server = MockServer::start().await;
+
+        let diff = "\
+diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,2 +1,2 @@
+ fn main() {
+-    println!(\"old\");
++    println!(\"new\");
+diff --git a/logo.png b/logo.png
+new file mode 100644
+index 0000000..3333333
+Binary files /dev/null and b/logo.png differ
+diff --git a/old.txt b/new.txt
+similarity index 90%
+rename from old.txt
+rename to new.txt
+index 4444444..5555555 100644
+--- a/old.txt
++++ b/new.txt
+@@ -1 +1,2 @@
+ line
++added
+";
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/owner/repo/pulls/7.diff"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(diff))
+            .mount(&mock_server)
+            .await;
+
+        let client = GiteaProvider::new(mock_server.uri());
+        let files = client.get_diff("owner", "repo", 7).await.unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].filename, "src/main.rs");
+        assert_eq!(files[0].status, FileStatus::Modified);
+        assert_eq!((files[0].additions, files[0].deletions), (1, 1));
+        assert_eq!(files[1].filename, "new.txt");
+        assert_eq!(files[1].status, FileStatus::Renamed);
+        assert_eq!((files[1].additions, files[1].deletions), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_get_commits() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/owner/repo/pulls/7/commits"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {
+                    "sha": "aaa111",
+                    "created": "2024-01-02T00:00:00Z",
+                    "commit": {
+                        "message": "Add feature",
+                        "author": { "name": "Alice", "date": "2024-01-01T00:00:00Z" },
+                        "committer": { "name": "Alice", "date": "2024-01-02T00:00:00Z" }
+                    },
+                    "author": user_json(1, "alice")
+                }
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        let client = GiteaProvider::new(mock_server.uri());
+        let commits = client.get_commits("owner", "repo", 7).await.unwrap();
+
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].sha, "aaa111");
+        assert_eq!(commits[0].message, "Add feature");
+        assert_eq!(commits[0].author.username, "alice");
+    }
+
+    #[tokio::test]
+    async fn test_get_comments_threads_by_line() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/owner/repo/pulls/7/reviews"))
+            .and(query_param("page", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "id": 10, "comments_count": 2 },
+                { "id": 11, "comments_count": 0 },
+                { "id": 12, "comments_count": 1 }
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        let mut resolved = review_comment_json(100, "2025-01-01T00:00:00Z", 5, 0);
+        resolved["resolver"] = user_json(2, "author");
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/owner/repo/pulls/7/reviews/10/comments"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                resolved,
+                review_comment_json(101, "2025-01-01T00:00:00Z", 0, 3)
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/owner/repo/pulls/7/reviews/12/comments"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                review_comment_json(102, "2025-01-02T00:00:00Z", 5, 0)
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/owner/repo/issues/7/comments"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                    "id": 200,
+                    "body": "General comment",
+                    "user": user_json(3, "someone"),
+                    "created_at": "2025-01-01T00:00:00Z",
+                    "updated_at": "2025-01-01T00:00:00Z"
+                }])),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = GiteaProvider::new(mock_server.uri());
+        let comments = client.get_comments("owner", "repo", 7).await.unwrap();
+
+        assert_eq!(comments.len(), 3);
+
+        assert_eq!(comments[0].id, 100);
+        assert!(comments[0].resolved);
+        assert_eq!(comments[0].replies.len(), 1);
+        assert_eq!(comments[0].replies[0].id, 102);
+        match &comments[0].comment_type {
+            CommentType::LineLevelComment { line, .. } => {
+                assert_eq!(*line, LineNumber::New { line: 5 });
+            }
+            other => panic!("Expected line comment, got {other:?}"),
+        }
+
+        assert_eq!(comments[1].id, 101);
+        match &comments[1].comment_type {
+            CommentType::LineLevelComment { line, .. } => {
+                assert_eq!(*line, LineNumber::Old { line: 3 });
+            }
+            other => panic!("Expected line comment, got {other:?}"),
+        }
+
+        assert_eq!(comments[2].id, 200);
+        assert_eq!(comments[2].comment_type, CommentType::General);
+    }
+
+    #[tokio::test]
+    async fn test_create_comment_line_level() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/v1/repos/owner/repo/pulls/7/reviews"))
+            .and(body_partial_json(serde_json::json!({
+                "event": "COMMENT",
+                "commit_id": "abc123",
+                "comments": [{ "path": "src/main.rs", "body": "Nice", "old_position": 3 }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": 20 })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/owner/repo/pulls/7/reviews/20/comments"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                review_comment_json(300, "2025-01-01T00:00:00Z", 0, 3)
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        let client = GiteaProvider::new(mock_server.uri());
+        let comment = client
+            .create_comment(
+                "owner",
+                "repo",
+                7,
+                CreateComment {
+                    body: "Nice".to_string(),
+                    comment_type: CommentType::LineLevelComment {
+                        path: "src/main.rs".to_string(),
+                        commit_sha: "abc123".to_string(),
+                        line: LineNumber::Old { line: 3 },
+                    },
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(comment.id, 300);
+    }
+
+    #[tokio::test]
+    async fn test_create_comment_file_level_unsupported() {
+        let client = GiteaProvider::new("http://localhost:1");
+        let result = client
+            .create_comment(
+                "owner",
+                "repo",
+                7,
+                CreateComment {
+                    body: "File comment".to_string(),
+                    comment_type: CommentType::FileLevelComment {
+                        path: "src/main.rs".to_string(),
+                    },
+                },
+            )
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delete_comment_unauthorized() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/repos/owner/repo/issues/comments/200"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&mock_server)
+            .await;
+
+        let client = GiteaProvider::new(mock_server.uri());
+        let result = client.delete_comment("owner", "repo", 7, 200).await;
+
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("403"));
+    }
+
+    #[tokio::test]
+    async fn test_toggle_reaction_removes_viewer_reaction() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path(
+                "/api/v1/repos/owner/repo/issues/comments/200/reactions",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "content": "heart", "user": { "id": 1, "login": "viewer" } },
+                { "content": "heart", "user": { "id": 2, "login": "other" } }
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/user"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "login": "viewer" })),
+            )
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("DELETE"))
+            .and(path(
+                "/api/v1/repos/owner/repo/issues/comments/200/reactions",
+            ))
+            .and(body_partial_json(serde_json::json!({ "content": "heart" })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = GiteaProvider::new(mock_server.uri()).with_token("token".to_string());
+        let reactions = client
+            .toggle_reaction("owner", "repo", 200, ReactionContent::Heart)
+            .await
+            .unwrap();
+
+        assert_eq!(reactions.len(), 1);
+        assert_eq!(reactions[0].content, ReactionContent::Heart);
+        assert_eq!(reactions[0].count, 2);
+        assert!(reactions[0].viewer_reacted);
+    }
+
+    #[tokio::test]
+    async fn test_submit_review_approve() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/v1/repos/owner/repo/pulls/7/reviews"))
+            .and(body_partial_json(serde_json::json!({
+                "event": "APPROVED",
+                "body": "LGTM",
+                "commit_id": "abc123",
+                "comments": [{ "path": "src/main.rs", "body": "Nice", "new_position": 4 }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": 30,
+                "user": user_json(1, "reviewer"),
+                "body": "LGTM",
+                "state": "APPROVED",
+                "submitted_at": "2025-01-01T00:00:00Z"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = GiteaProvider::new(mock_server.uri());
+        let review = client
+            .submit_review(
+                "owner",
+                "repo",
+                7,
+                ReviewVerdict::Approve,
+                "LGTM".to_string(),
+                vec![CreateComment {
+                    body: "Nice".to_string(),
+                    comment_type: CommentType::LineLevelComment {
+                        path: "src/main.rs".to_string(),
+                        commit_sha: "abc123".to_string(),
+                        line: LineNumber::New { line: 4 },
+                    },
+                }],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(review.id, 30);
+        assert_eq!(review.verdict, ReviewVerdict::Approve);
+        assert_eq!(review.author.username, "reviewer");
+    }
+
+    #[tokio::test]
+    async fn test_merge_pr() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/v1/repos/owner/repo/pulls/7/merge"))
+            .and(body_partial_json(serde_json::json!({ "Do": "rebase" })))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let client = GiteaProvider::new(mock_server.uri());
+        client
+            .merge_pr("owner", "repo", 7, MergeStrategy::Rebase)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_add_labels_by_id() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/owner/repo/labels"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "id": 1, "name": "bug", "color": "#d73a4a" },
+                { "id": 2, "name": "needs review", "color": "#0e8a16" }
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/api/v1/repos/owner/repo/issues/7/labels"))
+            .and(body_partial_json(serde_json::json!({ "labels": [2] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "id": 2, "name": "needs review", "color": "#0e8a16" }
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        let client = GiteaProvider::new(mock_server.uri());
+
+        let labels = client
+            .add_labels("owner", "repo", 7, vec!["needs review".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].color, "0e8a16");
+
+        assert!(
+            client
+                .add_labels("owner", "repo", 7, vec!["missing".to_string()])
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_checks() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/api/v1/repos/owner/repo/commits/abc123/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "state": "failure",
+                "statuses": [
+                    {
+                        "context": "ci / build",
+                        "status": "success",
+                        "description": "Successful in 1m",
+                        "target_url": "https://codeberg.org/owner/repo/actions/runs/1"
+                    },
+                    {
+                        "context": "ci / test",
+                        "status": "failure",
+                        "description": "",
+                        "target_url": ""
+                    }
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = GiteaProvider::new(mock_server.uri());
+        let checks = client.get_checks("owner", "repo", "abc123").await.unwrap();
+
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].status, CheckStatus::Success);
+        assert_eq!(
+            checks[0].details_url.as_deref(),
+            Some("https://codeberg.org/owner/repo/actions/runs/1")
+        );
+        assert_eq!(checks[1].status, CheckStatus::Failure);
+        assert!(checks[1].description.is_none());
+        assert!(checks[1].details_url.is_none());
+    }
+
+    #[test]
+    fn test_is_wip_title() {
+        assert!(is_wip_title("WIP: thing"));
+        assert!(is_wip_title("[wip] thing"));
+        assert!(!is_wip_title("Wipe cache"));
+    }
+
+    #[test]
+    fn test_with_wip_prefix() {
+        assert_eq!(with_wip_prefix("Add feature", true), "WIP: Add feature");
+        assert_eq!(
+            with_wip_prefix("WIP: Add feature", true),
+            "WIP: Add feature"
+        );
+        assert_eq!(with_wip_prefix("[WIP] Add feature", false), "Add feature");
+        assert_eq!(with_wip_prefix("wip:Add feature", false), "Add feature");
+    }
+}
@@ -0,0 +1,160 @@
+//! Conditional request cache for GitHub REST responses.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// How many responses are kept before the least recently used ones are
+/// dropped.
+const MAX_ENTRIES: usize = 1024;
+
+/// A response body along with the validators GitHub sent with it.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Raw `Link` header, kept so cached pages can still be paginated.
+    pub link: Option<String>,
+    pub body: serde_json::Value,
+}
+
+impl CachedResponse {
+    /// Whether the response can be revalidated with a conditional request.
+    #[must_use]
+    pub const fn has_validators(&self) -> bool {
+        self.etag.is_some() || self.last_modified.is_some()
+    }
+}
+
+/// Responses keyed by request URL, revalidated with `If-None-Match` /
+/// `If-Modified-Since`.
+///
+/// GitHub doesn't count `304 Not Modified` responses against the rate
+/// limit, so reloading an unchanged PR is nearly free. At most
+/// `MAX_ENTRIES` responses are kept, dropping the least recently used.
+#[derive(Debug, Default)]
+pub struct ResponseCache {
+    entries: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// Responses with the tick they were last used at.
+    responses: HashMap<String, (u64, CachedResponse)>,
+    tick: u64,
+}
+
+impl Entries {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+impl ResponseCache {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entries(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[must_use]
+    pub fn get(&self, url: &str) -> Option<CachedResponse> {
+        let mut entries = self.entries();
+        let tick = entries.next_tick();
+        let (last_used, response) = entries.responses.get_mut(url)?;
+        *last_used = tick;
+        Some(response.clone())
+    }
+
+    /// Store a response for `url`. Responses without an `ETag` or
+    /// `Last-Modified` header can't be revalidated and are not stored.
+    pub fn insert(&self, url: &str, response: CachedResponse) {
+        if !response.has_validators() {
+            return;
+        }
+
+        let mut entries = self.entries();
+        if entries.responses.len() >= MAX_ENTRIES && !entries.responses.contains_key(url) {
+            let least_recent = entries
+                .responses
+                .iter()
+                .min_by_key(|(_, (last_used, _))| *last_used)
+                .map(|(url, _)| url.clone());
+            if let Some(url) = least_recent {
+                entries.responses.remove(&url);
+            }
+        }
+        let tick = entries.next_tick();
+        entries.responses.insert(url.to_string(), (tick, response));
+    }
+
+    pub fn clear(&self) {
+        self.entries().responses.clear();
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries().responses.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(etag: Option<&str>) -> CachedResponse {
+        CachedResponse {
+            etag: etag.map(ToString::to_string),
+            last_modified: None,
+            link: None,
+            body: serde_json::json!({ "number": 1 }),
+        }
+    }
+
+    #[test]
+    fn test_stores_responses_with_validators() {
+        let cache = ResponseCache::new();
+        cache.insert("https://api.github.com/a", response(Some("\"abc\"")));
+
+        let cached = cache.get("https://api.github.com/a").unwrap();
+        assert_eq!(cached.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(cached.body["number"], 1);
+        assert!(cache.get("https://api.github.com/b").is_none());
+    }
+
+    #[test]
+    fn test_skips_responses_without_validators() {
+        let cache = ResponseCache::new();
+        cache.insert("https://api.github.com/a", response(None));
+
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_drops_least_recently_used_responses() {
+        let cache = ResponseCache::new();
+        for i in 0..MAX_ENTRIES {
+            cache.insert(
+                &format!("https://api.github.com/{i}"),
+                response(Some("\"abc\"")),
+            );
+        }
+        // Using the oldest response keeps it over the next oldest
+        assert!(cache.get("https://api.github.com/0").is_some());
+
+        cache.insert("https://api.github.com/new", response(Some("\"abc\"")));
+
+        assert_eq!(cache.len(), MAX_ENTRIES);
+        assert!(cache.get("https://api.github.com/0").is_some());
+        assert!(cache.get("https://api.github.com/1").is_none());
+        assert!(cache.get("https://api.github.com/new").is_some());
+    }
+}
@@ -1,18 +1,41 @@
 use anyhow::Result;
-use chadreview_diff::parse_unified_diff;
+use chadreview_diff::{parse_unified_diff, split_diff};
 use chadreview_diff_models::LineNumber;
 use chadreview_git_provider::GitProvider;
 use chadreview_pr_models::{
-    Comment, CommentType, CreateComment, DiffFile, FileStatus, Label, PrState, PullRequest, User,
+    Check, CheckStatus, Comment, CommentType, Commit, CreateComment, DiffFile, FileStatus, Label,
+    MergeStrategy, MergeableState, PrState, PullRequest, RateLimitStatus, Reaction,
+    ReactionContent, Review, ReviewVerdict, User,
 };
 use chadreview_syntax::SyntaxHighlighter;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use crate::cache::{CachedResponse, ResponseCache};
 
 pub struct GitHubProvider {
     http_client: reqwest::Client,
-    auth_token: Option<String>,
+    /// Swappable at runtime so signing in doesn't require a restart.
+    auth_token: RwLock<Option<String>>,
     base_url: String,
+    cache: ResponseCache,
+    /// Latest quota per rate limit resource (`core`, `graphql`, ...).
+    rate_limits: RwLock<HashMap<String, RateLimitStatus>>,
 }
 
+/// Review comment fields requested from GraphQL, parsed by
+/// [`parse_graphql_review_comment`].
+const REVIEW_COMMENT_FIELDS: &str = "id databaseId body createdAt updatedAt \
+    author { login avatarUrl url } path line originalLine diffHunk replyTo { databaseId } \
+    reactionGroups { content viewerHasReacted reactors { totalCount } }";
+
+/// Retry a rate limited request at most this many times.
+const MAX_RETRIES: u32 = 3;
+
+/// Give up instead of waiting longer than this for a rate limit to lift.
+const MAX_RETRY_DELAY: Duration = Duration::from_mins(1);
+
 impl GitHubProvider {
     /// Create a new GitHub provider without authentication.
     ///
@@ -27,23 +50,469 @@ impl GitHubProvider {
             .unwrap();
         Self {
             http_client,
-            auth_token: None,
+            auth_token: RwLock::new(None),
             base_url: "https://api.github.com".to_string(),
+            cache: ResponseCache::new(),
+            rate_limits: RwLock::new(HashMap::new()),
         }
     }
 
     #[must_use]
-    pub fn with_token(mut self, token: String) -> Self {
-        self.auth_token = Some(token);
+    pub fn with_token(self, token: String) -> Self {
+        self.set_token(Some(token));
         self
     }
 
+    /// Replace the token used for API requests, e.g. after signing in or
+    /// out. Cached responses are dropped since they may have been fetched
+    /// as a different user.
+    pub fn set_token(&self, token: Option<String>) {
+        *self
+            .auth_token
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = token;
+        self.cache.clear();
+    }
+
+    #[must_use]
+    pub fn has_token(&self) -> bool {
+        self.auth_token().is_some()
+    }
+
+    fn auth_token(&self) -> Option<String> {
+        self.auth_token
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
     #[must_use]
     pub fn with_base_url(mut self, base_url: String) -> Self {
         self.base_url = base_url;
         self
     }
 
+    /// Send a request, recording its rate limit headers and retrying when
+    /// GitHub asks us to back off (see [`retry_delay`]).
+    async fn send_request(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let mut attempt = 0;
+
+        loop {
+            let response = request
+                .try_clone()
+                .ok_or_else(|| anyhow::anyhow!("Request body can't be retried"))?
+                .send()
+                .await?;
+
+            if let Some(rate_limit) = parse_rate_limit(response.headers()) {
+                self.rate_limits
+                    .write()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .insert(rate_limit.resource.clone(), rate_limit);
+            }
+
+            if attempt < MAX_RETRIES
+                && let Some(delay) = retry_delay(response.status(), response.headers())
+            {
+                if delay > MAX_RETRY_DELAY {
+                    log::warn!("GitHub rate limit won't reset for {delay:?}, not retrying");
+                } else {
+                    log::warn!(
+                        "GitHub rate limited ({}), retrying in {delay:?}",
+                        response.status()
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                    continue;
+                }
+            }
+
+            return Ok(response);
+        }
+    }
+
+    /// Fetch a single JSON resource from the REST API.
+    async fn get_json(&self, url: &str) -> Result<serde_json::Value> {
+        Ok(self.get_cached(url).await?.body)
+    }
+
+    /// GET a REST resource, revalidating any cached copy with a conditional
+    /// request and serving it again on `304 Not Modified`.
+    async fn get_cached(&self, url: &str) -> Result<CachedResponse> {
+        let cached = self.cache.get(url);
+
+        log::debug!("GET {url}");
+        let mut request = self
+            .http_client
+            .get(url)
+            .header("Accept", "application/vnd.github.v3+json");
+
+        if let Some(token) = self.auth_token() {
+            request = request.bearer_auth(token);
+        }
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header("If-None-Match", etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header("If-Modified-Since", last_modified);
+            }
+        }
+
+        let response = self.send_request(request).await?;
+        let status = response.status();
+
+        if status == reqwest::StatusCode::NOT_MODIFIED
+            && let Some(cached) = cached
+        {
+            log::debug!("Serving cached response for {url}");
+            return Ok(cached);
+        }
+
+        if !status.is_success() {
+            log::error!("GitHub API error: {}", response.text().await?);
+            anyhow::bail!("GitHub API error: {status}");
+        }
+
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string)
+        };
+        let etag = header("ETag");
+        let last_modified = header("Last-Modified");
+        let link = header("Link");
+
+        let fetched = CachedResponse {
+            etag,
+            last_modified,
+            link,
+            body: response.json().await?,
+        };
+        self.cache.insert(url, fetched.clone());
+
+        Ok(fetched)
+    }
+
+    /// Send a REST API request, with an optional JSON body, and parse the
+    /// JSON response.
+    async fn send_json(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        log::debug!("{method} {url}");
+        let mut request = self
+            .http_client
+            .request(method, url)
+            .header("Accept", "application/vnd.github.v3+json");
+
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        if let Some(token) = self.auth_token() {
+            request = request.bearer_auth(token);
+        }
+
+        let response = self.send_request(request).await?;
+        let status = response.status();
+
+        if !status.is_success() {
+            log::error!("GitHub API error: {}", response.text().await?);
+            anyhow::bail!("GitHub API error: {status}");
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Fetch a PR or commit as a raw `git diff`.
+    async fn get_raw_diff(&self, url: &str) -> Result<String> {
+        log::debug!("GET {url} (diff)");
+        let mut request = self
+            .http_client
+            .get(url)
+            .header("Accept", "application/vnd.github.diff");
+
+        if let Some(token) = self.auth_token() {
+            request = request.bearer_auth(token);
+        }
+
+        let response = self.send_request(request).await?;
+        let status = response.status();
+
+        if !status.is_success() {
+            log::error!("GitHub API error: {}", response.text().await?);
+            anyhow::bail!("GitHub API error: {status}");
+        }
+
+        Ok(response.text().await?)
+    }
+
+    /// Patches for the files GitHub left out of a `files` list, taken from
+    /// the raw diff at `url`.
+    ///
+    /// GitHub omits `patch` for large files. The raw diff isn't truncated
+    /// the same way, so it's only fetched when something is missing.
+    async fn missing_patches(
+        &self,
+        url: &str,
+        files_data: &[serde_json::Value],
+    ) -> HashMap<String, String> {
+        let missing = files_data
+            .iter()
+            .filter(|file_data| file_data["patch"].is_null())
+            .count();
+        if missing == 0 {
+            return HashMap::new();
+        }
+
+        log::debug!("{missing} files have no patch data, falling back to the raw diff");
+        match self.get_raw_diff(url).await {
+            Ok(diff) => split_diff(&diff)
+                .into_iter()
+                .filter(|file| !file.binary && !file.patch.is_empty())
+                .map(|file| (file.filename, file.patch))
+                .collect(),
+            Err(e) => {
+                log::warn!("Failed to fetch raw diff for {missing} files without patches: {e}");
+                HashMap::new()
+            }
+        }
+    }
+
+    /// Login of the authenticated user, or `None` without a token.
+    async fn viewer_login(&self) -> Result<Option<String>> {
+        if !self.has_token() {
+            return Ok(None);
+        }
+
+        let user = self.get_json(&format!("{}/user", self.base_url)).await?;
+        Ok(user["login"].as_str().map(ToString::to_string))
+    }
+
+    /// List the raw reactions on a comment along with the reactions endpoint
+    /// they came from, trying the review comment endpoint before the issue
+    /// comment one.
+    async fn list_reactions(
+        &self,
+        owner: &str,
+        repo: &str,
+        comment_id: u64,
+    ) -> Result<(String, Vec<serde_json::Value>)> {
+        let review_url = format!(
+            "{}/repos/{owner}/{repo}/pulls/comments/{comment_id}/reactions",
+            self.base_url
+        );
+        if let Ok(reactions) = self.fetch_all_paginated(&review_url).await {
+            return Ok((review_url, reactions));
+        }
+
+        let issue_url = format!(
+            "{}/repos/{owner}/{repo}/issues/comments/{comment_id}/reactions",
+            self.base_url
+        );
+        let reactions = self.fetch_all_paginated(&issue_url).await?;
+        Ok((issue_url, reactions))
+    }
+
+    /// Fetch every page of a paginated REST endpoint, following `Link`
+    /// headers. Each page is cached separately (see [`Self::get_cached`]).
+    async fn fetch_all_paginated(&self, url: &str) -> Result<Vec<serde_json::Value>> {
+        const MAX_FILES: usize = 3000;
+        const PER_PAGE: u32 = 100;
+
+        let mut all_files = Vec::new();
+        let mut page = 1;
+
+        let mut url = url.to_string();
+
+        if url.contains('?') {
+            url.push('&');
+        } else {
+            url.push('?');
+        }
+
+        let url = url.as_str();
+
+        loop {
+            let url = format!("{url}per_page={PER_PAGE}&page={page}");
+
+            log::debug!("GET {url} (page {page})");
+
+            let response = self.get_cached(&url).await?;
+            let link_header = response.link.as_deref().map(parse_link_header);
+
+            let page_files: Vec<serde_json::Value> = serde_json::from_value(response.body)?;
+            let files_in_page = page_files.len();
+
+            log::debug!("Fetched {files_in_page} files from page {page}");
+
+            all_files.extend(page_files);
+
+            if all_files.len() >= MAX_FILES {
+                log::warn!(
+                    "PR has {} files, reached GitHub's {} file limit",
+                    all_files.len(),
+                    MAX_FILES
+                );
+                break;
+            }
+
+            if let Some(links) = link_header
+                && links.next.is_some()
+            {
+                page += 1;
+                continue;
+            }
+
+            break;
+        }
+
+        log::debug!(
+            "Fetched total of {} files across {} page(s)",
+            all_files.len(),
+            page
+        );
+
+        Ok(all_files)
+    }
+
+    async fn fetch_all_pr_files(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+    ) -> Result<Vec<serde_json::Value>> {
+        log::debug!("Fetching PR files for {owner}/{repo} #{number}");
+
+        self.fetch_all_paginated(&format!(
+            "{}/repos/{owner}/{repo}/pulls/{number}/files",
+            self.base_url
+        ))
+        .await
+    }
+
+    /// Fetch every review thread with its resolution state, thread ID and
+    /// reactions through GraphQL, following the thread and comment cursors.
+    async fn get_review_threads(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+    ) -> Result<Vec<Comment>> {
+        let mut all_comments = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let query = format!(
+                r#"query {{ repository(owner: "{owner}", name: "{repo}") {{ pullRequest(number: {number}) {{ reviewThreads(first: 100, after: {after}) {{ pageInfo {{ hasNextPage endCursor }} nodes {{ id isResolved comments(first: 100) {{ pageInfo {{ hasNextPage endCursor }} nodes {{ {REVIEW_COMMENT_FIELDS} }} }} }} }} }} }} }}"#,
+                after = serde_json::to_string(&cursor)?,
+            );
+            let result = self.graphql_query(&query).await?;
+            let threads = &result["data"]["repository"]["pullRequest"]["reviewThreads"];
+
+            for thread in threads["nodes"].as_array().into_iter().flatten() {
+                let is_resolved = thread["isResolved"].as_bool().unwrap_or(false);
+                let thread_id = thread["id"].as_str().map(ToString::to_string);
+
+                let mut comment_nodes = thread["comments"]["nodes"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default();
+                if let Some(thread_id) = &thread_id
+                    && let Some(after) = next_cursor(&thread["comments"]["pageInfo"])
+                {
+                    comment_nodes.extend(self.get_thread_comments(thread_id, after).await?);
+                }
+
+                let mut thread_comments_with_replies = Vec::new();
+
+                for comment_data in &comment_nodes {
+                    let mut comment = parse_graphql_review_comment(comment_data);
+                    // Set resolved status from thread
+                    comment.resolved = is_resolved;
+                    comment.thread_id.clone_from(&thread_id);
+
+                    let reply_to = comment_data["replyTo"]["databaseId"].as_u64();
+                    thread_comments_with_replies.push((comment, reply_to));
+                }
+
+                // Thread the comments within this review thread
+                all_comments.extend(thread_comments(thread_comments_with_replies));
+            }
+
+            match next_cursor(&threads["pageInfo"]) {
+                Some(after) => cursor = Some(after.to_string()),
+                None => break,
+            }
+        }
+
+        Ok(all_comments)
+    }
+
+    /// Fetch the comments of a review thread after `after`, for threads
+    /// with more comments than fit in the first page.
+    async fn get_thread_comments(
+        &self,
+        thread_id: &str,
+        after: &str,
+    ) -> Result<Vec<serde_json::Value>> {
+        let mut comments = Vec::new();
+        let mut cursor = after.to_string();
+
+        loop {
+            let query = format!(
+                r#"query {{ node(id: "{thread_id}") {{ ... on PullRequestReviewThread {{ comments(first: 100, after: "{cursor}") {{ pageInfo {{ hasNextPage endCursor }} nodes {{ {REVIEW_COMMENT_FIELDS} }} }} }} }} }}"#
+            );
+            let result = self.graphql_query(&query).await?;
+            let page = &result["data"]["node"]["comments"];
+
+            if let Some(nodes) = page["nodes"].as_array() {
+                comments.extend(nodes.iter().cloned());
+            }
+
+            match next_cursor(&page["pageInfo"]) {
+                Some(after) => cursor = after.to_string(),
+                None => break,
+            }
+        }
+
+        Ok(comments)
+    }
+
+    /// Fetch review comments through REST, threaded by `in_reply_to_id`.
+    ///
+    /// REST doesn't expose review thread state, so every comment is
+    /// unresolved and has no thread ID.
+    async fn get_review_comments_rest(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+    ) -> Result<Vec<Comment>> {
+        let review_comments = self
+            .fetch_all_paginated(&format!(
+                "{}/repos/{owner}/{repo}/pulls/{number}/comments",
+                self.base_url
+            ))
+            .await?;
+
+        Ok(thread_comments(
+            review_comments
+                .iter()
+                .map(|value| {
+                    (
+                        parse_review_comment(value),
+                        value["in_reply_to_id"].as_u64(),
+                    )
+                })
+                .collect(),
+        ))
+    }
+
     /// Execute a GraphQL query against GitHub's GraphQL API.
     async fn graphql_query(&self, query: &str) -> Result<serde_json::Value> {
         let url = format!("{}/graphql", self.base_url);
@@ -57,11 +526,11 @@ impl GitHubProvider {
             .header("Accept", "application/vnd.github.v3+json")
             .json(&body);
 
-        if let Some(token) = &self.auth_token {
+        if let Some(token) = self.auth_token() {
             request = request.bearer_auth(token);
         }
 
-        let response = request.send().await?;
+        let response = self.send_request(request).await?;
         let status = response.status();
 
         if !status.is_success() {
@@ -95,168 +564,92 @@ impl GitProvider for GitHubProvider {
             "{}/repos/{}/{}/pulls/{}",
             self.base_url, owner, repo, number
         );
-        log::debug!("GET {url}");
-        let mut request = self
-            .http_client
-            .get(&url)
-            .header("Accept", "application/vnd.github.v3+json");
-
-        if let Some(token) = &self.auth_token {
-            request = request.bearer_auth(token);
-        }
+        let pr_data = self.get_json(&url).await?;
 
-        let response = request.send().await?;
-        let status = response.status();
-
-        if !status.is_success() {
-            log::error!("GitHub API error: {}", response.text().await?);
-            anyhow::bail!("GitHub API error: {status}");
-        }
-
-        let pr_data: serde_json::Value = response.json().await?;
-
-        Ok(PullRequest {
-            number: pr_data["number"].as_u64().unwrap(),
-            owner: owner.to_string(),
-            repo: repo.to_string(),
-            title: pr_data["title"].as_str().unwrap().to_string(),
-            description: pr_data["body"].as_str().unwrap_or("").to_string(),
-            author: parse_user(&pr_data["user"]),
-            state: parse_pr_state(&pr_data),
-            draft: pr_data["draft"].as_bool().unwrap_or(false),
-            base_branch: pr_data["base"]["ref"].as_str().unwrap().to_string(),
-            head_branch: pr_data["head"]["ref"].as_str().unwrap().to_string(),
-            labels: parse_labels(&pr_data["labels"]),
-            assignees: parse_users(&pr_data["assignees"]),
-            reviewers: parse_users(&pr_data["requested_reviewers"]),
-            head_sha: pr_data["head"]["sha"].as_str().unwrap().to_string(),
-            created_at: parse_datetime(pr_data["created_at"].as_str().unwrap()),
-            updated_at: parse_datetime(pr_data["updated_at"].as_str().unwrap()),
-            provider: "github".to_string(),
-        })
+        Ok(parse_pull_request(owner, repo, &pr_data))
     }
 
     async fn get_diff(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<DiffFile>> {
-        let files_data = fetch_all_pr_files(
-            &self.http_client,
-            &self.base_url,
-            owner,
-            repo,
-            number,
-            self.auth_token.as_ref(),
-        )
-        .await?;
-
-        let highlighter = SyntaxHighlighter::new();
-        let mut result = Vec::new();
-
-        for file_data in &files_data {
-            let filename = file_data["filename"].as_str().unwrap();
-            let status = parse_file_status(file_data["status"].as_str().unwrap());
-            let additions = file_data["additions"].as_u64().unwrap();
-            let deletions = file_data["deletions"].as_u64().unwrap();
-
-            if let Some(patch_str) = file_data["patch"].as_str() {
-                let parsed = parse_unified_diff(
-                    filename,
-                    status,
-                    additions,
-                    deletions,
-                    patch_str,
-                    &highlighter,
-                )
-                .map_err(|e| anyhow::anyhow!(e))?;
-                result.push(parsed);
-            } else {
-                log::debug!(
-                    "Skipping {filename} - no patch data (likely binary or no content changes)"
-                );
-            }
-        }
+        let files_data = self.fetch_all_pr_files(owner, repo, number).await?;
+        let fallback = self
+            .missing_patches(
+                &format!("{}/repos/{owner}/{repo}/pulls/{number}", self.base_url),
+                &files_data,
+            )
+            .await;
 
-        Ok(result)
+        parse_diff_files(&files_data, &fallback)
     }
 
-    async fn get_comments(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<Comment>> {
-        // Use GraphQL to fetch review threads with resolved status
-        let query = format!(
-            r#"
-            query {{
-              repository(owner: "{owner}", name: "{repo}") {{
-                pullRequest(number: {number}) {{
-                  reviewThreads(first: 100) {{
-                    nodes {{
-                      id
-                      isResolved
-                      comments(first: 100) {{
-                        nodes {{
-                          id
-                          databaseId
-                          body
-                          createdAt
-                          updatedAt
-                          author {{
-                            login
-                            avatarUrl
-                            url
-                          }}
-                          path
-                          line
-                          originalLine
-                          diffHunk
-                          replyTo {{
-                            databaseId
-                          }}
-                        }}
-                      }}
-                    }}
-                  }}
-                }}
-              }}
-            }}
-            "#
-        );
-
-        let result = self.graphql_query(&query).await?;
-
-        // Also fetch general issue comments (not part of review threads)
-        let issue_comments = fetch_all_paginated(
-            &self.http_client,
-            &format!(
-                "{}/repos/{owner}/{repo}/issues/{number}/comments",
+    async fn get_commits(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<Commit>> {
+        let commits = self
+            .fetch_all_paginated(&format!(
+                "{}/repos/{owner}/{repo}/pulls/{number}/commits",
                 self.base_url
-            ),
-            self.auth_token.as_ref(),
-        )
-        .await?;
+            ))
+            .await?;
 
-        let mut all_comments = Vec::new();
+        Ok(commits.iter().map(parse_commit).collect())
+    }
 
-        // Parse GraphQL review threads
-        if let Some(threads) =
-            result["data"]["repository"]["pullRequest"]["reviewThreads"]["nodes"].as_array()
-        {
-            for thread in threads {
-                let is_resolved = thread["isResolved"].as_bool().unwrap_or(false);
+    async fn get_commit_diff(&self, owner: &str, repo: &str, sha: &str) -> Result<Vec<DiffFile>> {
+        let url = format!("{}/repos/{owner}/{repo}/commits/{sha}", self.base_url);
+        let commit = self.get_json(&url).await?;
+        let files_data = commit["files"].as_array().map_or(&[][..], Vec::as_slice);
+        let fallback = self.missing_patches(&url, files_data).await;
 
-                if let Some(comments) = thread["comments"]["nodes"].as_array() {
-                    let mut thread_comments_with_replies = Vec::new();
+        parse_diff_files(files_data, &fallback)
+    }
 
-                    for comment_data in comments {
-                        let mut comment = parse_graphql_review_comment(comment_data);
-                        // Set resolved status from thread
-                        comment.resolved = is_resolved;
+    async fn get_checks(&self, owner: &str, repo: &str, sha: &str) -> Result<Vec<Check>> {
+        // GitHub Actions and other apps report check runs, while older
+        // integrations report commit statuses; the PR page shows both
+        let check_runs = self
+            .get_json(&format!(
+                "{}/repos/{owner}/{repo}/commits/{sha}/check-runs?per_page=100",
+                self.base_url
+            ))
+            .await?;
+        let combined_status = self
+            .get_json(&format!(
+                "{}/repos/{owner}/{repo}/commits/{sha}/status",
+                self.base_url
+            ))
+            .await?;
+
+        let mut checks: Vec<Check> = check_runs["check_runs"]
+            .as_array()
+            .map(|runs| runs.iter().map(parse_check_run).collect())
+            .unwrap_or_default();
+        if let Some(statuses) = combined_status["statuses"].as_array() {
+            checks.extend(statuses.iter().map(parse_commit_status));
+        }
 
-                        let reply_to = comment_data["replyTo"]["databaseId"].as_u64();
-                        thread_comments_with_replies.push((comment, reply_to));
-                    }
+        Ok(checks)
+    }
 
-                    // Thread the comments within this review thread
-                    let threaded = thread_comments(thread_comments_with_replies);
-                    all_comments.extend(threaded);
+    async fn get_comments(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<Comment>> {
+        // GraphQL gives us resolution state and thread IDs, but requires a
+        // token, and fine-grained tokens may lack the scope for it
+        let mut all_comments = if self.has_token() {
+            match self.get_review_threads(owner, repo, number).await {
+                Ok(comments) => comments,
+                Err(e) => {
+                    log::warn!("Falling back to REST for review comments: {e}");
+                    self.get_review_comments_rest(owner, repo, number).await?
                 }
             }
-        }
+        } else {
+            self.get_review_comments_rest(owner, repo, number).await?
+        };
+
+        // Also fetch general issue comments (not part of review threads)
+        let issue_comments = self
+            .fetch_all_paginated(&format!(
+                "{}/repos/{owner}/{repo}/issues/{number}/comments",
+                self.base_url
+            ))
+            .await?;
 
         // Add general issue comments (not resolved)
         for comment_data in &issue_comments {
@@ -290,11 +683,11 @@ impl GitProvider for GitHubProvider {
                 .get(&review_url)
                 .header("Accept", "application/vnd.github.v3+json");
 
-            if let Some(token) = &self.auth_token {
+            if let Some(token) = self.auth_token() {
                 review_request = review_request.bearer_auth(token);
             }
 
-            let review_response = review_request.send().await?;
+            let review_response = self.send_request(review_request).await?;
 
             if review_response.status().is_success() {
                 let comment_data: serde_json::Value = review_response.json().await?;
@@ -311,11 +704,11 @@ impl GitProvider for GitHubProvider {
                 .get(&issue_url)
                 .header("Accept", "application/vnd.github.v3+json");
 
-            if let Some(token) = &self.auth_token {
+            if let Some(token) = self.auth_token() {
                 issue_request = issue_request.bearer_auth(token);
             }
 
-            let issue_response = issue_request.send().await?;
+            let issue_response = self.send_request(issue_request).await?;
             let status = issue_response.status();
 
             if !status.is_success() {
@@ -355,11 +748,11 @@ impl GitProvider for GitHubProvider {
                     .header("Accept", "application/vnd.github.v3+json")
                     .json(&body);
 
-                if let Some(token) = &self.auth_token {
+                if let Some(token) = self.auth_token() {
                     request = request.bearer_auth(token);
                 }
 
-                let response = request.send().await?;
+                let response = self.send_request(request).await?;
                 let status = response.status();
 
                 if !status.is_success() {
@@ -398,11 +791,11 @@ impl GitProvider for GitHubProvider {
                     .header("Accept", "application/vnd.github.v3+json")
                     .json(&body);
 
-                if let Some(token) = &self.auth_token {
+                if let Some(token) = self.auth_token() {
                     request = request.bearer_auth(token);
                 }
 
-                let response = request.send().await?;
+                let response = self.send_request(request).await?;
                 let status = response.status();
 
                 if !status.is_success() {
@@ -431,11 +824,11 @@ impl GitProvider for GitHubProvider {
                     .header("Accept", "application/vnd.github.v3+json")
                     .json(&body);
 
-                if let Some(token) = &self.auth_token {
+                if let Some(token) = self.auth_token() {
                     request = request.bearer_auth(token);
                 }
 
-                let response = request.send().await?;
+                let response = self.send_request(request).await?;
                 let status = response.status();
 
                 if !status.is_success() {
@@ -463,11 +856,11 @@ impl GitProvider for GitHubProvider {
                     .header("Accept", "application/vnd.github.v3+json")
                     .json(&body);
 
-                if let Some(token) = &self.auth_token {
+                if let Some(token) = self.auth_token() {
                     request = request.bearer_auth(token);
                 }
 
-                let response = request.send().await?;
+                let response = self.send_request(request).await?;
                 let status = response.status();
 
                 if !status.is_success() {
@@ -504,11 +897,11 @@ impl GitProvider for GitHubProvider {
             .header("Accept", "application/vnd.github.v3+json")
             .json(&request_body);
 
-        if let Some(token) = &self.auth_token {
+        if let Some(token) = self.auth_token() {
             review_request = review_request.bearer_auth(token);
         }
 
-        let review_response = review_request.send().await?;
+        let review_response = self.send_request(review_request).await?;
 
         if review_response.status().is_success() {
             let comment_data: serde_json::Value = review_response.json().await?;
@@ -526,11 +919,11 @@ impl GitProvider for GitHubProvider {
             .header("Accept", "application/vnd.github.v3+json")
             .json(&request_body);
 
-        if let Some(token) = &self.auth_token {
+        if let Some(token) = self.auth_token() {
             issue_request = issue_request.bearer_auth(token);
         }
 
-        let issue_response = issue_request.send().await?;
+        let issue_response = self.send_request(issue_request).await?;
         let status = issue_response.status();
 
         if !status.is_success() {
@@ -559,11 +952,11 @@ impl GitProvider for GitHubProvider {
             .delete(&review_url)
             .header("Accept", "application/vnd.github.v3+json");
 
-        if let Some(token) = &self.auth_token {
+        if let Some(token) = self.auth_token() {
             review_request = review_request.bearer_auth(token);
         }
 
-        let review_response = review_request.send().await?;
+        let review_response = self.send_request(review_request).await?;
 
         if review_response.status().is_success() {
             return Ok(());
@@ -579,11 +972,11 @@ impl GitProvider for GitHubProvider {
             .delete(&issue_url)
             .header("Accept", "application/vnd.github.v3+json");
 
-        if let Some(token) = &self.auth_token {
+        if let Some(token) = self.auth_token() {
             issue_request = issue_request.bearer_auth(token);
         }
 
-        let issue_response = issue_request.send().await?;
+        let issue_response = self.send_request(issue_request).await?;
         let status = issue_response.status();
 
         if !status.is_success() {
@@ -594,75 +987,327 @@ impl GitProvider for GitHubProvider {
         Ok(())
     }
 
-    async fn resolve_comment(
+    async fn resolve_thread(&self, thread_id: &str) -> Result<()> {
+        let mutation = format!(
+            r#"mutation {{ resolveReviewThread(input: {{threadId: "{thread_id}"}}) {{ thread {{ id }} }} }}"#
+        );
+        self.graphql_query(&mutation).await?;
+        Ok(())
+    }
+
+    async fn unresolve_thread(&self, thread_id: &str) -> Result<()> {
+        let mutation = format!(
+            r#"mutation {{ unresolveReviewThread(input: {{threadId: "{thread_id}"}}) {{ thread {{ id }} }} }}"#
+        );
+        self.graphql_query(&mutation).await?;
+        Ok(())
+    }
+
+    async fn get_reactions(
         &self,
         owner: &str,
         repo: &str,
-        _number: u64,
         comment_id: u64,
-        resolved: bool,
-    ) -> Result<()> {
-        // First get the node_id for the comment
-        let url = format!(
-            "{}/repos/{}/{}/pulls/comments/{}",
-            self.base_url, owner, repo, comment_id
-        );
+    ) -> Result<Vec<Reaction>> {
+        let (_, reactions) = self.list_reactions(owner, repo, comment_id).await?;
+        let viewer = self.viewer_login().await?;
+        Ok(summarize_reactions(&reactions, viewer.as_deref()))
+    }
 
-        log::debug!("GET {url} to fetch node_id for resolving");
+    async fn add_reaction(
+        &self,
+        owner: &str,
+        repo: &str,
+        comment_id: u64,
+        content: ReactionContent,
+    ) -> Result<Vec<Reaction>> {
+        let (url, _) = self.list_reactions(owner, repo, comment_id).await?;
+        let body = serde_json::json!({ "content": content.as_str() });
+        self.send_json(reqwest::Method::POST, &url, Some(&body))
+            .await?;
+
+        self.get_reactions(owner, repo, comment_id).await
+    }
 
+    async fn remove_reaction(
+        &self,
+        owner: &str,
+        repo: &str,
+        comment_id: u64,
+        content: ReactionContent,
+    ) -> Result<Vec<Reaction>> {
+        let viewer = self
+            .viewer_login()
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Removing a reaction requires authentication"))?;
+        let (url, reactions) = self.list_reactions(owner, repo, comment_id).await?;
+        let reaction_id = reactions
+            .iter()
+            .find(|reaction| {
+                reaction["content"].as_str() == Some(content.as_str())
+                    && reaction["user"]["login"].as_str() == Some(viewer.as_str())
+            })
+            .and_then(|reaction| reaction["id"].as_u64())
+            .ok_or_else(|| anyhow::anyhow!("No {} reaction from {viewer}", content.as_str()))?;
+
+        let url = format!("{url}/{reaction_id}");
+        log::debug!("DELETE {url}");
         let mut request = self
             .http_client
-            .get(&url)
+            .delete(&url)
             .header("Accept", "application/vnd.github.v3+json");
 
-        if let Some(token) = &self.auth_token {
+        if let Some(token) = self.auth_token() {
             request = request.bearer_auth(token);
         }
 
-        let response = request.send().await?;
-        let data: serde_json::Value = response.json().await?;
-        let node_id = data["node_id"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("No node_id found"))?;
+        let response = self.send_request(request).await?;
+        let status = response.status();
 
-        // Use GraphQL mutation to resolve/unresolve
-        let mutation = if resolved {
-            format!(
-                r#"mutation {{ resolveReviewThread(input: {{threadId: "{node_id}"}}) {{ thread {{ id }} }} }}"#
-            )
-        } else {
-            format!(
-                r#"mutation {{ unresolveReviewThread(input: {{threadId: "{node_id}"}}) {{ thread {{ id }} }} }}"#
-            )
-        };
+        if !status.is_success() {
+            log::error!("GitHub API error: {}", response.text().await?);
+            anyhow::bail!("GitHub API error: {status}");
+        }
+
+        self.get_reactions(owner, repo, comment_id).await
+    }
+
+    async fn submit_review(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        verdict: ReviewVerdict,
+        body: String,
+        pending_comments: Vec<CreateComment>,
+    ) -> Result<Review> {
+        let url = format!(
+            "{}/repos/{owner}/{repo}/pulls/{number}/reviews",
+            self.base_url
+        );
 
-        log::debug!("GraphQL mutation: {mutation}");
+        let commit_id = pending_comments.iter().find_map(|c| match &c.comment_type {
+            CommentType::LineLevelComment { commit_sha, .. } if !commit_sha.is_empty() => {
+                Some(commit_sha.clone())
+            }
+            _ => None,
+        });
+        let comments = pending_comments
+            .iter()
+            .map(review_comment_json)
+            .collect::<Result<Vec<_>>>()?;
 
-        let graphql_url = format!("{}/graphql", self.base_url);
-        let body = serde_json::json!({ "query": mutation });
+        let mut request_body = serde_json::json!({
+            "event": review_event(verdict),
+            "body": body,
+            "comments": comments,
+        });
+        if let Some(commit_id) = commit_id {
+            request_body["commit_id"] = commit_id.into();
+        }
+        log::debug!("POST url={url} body={request_body:?}");
 
         let mut request = self
             .http_client
-            .post(&graphql_url)
+            .post(&url)
             .header("Accept", "application/vnd.github.v3+json")
-            .json(&body);
+            .json(&request_body);
 
-        if let Some(token) = &self.auth_token {
+        if let Some(token) = self.auth_token() {
             request = request.bearer_auth(token);
         }
 
-        let response = request.send().await?;
+        let response = self.send_request(request).await?;
         let status = response.status();
 
         if !status.is_success() {
-            let error_text = response.text().await?;
-            log::error!("GitHub GraphQL error: {error_text}");
-            anyhow::bail!("GitHub GraphQL error: {status}");
+            log::error!("GitHub API error: {}", response.text().await?);
+            anyhow::bail!("GitHub API error: {status}");
+        }
+
+        let review_data: serde_json::Value = response.json().await?;
+        Ok(parse_review(&review_data, verdict))
+    }
+
+    async fn request_reviewers(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        reviewers: Vec<String>,
+    ) -> Result<Vec<User>> {
+        let url = format!(
+            "{}/repos/{owner}/{repo}/pulls/{number}/requested_reviewers",
+            self.base_url
+        );
+        let body = serde_json::json!({ "reviewers": reviewers });
+        let pr_data = self
+            .send_json(reqwest::Method::POST, &url, Some(&body))
+            .await?;
+
+        Ok(parse_users(&pr_data["requested_reviewers"]))
+    }
+
+    async fn remove_reviewers(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        reviewers: Vec<String>,
+    ) -> Result<Vec<User>> {
+        let url = format!(
+            "{}/repos/{owner}/{repo}/pulls/{number}/requested_reviewers",
+            self.base_url
+        );
+        let body = serde_json::json!({ "reviewers": reviewers });
+        let pr_data = self
+            .send_json(reqwest::Method::DELETE, &url, Some(&body))
+            .await?;
+
+        Ok(parse_users(&pr_data["requested_reviewers"]))
+    }
+
+    async fn set_assignees(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        assignees: Vec<String>,
+    ) -> Result<Vec<User>> {
+        let url = format!("{}/repos/{owner}/{repo}/issues/{number}", self.base_url);
+        let body = serde_json::json!({ "assignees": assignees });
+        let issue_data = self
+            .send_json(reqwest::Method::PATCH, &url, Some(&body))
+            .await?;
+
+        Ok(parse_users(&issue_data["assignees"]))
+    }
+
+    async fn add_labels(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        labels: Vec<String>,
+    ) -> Result<Vec<Label>> {
+        let url = format!(
+            "{}/repos/{owner}/{repo}/issues/{number}/labels",
+            self.base_url
+        );
+        let body = serde_json::json!({ "labels": labels });
+        let labels_data = self
+            .send_json(reqwest::Method::POST, &url, Some(&body))
+            .await?;
+
+        Ok(parse_labels(&labels_data))
+    }
+
+    async fn remove_label(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        label: &str,
+    ) -> Result<Vec<Label>> {
+        let url = format!(
+            "{}/repos/{owner}/{repo}/issues/{number}/labels/{}",
+            self.base_url,
+            urlencoding::encode(label)
+        );
+        let labels_data = self.send_json(reqwest::Method::DELETE, &url, None).await?;
+
+        Ok(parse_labels(&labels_data))
+    }
+
+    async fn get_collaborators(&self, owner: &str, repo: &str) -> Result<Vec<User>> {
+        // Listing collaborators needs push access; assignable users only
+        // need read access and are the same people
+        let url = format!("{}/repos/{owner}/{repo}/assignees", self.base_url);
+        let users = self.fetch_all_paginated(&url).await?;
+
+        Ok(users.iter().map(parse_user).collect())
+    }
+
+    async fn merge_pr(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        strategy: MergeStrategy,
+    ) -> Result<()> {
+        let url = format!(
+            "{}/repos/{owner}/{repo}/pulls/{number}/merge",
+            self.base_url
+        );
+        let merge_method = match strategy {
+            MergeStrategy::Merge => "merge",
+            MergeStrategy::Squash => "squash",
+            MergeStrategy::Rebase => "rebase",
+        };
+        let body = serde_json::json!({ "merge_method": merge_method });
+        let result = self
+            .send_json(reqwest::Method::PUT, &url, Some(&body))
+            .await?;
+
+        if result["merged"].as_bool() != Some(true) {
+            anyhow::bail!(
+                "Pull request was not merged: {}",
+                result["message"].as_str().unwrap_or("unknown reason")
+            );
         }
 
         Ok(())
     }
 
+    async fn update_pr(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+        title: Option<String>,
+        body: Option<String>,
+        draft: Option<bool>,
+    ) -> Result<PullRequest> {
+        let url = format!("{}/repos/{owner}/{repo}/pulls/{number}", self.base_url);
+        let mut fields = serde_json::Map::new();
+        if let Some(title) = title {
+            fields.insert("title".to_string(), title.into());
+        }
+        if let Some(body) = body {
+            fields.insert("body".to_string(), body.into());
+        }
+        let pr_data = if fields.is_empty() {
+            self.get_json(&url).await?
+        } else {
+            self.send_json(
+                reqwest::Method::PATCH,
+                &url,
+                Some(&serde_json::Value::Object(fields)),
+            )
+            .await?
+        };
+
+        // The REST API can't change whether a pull request is a draft
+        let Some(draft) = draft.filter(|draft| pr_data["draft"].as_bool() != Some(*draft)) else {
+            return Ok(parse_pull_request(owner, repo, &pr_data));
+        };
+        let node_id = pr_data["node_id"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Pull request #{number} has no node ID"))?;
+        let mutation = if draft {
+            format!(
+                r#"mutation {{ convertPullRequestToDraft(input: {{pullRequestId: "{node_id}"}}) {{ pullRequest {{ id }} }} }}"#
+            )
+        } else {
+            format!(
+                r#"mutation {{ markPullRequestReadyForReview(input: {{pullRequestId: "{node_id}"}}) {{ pullRequest {{ id }} }} }}"#
+            )
+        };
+        self.graphql_query(&mutation).await?;
+
+        self.get_pr(owner, repo, number).await
+    }
+
     fn provider_name(&self) -> &'static str {
         "github"
     }
@@ -670,6 +1315,88 @@ impl GitProvider for GitHubProvider {
     fn supports_drafts(&self) -> bool {
         true
     }
+
+    fn rate_limit(&self) -> Option<RateLimitStatus> {
+        self.rate_limits
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .values()
+            .min_by_key(|rate_limit| rate_limit.remaining)
+            .cloned()
+    }
+}
+
+/// Read GitHub's `X-RateLimit-*` headers.
+fn parse_rate_limit(headers: &reqwest::header::HeaderMap) -> Option<RateLimitStatus> {
+    let header = |name: &str| headers.get(name)?.to_str().ok();
+    let number = |name: &str| header(name)?.parse::<u64>().ok();
+
+    let reset = i64::try_from(number("x-ratelimit-reset")?).ok()?;
+
+    Some(RateLimitStatus {
+        resource: header("x-ratelimit-resource").unwrap_or("core").to_string(),
+        limit: number("x-ratelimit-limit")?,
+        remaining: number("x-ratelimit-remaining")?,
+        used: number("x-ratelimit-used").unwrap_or(0),
+        reset_at: chrono::DateTime::from_timestamp(reset, 0)?,
+    })
+}
+
+/// How long to wait before retrying a rate limited response, following
+/// GitHub's guidance: honour `Retry-After`, otherwise wait for the primary
+/// limit to reset, otherwise (a secondary limit without hints) wait a
+/// minute. `None` if the response wasn't rate limited.
+fn retry_delay(
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+) -> Option<Duration> {
+    if status != reqwest::StatusCode::FORBIDDEN && status != reqwest::StatusCode::TOO_MANY_REQUESTS
+    {
+        return None;
+    }
+
+    if let Some(seconds) = headers
+        .get("retry-after")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+    {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    if let Some(rate_limit) = parse_rate_limit(headers)
+        && rate_limit.remaining == 0
+    {
+        let wait = (rate_limit.reset_at - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default();
+        return Some(wait.max(Duration::from_secs(1)));
+    }
+
+    // A plain 403 is a permissions error, not a rate limit
+    (status == reqwest::StatusCode::TOO_MANY_REQUESTS).then_some(MAX_RETRY_DELAY)
+}
+
+fn parse_pull_request(owner: &str, repo: &str, pr_data: &serde_json::Value) -> PullRequest {
+    PullRequest {
+        number: pr_data["number"].as_u64().unwrap(),
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+        title: pr_data["title"].as_str().unwrap().to_string(),
+        description: pr_data["body"].as_str().unwrap_or("").to_string(),
+        author: parse_user(&pr_data["user"]),
+        state: parse_pr_state(pr_data),
+        draft: pr_data["draft"].as_bool().unwrap_or(false),
+        base_branch: pr_data["base"]["ref"].as_str().unwrap().to_string(),
+        head_branch: pr_data["head"]["ref"].as_str().unwrap().to_string(),
+        labels: parse_labels(&pr_data["labels"]),
+        assignees: parse_users(&pr_data["assignees"]),
+        reviewers: parse_users(&pr_data["requested_reviewers"]),
+        head_sha: pr_data["head"]["sha"].as_str().unwrap().to_string(),
+        created_at: parse_datetime(pr_data["created_at"].as_str().unwrap()),
+        updated_at: parse_datetime(pr_data["updated_at"].as_str().unwrap()),
+        provider: "github".to_string(),
+        mergeable_state: parse_mergeable_state(pr_data),
+    }
 }
 
 fn parse_user(value: &serde_json::Value) -> User {
@@ -681,6 +1408,81 @@ fn parse_user(value: &serde_json::Value) -> User {
     }
 }
 
+/// Parse a commit from the commits API.
+///
+/// Falls back to the git author name when the commit's email isn't linked
+/// to a GitHub account.
+fn parse_commit(value: &serde_json::Value) -> Commit {
+    let author = if value["author"].is_object() {
+        parse_user(&value["author"])
+    } else {
+        User {
+            id: String::new(),
+            username: value["commit"]["author"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            avatar_url: String::new(),
+            html_url: String::new(),
+        }
+    };
+    let committed_at = value["commit"]["committer"]["date"]
+        .as_str()
+        .or_else(|| value["commit"]["author"]["date"].as_str())
+        .map_or_else(chrono::Utc::now, parse_datetime);
+
+    Commit {
+        sha: value["sha"].as_str().unwrap().to_string(),
+        message: value["commit"]["message"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        author,
+        committed_at,
+    }
+}
+
+/// Parse the `files` of a PR or commit into diffs, using `fallback` patches
+/// (keyed by filename) for files GitHub sent without one. Files with no
+/// patch either way (binary files, pure renames) are skipped.
+fn parse_diff_files(
+    files_data: &[serde_json::Value],
+    fallback: &HashMap<String, String>,
+) -> Result<Vec<DiffFile>> {
+    let highlighter = SyntaxHighlighter::new();
+    let mut result = Vec::new();
+
+    for file_data in files_data {
+        let filename = file_data["filename"].as_str().unwrap();
+        let status = parse_file_status(file_data["status"].as_str().unwrap());
+        let additions = file_data["additions"].as_u64().unwrap();
+        let deletions = file_data["deletions"].as_u64().unwrap();
+
+        let patch = file_data["patch"]
+            .as_str()
+            .or_else(|| fallback.get(filename).map(String::as_str));
+
+        if let Some(patch_str) = patch {
+            let parsed = parse_unified_diff(
+                filename,
+                status,
+                additions,
+                deletions,
+                patch_str,
+                &highlighter,
+            )
+            .map_err(|e| anyhow::anyhow!(e))?;
+            result.push(parsed);
+        } else {
+            log::debug!(
+                "Skipping {filename} - no patch data (likely binary or no content changes)"
+            );
+        }
+    }
+
+    Ok(result)
+}
+
 fn parse_users(value: &serde_json::Value) -> Vec<User> {
     value
         .as_array()
@@ -696,6 +1498,21 @@ fn parse_pr_state(value: &serde_json::Value) -> PrState {
     }
 }
 
+fn parse_mergeable_state(value: &serde_json::Value) -> MergeableState {
+    if value["mergeable"].as_bool() == Some(false) {
+        return MergeableState::Conflicting;
+    }
+
+    match value["mergeable_state"].as_str() {
+        Some("clean" | "has_hooks") => MergeableState::Clean,
+        Some("unstable") => MergeableState::Unstable,
+        Some("behind") => MergeableState::Behind,
+        Some("blocked") => MergeableState::Blocked,
+        Some("dirty") => MergeableState::Conflicting,
+        _ => MergeableState::Unknown,
+    }
+}
+
 fn parse_labels(value: &serde_json::Value) -> Vec<Label> {
     value
         .as_array()
@@ -710,6 +1527,111 @@ fn parse_labels(value: &serde_json::Value) -> Vec<Label> {
         .unwrap_or_default()
 }
 
+/// Count reactions per emoji from the reactions list endpoint.
+fn summarize_reactions(reactions: &[serde_json::Value], viewer: Option<&str>) -> Vec<Reaction> {
+    ReactionContent::ALL
+        .into_iter()
+        .filter_map(|content| {
+            let matching = reactions
+                .iter()
+                .filter(|reaction| reaction["content"].as_str() == Some(content.as_str()))
+                .collect::<Vec<_>>();
+            (!matching.is_empty()).then(|| Reaction {
+                content,
+                count: matching.len() as u64,
+                viewer_reacted: viewer.is_some_and(|viewer| {
+                    matching
+                        .iter()
+                        .any(|reaction| reaction["user"]["login"].as_str() == Some(viewer))
+                }),
+            })
+        })
+        .collect()
+}
+
+/// Parse the `reactions` rollup embedded in REST comment payloads, which
+/// doesn't say whether the viewer reacted.
+fn parse_reaction_rollup(value: &serde_json::Value) -> Vec<Reaction> {
+    ReactionContent::ALL
+        .into_iter()
+        .filter_map(|content| {
+            let count = value[content.as_str()].as_u64().unwrap_or(0);
+            (count > 0).then_some(Reaction {
+                content,
+                count,
+                viewer_reacted: false,
+            })
+        })
+        .collect()
+}
+
+fn parse_graphql_reaction_groups(value: &serde_json::Value) -> Vec<Reaction> {
+    let Some(groups) = value.as_array() else {
+        return Vec::new();
+    };
+
+    groups
+        .iter()
+        .filter_map(|group| {
+            let content = match group["content"].as_str()? {
+                "THUMBS_UP" => ReactionContent::ThumbsUp,
+                "THUMBS_DOWN" => ReactionContent::ThumbsDown,
+                "LAUGH" => ReactionContent::Laugh,
+                "HOORAY" => ReactionContent::Hooray,
+                "CONFUSED" => ReactionContent::Confused,
+                "HEART" => ReactionContent::Heart,
+                "ROCKET" => ReactionContent::Rocket,
+                "EYES" => ReactionContent::Eyes,
+                _ => return None,
+            };
+            let count = group["reactors"]["totalCount"].as_u64().unwrap_or(0);
+            (count > 0).then(|| Reaction {
+                content,
+                count,
+                viewer_reacted: group["viewerHasReacted"].as_bool().unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+fn parse_check_run(value: &serde_json::Value) -> Check {
+    let status = if value["status"].as_str() == Some("completed") {
+        match value["conclusion"].as_str() {
+            Some("success") => CheckStatus::Success,
+            Some("neutral") => CheckStatus::Neutral,
+            Some("skipped" | "stale") => CheckStatus::Skipped,
+            _ => CheckStatus::Failure,
+        }
+    } else {
+        CheckStatus::Pending
+    };
+
+    Check {
+        name: value["name"].as_str().unwrap_or("").to_string(),
+        status,
+        description: value["output"]["title"].as_str().map(ToString::to_string),
+        details_url: value["html_url"]
+            .as_str()
+            .or_else(|| value["details_url"].as_str())
+            .map(ToString::to_string),
+    }
+}
+
+fn parse_commit_status(value: &serde_json::Value) -> Check {
+    let status = match value["state"].as_str() {
+        Some("success") => CheckStatus::Success,
+        Some("pending") => CheckStatus::Pending,
+        _ => CheckStatus::Failure,
+    };
+
+    Check {
+        name: value["context"].as_str().unwrap_or("").to_string(),
+        status,
+        description: value["description"].as_str().map(ToString::to_string),
+        details_url: value["target_url"].as_str().map(ToString::to_string),
+    }
+}
+
 fn parse_datetime(s: &str) -> chrono::DateTime<chrono::Utc> {
     chrono::DateTime::parse_from_rfc3339(s)
         .unwrap()
@@ -755,108 +1677,6 @@ fn parse_link_header(header_value: &str) -> LinkHeader {
     result
 }
 
-async fn fetch_all_paginated(
-    http_client: &reqwest::Client,
-    url: &str,
-    auth_token: Option<&String>,
-) -> Result<Vec<serde_json::Value>> {
-    const MAX_FILES: usize = 3000;
-    const PER_PAGE: u32 = 100;
-
-    let mut all_files = Vec::new();
-    let mut page = 1;
-
-    let mut url = url.to_string();
-
-    if url.contains('?') {
-        url.push('&');
-    } else {
-        url.push('?');
-    }
-
-    let url = url.as_str();
-
-    loop {
-        let url = format!("{url}per_page={PER_PAGE}&page={page}");
-
-        log::debug!("GET {url} (page {page})");
-
-        let mut request = http_client
-            .get(&url)
-            .header("Accept", "application/vnd.github.v3+json");
-
-        if let Some(token) = auth_token {
-            request = request.bearer_auth(token);
-        }
-
-        let response = request.send().await?;
-        let status = response.status();
-
-        if !status.is_success() {
-            let error_text = response.text().await?;
-            log::error!("GitHub API error on page {page}: {error_text}");
-            anyhow::bail!("GitHub API error: {status}");
-        }
-
-        let link_header = response
-            .headers()
-            .get("Link")
-            .and_then(|h| h.to_str().ok())
-            .map(parse_link_header);
-
-        let page_files: Vec<serde_json::Value> = response.json().await?;
-        let files_in_page = page_files.len();
-
-        log::debug!("Fetched {files_in_page} files from page {page}");
-
-        all_files.extend(page_files);
-
-        if all_files.len() >= MAX_FILES {
-            log::warn!(
-                "PR has {} files, reached GitHub's {} file limit",
-                all_files.len(),
-                MAX_FILES
-            );
-            break;
-        }
-
-        if let Some(links) = link_header
-            && links.next.is_some()
-        {
-            page += 1;
-            continue;
-        }
-
-        break;
-    }
-
-    log::debug!(
-        "Fetched total of {} files across {} page(s)",
-        all_files.len(),
-        page
-    );
-
-    Ok(all_files)
-}
-
-async fn fetch_all_pr_files(
-    http_client: &reqwest::Client,
-    base_url: &str,
-    owner: &str,
-    repo: &str,
-    number: u64,
-    auth_token: Option<&String>,
-) -> Result<Vec<serde_json::Value>> {
-    log::debug!("Fetching PR files for {owner}/{repo} #{number}");
-
-    fetch_all_paginated(
-        http_client,
-        &format!("{base_url}/repos/{owner}/{repo}/pulls/{number}/files"),
-        auth_token,
-    )
-    .await
-}
-
 fn parse_review_comment(value: &serde_json::Value) -> Comment {
     use chadreview_pr_models::CommentType;
 
@@ -893,6 +1713,8 @@ fn parse_review_comment(value: &serde_json::Value) -> Comment {
         comment_type,
         replies: Vec::new(),
         resolved: value["resolved"].as_bool().unwrap_or(false),
+        thread_id: None,
+        reactions: parse_reaction_rollup(&value["reactions"]),
     }
 }
 
@@ -908,6 +1730,60 @@ fn parse_issue_comment(value: &serde_json::Value) -> Comment {
         comment_type: CommentType::General,
         replies: Vec::new(),
         resolved: false,
+        thread_id: None,
+        reactions: parse_reaction_rollup(&value["reactions"]),
+    }
+}
+
+/// The Reviews API `event` for a verdict.
+const fn review_event(verdict: ReviewVerdict) -> &'static str {
+    match verdict {
+        ReviewVerdict::Approve => "APPROVE",
+        ReviewVerdict::RequestChanges => "REQUEST_CHANGES",
+        ReviewVerdict::Comment => "COMMENT",
+    }
+}
+
+/// Convert a pending comment into an entry of the Reviews API `comments`
+/// array.
+fn review_comment_json(comment: &CreateComment) -> Result<serde_json::Value> {
+    match &comment.comment_type {
+        CommentType::LineLevelComment { path, line, .. } => Ok(serde_json::json!({
+            "path": path,
+            "body": comment.body,
+            "side": match line {
+                LineNumber::Old { .. } => "LEFT",
+                LineNumber::New { .. } => "RIGHT",
+            },
+            "line": line.number(),
+        })),
+        CommentType::FileLevelComment { path } => Ok(serde_json::json!({
+            "path": path,
+            "body": comment.body,
+            "subject_type": "file",
+        })),
+        CommentType::General | CommentType::Reply { .. } => {
+            anyhow::bail!("Only line-level and file-level comments can be part of a review")
+        }
+    }
+}
+
+/// Parse a Reviews API response, falling back to the submitted verdict for
+/// states that don't map to one (e.g. `PENDING`).
+fn parse_review(value: &serde_json::Value, verdict: ReviewVerdict) -> Review {
+    let verdict = match value["state"].as_str() {
+        Some("APPROVED") => ReviewVerdict::Approve,
+        Some("CHANGES_REQUESTED") => ReviewVerdict::RequestChanges,
+        Some("COMMENTED") => ReviewVerdict::Comment,
+        _ => verdict,
+    };
+
+    Review {
+        id: value["id"].as_u64().unwrap(),
+        author: parse_user(&value["user"]),
+        verdict,
+        body: value["body"].as_str().unwrap_or("").to_string(),
+        submitted_at: value["submitted_at"].as_str().map(parse_datetime),
     }
 }
 
@@ -951,6 +1827,8 @@ fn parse_graphql_review_comment(value: &serde_json::Value) -> Comment {
         comment_type,
         replies: Vec::new(),
         resolved: false, // Will be set by caller based on thread
+        thread_id: None, // Will be set by caller based on thread
+        reactions: parse_graphql_reaction_groups(&value["reactionGroups"]),
     }
 }
 
@@ -963,6 +1841,16 @@ fn parse_graphql_user(value: &serde_json::Value) -> User {
     }
 }
 
+/// The cursor to continue from, if a GraphQL connection's `pageInfo` says
+/// there's another page.
+fn next_cursor(page_info: &serde_json::Value) -> Option<&str> {
+    if page_info["hasNextPage"].as_bool() == Some(true) {
+        page_info["endCursor"].as_str()
+    } else {
+        None
+    }
+}
+
 fn build_tree(
     comment_id: u64,
     comment_map: &mut std::collections::HashMap<u64, Comment>,
@@ -1025,7 +1913,7 @@ fn find_comment_in_tree(comments: &[Comment], comment_id: u64) -> Option<Comment
 mod tests {
     use super::*;
     use chadreview_diff_models::LineNumber;
-    use wiremock::matchers::{method, path};
+    use wiremock::matchers::{body_partial_json, header, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[tokio::test]
@@ -1074,21 +1962,165 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_get_pr_merged_state() {
+    async fn test_get_pr_served_from_cache_when_not_modified() {
         let mock_server = MockServer::start().await;
 
         let pr_json = serde_json::json!({
-            "number": 456,
-            "title": "Merged PR",
-            "body": "",
-            "state": "closed",
-            "merged": true,
+            "number": 123,
+            "title": "Cached PR",
+            "body": null,
+            "state": "open",
             "draft": false,
             "user": {
-                "id": 67890,
-                "login": "author",
+                "id": 12345,
+                "login": "testuser",
                 "avatar_url": "https://example.com/avatar.png",
-                "html_url": "https://github.com/author"
+                "html_url": "https://github.com/testuser"
+            },
+            "base": { "ref": "main" },
+            "head": { "ref": "feature-branch", "sha": "1234567890" },
+            "labels": [],
+            "assignees": [],
+            "requested_reviewers": [],
+            "created_at": "2025-01-01T00:00:00Z",
+            "updated_at": "2025-01-02T00:00:00Z",
+            "merged": false
+        });
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/pulls/123"))
+            .and(header("If-None-Match", "\"abc123\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/pulls/123"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(&pr_json)
+                    .append_header("ETag", "\"abc123\""),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new()
+            .with_token("test-token".to_string())
+            .with_base_url(mock_server.uri());
+
+        let first = client.get_pr("owner", "repo", 123).await.unwrap();
+        let second = client.get_pr("owner", "repo", 123).await.unwrap();
+
+        assert_eq!(first.title, "Cached PR");
+        assert_eq!(second.title, "Cached PR");
+    }
+
+    #[test]
+    fn test_parse_rate_limit() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert!(parse_rate_limit(&headers).is_none());
+
+        headers.insert("x-ratelimit-limit", "5000".parse().unwrap());
+        headers.insert("x-ratelimit-remaining", "4321".parse().unwrap());
+        headers.insert("x-ratelimit-used", "679".parse().unwrap());
+        headers.insert("x-ratelimit-reset", "1735689600".parse().unwrap());
+        headers.insert("x-ratelimit-resource", "graphql".parse().unwrap());
+
+        let rate_limit = parse_rate_limit(&headers).unwrap();
+        assert_eq!(rate_limit.resource, "graphql");
+        assert_eq!(rate_limit.limit, 5000);
+        assert_eq!(rate_limit.remaining, 4321);
+        assert_eq!(rate_limit.used, 679);
+        assert_eq!(
+            rate_limit.reset_at.to_rfc3339(),
+            "2025-01-01T00:00:00+00:00"
+        );
+        assert!(!rate_limit.is_low());
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert_eq!(retry_delay(reqwest::StatusCode::FORBIDDEN, &headers), None);
+        assert_eq!(
+            retry_delay(reqwest::StatusCode::TOO_MANY_REQUESTS, &headers),
+            Some(MAX_RETRY_DELAY)
+        );
+
+        headers.insert("retry-after", "5".parse().unwrap());
+        assert_eq!(
+            retry_delay(reqwest::StatusCode::FORBIDDEN, &headers),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(retry_delay(reqwest::StatusCode::OK, &headers), None);
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        let reset = chrono::Utc::now().timestamp() + 3600;
+        headers.insert("x-ratelimit-limit", "5000".parse().unwrap());
+        headers.insert("x-ratelimit-remaining", "0".parse().unwrap());
+        headers.insert("x-ratelimit-reset", reset.to_string().parse().unwrap());
+        assert!(retry_delay(reqwest::StatusCode::FORBIDDEN, &headers).unwrap() > MAX_RETRY_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_retries_after_secondary_rate_limit() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/commits/abc/status"))
+            .respond_with(ResponseTemplate::new(403).append_header("Retry-After", "0"))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/commits/abc/status"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "statuses": [] }))
+                    .append_header("x-ratelimit-limit", "5000")
+                    .append_header("x-ratelimit-remaining", "12")
+                    .append_header("x-ratelimit-reset", "1735689600")
+                    .append_header("x-ratelimit-resource", "core"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+        assert!(client.rate_limit().is_none());
+
+        let status = client
+            .get_json(&format!(
+                "{}/repos/owner/repo/commits/abc/status",
+                mock_server.uri()
+            ))
+            .await
+            .unwrap();
+        assert!(status["statuses"].as_array().unwrap().is_empty());
+
+        let rate_limit = client.rate_limit().unwrap();
+        assert_eq!(rate_limit.remaining, 12);
+        assert!(rate_limit.is_low());
+    }
+
+    #[tokio::test]
+    async fn test_get_pr_merged_state() {
+        let mock_server = MockServer::start().await;
+
+        let pr_json = serde_json::json!({
+            "number": 456,
+            "title": "Merged PR",
+            "body": "",
+            "state": "closed",
+            "merged": true,
+            "draft": false,
+            "user": {
+                "id": 67890,
+                "login": "author",
+                "avatar_url": "https://example.com/avatar.png",
+                "html_url": "https://github.com/author"
             },
             "base": { "ref": "main" },
             "head": { "ref": "feature", "sha": "1234567890" },
@@ -1239,6 +2271,7 @@ mod tests {
         assert_eq!(comments.len(), 1);
         assert_eq!(comments[0].id, 2001);
         assert_eq!(comments[0].body, "This needs fixing");
+        assert_eq!(comments[0].thread_id.as_deref(), Some("thread1"));
         match &comments[0].comment_type {
             chadreview_pr_models::CommentType::LineLevelComment {
                 commit_sha,
@@ -1343,6 +2376,178 @@ mod tests {
         assert_eq!(comments[0].replies[0].author.username, "author");
     }
 
+    fn graphql_comment(database_id: u64, reply_to: Option<u64>) -> serde_json::Value {
+        serde_json::json!({
+            "id": format!("comment{database_id}"),
+            "databaseId": database_id,
+            "body": format!("Comment {database_id}"),
+            "createdAt": "2025-01-01T10:00:00Z",
+            "updatedAt": "2025-01-01T10:00:00Z",
+            "author": {
+                "login": "reviewer1",
+                "avatarUrl": "https://example.com/avatar1.png",
+                "url": "https://github.com/reviewer1"
+            },
+            "path": "src/lib.rs",
+            "line": 10,
+            "originalLine": null,
+            "diffHunk": "@@ -8,6 +8,7 @@",
+            "replyTo": reply_to.map(|id| serde_json::json!({ "databaseId": id }))
+        })
+    }
+
+    #[tokio::test]
+    async fn test_get_comments_follows_graphql_cursors() {
+        use wiremock::matchers::body_string_contains;
+
+        let mock_server = MockServer::start().await;
+
+        // Remaining comments of the first thread
+        Mock::given(method("POST"))
+            .and(path("/graphql"))
+            .and(body_string_contains("cursor-comments"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": {
+                    "node": {
+                        "comments": {
+                            "pageInfo": { "hasNextPage": false, "endCursor": null },
+                            "nodes": [graphql_comment(3002, Some(3001))]
+                        }
+                    }
+                }
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // Second page of threads
+        Mock::given(method("POST"))
+            .and(path("/graphql"))
+            .and(body_string_contains("cursor-threads"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": { "repository": { "pullRequest": { "reviewThreads": {
+                    "pageInfo": { "hasNextPage": false, "endCursor": null },
+                    "nodes": [{
+                        "id": "thread2",
+                        "isResolved": true,
+                        "comments": { "nodes": [graphql_comment(3003, None)] }
+                    }]
+                } } } }
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        // First page of threads
+        Mock::given(method("POST"))
+            .and(path("/graphql"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": { "repository": { "pullRequest": { "reviewThreads": {
+                    "pageInfo": { "hasNextPage": true, "endCursor": "cursor-threads" },
+                    "nodes": [{
+                        "id": "thread1",
+                        "isResolved": false,
+                        "comments": {
+                            "pageInfo": { "hasNextPage": true, "endCursor": "cursor-comments" },
+                            "nodes": [graphql_comment(3001, None)]
+                        }
+                    }]
+                } } } }
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/issues/123/comments"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new()
+            .with_token("test-token".to_string())
+            .with_base_url(mock_server.uri());
+
+        let comments = client.get_comments("owner", "repo", 123).await.unwrap();
+
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].id, 3001);
+        assert_eq!(comments[0].replies.len(), 1);
+        assert_eq!(comments[0].replies[0].id, 3002);
+        assert_eq!(comments[0].thread_id.as_deref(), Some("thread1"));
+        assert_eq!(comments[1].id, 3003);
+        assert!(comments[1].resolved);
+    }
+
+    #[tokio::test]
+    async fn test_get_comments_uses_rest_without_token() {
+        let mock_server = MockServer::start().await;
+
+        let review_comments = serde_json::json!([
+            {
+                "id": 5001,
+                "body": "Root",
+                "user": {
+                    "id": 1,
+                    "login": "reviewer1",
+                    "avatar_url": "https://example.com/reviewer1.png",
+                    "html_url": "https://github.com/reviewer1"
+                },
+                "path": "src/lib.rs",
+                "line": 4,
+                "side": "RIGHT",
+                "commit_id": "abc",
+                "created_at": "2025-01-01T10:00:00Z",
+                "updated_at": "2025-01-01T10:00:00Z"
+            },
+            {
+                "id": 5002,
+                "body": "Reply",
+                "user": {
+                    "id": 2,
+                    "login": "author",
+                    "avatar_url": "https://example.com/author.png",
+                    "html_url": "https://github.com/author"
+                },
+                "path": "src/lib.rs",
+                "line": 4,
+                "side": "RIGHT",
+                "commit_id": "abc",
+                "in_reply_to_id": 5001,
+                "created_at": "2025-01-01T11:00:00Z",
+                "updated_at": "2025-01-01T11:00:00Z"
+            }
+        ]);
+
+        Mock::given(method("POST"))
+            .and(path("/graphql"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/pulls/123/comments"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&review_comments))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/issues/123/comments"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+        let comments = client.get_comments("owner", "repo", 123).await.unwrap();
+
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].id, 5001);
+        assert_eq!(comments[0].replies.len(), 1);
+        assert_eq!(comments[0].replies[0].id, 5002);
+        assert!(comments[0].thread_id.is_none());
+    }
+
     #[tokio::test]
     async fn test_get_comments_mixed_types() {
         let mock_server = MockServer::start().await;
@@ -1804,6 +3009,543 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_submit_review_with_comments() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/repos/org/repo/pulls/5/reviews"))
+            .and(body_partial_json(serde_json::json!({
+                "event": "REQUEST_CHANGES",
+                "body": "Needs work",
+                "commit_id": "abc123",
+                "comments": [
+                    { "path": "src/lib.rs", "body": "Typo", "side": "RIGHT", "line": 12 },
+                    { "path": "README.md", "body": "Outdated", "subject_type": "file" }
+                ]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": 80,
+                "user": {
+                    "id": 1,
+                    "login": "reviewer",
+                    "avatar_url": "https://example.com/avatar.png",
+                    "html_url": "https://github.com/reviewer"
+                },
+                "body": "Needs work",
+                "state": "CHANGES_REQUESTED",
+                "submitted_at": "2025-01-01T00:00:00Z"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new()
+            .with_token("test-token".to_string())
+            .with_base_url(mock_server.uri());
+
+        let review = client
+            .submit_review(
+                "org",
+                "repo",
+                5,
+                ReviewVerdict::RequestChanges,
+                "Needs work".to_string(),
+                vec![
+                    CreateComment {
+                        body: "Typo".to_string(),
+                        comment_type: CommentType::LineLevelComment {
+                            path: "src/lib.rs".to_string(),
+                            commit_sha: "abc123".to_string(),
+                            line: LineNumber::New { line: 12 },
+                        },
+                    },
+                    CreateComment {
+                        body: "Outdated".to_string(),
+                        comment_type: CommentType::FileLevelComment {
+                            path: "README.md".to_string(),
+                        },
+                    },
+                ],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(review.id, 80);
+        assert_eq!(review.verdict, ReviewVerdict::RequestChanges);
+        assert_eq!(review.author.username, "reviewer");
+        assert!(review.submitted_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_submit_review_rejects_general_comments() {
+        let client = GitHubProvider::new().with_base_url("http://localhost:1".to_string());
+
+        let result = client
+            .submit_review(
+                "org",
+                "repo",
+                5,
+                ReviewVerdict::Comment,
+                String::new(),
+                vec![CreateComment {
+                    body: "General".to_string(),
+                    comment_type: CommentType::General,
+                }],
+            )
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_checks() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/commits/abc123/check-runs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": 3,
+                "check_runs": [
+                    {
+                        "name": "build",
+                        "status": "completed",
+                        "conclusion": "success",
+                        "html_url": "https://github.com/owner/repo/runs/1",
+                        "output": { "title": "Build succeeded" }
+                    },
+                    {
+                        "name": "test",
+                        "status": "completed",
+                        "conclusion": "timed_out",
+                        "html_url": "https://github.com/owner/repo/runs/2",
+                        "output": { "title": null }
+                    },
+                    {
+                        "name": "lint",
+                        "status": "in_progress",
+                        "conclusion": null,
+                        "html_url": "https://github.com/owner/repo/runs/3",
+                        "output": {}
+                    }
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/commits/abc123/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "state": "pending",
+                "statuses": [
+                    {
+                        "context": "ci/legacy",
+                        "state": "error",
+                        "description": "Build errored",
+                        "target_url": "https://ci.example.com/1"
+                    }
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+        let checks = client.get_checks("owner", "repo", "abc123").await.unwrap();
+
+        let statuses: Vec<_> = checks.iter().map(|c| (c.name.as_str(), c.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("build", CheckStatus::Success),
+                ("test", CheckStatus::Failure),
+                ("lint", CheckStatus::Pending),
+                ("ci/legacy", CheckStatus::Failure),
+            ]
+        );
+        assert_eq!(checks[0].description.as_deref(), Some("Build succeeded"));
+        assert_eq!(
+            checks[3].details_url.as_deref(),
+            Some("https://ci.example.com/1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_pr() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PUT"))
+            .and(path("/repos/owner/repo/pulls/1/merge"))
+            .and(body_partial_json(
+                serde_json::json!({ "merge_method": "squash" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sha": "abc123",
+                "merged": true,
+                "message": "Pull Request successfully merged"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("PUT"))
+            .and(path("/repos/owner/repo/pulls/2/merge"))
+            .respond_with(ResponseTemplate::new(405).set_body_json(serde_json::json!({
+                "message": "Pull Request is not mergeable"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+
+        client
+            .merge_pr("owner", "repo", 1, MergeStrategy::Squash)
+            .await
+            .unwrap();
+        assert!(
+            client
+                .merge_pr("owner", "repo", 2, MergeStrategy::Merge)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_pr_marks_ready_for_review() {
+        let mock_server = MockServer::start().await;
+
+        let pr_json = |title: &str, draft: bool| {
+            serde_json::json!({
+                "number": 1,
+                "node_id": "PR_kwDOABC",
+                "title": title,
+                "body": "New description",
+                "state": "open",
+                "draft": draft,
+                "user": {
+                    "id": 1,
+                    "login": "testuser",
+                    "avatar_url": "https://example.com/avatar.png",
+                    "html_url": "https://github.com/testuser"
+                },
+                "base": { "ref": "main" },
+                "head": { "ref": "feature", "sha": "abc123" },
+                "labels": [],
+                "assignees": [],
+                "requested_reviewers": [],
+                "created_at": "2025-01-01T00:00:00Z",
+                "updated_at": "2025-01-02T00:00:00Z",
+                "merged": false
+            })
+        };
+
+        Mock::given(method("PATCH"))
+            .and(path("/repos/owner/repo/pulls/1"))
+            .and(body_partial_json(serde_json::json!({
+                "title": "New title",
+                "body": "New description"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(pr_json("New title", true)))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/graphql"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": { "markPullRequestReadyForReview": { "pullRequest": { "id": "PR_kwDOABC" } } }
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/pulls/1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(pr_json("New title", false)))
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new()
+            .with_token("test-token".to_string())
+            .with_base_url(mock_server.uri());
+
+        let pr = client
+            .update_pr(
+                "owner",
+                "repo",
+                1,
+                Some("New title".to_string()),
+                Some("New description".to_string()),
+                Some(false),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(pr.title, "New title");
+        assert_eq!(pr.description, "New description");
+        assert!(!pr.draft);
+    }
+
+    #[test]
+    fn test_parse_mergeable_state() {
+        let state = |mergeable: serde_json::Value, mergeable_state: &str| {
+            parse_mergeable_state(&serde_json::json!({
+                "mergeable": mergeable,
+                "mergeable_state": mergeable_state,
+            }))
+        };
+
+        assert_eq!(state(true.into(), "clean"), MergeableState::Clean);
+        assert_eq!(state(true.into(), "blocked"), MergeableState::Blocked);
+        assert_eq!(state(false.into(), "dirty"), MergeableState::Conflicting);
+        assert_eq!(
+            state(serde_json::Value::Null, "unknown"),
+            MergeableState::Unknown
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_reviewers() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/repos/owner/repo/pulls/1/requested_reviewers"))
+            .and(body_partial_json(
+                serde_json::json!({ "reviewers": ["reviewer"] }),
+            ))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "number": 1,
+                "requested_reviewers": [
+                    {
+                        "id": 2,
+                        "login": "reviewer",
+                        "avatar_url": "https://avatars.githubusercontent.com/u/2",
+                        "html_url": "https://github.com/reviewer"
+                    }
+                ]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+        let reviewers = client
+            .request_reviewers("owner", "repo", 1, vec!["reviewer".to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(reviewers.len(), 1);
+        assert_eq!(reviewers[0].username, "reviewer");
+    }
+
+    #[tokio::test]
+    async fn test_get_collaborators() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/assignees"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {
+                    "id": 1,
+                    "login": "octocat",
+                    "avatar_url": "https://avatars.githubusercontent.com/u/1",
+                    "html_url": "https://github.com/octocat"
+                },
+                {
+                    "id": 2,
+                    "login": "reviewer",
+                    "avatar_url": "https://avatars.githubusercontent.com/u/2",
+                    "html_url": "https://github.com/reviewer"
+                }
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+        let collaborators = client.get_collaborators("owner", "repo").await.unwrap();
+
+        let usernames: Vec<_> = collaborators.iter().map(|u| u.username.as_str()).collect();
+        assert_eq!(usernames, vec!["octocat", "reviewer"]);
+    }
+
+    #[tokio::test]
+    async fn test_add_and_remove_labels() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/repos/owner/repo/issues/1/labels"))
+            .and(body_partial_json(
+                serde_json::json!({ "labels": ["needs review"] }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "name": "bug", "color": "d73a4a" },
+                { "name": "needs review", "color": "0e8a16" }
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/repos/owner/repo/issues/1/labels/needs%20review"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "name": "bug", "color": "d73a4a" }
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+
+        let labels = client
+            .add_labels("owner", "repo", 1, vec!["needs review".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(labels.len(), 2);
+
+        let labels = client
+            .remove_label("owner", "repo", 1, "needs review")
+            .await
+            .unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].name, "bug");
+    }
+
+    #[test]
+    fn test_summarize_reactions() {
+        let reactions = serde_json::json!([
+            { "id": 1, "content": "heart", "user": { "login": "alice" } },
+            { "id": 2, "content": "+1", "user": { "login": "bob" } },
+            { "id": 3, "content": "heart", "user": { "login": "bob" } }
+        ]);
+        let reactions = summarize_reactions(reactions.as_array().unwrap(), Some("alice"));
+
+        assert_eq!(reactions.len(), 2);
+        assert_eq!(reactions[0].content, ReactionContent::ThumbsUp);
+        assert_eq!(reactions[0].count, 1);
+        assert!(!reactions[0].viewer_reacted);
+        assert_eq!(reactions[1].content, ReactionContent::Heart);
+        assert_eq!(reactions[1].count, 2);
+        assert!(reactions[1].viewer_reacted);
+    }
+
+    #[test]
+    fn test_parse_reactions_from_comments() {
+        let rollup = parse_reaction_rollup(&serde_json::json!({
+            "url": "https://api.github.com/repos/owner/repo/issues/comments/1/reactions",
+            "total_count": 3,
+            "+1": 2,
+            "-1": 0,
+            "rocket": 1
+        }));
+        assert_eq!(rollup.len(), 2);
+        assert_eq!(rollup[0].content, ReactionContent::ThumbsUp);
+        assert_eq!(rollup[0].count, 2);
+        assert_eq!(rollup[1].content, ReactionContent::Rocket);
+
+        let groups = parse_graphql_reaction_groups(&serde_json::json!([
+            { "content": "THUMBS_UP", "viewerHasReacted": false, "reactors": { "totalCount": 0 } },
+            { "content": "EYES", "viewerHasReacted": true, "reactors": { "totalCount": 1 } }
+        ]));
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].content, ReactionContent::Eyes);
+        assert!(groups[0].viewer_reacted);
+    }
+
+    #[tokio::test]
+    async fn test_toggle_reaction_on_issue_comment() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/pulls/comments/7/reactions"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/issues/comments/7/reactions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                { "id": 99, "content": "heart", "user": { "login": "viewer" } }
+            ])))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/user"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "login": "viewer" })),
+            )
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("DELETE"))
+            .and(path("/repos/owner/repo/issues/comments/7/reactions/99"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/repos/owner/repo/issues/comments/7/reactions"))
+            .and(body_partial_json(serde_json::json!({ "content": "+1" })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": 100,
+                "content": "+1",
+                "user": { "login": "viewer" }
+            })))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new()
+            .with_token("test-token".to_string())
+            .with_base_url(mock_server.uri());
+
+        client
+            .toggle_reaction("owner", "repo", 7, ReactionContent::Heart)
+            .await
+            .unwrap();
+        client
+            .toggle_reaction("owner", "repo", 7, ReactionContent::ThumbsUp)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_resolve_and_unresolve_thread() {
+        use wiremock::matchers::body_string_contains;
+
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/graphql"))
+            .and(body_string_contains("resolveReviewThread"))
+            .and(body_string_contains("PRRT_thread1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": { "resolveReviewThread": { "thread": { "id": "PRRT_thread1" } } }
+            })))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new()
+            .with_token("test-token".to_string())
+            .with_base_url(mock_server.uri());
+
+        client.resolve_thread("PRRT_thread1").await.unwrap();
+        client.unresolve_thread("PRRT_thread1").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_resolve_thread_graphql_error() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/graphql"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "errors": [{ "message": "Could not resolve to a node" }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+
+        assert!(client.resolve_thread("missing").await.is_err());
+    }
+
     #[test]
     fn test_parse_link_header_with_next() {
         let header = r#"<https://api.github.com/repos/o/r/pulls/1/files?page=2>; rel="next", <https://api.github.com/repos/o/r/pulls/1/files?page=3>; rel="last""#;
@@ -1855,8 +3597,9 @@ mod tests {
             .mount(&mock_server)
             .await;
 
-        let client = reqwest::Client::new();
-        let result = fetch_all_pr_files(&client, &mock_server.uri(), "owner", "repo", 123, None)
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+        let result = client
+            .fetch_all_pr_files("owner", "repo", 123)
             .await
             .unwrap();
 
@@ -1914,8 +3657,9 @@ mod tests {
             .mount(&mock_server)
             .await;
 
-        let client = reqwest::Client::new();
-        let result = fetch_all_pr_files(&client, &mock_server.uri(), "owner", "repo", 123, None)
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+        let result = client
+            .fetch_all_pr_files("owner", "repo", 123)
             .await
             .unwrap();
 
@@ -1990,6 +3734,185 @@ mod tests {
         assert_eq!(result[0].filename, "src/lib.rs");
     }
 
+    #[tokio::test]
+    async fn test_get_diff_falls_back_to_raw_diff_for_missing_patches() {
+        let mock_server = MockServer::start().await;
+
+        let files = serde_json::json!([
+            {
+                "filename": "big.rs",
+                "status": "modified",
+                "additions": 1,
+                "deletions": 1
+            },
+            {
+                "filename": "logo.png",
+                "status": "added",
+                "additions": 0,
+                "deletions": 0
+            },
+            {
+                "filename": "src/lib.rs",
+                "status": "modified",
+                "additions": 1,
+                "deletions": 0,
+                "patch": "@@ -1 +1,2 @@\n code\n+more"
+            }
+        ]);
+        let raw_diff = "\
+diff --git a/big.rs b/big.rs
+index 1111111..2222222 100644
+--- a/big.rs
++++ b/big.rs
+@@ -1 +1 @@
+-old
++new
+diff --git a/logo.png b/logo.png
+new file mode 100644
+index 0000000..3333333
+Binary files /dev/null and b/logo.png differ
+";
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/pulls/123/files"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&files))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/pulls/123"))
+            .and(header("Accept", "application/vnd.github.diff"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(raw_diff))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+        let result = client.get_diff("owner", "repo", 123).await.unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].filename, "big.rs");
+        assert_eq!(result[0].hunks.len(), 1);
+        assert_eq!(result[1].filename, "src/lib.rs");
+    }
+
+    #[tokio::test]
+    async fn test_get_diff_skips_raw_diff_when_all_patches_present() {
+        let mock_server = MockServer::start().await;
+
+        let files = serde_json::json!([
+            {
+                "filename": "src/lib.rs",
+                "status": "modified",
+                "additions": 1,
+                "deletions": 0,
+                "patch": "@@ -1 +1,2 @@\n code\n+more"
+            }
+        ]);
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/pulls/123/files"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&files))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/pulls/123"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(""))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+        let result = client.get_diff("owner", "repo", 123).await.unwrap();
+
+        assert_eq!(result.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_commits() {
+        let mock_server = MockServer::start().await;
+
+        let commits = serde_json::json!([
+            {
+                "sha": "aaa111",
+                "commit": {
+                    "message": "Add feature\n\nLonger description",
+                    "author": { "name": "Alice", "date": "2024-01-01T00:00:00Z" },
+                    "committer": { "name": "Alice", "date": "2024-01-02T00:00:00Z" }
+                },
+                "author": {
+                    "id": 1,
+                    "login": "alice",
+                    "avatar_url": "https://avatars.githubusercontent.com/u/1",
+                    "html_url": "https://github.com/alice"
+                }
+            },
+            {
+                "sha": "bbb222",
+                "commit": {
+                    "message": "Fix typo",
+                    "author": { "name": "Bob Unlinked", "date": "2024-01-03T00:00:00Z" },
+                    "committer": { "name": "Bob Unlinked", "date": "2024-01-03T00:00:00Z" }
+                },
+                "author": null
+            }
+        ]);
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/pulls/123/commits"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&commits))
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+        let result = client.get_commits("owner", "repo", 123).await.unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].sha, "aaa111");
+        assert_eq!(result[0].author.username, "alice");
+        assert_eq!(
+            result[0].committed_at,
+            parse_datetime("2024-01-02T00:00:00Z")
+        );
+        assert_eq!(result[1].message, "Fix typo");
+        assert_eq!(result[1].author.username, "Bob Unlinked");
+    }
+
+    #[tokio::test]
+    async fn test_get_commit_diff() {
+        let mock_server = MockServer::start().await;
+
+        let commit = serde_json::json!({
+            "sha": "aaa111",
+            "files": [
+                {
+                    "filename": "src/lib.rs",
+                    "status": "modified",
+                    "additions": 1,
+                    "deletions": 1,
+                    "patch": "@@ -1 +1 @@\n-old\n+new"
+                }
+            ]
+        });
+
+        Mock::given(method("GET"))
+            .and(path("/repos/owner/repo/commits/aaa111"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&commit))
+            .mount(&mock_server)
+            .await;
+
+        let client = GitHubProvider::new().with_base_url(mock_server.uri());
+        let result = client
+            .get_commit_diff("owner", "repo", "aaa111")
+            .await
+            .unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].filename, "src/lib.rs");
+        assert_eq!((result[0].additions, result[0].deletions), (1, 1));
+    }
+
     #[tokio::test]
     async fn test_get_comment_review_without_replies() {
         let mock_server = MockServer::start().await;
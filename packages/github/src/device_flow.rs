@@ -0,0 +1,282 @@
+//! GitHub's OAuth device authorization flow.
+//!
+//! The user opens [`DeviceCode::verification_uri`] in a browser and enters
+//! [`DeviceCode::user_code`] while we poll for the access token, so no
+//! redirect URL or client secret is needed.
+
+use std::time::Duration;
+
+use anyhow::Result;
+
+/// Scopes needed to read and review pull requests.
+pub const DEFAULT_SCOPE: &str = "repo read:org";
+
+/// A pending device authorization.
+#[derive(Debug, Clone)]
+pub struct DeviceCode {
+    pub device_code: String,
+    /// Code the user enters at [`Self::verification_uri`].
+    pub user_code: String,
+    pub verification_uri: String,
+    /// Seconds until the codes expire.
+    pub expires_in: u64,
+    /// Minimum seconds between token polls.
+    pub interval: u64,
+}
+
+/// Result of a single token poll.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenPoll {
+    /// The user hasn't entered the code yet.
+    Pending,
+    /// Polling too often; wait longer between polls.
+    SlowDown,
+    /// The user authorized the app.
+    Token(String),
+}
+
+pub struct DeviceFlow {
+    http_client: reqwest::Client,
+    client_id: String,
+    scope: String,
+    base_url: String,
+}
+
+impl DeviceFlow {
+    /// Create a device flow for the OAuth app with `client_id`.
+    ///
+    /// # Panics
+    ///
+    /// * If the `reqwest::Client` fails to build.
+    #[must_use]
+    pub fn new(client_id: impl Into<String>) -> Self {
+        let http_client = reqwest::Client::builder()
+            .user_agent("ChadReview")
+            .build()
+            .unwrap();
+        Self {
+            http_client,
+            client_id: client_id.into(),
+            scope: DEFAULT_SCOPE.to_string(),
+            base_url: "https://github.com".to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = scope.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    async fn post(&self, path: &str, body: &serde_json::Value) -> Result<serde_json::Value> {
+        let url = format!("{}{path}", self.base_url);
+        log::debug!("POST {url}");
+
+        let response = self
+            .http_client
+            .post(&url)
+            .header("Accept", "application/json")
+            .json(body)
+            .send()
+            .await?;
+        let status = response.status();
+
+        if !status.is_success() {
+            log::error!("GitHub OAuth error: {}", response.text().await?);
+            anyhow::bail!("GitHub OAuth error: {status}");
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Start an authorization, returning the code to show the user.
+    ///
+    /// # Errors
+    ///
+    /// * If the request fails or the OAuth app doesn't have device flow
+    ///   enabled.
+    pub async fn request_code(&self) -> Result<DeviceCode> {
+        let value = self
+            .post(
+                "/login/device/code",
+                &serde_json::json!({ "client_id": self.client_id, "scope": self.scope }),
+            )
+            .await?;
+
+        if let Some(error) = value["error"].as_str() {
+            anyhow::bail!("Device flow unavailable: {error}");
+        }
+
+        let field = |name: &str| {
+            value[name]
+                .as_str()
+                .map(ToString::to_string)
+                .ok_or_else(|| anyhow::anyhow!("Missing {name} in device code response"))
+        };
+
+        Ok(DeviceCode {
+            device_code: field("device_code")?,
+            user_code: field("user_code")?,
+            verification_uri: field("verification_uri")?,
+            expires_in: value["expires_in"].as_u64().unwrap_or(900),
+            interval: value["interval"].as_u64().unwrap_or(5),
+        })
+    }
+
+    /// Check once whether the user has authorized `code`.
+    ///
+    /// # Errors
+    ///
+    /// * If the request fails, the code expired, or the user denied access.
+    pub async fn poll_token(&self, code: &DeviceCode) -> Result<TokenPoll> {
+        let value = self
+            .post(
+                "/login/oauth/access_token",
+                &serde_json::json!({
+                    "client_id": self.client_id,
+                    "device_code": code.device_code,
+                    "grant_type": "urn:ietf:params:oauth:grant-type:device_code",
+                }),
+            )
+            .await?;
+
+        if let Some(token) = value["access_token"].as_str() {
+            return Ok(TokenPoll::Token(token.to_string()));
+        }
+
+        match value["error"].as_str() {
+            Some("authorization_pending") => Ok(TokenPoll::Pending),
+            Some("slow_down") => Ok(TokenPoll::SlowDown),
+            Some("expired_token") => anyhow::bail!("The code expired, please try again"),
+            Some("access_denied") => anyhow::bail!("Access was denied"),
+            Some(error) => anyhow::bail!("GitHub OAuth error: {error}"),
+            None => anyhow::bail!("No access token in response"),
+        }
+    }
+
+    /// Poll until the user authorizes `code`, respecting the requested
+    /// interval, and return the access token.
+    ///
+    /// # Errors
+    ///
+    /// * If polling fails, the code expires, or the user denies access.
+    pub async fn wait_for_token(&self, code: &DeviceCode) -> Result<String> {
+        let mut interval = Duration::from_secs(code.interval);
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            match self.poll_token(code).await? {
+                TokenPoll::Token(token) => return Ok(token),
+                TokenPoll::Pending => {}
+                // GitHub asks for 5 more seconds each time
+                TokenPoll::SlowDown => interval += Duration::from_secs(5),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn device_code() -> DeviceCode {
+        DeviceCode {
+            device_code: "device123".to_string(),
+            user_code: "ABCD-1234".to_string(),
+            verification_uri: "https://github.com/login/device".to_string(),
+            expires_in: 900,
+            interval: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_code() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/login/device/code"))
+            .and(body_partial_json(
+                serde_json::json!({ "client_id": "client", "scope": "repo" }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "device_code": "device123",
+                "user_code": "ABCD-1234",
+                "verification_uri": "https://github.com/login/device",
+                "expires_in": 900,
+                "interval": 5
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let flow = DeviceFlow::new("client")
+            .with_scope("repo")
+            .with_base_url(mock_server.uri());
+        let code = flow.request_code().await.unwrap();
+
+        assert_eq!(code.user_code, "ABCD-1234");
+        assert_eq!(code.verification_uri, "https://github.com/login/device");
+        assert_eq!(code.interval, 5);
+    }
+
+    #[tokio::test]
+    async fn test_poll_token_states() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/login/oauth/access_token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "error": "authorization_pending" })),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/login/oauth/access_token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "gho_token",
+                "token_type": "bearer",
+                "scope": "repo"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let flow = DeviceFlow::new("client").with_base_url(mock_server.uri());
+        let code = device_code();
+
+        assert_eq!(flow.poll_token(&code).await.unwrap(), TokenPoll::Pending);
+        assert_eq!(
+            flow.poll_token(&code).await.unwrap(),
+            TokenPoll::Token("gho_token".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_token_fails_when_denied() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/login/oauth/access_token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "error": "access_denied" })),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let flow = DeviceFlow::new("client").with_base_url(mock_server.uri());
+        let result = flow.wait_for_token(&device_code()).await;
+
+        assert!(result.unwrap_err().to_string().contains("denied"));
+    }
+}
@@ -2,6 +2,9 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 #![allow(clippy::multiple_crate_versions)]
 
+pub mod cache;
 pub mod client;
+pub mod device_flow;
 
 pub use client::GitHubProvider;
+pub use device_flow::{DeviceCode, DeviceFlow};
@@ -0,0 +1,262 @@
+//! Hook configuration.
+//!
+//! Hooks are read from the user's config directory. A repository's own
+//! `.chadreview/hooks.json` runs arbitrary commands from whoever wrote the
+//! repository, so it is only loaded for repositories the user listed in
+//! `trusted_repos`.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{HookError, HookEvent, WebhookDefinition};
+
+/// Path of the hooks config file, relative to the repository root.
+pub const HOOKS_CONFIG_PATH: &str = ".chadreview/hooks.json";
+
+/// Path of the global hooks config file, relative to the config directory.
+const GLOBAL_HOOKS_CONFIG_PATH: &str = "chadreview/hooks.json";
+
+/// Default timeout for a hook command.
+const DEFAULT_TIMEOUT_SECONDS: u64 = 30;
+
+/// Parsed `hooks.json`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// Configured command hooks.
+    #[serde(default)]
+    pub hooks: Vec<HookDefinition>,
+    /// Configured outgoing webhooks.
+    #[serde(default)]
+    pub webhooks: Vec<WebhookDefinition>,
+    /// Repositories whose own hooks config is loaded. Only read from the
+    /// global config.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_repos: Vec<PathBuf>,
+}
+
+/// A single external command hook.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookDefinition {
+    /// Command to run (resolved relative to the repository root).
+    pub command: String,
+    /// Arguments passed to the command.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Events this hook subscribes to. Empty means all events.
+    #[serde(default)]
+    pub events: Vec<HookEvent>,
+    /// Maximum run time before the command is killed.
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+pub const fn default_timeout_seconds() -> u64 {
+    DEFAULT_TIMEOUT_SECONDS
+}
+
+impl HookDefinition {
+    /// Whether this hook should run for the given event.
+    #[must_use]
+    pub fn handles(&self, event: HookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+impl HooksConfig {
+    /// Load a repository's own hooks config, if the global config lists it
+    /// in `trusted_repos`. Untrusted repositories yield an empty config.
+    ///
+    /// A missing config file is treated as empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a config file exists but cannot be read or parsed.
+    pub fn load(repo_path: &Path) -> Result<Self, HookError> {
+        Self::load_from(global_config_path().as_deref(), repo_path)
+    }
+
+    /// Load the global hooks config (`$XDG_CONFIG_HOME/chadreview/hooks.json`).
+    ///
+    /// Global hooks apply to every repository and to hosted PR events.
+    /// A missing config file yields an empty config.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config file exists but cannot be read or parsed.
+    pub fn load_global() -> Result<Self, HookError> {
+        global_config_path().map_or_else(|| Ok(Self::default()), |path| Self::load_from_path(&path))
+    }
+
+    fn load_from(global_path: Option<&Path>, repo_path: &Path) -> Result<Self, HookError> {
+        let global = global_path.map_or_else(|| Ok(Self::default()), Self::load_from_path)?;
+        if !global.trusts(repo_path) {
+            return Ok(Self::default());
+        }
+
+        Self::load_from_path(&repo_path.join(HOOKS_CONFIG_PATH))
+    }
+
+    fn load_from_path(path: &Path) -> Result<Self, HookError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(HookError::Parse)
+    }
+
+    /// Whether `repo_path` is one of the trusted repositories.
+    fn trusts(&self, repo_path: &Path) -> bool {
+        let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let repo_path = canonical(repo_path);
+        self.trusted_repos
+            .iter()
+            .any(|trusted| canonical(trusted) == repo_path)
+    }
+
+    /// Append the hooks and webhooks from another config.
+    #[must_use]
+    pub fn merge(mut self, other: Self) -> Self {
+        self.hooks.extend(other.hooks);
+        self.webhooks.extend(other.webhooks);
+        self
+    }
+
+    /// Hooks subscribed to the given event.
+    pub fn hooks_for(&self, event: HookEvent) -> impl Iterator<Item = &HookDefinition> {
+        self.hooks.iter().filter(move |hook| hook.handles(event))
+    }
+
+    /// Webhooks subscribed to the given event.
+    pub fn webhooks_for(&self, event: HookEvent) -> impl Iterator<Item = &WebhookDefinition> {
+        self.webhooks
+            .iter()
+            .filter(move |webhook| webhook.handles(event))
+    }
+}
+
+fn global_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(GLOBAL_HOOKS_CONFIG_PATH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let json = r#"{
+            "hooks": [
+                {
+                    "command": "./notify.sh",
+                    "args": ["--channel", "reviews"],
+                    "events": ["thread-created", "ai-completed"]
+                },
+                {
+                    "command": "logger",
+                    "timeout_seconds": 5
+                }
+            ]
+        }"#;
+
+        let config: HooksConfig = serde_json::from_str(json).unwrap();
+
+        assert_eq!(config.hooks.len(), 2);
+        assert_eq!(config.hooks[0].args, vec!["--channel", "reviews"]);
+        assert_eq!(config.hooks[0].timeout_seconds, DEFAULT_TIMEOUT_SECONDS);
+        assert_eq!(config.hooks[1].timeout_seconds, 5);
+    }
+
+    #[test]
+    fn test_hooks_for_event() {
+        let json = r#"{
+            "hooks": [
+                { "command": "a", "events": ["thread-created"] },
+                { "command": "b", "events": ["thread-resolved"] },
+                { "command": "c" }
+            ]
+        }"#;
+
+        let config: HooksConfig = serde_json::from_str(json).unwrap();
+        let commands: Vec<_> = config
+            .hooks_for(HookEvent::ThreadCreated)
+            .map(|hook| hook.command.as_str())
+            .collect();
+
+        assert_eq!(commands, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_parse_webhooks_and_merge() {
+        let repo: HooksConfig = serde_json::from_str(
+            r#"{
+                "webhooks": [
+                    {
+                        "url": "https://hooks.slack.com/services/T/B/X",
+                        "events": ["review-exported", "ai-blocker-found"],
+                        "templates": { "review-exported": "Done: {{data.summary}}" }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let global: HooksConfig =
+            serde_json::from_str(r#"{ "hooks": [{ "command": "logger" }] }"#).unwrap();
+
+        let config = global.merge(repo);
+
+        assert_eq!(config.hooks.len(), 1);
+        assert_eq!(config.webhooks_for(HookEvent::ReviewExported).count(), 1);
+        assert_eq!(config.webhooks_for(HookEvent::ThreadCreated).count(), 0);
+        assert_eq!(
+            config.webhooks[0].templates[&HookEvent::ReviewExported],
+            "Done: {{data.summary}}"
+        );
+    }
+
+    #[test]
+    fn test_missing_config_is_empty() {
+        let dir = std::env::temp_dir().join("chadreview-hooks-does-not-exist");
+        let config = HooksConfig::load_from(Some(&dir.join("hooks.json")), &dir).unwrap();
+        assert!(config.hooks.is_empty());
+    }
+
+    #[test]
+    fn test_repo_hooks_need_trust() {
+        let dir = std::env::temp_dir().join(format!(
+            "chadreview-hooks-test-{}",
+            switchy::uuid::Uuid::new_v4()
+        ));
+        let repo = dir.join("repo");
+        std::fs::create_dir_all(repo.join(".chadreview")).unwrap();
+        std::fs::write(
+            repo.join(HOOKS_CONFIG_PATH),
+            r#"{ "hooks": [{ "command": "repo-hook" }] }"#,
+        )
+        .unwrap();
+
+        let user_path = dir.join("hooks.json");
+        std::fs::write(&user_path, r#"{ "hooks": [{ "command": "user-hook" }] }"#).unwrap();
+        let commands = |config: HooksConfig| {
+            config
+                .hooks
+                .into_iter()
+                .map(|hook| hook.command)
+                .collect::<Vec<_>>()
+        };
+
+        let config = HooksConfig::load_from(Some(&user_path), &repo).unwrap();
+        assert!(commands(config).is_empty());
+
+        let trusted = serde_json::json!({
+            "hooks": [{ "command": "user-hook" }],
+            "trusted_repos": [repo],
+        });
+        std::fs::write(&user_path, trusted.to_string()).unwrap();
+        let config = HooksConfig::load_from(Some(&user_path), &repo).unwrap();
+        assert_eq!(commands(config), vec!["repo-hook"]);
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
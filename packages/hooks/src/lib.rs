@@ -0,0 +1,113 @@
+#![cfg_attr(feature = "fail-on-warnings", deny(warnings))]
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![allow(clippy::multiple_crate_versions)]
+
+//! Review event hooks for `ChadReview`.
+//!
+//! This crate runs external commands and sends Slack-compatible webhooks
+//! when review events occur. Commands receive the event as JSON on stdin;
+//! webhooks receive a templated `{"text": ...}` message. Hooks come from the
+//! user's config, or from repositories the user has marked as trusted.
+
+mod config;
+mod runner;
+mod webhook;
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub use config::{HOOKS_CONFIG_PATH, HookDefinition, HooksConfig};
+pub use runner::{HookOutcome, dispatch, dispatch_global, run_hook};
+pub use webhook::{WebhookDefinition, render_template, send_webhook};
+
+/// Errors that can occur when loading or running hooks.
+#[derive(Debug, thiserror::Error)]
+pub enum HookError {
+    /// Failed to read the hooks config or spawn a hook command.
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Failed to parse the hooks config.
+    #[error("Failed to parse hooks config: {0}")]
+    Parse(serde_json::Error),
+
+    /// Failed to serialize the event payload.
+    #[error("Failed to serialize hook payload: {0}")]
+    Serialize(serde_json::Error),
+
+    /// Failed to send a webhook request.
+    #[error("Webhook request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// A webhook endpoint responded with a non-success status.
+    #[error("Webhook returned status {0}")]
+    WebhookStatus(u16),
+}
+
+/// Review events that hooks can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookEvent {
+    /// A new comment thread was created.
+    ThreadCreated,
+    /// A comment thread was marked as resolved.
+    ThreadResolved,
+    /// An AI action finished (successfully or not).
+    AiCompleted,
+    /// A review was exported.
+    ReviewExported,
+    /// An AI response reported blocker-severity issues.
+    AiBlockerFound,
+    /// A subscribed pull request received a new review.
+    PrReviewReceived,
+}
+
+impl HookEvent {
+    /// The event name as used in config files and payloads.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::ThreadCreated => "thread-created",
+            Self::ThreadResolved => "thread-resolved",
+            Self::AiCompleted => "ai-completed",
+            Self::ReviewExported => "review-exported",
+            Self::AiBlockerFound => "ai-blocker-found",
+            Self::PrReviewReceived => "pr-review-received",
+        }
+    }
+}
+
+impl std::fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// JSON payload written to a hook's stdin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookPayload {
+    /// The event that triggered the hook.
+    pub event: HookEvent,
+    /// Repository the event happened in (absent for hosted PR events).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_path: Option<PathBuf>,
+    /// When the event happened.
+    pub timestamp: DateTime<Utc>,
+    /// Event-specific data (e.g., the comment thread).
+    pub data: serde_json::Value,
+}
+
+impl HookPayload {
+    /// Create a new payload for the given event.
+    #[must_use]
+    pub fn new(event: HookEvent, repo_path: Option<PathBuf>, data: serde_json::Value) -> Self {
+        Self {
+            event,
+            repo_path,
+            timestamp: Utc::now(),
+            data,
+        }
+    }
+}
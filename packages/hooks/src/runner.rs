@@ -0,0 +1,320 @@
+//! Hook command execution.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::{
+    HookDefinition, HookError, HookEvent, HookPayload, HooksConfig, WebhookDefinition, send_webhook,
+};
+
+/// Interval between checks for a finished hook process.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Result of running a single hook command.
+#[derive(Debug)]
+pub enum HookOutcome {
+    /// The command exited (successfully or not).
+    Exited(ExitStatus),
+    /// The command ran longer than its timeout and was killed.
+    TimedOut,
+}
+
+/// Fire an event for all hooks configured in the repository and globally.
+///
+/// Hooks and webhooks run on background threads so that slow commands never
+/// block the request that triggered the event. Failures are logged, not
+/// returned.
+pub fn dispatch(repo_path: &Path, event: HookEvent, data: &impl Serialize) {
+    let config = load_config(HooksConfig::load(repo_path), "repository");
+    dispatch_with_config(config, Some(repo_path.to_path_buf()), event, data);
+}
+
+/// Fire an event that is not tied to a local repository (e.g. a hosted PR
+/// review) for the globally configured hooks.
+pub fn dispatch_global(event: HookEvent, data: &impl Serialize) {
+    dispatch_with_config(HooksConfig::default(), None, event, data);
+}
+
+fn load_config(result: Result<HooksConfig, HookError>, kind: &str) -> HooksConfig {
+    result
+        .inspect_err(|e| log::warn!("Failed to load {kind} hooks config: {e}"))
+        .unwrap_or_default()
+}
+
+fn dispatch_with_config(
+    config: HooksConfig,
+    repo_path: Option<PathBuf>,
+    event: HookEvent,
+    data: &impl Serialize,
+) {
+    let config = load_config(HooksConfig::load_global(), "global").merge(config);
+
+    let hooks: Vec<HookDefinition> = config.hooks_for(event).cloned().collect();
+    let webhooks: Vec<WebhookDefinition> = config.webhooks_for(event).cloned().collect();
+    if hooks.is_empty() && webhooks.is_empty() {
+        return;
+    }
+
+    let data = match serde_json::to_value(data) {
+        Ok(data) => data,
+        Err(e) => {
+            log::error!("Failed to serialize {event} hook data: {e}");
+            return;
+        }
+    };
+    let payload = HookPayload::new(event, repo_path, data);
+
+    for hook in hooks {
+        let payload = payload.clone();
+        std::thread::spawn(move || match run_hook(&hook, &payload) {
+            Ok(HookOutcome::Exited(status)) if status.success() => {
+                log::debug!("Hook '{}' handled {}", hook.command, payload.event);
+            }
+            Ok(HookOutcome::Exited(status)) => {
+                log::warn!(
+                    "Hook '{}' failed for {} with {status}",
+                    hook.command,
+                    payload.event
+                );
+            }
+            Ok(HookOutcome::TimedOut) => {
+                log::warn!(
+                    "Hook '{}' timed out after {}s for {}",
+                    hook.command,
+                    hook.timeout_seconds,
+                    payload.event
+                );
+            }
+            Err(e) => {
+                log::error!("Failed to run hook '{}': {e}", hook.command);
+            }
+        });
+    }
+
+    for webhook in webhooks {
+        let payload = payload.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = send_webhook(&webhook, &payload) {
+                log::error!("Failed to send {} webhook: {e}", payload.event);
+            }
+        });
+    }
+}
+
+/// Run a single hook command synchronously, writing the payload to stdin.
+///
+/// # Errors
+///
+/// Returns an error if the payload cannot be serialized or the command
+/// cannot be spawned.
+pub fn run_hook(hook: &HookDefinition, payload: &HookPayload) -> Result<HookOutcome, HookError> {
+    let input = serde_json::to_vec(payload).map_err(HookError::Serialize)?;
+
+    let mut command = payload.repo_path.as_ref().map_or_else(
+        || Command::new(&hook.command),
+        |repo_path| {
+            let mut command = Command::new(resolve_command(&hook.command, repo_path));
+            command.current_dir(repo_path);
+            command
+        },
+    );
+
+    // In a process group of its own, so the processes it starts can be
+    // killed with it on timeout
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let mut child = command
+        .args(&hook.args)
+        .env("CHADREVIEW_EVENT", payload.event.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+
+    // Written from its own thread, so a hook that doesn't read a payload
+    // bigger than the pipe buffer can't keep the timeout from firing
+    if let Some(mut stdin) = child.stdin.take() {
+        let command = hook.command.clone();
+        std::thread::spawn(move || {
+            // A hook may exit without reading stdin; that's not an error
+            if let Err(e) = stdin.write_all(&input) {
+                log::debug!("Hook '{command}' did not read its payload: {e}");
+            }
+        });
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(hook.timeout_seconds);
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(HookOutcome::Exited(status));
+        }
+
+        if Instant::now() >= deadline {
+            kill_hook(&mut child)?;
+            child.wait()?;
+            return Ok(HookOutcome::TimedOut);
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Kill a hook's process along with, on Unix, every process it started
+/// and left in its process group.
+fn kill_hook(child: &mut Child) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use nix::sys::signal::{Signal, killpg};
+        use nix::unistd::Pid;
+
+        // The hook leads its own process group, so the group has its PID
+        let pid = child.id().cast_signed();
+        match killpg(Pid::from_raw(pid), Signal::SIGKILL) {
+            Ok(()) => return Ok(()),
+            Err(e) => log::warn!(
+                "Failed to kill the process group of hook process {pid}, \
+                 killing only the hook: {e}"
+            ),
+        }
+    }
+
+    child.kill()
+}
+
+/// Resolve relative command paths (e.g. `./scripts/notify.sh`) against the
+/// repository root. Bare command names are left for `PATH` lookup.
+fn resolve_command(command: &str, repo_path: &Path) -> PathBuf {
+    let path = Path::new(command);
+
+    if path.is_relative() && path.components().count() > 1 {
+        repo_path.join(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use switchy::uuid::Uuid;
+
+    use super::*;
+
+    fn temp_repo() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("chadreview-hooks-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn shell_hook(script: &str, timeout_seconds: u64) -> HookDefinition {
+        HookDefinition {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            events: vec![],
+            timeout_seconds,
+        }
+    }
+
+    #[test]
+    fn test_run_hook_receives_payload_on_stdin() {
+        let repo = temp_repo();
+        let hook = shell_hook(
+            "cat > payload.json && echo \"$CHADREVIEW_EVENT\" > event",
+            5,
+        );
+        let payload = HookPayload::new(
+            HookEvent::ThreadCreated,
+            Some(repo.clone()),
+            serde_json::json!({ "body": "hello" }),
+        );
+
+        let outcome = run_hook(&hook, &payload).unwrap();
+        assert!(matches!(outcome, HookOutcome::Exited(status) if status.success()));
+
+        let written: HookPayload =
+            serde_json::from_str(&std::fs::read_to_string(repo.join("payload.json")).unwrap())
+                .unwrap();
+        assert_eq!(written.event, HookEvent::ThreadCreated);
+        assert_eq!(written.data["body"], "hello");
+
+        let event = std::fs::read_to_string(repo.join("event")).unwrap();
+        assert_eq!(event.trim(), "thread-created");
+
+        std::fs::remove_dir_all(repo).ok();
+    }
+
+    #[test]
+    fn test_resolve_command() {
+        let repo = Path::new("/repo");
+        assert_eq!(resolve_command("sh", repo), PathBuf::from("sh"));
+        assert_eq!(
+            resolve_command("./scripts/notify.sh", repo),
+            PathBuf::from("/repo/./scripts/notify.sh")
+        );
+        assert_eq!(
+            resolve_command("/usr/bin/env", repo),
+            PathBuf::from("/usr/bin/env")
+        );
+    }
+
+    #[test]
+    fn test_run_hook_times_out() {
+        let repo = temp_repo();
+        let hook = shell_hook("sleep 10", 0);
+        let payload = HookPayload::new(
+            HookEvent::AiCompleted,
+            Some(repo.clone()),
+            serde_json::Value::Null,
+        );
+
+        let outcome = run_hook(&hook, &payload).unwrap();
+        assert!(matches!(outcome, HookOutcome::TimedOut));
+
+        std::fs::remove_dir_all(repo).ok();
+    }
+
+    #[test]
+    fn test_run_hook_times_out_without_reading_large_payload() {
+        let repo = temp_repo();
+        let hook = shell_hook("sleep 10", 1);
+        let payload = HookPayload::new(
+            HookEvent::ThreadCreated,
+            Some(repo.clone()),
+            serde_json::Value::String("x".repeat(1024 * 1024)),
+        );
+
+        let outcome = run_hook(&hook, &payload).unwrap();
+        assert!(matches!(outcome, HookOutcome::TimedOut));
+
+        std::fs::remove_dir_all(repo).ok();
+    }
+
+    #[test]
+    fn test_run_hook_timeout_kills_started_processes() {
+        let repo = temp_repo();
+        let hook = shell_hook(
+            "(while true; do echo tick >> ticks; sleep 0.05; done) & sleep 10",
+            1,
+        );
+        let payload = HookPayload::new(
+            HookEvent::ThreadCreated,
+            Some(repo.clone()),
+            serde_json::Value::Null,
+        );
+
+        let outcome = run_hook(&hook, &payload).unwrap();
+        assert!(matches!(outcome, HookOutcome::TimedOut));
+
+        let ticks = || std::fs::read_to_string(repo.join("ticks")).unwrap().len();
+        let after_timeout = ticks();
+        std::thread::sleep(Duration::from_millis(300));
+        assert_eq!(ticks(), after_timeout);
+
+        std::fs::remove_dir_all(repo).ok();
+    }
+}
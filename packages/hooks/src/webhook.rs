@@ -0,0 +1,210 @@
+//! Slack-compatible outgoing webhooks.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::default_timeout_seconds;
+use crate::{HookError, HookEvent, HookPayload};
+
+/// An outgoing webhook receiving Slack-compatible `{"text": ...}` messages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDefinition {
+    /// Endpoint to POST messages to (e.g. a Slack incoming webhook URL).
+    pub url: String,
+    /// Events this webhook subscribes to. Empty means all events.
+    #[serde(default)]
+    pub events: Vec<HookEvent>,
+    /// Per-event message templates overriding the defaults.
+    ///
+    /// Templates may reference `{{event}}`, `{{repo}}`, `{{repo_path}}`,
+    /// `{{timestamp}}` and fields of the event data such as
+    /// `{{data.body}}` or `{{data.author.name}}`.
+    #[serde(default)]
+    pub templates: BTreeMap<HookEvent, String>,
+    /// Request timeout.
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+impl WebhookDefinition {
+    /// Whether this webhook should fire for the given event.
+    #[must_use]
+    pub fn handles(&self, event: HookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+
+    /// Render the message text for a payload.
+    #[must_use]
+    pub fn message_text(&self, payload: &HookPayload) -> String {
+        let template = self
+            .templates
+            .get(&payload.event)
+            .map_or_else(|| default_template(payload.event), String::as_str);
+
+        render_template(template, payload)
+    }
+}
+
+/// Default message template for an event.
+const fn default_template(event: HookEvent) -> &'static str {
+    match event {
+        HookEvent::ThreadCreated => "New review thread in {{repo}}: {{data.body}}",
+        HookEvent::ThreadResolved => "Review thread resolved in {{repo}}: {{data.body}}",
+        HookEvent::AiCompleted => "AI action finished in {{repo}} ({{data.status.status}})",
+        HookEvent::ReviewExported => "Review exported for {{repo}}: {{data.summary}}",
+        HookEvent::AiBlockerFound => {
+            ":rotating_light: AI review found blocker issues in {{repo}}:\n{{data.excerpt}}"
+        }
+        HookEvent::PrReviewReceived => {
+            "New review on {{data.owner}}/{{data.repo}}#{{data.number}} by {{data.author}}: {{data.body}}"
+        }
+    }
+}
+
+/// Render a message template, substituting `{{placeholder}}`s from the payload.
+///
+/// Unknown placeholders render as an empty string.
+#[must_use]
+pub fn render_template(template: &str, payload: &HookPayload) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+
+        let Some(end) = after_open.find("}}") else {
+            output.push_str(&rest[start..]);
+            return output;
+        };
+
+        output.push_str(&resolve_placeholder(after_open[..end].trim(), payload));
+        rest = &after_open[end + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn resolve_placeholder(key: &str, payload: &HookPayload) -> String {
+    match key {
+        "event" => payload.event.to_string(),
+        "repo" => payload
+            .repo_path
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        "repo_path" => payload
+            .repo_path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default(),
+        "timestamp" => payload.timestamp.to_rfc3339(),
+        _ => key.strip_prefix("data.").map_or_else(String::new, |path| {
+            let pointer = format!("/{}", path.replace('.', "/"));
+            match payload.data.pointer(&pointer) {
+                None | Some(serde_json::Value::Null) => String::new(),
+                Some(serde_json::Value::String(value)) => value.clone(),
+                Some(value) => value.to_string(),
+            }
+        }),
+    }
+}
+
+/// Send a payload to a webhook, blocking until the request completes.
+///
+/// # Errors
+///
+/// Returns an error if the request fails or the endpoint responds with a
+/// non-success status.
+pub fn send_webhook(webhook: &WebhookDefinition, payload: &HookPayload) -> Result<(), HookError> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(webhook.timeout_seconds))
+        .build()?;
+
+    let response = client
+        .post(&webhook.url)
+        .json(&serde_json::json!({ "text": webhook.message_text(payload) }))
+        .send()?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(HookError::WebhookStatus(status.as_u16()));
+    }
+
+    log::debug!("Sent {} webhook", payload.event);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn payload(event: HookEvent, data: serde_json::Value) -> HookPayload {
+        HookPayload::new(event, Some(PathBuf::from("/home/me/chadreview")), data)
+    }
+
+    fn webhook(templates: BTreeMap<HookEvent, String>) -> WebhookDefinition {
+        WebhookDefinition {
+            url: "http://localhost/hook".to_string(),
+            events: vec![],
+            templates,
+            timeout_seconds: 5,
+        }
+    }
+
+    #[test]
+    fn test_render_template() {
+        let payload = payload(
+            HookEvent::ThreadCreated,
+            serde_json::json!({ "body": "Looks off", "author": { "name": "sam" }, "line": 42 }),
+        );
+
+        assert_eq!(
+            render_template(
+                "[{{event}}] {{ repo }}: {{data.author.name}} on line {{data.line}}: {{data.body}}",
+                &payload
+            ),
+            "[thread-created] chadreview: sam on line 42: Looks off"
+        );
+    }
+
+    #[test]
+    fn test_render_template_missing_values() {
+        let payload = payload(HookEvent::ThreadCreated, serde_json::json!({}));
+
+        assert_eq!(
+            render_template("a{{data.nope}}b{{unknown}}c", &payload),
+            "abc"
+        );
+        assert_eq!(
+            render_template("unterminated {{event", &payload),
+            "unterminated {{event"
+        );
+    }
+
+    #[test]
+    fn test_message_text_uses_template_override() {
+        let payload = payload(
+            HookEvent::ReviewExported,
+            serde_json::json!({ "summary": "3 threads" }),
+        );
+
+        let default = webhook(BTreeMap::new());
+        assert_eq!(
+            default.message_text(&payload),
+            "Review exported for chadreview: 3 threads"
+        );
+
+        let custom = webhook(BTreeMap::from([(
+            HookEvent::ReviewExported,
+            "Done ({{data.summary}})".to_string(),
+        )]));
+        assert_eq!(custom.message_text(&payload), "Done (3 threads)");
+    }
+}
@@ -47,8 +47,22 @@ pub struct LocalComment {
     pub created_at: DateTime<Utc>,
     /// When the comment was last updated.
     pub updated_at: DateTime<Utc>,
+    /// When the comment's body was last edited, if it ever was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<DateTime<Utc>>,
+    /// Bodies the comment had before its edits, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<CommentRevision>,
     /// Type/location of the comment.
     pub comment_type: LocalCommentType,
+    /// The code a line comment was made on, recorded while its line was in
+    /// the diff.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<LineAnchor>,
+    /// The diff the thread was made on. Threads without one (made before
+    /// scopes were recorded, or imported) belong to every diff.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_scope: Option<DiffScope>,
     /// Nested replies to this comment.
     pub replies: Vec<Self>,
     /// State of this comment thread (open, resolved, or saved for later).
@@ -57,12 +71,71 @@ pub struct LocalComment {
     pub ai_action: Option<AiAction>,
     /// Current status of AI execution (if an AI action was specified).
     pub ai_status: Option<AiExecutionStatus>,
+    /// Changes the AI action replied to made to the working tree, as a
+    /// unified diff. Only set on AI replies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ai_changes: Option<String>,
     /// `OpenCode` session ID for continuing conversations.
     /// Only set on root threads (not replies).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub opencode_session_id: Option<String>,
 }
 
+/// A previous body of an edited comment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommentRevision {
+    /// The comment body (markdown) as it was.
+    pub body: String,
+    /// When this body was written.
+    pub written_at: DateTime<Utc>,
+}
+
+/// The diff excerpt a line comment was made on, kept so the comment still
+/// makes sense once the line is gone.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LineAnchor {
+    /// Header of the hunk the line was in (e.g. `@@ -10,7 +10,8 @@`).
+    pub hunk_header: String,
+    /// The commented line and the lines before it in its hunk, as unified
+    /// diff lines (prefixed with `+`, `-` or a space).
+    pub excerpt: Vec<String>,
+    /// When the excerpt was recorded.
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// The diff a thread was made on, telling which diffs it belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DiffScope {
+    /// Uncommitted changes.
+    WorkingTree {
+        /// The ref the changes were compared against.
+        against: String,
+    },
+    /// Changes made by a set of commits.
+    Commits {
+        /// Full SHAs of the commits.
+        shas: Vec<String>,
+    },
+}
+
+impl DiffScope {
+    /// Whether a thread made on this diff belongs to `other`: both are the
+    /// working tree against the same ref, or they share a commit.
+    #[must_use]
+    pub fn overlaps(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::WorkingTree { against }, Self::WorkingTree { against: other }) => {
+                against == other
+            }
+            (Self::Commits { shas }, Self::Commits { shas: other }) => {
+                shas.iter().any(|sha| other.contains(sha))
+            }
+            _ => false,
+        }
+    }
+}
+
 /// Type of comment indicating where it is attached.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -104,6 +177,37 @@ pub struct AiAction {
     pub custom_instructions: Option<String>,
 }
 
+/// The kind of description an AI agent can write for a diff.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DescriptionKind {
+    /// A commit message.
+    #[default]
+    Commit,
+    /// A pull request description.
+    Pr,
+}
+
+impl DescriptionKind {
+    /// The value the kind is serialized as.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Commit => "commit",
+            Self::Pr => "pr",
+        }
+    }
+
+    /// Human-readable name of the description.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Commit => "Commit message",
+            Self::Pr => "PR description",
+        }
+    }
+}
+
 /// Status of AI execution for a comment.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "status", rename_all = "snake_case")]
@@ -133,6 +237,16 @@ pub enum AiExecutionStatus {
         /// Error message.
         error: String,
     },
+    /// AI execution wasn't started because the repository's AI spend
+    /// reached its budget.
+    OverBudget {
+        /// When execution was blocked.
+        blocked_at: DateTime<Utc>,
+        /// Dollars spent in the repository so far.
+        spent: f64,
+        /// The budget, in dollars.
+        budget: f64,
+    },
 }
 
 /// A progress entry during AI execution (e.g., tool call).
@@ -201,6 +315,26 @@ impl Default for LocalUser {
     }
 }
 
+impl LocalUser {
+    /// Email of the author of the replies AI agents post.
+    pub const AI_EMAIL: &str = "ai@local";
+
+    /// The author of the replies posted by AI agent `agent`.
+    #[must_use]
+    pub fn ai(agent: &str) -> Self {
+        Self {
+            name: format!("AI ({agent})"),
+            email: Self::AI_EMAIL.to_string(),
+        }
+    }
+
+    /// Whether this is the author of an AI agent's replies.
+    #[must_use]
+    pub fn is_ai(&self) -> bool {
+        self.email == Self::AI_EMAIL
+    }
+}
+
 /// Index entry for efficient thread listing.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommentThreadIndex {
@@ -238,7 +372,7 @@ impl From<&AiExecutionStatus> for AiExecutionStatusSummary {
             AiExecutionStatus::Pending => Self::Pending,
             AiExecutionStatus::Running { .. } => Self::Running,
             AiExecutionStatus::Completed { .. } => Self::Completed,
-            AiExecutionStatus::Failed { .. } => Self::Failed,
+            AiExecutionStatus::Failed { .. } | AiExecutionStatus::OverBudget { .. } => Self::Failed,
         }
     }
 }
@@ -266,15 +400,27 @@ impl LocalComment {
             body,
             created_at: now,
             updated_at: now,
+            edited_at: None,
+            revisions: vec![],
             comment_type,
+            anchor: None,
+            diff_scope: None,
             replies: vec![],
             state: ThreadState::default(),
             ai_action: None,
             ai_status: None,
+            ai_changes: None,
             opencode_session_id: None,
         }
     }
 
+    /// Create a new comment scoped to the diff it was made on.
+    #[must_use]
+    pub fn with_diff_scope(mut self, scope: DiffScope) -> Self {
+        self.diff_scope = Some(scope);
+        self
+    }
+
     /// Create a new comment with an AI action.
     #[must_use]
     pub fn with_ai_action(mut self, action: AiAction) -> Self {
@@ -283,6 +429,31 @@ impl LocalComment {
         self
     }
 
+    /// Create a new AI reply with the changes its AI action made to the
+    /// working tree, as a unified diff.
+    #[must_use]
+    pub fn with_ai_changes(mut self, changes: String) -> Self {
+        self.ai_changes = Some(changes);
+        self
+    }
+
+    /// Replace the comment's body, keeping the current one in its
+    /// revisions. Returns `false` (and changes nothing) if the body is the
+    /// same.
+    pub fn edit(&mut self, body: String, at: DateTime<Utc>) -> bool {
+        if body == self.body {
+            return false;
+        }
+
+        self.revisions.push(CommentRevision {
+            body: std::mem::replace(&mut self.body, body),
+            written_at: self.edited_at.unwrap_or(self.created_at),
+        });
+        self.edited_at = Some(at);
+        self.updated_at = at;
+        true
+    }
+
     /// Count total replies recursively.
     #[must_use]
     pub fn count_replies(&self) -> usize {
@@ -338,3 +509,209 @@ pub struct ViewedRepliesIndex {
     #[serde(default)]
     pub replies: std::collections::HashMap<Uuid, DateTime<Utc>>,
 }
+
+// =============================================================================
+// Drafts
+// =============================================================================
+
+/// The text of a comment form that hasn't been submitted yet.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommentDraft {
+    /// The draft body text.
+    pub body: String,
+    /// When the draft was last saved.
+    pub saved_at: DateTime<Utc>,
+}
+
+/// Index of in-progress comment drafts.
+///
+/// This is stored separately from comments and keeps what was typed into
+/// comment forms until it's submitted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DraftsIndex {
+    /// Map of comment form location (e.g. a file and line) to its draft.
+    #[serde(default)]
+    pub drafts: std::collections::HashMap<String, CommentDraft>,
+}
+
+// =============================================================================
+// AI Usage
+// =============================================================================
+
+/// What one AI execution used, as recorded in the usage ledger.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageEntry {
+    /// The comment whose AI action ran, if it ran for a comment.
+    pub comment_id: Option<Uuid>,
+    /// When the execution finished.
+    pub recorded_at: DateTime<Utc>,
+    /// Model that was used.
+    pub model: String,
+    /// Token usage statistics.
+    pub tokens: TokenUsage,
+    /// Cost in dollars (if available).
+    pub cost: Option<f64>,
+}
+
+/// Ledger of the AI executions run in a repository.
+///
+/// This is stored separately from comments and adds up AI spend so it can
+/// be held to a budget.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageLedger {
+    /// Recorded executions, oldest first.
+    #[serde(default)]
+    pub entries: Vec<UsageEntry>,
+}
+
+impl UsageLedger {
+    /// Dollars spent across every recorded execution. Executions without a
+    /// known cost count as free.
+    #[must_use]
+    pub fn total_cost(&self) -> f64 {
+        self.entries.iter().filter_map(|entry| entry.cost).sum()
+    }
+
+    /// Tokens used across every recorded execution.
+    #[must_use]
+    pub fn total_tokens(&self) -> TokenUsage {
+        self.entries
+            .iter()
+            .fold(TokenUsage::default(), |total, entry| TokenUsage {
+                input: total.input + entry.tokens.input,
+                output: total.output + entry.tokens.output,
+            })
+    }
+}
+
+// =============================================================================
+// Review History
+// =============================================================================
+
+/// Most diffs kept in a repository's review history.
+pub const MAX_REVIEW_HISTORY: usize = 20;
+
+/// A diff reviewed in a repository, as of the last time it was viewed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewSession {
+    /// Query string of the diff spec, as used by `/local`.
+    pub spec: String,
+    /// Description of what was diffed.
+    pub description: String,
+    /// Paths of the files in the diff.
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Number of comment threads shown with the diff.
+    #[serde(default)]
+    pub threads: usize,
+    /// When the diff was last viewed.
+    pub viewed_at: DateTime<Utc>,
+}
+
+impl ReviewSession {
+    /// How many of the diff's files are among `viewed_paths`, out of how
+    /// many.
+    #[must_use]
+    pub fn progress(&self, viewed_paths: &std::collections::HashSet<String>) -> (usize, usize) {
+        let viewed = self
+            .files
+            .iter()
+            .filter(|path| viewed_paths.contains(*path))
+            .count();
+        (viewed, self.files.len())
+    }
+}
+
+/// The diffs reviewed recently in a repository.
+///
+/// This is stored separately from comments so an in-progress review can be
+/// picked up again.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewHistory {
+    /// Reviewed diffs, most recently viewed first.
+    #[serde(default)]
+    pub sessions: Vec<ReviewSession>,
+}
+
+impl ReviewHistory {
+    /// Record `session`, replacing an earlier view of the same diff and
+    /// forgetting the oldest diffs beyond [`MAX_REVIEW_HISTORY`].
+    pub fn record(&mut self, session: ReviewSession) {
+        self.sessions.retain(|existing| existing.spec != session.spec);
+        self.sessions.insert(0, session);
+        self.sessions.truncate(MAX_REVIEW_HISTORY);
+    }
+}
+
+// =============================================================================
+// Thread Filters
+// =============================================================================
+
+/// What to look for in comment threads. Criteria that aren't set match
+/// every thread; text criteria ignore case.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreadFilter {
+    /// Only threads in this state.
+    pub state: Option<ThreadState>,
+    /// Only threads on files whose path contains this.
+    pub path: Option<String>,
+    /// Only threads someone whose name contains this took part in.
+    pub author: Option<String>,
+    /// Only threads with (or without) an AI action.
+    pub has_ai_action: Option<bool>,
+    /// Only threads with this text in a comment.
+    pub query: Option<String>,
+}
+
+impl ThreadFilter {
+    /// Whether no criteria are set.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether a thread's index entry matches the criteria that don't need
+    /// the thread's comments.
+    #[must_use]
+    pub fn matches_index(&self, entry: &CommentThreadIndex) -> bool {
+        self.state.is_none_or(|state| entry.state == state)
+            && self
+                .has_ai_action
+                .is_none_or(|has_ai_action| entry.has_ai_action == has_ai_action)
+            && self.path.as_deref().is_none_or(|path| {
+                thread_path(&entry.comment_type).is_some_and(|p| contains_ignore_case(p, path))
+            })
+    }
+
+    /// Whether a thread matches every criterion.
+    #[must_use]
+    pub fn matches(&self, thread: &LocalComment) -> bool {
+        self.matches_index(&thread.to_index_entry())
+            && self.author.as_deref().is_none_or(|author| {
+                any_comment(thread, &|c| contains_ignore_case(&c.author.name, author))
+            })
+            && self
+                .query
+                .as_deref()
+                .is_none_or(|query| any_comment(thread, &|c| contains_ignore_case(&c.body, query)))
+    }
+}
+
+/// The file a thread is on, if any.
+fn thread_path(comment_type: &LocalCommentType) -> Option<&str> {
+    match comment_type {
+        LocalCommentType::FileLevelComment { path }
+        | LocalCommentType::LineLevelComment { path, .. } => Some(path),
+        LocalCommentType::General | LocalCommentType::Reply { .. } => None,
+    }
+}
+
+/// Whether `comment` or any of its replies, at any depth, satisfies
+/// `predicate`.
+fn any_comment(comment: &LocalComment, predicate: &dyn Fn(&LocalComment) -> bool) -> bool {
+    predicate(comment) || comment.replies.iter().any(|r| any_comment(r, predicate))
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
@@ -0,0 +1,210 @@
+//! Anchoring of line comments to the code they were made on.
+//!
+//! A line thread only points at a path and line number, which stop meaning
+//! anything once the code changes. While the line is still in the diff, the
+//! thread records a [`LineAnchor`] with the hunk excerpt leading up to it;
+//! once the line is gone the thread is outdated, and the excerpt shows what
+//! it was about until it's re-attached to another line.
+
+use chadreview_local_comment_models::{LineAnchor, LineNumber, LocalComment, LocalCommentType};
+use chadreview_pr_models::{DiffFile, DiffHunk, DiffLine, LineType};
+use chrono::Utc;
+
+use crate::{LocalCommentStore, LocalCommentStoreError};
+
+/// Number of diff lines kept above a commented line.
+const EXCERPT_CONTEXT_LINES: usize = 3;
+
+/// The anchor of `line` in `path`, if the line is part of `diffs`.
+#[must_use]
+pub fn line_anchor(diffs: &[DiffFile], path: &str, line: LineNumber) -> Option<LineAnchor> {
+    let (hunk, index) = find_line(diffs, path, line)?;
+
+    Some(LineAnchor {
+        hunk_header: format!(
+            "@@ -{},{} +{},{} @@",
+            hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+        ),
+        excerpt: excerpt(hunk, index),
+        recorded_at: Utc::now(),
+    })
+}
+
+/// Whether `thread` is a line thread whose line isn't part of `diffs`.
+#[must_use]
+pub fn is_outdated(thread: &LocalComment, diffs: &[DiffFile]) -> bool {
+    match &thread.comment_type {
+        LocalCommentType::LineLevelComment { path, line } => {
+            find_line(diffs, path, *line).is_none()
+        }
+        _ => false,
+    }
+}
+
+/// Record the anchors of line threads that don't have one yet and whose
+/// line is part of `diffs`, in the store and in `threads`. Returns how many
+/// were recorded.
+///
+/// # Errors
+///
+/// Returns an error if a thread can't be updated in the store.
+pub fn record_anchors<'a>(
+    store: &LocalCommentStore,
+    threads: impl IntoIterator<Item = &'a mut LocalComment>,
+    diffs: &[DiffFile],
+) -> Result<usize, LocalCommentStoreError> {
+    let mut recorded = 0;
+
+    for thread in threads.into_iter().filter(|thread| thread.anchor.is_none()) {
+        let LocalCommentType::LineLevelComment { path, line } = &thread.comment_type else {
+            continue;
+        };
+        let Some(anchor) = line_anchor(diffs, path, *line) else {
+            continue;
+        };
+        if store.record_anchor(thread.id, &anchor)? {
+            recorded += 1;
+        }
+        thread.anchor = Some(anchor);
+    }
+
+    Ok(recorded)
+}
+
+/// The hunk containing `line` in `path`, and the line's index in it.
+pub fn find_line<'a>(
+    diffs: &'a [DiffFile],
+    path: &str,
+    line: LineNumber,
+) -> Option<(&'a DiffHunk, usize)> {
+    let matches_line = |diff_line: &DiffLine| match line {
+        LineNumber::New { line } => diff_line.new_line_number == Some(line),
+        LineNumber::Old { line } => diff_line.old_line_number == Some(line),
+    };
+
+    let file = diffs.iter().find(|file| file.filename == path)?;
+    file.hunks.iter().find_map(|hunk| {
+        hunk.lines
+            .iter()
+            .position(matches_line)
+            .map(|index| (hunk, index))
+    })
+}
+
+/// The lines of `hunk` leading up to and including the one at `index`, with
+/// their `+`/`-` markers.
+pub fn excerpt(hunk: &DiffHunk, index: usize) -> Vec<String> {
+    hunk.lines[index.saturating_sub(EXCERPT_CONTEXT_LINES)..=index]
+        .iter()
+        .map(|line| {
+            let marker = match line.line_type {
+                LineType::Addition => '+',
+                LineType::Deletion => '-',
+                LineType::Context => ' ',
+            };
+            format!("{marker}{}", line.content.trim_end_matches(['\r', '\n']))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chadreview_local_comment_models::LocalUser;
+    use chadreview_pr_models::FileStatus;
+
+    fn diff_line(line_type: LineType, old: Option<u64>, new: Option<u64>) -> DiffLine {
+        DiffLine {
+            line_type,
+            old_line_number: old,
+            new_line_number: new,
+            content: format!("line {old:?} {new:?}\n"),
+            highlighted_html: String::new(),
+        }
+    }
+
+    fn diffs() -> Vec<DiffFile> {
+        vec![DiffFile {
+            filename: "src/lib.rs".to_string(),
+            status: FileStatus::Modified,
+            additions: 2,
+            deletions: 1,
+            mode_change: None,
+            hunks: vec![DiffHunk {
+                old_start: 10,
+                old_lines: 5,
+                new_start: 10,
+                new_lines: 6,
+                lines: vec![
+                    diff_line(LineType::Context, Some(10), Some(10)),
+                    diff_line(LineType::Context, Some(11), Some(11)),
+                    diff_line(LineType::Deletion, Some(12), None),
+                    diff_line(LineType::Addition, None, Some(12)),
+                    diff_line(LineType::Addition, None, Some(13)),
+                    diff_line(LineType::Context, Some(13), Some(14)),
+                ],
+            }],
+            decoded_lossy: false,
+            submodule: None,
+        }]
+    }
+
+    fn line_thread(path: &str, line: LineNumber) -> LocalComment {
+        LocalComment::new(
+            LocalUser::default(),
+            "comment".to_string(),
+            LocalCommentType::LineLevelComment {
+                path: path.to_string(),
+                line,
+            },
+        )
+    }
+
+    #[test]
+    fn test_line_anchor_keeps_hunk_header_and_preceding_lines() {
+        let anchor = line_anchor(&diffs(), "src/lib.rs", LineNumber::New { line: 13 }).unwrap();
+
+        assert_eq!(anchor.hunk_header, "@@ -10,5 +10,6 @@");
+        assert_eq!(
+            anchor.excerpt,
+            vec![
+                " line Some(11) Some(11)",
+                "-line Some(12) None",
+                "+line None Some(12)",
+                "+line None Some(13)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_anchor_at_start_of_hunk() {
+        let anchor = line_anchor(&diffs(), "src/lib.rs", LineNumber::Old { line: 10 }).unwrap();
+
+        assert_eq!(anchor.excerpt, vec![" line Some(10) Some(10)"]);
+    }
+
+    #[test]
+    fn test_is_outdated() {
+        let diffs = diffs();
+
+        assert!(!is_outdated(
+            &line_thread("src/lib.rs", LineNumber::Old { line: 12 }),
+            &diffs
+        ));
+        assert!(is_outdated(
+            &line_thread("src/lib.rs", LineNumber::New { line: 40 }),
+            &diffs
+        ));
+        assert!(is_outdated(
+            &line_thread("src/main.rs", LineNumber::New { line: 12 }),
+            &diffs
+        ));
+
+        let general = LocalComment::new(
+            LocalUser::default(),
+            "general".to_string(),
+            LocalCommentType::General,
+        );
+        assert!(!is_outdated(&general, &diffs));
+    }
+}
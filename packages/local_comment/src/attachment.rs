@@ -0,0 +1,169 @@
+//! Image attachments of local comments.
+//!
+//! Images uploaded or pasted into a comment form are stored in the
+//! repository's storage directory under `attachments/`, named by a new ID
+//! and their type's extension, whichever backend holds the threads. Comment
+//! bodies reference them as markdown images.
+
+use std::fs;
+use std::path::PathBuf;
+
+use switchy::uuid::Uuid;
+
+use crate::{CommentStore, LocalCommentStoreError};
+
+/// Largest attachment accepted, in bytes.
+pub const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+
+/// Directory holding attachments, relative to the storage directory.
+const ATTACHMENTS_DIR: &str = "attachments";
+
+/// Accepted content types and the extension files of each are stored with.
+///
+/// SVG is left out since it can carry scripts.
+const CONTENT_TYPES: &[(&str, &str)] = &[
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+];
+
+/// A stored attachment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    pub id: Uuid,
+    pub content_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+/// Store `data` of type `content_type` as a new attachment and return its
+/// ID.
+///
+/// # Errors
+///
+/// Returns an error if the content type isn't an accepted image type, the
+/// data is empty or larger than [`MAX_ATTACHMENT_BYTES`], or the file can't
+/// be written.
+pub fn save_attachment(
+    store: &dyn CommentStore,
+    content_type: &str,
+    data: &[u8],
+) -> Result<Uuid, LocalCommentStoreError> {
+    let Some(extension) = extension_for(content_type) else {
+        return Err(LocalCommentStoreError::InvalidAttachment(format!(
+            "unsupported type {content_type}"
+        )));
+    };
+    if data.is_empty() {
+        return Err(LocalCommentStoreError::InvalidAttachment(
+            "empty file".to_string(),
+        ));
+    }
+    if data.len() > MAX_ATTACHMENT_BYTES {
+        return Err(LocalCommentStoreError::InvalidAttachment(format!(
+            "larger than {} MiB",
+            MAX_ATTACHMENT_BYTES / 1024 / 1024
+        )));
+    }
+
+    let dir = store.storage_path().join(ATTACHMENTS_DIR);
+    fs::create_dir_all(&dir).map_err(LocalCommentStoreError::CreateDir)?;
+
+    let id = Uuid::new_v4();
+    fs::write(dir.join(format!("{id}.{extension}")), data)
+        .map_err(LocalCommentStoreError::Write)?;
+
+    Ok(id)
+}
+
+/// Load the attachment `id`.
+///
+/// # Errors
+///
+/// Returns an error if there is no such attachment or it can't be read.
+pub fn load_attachment(
+    store: &dyn CommentStore,
+    id: Uuid,
+) -> Result<Attachment, LocalCommentStoreError> {
+    let (path, content_type) = CONTENT_TYPES
+        .iter()
+        .map(|(content_type, extension)| (attachment_path(store, id, extension), *content_type))
+        .find(|(path, _)| path.exists())
+        .ok_or(LocalCommentStoreError::NotFound(id))?;
+
+    Ok(Attachment {
+        id,
+        content_type,
+        data: fs::read(path).map_err(LocalCommentStoreError::Read)?,
+    })
+}
+
+/// The extension attachments of `content_type` are stored with, if it's
+/// accepted.
+fn extension_for(content_type: &str) -> Option<&'static str> {
+    let content_type = content_type.split(';').next().unwrap_or_default().trim();
+    CONTENT_TYPES
+        .iter()
+        .find(|(accepted, _)| accepted.eq_ignore_ascii_case(content_type))
+        .map(|(_, extension)| *extension)
+}
+
+fn attachment_path(store: &dyn CommentStore, id: Uuid, extension: &str) -> PathBuf {
+    store
+        .storage_path()
+        .join(ATTACHMENTS_DIR)
+        .join(format!("{id}.{extension}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonCommentStore;
+    use std::env;
+
+    fn temp_store() -> JsonCommentStore {
+        let temp_dir = env::temp_dir().join(format!("chadreview-test-{}", Uuid::new_v4()));
+        JsonCommentStore::new(temp_dir)
+    }
+
+    #[test]
+    fn test_save_and_load_attachment() {
+        let store = temp_store();
+        let data = b"\x89PNG\r\n\x1a\nimage".to_vec();
+
+        let id = save_attachment(&store, "image/png", &data).unwrap();
+        let attachment = load_attachment(&store, id).unwrap();
+
+        assert_eq!(attachment.content_type, "image/png");
+        assert_eq!(attachment.data, data);
+        assert!(matches!(
+            load_attachment(&store, Uuid::new_v4()),
+            Err(LocalCommentStoreError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_invalid_attachments() {
+        let store = temp_store();
+
+        assert!(matches!(
+            save_attachment(&store, "image/svg+xml", b"<svg/>"),
+            Err(LocalCommentStoreError::InvalidAttachment(_))
+        ));
+        assert!(matches!(
+            save_attachment(&store, "image/png", b""),
+            Err(LocalCommentStoreError::InvalidAttachment(_))
+        ));
+        assert!(matches!(
+            save_attachment(&store, "image/png", &vec![0; MAX_ATTACHMENT_BYTES + 1]),
+            Err(LocalCommentStoreError::InvalidAttachment(_))
+        ));
+    }
+
+    #[test]
+    fn test_extension_ignores_case_and_parameters() {
+        assert_eq!(extension_for("IMAGE/JPEG"), Some("jpg"));
+        assert_eq!(extension_for("image/webp; charset=binary"), Some("webp"));
+        assert_eq!(extension_for("text/html"), None);
+    }
+}
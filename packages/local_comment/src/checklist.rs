@@ -0,0 +1,104 @@
+//! Checked review checklist items of the diffs reviewed in a repository.
+//!
+//! Items are recorded by their text against the diff spec they were checked
+//! on, in the repository's storage directory (`checklists.json`), so each
+//! diff has its own progress through the checklist.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+
+use crate::{CommentStore, LocalCommentStoreError};
+
+/// File holding the checked items, relative to the storage directory.
+const CHECKLISTS_FILE: &str = "checklists.json";
+
+/// Checked items, by diff spec.
+type Checklists = BTreeMap<String, BTreeSet<String>>;
+
+fn load_checklists(store: &dyn CommentStore) -> Result<Checklists, LocalCommentStoreError> {
+    match fs::read_to_string(store.storage_path().join(CHECKLISTS_FILE)) {
+        Ok(json) => serde_json::from_str(&json).map_err(LocalCommentStoreError::Parse),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Checklists::new()),
+        Err(e) => Err(LocalCommentStoreError::Read(e)),
+    }
+}
+
+/// Load the checklist items checked on the diff of `spec`.
+///
+/// # Errors
+///
+/// Returns an error if the checked items exist but can't be read or parsed.
+pub fn load_checked_items(
+    store: &dyn CommentStore,
+    spec: &str,
+) -> Result<HashSet<String>, LocalCommentStoreError> {
+    Ok(load_checklists(store)?
+        .remove(spec)
+        .unwrap_or_default()
+        .into_iter()
+        .collect())
+}
+
+/// Check or uncheck a checklist item on the diff of `spec`.
+///
+/// # Errors
+///
+/// Returns an error if the checked items can't be read or written.
+pub fn set_checklist_item(
+    store: &dyn CommentStore,
+    spec: &str,
+    item: &str,
+    checked: bool,
+) -> Result<(), LocalCommentStoreError> {
+    let mut checklists = load_checklists(store)?;
+    if checked {
+        checklists
+            .entry(spec.to_string())
+            .or_default()
+            .insert(item.to_string());
+    } else if let Some(items) = checklists.get_mut(spec) {
+        items.remove(item);
+        if items.is_empty() {
+            checklists.remove(spec);
+        }
+    }
+
+    fs::create_dir_all(store.storage_path()).map_err(LocalCommentStoreError::CreateDir)?;
+    let json =
+        serde_json::to_string_pretty(&checklists).map_err(LocalCommentStoreError::Serialize)?;
+    fs::write(store.storage_path().join(CHECKLISTS_FILE), json)
+        .map_err(LocalCommentStoreError::Write)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use switchy::uuid::Uuid;
+
+    use super::*;
+    use crate::JsonCommentStore;
+
+    fn temp_store() -> JsonCommentStore {
+        let temp_dir = env::temp_dir().join(format!("chadreview-test-{}", Uuid::new_v4()));
+        JsonCommentStore::new(temp_dir)
+    }
+
+    #[test]
+    fn test_items_are_checked_per_diff() {
+        let store = temp_store();
+
+        set_checklist_item(&store, "commit=abc", "Tests added", true).unwrap();
+        set_checklist_item(&store, "commit=abc", "Docs updated", true).unwrap();
+        set_checklist_item(&store, "commit=def", "Tests added", true).unwrap();
+        set_checklist_item(&store, "commit=abc", "Docs updated", false).unwrap();
+
+        assert_eq!(
+            load_checked_items(&store, "commit=abc").unwrap(),
+            HashSet::from(["Tests added".to_string()])
+        );
+        assert_eq!(load_checked_items(&store, "commit=def").unwrap().len(), 1);
+        assert!(load_checked_items(&store, "commit=ghi").unwrap().is_empty());
+    }
+}
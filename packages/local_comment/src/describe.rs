@@ -0,0 +1,64 @@
+//! AI-written descriptions of a diff.
+//!
+//! A describing agent is handed the whole diff, formatted as for a review,
+//! along with the [`description_request`] of the kind of description wanted,
+//! and answers with a commit message or PR description ready to be copied.
+
+use chadreview_local_comment_models::DescriptionKind;
+
+/// Instructions given to the describing agent along with the diff.
+#[must_use]
+pub const fn description_request(kind: DescriptionKind) -> &'static str {
+    match kind {
+        DescriptionKind::Commit => {
+            "\
+Write a commit message for this diff: a summary line of at most 72 \
+characters in the imperative mood, a blank line, then a body wrapped at 72 \
+characters explaining what changed and why. Answer with the commit message \
+only."
+        }
+        DescriptionKind::Pr => {
+            "\
+Write a pull request description for this diff in markdown: a short \
+overview of what it does and why, the notable changes as a list, and \
+anything reviewers should look at closely. Answer with the description \
+only."
+        }
+    }
+}
+
+/// The description in a describing agent's response, without the code
+/// fence it may be wrapped in.
+#[must_use]
+pub fn parse_description(response: &str) -> String {
+    let response = response.trim();
+
+    response
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .and_then(|fenced| fenced.split_once('\n'))
+        .map_or(response, |(_language, description)| description)
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_description_strips_fence() {
+        assert_eq!(
+            parse_description("```markdown\nAdd caching\n\nCaches summaries.\n```\n"),
+            "Add caching\n\nCaches summaries."
+        );
+        assert_eq!(parse_description("```\nFix crash\n```"), "Fix crash");
+    }
+
+    #[test]
+    fn test_parse_description_keeps_inner_code_blocks() {
+        let response = "Add `foo`\n\n```rust\nfoo();\n```";
+
+        assert_eq!(parse_description(response), response);
+    }
+}
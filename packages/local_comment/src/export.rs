@@ -0,0 +1,314 @@
+//! Markdown export of a local review.
+//!
+//! Compiles every comment thread into one document, grouped by file and
+//! line, with the code each line comment refers to and the state of each
+//! thread, so a review can be pasted into a PR or shared as a file.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use chadreview_local_comment_models::{LineNumber, LocalComment, LocalCommentType, ThreadState};
+use chadreview_pr_models::DiffFile;
+
+use crate::anchor;
+
+/// Compile comment threads into a markdown review report.
+///
+/// General threads come first, followed by one section per file with its
+/// file-level threads and then its line threads in line order. Line threads
+/// include a snippet of `diffs` leading up to the commented line, or the
+/// excerpt recorded in their anchor once the line is no longer in the diff.
+#[must_use]
+pub fn export_markdown(title: &str, threads: &[LocalComment], diffs: &[DiffFile]) -> String {
+    let mut general = vec![];
+    let mut files: BTreeMap<&str, Vec<(Option<LineNumber>, &LocalComment)>> = BTreeMap::new();
+
+    for thread in threads {
+        match &thread.comment_type {
+            LocalCommentType::General => general.push(thread),
+            LocalCommentType::FileLevelComment { path } => {
+                files.entry(path).or_default().push((None, thread));
+            }
+            LocalCommentType::LineLevelComment { path, line } => {
+                files.entry(path).or_default().push((Some(*line), thread));
+            }
+            LocalCommentType::Reply { .. } => {}
+        }
+    }
+
+    let mut markdown = format!("# Review: {title}\n\n{}\n", export_summary(threads));
+
+    if !general.is_empty() {
+        general.sort_by_key(|thread| thread.created_at);
+        markdown.push_str("\n## General comments\n");
+        for thread in general {
+            write_thread(&mut markdown, "Comment", thread, None);
+        }
+    }
+
+    for (path, mut threads) in files {
+        threads.sort_by_key(|(line, thread)| {
+            (
+                line.map(|line| (line.number(), line.is_new())),
+                thread.created_at,
+            )
+        });
+        write!(markdown, "\n## `{path}`\n").unwrap();
+        for (line, thread) in threads {
+            let heading = match line {
+                None => "File comment".to_string(),
+                Some(LineNumber::New { line }) => format!("Line {line}"),
+                Some(LineNumber::Old { line }) => format!("Old line {line}"),
+            };
+            let snippet = line
+                .and_then(|line| code_snippet(diffs, path, line))
+                .or_else(|| thread.anchor.as_ref().map(|a| a.excerpt.join("\n")));
+            write_thread(&mut markdown, &heading, thread, snippet.as_deref());
+        }
+    }
+
+    markdown
+}
+
+/// One-line summary of how many threads there are in each state, e.g.
+/// "3 threads (2 open, 1 resolved)".
+#[must_use]
+pub fn export_summary(threads: &[LocalComment]) -> String {
+    let threads: Vec<_> = threads
+        .iter()
+        .filter(|thread| !matches!(thread.comment_type, LocalCommentType::Reply { .. }))
+        .collect();
+    if threads.is_empty() {
+        return "No comment threads.".to_string();
+    }
+
+    let count = |state: ThreadState| threads.iter().filter(|t| t.state == state).count();
+    let states = [
+        (count(ThreadState::Open), "open"),
+        (count(ThreadState::Resolved), "resolved"),
+        (count(ThreadState::SavedForLater), "saved for later"),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, label)| format!("{count} {label}"))
+    .collect::<Vec<_>>()
+    .join(", ");
+    let noun = if threads.len() == 1 {
+        "thread"
+    } else {
+        "threads"
+    };
+
+    format!("{} {noun} ({states})", threads.len())
+}
+
+fn write_thread(
+    markdown: &mut String,
+    heading: &str,
+    thread: &LocalComment,
+    snippet: Option<&str>,
+) {
+    let state = match thread.state {
+        ThreadState::Open => "Open",
+        ThreadState::Resolved => "Resolved",
+        ThreadState::SavedForLater => "Saved for later",
+    };
+    write!(markdown, "\n### {heading} · {state}\n\n").unwrap();
+
+    if let Some(snippet) = snippet {
+        let fence = code_fence(snippet);
+        write!(markdown, "{fence}diff\n{snippet}\n{fence}\n\n").unwrap();
+    }
+
+    writeln!(markdown, "{}", comment_byline(thread)).unwrap();
+    writeln!(markdown, "\n{}", thread.body.trim_end()).unwrap();
+
+    let mut replies = vec![];
+    collect_replies(thread, &mut replies);
+    for reply in replies {
+        writeln!(markdown, "\n> {}", comment_byline(reply)).unwrap();
+        markdown.push_str(">\n");
+        for line in reply.body.trim_end().lines() {
+            if line.is_empty() {
+                markdown.push_str(">\n");
+            } else {
+                writeln!(markdown, "> {line}").unwrap();
+            }
+        }
+    }
+}
+
+fn comment_byline(comment: &LocalComment) -> String {
+    format!(
+        "**{}** ({})",
+        comment.author.name,
+        comment.created_at.format("%Y-%m-%d %H:%M UTC")
+    )
+}
+
+/// Replies of a thread in the order they were written, nested ones included.
+fn collect_replies<'a>(comment: &'a LocalComment, replies: &mut Vec<&'a LocalComment>) {
+    for reply in &comment.replies {
+        replies.push(reply);
+        collect_replies(reply, replies);
+    }
+}
+
+/// The diff lines leading up to and including `line` in `path`, with their
+/// `+`/`-` markers.
+fn code_snippet(diffs: &[DiffFile], path: &str, line: LineNumber) -> Option<String> {
+    let (hunk, index) = anchor::find_line(diffs, path, line)?;
+    Some(anchor::excerpt(hunk, index).join("\n"))
+}
+
+/// A backtick fence longer than any backtick run in `code`.
+fn code_fence(code: &str) -> String {
+    let longest = code
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+
+    "`".repeat(longest.max(2) + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chadreview_local_comment_models::LocalUser;
+    use chadreview_pr_models::{DiffHunk, DiffLine, FileStatus, LineType};
+
+    fn diff_line(
+        line_type: LineType,
+        old: Option<u64>,
+        new: Option<u64>,
+        content: &str,
+    ) -> DiffLine {
+        DiffLine {
+            line_type,
+            old_line_number: old,
+            new_line_number: new,
+            content: content.to_string(),
+            highlighted_html: String::new(),
+        }
+    }
+
+    fn diffs() -> Vec<DiffFile> {
+        vec![DiffFile {
+            filename: "src/main.rs".to_string(),
+            status: FileStatus::Modified,
+            additions: 1,
+            deletions: 1,
+            mode_change: None,
+            hunks: vec![DiffHunk {
+                old_start: 1,
+                old_lines: 4,
+                new_start: 1,
+                new_lines: 4,
+                lines: vec![
+                    diff_line(LineType::Context, Some(1), Some(1), "fn main() {"),
+                    diff_line(LineType::Context, Some(2), Some(2), "    let a = 1;"),
+                    diff_line(LineType::Context, Some(3), Some(3), "    let b = 2;"),
+                    diff_line(LineType::Deletion, Some(4), None, "    old();"),
+                    diff_line(LineType::Addition, None, Some(4), "    new();"),
+                    diff_line(LineType::Context, Some(5), Some(5), "}"),
+                ],
+            }],
+            decoded_lossy: false,
+            submodule: None,
+        }]
+    }
+
+    fn comment(body: &str, comment_type: LocalCommentType) -> LocalComment {
+        LocalComment::new(
+            LocalUser {
+                name: "Alice".to_string(),
+                email: "alice@example.com".to_string(),
+            },
+            body.to_string(),
+            comment_type,
+        )
+    }
+
+    #[test]
+    fn test_code_snippet_includes_context_above_line() {
+        let snippet = code_snippet(&diffs(), "src/main.rs", LineNumber::New { line: 4 }).unwrap();
+        assert_eq!(
+            snippet,
+            "     let a = 1;\n     let b = 2;\n-    old();\n+    new();"
+        );
+
+        let snippet = code_snippet(&diffs(), "src/main.rs", LineNumber::Old { line: 1 }).unwrap();
+        assert_eq!(snippet, " fn main() {");
+
+        assert!(code_snippet(&diffs(), "src/main.rs", LineNumber::New { line: 40 }).is_none());
+        assert!(code_snippet(&diffs(), "src/lib.rs", LineNumber::New { line: 1 }).is_none());
+    }
+
+    #[test]
+    fn test_export_summary_counts_states() {
+        let mut resolved = comment("b", LocalCommentType::General);
+        resolved.state = ThreadState::Resolved;
+        let threads = vec![
+            comment("a", LocalCommentType::General),
+            resolved,
+            comment("c", LocalCommentType::General),
+        ];
+
+        assert_eq!(export_summary(&threads), "3 threads (2 open, 1 resolved)");
+        assert_eq!(export_summary(&threads[..1]), "1 thread (1 open)");
+        assert_eq!(export_summary(&[]), "No comment threads.");
+    }
+
+    #[test]
+    fn test_export_markdown_groups_by_file_and_line() {
+        let mut line_thread = comment(
+            "Why call `new` here?",
+            LocalCommentType::LineLevelComment {
+                path: "src/main.rs".to_string(),
+                line: LineNumber::New { line: 4 },
+            },
+        );
+        let mut reply = comment("Because.\n\nIt's faster.", LocalCommentType::General);
+        reply.author.name = "Bob".to_string();
+        line_thread.replies.push(reply);
+        line_thread.state = ThreadState::Resolved;
+
+        let threads = vec![
+            line_thread,
+            comment(
+                "Needs docs",
+                LocalCommentType::FileLevelComment {
+                    path: "src/main.rs".to_string(),
+                },
+            ),
+            comment("Looks good overall", LocalCommentType::General),
+        ];
+
+        let markdown = export_markdown("main..feature", &threads, &diffs());
+
+        assert!(
+            markdown.starts_with("# Review: main..feature\n\n3 threads (2 open, 1 resolved)\n")
+        );
+        let general = markdown.find("## General comments").unwrap();
+        let file = markdown.find("## `src/main.rs`").unwrap();
+        let file_comment = markdown.find("### File comment · Open").unwrap();
+        let line_comment = markdown.find("### Line 4 · Resolved").unwrap();
+        assert!(general < file && file < file_comment && file_comment < line_comment);
+
+        assert!(
+            markdown.contains(
+                "```diff\n     let a = 1;\n     let b = 2;\n-    old();\n+    new();\n```"
+            )
+        );
+        assert!(markdown.contains("Why call `new` here?"));
+        assert!(markdown.contains("> **Bob** ("));
+        assert!(markdown.contains(">\n> Because.\n>\n> It's faster.\n"));
+    }
+
+    #[test]
+    fn test_code_fence_outlasts_backticks_in_code() {
+        assert_eq!(code_fence("let a = 1;"), "```");
+        assert_eq!(code_fence("// ```rust"), "````");
+    }
+}
@@ -0,0 +1,145 @@
+//! Filtering of comment threads.
+//!
+//! Long review sessions collect dozens of threads. [`filter_threads`] narrows
+//! them down by state, file, author, AI action and text, checking what it
+//! can against the thread index before loading any thread.
+
+use chadreview_local_comment_models::{LocalComment, ThreadFilter};
+
+use crate::{LocalCommentStore, LocalCommentStoreError};
+
+/// Find the threads in `store` matching `filter`, most recently updated
+/// first. Threads that can't be read are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the thread index can't be read.
+pub fn filter_threads(
+    store: &LocalCommentStore,
+    filter: &ThreadFilter,
+) -> Result<Vec<LocalComment>, LocalCommentStoreError> {
+    let mut threads: Vec<_> = store
+        .list_threads()?
+        .iter()
+        .filter(|entry| filter.matches_index(entry))
+        .filter_map(|entry| store.load_thread(entry.id).ok())
+        .filter(|thread| filter.matches(thread))
+        .collect();
+    threads.sort_by_key(|thread| std::cmp::Reverse(thread.updated_at));
+
+    Ok(threads)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chadreview_local_comment_models::{
+        AiAction, LineNumber, LocalCommentType, LocalUser, ThreadState,
+    };
+
+    fn user(name: &str) -> LocalUser {
+        LocalUser {
+            name: name.to_string(),
+            email: String::new(),
+        }
+    }
+
+    fn thread() -> LocalComment {
+        let mut thread = LocalComment::new(
+            user("Alice"),
+            "This allocates on every call".to_string(),
+            LocalCommentType::LineLevelComment {
+                path: "src/parser/mod.rs".to_string(),
+                line: LineNumber::New { line: 12 },
+            },
+        );
+        let mut reply =
+            LocalComment::new(user("Bob"), "Agreed".to_string(), LocalCommentType::General);
+        reply.replies.push(LocalComment::new(
+            user("Carol"),
+            "Fixed with a buffer".to_string(),
+            LocalCommentType::General,
+        ));
+        thread.replies.push(reply);
+        thread
+    }
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        assert!(ThreadFilter::default().is_empty());
+        assert!(ThreadFilter::default().matches(&thread()));
+    }
+
+    #[test]
+    fn test_filter_by_state_and_ai_action() {
+        let mut thread = thread();
+
+        let open = ThreadFilter {
+            state: Some(ThreadState::Open),
+            ..ThreadFilter::default()
+        };
+        assert!(open.matches(&thread));
+        thread.state = ThreadState::Resolved;
+        assert!(!open.matches(&thread));
+
+        let with_ai = ThreadFilter {
+            has_ai_action: Some(true),
+            ..ThreadFilter::default()
+        };
+        assert!(!with_ai.matches(&thread));
+        let thread = thread.with_ai_action(AiAction {
+            provider: "opencode".to_string(),
+            agent: "plan".to_string(),
+            model: None,
+            custom_instructions: None,
+        });
+        assert!(with_ai.matches(&thread));
+    }
+
+    #[test]
+    fn test_filter_by_path_author_and_query_ignores_case() {
+        let thread = thread();
+        let filter = |filter: ThreadFilter| filter.matches(&thread);
+
+        assert!(filter(ThreadFilter {
+            path: Some("PARSER/".to_string()),
+            ..ThreadFilter::default()
+        }));
+        assert!(!filter(ThreadFilter {
+            path: Some("lexer".to_string()),
+            ..ThreadFilter::default()
+        }));
+        assert!(filter(ThreadFilter {
+            author: Some("carol".to_string()),
+            ..ThreadFilter::default()
+        }));
+        assert!(!filter(ThreadFilter {
+            author: Some("dave".to_string()),
+            ..ThreadFilter::default()
+        }));
+        assert!(filter(ThreadFilter {
+            query: Some("BUFFER".to_string()),
+            author: Some("alice".to_string()),
+            ..ThreadFilter::default()
+        }));
+        assert!(!filter(ThreadFilter {
+            query: Some("unsafe".to_string()),
+            ..ThreadFilter::default()
+        }));
+    }
+
+    #[test]
+    fn test_general_threads_have_no_path() {
+        let general = LocalComment::new(
+            user("Alice"),
+            "Looks good".to_string(),
+            LocalCommentType::General,
+        );
+        let filter = ThreadFilter {
+            path: Some(String::new()),
+            ..ThreadFilter::default()
+        };
+
+        assert!(!filter.matches(&general));
+    }
+}
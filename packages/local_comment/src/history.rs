@@ -0,0 +1,113 @@
+//! History of the diffs reviewed in a repository.
+//!
+//! Viewing a diff records it as a [`ReviewSession`] in the repository's
+//! storage directory (`history.json`), so it can be resumed later with the
+//! same diff spec. Which of its files have been viewed is looked up when the
+//! history is shown, so the progress of a review stays current.
+
+use std::fs;
+use std::io::ErrorKind;
+
+use chadreview_local_comment_models::{ReviewHistory, ReviewSession};
+
+use crate::{CommentStore, LocalCommentStoreError};
+
+/// File holding the review history, relative to the storage directory.
+const HISTORY_FILE: &str = "history.json";
+
+/// Load the diffs reviewed in the repository of `store`, most recently
+/// viewed first.
+///
+/// # Errors
+///
+/// Returns an error if the history exists but can't be read or parsed.
+pub fn load_review_history(
+    store: &dyn CommentStore,
+) -> Result<ReviewHistory, LocalCommentStoreError> {
+    match fs::read_to_string(store.storage_path().join(HISTORY_FILE)) {
+        Ok(json) => serde_json::from_str(&json).map_err(LocalCommentStoreError::Parse),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(ReviewHistory::default()),
+        Err(e) => Err(LocalCommentStoreError::Read(e)),
+    }
+}
+
+/// Record that `session`'s diff was just viewed.
+///
+/// # Errors
+///
+/// Returns an error if the history can't be read or written.
+pub fn record_review_session(
+    store: &dyn CommentStore,
+    session: ReviewSession,
+) -> Result<(), LocalCommentStoreError> {
+    let mut history = load_review_history(store)?;
+    history.record(session);
+
+    fs::create_dir_all(store.storage_path()).map_err(LocalCommentStoreError::CreateDir)?;
+    let json = serde_json::to_string_pretty(&history).map_err(LocalCommentStoreError::Serialize)?;
+    fs::write(store.storage_path().join(HISTORY_FILE), json).map_err(LocalCommentStoreError::Write)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::env;
+
+    use chadreview_local_comment_models::MAX_REVIEW_HISTORY;
+    use chrono::Utc;
+    use switchy::uuid::Uuid;
+
+    use super::*;
+    use crate::JsonCommentStore;
+
+    fn temp_store() -> JsonCommentStore {
+        let temp_dir = env::temp_dir().join(format!("chadreview-test-{}", Uuid::new_v4()));
+        JsonCommentStore::new(temp_dir)
+    }
+
+    fn session(spec: &str, files: &[&str]) -> ReviewSession {
+        ReviewSession {
+            spec: spec.to_string(),
+            description: spec.to_string(),
+            files: files.iter().map(ToString::to_string).collect(),
+            threads: 0,
+            viewed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_record_moves_diff_to_front() {
+        let store = temp_store();
+
+        record_review_session(&store, session("base=main&head=a", &[])).unwrap();
+        record_review_session(&store, session("base=main&head=b", &[])).unwrap();
+        record_review_session(&store, session("base=main&head=a", &["src/lib.rs"])).unwrap();
+
+        let history = load_review_history(&store).unwrap();
+        let specs: Vec<_> = history.sessions.iter().map(|s| s.spec.as_str()).collect();
+        assert_eq!(specs, vec!["base=main&head=a", "base=main&head=b"]);
+        assert_eq!(history.sessions[0].files, vec!["src/lib.rs".to_string()]);
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let mut history = ReviewHistory::default();
+        for i in 0..=MAX_REVIEW_HISTORY {
+            history.record(session(&format!("commit={i}"), &[]));
+        }
+
+        assert_eq!(history.sessions.len(), MAX_REVIEW_HISTORY);
+        assert_eq!(
+            history.sessions[0].spec,
+            format!("commit={MAX_REVIEW_HISTORY}")
+        );
+    }
+
+    #[test]
+    fn test_progress_counts_viewed_files_of_diff() {
+        let session = session("commit=abc", &["a.rs", "b.rs", "c.rs"]);
+        let viewed = HashSet::from(["a.rs".to_string(), "other.rs".to_string()]);
+
+        assert_eq!(session.progress(&viewed), (1, 3));
+    }
+}
@@ -0,0 +1,333 @@
+//! Static HTML export of a local review.
+//!
+//! Renders the diff with each comment thread below the line it was made on
+//! as a single self-contained page: styles and syntax highlighting are
+//! inlined, so the file can be archived or shared and opened without the
+//! server.
+
+use std::fmt::Write as _;
+
+use chadreview_local_comment_models::{LocalComment, LocalCommentType, ThreadState};
+use chadreview_markdown::{code_theme_css, render_markdown};
+use chadreview_pr_models::{DiffFile, DiffHunk, FileStatus, LineType};
+
+use crate::anchor;
+use crate::export::export_summary;
+
+/// Styles of the exported page, on top of the code theme.
+const STYLE: &str = concat!(
+    "body { margin: 0 auto; max-width: 1200px; padding: 24px; color: #1f2328; ",
+    "font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif; font-size: 14px; }\n",
+    "h1 { font-size: 24px; margin: 0 0 8px; }\n",
+    ".summary { color: #57606a; margin: 0 0 24px; }\n",
+    ".file { border: 1px solid #d0d7de; border-radius: 6px; margin-bottom: 24px; overflow: hidden; }\n",
+    ".file-header { padding: 8px 16px; background: #f6f8fa; border-bottom: 1px solid #d0d7de; font-family: monospace; }\n",
+    ".file-status { margin-left: 8px; color: #57606a; font-family: sans-serif; font-size: 12px; }\n",
+    ".diff { width: 100%; border-collapse: collapse; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 12px; }\n",
+    ".diff td { padding: 0 8px; vertical-align: top; }\n",
+    ".line-number { width: 1%; min-width: 40px; color: #6e7781; text-align: right; user-select: none; }\n",
+    ".code { white-space: pre-wrap; word-break: break-all; }\n",
+    ".hunk-header td { padding: 4px 8px; background: #ddf4ff; color: #57606a; }\n",
+    ".addition { background: #e6ffec; }\n",
+    ".deletion { background: #ffebe9; }\n",
+    ".threads td { padding: 8px 16px; background: #f6f8fa; font-family: sans-serif; font-size: 14px; }\n",
+    ".thread { border: 1px solid #d0d7de; border-radius: 6px; background: #ffffff; margin: 8px 0; }\n",
+    ".thread-header { padding: 4px 12px; border-bottom: 1px solid #d0d7de; color: #57606a; font-size: 12px; }\n",
+    ".comment { padding: 8px 12px; }\n",
+    ".comment + .comment { border-top: 1px solid #d0d7de; }\n",
+    ".byline { color: #57606a; font-size: 12px; }\n",
+    ".excerpt { margin: 8px 12px; padding: 8px; background: #f6f8fa; border-radius: 6px; font-size: 12px; }\n",
+);
+
+/// Compile a diff and its comment threads into a self-contained HTML page.
+///
+/// General threads come first, followed by each file of `diffs` with its
+/// file-level threads and its line threads below their lines. Threads on
+/// lines that are no longer in the diff follow their file with the excerpt
+/// recorded in their anchor, and threads on files not in the diff are left
+/// out.
+#[must_use]
+pub fn export_html(title: &str, threads: &[LocalComment], diffs: &[DiffFile]) -> String {
+    let title = escape_html(title);
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Review: {title}</title>\n<style>\n{}{STYLE}</style>\n</head>\n<body>\n<h1>Review: {title}</h1>\n<p class=\"summary\">{}</p>\n",
+        code_theme_css(),
+        escape_html(&export_summary(threads)),
+    );
+
+    let mut threads: Vec<_> = threads
+        .iter()
+        .filter(|thread| !matches!(thread.comment_type, LocalCommentType::Reply { .. }))
+        .collect();
+    threads.sort_by_key(|thread| thread.created_at);
+
+    let general: Vec<_> = threads
+        .iter()
+        .filter(|thread| thread.comment_type == LocalCommentType::General)
+        .collect();
+    if !general.is_empty() {
+        html.push_str("<section class=\"file\">\n<div class=\"file-header\">General comments</div>\n<div class=\"comment\">\n");
+        for thread in general {
+            write_thread(&mut html, "Comment", thread, false);
+        }
+        html.push_str("</div>\n</section>\n");
+    }
+
+    for file in diffs {
+        write_file(&mut html, file, &threads, diffs);
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn write_file(html: &mut String, file: &DiffFile, threads: &[&LocalComment], diffs: &[DiffFile]) {
+    let status = match file.status {
+        FileStatus::Added => "added",
+        FileStatus::Modified => "modified",
+        FileStatus::Deleted => "deleted",
+        FileStatus::Renamed => "renamed",
+    };
+    writeln!(
+        html,
+        "<section class=\"file\">\n<div class=\"file-header\">{}<span class=\"file-status\">{status} +{} -{}</span></div>",
+        escape_html(&file.filename),
+        file.additions,
+        file.deletions
+    )
+    .unwrap();
+
+    let path = file.filename.as_str();
+    let file_threads: Vec<_> = threads
+        .iter()
+        .filter(|thread| {
+            matches!(&thread.comment_type, LocalCommentType::FileLevelComment { path: p } if p == path)
+        })
+        .collect();
+    if !file_threads.is_empty() {
+        html.push_str("<div class=\"comment\">\n");
+        for thread in file_threads {
+            write_thread(html, "File comment", thread, false);
+        }
+        html.push_str("</div>\n");
+    }
+
+    let line_threads: Vec<_> = threads
+        .iter()
+        .filter_map(|thread| match &thread.comment_type {
+            LocalCommentType::LineLevelComment { path: p, line } if p == path => {
+                Some((*thread, anchor::find_line(diffs, path, *line)))
+            }
+            _ => None,
+        })
+        .collect();
+
+    html.push_str("<table class=\"diff\">\n");
+    for hunk in &file.hunks {
+        write_hunk(html, hunk, &line_threads);
+    }
+    html.push_str("</table>\n");
+
+    let outdated: Vec<_> = line_threads
+        .iter()
+        .filter(|(_, location)| location.is_none())
+        .collect();
+    if !outdated.is_empty() {
+        html.push_str("<div class=\"comment\">\n");
+        for (thread, _) in outdated {
+            write_thread(html, "Outdated comment", thread, true);
+        }
+        html.push_str("</div>\n");
+    }
+
+    html.push_str("</section>\n");
+}
+
+fn write_hunk(
+    html: &mut String,
+    hunk: &DiffHunk,
+    line_threads: &[(&LocalComment, Option<(&DiffHunk, usize)>)],
+) {
+    writeln!(
+        html,
+        "<tr class=\"hunk-header\"><td colspan=\"3\">@@ -{},{} +{},{} @@</td></tr>",
+        hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+    )
+    .unwrap();
+
+    for (index, line) in hunk.lines.iter().enumerate() {
+        let class = match line.line_type {
+            LineType::Addition => "addition",
+            LineType::Deletion => "deletion",
+            LineType::Context => "context",
+        };
+        let number = |number: Option<u64>| number.map(|n| n.to_string()).unwrap_or_default();
+        let code = if line.highlighted_html.is_empty() {
+            escape_html(line.content.trim_end_matches(['\r', '\n']))
+        } else {
+            line.highlighted_html.clone()
+        };
+        writeln!(
+            html,
+            "<tr class=\"{class}\"><td class=\"line-number\">{}</td><td class=\"line-number\">{}</td><td class=\"code\">{code}</td></tr>",
+            number(line.old_line_number),
+            number(line.new_line_number),
+        )
+        .unwrap();
+
+        let threads: Vec<_> = line_threads
+            .iter()
+            .filter(|(_, location)| {
+                location.is_some_and(|(h, i)| std::ptr::eq(h, hunk) && i == index)
+            })
+            .collect();
+        if !threads.is_empty() {
+            html.push_str("<tr class=\"threads\"><td colspan=\"3\">\n");
+            for (thread, _) in threads {
+                write_thread(html, "Comment", thread, false);
+            }
+            html.push_str("</td></tr>\n");
+        }
+    }
+}
+
+fn write_thread(html: &mut String, heading: &str, thread: &LocalComment, with_excerpt: bool) {
+    let state = match thread.state {
+        ThreadState::Open => "Open",
+        ThreadState::Resolved => "Resolved",
+        ThreadState::SavedForLater => "Saved for later",
+    };
+    writeln!(
+        html,
+        "<div class=\"thread\">\n<div class=\"thread-header\">{heading} · {state}</div>"
+    )
+    .unwrap();
+
+    if with_excerpt && let Some(anchor) = &thread.anchor {
+        writeln!(
+            html,
+            "<pre class=\"excerpt\">{}</pre>",
+            escape_html(&anchor.excerpt.join("\n"))
+        )
+        .unwrap();
+    }
+
+    write_comment(html, thread);
+    let mut replies = vec![];
+    collect_replies(thread, &mut replies);
+    for reply in replies {
+        write_comment(html, reply);
+    }
+
+    html.push_str("</div>\n");
+}
+
+fn write_comment(html: &mut String, comment: &LocalComment) {
+    writeln!(
+        html,
+        "<div class=\"comment\">\n<div class=\"byline\"><strong>{}</strong> {}</div>\n{}</div>",
+        escape_html(&comment.author.name),
+        comment.created_at.format("%Y-%m-%d %H:%M UTC"),
+        render_markdown(&comment.body),
+    )
+    .unwrap();
+}
+
+/// Replies of a thread in the order they were written, nested ones included.
+fn collect_replies<'a>(comment: &'a LocalComment, replies: &mut Vec<&'a LocalComment>) {
+    for reply in &comment.replies {
+        replies.push(reply);
+        collect_replies(reply, replies);
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chadreview_local_comment_models::{LineNumber, LocalUser};
+    use chadreview_pr_models::DiffLine;
+
+    fn diff_line(
+        line_type: LineType,
+        old: Option<u64>,
+        new: Option<u64>,
+        content: &str,
+    ) -> DiffLine {
+        DiffLine {
+            line_type,
+            old_line_number: old,
+            new_line_number: new,
+            content: content.to_string(),
+            highlighted_html: String::new(),
+        }
+    }
+
+    fn diffs() -> Vec<DiffFile> {
+        vec![DiffFile {
+            filename: "src/main.rs".to_string(),
+            status: FileStatus::Modified,
+            additions: 1,
+            deletions: 1,
+            mode_change: None,
+            hunks: vec![DiffHunk {
+                old_start: 1,
+                old_lines: 3,
+                new_start: 1,
+                new_lines: 3,
+                lines: vec![
+                    diff_line(LineType::Context, Some(1), Some(1), "fn main() {"),
+                    diff_line(LineType::Deletion, Some(2), None, "    old::<T>();"),
+                    diff_line(LineType::Addition, None, Some(2), "    new();"),
+                    diff_line(LineType::Context, Some(3), Some(3), "}"),
+                ],
+            }],
+            decoded_lossy: false,
+            submodule: None,
+        }]
+    }
+
+    fn comment(body: &str, comment_type: LocalCommentType) -> LocalComment {
+        LocalComment::new(
+            LocalUser {
+                name: "Alice".to_string(),
+                email: "alice@example.com".to_string(),
+            },
+            body.to_string(),
+            comment_type,
+        )
+    }
+
+    #[test]
+    fn test_export_html_places_threads_below_their_lines() {
+        let threads = vec![
+            comment("Looks **good**", LocalCommentType::General),
+            comment(
+                "Why the change?",
+                LocalCommentType::LineLevelComment {
+                    path: "src/main.rs".to_string(),
+                    line: LineNumber::New { line: 2 },
+                },
+            ),
+        ];
+
+        let html = export_html("main..feature", &threads, &diffs());
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<style>"));
+        assert!(html.contains("<strong>good</strong>"));
+        assert!(html.contains("old::&lt;T&gt;();"));
+
+        let added_line = html.find("    new();").unwrap();
+        let thread = html.find("Why the change?").unwrap();
+        let closing_line = html.find("<td class=\"code\">}</td>").unwrap();
+        assert!(added_line < thread && thread < closing_line);
+    }
+}
@@ -0,0 +1,274 @@
+//! Import of hosted PR review comments into the local store.
+//!
+//! Each PR comment thread becomes a [`LocalComment`] thread on the same file
+//! and line, so reviewer feedback can be worked through offline and handed
+//! to AI agents like any local comment. Imported comments get IDs derived
+//! from their remote IDs, which makes importing the same PR again only add
+//! the replies that are new.
+
+use chadreview_local_comment_models::{LocalComment, LocalCommentType, LocalUser, ThreadState};
+use chadreview_pr_models::{Comment, CommentType};
+use switchy::uuid::Uuid;
+
+use crate::{LocalCommentStore, LocalCommentStoreError};
+
+/// High bits of every imported comment's ID ("chadrevw"), keeping imported
+/// IDs apart from the random IDs of comments written locally.
+const IMPORTED_ID_PREFIX: u64 = 0x6368_6164_7265_7677;
+
+/// What an import changed in the store.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Threads that were not in the store yet.
+    pub threads_created: usize,
+    /// New replies added to threads imported before.
+    pub replies_added: usize,
+    /// Threads imported before that had nothing new.
+    pub threads_unchanged: usize,
+}
+
+impl std::fmt::Display for ImportSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} new threads, {} new replies, {} threads already up to date",
+            self.threads_created, self.replies_added, self.threads_unchanged
+        )
+    }
+}
+
+/// The local ID of an imported remote comment.
+#[must_use]
+pub const fn imported_comment_id(remote_id: u64) -> Uuid {
+    Uuid::from_u64_pair(IMPORTED_ID_PREFIX, remote_id)
+}
+
+/// Import PR comment threads into `store`.
+///
+/// Threads imported before keep their local state, replies and AI
+/// responses; only remote replies they don't have yet are added.
+///
+/// # Errors
+///
+/// Returns an error if a thread can't be read from or written to the store.
+pub fn import_pr_comments(
+    store: &LocalCommentStore,
+    comments: &[Comment],
+) -> Result<ImportSummary, LocalCommentStoreError> {
+    let mut summary = ImportSummary::default();
+
+    for thread in comments.iter().filter_map(to_local_thread) {
+        match store.load_thread(thread.id) {
+            Ok(mut existing) => {
+                let added = merge_replies(&mut existing, thread);
+                if added == 0 {
+                    summary.threads_unchanged += 1;
+                } else {
+                    store.save_thread(&existing)?;
+                    summary.replies_added += added;
+                }
+            }
+            Err(LocalCommentStoreError::NotFound(_)) => {
+                store.save_thread(&thread)?;
+                summary.threads_created += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Convert a PR comment thread to a local thread, with its nested replies
+/// flattened in the order they were written. Returns `None` for replies,
+/// which are imported with their thread.
+fn to_local_thread(comment: &Comment) -> Option<LocalComment> {
+    let comment_type = match &comment.comment_type {
+        CommentType::General => LocalCommentType::General,
+        CommentType::FileLevelComment { path } => {
+            LocalCommentType::FileLevelComment { path: path.clone() }
+        }
+        CommentType::LineLevelComment { path, line, .. } => LocalCommentType::LineLevelComment {
+            path: path.clone(),
+            line: *line,
+        },
+        CommentType::Reply { .. } => return None,
+    };
+
+    let root_id = imported_comment_id(comment.id);
+    let mut thread = to_local_comment(comment, comment_type);
+    thread.state = if comment.resolved {
+        ThreadState::Resolved
+    } else {
+        ThreadState::Open
+    };
+
+    let mut replies = vec![];
+    collect_replies(comment, &mut replies);
+    thread.replies = replies
+        .into_iter()
+        .map(|reply| {
+            let in_reply_to = match reply.comment_type {
+                CommentType::Reply { in_reply_to, .. } => imported_comment_id(in_reply_to),
+                _ => root_id,
+            };
+            to_local_comment(
+                reply,
+                LocalCommentType::Reply {
+                    root_comment_id: root_id,
+                    in_reply_to,
+                },
+            )
+        })
+        .collect();
+
+    Some(thread)
+}
+
+fn to_local_comment(comment: &Comment, comment_type: LocalCommentType) -> LocalComment {
+    let author = LocalUser {
+        name: comment.author.username.clone(),
+        email: String::new(),
+    };
+    let mut local = LocalComment::new(author, comment.body.clone(), comment_type);
+    local.id = imported_comment_id(comment.id);
+    local.created_at = comment.created_at;
+    local.updated_at = comment.updated_at;
+    local
+}
+
+fn collect_replies<'a>(comment: &'a Comment, replies: &mut Vec<&'a Comment>) {
+    for reply in &comment.replies {
+        replies.push(reply);
+        collect_replies(reply, replies);
+    }
+}
+
+/// Add the replies of `imported` that `existing` doesn't have yet. Returns
+/// how many were added.
+fn merge_replies(existing: &mut LocalComment, imported: LocalComment) -> usize {
+    let mut added = 0;
+    for reply in imported.replies {
+        if existing.replies.iter().all(|r| r.id != reply.id) {
+            existing.updated_at = existing.updated_at.max(reply.updated_at);
+            existing.replies.push(reply);
+            added += 1;
+        }
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chadreview_local_comment_models::LineNumber;
+    use chadreview_pr_models::User;
+    use chrono::Utc;
+
+    fn remote(id: u64, comment_type: CommentType, replies: Vec<Comment>) -> Comment {
+        Comment {
+            id,
+            author: User {
+                id: "1".to_string(),
+                username: "reviewer".to_string(),
+                avatar_url: String::new(),
+                html_url: String::new(),
+            },
+            body: format!("comment {id}"),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            comment_type,
+            replies,
+            resolved: false,
+            thread_id: None,
+            reactions: vec![],
+        }
+    }
+
+    fn reply(id: u64, root: u64, in_reply_to: u64, replies: Vec<Comment>) -> Comment {
+        remote(
+            id,
+            CommentType::Reply {
+                root_comment_id: root,
+                in_reply_to,
+            },
+            replies,
+        )
+    }
+
+    #[test]
+    fn test_to_local_thread_keeps_position_and_flattens_replies() {
+        let mut comment = remote(
+            10,
+            CommentType::LineLevelComment {
+                path: "src/main.rs".to_string(),
+                commit_sha: "abc123".to_string(),
+                line: LineNumber::New { line: 42 },
+            },
+            vec![reply(11, 10, 10, vec![reply(12, 10, 11, vec![])])],
+        );
+        comment.resolved = true;
+
+        let thread = to_local_thread(&comment).unwrap();
+
+        assert_eq!(thread.id, imported_comment_id(10));
+        assert_eq!(thread.author.name, "reviewer");
+        assert_eq!(thread.body, "comment 10");
+        assert_eq!(thread.state, ThreadState::Resolved);
+        assert_eq!(
+            thread.comment_type,
+            LocalCommentType::LineLevelComment {
+                path: "src/main.rs".to_string(),
+                line: LineNumber::New { line: 42 },
+            }
+        );
+
+        let reply_ids: Vec<_> = thread.replies.iter().map(|r| r.id).collect();
+        assert_eq!(
+            reply_ids,
+            vec![imported_comment_id(11), imported_comment_id(12)]
+        );
+        assert_eq!(
+            thread.replies[1].comment_type,
+            LocalCommentType::Reply {
+                root_comment_id: imported_comment_id(10),
+                in_reply_to: imported_comment_id(11),
+            }
+        );
+    }
+
+    #[test]
+    fn test_to_local_thread_skips_replies() {
+        assert!(to_local_thread(&reply(2, 1, 1, vec![])).is_none());
+    }
+
+    #[test]
+    fn test_merge_replies_adds_only_new_replies() {
+        let before = remote(1, CommentType::General, vec![reply(2, 1, 1, vec![])]);
+        let mut existing = to_local_thread(&before).unwrap();
+        existing.state = ThreadState::SavedForLater;
+        existing.replies.push(LocalComment::new(
+            LocalUser::default(),
+            "local".to_string(),
+            LocalCommentType::General,
+        ));
+
+        let after = remote(
+            1,
+            CommentType::General,
+            vec![reply(2, 1, 1, vec![]), reply(3, 1, 2, vec![])],
+        );
+        let added = merge_replies(&mut existing, to_local_thread(&after).unwrap());
+
+        assert_eq!(added, 1);
+        assert_eq!(existing.replies.len(), 3);
+        assert_eq!(existing.replies[2].id, imported_comment_id(3));
+        assert_eq!(existing.state, ThreadState::SavedForLater);
+    }
+
+    #[test]
+    fn test_imported_ids_are_stable_and_distinct() {
+        assert_eq!(imported_comment_id(7), imported_comment_id(7));
+        assert_ne!(imported_comment_id(7), imported_comment_id(8));
+    }
+}
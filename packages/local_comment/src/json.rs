@@ -0,0 +1,698 @@
+//! JSON-file storage for local comments.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use switchy::uuid::Uuid;
+
+use chadreview_local_comment_models::{
+    CommentThreadIndex, DraftsIndex, LocalComment, ThreadState, UsageLedger, ViewedFilesIndex,
+    ViewedRepliesIndex,
+};
+
+use crate::store::{CommentStore, LocalCommentStoreError};
+
+/// File-based storage for local comments, one JSON file per thread.
+///
+/// Storage layout:
+/// ```text
+/// $XDG_DATA_HOME/chadreview/comments/{repo-hash}/
+/// ├── index.json          # Thread index for fast listing
+/// ├── viewed_files.json   # Files marked as viewed
+/// ├── viewed_replies.json # Replies marked as viewed
+/// ├── drafts.json         # Unsubmitted comment form text
+/// ├── usage.json          # AI usage ledger
+/// ├── threads/
+/// │   ├── {uuid}.json     # Individual comment threads
+/// │   └── ...
+/// └── archive/
+///     └── {uuid}.json     # Archived resolved threads
+/// ```
+pub struct JsonCommentStore {
+    /// Base path for this repository's comments.
+    repo_path: PathBuf,
+    /// In-memory cache of comment threads (thread-safe).
+    cache: RwLock<HashMap<Uuid, LocalComment>>,
+}
+
+impl JsonCommentStore {
+    /// Create a store in a repository's storage directory.
+    #[must_use]
+    pub fn new(repo_path: PathBuf) -> Self {
+        Self {
+            repo_path,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Whether the storage directory holds any comments or viewed state.
+    #[must_use]
+    pub fn has_data(&self) -> bool {
+        [
+            self.index_path(),
+            self.viewed_files_path(),
+            self.viewed_replies_path(),
+            self.drafts_path(),
+            self.usage_path(),
+        ]
+        .iter()
+        .any(|path| path.exists())
+    }
+
+    /// Load every archived thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an archived thread can't be read.
+    pub fn load_archived_threads(&self) -> Result<Vec<LocalComment>, LocalCommentStoreError> {
+        let archive_dir = self.repo_path.join("archive");
+        if !archive_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut threads = Vec::new();
+        for entry in fs::read_dir(&archive_dir).map_err(LocalCommentStoreError::Read)? {
+            let path = entry.map_err(LocalCommentStoreError::Read)?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                threads.push(Self::read_json(&path)?);
+            }
+        }
+
+        Ok(threads)
+    }
+
+    /// Ensure storage directories exist.
+    fn ensure_dirs(&self) -> Result<(), LocalCommentStoreError> {
+        let threads_dir = self.repo_path.join("threads");
+        fs::create_dir_all(&threads_dir).map_err(LocalCommentStoreError::CreateDir)?;
+        Ok(())
+    }
+
+    /// Get the path to a thread file.
+    fn thread_path(&self, id: Uuid) -> PathBuf {
+        self.repo_path.join("threads").join(format!("{id}.json"))
+    }
+
+    /// Get the path to an archived thread file.
+    fn archive_path(&self, id: Uuid) -> PathBuf {
+        self.repo_path.join("archive").join(format!("{id}.json"))
+    }
+
+    /// Get the path to the index file.
+    fn index_path(&self) -> PathBuf {
+        self.repo_path.join("index.json")
+    }
+
+    /// Get the path to the viewed files index.
+    fn viewed_files_path(&self) -> PathBuf {
+        self.repo_path.join("viewed_files.json")
+    }
+
+    /// Get the path to the viewed replies index.
+    fn viewed_replies_path(&self) -> PathBuf {
+        self.repo_path.join("viewed_replies.json")
+    }
+
+    /// Get the path to the drafts index.
+    fn drafts_path(&self) -> PathBuf {
+        self.repo_path.join("drafts.json")
+    }
+
+    /// Get the path to the AI usage ledger.
+    fn usage_path(&self) -> PathBuf {
+        self.repo_path.join("usage.json")
+    }
+
+    fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, LocalCommentStoreError> {
+        let file = File::open(path).map_err(LocalCommentStoreError::Read)?;
+        let reader = BufReader::new(file);
+        serde_json::from_reader(reader).map_err(LocalCommentStoreError::Parse)
+    }
+
+    fn write_json<T: serde::Serialize + ?Sized>(
+        path: &Path,
+        value: &T,
+    ) -> Result<(), LocalCommentStoreError> {
+        let file = File::create(path).map_err(LocalCommentStoreError::Write)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, value).map_err(LocalCommentStoreError::Serialize)
+    }
+
+    /// Update the thread index.
+    fn update_index(&self, comment: &LocalComment) -> Result<(), LocalCommentStoreError> {
+        let mut index = self.load_index().unwrap_or_default();
+
+        // Update or add entry
+        let entry = comment.to_index_entry();
+        if let Some(existing) = index.iter_mut().find(|e| e.id == comment.id) {
+            *existing = entry;
+        } else {
+            index.push(entry);
+        }
+
+        self.save_index(&index)
+    }
+
+    /// Remove a thread from the index.
+    fn remove_from_index(&self, id: Uuid) -> Result<(), LocalCommentStoreError> {
+        let mut index = self.load_index().unwrap_or_default();
+        index.retain(|e| e.id != id);
+        self.save_index(&index)
+    }
+
+    /// Load the thread index.
+    fn load_index(&self) -> Result<Vec<CommentThreadIndex>, LocalCommentStoreError> {
+        let path = self.index_path();
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+
+        Self::read_json(&path)
+    }
+
+    /// Save the thread index.
+    fn save_index(&self, index: &[CommentThreadIndex]) -> Result<(), LocalCommentStoreError> {
+        self.ensure_dirs()?;
+        Self::write_json(&self.index_path(), index)
+    }
+}
+
+impl CommentStore for JsonCommentStore {
+    fn save_thread(&self, comment: &LocalComment) -> Result<(), LocalCommentStoreError> {
+        self.ensure_dirs()?;
+
+        // Save thread file
+        Self::write_json(&self.thread_path(comment.id), comment)?;
+
+        // Update cache
+        if let Ok(mut cache) = self.cache.write() {
+            cache.insert(comment.id, comment.clone());
+        }
+
+        // Update index
+        self.update_index(comment)?;
+
+        Ok(())
+    }
+
+    fn load_thread(&self, id: Uuid) -> Result<LocalComment, LocalCommentStoreError> {
+        // Check cache first
+        if let Ok(cache) = self.cache.read()
+            && let Some(comment) = cache.get(&id)
+        {
+            return Ok(comment.clone());
+        }
+
+        // Load from disk
+        let path = self.thread_path(id);
+        if !path.exists() {
+            return Err(LocalCommentStoreError::NotFound(id));
+        }
+
+        let comment: LocalComment = Self::read_json(&path)?;
+
+        // Update cache
+        if let Ok(mut cache) = self.cache.write() {
+            cache.insert(id, comment.clone());
+        }
+
+        Ok(comment)
+    }
+
+    fn delete_thread(&self, id: Uuid) -> Result<(), LocalCommentStoreError> {
+        let path = self.thread_path(id);
+        if !path.exists() {
+            return Err(LocalCommentStoreError::NotFound(id));
+        }
+
+        fs::remove_file(&path).map_err(LocalCommentStoreError::Write)?;
+
+        // Remove from cache
+        if let Ok(mut cache) = self.cache.write() {
+            cache.remove(&id);
+        }
+
+        // Remove from index
+        self.remove_from_index(id)?;
+
+        Ok(())
+    }
+
+    fn list_threads(&self) -> Result<Vec<CommentThreadIndex>, LocalCommentStoreError> {
+        self.load_index()
+    }
+
+    fn update_thread(
+        &self,
+        id: Uuid,
+        update: &mut dyn FnMut(&mut LocalComment) -> Result<bool, LocalCommentStoreError>,
+    ) -> Result<bool, LocalCommentStoreError> {
+        let mut thread = self.load_thread(id)?;
+        let changed = update(&mut thread)?;
+        if changed {
+            self.save_thread(&thread)?;
+        }
+        Ok(changed)
+    }
+
+    fn load_viewed_files(&self) -> Result<ViewedFilesIndex, LocalCommentStoreError> {
+        let path = self.viewed_files_path();
+        if !path.exists() {
+            return Ok(ViewedFilesIndex::default());
+        }
+
+        Self::read_json(&path)
+    }
+
+    fn save_viewed_files(&self, index: &ViewedFilesIndex) -> Result<(), LocalCommentStoreError> {
+        // Ensure directory exists
+        fs::create_dir_all(&self.repo_path).map_err(LocalCommentStoreError::CreateDir)?;
+        Self::write_json(&self.viewed_files_path(), index)
+    }
+
+    fn load_viewed_replies(&self) -> Result<ViewedRepliesIndex, LocalCommentStoreError> {
+        let path = self.viewed_replies_path();
+        if !path.exists() {
+            return Ok(ViewedRepliesIndex::default());
+        }
+
+        Self::read_json(&path)
+    }
+
+    fn save_viewed_replies(
+        &self,
+        index: &ViewedRepliesIndex,
+    ) -> Result<(), LocalCommentStoreError> {
+        // Ensure directory exists
+        fs::create_dir_all(&self.repo_path).map_err(LocalCommentStoreError::CreateDir)?;
+        Self::write_json(&self.viewed_replies_path(), index)
+    }
+
+    fn load_drafts(&self) -> Result<DraftsIndex, LocalCommentStoreError> {
+        let path = self.drafts_path();
+        if !path.exists() {
+            return Ok(DraftsIndex::default());
+        }
+
+        Self::read_json(&path)
+    }
+
+    fn save_drafts(&self, index: &DraftsIndex) -> Result<(), LocalCommentStoreError> {
+        // Ensure directory exists
+        fs::create_dir_all(&self.repo_path).map_err(LocalCommentStoreError::CreateDir)?;
+        Self::write_json(&self.drafts_path(), index)
+    }
+
+    fn load_usage(&self) -> Result<UsageLedger, LocalCommentStoreError> {
+        let path = self.usage_path();
+        if !path.exists() {
+            return Ok(UsageLedger::default());
+        }
+
+        Self::read_json(&path)
+    }
+
+    fn save_usage(&self, ledger: &UsageLedger) -> Result<(), LocalCommentStoreError> {
+        // Ensure directory exists
+        fs::create_dir_all(&self.repo_path).map_err(LocalCommentStoreError::CreateDir)?;
+        Self::write_json(&self.usage_path(), ledger)
+    }
+
+    /// Moves the threads out of the index into `archive/`, keeping their
+    /// files.
+    fn archive_resolved_threads(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<usize, LocalCommentStoreError> {
+        let stale: Vec<Uuid> = self
+            .load_index()?
+            .into_iter()
+            .filter(|entry| entry.state == ThreadState::Resolved && entry.updated_at < cutoff)
+            .map(|entry| entry.id)
+            .collect();
+
+        if stale.is_empty() {
+            return Ok(0);
+        }
+
+        fs::create_dir_all(self.repo_path.join("archive"))
+            .map_err(LocalCommentStoreError::CreateDir)?;
+
+        for id in &stale {
+            let path = self.thread_path(*id);
+            if path.exists() {
+                fs::rename(&path, self.archive_path(*id)).map_err(LocalCommentStoreError::Write)?;
+            }
+            if let Ok(mut cache) = self.cache.write() {
+                cache.remove(id);
+            }
+        }
+
+        let mut index = self.load_index()?;
+        index.retain(|entry| !stale.contains(&entry.id));
+        self.save_index(&index)?;
+
+        Ok(stale.len())
+    }
+
+    fn storage_path(&self) -> &Path {
+        &self.repo_path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chadreview_local_comment_models::{
+        AiExecutionStatus, LineAnchor, LineNumber, LocalCommentType, LocalUser, TokenUsage,
+        UsageEntry,
+    };
+    use std::env;
+
+    fn temp_store() -> JsonCommentStore {
+        let temp_dir = env::temp_dir().join(format!("chadreview-test-{}", Uuid::new_v4()));
+        JsonCommentStore::new(temp_dir)
+    }
+
+    #[test]
+    fn test_save_and_load_thread() {
+        let store = temp_store();
+
+        let comment = LocalComment::new(
+            LocalUser::default(),
+            "Test comment".to_string(),
+            LocalCommentType::General,
+        );
+
+        store.save_thread(&comment).unwrap();
+        let loaded = store.load_thread(comment.id).unwrap();
+
+        assert_eq!(loaded.id, comment.id);
+        assert_eq!(loaded.body, comment.body);
+    }
+
+    #[test]
+    fn test_list_threads() {
+        let store = temp_store();
+
+        let comment1 = LocalComment::new(
+            LocalUser::default(),
+            "Comment 1".to_string(),
+            LocalCommentType::General,
+        );
+        let comment2 = LocalComment::new(
+            LocalUser::default(),
+            "Comment 2".to_string(),
+            LocalCommentType::LineLevelComment {
+                path: "src/main.rs".to_string(),
+                line: LineNumber::New { line: 10 },
+            },
+        );
+
+        store.save_thread(&comment1).unwrap();
+        store.save_thread(&comment2).unwrap();
+
+        let threads = store.list_threads().unwrap();
+        assert_eq!(threads.len(), 2);
+    }
+
+    #[test]
+    fn test_delete_thread() {
+        let store = temp_store();
+
+        let comment = LocalComment::new(
+            LocalUser::default(),
+            "To be deleted".to_string(),
+            LocalCommentType::General,
+        );
+
+        store.save_thread(&comment).unwrap();
+        assert!(store.load_thread(comment.id).is_ok());
+
+        store.delete_thread(comment.id).unwrap();
+        assert!(store.load_thread(comment.id).is_err());
+    }
+
+    #[test]
+    fn test_edit_nested_reply() {
+        let store = temp_store();
+
+        let mut thread = LocalComment::new(
+            LocalUser::default(),
+            "Question".to_string(),
+            LocalCommentType::General,
+        );
+        let mut reply = LocalComment::new(
+            LocalUser::default(),
+            "Answer".to_string(),
+            LocalCommentType::General,
+        );
+        let nested = LocalComment::new(
+            LocalUser::default(),
+            "Follow-up".to_string(),
+            LocalCommentType::General,
+        );
+        let nested_id = nested.id;
+        reply.replies.push(nested);
+        thread.replies.push(reply);
+        store.save_thread(&thread).unwrap();
+
+        let edited = store
+            .edit_comment(thread.id, nested_id, "Edited follow-up".to_string())
+            .unwrap();
+        assert_eq!(edited.body, "Edited follow-up");
+        assert!(edited.edited_at.is_some());
+        assert_eq!(edited.revisions.len(), 1);
+        assert_eq!(edited.revisions[0].body, "Follow-up");
+        assert_eq!(edited.revisions[0].written_at, edited.created_at);
+
+        let loaded = store.load_thread(thread.id).unwrap();
+        assert_eq!(loaded.body, "Question");
+        assert!(loaded.edited_at.is_none());
+        assert_eq!(loaded.replies[0].replies[0].body, "Edited follow-up");
+
+        assert!(matches!(
+            store.edit_comment(thread.id, Uuid::new_v4(), "Nope".to_string()),
+            Err(LocalCommentStoreError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_edit_keeps_revisions() {
+        let store = temp_store();
+
+        let thread = LocalComment::new(
+            LocalUser::default(),
+            "First".to_string(),
+            LocalCommentType::General,
+        );
+        store.save_thread(&thread).unwrap();
+
+        let second = store
+            .edit_comment(thread.id, thread.id, "Second".to_string())
+            .unwrap();
+        let unchanged = store
+            .edit_comment(thread.id, thread.id, "Second".to_string())
+            .unwrap();
+        assert_eq!(unchanged.revisions, second.revisions);
+
+        let third = store
+            .edit_comment(thread.id, thread.id, "Third".to_string())
+            .unwrap();
+        let bodies: Vec<_> = third.revisions.iter().map(|r| r.body.as_str()).collect();
+        assert_eq!(bodies, vec!["First", "Second"]);
+        assert_eq!(Some(third.revisions[1].written_at), second.edited_at);
+
+        let loaded = store.load_thread(thread.id).unwrap();
+        assert_eq!(loaded.body, "Third");
+        assert_eq!(loaded.revisions, third.revisions);
+    }
+
+    #[test]
+    fn test_record_anchor_and_reattach_thread() {
+        let store = temp_store();
+
+        let thread = LocalComment::new(
+            LocalUser::default(),
+            "Off by one".to_string(),
+            LocalCommentType::LineLevelComment {
+                path: "src/main.rs".to_string(),
+                line: LineNumber::New { line: 10 },
+            },
+        );
+        store.save_thread(&thread).unwrap();
+
+        let anchor = |line: &str| LineAnchor {
+            hunk_header: "@@ -1,3 +1,3 @@".to_string(),
+            excerpt: vec![line.to_string()],
+            recorded_at: Utc::now(),
+        };
+        assert!(store.record_anchor(thread.id, &anchor("+first")).unwrap());
+        assert!(!store.record_anchor(thread.id, &anchor("+second")).unwrap());
+        let loaded = store.load_thread(thread.id).unwrap();
+        assert_eq!(loaded.anchor.unwrap().excerpt, vec!["+first"]);
+
+        let moved = store
+            .reattach_thread(thread.id, LineNumber::Old { line: 7 }, None)
+            .unwrap();
+        assert_eq!(
+            moved.comment_type,
+            LocalCommentType::LineLevelComment {
+                path: "src/main.rs".to_string(),
+                line: LineNumber::Old { line: 7 },
+            }
+        );
+        assert!(moved.anchor.is_none());
+
+        let general = LocalComment::new(
+            LocalUser::default(),
+            "General".to_string(),
+            LocalCommentType::General,
+        );
+        store.save_thread(&general).unwrap();
+        assert!(matches!(
+            store.reattach_thread(general.id, LineNumber::New { line: 1 }, None),
+            Err(LocalCommentStoreError::NotLineComment(_))
+        ));
+    }
+
+    #[test]
+    fn test_drafts() {
+        let store = temp_store();
+
+        store
+            .save_draft("local-reply-form-1", "Half a thought")
+            .unwrap();
+        store.save_draft("local-reply-form-2", "Another").unwrap();
+        store.save_draft("local-reply-form-2", "  ").unwrap();
+
+        let drafts = store.get_draft_bodies().unwrap();
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts["local-reply-form-1"], "Half a thought");
+        assert!(store.has_data());
+    }
+
+    #[test]
+    fn test_usage() {
+        let store = temp_store();
+        assert!(store.load_usage().unwrap().entries.is_empty());
+
+        for cost in [Some(0.25), None, Some(0.5)] {
+            store
+                .record_usage(UsageEntry {
+                    comment_id: Some(Uuid::new_v4()),
+                    recorded_at: Utc::now(),
+                    model: "model".to_string(),
+                    tokens: TokenUsage {
+                        input: 100,
+                        output: 10,
+                    },
+                    cost,
+                })
+                .unwrap();
+        }
+
+        let ledger = store.load_usage().unwrap();
+        assert_eq!(ledger.entries.len(), 3);
+        assert!((ledger.total_cost() - 0.75).abs() < f64::EPSILON);
+        assert_eq!(ledger.total_tokens().input, 300);
+        assert_eq!(ledger.total_tokens().output, 30);
+        assert!(store.has_data());
+    }
+
+    #[test]
+    fn test_archive_resolved_threads() {
+        let store = temp_store();
+
+        let mut resolved = LocalComment::new(
+            LocalUser::default(),
+            "Resolved".to_string(),
+            LocalCommentType::General,
+        );
+        resolved.state = ThreadState::Resolved;
+        let open = LocalComment::new(
+            LocalUser::default(),
+            "Open".to_string(),
+            LocalCommentType::General,
+        );
+        store.save_thread(&resolved).unwrap();
+        store.save_thread(&open).unwrap();
+
+        // Nothing is older than a cutoff in the past
+        let past = chrono::Utc::now() - chrono::Duration::days(1);
+        assert_eq!(store.archive_resolved_threads(past).unwrap(), 0);
+
+        let future = chrono::Utc::now() + chrono::Duration::days(1);
+        assert_eq!(store.archive_resolved_threads(future).unwrap(), 1);
+
+        let threads = store.list_threads().unwrap();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].id, open.id);
+        assert!(store.load_thread(resolved.id).is_err());
+        assert!(store.archive_path(resolved.id).exists());
+    }
+
+    #[test]
+    fn test_fail_stale_ai_executions() {
+        let store = temp_store();
+        let started_at = chrono::Utc::now() - chrono::Duration::hours(2);
+
+        let mut thread = LocalComment::new(
+            LocalUser::default(),
+            "Question".to_string(),
+            LocalCommentType::General,
+        );
+        thread.ai_status = Some(AiExecutionStatus::Running {
+            started_at,
+            progress: vec![],
+        });
+        let mut reply = LocalComment::new(
+            LocalUser::default(),
+            "Follow-up".to_string(),
+            LocalCommentType::General,
+        );
+        reply.ai_status = Some(AiExecutionStatus::Running {
+            started_at: chrono::Utc::now(),
+            progress: vec![],
+        });
+        thread.replies.push(reply);
+        let mut still_running = LocalComment::new(
+            LocalUser::default(),
+            "Long question".to_string(),
+            LocalCommentType::General,
+        );
+        still_running.ai_status = Some(AiExecutionStatus::Running {
+            started_at,
+            progress: vec![],
+        });
+        thread.replies.push(still_running);
+        store.save_thread(&thread).unwrap();
+
+        let running_id = thread.replies[1].id;
+        let cutoff = chrono::Utc::now() - chrono::Duration::hours(1);
+        assert_eq!(
+            store
+                .fail_stale_ai_executions(cutoff, &|id| id == running_id)
+                .unwrap(),
+            1
+        );
+
+        let loaded = store.load_thread(thread.id).unwrap();
+        assert!(matches!(
+            loaded.ai_status,
+            Some(AiExecutionStatus::Failed { .. })
+        ));
+        assert!(matches!(
+            loaded.replies[0].ai_status,
+            Some(AiExecutionStatus::Running { .. })
+        ));
+        assert!(matches!(
+            loaded.replies[1].ai_status,
+            Some(AiExecutionStatus::Running { .. })
+        ));
+    }
+}
@@ -4,9 +4,58 @@
 
 //! Local comment storage for `ChadReview`.
 //!
-//! This crate provides XDG-compliant file-based storage for local diff comments.
+//! This crate provides XDG-compliant storage for local diff comments, as
+//! JSON files, (with the `sqlite` feature) a `SQLite` database or (with the
+//! `git-notes` feature) git notes in the repository itself, and exports them
+//! as a markdown review report or a static HTML page. Line comments are
+//! anchored to the code they were made on, so they can be told apart once
+//! outdated. Comments from a hosted PR can be imported into the store as local
+//! threads, images attached to comments are kept alongside them, and the
+//! findings of an AI review of a whole diff become threads of their own. AI
+//! summaries of changed files are cached by their changes, and agents can be
+//! asked to describe a diff as a commit message or PR description. The diffs
+//! reviewed in a repository are remembered so a review can be resumed, along
+//! with the review checklist items checked on each.
 
+mod anchor;
+mod attachment;
+mod checklist;
+mod describe;
+mod export;
+mod filter;
+mod history;
+mod html;
+mod import;
+mod json;
+#[cfg(feature = "git-notes")]
+mod notes;
+mod review;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 mod store;
+mod summary;
 
+pub use anchor::{is_outdated, line_anchor, record_anchors};
+pub use attachment::{Attachment, MAX_ATTACHMENT_BYTES, load_attachment, save_attachment};
 pub use chadreview_local_comment_models as models;
-pub use store::{LocalCommentStore, LocalCommentStoreError};
+pub use checklist::{load_checked_items, set_checklist_item};
+pub use describe::{description_request, parse_description};
+pub use export::{export_markdown, export_summary};
+pub use filter::filter_threads;
+pub use history::{load_review_history, record_review_session};
+pub use html::export_html;
+pub use import::{ImportSummary, import_pr_comments, imported_comment_id};
+pub use json::JsonCommentStore;
+#[cfg(feature = "git-notes")]
+pub use notes::{ARCHIVE_NOTES_REF, NOTES_REF, NotesCommentStore};
+pub use review::{
+    REVIEW_REQUEST, ReviewFinding, ReviewResponse, finding_threads, format_review_diff,
+    parse_review_response,
+};
+#[cfg(feature = "sqlite")]
+pub use sqlite::{MigrationReport, SqliteCommentStore, migrate_json_stores};
+pub use store::{CommentStore, LocalCommentStore, LocalCommentStoreError};
+pub use summary::{
+    MAX_SUMMARY_BATCH_BYTES, SUMMARY_REQUEST, file_summary_key, load_file_summaries,
+    load_file_summary, parse_file_summaries, save_file_summary, summary_batches,
+};
@@ -0,0 +1,317 @@
+//! Git notes storage for local comments.
+//!
+//! Keeps a repository's threads in the repository itself, one note per
+//! thread under [`NOTES_REF`], so they travel with it when the notes ref is
+//! pushed and fetched:
+//!
+//! ```sh
+//! git push origin refs/notes/chadreview
+//! git fetch origin refs/notes/chadreview:refs/notes/chadreview
+//! ```
+//!
+//! Viewed files and replies, comment drafts and the AI usage ledger are each
+//! reviewer's own, so they stay in the repository's storage directory as
+//! JSON.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use chadreview_config::Config;
+use chadreview_git_backend::{GitBackend, GitRepository};
+use chadreview_git_backend_git2::Git2Backend;
+use chrono::{DateTime, Utc};
+use switchy::uuid::Uuid;
+
+use chadreview_local_comment_models::{
+    CommentThreadIndex, DraftsIndex, LocalComment, ThreadState, UsageLedger, ViewedFilesIndex,
+    ViewedRepliesIndex,
+};
+
+use crate::json::JsonCommentStore;
+use crate::store::{CommentStore, LocalCommentStoreError};
+
+/// Notes ref holding a repository's comment threads.
+pub const NOTES_REF: &str = "refs/notes/chadreview";
+
+/// Notes ref holding a repository's archived threads.
+pub const ARCHIVE_NOTES_REF: &str = "refs/notes/chadreview-archive";
+
+/// Git notes-backed storage for a repository's comments.
+///
+/// Each thread is a JSON note named by its ID, so the models are the same
+/// as with [`JsonCommentStore`].
+pub struct NotesCommentStore {
+    repo: Mutex<Box<dyn GitRepository>>,
+    /// Storage for the reviewer's own state.
+    local: JsonCommentStore,
+}
+
+impl NotesCommentStore {
+    /// Whether the repository at `repo_path` is configured to keep its
+    /// comments in git notes (`comments.git_notes`).
+    #[must_use]
+    pub fn is_used_for(repo_path: &Path) -> bool {
+        Config::load(Some(repo_path)).is_ok_and(|config| config.comments.git_notes)
+    }
+
+    /// Open the notes of the repository at `repo_path`, keeping the
+    /// reviewer's own state in the storage directory `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `repo_path` isn't in a git repository.
+    pub fn open(repo_path: &Path, dir: PathBuf) -> Result<Self, LocalCommentStoreError> {
+        Ok(Self::new(Git2Backend::new().discover(repo_path)?, dir))
+    }
+
+    /// Create a store on an opened repository, keeping the reviewer's own
+    /// state in the storage directory `dir`.
+    #[must_use]
+    pub fn new(repo: Box<dyn GitRepository>, dir: PathBuf) -> Self {
+        Self {
+            repo: Mutex::new(repo),
+            local: JsonCommentStore::new(dir),
+        }
+    }
+
+    /// Lock the repository. Holding the lock across a read and a write makes
+    /// them atomic within this process.
+    fn repo(&self) -> MutexGuard<'_, Box<dyn GitRepository>> {
+        self.repo.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Load every archived thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive notes can't be read or parsed.
+    pub fn load_archived_threads(&self) -> Result<Vec<LocalComment>, LocalCommentStoreError> {
+        read_threads(&**self.repo(), ARCHIVE_NOTES_REF)
+    }
+}
+
+/// Read the thread `id` from the live notes.
+fn read_thread(repo: &dyn GitRepository, id: Uuid) -> Result<LocalComment, LocalCommentStoreError> {
+    let note = repo
+        .read_note(NOTES_REF, &id.to_string())?
+        .ok_or(LocalCommentStoreError::NotFound(id))?;
+    serde_json::from_str(&note).map_err(LocalCommentStoreError::Parse)
+}
+
+/// Write `thread` as a note under `notes_ref`.
+fn write_thread(
+    repo: &dyn GitRepository,
+    notes_ref: &str,
+    thread: &LocalComment,
+) -> Result<(), LocalCommentStoreError> {
+    let note = serde_json::to_string_pretty(thread).map_err(LocalCommentStoreError::Serialize)?;
+    repo.write_note(notes_ref, &thread.id.to_string(), &note)?;
+    Ok(())
+}
+
+/// Read every thread under `notes_ref`.
+fn read_threads(
+    repo: &dyn GitRepository,
+    notes_ref: &str,
+) -> Result<Vec<LocalComment>, LocalCommentStoreError> {
+    repo.list_notes(notes_ref)?
+        .iter()
+        .map(|note| serde_json::from_str(note).map_err(LocalCommentStoreError::Parse))
+        .collect()
+}
+
+impl CommentStore for NotesCommentStore {
+    fn save_thread(&self, comment: &LocalComment) -> Result<(), LocalCommentStoreError> {
+        write_thread(&**self.repo(), NOTES_REF, comment)
+    }
+
+    fn load_thread(&self, id: Uuid) -> Result<LocalComment, LocalCommentStoreError> {
+        read_thread(&**self.repo(), id)
+    }
+
+    fn delete_thread(&self, id: Uuid) -> Result<(), LocalCommentStoreError> {
+        if self.repo().remove_note(NOTES_REF, &id.to_string())? {
+            Ok(())
+        } else {
+            Err(LocalCommentStoreError::NotFound(id))
+        }
+    }
+
+    fn list_threads(&self) -> Result<Vec<CommentThreadIndex>, LocalCommentStoreError> {
+        let mut index: Vec<CommentThreadIndex> = read_threads(&**self.repo(), NOTES_REF)?
+            .iter()
+            .map(LocalComment::to_index_entry)
+            .collect();
+        index.sort_by_key(|entry| entry.created_at);
+        Ok(index)
+    }
+
+    fn update_thread(
+        &self,
+        id: Uuid,
+        update: &mut dyn FnMut(&mut LocalComment) -> Result<bool, LocalCommentStoreError>,
+    ) -> Result<bool, LocalCommentStoreError> {
+        let repo = self.repo();
+        let mut thread = read_thread(&**repo, id)?;
+        let changed = update(&mut thread)?;
+        if changed {
+            write_thread(&**repo, NOTES_REF, &thread)?;
+        }
+        Ok(changed)
+    }
+
+    fn load_viewed_files(&self) -> Result<ViewedFilesIndex, LocalCommentStoreError> {
+        self.local.load_viewed_files()
+    }
+
+    fn save_viewed_files(&self, index: &ViewedFilesIndex) -> Result<(), LocalCommentStoreError> {
+        self.local.save_viewed_files(index)
+    }
+
+    fn load_viewed_replies(&self) -> Result<ViewedRepliesIndex, LocalCommentStoreError> {
+        self.local.load_viewed_replies()
+    }
+
+    fn save_viewed_replies(
+        &self,
+        index: &ViewedRepliesIndex,
+    ) -> Result<(), LocalCommentStoreError> {
+        self.local.save_viewed_replies(index)
+    }
+
+    fn load_drafts(&self) -> Result<DraftsIndex, LocalCommentStoreError> {
+        self.local.load_drafts()
+    }
+
+    fn save_drafts(&self, index: &DraftsIndex) -> Result<(), LocalCommentStoreError> {
+        self.local.save_drafts(index)
+    }
+
+    fn load_usage(&self) -> Result<UsageLedger, LocalCommentStoreError> {
+        self.local.load_usage()
+    }
+
+    fn save_usage(&self, ledger: &UsageLedger) -> Result<(), LocalCommentStoreError> {
+        self.local.save_usage(ledger)
+    }
+
+    /// Moves the threads' notes to [`ARCHIVE_NOTES_REF`].
+    fn archive_resolved_threads(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<usize, LocalCommentStoreError> {
+        let repo = self.repo();
+        let stale: Vec<LocalComment> = read_threads(&**repo, NOTES_REF)?
+            .into_iter()
+            .filter(|thread| thread.state == ThreadState::Resolved && thread.updated_at < cutoff)
+            .collect();
+
+        for thread in &stale {
+            write_thread(&**repo, ARCHIVE_NOTES_REF, thread)?;
+            repo.remove_note(NOTES_REF, &thread.id.to_string())?;
+        }
+
+        Ok(stale.len())
+    }
+
+    fn storage_path(&self) -> &Path {
+        self.local.storage_path()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chadreview_local_comment_models::{LocalCommentType, LocalUser};
+    use std::env;
+
+    fn temp_store() -> (tempfile::TempDir, NotesCommentStore) {
+        let repo_dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(repo_dir.path()).unwrap();
+        let storage_dir = env::temp_dir().join(format!("chadreview-test-{}", Uuid::new_v4()));
+        let store = NotesCommentStore::open(repo_dir.path(), storage_dir).unwrap();
+        (repo_dir, store)
+    }
+
+    #[test]
+    fn test_save_load_and_delete_thread() {
+        let (_dir, store) = temp_store();
+
+        let first = LocalComment::new(
+            LocalUser::default(),
+            "First".to_string(),
+            LocalCommentType::General,
+        );
+        let second = LocalComment::new(
+            LocalUser::default(),
+            "Second".to_string(),
+            LocalCommentType::General,
+        );
+        store.save_thread(&first).unwrap();
+        store.save_thread(&second).unwrap();
+
+        assert_eq!(store.load_thread(first.id).unwrap().body, "First");
+        let threads = store.list_threads().unwrap();
+        assert_eq!(
+            threads.iter().map(|entry| entry.id).collect::<Vec<_>>(),
+            vec![first.id, second.id]
+        );
+
+        store.delete_thread(first.id).unwrap();
+        assert!(store.load_thread(first.id).is_err());
+        assert!(store.delete_thread(first.id).is_err());
+        assert_eq!(store.list_threads().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_update_thread() {
+        let (_dir, store) = temp_store();
+
+        let thread = LocalComment::new(
+            LocalUser::default(),
+            "Question".to_string(),
+            LocalCommentType::General,
+        );
+        store.save_thread(&thread).unwrap();
+
+        let reply = LocalComment::new(
+            LocalUser::default(),
+            "Answer".to_string(),
+            LocalCommentType::General,
+        );
+        store.add_reply(thread.id, reply).unwrap();
+
+        let loaded = store.load_thread(thread.id).unwrap();
+        assert_eq!(loaded.replies.len(), 1);
+        assert_eq!(loaded.replies[0].body, "Answer");
+    }
+
+    #[test]
+    fn test_archive_resolved_threads() {
+        let (_dir, store) = temp_store();
+
+        let mut resolved = LocalComment::new(
+            LocalUser::default(),
+            "Resolved".to_string(),
+            LocalCommentType::General,
+        );
+        resolved.state = ThreadState::Resolved;
+        let open = LocalComment::new(
+            LocalUser::default(),
+            "Open".to_string(),
+            LocalCommentType::General,
+        );
+        store.save_thread(&resolved).unwrap();
+        store.save_thread(&open).unwrap();
+
+        let future = Utc::now() + chrono::Duration::days(1);
+        assert_eq!(store.archive_resolved_threads(future).unwrap(), 1);
+
+        let threads = store.list_threads().unwrap();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].id, open.id);
+        let archived = store.load_archived_threads().unwrap();
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].id, resolved.id);
+    }
+}
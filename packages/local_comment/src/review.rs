@@ -0,0 +1,309 @@
+//! AI review of a whole diff.
+//!
+//! A review agent is handed the entire diff, each line numbered as in the
+//! new version of its file, along with [`REVIEW_REQUEST`] asking for its
+//! findings as a JSON array. Each finding then becomes its own comment
+//! thread on the file and line it cites, falling back to a file-level or
+//! general thread when the line or file isn't part of the diff.
+
+use std::fmt::Write as _;
+
+use chadreview_local_comment_models::{LineNumber, LocalComment, LocalCommentType, LocalUser};
+use chadreview_pr_models::{DiffFile, LineType};
+use serde::Deserialize;
+
+/// Instructions given to the review agent along with the diff.
+pub const REVIEW_REQUEST: &str = "\
+Review this diff as a code reviewer. Report each problem worth fixing \
+(bugs, missing error handling, unclear code, missing tests, ...) as a \
+separate finding.
+
+Answer with a short overall summary, followed by the findings as a JSON \
+array in a ```json code block. Each finding is an object with:
+- \"path\": the file the finding is about
+- \"line\": the line number in the new version of the file (the number \
+shown before each diff line), or null if it is about the whole file
+- \"body\": the finding, in markdown
+
+Use an empty array if there is nothing to report.";
+
+/// A problem reported by the review agent.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ReviewFinding {
+    /// File the finding is about, if any.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Line in the new version of the file, if the finding is about a line.
+    #[serde(default)]
+    pub line: Option<u64>,
+    /// The finding, in markdown.
+    pub body: String,
+}
+
+/// A review agent's response, split into its summary and findings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewResponse {
+    /// The response without the findings block.
+    pub summary: String,
+    /// The findings, empty if the response had none or they couldn't be
+    /// parsed.
+    pub findings: Vec<ReviewFinding>,
+}
+
+/// Format `diffs` for the review agent: each file's hunks, with every line
+/// prefixed by its number in the new version of the file (blank for
+/// removed lines).
+#[must_use]
+pub fn format_review_diff(diffs: &[DiffFile]) -> String {
+    let mut text = String::new();
+
+    for file in diffs {
+        writeln!(text, "=== {} ({:?})", file.filename, file.status).unwrap();
+        for hunk in &file.hunks {
+            writeln!(
+                text,
+                "@@ -{},{} +{},{} @@",
+                hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+            )
+            .unwrap();
+            for line in &hunk.lines {
+                let (number, marker) = match line.line_type {
+                    LineType::Addition => (line.new_line_number, '+'),
+                    LineType::Deletion => (None, '-'),
+                    LineType::Context => (line.new_line_number, ' '),
+                };
+                let number = number.map_or_else(String::new, |n| n.to_string());
+                writeln!(text, "{number:>5} |{marker}{}", line.content).unwrap();
+            }
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+/// Split a review agent's response into its summary and findings.
+///
+/// The findings are read from the first `json` code block, or else from
+/// the outermost `[...]` of the response.
+#[must_use]
+pub fn parse_review_response(response: &str) -> ReviewResponse {
+    let Some((start, end, json)) = json_block(response, '[', ']') else {
+        return ReviewResponse {
+            summary: response.trim().to_string(),
+            findings: vec![],
+        };
+    };
+
+    serde_json::from_str(json).map_or_else(
+        |_| ReviewResponse {
+            summary: response.trim().to_string(),
+            findings: vec![],
+        },
+        |findings| ReviewResponse {
+            summary: format!("{}{}", &response[..start], &response[end..])
+                .trim()
+                .to_string(),
+            findings,
+        },
+    )
+}
+
+/// The byte range of the JSON block in an agent's `response` and the JSON
+/// in it: the first `json` code block, or else the outermost
+/// `open`...`close` pair.
+pub fn json_block(response: &str, open: char, close: char) -> Option<(usize, usize, &str)> {
+    if let Some(start) = response.find("```json") {
+        let json_start = start + "```json".len();
+        let json_end = json_start + response[json_start..].find("```")?;
+        return Some((start, json_end + 3, &response[json_start..json_end]));
+    }
+
+    let start = response.find(open)?;
+    let end = response.rfind(close)? + close.len_utf8();
+    (start < end).then(|| (start, end, &response[start..end]))
+}
+
+/// Comment threads by `author` for `findings` on `diffs`.
+///
+/// Findings on a line of the diff become line comments. Findings on a file
+/// of the diff whose line isn't in it become file comments, and findings on
+/// files outside the diff become general comments, each mentioning the
+/// location they cite.
+#[must_use]
+pub fn finding_threads(
+    findings: &[ReviewFinding],
+    diffs: &[DiffFile],
+    author: &LocalUser,
+) -> Vec<LocalComment> {
+    findings
+        .iter()
+        .filter(|finding| !finding.body.trim().is_empty())
+        .map(|finding| {
+            let file = finding
+                .path
+                .as_deref()
+                .and_then(|path| diffs.iter().find(|file| file.filename == path));
+            let (comment_type, body) = match (file, finding.line) {
+                (Some(file), Some(line)) if has_new_line(file, line) => (
+                    LocalCommentType::LineLevelComment {
+                        path: file.filename.clone(),
+                        line: LineNumber::New { line },
+                    },
+                    finding.body.clone(),
+                ),
+                (Some(file), line) => (
+                    LocalCommentType::FileLevelComment {
+                        path: file.filename.clone(),
+                    },
+                    line.map_or_else(
+                        || finding.body.clone(),
+                        |line| format!("**Line {line}:** {}", finding.body),
+                    ),
+                ),
+                (None, _) => (LocalCommentType::General, with_location(finding)),
+            };
+
+            LocalComment::new(author.clone(), body, comment_type)
+        })
+        .collect()
+}
+
+fn has_new_line(file: &DiffFile, line: u64) -> bool {
+    file.hunks
+        .iter()
+        .flat_map(|hunk| &hunk.lines)
+        .any(|diff_line| diff_line.new_line_number == Some(line))
+}
+
+/// The finding's body, led by the location it cites.
+fn with_location(finding: &ReviewFinding) -> String {
+    match (&finding.path, finding.line) {
+        (Some(path), Some(line)) => format!("**`{path}:{line}`:** {}", finding.body),
+        (Some(path), None) => format!("**`{path}`:** {}", finding.body),
+        (None, _) => finding.body.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chadreview_pr_models::{DiffHunk, DiffLine, FileStatus};
+
+    use super::*;
+
+    fn diff_line(line_type: LineType, old: Option<u64>, new: Option<u64>) -> DiffLine {
+        DiffLine {
+            line_type,
+            old_line_number: old,
+            new_line_number: new,
+            content: "code".to_string(),
+            highlighted_html: String::new(),
+        }
+    }
+
+    fn diffs() -> Vec<DiffFile> {
+        vec![DiffFile {
+            filename: "src/lib.rs".to_string(),
+            status: FileStatus::Modified,
+            additions: 1,
+            deletions: 1,
+            mode_change: None,
+            hunks: vec![DiffHunk {
+                old_start: 10,
+                old_lines: 2,
+                new_start: 10,
+                new_lines: 2,
+                lines: vec![
+                    diff_line(LineType::Context, Some(10), Some(10)),
+                    diff_line(LineType::Deletion, Some(11), None),
+                    diff_line(LineType::Addition, None, Some(11)),
+                ],
+            }],
+            decoded_lossy: false,
+            submodule: None,
+        }]
+    }
+
+    fn finding(path: Option<&str>, line: Option<u64>, body: &str) -> ReviewFinding {
+        ReviewFinding {
+            path: path.map(str::to_string),
+            line,
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_format_review_diff_numbers_new_lines() {
+        let text = format_review_diff(&diffs());
+
+        assert!(text.contains("=== src/lib.rs (Modified)"));
+        assert!(text.contains("@@ -10,2 +10,2 @@"));
+        assert!(text.contains("   10 | code"));
+        assert!(text.contains("      |-code"));
+        assert!(text.contains("   11 |+code"));
+    }
+
+    #[test]
+    fn test_parse_review_response_with_json_block() {
+        let response = parse_review_response(
+            "Mostly fine.\n\n```json\n[{\"path\": \"src/lib.rs\", \"line\": 11, \"body\": \"Unwrap can panic\"}]\n```\n",
+        );
+
+        assert_eq!(response.summary, "Mostly fine.");
+        assert_eq!(
+            response.findings,
+            vec![finding(Some("src/lib.rs"), Some(11), "Unwrap can panic")]
+        );
+    }
+
+    #[test]
+    fn test_parse_review_response_with_bare_array() {
+        let response = parse_review_response("[{\"path\": \"a.rs\", \"body\": \"Rename\"}]");
+
+        assert_eq!(response.summary, "");
+        assert_eq!(
+            response.findings,
+            vec![finding(Some("a.rs"), None, "Rename")]
+        );
+    }
+
+    #[test]
+    fn test_parse_review_response_without_findings() {
+        let response = parse_review_response("Looks good [no issues].");
+
+        assert_eq!(response.summary, "Looks good [no issues].");
+        assert!(response.findings.is_empty());
+    }
+
+    #[test]
+    fn test_finding_threads_attach_to_cited_location() {
+        let findings = vec![
+            finding(Some("src/lib.rs"), Some(11), "On a diff line"),
+            finding(Some("src/lib.rs"), Some(99), "On a line outside the diff"),
+            finding(Some("src/lib.rs"), None, "On the file"),
+            finding(Some("src/main.rs"), Some(3), "On another file"),
+            finding(None, None, "   "),
+        ];
+
+        let threads = finding_threads(&findings, &diffs(), &LocalUser::default());
+
+        assert_eq!(threads.len(), 4);
+        assert_eq!(
+            threads[0].comment_type,
+            LocalCommentType::LineLevelComment {
+                path: "src/lib.rs".to_string(),
+                line: LineNumber::New { line: 11 },
+            }
+        );
+        assert_eq!(
+            threads[1].comment_type,
+            LocalCommentType::FileLevelComment {
+                path: "src/lib.rs".to_string(),
+            }
+        );
+        assert_eq!(threads[1].body, "**Line 99:** On a line outside the diff");
+        assert_eq!(threads[2].body, "On the file");
+        assert_eq!(threads[3].comment_type, LocalCommentType::General);
+        assert_eq!(threads[3].body, "**`src/main.rs:3`:** On another file");
+    }
+}
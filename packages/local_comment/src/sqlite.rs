@@ -0,0 +1,762 @@
+//! `SQLite` storage for local comments.
+//!
+//! Keeps a repository's threads and viewed state in one database next to
+//! where its JSON files would be, so writes are transactional and listing
+//! doesn't read a file per thread. Repositories with JSON comments keep
+//! using them until they're moved over with [`migrate_json_stores`].
+//!
+//! This talks to `rusqlite` directly rather than through `switchy`'s
+//! database layer: that layer is async, while [`CommentStore`] is called
+//! synchronously from the CLI, the TUI and the routes' blocking tasks, and
+//! its transactions can't take the write lock up front the way
+//! [`update_thread`](CommentStore::update_thread)'s read-modify-write
+//! needs to stay safe against other processes.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rusqlite::types::Type;
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params};
+use switchy::uuid::Uuid;
+
+use chadreview_local_comment_models::{
+    CommentDraft, CommentThreadIndex, DraftsIndex, LocalComment, ThreadState, UsageEntry,
+    UsageLedger, ViewedFilesIndex, ViewedRepliesIndex,
+};
+
+use crate::json::JsonCommentStore;
+use crate::store::{CommentStore, LocalCommentStoreError, repo_dirs};
+
+/// Name of the database file in a repository's storage directory.
+const DATABASE_FILE: &str = "comments.db";
+
+/// How long to wait for another process holding the database lock.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+    PRAGMA journal_mode = WAL;
+
+    CREATE TABLE IF NOT EXISTS threads (
+        id          TEXT PRIMARY KEY NOT NULL,
+        thread      TEXT NOT NULL,
+        index_entry TEXT NOT NULL,
+        archived    INTEGER NOT NULL DEFAULT 0
+    );
+
+    CREATE TABLE IF NOT EXISTS viewed_files (
+        path      TEXT PRIMARY KEY NOT NULL,
+        viewed_at TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS viewed_replies (
+        reply_id  TEXT PRIMARY KEY NOT NULL,
+        viewed_at TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS drafts (
+        key      TEXT PRIMARY KEY NOT NULL,
+        body     TEXT NOT NULL,
+        saved_at TEXT NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS ai_usage (
+        id    INTEGER PRIMARY KEY,
+        entry TEXT NOT NULL
+    );
+";
+
+/// `SQLite`-backed storage for a repository's comments.
+///
+/// Threads are stored as JSON documents alongside their index entries, so
+/// the models are the same as with [`JsonCommentStore`].
+pub struct SqliteCommentStore {
+    /// Base path for this repository's comments.
+    dir: PathBuf,
+    conn: Mutex<Connection>,
+}
+
+/// What [`migrate_json_stores`] moved into a repository's database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// The repository's storage directory.
+    pub storage_path: PathBuf,
+    /// Threads copied into the database.
+    pub threads: usize,
+    /// Archived threads copied into the database.
+    pub archived_threads: usize,
+    /// Viewed files and replies copied into the database.
+    pub viewed_entries: usize,
+    /// Comment drafts copied into the database.
+    pub drafts: usize,
+    /// AI usage ledger entries copied into the database.
+    pub usage_entries: usize,
+}
+
+impl std::fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} threads, {} archived threads, {} viewed entries, {} drafts, {} usage entries",
+            self.storage_path.display(),
+            self.threads,
+            self.archived_threads,
+            self.viewed_entries,
+            self.drafts,
+            self.usage_entries
+        )
+    }
+}
+
+impl SqliteCommentStore {
+    /// Whether the storage directory `dir` uses a database: it has one
+    /// already, or has no JSON comments that would be left behind.
+    #[must_use]
+    pub fn is_used_in(dir: &Path) -> bool {
+        dir.join(DATABASE_FILE).exists() || !JsonCommentStore::new(dir.to_path_buf()).has_data()
+    }
+
+    /// Open (or create) the database in the storage directory `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory can't be created or the database
+    /// can't be opened.
+    pub fn open(dir: PathBuf) -> Result<Self, LocalCommentStoreError> {
+        std::fs::create_dir_all(&dir).map_err(LocalCommentStoreError::CreateDir)?;
+
+        let conn = Connection::open(dir.join(DATABASE_FILE))?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            dir,
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Copy the threads and viewed state of `json` into this database.
+    ///
+    /// Everything is copied in one transaction; the JSON files are left in
+    /// place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON files can't be read or the database
+    /// can't be written.
+    pub fn import_json(
+        &self,
+        json: &JsonCommentStore,
+    ) -> Result<MigrationReport, LocalCommentStoreError> {
+        let mut report = MigrationReport {
+            storage_path: self.dir.clone(),
+            threads: 0,
+            archived_threads: 0,
+            viewed_entries: 0,
+            drafts: 0,
+            usage_entries: 0,
+        };
+
+        self.write(|conn| {
+            for entry in json.list_threads()? {
+                match json.load_thread(entry.id) {
+                    Ok(thread) => {
+                        write_thread(conn, &thread, false)?;
+                        report.threads += 1;
+                    }
+                    // An index entry left behind by a deleted thread
+                    Err(LocalCommentStoreError::NotFound(_)) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+
+            for thread in json.load_archived_threads()? {
+                write_thread(conn, &thread, true)?;
+                report.archived_threads += 1;
+            }
+
+            for (path, viewed_at) in json.load_viewed_files()?.files {
+                upsert_viewed_file(conn, &path, viewed_at)?;
+                report.viewed_entries += 1;
+            }
+
+            for (reply_id, viewed_at) in json.load_viewed_replies()?.replies {
+                upsert_viewed_reply(conn, reply_id, viewed_at)?;
+                report.viewed_entries += 1;
+            }
+
+            for (key, draft) in json.load_drafts()?.drafts {
+                upsert_draft(conn, &key, &draft)?;
+                report.drafts += 1;
+            }
+
+            for entry in json.load_usage()?.entries {
+                insert_usage(conn, &entry)?;
+                report.usage_entries += 1;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(report)
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Run `f` in a transaction holding the database's write lock from the
+    /// start, so no other process can change what `f` reads before it
+    /// writes. Commits if `f` succeeds and rolls back otherwise.
+    fn write<T>(
+        &self,
+        f: impl FnOnce(&Connection) -> Result<T, LocalCommentStoreError>,
+    ) -> Result<T, LocalCommentStoreError> {
+        let mut conn = self.conn();
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let value = f(&tx)?;
+        tx.commit()?;
+        drop(conn);
+        Ok(value)
+    }
+}
+
+/// Move every repository's JSON comments into a `SQLite` database.
+///
+/// Repositories that already have a database are skipped. The JSON files
+/// are kept as a backup; once a repository has a database they're no
+/// longer read.
+///
+/// # Errors
+///
+/// Returns an error if the XDG data directory can't be read or a
+/// repository's comments can't be copied.
+pub fn migrate_json_stores() -> Result<Vec<MigrationReport>, LocalCommentStoreError> {
+    let mut reports = vec![];
+
+    for dir in repo_dirs()? {
+        if dir.join(DATABASE_FILE).exists() {
+            continue;
+        }
+
+        let json = JsonCommentStore::new(dir.clone());
+        if !json.has_data() {
+            continue;
+        }
+
+        let store = SqliteCommentStore::open(dir)?;
+        reports.push(store.import_json(&json)?);
+    }
+
+    Ok(reports)
+}
+
+fn write_thread(
+    conn: &Connection,
+    comment: &LocalComment,
+    archived: bool,
+) -> Result<(), LocalCommentStoreError> {
+    let thread = serde_json::to_string(comment).map_err(LocalCommentStoreError::Serialize)?;
+    let index_entry = serde_json::to_string(&comment.to_index_entry())
+        .map_err(LocalCommentStoreError::Serialize)?;
+
+    conn.execute(
+        "INSERT INTO threads (id, thread, index_entry, archived) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT (id) DO UPDATE SET
+             thread = excluded.thread,
+             index_entry = excluded.index_entry,
+             archived = excluded.archived",
+        params![comment.id.to_string(), thread, index_entry, archived],
+    )?;
+
+    Ok(())
+}
+
+fn read_thread(conn: &Connection, id: Uuid) -> Result<LocalComment, LocalCommentStoreError> {
+    let thread: Option<String> = conn
+        .query_row(
+            "SELECT thread FROM threads WHERE id = ?1 AND archived = 0",
+            params![id.to_string()],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    let thread = thread.ok_or(LocalCommentStoreError::NotFound(id))?;
+    serde_json::from_str(&thread).map_err(LocalCommentStoreError::Parse)
+}
+
+fn upsert_viewed_file(
+    conn: &Connection,
+    path: &str,
+    viewed_at: DateTime<Utc>,
+) -> Result<(), LocalCommentStoreError> {
+    conn.execute(
+        "INSERT INTO viewed_files (path, viewed_at) VALUES (?1, ?2)
+         ON CONFLICT (path) DO UPDATE SET viewed_at = excluded.viewed_at",
+        params![path, viewed_at],
+    )?;
+    Ok(())
+}
+
+fn upsert_viewed_reply(
+    conn: &Connection,
+    reply_id: Uuid,
+    viewed_at: DateTime<Utc>,
+) -> Result<(), LocalCommentStoreError> {
+    conn.execute(
+        "INSERT INTO viewed_replies (reply_id, viewed_at) VALUES (?1, ?2)
+         ON CONFLICT (reply_id) DO UPDATE SET viewed_at = excluded.viewed_at",
+        params![reply_id.to_string(), viewed_at],
+    )?;
+    Ok(())
+}
+
+fn upsert_draft(
+    conn: &Connection,
+    key: &str,
+    draft: &CommentDraft,
+) -> Result<(), LocalCommentStoreError> {
+    conn.execute(
+        "INSERT INTO drafts (key, body, saved_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT (key) DO UPDATE SET body = excluded.body, saved_at = excluded.saved_at",
+        params![key, draft.body, draft.saved_at],
+    )?;
+    Ok(())
+}
+
+fn insert_usage(conn: &Connection, entry: &UsageEntry) -> Result<(), LocalCommentStoreError> {
+    let entry = serde_json::to_string(entry).map_err(LocalCommentStoreError::Serialize)?;
+    conn.execute("INSERT INTO ai_usage (entry) VALUES (?1)", params![entry])?;
+    Ok(())
+}
+
+fn parse_uuid(text: &str) -> rusqlite::Result<Uuid> {
+    Uuid::parse_str(text)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(e)))
+}
+
+fn query_viewed_files(
+    conn: &Connection,
+) -> Result<HashMap<String, DateTime<Utc>>, LocalCommentStoreError> {
+    let mut statement = conn.prepare("SELECT path, viewed_at FROM viewed_files")?;
+    let files = statement
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    Ok(files)
+}
+
+fn query_viewed_replies(
+    conn: &Connection,
+) -> Result<HashMap<Uuid, DateTime<Utc>>, LocalCommentStoreError> {
+    let mut statement = conn.prepare("SELECT reply_id, viewed_at FROM viewed_replies")?;
+    let replies = statement
+        .query_map([], |row| {
+            Ok((parse_uuid(&row.get::<_, String>(0)?)?, row.get(1)?))
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(replies)
+}
+
+fn query_drafts(
+    conn: &Connection,
+) -> Result<HashMap<String, CommentDraft>, LocalCommentStoreError> {
+    let mut statement = conn.prepare("SELECT key, body, saved_at FROM drafts")?;
+    let drafts = statement
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                CommentDraft {
+                    body: row.get(1)?,
+                    saved_at: row.get(2)?,
+                },
+            ))
+        })?
+        .collect::<Result<_, _>>()?;
+    Ok(drafts)
+}
+
+fn query_usage(conn: &Connection) -> Result<Vec<UsageEntry>, LocalCommentStoreError> {
+    let mut statement = conn.prepare("SELECT entry FROM ai_usage ORDER BY id")?;
+    let entries = statement
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    entries
+        .iter()
+        .map(|entry| serde_json::from_str(entry).map_err(LocalCommentStoreError::Parse))
+        .collect()
+}
+
+fn list_index_entries(
+    conn: &Connection,
+) -> Result<Vec<CommentThreadIndex>, LocalCommentStoreError> {
+    let mut statement =
+        conn.prepare("SELECT index_entry FROM threads WHERE archived = 0 ORDER BY rowid")?;
+    let entries = statement
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    entries
+        .iter()
+        .map(|entry| serde_json::from_str(entry).map_err(LocalCommentStoreError::Parse))
+        .collect()
+}
+
+impl CommentStore for SqliteCommentStore {
+    fn save_thread(&self, comment: &LocalComment) -> Result<(), LocalCommentStoreError> {
+        write_thread(&self.conn(), comment, false)
+    }
+
+    fn load_thread(&self, id: Uuid) -> Result<LocalComment, LocalCommentStoreError> {
+        read_thread(&self.conn(), id)
+    }
+
+    fn delete_thread(&self, id: Uuid) -> Result<(), LocalCommentStoreError> {
+        let deleted = self.conn().execute(
+            "DELETE FROM threads WHERE id = ?1 AND archived = 0",
+            params![id.to_string()],
+        )?;
+
+        if deleted == 0 {
+            return Err(LocalCommentStoreError::NotFound(id));
+        }
+
+        Ok(())
+    }
+
+    fn list_threads(&self) -> Result<Vec<CommentThreadIndex>, LocalCommentStoreError> {
+        list_index_entries(&self.conn())
+    }
+
+    fn update_thread(
+        &self,
+        id: Uuid,
+        update: &mut dyn FnMut(&mut LocalComment) -> Result<bool, LocalCommentStoreError>,
+    ) -> Result<bool, LocalCommentStoreError> {
+        self.write(|conn| {
+            let mut thread = read_thread(conn, id)?;
+            let changed = update(&mut thread)?;
+            if changed {
+                write_thread(conn, &thread, false)?;
+            }
+            Ok(changed)
+        })
+    }
+
+    fn load_viewed_files(&self) -> Result<ViewedFilesIndex, LocalCommentStoreError> {
+        Ok(ViewedFilesIndex {
+            files: query_viewed_files(&self.conn())?,
+        })
+    }
+
+    fn save_viewed_files(&self, index: &ViewedFilesIndex) -> Result<(), LocalCommentStoreError> {
+        self.write(|conn| {
+            conn.execute("DELETE FROM viewed_files", [])?;
+            for (path, viewed_at) in &index.files {
+                upsert_viewed_file(conn, path, *viewed_at)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn load_viewed_replies(&self) -> Result<ViewedRepliesIndex, LocalCommentStoreError> {
+        Ok(ViewedRepliesIndex {
+            replies: query_viewed_replies(&self.conn())?,
+        })
+    }
+
+    fn save_viewed_replies(
+        &self,
+        index: &ViewedRepliesIndex,
+    ) -> Result<(), LocalCommentStoreError> {
+        self.write(|conn| {
+            conn.execute("DELETE FROM viewed_replies", [])?;
+            for (reply_id, viewed_at) in &index.replies {
+                upsert_viewed_reply(conn, *reply_id, *viewed_at)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn load_drafts(&self) -> Result<DraftsIndex, LocalCommentStoreError> {
+        Ok(DraftsIndex {
+            drafts: query_drafts(&self.conn())?,
+        })
+    }
+
+    fn save_drafts(&self, index: &DraftsIndex) -> Result<(), LocalCommentStoreError> {
+        self.write(|conn| {
+            conn.execute("DELETE FROM drafts", [])?;
+            for (key, draft) in &index.drafts {
+                upsert_draft(conn, key, draft)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn save_draft(&self, key: &str, body: &str) -> Result<(), LocalCommentStoreError> {
+        if body.trim().is_empty() {
+            self.conn()
+                .execute("DELETE FROM drafts WHERE key = ?1", params![key])?;
+            return Ok(());
+        }
+
+        let draft = CommentDraft {
+            body: body.to_string(),
+            saved_at: Utc::now(),
+        };
+        upsert_draft(&self.conn(), key, &draft)
+    }
+
+    fn load_usage(&self) -> Result<UsageLedger, LocalCommentStoreError> {
+        Ok(UsageLedger {
+            entries: query_usage(&self.conn())?,
+        })
+    }
+
+    fn save_usage(&self, ledger: &UsageLedger) -> Result<(), LocalCommentStoreError> {
+        self.write(|conn| {
+            conn.execute("DELETE FROM ai_usage", [])?;
+            for entry in &ledger.entries {
+                insert_usage(conn, entry)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn record_usage(&self, entry: UsageEntry) -> Result<(), LocalCommentStoreError> {
+        insert_usage(&self.conn(), &entry)
+    }
+
+    fn mark_file_viewed(&self, path: &str) -> Result<(), LocalCommentStoreError> {
+        upsert_viewed_file(&self.conn(), path, Utc::now())
+    }
+
+    fn mark_file_unviewed(&self, path: &str) -> Result<(), LocalCommentStoreError> {
+        self.conn()
+            .execute("DELETE FROM viewed_files WHERE path = ?1", params![path])?;
+        Ok(())
+    }
+
+    fn mark_reply_viewed(&self, reply_id: Uuid) -> Result<(), LocalCommentStoreError> {
+        upsert_viewed_reply(&self.conn(), reply_id, Utc::now())
+    }
+
+    fn mark_reply_unviewed(&self, reply_id: Uuid) -> Result<(), LocalCommentStoreError> {
+        self.conn().execute(
+            "DELETE FROM viewed_replies WHERE reply_id = ?1",
+            params![reply_id.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Flags the threads as archived, keeping their rows.
+    fn archive_resolved_threads(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<usize, LocalCommentStoreError> {
+        self.write(|conn| {
+            let stale: Vec<Uuid> = list_index_entries(conn)?
+                .into_iter()
+                .filter(|entry| entry.state == ThreadState::Resolved && entry.updated_at < cutoff)
+                .map(|entry| entry.id)
+                .collect();
+
+            for id in &stale {
+                conn.execute(
+                    "UPDATE threads SET archived = 1 WHERE id = ?1",
+                    params![id.to_string()],
+                )?;
+            }
+
+            Ok(stale.len())
+        })
+    }
+
+    fn storage_path(&self) -> &Path {
+        &self.dir
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chadreview_local_comment_models::{LocalCommentType, LocalUser, TokenUsage};
+    use std::env;
+
+    fn temp_dir() -> PathBuf {
+        env::temp_dir().join(format!("chadreview-test-{}", Uuid::new_v4()))
+    }
+
+    fn comment(body: &str) -> LocalComment {
+        LocalComment::new(
+            LocalUser::default(),
+            body.to_string(),
+            LocalCommentType::General,
+        )
+    }
+
+    #[test]
+    fn test_save_load_list_and_delete_threads() {
+        let store = SqliteCommentStore::open(temp_dir()).unwrap();
+        let first = comment("First");
+        let mut second = comment("Second");
+
+        store.save_thread(&first).unwrap();
+        store.save_thread(&second).unwrap();
+        second.body = "Second, edited".to_string();
+        store.save_thread(&second).unwrap();
+
+        let ids: Vec<_> = store.list_threads().unwrap().iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![first.id, second.id]);
+        assert_eq!(store.load_thread(second.id).unwrap().body, "Second, edited");
+
+        store.delete_thread(first.id).unwrap();
+        assert!(matches!(
+            store.load_thread(first.id),
+            Err(LocalCommentStoreError::NotFound(_))
+        ));
+        assert!(store.delete_thread(first.id).is_err());
+    }
+
+    #[test]
+    fn test_update_thread_saves_nothing_on_error() {
+        let store = SqliteCommentStore::open(temp_dir()).unwrap();
+        let thread = comment("Thread");
+        store.save_thread(&thread).unwrap();
+
+        let missing = Uuid::new_v4();
+        assert!(store.delete_reply(thread.id, missing).is_err());
+        assert!(
+            store
+                .update_thread(thread.id, &mut |thread| {
+                    thread.body = "Changed".to_string();
+                    Err(LocalCommentStoreError::NotFound(missing))
+                })
+                .is_err()
+        );
+        assert_eq!(store.load_thread(thread.id).unwrap().body, "Thread");
+
+        store.add_reply(thread.id, comment("Reply")).unwrap();
+        assert_eq!(store.load_thread(thread.id).unwrap().replies.len(), 1);
+    }
+
+    #[test]
+    fn test_viewed_state() {
+        let store = SqliteCommentStore::open(temp_dir()).unwrap();
+        let reply_id = Uuid::new_v4();
+
+        store.mark_file_viewed("src/main.rs").unwrap();
+        store.mark_file_viewed("src/lib.rs").unwrap();
+        store.mark_file_unviewed("src/lib.rs").unwrap();
+        store.mark_reply_viewed(reply_id).unwrap();
+
+        assert!(store.is_file_viewed("src/main.rs"));
+        assert!(!store.is_file_viewed("src/lib.rs"));
+        assert!(store.is_reply_viewed(reply_id));
+
+        store
+            .save_viewed_replies(&ViewedRepliesIndex::default())
+            .unwrap();
+        assert!(!store.is_reply_viewed(reply_id));
+    }
+
+    #[test]
+    fn test_drafts() {
+        let store = SqliteCommentStore::open(temp_dir()).unwrap();
+
+        store.save_draft("local-reply-form-1", "First try").unwrap();
+        store
+            .save_draft("local-reply-form-1", "Second try")
+            .unwrap();
+        store.save_draft("local-reply-form-2", "Gone").unwrap();
+        store.save_draft("local-reply-form-2", "").unwrap();
+
+        let drafts = store.get_draft_bodies().unwrap();
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts["local-reply-form-1"], "Second try");
+    }
+
+    #[test]
+    fn test_usage() {
+        let store = SqliteCommentStore::open(temp_dir()).unwrap();
+
+        for (model, cost) in [("first", Some(1.5)), ("second", None)] {
+            store
+                .record_usage(UsageEntry {
+                    comment_id: None,
+                    recorded_at: Utc::now(),
+                    model: model.to_string(),
+                    tokens: TokenUsage {
+                        input: 10,
+                        output: 5,
+                    },
+                    cost,
+                })
+                .unwrap();
+        }
+
+        let ledger = store.load_usage().unwrap();
+        let models: Vec<_> = ledger.entries.iter().map(|e| e.model.as_str()).collect();
+        assert_eq!(models, vec!["first", "second"]);
+        assert!((ledger.total_cost() - 1.5).abs() < f64::EPSILON);
+
+        store.save_usage(&UsageLedger::default()).unwrap();
+        assert!(store.load_usage().unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn test_archive_resolved_threads() {
+        let store = SqliteCommentStore::open(temp_dir()).unwrap();
+        let mut resolved = comment("Resolved");
+        resolved.state = ThreadState::Resolved;
+        let open = comment("Open");
+        store.save_thread(&resolved).unwrap();
+        store.save_thread(&open).unwrap();
+
+        let future = Utc::now() + chrono::Duration::days(1);
+        assert_eq!(store.archive_resolved_threads(future).unwrap(), 1);
+
+        let threads = store.list_threads().unwrap();
+        assert_eq!(threads.len(), 1);
+        assert_eq!(threads[0].id, open.id);
+        assert!(store.load_thread(resolved.id).is_err());
+    }
+
+    #[test]
+    fn test_import_json() {
+        let dir = temp_dir();
+        let json = JsonCommentStore::new(dir.clone());
+        let mut resolved = comment("Resolved");
+        resolved.state = ThreadState::Resolved;
+        let open = comment("Open");
+        json.save_thread(&resolved).unwrap();
+        json.save_thread(&open).unwrap();
+        json.archive_resolved_threads(Utc::now() + chrono::Duration::days(1))
+            .unwrap();
+        json.mark_file_viewed("src/main.rs").unwrap();
+        json.record_usage(UsageEntry {
+            comment_id: Some(open.id),
+            recorded_at: Utc::now(),
+            model: "model".to_string(),
+            tokens: TokenUsage::default(),
+            cost: Some(0.1),
+        })
+        .unwrap();
+        assert!(!SqliteCommentStore::is_used_in(&dir));
+
+        let store = SqliteCommentStore::open(dir.clone()).unwrap();
+        let report = store.import_json(&json).unwrap();
+
+        assert_eq!(report.threads, 1);
+        assert_eq!(report.archived_threads, 1);
+        assert_eq!(report.viewed_entries, 1);
+        assert_eq!(report.usage_entries, 1);
+        assert!(SqliteCommentStore::is_used_in(&dir));
+        assert_eq!(store.load_thread(open.id).unwrap().body, "Open");
+        assert!(store.load_thread(resolved.id).is_err());
+        assert!(store.is_file_viewed("src/main.rs"));
+    }
+}
@@ -1,19 +1,30 @@
-//! XDG-compliant file-based storage for local comments.
-
-use std::collections::HashMap;
+//! The comment store interface and the per-repository store handle.
+//!
+//! Every repository's comments live in their own directory under
+//! `$XDG_DATA_HOME/chadreview/comments/{repo-hash}/`, either as JSON files
+//! ([`JsonCommentStore`]) or, with the `sqlite` feature, in a `SQLite`
+//! database (`SqliteCommentStore`). With the `git-notes` feature, a
+//! repository can instead keep its threads in git notes in the repository
+//! itself (`NotesCommentStore`). [`LocalCommentStore`] opens whichever one
+//! a repository uses.
+
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
-use std::fs::{self, File};
-use std::io::{BufReader, BufWriter};
+use std::fs;
+use std::ops::Deref;
 use std::path::{Path, PathBuf};
-use std::sync::RwLock;
 
+use chrono::{DateTime, Utc};
 use sha2::{Digest, Sha256};
 use switchy::uuid::Uuid;
 
 use chadreview_local_comment_models::{
-    AiExecutionStatus, CommentThreadIndex, LocalComment, LocalCommentType,
+    AiExecutionStatus, CommentDraft, CommentThreadIndex, DraftsIndex, LineAnchor, LineNumber,
+    LocalComment, LocalCommentType, UsageEntry, UsageLedger, ViewedFilesIndex, ViewedRepliesIndex,
 };
 
+use crate::json::JsonCommentStore;
+
 /// Errors that can occur when using the local comment store.
 #[derive(Debug, thiserror::Error)]
 pub enum LocalCommentStoreError {
@@ -41,150 +52,158 @@ pub enum LocalCommentStoreError {
     #[error("Comment not found: {0}")]
     NotFound(Uuid),
 
+    /// The thread isn't attached to a line.
+    #[error("Comment is not a line comment: {0}")]
+    NotLineComment(Uuid),
+
+    /// An attachment has an unsupported type or size.
+    #[error("Invalid attachment: {0}")]
+    InvalidAttachment(String),
+
     /// Could not determine data directory.
     #[error("Could not determine XDG data directory")]
     NoDataDir,
-}
 
-/// XDG-compliant file-based storage for local comments.
-///
-/// Storage layout:
-/// ```text
-/// $XDG_DATA_HOME/chadreview/comments/{repo-hash}/
-/// ├── index.json          # Thread index for fast listing
-/// └── threads/
-///     ├── {uuid}.json     # Individual comment threads
-///     └── ...
-/// ```
-pub struct LocalCommentStore {
-    /// Base path for this repository's comments.
-    repo_path: PathBuf,
-    /// In-memory cache of comment threads (thread-safe).
-    cache: RwLock<HashMap<Uuid, LocalComment>>,
+    /// A database query failed.
+    #[cfg(feature = "sqlite")]
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    /// A git operation failed.
+    #[cfg(feature = "git-notes")]
+    #[error("Git error: {0}")]
+    Git(#[from] chadreview_git_backend::GitBackendError),
 }
 
-impl LocalCommentStore {
-    /// Create a new store for the given repository path.
+/// Storage for a repository's comment threads and viewed state.
+///
+/// Implementations provide the basic thread and viewed-state storage; the
+/// operations built on top of it (replies, AI status, viewed toggles,
+/// maintenance) are provided in terms of [`CommentStore::update_thread`],
+/// so a backend that can update a thread atomically makes all of them
+/// atomic.
+pub trait CommentStore: Send + Sync {
+    /// Save a comment thread, replacing any thread with the same ID.
     ///
     /// # Errors
     ///
-    /// Returns an error if the XDG data directory cannot be determined.
-    pub fn new(repo_path: &Path) -> Result<Self, LocalCommentStoreError> {
-        let base_path = Self::get_storage_path(repo_path)?;
-
-        Ok(Self {
-            repo_path: base_path,
-            cache: RwLock::new(HashMap::new()),
-        })
-    }
-
-    /// Get the XDG-compliant storage path for a repository.
-    fn get_storage_path(repo_path: &Path) -> Result<PathBuf, LocalCommentStoreError> {
-        let data_dir = dirs::data_dir().ok_or(LocalCommentStoreError::NoDataDir)?;
-
-        // Create a hash of the canonical repo path for the directory name
-        let canonical = repo_path
-            .canonicalize()
-            .unwrap_or_else(|_| repo_path.to_path_buf());
-        let repo_hash = Self::hash_path(&canonical);
+    /// Returns an error if writing to storage fails.
+    fn save_thread(&self, comment: &LocalComment) -> Result<(), LocalCommentStoreError>;
 
-        Ok(data_dir.join("chadreview").join("comments").join(repo_hash))
-    }
-
-    /// Hash a path to create a directory-safe identifier.
-    fn hash_path(path: &Path) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(path.to_string_lossy().as_bytes());
-        let result = hasher.finalize();
-        // Use first 16 bytes (32 hex chars) for reasonable uniqueness
-        result[..16]
-            .iter()
-            .fold(String::with_capacity(32), |mut acc, b| {
-                write!(acc, "{b:02x}").unwrap();
-                acc
-            })
-    }
+    /// Load a comment thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thread doesn't exist or can't be read.
+    fn load_thread(&self, id: Uuid) -> Result<LocalComment, LocalCommentStoreError>;
 
-    /// Ensure storage directories exist.
-    fn ensure_dirs(&self) -> Result<(), LocalCommentStoreError> {
-        let threads_dir = self.repo_path.join("threads");
-        fs::create_dir_all(&threads_dir).map_err(LocalCommentStoreError::CreateDir)?;
-        Ok(())
-    }
+    /// Delete a comment thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thread doesn't exist or can't be deleted.
+    fn delete_thread(&self, id: Uuid) -> Result<(), LocalCommentStoreError>;
 
-    /// Get the path to a thread file.
-    fn thread_path(&self, id: Uuid) -> PathBuf {
-        self.repo_path.join("threads").join(format!("{id}.json"))
-    }
+    /// List all comment threads, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index can't be read.
+    fn list_threads(&self) -> Result<Vec<CommentThreadIndex>, LocalCommentStoreError>;
 
-    /// Get the path to the index file.
-    fn index_path(&self) -> PathBuf {
-        self.repo_path.join("index.json")
-    }
+    /// Load a thread, apply `update` to it and save it if `update` returns
+    /// `true`. Returns what `update` returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thread doesn't exist, can't be saved, or
+    /// `update` fails (in which case nothing is saved).
+    fn update_thread(
+        &self,
+        id: Uuid,
+        update: &mut dyn FnMut(&mut LocalComment) -> Result<bool, LocalCommentStoreError>,
+    ) -> Result<bool, LocalCommentStoreError>;
 
-    /// Save a comment thread to disk.
+    /// Load the viewed files index.
     ///
     /// # Errors
     ///
-    /// Returns an error if writing to disk fails.
-    pub fn save_thread(&self, comment: &LocalComment) -> Result<(), LocalCommentStoreError> {
-        self.ensure_dirs()?;
+    /// Returns an error if the index exists but cannot be read.
+    fn load_viewed_files(&self) -> Result<ViewedFilesIndex, LocalCommentStoreError>;
 
-        // Save thread file
-        let path = self.thread_path(comment.id);
-        let file = File::create(&path).map_err(LocalCommentStoreError::Write)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, comment).map_err(LocalCommentStoreError::Serialize)?;
+    /// Save the viewed files index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index cannot be written.
+    fn save_viewed_files(&self, index: &ViewedFilesIndex) -> Result<(), LocalCommentStoreError>;
 
-        // Update cache
-        if let Ok(mut cache) = self.cache.write() {
-            cache.insert(comment.id, comment.clone());
-        }
+    /// Load the viewed replies index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index exists but cannot be read.
+    fn load_viewed_replies(&self) -> Result<ViewedRepliesIndex, LocalCommentStoreError>;
 
-        // Update index
-        self.update_index(comment)?;
+    /// Save the viewed replies index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index cannot be written.
+    fn save_viewed_replies(&self, index: &ViewedRepliesIndex)
+    -> Result<(), LocalCommentStoreError>;
 
-        Ok(())
-    }
+    /// Load the comment drafts index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index exists but cannot be read.
+    fn load_drafts(&self) -> Result<DraftsIndex, LocalCommentStoreError>;
 
-    /// Load a comment thread from disk.
+    /// Save the comment drafts index.
     ///
     /// # Errors
     ///
-    /// Returns an error if the thread doesn't exist or can't be read.
-    pub fn load_thread(&self, id: Uuid) -> Result<LocalComment, LocalCommentStoreError> {
-        // Check cache first
-        if let Ok(cache) = self.cache.read()
-            && let Some(comment) = cache.get(&id)
-        {
-            return Ok(comment.clone());
-        }
+    /// Returns an error if the index cannot be written.
+    fn save_drafts(&self, index: &DraftsIndex) -> Result<(), LocalCommentStoreError>;
 
-        // Load from disk
-        let path = self.thread_path(id);
-        if !path.exists() {
-            return Err(LocalCommentStoreError::NotFound(id));
-        }
+    /// Load the AI usage ledger.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ledger exists but cannot be read.
+    fn load_usage(&self) -> Result<UsageLedger, LocalCommentStoreError>;
 
-        let file = File::open(&path).map_err(LocalCommentStoreError::Read)?;
-        let reader = BufReader::new(file);
-        let comment: LocalComment =
-            serde_json::from_reader(reader).map_err(LocalCommentStoreError::Parse)?;
+    /// Save the AI usage ledger.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ledger cannot be written.
+    fn save_usage(&self, ledger: &UsageLedger) -> Result<(), LocalCommentStoreError>;
 
-        // Update cache
-        if let Ok(mut cache) = self.cache.write() {
-            cache.insert(id, comment.clone());
-        }
+    /// Archive resolved threads with no activity since `cutoff`.
+    ///
+    /// Archived threads no longer load or list, but are kept. Returns the
+    /// number of threads archived.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index can't be read or a thread can't be
+    /// archived.
+    fn archive_resolved_threads(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> Result<usize, LocalCommentStoreError>;
 
-        Ok(comment)
-    }
+    /// Get the storage base path for this repository.
+    fn storage_path(&self) -> &Path;
 
     /// Get a comment by ID (may be a nested reply).
     ///
     /// # Errors
     ///
     /// Returns an error if the comment is not found.
-    pub fn get_comment(&self, id: Uuid) -> Result<LocalComment, LocalCommentStoreError> {
+    fn get_comment(&self, id: Uuid) -> Result<LocalComment, LocalCommentStoreError> {
         // First try loading as a root thread
         if let Ok(comment) = self.load_thread(id) {
             return Ok(comment);
@@ -193,115 +212,25 @@ impl LocalCommentStore {
         // Search through all threads for a reply with this ID
         for entry in self.list_threads()? {
             let thread = self.load_thread(entry.id)?;
-            if let Some(comment) = Self::find_comment_in_thread(&thread, id) {
-                return Ok(comment);
+            if let Some(comment) = find_comment_in_thread(&thread, id) {
+                return Ok(comment.clone());
             }
         }
 
         Err(LocalCommentStoreError::NotFound(id))
     }
 
-    /// Find a comment within a thread (including replies).
-    fn find_comment_in_thread(comment: &LocalComment, id: Uuid) -> Option<LocalComment> {
-        if comment.id == id {
-            return Some(comment.clone());
-        }
-        for reply in &comment.replies {
-            if let Some(found) = Self::find_comment_in_thread(reply, id) {
-                return Some(found);
-            }
-        }
-        None
-    }
-
-    /// Delete a comment thread.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the thread doesn't exist or can't be deleted.
-    pub fn delete_thread(&self, id: Uuid) -> Result<(), LocalCommentStoreError> {
-        let path = self.thread_path(id);
-        if !path.exists() {
-            return Err(LocalCommentStoreError::NotFound(id));
-        }
-
-        fs::remove_file(&path).map_err(LocalCommentStoreError::Write)?;
-
-        // Remove from cache
-        if let Ok(mut cache) = self.cache.write() {
-            cache.remove(&id);
-        }
-
-        // Remove from index
-        self.remove_from_index(id)?;
-
-        Ok(())
-    }
-
-    /// Update the thread index.
-    fn update_index(&self, comment: &LocalComment) -> Result<(), LocalCommentStoreError> {
-        let mut index = self.load_index().unwrap_or_default();
-
-        // Update or add entry
-        let entry = comment.to_index_entry();
-        if let Some(existing) = index.iter_mut().find(|e| e.id == comment.id) {
-            *existing = entry;
-        } else {
-            index.push(entry);
-        }
-
-        self.save_index(&index)
-    }
-
-    /// Remove a thread from the index.
-    fn remove_from_index(&self, id: Uuid) -> Result<(), LocalCommentStoreError> {
-        let mut index = self.load_index().unwrap_or_default();
-        index.retain(|e| e.id != id);
-        self.save_index(&index)
-    }
-
-    /// Load the thread index.
-    fn load_index(&self) -> Result<Vec<CommentThreadIndex>, LocalCommentStoreError> {
-        let path = self.index_path();
-        if !path.exists() {
-            return Ok(vec![]);
-        }
-
-        let file = File::open(&path).map_err(LocalCommentStoreError::Read)?;
-        let reader = BufReader::new(file);
-        serde_json::from_reader(reader).map_err(LocalCommentStoreError::Parse)
-    }
-
-    /// Save the thread index.
-    fn save_index(&self, index: &[CommentThreadIndex]) -> Result<(), LocalCommentStoreError> {
-        self.ensure_dirs()?;
-
-        let path = self.index_path();
-        let file = File::create(&path).map_err(LocalCommentStoreError::Write)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, index).map_err(LocalCommentStoreError::Serialize)
-    }
-
-    /// List all comment threads.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the index can't be read.
-    pub fn list_threads(&self) -> Result<Vec<CommentThreadIndex>, LocalCommentStoreError> {
-        self.load_index()
-    }
-
     /// List threads for a specific file.
     ///
     /// # Errors
     ///
     /// Returns an error if the index can't be read.
-    pub fn list_threads_for_file(
+    fn list_threads_for_file(
         &self,
         path: &str,
     ) -> Result<Vec<CommentThreadIndex>, LocalCommentStoreError> {
-        let index = self.load_index()?;
-        Ok(index
+        Ok(self
+            .list_threads()?
             .into_iter()
             .filter(|entry| match &entry.comment_type {
                 LocalCommentType::FileLevelComment { path: p }
@@ -316,13 +245,13 @@ impl LocalCommentStore {
     /// # Errors
     ///
     /// Returns an error if the index can't be read.
-    pub fn list_threads_for_line(
+    fn list_threads_for_line(
         &self,
         path: &str,
-        line: chadreview_local_comment_models::LineNumber,
+        line: LineNumber,
     ) -> Result<Vec<CommentThreadIndex>, LocalCommentStoreError> {
-        let index = self.load_index()?;
-        Ok(index
+        Ok(self
+            .list_threads()?
             .into_iter()
             .filter(|entry| {
                 matches!(
@@ -339,15 +268,17 @@ impl LocalCommentStore {
     /// # Errors
     ///
     /// Returns an error if the thread doesn't exist or can't be updated.
-    pub fn add_reply(
+    fn add_reply(
         &self,
         thread_id: Uuid,
         reply: LocalComment,
     ) -> Result<(), LocalCommentStoreError> {
-        let mut thread = self.load_thread(thread_id)?;
-        thread.replies.push(reply);
-        thread.updated_at = chrono::Utc::now();
-        self.save_thread(&thread)
+        self.update_thread(thread_id, &mut |thread| {
+            thread.replies.push(reply.clone());
+            thread.updated_at = Utc::now();
+            Ok(true)
+        })
+        .map(|_| ())
     }
 
     /// Update the AI execution status for a comment.
@@ -355,22 +286,16 @@ impl LocalCommentStore {
     /// # Errors
     ///
     /// Returns an error if the thread doesn't exist or can't be updated.
-    pub fn update_ai_status(
+    fn update_ai_status(
         &self,
         thread_id: Uuid,
         status: AiExecutionStatus,
     ) -> Result<(), LocalCommentStoreError> {
-        let mut thread = self.load_thread(thread_id)?;
-        thread.ai_status = Some(status);
-        thread.updated_at = chrono::Utc::now();
-        self.save_thread(&thread)
+        self.update_reply_ai_status(thread_id, thread_id, status)
     }
 
     /// Update the AI execution status for a comment (root or reply).
     ///
-    /// This method handles both root comments and nested replies by loading
-    /// the thread and finding the appropriate comment to update.
-    ///
     /// # Arguments
     /// * `thread_id` - The root thread ID
     /// * `comment_id` - The comment to update (can be the same as `thread_id` for root comments)
@@ -379,44 +304,20 @@ impl LocalCommentStore {
     /// # Errors
     ///
     /// Returns an error if the thread doesn't exist, the comment isn't found, or can't be updated.
-    pub fn update_reply_ai_status(
+    fn update_reply_ai_status(
         &self,
         thread_id: Uuid,
         comment_id: Uuid,
         status: AiExecutionStatus,
     ) -> Result<(), LocalCommentStoreError> {
-        let mut thread = self.load_thread(thread_id)?;
-
-        if thread_id == comment_id {
-            // Updating the root comment itself
-            thread.ai_status = Some(status);
-        } else {
-            // Find and update the reply
-            if !Self::update_reply_status_recursive(&mut thread.replies, comment_id, &status) {
-                return Err(LocalCommentStoreError::NotFound(comment_id));
-            }
-        }
-
-        thread.updated_at = chrono::Utc::now();
-        self.save_thread(&thread)
-    }
-
-    /// Recursively find and update a reply's AI status.
-    fn update_reply_status_recursive(
-        replies: &mut [LocalComment],
-        target_id: Uuid,
-        status: &AiExecutionStatus,
-    ) -> bool {
-        for reply in replies.iter_mut() {
-            if reply.id == target_id {
-                reply.ai_status = Some(status.clone());
-                return true;
-            }
-            if Self::update_reply_status_recursive(&mut reply.replies, target_id, status) {
-                return true;
-            }
-        }
-        false
+        self.update_thread(thread_id, &mut |thread| {
+            let comment = find_comment_in_thread_mut(thread, comment_id)
+                .ok_or(LocalCommentStoreError::NotFound(comment_id))?;
+            comment.ai_status = Some(status.clone());
+            thread.updated_at = Utc::now();
+            Ok(true)
+        })
+        .map(|_| ())
     }
 
     /// Update the `OpenCode` session ID for a thread.
@@ -424,118 +325,113 @@ impl LocalCommentStore {
     /// This is used to continue conversations with `OpenCode` by passing
     /// the session ID to subsequent executions.
     ///
-    /// # Arguments
-    /// * `thread_id` - The root thread ID
-    /// * `session_id` - The `OpenCode` session ID
-    ///
     /// # Errors
     ///
     /// Returns an error if the thread doesn't exist or can't be updated.
-    pub fn update_session_id(
+    fn update_session_id(
         &self,
         thread_id: Uuid,
         session_id: String,
     ) -> Result<(), LocalCommentStoreError> {
-        let mut thread = self.load_thread(thread_id)?;
-        thread.opencode_session_id = Some(session_id);
-        thread.updated_at = chrono::Utc::now();
-        self.save_thread(&thread)
+        self.update_thread(thread_id, &mut |thread| {
+            thread.opencode_session_id = Some(session_id.clone());
+            thread.updated_at = Utc::now();
+            Ok(true)
+        })
+        .map(|_| ())
     }
 
-    /// Delete a reply from a thread.
+    /// Delete a reply (and all its nested replies) from a thread.
     ///
-    /// This removes a specific reply (and all its nested replies) from a thread.
+    /// # Errors
     ///
-    /// # Arguments
-    /// * `thread_id` - The root thread ID
-    /// * `reply_id` - The reply to delete
+    /// Returns an error if the thread doesn't exist, the reply isn't found, or can't be updated.
+    fn delete_reply(&self, thread_id: Uuid, reply_id: Uuid) -> Result<(), LocalCommentStoreError> {
+        self.update_thread(thread_id, &mut |thread| {
+            if !remove_reply_recursive(&mut thread.replies, reply_id) {
+                return Err(LocalCommentStoreError::NotFound(reply_id));
+            }
+            thread.updated_at = Utc::now();
+            Ok(true)
+        })
+        .map(|_| ())
+    }
+
+    /// Replace the body of a comment in a thread (the root or a reply at
+    /// any depth), keeping the previous body in its revisions. Returns the
+    /// edited comment.
     ///
     /// # Errors
     ///
-    /// Returns an error if the thread doesn't exist, the reply isn't found, or can't be updated.
-    pub fn delete_reply(
+    /// Returns an error if the thread doesn't exist, the comment isn't found, or can't be updated.
+    fn edit_comment(
         &self,
         thread_id: Uuid,
-        reply_id: Uuid,
-    ) -> Result<(), LocalCommentStoreError> {
-        let mut thread = self.load_thread(thread_id)?;
-
-        if !Self::remove_reply_recursive(&mut thread.replies, reply_id) {
-            return Err(LocalCommentStoreError::NotFound(reply_id));
-        }
-
-        thread.updated_at = chrono::Utc::now();
-        self.save_thread(&thread)
-    }
-
-    /// Recursively find and remove a reply from a thread.
-    fn remove_reply_recursive(replies: &mut Vec<LocalComment>, target_id: Uuid) -> bool {
-        // First check if it's a direct child
-        if let Some(pos) = replies.iter().position(|r| r.id == target_id) {
-            replies.remove(pos);
-            return true;
-        }
-        // Otherwise recurse into children
-        for reply in replies.iter_mut() {
-            if Self::remove_reply_recursive(&mut reply.replies, target_id) {
-                return true;
+        comment_id: Uuid,
+        body: String,
+    ) -> Result<LocalComment, LocalCommentStoreError> {
+        let mut edited = None;
+        self.update_thread(thread_id, &mut |thread| {
+            let now = Utc::now();
+            let comment = find_comment_in_thread_mut(thread, comment_id)
+                .ok_or(LocalCommentStoreError::NotFound(comment_id))?;
+            let changed = comment.edit(body.clone(), now);
+            edited = Some(comment.clone());
+            if changed {
+                thread.updated_at = now;
             }
-        }
-        false
-    }
-
-    /// Get the storage base path for this repository.
-    #[must_use]
-    pub fn storage_path(&self) -> &Path {
-        &self.repo_path
-    }
+            Ok(changed)
+        })?;
 
-    // =========================================================================
-    // Viewed Files
-    // =========================================================================
-
-    /// Get the path to the viewed files index.
-    fn viewed_files_path(&self) -> PathBuf {
-        self.repo_path.join("viewed_files.json")
+        edited.ok_or(LocalCommentStoreError::NotFound(comment_id))
     }
 
-    /// Load the viewed files index.
-    ///
-    /// Returns an empty index if the file doesn't exist.
+    /// Record the excerpt a line thread was made on, unless it already has
+    /// one. Returns whether the anchor was recorded.
     ///
     /// # Errors
     ///
-    /// Returns an error if the file exists but cannot be read or parsed.
-    pub fn load_viewed_files(
+    /// Returns an error if the thread doesn't exist or can't be updated.
+    fn record_anchor(
         &self,
-    ) -> Result<chadreview_local_comment_models::ViewedFilesIndex, LocalCommentStoreError> {
-        let path = self.viewed_files_path();
-
-        if !path.exists() {
-            return Ok(chadreview_local_comment_models::ViewedFilesIndex::default());
-        }
-
-        let file = File::open(&path).map_err(LocalCommentStoreError::Read)?;
-        let reader = BufReader::new(file);
-        serde_json::from_reader(reader).map_err(LocalCommentStoreError::Parse)
+        thread_id: Uuid,
+        anchor: &LineAnchor,
+    ) -> Result<bool, LocalCommentStoreError> {
+        self.update_thread(thread_id, &mut |thread| {
+            if thread.anchor.is_some() {
+                return Ok(false);
+            }
+            thread.anchor = Some(anchor.clone());
+            Ok(true)
+        })
     }
 
-    /// Save the viewed files index.
+    /// Move a line thread to another line of the same file, replacing its
+    /// anchor with the excerpt of the new line. Returns the moved thread.
     ///
     /// # Errors
     ///
-    /// Returns an error if the file cannot be written.
-    pub fn save_viewed_files(
+    /// Returns an error if the thread doesn't exist, isn't a line thread, or can't be updated.
+    fn reattach_thread(
         &self,
-        index: &chadreview_local_comment_models::ViewedFilesIndex,
-    ) -> Result<(), LocalCommentStoreError> {
-        // Ensure directory exists
-        fs::create_dir_all(&self.repo_path).map_err(LocalCommentStoreError::CreateDir)?;
-
-        let path = self.viewed_files_path();
-        let file = File::create(&path).map_err(LocalCommentStoreError::Write)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, index).map_err(LocalCommentStoreError::Serialize)
+        thread_id: Uuid,
+        line: LineNumber,
+        anchor: Option<LineAnchor>,
+    ) -> Result<LocalComment, LocalCommentStoreError> {
+        let mut moved = None;
+        self.update_thread(thread_id, &mut |thread| {
+            let LocalCommentType::LineLevelComment { line: current, .. } = &mut thread.comment_type
+            else {
+                return Err(LocalCommentStoreError::NotLineComment(thread_id));
+            };
+            *current = line;
+            thread.anchor.clone_from(&anchor);
+            thread.updated_at = Utc::now();
+            moved = Some(thread.clone());
+            Ok(true)
+        })?;
+
+        moved.ok_or(LocalCommentStoreError::NotFound(thread_id))
     }
 
     /// Mark a file as viewed.
@@ -543,9 +439,9 @@ impl LocalCommentStore {
     /// # Errors
     ///
     /// Returns an error if the viewed files index cannot be loaded or saved.
-    pub fn mark_file_viewed(&self, path: &str) -> Result<(), LocalCommentStoreError> {
+    fn mark_file_viewed(&self, path: &str) -> Result<(), LocalCommentStoreError> {
         let mut index = self.load_viewed_files()?;
-        index.files.insert(path.to_string(), chrono::Utc::now());
+        index.files.insert(path.to_string(), Utc::now());
         self.save_viewed_files(&index)
     }
 
@@ -554,7 +450,7 @@ impl LocalCommentStore {
     /// # Errors
     ///
     /// Returns an error if the viewed files index cannot be loaded or saved.
-    pub fn mark_file_unviewed(&self, path: &str) -> Result<(), LocalCommentStoreError> {
+    fn mark_file_unviewed(&self, path: &str) -> Result<(), LocalCommentStoreError> {
         let mut index = self.load_viewed_files()?;
         index.files.remove(path);
         self.save_viewed_files(&index)
@@ -563,11 +459,9 @@ impl LocalCommentStore {
     /// Check if a file is marked as viewed.
     ///
     /// Returns `false` if the file is not viewed or if there's an error loading the index.
-    #[must_use]
-    pub fn is_file_viewed(&self, path: &str) -> bool {
+    fn is_file_viewed(&self, path: &str) -> bool {
         self.load_viewed_files()
-            .map(|index| index.files.contains_key(path))
-            .unwrap_or(false)
+            .is_ok_and(|index| index.files.contains_key(path))
     }
 
     /// Get the set of all viewed file paths.
@@ -575,59 +469,8 @@ impl LocalCommentStore {
     /// # Errors
     ///
     /// Returns an error if the viewed files index cannot be loaded.
-    pub fn get_viewed_file_paths(
-        &self,
-    ) -> Result<std::collections::HashSet<String>, LocalCommentStoreError> {
-        let index = self.load_viewed_files()?;
-        Ok(index.files.keys().cloned().collect())
-    }
-
-    // =========================================================================
-    // Viewed Replies
-    // =========================================================================
-
-    /// Get the path to the viewed replies index.
-    fn viewed_replies_path(&self) -> PathBuf {
-        self.repo_path.join("viewed_replies.json")
-    }
-
-    /// Load the viewed replies index.
-    ///
-    /// Returns an empty index if the file doesn't exist.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the file exists but cannot be read or parsed.
-    pub fn load_viewed_replies(
-        &self,
-    ) -> Result<chadreview_local_comment_models::ViewedRepliesIndex, LocalCommentStoreError> {
-        let path = self.viewed_replies_path();
-
-        if !path.exists() {
-            return Ok(chadreview_local_comment_models::ViewedRepliesIndex::default());
-        }
-
-        let file = File::open(&path).map_err(LocalCommentStoreError::Read)?;
-        let reader = BufReader::new(file);
-        serde_json::from_reader(reader).map_err(LocalCommentStoreError::Parse)
-    }
-
-    /// Save the viewed replies index.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the file cannot be written.
-    pub fn save_viewed_replies(
-        &self,
-        index: &chadreview_local_comment_models::ViewedRepliesIndex,
-    ) -> Result<(), LocalCommentStoreError> {
-        // Ensure directory exists
-        fs::create_dir_all(&self.repo_path).map_err(LocalCommentStoreError::CreateDir)?;
-
-        let path = self.viewed_replies_path();
-        let file = File::create(&path).map_err(LocalCommentStoreError::Write)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, index).map_err(LocalCommentStoreError::Serialize)
+    fn get_viewed_file_paths(&self) -> Result<HashSet<String>, LocalCommentStoreError> {
+        Ok(self.load_viewed_files()?.files.into_keys().collect())
     }
 
     /// Mark a reply as viewed.
@@ -635,9 +478,9 @@ impl LocalCommentStore {
     /// # Errors
     ///
     /// Returns an error if the viewed replies index cannot be loaded or saved.
-    pub fn mark_reply_viewed(&self, reply_id: Uuid) -> Result<(), LocalCommentStoreError> {
+    fn mark_reply_viewed(&self, reply_id: Uuid) -> Result<(), LocalCommentStoreError> {
         let mut index = self.load_viewed_replies()?;
-        index.replies.insert(reply_id, chrono::Utc::now());
+        index.replies.insert(reply_id, Utc::now());
         self.save_viewed_replies(&index)
     }
 
@@ -646,7 +489,7 @@ impl LocalCommentStore {
     /// # Errors
     ///
     /// Returns an error if the viewed replies index cannot be loaded or saved.
-    pub fn mark_reply_unviewed(&self, reply_id: Uuid) -> Result<(), LocalCommentStoreError> {
+    fn mark_reply_unviewed(&self, reply_id: Uuid) -> Result<(), LocalCommentStoreError> {
         let mut index = self.load_viewed_replies()?;
         index.replies.remove(&reply_id);
         self.save_viewed_replies(&index)
@@ -655,11 +498,9 @@ impl LocalCommentStore {
     /// Check if a reply is marked as viewed.
     ///
     /// Returns `false` if the reply is not viewed or if there's an error loading the index.
-    #[must_use]
-    pub fn is_reply_viewed(&self, reply_id: Uuid) -> bool {
+    fn is_reply_viewed(&self, reply_id: Uuid) -> bool {
         self.load_viewed_replies()
-            .map(|index| index.replies.contains_key(&reply_id))
-            .unwrap_or(false)
+            .is_ok_and(|index| index.replies.contains_key(&reply_id))
     }
 
     /// Get the set of all viewed reply IDs.
@@ -667,84 +508,257 @@ impl LocalCommentStore {
     /// # Errors
     ///
     /// Returns an error if the viewed replies index cannot be loaded.
-    pub fn get_viewed_reply_ids(
+    fn get_viewed_reply_ids(&self) -> Result<HashSet<Uuid>, LocalCommentStoreError> {
+        Ok(self.load_viewed_replies()?.replies.into_keys().collect())
+    }
+
+    /// Save the draft of the comment form at `key`. An empty `body` removes
+    /// the draft.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the drafts index cannot be loaded or saved.
+    fn save_draft(&self, key: &str, body: &str) -> Result<(), LocalCommentStoreError> {
+        let mut index = self.load_drafts()?;
+        if body.trim().is_empty() {
+            index.drafts.remove(key);
+        } else {
+            index.drafts.insert(
+                key.to_string(),
+                CommentDraft {
+                    body: body.to_string(),
+                    saved_at: Utc::now(),
+                },
+            );
+        }
+        self.save_drafts(&index)
+    }
+
+    /// Get every draft's body by comment form key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the drafts index cannot be loaded.
+    fn get_draft_bodies(&self) -> Result<HashMap<String, String>, LocalCommentStoreError> {
+        Ok(self
+            .load_drafts()?
+            .drafts
+            .into_iter()
+            .map(|(key, draft)| (key, draft.body))
+            .collect())
+    }
+
+    /// Add an AI execution's usage to the ledger.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ledger cannot be loaded or saved.
+    fn record_usage(&self, entry: UsageEntry) -> Result<(), LocalCommentStoreError> {
+        let mut ledger = self.load_usage()?;
+        ledger.entries.push(entry);
+        self.save_usage(&ledger)
+    }
+
+    /// Mark AI executions that have been pending or running since before
+    /// `cutoff` as failed, unless `is_running` says the task executing the
+    /// comment's action is still alive.
+    ///
+    /// Executions are tracked by in-process tasks, so one whose task is gone
+    /// was interrupted (e.g. by a restart) and would otherwise show as
+    /// running forever. Returns the number of executions marked as failed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a thread can't be read or saved.
+    fn fail_stale_ai_executions(
         &self,
-    ) -> Result<std::collections::HashSet<Uuid>, LocalCommentStoreError> {
-        let index = self.load_viewed_replies()?;
-        Ok(index.replies.keys().copied().collect())
+        cutoff: DateTime<Utc>,
+        is_running: &dyn Fn(Uuid) -> bool,
+    ) -> Result<usize, LocalCommentStoreError> {
+        let mut failed = 0;
+
+        for entry in self.list_threads()? {
+            self.update_thread(entry.id, &mut |thread| {
+                let count = fail_stale_recursive(thread, cutoff, is_running);
+                failed += count;
+                Ok(count > 0)
+            })?;
+        }
+
+        Ok(failed)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use chadreview_local_comment_models::{LineNumber, LocalUser};
-    use std::env;
-
-    fn temp_store() -> LocalCommentStore {
-        let temp_dir = env::temp_dir().join(format!("chadreview-test-{}", Uuid::new_v4()));
-        LocalCommentStore {
-            repo_path: temp_dir,
-            cache: RwLock::new(HashMap::new()),
+/// A handle to a repository's comment store.
+///
+/// Opens the repository's git notes if it's configured to use them and the
+/// `git-notes` feature is enabled, its `SQLite` database if it has one (or
+/// has no comments yet) and the `sqlite` feature is enabled, and its JSON
+/// files otherwise. All store operations are available through
+/// [`CommentStore`].
+pub struct LocalCommentStore {
+    store: Box<dyn CommentStore>,
+}
+
+impl LocalCommentStore {
+    /// Open the store for the given repository path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the XDG data directory cannot be determined, or
+    /// the database or the repository's notes cannot be opened.
+    pub fn new(repo_path: &Path) -> Result<Self, LocalCommentStoreError> {
+        let dir = storage_dir(repo_path)?;
+
+        #[cfg(feature = "git-notes")]
+        if crate::notes::NotesCommentStore::is_used_for(repo_path) {
+            return Ok(Self {
+                store: Box::new(crate::notes::NotesCommentStore::open(repo_path, dir)?),
+            });
+        }
+
+        Self::open_dir(dir)
+    }
+
+    /// Open the stores of every repository that has stored comments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the XDG data directory cannot be determined or read.
+    pub fn open_all() -> Result<Vec<Self>, LocalCommentStoreError> {
+        repo_dirs()?.into_iter().map(Self::open_dir).collect()
+    }
+
+    #[cfg_attr(not(feature = "sqlite"), allow(clippy::unnecessary_wraps))]
+    fn open_dir(dir: PathBuf) -> Result<Self, LocalCommentStoreError> {
+        #[cfg(feature = "sqlite")]
+        if crate::sqlite::SqliteCommentStore::is_used_in(&dir) {
+            return Ok(Self {
+                store: Box::new(crate::sqlite::SqliteCommentStore::open(dir)?),
+            });
         }
+
+        Ok(Self {
+            store: Box::new(JsonCommentStore::new(dir)),
+        })
     }
+}
 
-    #[test]
-    fn test_save_and_load_thread() {
-        let store = temp_store();
+impl Deref for LocalCommentStore {
+    type Target = dyn CommentStore;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.store
+    }
+}
 
-        let comment = LocalComment::new(
-            LocalUser::default(),
-            "Test comment".to_string(),
-            LocalCommentType::General,
-        );
+/// Get the directory containing every repository's comments.
+fn comments_root() -> Result<PathBuf, LocalCommentStoreError> {
+    let data_dir = dirs::data_dir().ok_or(LocalCommentStoreError::NoDataDir)?;
+    Ok(data_dir.join("chadreview").join("comments"))
+}
 
-        store.save_thread(&comment).unwrap();
-        let loaded = store.load_thread(comment.id).unwrap();
+/// Get the storage directories of every repository with stored comments.
+pub fn repo_dirs() -> Result<Vec<PathBuf>, LocalCommentStoreError> {
+    let root = comments_root()?;
+    if !root.exists() {
+        return Ok(vec![]);
+    }
 
-        assert_eq!(loaded.id, comment.id);
-        assert_eq!(loaded.body, comment.body);
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(&root).map_err(LocalCommentStoreError::Read)? {
+        let entry = entry.map_err(LocalCommentStoreError::Read)?;
+        if entry.path().is_dir() {
+            dirs.push(entry.path());
+        }
     }
 
-    #[test]
-    fn test_list_threads() {
-        let store = temp_store();
+    Ok(dirs)
+}
+
+/// Get the XDG-compliant storage directory for a repository.
+fn storage_dir(repo_path: &Path) -> Result<PathBuf, LocalCommentStoreError> {
+    // Create a hash of the canonical repo path for the directory name
+    let canonical = repo_path
+        .canonicalize()
+        .unwrap_or_else(|_| repo_path.to_path_buf());
+    let repo_hash = hash_path(&canonical);
+
+    Ok(comments_root()?.join(repo_hash))
+}
 
-        let comment1 = LocalComment::new(
-            LocalUser::default(),
-            "Comment 1".to_string(),
-            LocalCommentType::General,
-        );
-        let comment2 = LocalComment::new(
-            LocalUser::default(),
-            "Comment 2".to_string(),
-            LocalCommentType::LineLevelComment {
-                path: "src/main.rs".to_string(),
-                line: LineNumber::New { line: 10 },
-            },
-        );
+/// Hash a path to create a directory-safe identifier.
+fn hash_path(path: &Path) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(path.to_string_lossy().as_bytes());
+    let result = hasher.finalize();
+    // Use first 16 bytes (32 hex chars) for reasonable uniqueness
+    result[..16]
+        .iter()
+        .fold(String::with_capacity(32), |mut acc, b| {
+            write!(acc, "{b:02x}").unwrap();
+            acc
+        })
+}
 
-        store.save_thread(&comment1).unwrap();
-        store.save_thread(&comment2).unwrap();
+/// Find a comment within a thread (including replies).
+fn find_comment_in_thread(comment: &LocalComment, id: Uuid) -> Option<&LocalComment> {
+    if comment.id == id {
+        return Some(comment);
+    }
+    comment
+        .replies
+        .iter()
+        .find_map(|reply| find_comment_in_thread(reply, id))
+}
 
-        let threads = store.list_threads().unwrap();
-        assert_eq!(threads.len(), 2);
+/// Find a comment within a thread (including replies) for updating.
+fn find_comment_in_thread_mut(comment: &mut LocalComment, id: Uuid) -> Option<&mut LocalComment> {
+    if comment.id == id {
+        return Some(comment);
     }
+    comment
+        .replies
+        .iter_mut()
+        .find_map(|reply| find_comment_in_thread_mut(reply, id))
+}
 
-    #[test]
-    fn test_delete_thread() {
-        let store = temp_store();
+/// Recursively find and remove a reply from a thread.
+fn remove_reply_recursive(replies: &mut Vec<LocalComment>, target_id: Uuid) -> bool {
+    // First check if it's a direct child
+    if let Some(pos) = replies.iter().position(|r| r.id == target_id) {
+        replies.remove(pos);
+        return true;
+    }
+    // Otherwise recurse into children
+    replies
+        .iter_mut()
+        .any(|reply| remove_reply_recursive(&mut reply.replies, target_id))
+}
 
-        let comment = LocalComment::new(
-            LocalUser::default(),
-            "To be deleted".to_string(),
-            LocalCommentType::General,
-        );
+fn fail_stale_recursive(
+    comment: &mut LocalComment,
+    cutoff: DateTime<Utc>,
+    is_running: &dyn Fn(Uuid) -> bool,
+) -> usize {
+    let stale = match &comment.ai_status {
+        Some(AiExecutionStatus::Pending) => comment.updated_at < cutoff,
+        Some(AiExecutionStatus::Running { started_at, .. }) => *started_at < cutoff,
+        _ => false,
+    } && !is_running(comment.id);
 
-        store.save_thread(&comment).unwrap();
-        assert!(store.load_thread(comment.id).is_ok());
+    let mut count = 0;
+    if stale {
+        comment.ai_status = Some(AiExecutionStatus::Failed {
+            finished_at: Utc::now(),
+            error: "Execution was interrupted".to_string(),
+        });
+        count += 1;
+    }
 
-        store.delete_thread(comment.id).unwrap();
-        assert!(store.load_thread(comment.id).is_err());
+    for reply in &mut comment.replies {
+        count += fail_stale_recursive(reply, cutoff, is_running);
     }
+
+    count
 }
@@ -0,0 +1,260 @@
+//! AI summaries of changed files.
+//!
+//! A summary agent is handed batches of changed files, formatted as for a
+//! review, along with [`SUMMARY_REQUEST`] asking for a short summary of
+//! each as a JSON object. Summaries are cached in the repository's storage
+//! directory under `summaries/`, keyed by [`file_summary_key`], so a file is
+//! only summarized again once its changes do.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use chadreview_pr_models::DiffFile;
+use sha2::{Digest, Sha256};
+
+use crate::review::{format_review_diff, json_block};
+use crate::{CommentStore, LocalCommentStoreError};
+
+/// Instructions given to the summary agent along with a batch of files.
+pub const SUMMARY_REQUEST: &str = "\
+Summarize the changes to each file of this diff in one to three sentences, \
+so a reviewer knows what to expect before reading it. Describe what changed \
+and why it matters, not line by line.
+
+Answer with a JSON object in a ```json code block, mapping each file's path \
+(as shown after \"===\") to its summary in markdown.";
+
+/// Largest formatted diff, in bytes, sent to the summary agent at once.
+/// Files larger than this are sent on their own.
+pub const MAX_SUMMARY_BATCH_BYTES: usize = 48 * 1024;
+
+/// Directory holding cached summaries, relative to the storage directory.
+const SUMMARIES_DIR: &str = "summaries";
+
+/// The key a summary of `file` is cached under: a SHA-256 of its path and
+/// changes.
+///
+/// Diffs don't carry the blob IDs of either side, so the changes stand in
+/// for them: the key changes whenever either side of the file does in a way
+/// that shows in the diff.
+#[must_use]
+pub fn file_summary_key(file: &DiffFile) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format_review_diff(std::slice::from_ref(file)).as_bytes());
+
+    hasher
+        .finalize()
+        .iter()
+        .fold(String::with_capacity(64), |mut acc, b| {
+            write!(acc, "{b:02x}").unwrap();
+            acc
+        })
+}
+
+/// Load the cached summary of `file`, if it has one.
+///
+/// # Errors
+///
+/// Returns an error if the cached summary exists but can't be read.
+pub fn load_file_summary(
+    store: &dyn CommentStore,
+    file: &DiffFile,
+) -> Result<Option<String>, LocalCommentStoreError> {
+    match fs::read_to_string(summary_path(store, file)) {
+        Ok(summary) => Ok(Some(summary)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(LocalCommentStoreError::Read(e)),
+    }
+}
+
+/// Load the cached summaries of `diffs`, keyed by file path. Files without
+/// a cached summary are left out.
+///
+/// # Errors
+///
+/// Returns an error if a cached summary exists but can't be read.
+pub fn load_file_summaries(
+    store: &dyn CommentStore,
+    diffs: &[DiffFile],
+) -> Result<HashMap<String, String>, LocalCommentStoreError> {
+    let mut summaries = HashMap::new();
+
+    for file in diffs {
+        if let Some(summary) = load_file_summary(store, file)? {
+            summaries.insert(file.filename.clone(), summary);
+        }
+    }
+
+    Ok(summaries)
+}
+
+/// Cache `summary` as the summary of `file`.
+///
+/// # Errors
+///
+/// Returns an error if the summary can't be written.
+pub fn save_file_summary(
+    store: &dyn CommentStore,
+    file: &DiffFile,
+    summary: &str,
+) -> Result<(), LocalCommentStoreError> {
+    let dir = store.storage_path().join(SUMMARIES_DIR);
+    fs::create_dir_all(&dir).map_err(LocalCommentStoreError::CreateDir)?;
+
+    fs::write(summary_path(store, file), summary).map_err(LocalCommentStoreError::Write)
+}
+
+/// Split `diffs` into batches to summarize together, each formatting to at
+/// most [`MAX_SUMMARY_BATCH_BYTES`] unless it holds a single larger file.
+///
+/// Files without hunks (binary files, pure renames) have nothing to
+/// summarize and are left out.
+#[must_use]
+pub fn summary_batches(diffs: &[DiffFile]) -> Vec<Vec<DiffFile>> {
+    let mut batches = vec![];
+    let mut batch: Vec<DiffFile> = vec![];
+    let mut batch_bytes = 0;
+
+    for file in diffs.iter().filter(|file| !file.hunks.is_empty()) {
+        let bytes = format_review_diff(std::slice::from_ref(file)).len();
+        if !batch.is_empty() && batch_bytes + bytes > MAX_SUMMARY_BATCH_BYTES {
+            batches.push(std::mem::take(&mut batch));
+            batch_bytes = 0;
+        }
+        batch.push(file.clone());
+        batch_bytes += bytes;
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+
+    batches
+}
+
+/// Read the per-file summaries from a summary agent's response, keyed by
+/// file path.
+///
+/// The summaries are read from the first `json` code block, or else from
+/// the outermost `{...}` of the response. Blank summaries are left out, and
+/// an unparseable response has none.
+#[must_use]
+pub fn parse_file_summaries(response: &str) -> HashMap<String, String> {
+    json_block(response, '{', '}')
+        .and_then(|(_, _, json)| serde_json::from_str::<HashMap<String, String>>(json).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(path, summary)| (path, summary.trim().to_string()))
+        .filter(|(_, summary)| !summary.is_empty())
+        .collect()
+}
+
+fn summary_path(store: &dyn CommentStore, file: &DiffFile) -> PathBuf {
+    store
+        .storage_path()
+        .join(SUMMARIES_DIR)
+        .join(format!("{}.md", file_summary_key(file)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use chadreview_pr_models::{DiffHunk, DiffLine, FileStatus, LineType};
+    use switchy::uuid::Uuid;
+
+    use super::*;
+    use crate::JsonCommentStore;
+
+    fn temp_store() -> JsonCommentStore {
+        let temp_dir = env::temp_dir().join(format!("chadreview-test-{}", Uuid::new_v4()));
+        JsonCommentStore::new(temp_dir)
+    }
+
+    fn diff_file(filename: &str, content: &str) -> DiffFile {
+        DiffFile {
+            filename: filename.to_string(),
+            status: FileStatus::Modified,
+            additions: 1,
+            deletions: 0,
+            mode_change: None,
+            hunks: vec![DiffHunk {
+                old_start: 1,
+                old_lines: 0,
+                new_start: 1,
+                new_lines: 1,
+                lines: vec![DiffLine {
+                    line_type: LineType::Addition,
+                    old_line_number: None,
+                    new_line_number: Some(1),
+                    content: content.to_string(),
+                    highlighted_html: String::new(),
+                }],
+            }],
+            decoded_lossy: false,
+            submodule: None,
+        }
+    }
+
+    #[test]
+    fn test_summaries_are_cached_by_changes() {
+        let store = temp_store();
+        let file = diff_file("src/lib.rs", "fn main() {}");
+
+        assert_eq!(load_file_summary(&store, &file).unwrap(), None);
+        save_file_summary(&store, &file, "Adds `main`.").unwrap();
+
+        assert_eq!(
+            load_file_summary(&store, &file).unwrap().as_deref(),
+            Some("Adds `main`.")
+        );
+        assert_eq!(
+            load_file_summaries(&store, &[file, diff_file("src/lib.rs", "fn other() {}")]).unwrap(),
+            HashMap::from([("src/lib.rs".to_string(), "Adds `main`.".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_summary_batches_respect_budget() {
+        let small = "x".repeat(100);
+        let large = "x".repeat(MAX_SUMMARY_BATCH_BYTES);
+        let mut binary = diff_file("image.png", "");
+        binary.hunks.clear();
+
+        let batches = summary_batches(&[
+            diff_file("a.rs", &small),
+            diff_file("b.rs", &small),
+            binary,
+            diff_file("c.rs", &large),
+            diff_file("d.rs", &small),
+        ]);
+
+        let names: Vec<Vec<&str>> = batches
+            .iter()
+            .map(|batch| batch.iter().map(|file| file.filename.as_str()).collect())
+            .collect();
+        assert_eq!(
+            names,
+            vec![vec!["a.rs", "b.rs"], vec!["c.rs"], vec!["d.rs"]]
+        );
+    }
+
+    #[test]
+    fn test_parse_file_summaries() {
+        let summaries = parse_file_summaries(
+            "Here you go:\n\n```json\n{\"a.rs\": \" Renames `foo`. \", \"b.rs\": \"\"}\n```\n",
+        );
+        assert_eq!(
+            summaries,
+            HashMap::from([("a.rs".to_string(), "Renames `foo`.".to_string())])
+        );
+
+        assert_eq!(
+            parse_file_summaries("{\"a.rs\": \"Adds tests.\"}").get("a.rs"),
+            Some(&"Adds tests.".to_string())
+        );
+        assert!(parse_file_summaries("No summaries {here}.").is_empty());
+    }
+}
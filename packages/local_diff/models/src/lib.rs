@@ -23,6 +23,10 @@ pub enum DiffSpec {
         /// If true, use merge-base semantics (three-dot: `base...head`).
         /// This shows changes on head since it diverged from base.
         three_dot: bool,
+        /// How to show the range. `Squashed` (the default for ranges) is the
+        /// combined diff; `Separate` walks through it commit by commit.
+        #[serde(default = "squashed")]
+        mode: MultiCommitMode,
     },
 
     /// Working tree changes.
@@ -51,6 +55,10 @@ pub enum DiffSpec {
     },
 }
 
+const fn squashed() -> MultiCommitMode {
+    MultiCommitMode::Squashed
+}
+
 /// How to combine multiple commits in a diff view.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub enum MultiCommitMode {
@@ -145,10 +153,16 @@ impl DiffSpec {
                 .or_else(|| params.get("merge_base"))
                 .is_some_and(|v| v == "true" || v == "1");
 
+            let mode = match params.get("mode").map(String::as_str) {
+                Some("separate") => MultiCommitMode::Separate,
+                _ => MultiCommitMode::Squashed,
+            };
+
             return Ok(Self::Range {
                 base: base.clone(),
                 head: head.clone(),
                 three_dot,
+                mode,
             });
         }
 
@@ -185,6 +199,81 @@ impl DiffSpec {
         })
     }
 
+    /// Parse a diff spec from git revision syntax, as given on the command
+    /// line:
+    ///
+    /// - `base..head` (range) or `base...head` (merge-base range), where an
+    ///   omitted side means `HEAD`
+    /// - `sha1,sha2,sha3` (multiple specific commits, one at a time)
+    /// - `sha` (single commit)
+    /// - Nothing: working tree against HEAD
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a list of commits has an empty entry.
+    pub fn from_revisions(revisions: &str) -> Result<Self, DiffSpecError> {
+        let revisions = revisions.trim();
+
+        if revisions.is_empty() {
+            return Self::from_query(&BTreeMap::new());
+        }
+
+        let range = revisions
+            .split_once("...")
+            .map(|(base, head)| (base, head, true))
+            .or_else(|| {
+                revisions
+                    .split_once("..")
+                    .map(|(base, head)| (base, head, false))
+            });
+        if let Some((base, head, three_dot)) = range {
+            let or_head = |rev: &str| {
+                if rev.is_empty() {
+                    "HEAD".to_string()
+                } else {
+                    rev.to_string()
+                }
+            };
+            return Ok(Self::Range {
+                base: or_head(base),
+                head: or_head(head),
+                three_dot,
+                mode: MultiCommitMode::Squashed,
+            });
+        }
+
+        if revisions.contains(',') {
+            let shas: Vec<String> = revisions.split(',').map(|s| s.trim().to_string()).collect();
+            if shas.iter().any(String::is_empty) {
+                return Err(DiffSpecError::InvalidParam("commits cannot be empty"));
+            }
+            return Ok(Self::Commits {
+                shas,
+                mode: MultiCommitMode::Separate,
+            });
+        }
+
+        Ok(Self::Commit {
+            sha: revisions.to_string(),
+        })
+    }
+
+    /// Whether this diff is reviewed one commit at a time, with each
+    /// commit's changes shown in its own section.
+    #[must_use]
+    pub const fn is_by_commit(&self) -> bool {
+        matches!(
+            self,
+            Self::Range {
+                mode: MultiCommitMode::Separate,
+                ..
+            } | Self::Commits {
+                mode: MultiCommitMode::Separate,
+                ..
+            }
+        )
+    }
+
     /// Human-readable description of this diff spec.
     #[must_use]
     pub fn description(&self) -> String {
@@ -193,11 +282,12 @@ impl DiffSpec {
                 base,
                 head,
                 three_dot,
+                mode,
             } => {
-                if *three_dot {
-                    format!("{base}...{head}")
-                } else {
-                    format!("{base}..{head}")
+                let dots = if *three_dot { "..." } else { ".." };
+                match mode {
+                    MultiCommitMode::Separate => format!("{base}{dots}{head} (by commit)"),
+                    MultiCommitMode::Squashed => format!("{base}{dots}{head}"),
                 }
             }
             Self::WorkingTree {
@@ -232,11 +322,15 @@ impl DiffSpec {
                 base,
                 head,
                 three_dot,
+                mode,
             } => {
                 let mut s = format!("base={base}&head={head}");
                 if *three_dot {
                     s.push_str("&three_dot=true");
                 }
+                if *mode == MultiCommitMode::Separate {
+                    s.push_str("&mode=separate");
+                }
                 s
             }
             Self::WorkingTree {
@@ -265,6 +359,147 @@ impl DiffSpec {
     }
 }
 
+/// Which commits to show of a diff reviewed commit by commit, narrowed down
+/// with the filter chips above the commit list.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitFilter {
+    /// Only commits by this author, by name or email.
+    pub author: Option<String>,
+    /// Only commits changing files under this path prefix, showing just
+    /// those files.
+    pub path: Option<String>,
+    /// Leave out merge commits.
+    pub hide_merges: bool,
+}
+
+impl CommitFilter {
+    /// Parse a filter from the `author`, `path` and `merges=hide` query
+    /// parameters. Blank values are ignored.
+    #[must_use]
+    pub fn from_query(params: &BTreeMap<String, String>) -> Self {
+        let non_empty = |name: &str| {
+            params
+                .get(name)
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+                .map(ToString::to_string)
+        };
+
+        Self {
+            author: non_empty("author"),
+            path: non_empty("path"),
+            hide_merges: params.get("merges").is_some_and(|v| v == "hide"),
+        }
+    }
+
+    /// Whether the filter lets every commit through.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.author.is_none() && self.path.is_none() && !self.hide_merges
+    }
+
+    /// Whether `commit` passes the author and merge commit filters.
+    #[must_use]
+    pub fn matches_commit(&self, commit: &CommitInfo) -> bool {
+        if self.hide_merges && commit.parent_shas.len() > 1 {
+            return false;
+        }
+
+        self.author
+            .as_ref()
+            .is_none_or(|author| commit.author_name == *author || commit.author_email == *author)
+    }
+
+    /// Whether a file at `path` passes the path prefix filter.
+    #[must_use]
+    pub fn matches_path(&self, path: &str) -> bool {
+        self.path.as_ref().is_none_or(|prefix| {
+            let prefix = prefix.trim_end_matches('/');
+            path == prefix
+                || path
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+}
+
+/// Order of the files of a diff, picked with the sort menu above it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileOrder {
+    /// By path (default).
+    #[default]
+    Path,
+    /// Most changed lines first.
+    Changes,
+    /// Added, then modified, renamed and deleted files.
+    Status,
+    /// Grouped by directory like the file tree, each directory's own files
+    /// before its subdirectories'.
+    Directory,
+}
+
+impl FileOrder {
+    /// Every order, as listed in the sort menu.
+    pub const ALL: [Self; 4] = [Self::Path, Self::Changes, Self::Status, Self::Directory];
+
+    /// Parse the order from the `sort` query parameter, defaulting to
+    /// [`FileOrder::Path`] when missing or unknown.
+    #[must_use]
+    pub fn from_query(params: &BTreeMap<String, String>) -> Self {
+        params
+            .get("sort")
+            .and_then(|value| Self::ALL.into_iter().find(|order| order.as_str() == value))
+            .unwrap_or_default()
+    }
+
+    /// Value of the `sort` query parameter selecting this order.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Path => "path",
+            Self::Changes => "changes",
+            Self::Status => "status",
+            Self::Directory => "directory",
+        }
+    }
+
+    /// Name of the order shown in the sort menu.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Path => "Path",
+            Self::Changes => "Most changes",
+            Self::Status => "Status",
+            Self::Directory => "Directory",
+        }
+    }
+}
+
+/// A place a symbol is defined or used.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolLocation {
+    /// Path of the file, relative to the repository root.
+    pub path: String,
+    /// Line number in the working tree version of the file.
+    pub line: u64,
+    /// Kind of definition (e.g. `function`, `struct`), or `None` for
+    /// references.
+    pub kind: Option<String>,
+    /// The line's text.
+    pub text: String,
+}
+
+/// The definitions and references of a symbol in a repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolLookup {
+    /// Where the symbol is defined.
+    pub definitions: Vec<SymbolLocation>,
+    /// Where the symbol is used, outside its definitions.
+    pub references: Vec<SymbolLocation>,
+    /// Whether more references exist than were returned.
+    pub references_truncated: bool,
+}
+
 /// Metadata about a local diff view.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalDiffInfo {
@@ -339,6 +574,7 @@ mod tests {
                 base: "main".to_string(),
                 head: "feature".to_string(),
                 three_dot: false,
+                mode: MultiCommitMode::Squashed,
             }
         );
     }
@@ -400,7 +636,8 @@ mod tests {
             DiffSpec::Range {
                 base: "main".to_string(),
                 head: "feature".to_string(),
-                three_dot: false
+                three_dot: false,
+                mode: MultiCommitMode::Squashed,
             }
             .description(),
             "main..feature"
@@ -410,12 +647,24 @@ mod tests {
             DiffSpec::Range {
                 base: "main".to_string(),
                 head: "feature".to_string(),
-                three_dot: true
+                three_dot: true,
+                mode: MultiCommitMode::Squashed,
             }
             .description(),
             "main...feature"
         );
 
+        assert_eq!(
+            DiffSpec::Range {
+                base: "main".to_string(),
+                head: "feature".to_string(),
+                three_dot: false,
+                mode: MultiCommitMode::Separate,
+            }
+            .description(),
+            "main..feature (by commit)"
+        );
+
         assert_eq!(
             DiffSpec::WorkingTree {
                 against: "HEAD".to_string(),
@@ -441,6 +690,7 @@ mod tests {
             base: "main".to_string(),
             head: "feature".to_string(),
             three_dot: true,
+            mode: MultiCommitMode::Squashed,
         };
         assert_eq!(
             spec.to_query_string(),
@@ -448,10 +698,155 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_range_by_commit() {
+        let params = make_params(&[("base", "main"), ("head", "feature"), ("mode", "separate")]);
+        let spec = DiffSpec::from_query(&params).unwrap();
+
+        assert!(spec.is_by_commit());
+        assert_eq!(
+            spec.to_query_string(),
+            "base=main&head=feature&mode=separate"
+        );
+        assert!(
+            !DiffSpec::from_query(&make_params(&[("base", "main"), ("head", "feature")]))
+                .unwrap()
+                .is_by_commit()
+        );
+    }
+
+    fn commit(author: &str, parents: usize) -> CommitInfo {
+        CommitInfo {
+            sha: "abc123".to_string(),
+            short_sha: "abc123".to_string(),
+            message: "Change".to_string(),
+            summary: "Change".to_string(),
+            author_name: author.to_string(),
+            author_email: format!("{}@example.com", author.to_lowercase()),
+            timestamp: 0,
+            parent_shas: vec!["parent".to_string(); parents],
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_commit_filter() {
+        let filter = CommitFilter::from_query(&make_params(&[
+            ("author", "Ada"),
+            ("path", " "),
+            ("merges", "hide"),
+        ]));
+
+        assert_eq!(
+            filter,
+            CommitFilter {
+                author: Some("Ada".to_string()),
+                path: None,
+                hide_merges: true,
+            }
+        );
+        assert!(CommitFilter::from_query(&BTreeMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_commit_filter_matches() {
+        let filter = CommitFilter {
+            author: Some("ada@example.com".to_string()),
+            path: Some("src/".to_string()),
+            hide_merges: true,
+        };
+
+        assert!(filter.matches_commit(&commit("Ada", 1)));
+        assert!(!filter.matches_commit(&commit("Ada", 2)));
+        assert!(!filter.matches_commit(&commit("Grace", 1)));
+        assert!(filter.matches_path("src/lib.rs"));
+        assert!(filter.matches_path("src"));
+        assert!(!filter.matches_path("src_old/lib.rs"));
+        assert!(CommitFilter::default().matches_path("README.md"));
+    }
+
+    #[test]
+    fn test_parse_file_order() {
+        assert_eq!(
+            FileOrder::from_query(&make_params(&[("sort", "changes")])),
+            FileOrder::Changes
+        );
+        assert_eq!(
+            FileOrder::from_query(&make_params(&[("sort", "directory")])),
+            FileOrder::Directory
+        );
+        assert_eq!(
+            FileOrder::from_query(&make_params(&[("sort", "size")])),
+            FileOrder::Path
+        );
+        assert_eq!(FileOrder::from_query(&BTreeMap::new()), FileOrder::Path);
+    }
+
     #[test]
     fn test_error_partial_range() {
         let params = make_params(&[("base", "main")]);
         let result = DiffSpec::from_query(&params);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_from_revisions_range() {
+        assert_eq!(
+            DiffSpec::from_revisions("main..HEAD").unwrap(),
+            DiffSpec::Range {
+                base: "main".to_string(),
+                head: "HEAD".to_string(),
+                three_dot: false,
+                mode: MultiCommitMode::Squashed,
+            }
+        );
+        assert_eq!(
+            DiffSpec::from_revisions("main...feature").unwrap(),
+            DiffSpec::Range {
+                base: "main".to_string(),
+                head: "feature".to_string(),
+                three_dot: true,
+                mode: MultiCommitMode::Squashed,
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_revisions_range_defaults_to_head() {
+        assert_eq!(
+            DiffSpec::from_revisions("main..").unwrap(),
+            DiffSpec::Range {
+                base: "main".to_string(),
+                head: "HEAD".to_string(),
+                three_dot: false,
+                mode: MultiCommitMode::Squashed,
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_revisions_commits() {
+        assert_eq!(
+            DiffSpec::from_revisions("abc,def").unwrap(),
+            DiffSpec::Commits {
+                shas: vec!["abc".to_string(), "def".to_string()],
+                mode: MultiCommitMode::Separate,
+            }
+        );
+        assert!(DiffSpec::from_revisions("abc,").is_err());
+    }
+
+    #[test]
+    fn test_from_revisions_commit_and_working_tree() {
+        assert_eq!(
+            DiffSpec::from_revisions("abc123").unwrap(),
+            DiffSpec::Commit {
+                sha: "abc123".to_string()
+            }
+        );
+        assert_eq!(
+            DiffSpec::from_revisions("").unwrap(),
+            DiffSpec::from_query(&BTreeMap::new()).unwrap()
+        );
+    }
 }
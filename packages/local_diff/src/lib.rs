@@ -5,8 +5,16 @@
 //! Local git diff provider for `ChadReview`.
 //!
 //! This crate provides a `LocalDiffProvider` that extracts diffs from local
-//! git repositories using the `GitBackend` trait abstraction.
+//! git repositories using the `GitBackend` trait abstraction, and looks up
+//! symbols in them with a `ctags` index.
 
 mod provider;
+mod ref_cache;
+mod symbols;
 
-pub use provider::LocalDiffProvider;
+pub use provider::{
+    CommitDiff, DiffPage, LocalDiffProvider, filter_commit_diffs, sort_diff_files,
+    working_tree_changes,
+};
+pub use ref_cache::evict_idle_caches;
+pub use symbols::{CTAGS_COMMAND, SymbolIndex, SymbolIndexes};
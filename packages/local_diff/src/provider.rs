@@ -1,16 +1,51 @@
 //! Local diff provider implementation.
 
-use std::path::{Path, PathBuf};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::Result;
-use chadreview_diff::parse_unified_diff;
-use chadreview_git_backend::{GitBackend, GitRepository, WorkingTreeDiffOptions};
-use chadreview_git_backend_models::{DiffResult, DiffStatus};
-use chadreview_local_diff_models::{DiffSpec, LocalDiffInfo, MultiCommitMode};
-use chadreview_pr_models::{DiffFile, FileStatus};
+use chadreview_diff::{highlight_to_html, search_diff, search_text};
+use chadreview_diff_models::{MatchSource, SearchMatch};
+use chadreview_git_backend::{
+    DiffOptions, GitBackend, GitRepository, LazyDiff, WorkingTreeDiffOptions,
+};
+use chadreview_git_backend_models::{
+    Author, CommitInfo, ConflictFile, DiffResult, DiffStatus, FileDiff, Hunk, Identity, LineOrigin,
+    RepoOperation, Worktree,
+};
+use chadreview_local_diff_models::{
+    CommitFilter, DiffSpec, FileOrder, LocalDiffInfo, MultiCommitMode, SymbolLookup,
+};
+use chadreview_pr_models::{DiffFile, DiffHunk, DiffLine, FileStatus, LineType, ModeChange};
 use chadreview_syntax::SyntaxHighlighter;
 
+use crate::ref_cache;
+use crate::symbols::{SymbolIndex, SymbolIndexes};
+
+/// SHA of git's empty tree, the base of a commit without parents.
+const EMPTY_TREE_SHA: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// One commit's changes within a diff reviewed commit by commit.
+#[derive(Debug, Clone)]
+pub struct CommitDiff {
+    /// The commit.
+    pub commit: CommitInfo,
+    /// Files changed by the commit, against its first parent.
+    pub files: Vec<DiffFile>,
+}
+
+/// A page of the files of a diff, as loaded by
+/// [`LocalDiffProvider::get_diff_page`].
+#[derive(Debug, Clone)]
+pub struct DiffPage {
+    /// The files of the page.
+    pub files: Vec<DiffFile>,
+    /// Paths of every file of the whole diff, in the page's order.
+    pub paths: Vec<String>,
+}
+
 /// Provider for local git diff operations.
 ///
 /// This provider uses a `GitBackend` implementation to extract diffs from
@@ -18,6 +53,8 @@ use chadreview_syntax::SyntaxHighlighter;
 pub struct LocalDiffProvider<B: GitBackend> {
     backend: Arc<B>,
     repo_path: PathBuf,
+    diff_options: DiffOptions,
+    recurse_submodules: bool,
 }
 
 impl<B: GitBackend> LocalDiffProvider<B> {
@@ -29,7 +66,14 @@ impl<B: GitBackend> LocalDiffProvider<B> {
     /// * `repo_path` - Path to the repository root.
     #[must_use]
     pub const fn new(backend: Arc<B>, repo_path: PathBuf) -> Self {
-        Self { backend, repo_path }
+        Self {
+            backend,
+            repo_path,
+            diff_options: DiffOptions {
+                context_lines: None,
+            },
+            recurse_submodules: false,
+        }
     }
 
     /// Create a provider by discovering a repository from the current directory.
@@ -58,7 +102,23 @@ impl<B: GitBackend> LocalDiffProvider<B> {
             .workdir()
             .ok_or_else(|| anyhow::anyhow!("Bare repositories are not supported"))?
             .to_path_buf();
-        Ok(Self { backend, repo_path })
+        Ok(Self::new(backend, repo_path))
+    }
+
+    /// Show `context_lines` unchanged lines around each change of the
+    /// diffs, or git's default of 3 if None.
+    #[must_use]
+    pub const fn with_context_lines(mut self, context_lines: Option<u32>) -> Self {
+        self.diff_options.context_lines = context_lines;
+        self
+    }
+
+    /// Also show the changes inside submodules in working tree diffs, with
+    /// their paths prefixed with the submodule's path.
+    #[must_use]
+    pub const fn with_submodules(mut self, recurse_submodules: bool) -> Self {
+        self.recurse_submodules = recurse_submodules;
+        self
     }
 
     /// Get the repository path.
@@ -79,6 +139,389 @@ impl<B: GitBackend> LocalDiffProvider<B> {
             .map_err(|e| anyhow::anyhow!("{e}"))
     }
 
+    /// Resolve a range's endpoints to commit SHAs, using the merge base as
+    /// the start of a three-dot range. Both are cached across providers of
+    /// the repository, see [`ref_cache`].
+    fn resolve_range(
+        &self,
+        repo: &dyn GitRepository,
+        base: &str,
+        head: &str,
+        three_dot: bool,
+    ) -> Result<(String, String)> {
+        let head_sha = ref_cache::resolve_ref(&self.repo_path, repo, head)?.sha;
+        let base_sha = ref_cache::resolve_ref(&self.repo_path, repo, base)?.sha;
+        let base_sha = if three_dot {
+            ref_cache::merge_base(&self.repo_path, repo, &base_sha, &head_sha)?
+        } else {
+            base_sha
+        };
+
+        Ok((base_sha, head_sha))
+    }
+
+    /// Fetch a branch from another repository (e.g. a fork) and build a
+    /// spec comparing it against a local ref.
+    ///
+    /// The comparison uses merge-base semantics, matching what a pull
+    /// request from that branch would show.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - URL (or path) of the other repository.
+    /// * `branch` - Branch to fetch from it.
+    /// * `base` - Local ref to compare against (e.g. `main`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fetch fails or `base` doesn't exist.
+    pub fn fetch_compare(&self, url: &str, branch: &str, base: &str) -> Result<DiffSpec> {
+        let repo = self.open_repo()?;
+        repo.resolve_ref(base)?;
+        let fetched = repo.fetch_branch(url, branch)?;
+
+        Ok(DiffSpec::Range {
+            base: base.to_string(),
+            head: fetched.name,
+            three_dot: true,
+            mode: MultiCommitMode::Squashed,
+        })
+    }
+
+    /// Apply a suggested change to a file in the working tree, replacing
+    /// line `line` (1-based) with `replacement`.
+    ///
+    /// An empty `replacement` removes the line. The file's line endings and
+    /// trailing newline are preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - File path relative to the repository root.
+    /// * `line` - Line to replace, as numbered in the working tree.
+    /// * `replacement` - The suggested lines, without a trailing newline.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path escapes the repository, the repository
+    /// is bare, the line doesn't exist, or the file can't be read or written.
+    pub fn apply_suggestion(&self, path: &str, line: u64, replacement: &str) -> Result<()> {
+        let relative = relative_path(path)?;
+
+        let repo = self.open_repo()?;
+        let full_path = repo
+            .workdir()
+            .ok_or_else(|| anyhow::anyhow!("Bare repositories are not supported"))?
+            .join(relative);
+
+        let content = std::fs::read_to_string(&full_path)?;
+        let updated = replace_line(&content, line, replacement)
+            .ok_or_else(|| anyhow::anyhow!("Line {line} does not exist in {path}"))?;
+        std::fs::write(&full_path, updated)?;
+
+        Ok(())
+    }
+
+    /// Paths of the files with changes staged in the index, compared to
+    /// `against`. Files inside submodules are included when their changes
+    /// are shown, compared to the submodule's checked out commit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index cannot be diffed against `against`.
+    pub fn staged_paths(&self, against: &str) -> Result<HashSet<String>> {
+        let repo = self.open_repo()?;
+        let options = WorkingTreeDiffOptions {
+            staged_only: true,
+            include_untracked: false,
+            include_ignored: false,
+            recurse_submodules: self.recurse_submodules,
+            diff: DiffOptions::default(),
+        };
+
+        Ok(repo
+            .diff_working_tree(against, options)?
+            .flatten()
+            .files
+            .into_iter()
+            .filter_map(|file| file.new_path.or(file.old_path))
+            .collect())
+    }
+
+    /// Stage the working tree state of the file at `path` in the working
+    /// tree diff of `spec`, relative to the repository root. Files inside
+    /// submodules are staged in the submodule's index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `spec` isn't a working tree diff, the path
+    /// escapes the repository, or the index can't be updated.
+    pub fn stage_path(&self, spec: &DiffSpec, path: &str) -> Result<()> {
+        relative_path(path)?;
+        let submodule = self.submodule_of(spec, path)?;
+        let (repo, path, _) = self.file_repository(spec, submodule.as_deref(), path)?;
+        Ok(repo.stage_path(path)?)
+    }
+
+    /// Unstage the file at `path` in the working tree diff of `spec`,
+    /// relative to the repository root. Files inside submodules are
+    /// unstaged in the submodule's index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `spec` isn't a working tree diff, the path
+    /// escapes the repository, or the index can't be updated.
+    pub fn unstage_path(&self, spec: &DiffSpec, path: &str) -> Result<()> {
+        relative_path(path)?;
+        let submodule = self.submodule_of(spec, path)?;
+        let (repo, path, _) = self.file_repository(spec, submodule.as_deref(), path)?;
+        Ok(repo.unstage_path(path)?)
+    }
+
+    /// Stage the `index`th hunk of the file at `path` in the working tree
+    /// diff of `spec`, leaving the file's other changes unstaged.
+    ///
+    /// The hunk is applied to the index as it is shown, so it only applies
+    /// while the file's index entry still matches the diff's base, i.e.
+    /// none of its changes are staged yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `spec` isn't an unstaged working tree diff, the
+    /// hunk doesn't exist, the file isn't valid UTF-8, or the hunk doesn't
+    /// apply to the index.
+    pub fn stage_hunk(&self, spec: &DiffSpec, path: &str, index: usize) -> Result<()> {
+        if !matches!(
+            spec,
+            DiffSpec::WorkingTree {
+                staged_only: false,
+                ..
+            }
+        ) {
+            anyhow::bail!("Hunks can only be staged from a working tree diff");
+        }
+        relative_path(path)?;
+
+        let diffs = self.get_diff(spec)?;
+        let file = diffs
+            .iter()
+            .find(|file| file.filename == path)
+            .ok_or_else(|| anyhow::anyhow!("{path} has no changes"))?;
+        let hunk = file
+            .hunks
+            .get(index)
+            .ok_or_else(|| anyhow::anyhow!("{path} has no hunk {index}"))?;
+        // Its lines were re-encoded for display, so the patch wouldn't
+        // match the file's bytes
+        if file.decoded_lossy {
+            anyhow::bail!(
+                "{path} isn't valid UTF-8, so its hunks can't be staged one by one; stage the whole file instead"
+            );
+        }
+
+        let (repo, path, _) = self.file_repository(spec, file.submodule.as_deref(), path)?;
+        let file = DiffFile {
+            filename: path.to_string(),
+            ..file.clone()
+        };
+        Ok(repo.apply_patch_to_index(&file.hunk_patch(hunk))?)
+    }
+
+    /// Discard the changes to the file at `path` in the working tree diff
+    /// of `spec`, restoring it in the index and working tree to its state
+    /// in the diff's base.
+    ///
+    /// Files the base doesn't have, such as untracked ones, are deleted,
+    /// so they are only discarded with `delete_added`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `spec` isn't a working tree diff, the file has no
+    /// changes, it is new without `delete_added`, or it can't be restored.
+    pub fn discard_changes(&self, spec: &DiffSpec, path: &str, delete_added: bool) -> Result<()> {
+        if !matches!(spec, DiffSpec::WorkingTree { .. }) {
+            anyhow::bail!("Changes can only be discarded from a working tree diff");
+        }
+        relative_path(path)?;
+
+        let diffs = self.get_diff(spec)?;
+        let file = diffs
+            .iter()
+            .find(|file| file.filename == path)
+            .ok_or_else(|| anyhow::anyhow!("{path} has no changes"))?;
+        if file.status == FileStatus::Added && !delete_added {
+            anyhow::bail!("{path} is new, discarding its changes would delete it");
+        }
+
+        let (repo, path, against) = self.file_repository(spec, file.submodule.as_deref(), path)?;
+        Ok(repo.checkout_path(path, against)?)
+    }
+
+    /// The submodule file `path` of the working tree diff of `spec` is in,
+    /// if the diff shows the changes inside submodules.
+    fn submodule_of(&self, spec: &DiffSpec, path: &str) -> Result<Option<String>> {
+        if !self.recurse_submodules || !matches!(spec, DiffSpec::WorkingTree { .. }) {
+            return Ok(None);
+        }
+
+        Ok(self
+            .diff_result(spec)?
+            .files
+            .into_iter()
+            .find(|file| file.path() == Some(path))
+            .and_then(|file| file.submodule))
+    }
+
+    /// The repository holding file `path` of the working tree diff of
+    /// `spec`, the file's path in it, and the reference its changes are
+    /// diffed against there.
+    ///
+    /// Files inside `submodule` belong to the submodule's own repository,
+    /// where they are diffed against its checked out commit.
+    fn file_repository<'a>(
+        &self,
+        spec: &'a DiffSpec,
+        submodule: Option<&str>,
+        path: &'a str,
+    ) -> Result<(Box<dyn GitRepository>, &'a str, &'a str)> {
+        let DiffSpec::WorkingTree { against, .. } = spec else {
+            anyhow::bail!("Files can only be staged or restored from a working tree diff");
+        };
+        let Some(submodule) = submodule else {
+            return Ok((self.open_repo()?, path, against));
+        };
+
+        let path = path
+            .strip_prefix(submodule)
+            .and_then(|path| path.strip_prefix('/'))
+            .ok_or_else(|| anyhow::anyhow!("{path} is not inside submodule {submodule}"))?;
+        let repo = self
+            .backend
+            .open(&self.repo_path.join(relative_path(submodule)?))
+            .map_err(|e| anyhow::anyhow!("{e}"))?;
+
+        Ok((repo, path, "HEAD"))
+    }
+
+    /// The worktrees of the repository, the main one first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the worktrees cannot be read.
+    pub fn worktrees(&self) -> Result<Vec<Worktree>> {
+        Ok(self.open_repo()?.list_worktrees()?)
+    }
+
+    /// The operation in progress, such as a merge or rebase stopped on
+    /// conflicts, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository state cannot be read.
+    pub fn operation_in_progress(&self) -> Result<Option<RepoOperation>> {
+        Ok(self.open_repo()?.operation_in_progress()?)
+    }
+
+    /// The files with unresolved conflicts, with the base, ours and theirs
+    /// versions of each.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index cannot be read.
+    pub fn conflicts(&self) -> Result<Vec<ConflictFile>> {
+        Ok(self.open_repo()?.list_conflicts()?)
+    }
+
+    /// Mark the conflicts of the file at `path` resolved by staging its
+    /// working tree state, like `git add` does during a merge.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path escapes the repository, the file still
+    /// has conflict markers, or the index can't be updated.
+    pub fn mark_resolved(&self, path: &str) -> Result<()> {
+        let relative = relative_path(path)?;
+
+        match std::fs::read_to_string(self.repo_path.join(relative)) {
+            Ok(content) if has_conflict_markers(&content) => {
+                anyhow::bail!("{path} still has conflict markers");
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            // Binary files can't have markers
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        Ok(self.open_repo()?.stage_path(path)?)
+    }
+
+    /// Commit the staged changes with `message`, or amend the last commit
+    /// with them. `sign_off` adds a `Signed-off-by` trailer with the
+    /// committer's identity, like `git commit --signoff`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `message` is blank or the commit can't be made,
+    /// e.g. because nothing is staged.
+    pub fn commit(&self, message: &str, amend: bool, sign_off: bool) -> Result<CommitInfo> {
+        let message = message.trim();
+        if message.is_empty() {
+            anyhow::bail!("The commit message is empty");
+        }
+
+        let repo = self.open_repo()?;
+        let message = if sign_off {
+            with_sign_off(message, &repo.identity()?)
+        } else {
+            format!("{message}\n")
+        };
+
+        Ok(repo.commit(&message, amend)?)
+    }
+
+    /// What the commit [`Self::commit`] would make is based on, to diff the
+    /// staged changes against: HEAD, or HEAD's parent when amending. That
+    /// is the empty tree when there is no such commit, e.g. when amending
+    /// the root commit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository can't be opened.
+    pub fn commit_base(&self, amend: bool) -> Result<String> {
+        let Ok(head) = self.open_repo()?.get_commit("HEAD") else {
+            return Ok(EMPTY_TREE_SHA.to_string());
+        };
+        if !amend {
+            return Ok(head.sha);
+        }
+
+        Ok(head
+            .parent_shas
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| EMPTY_TREE_SHA.to_string()))
+    }
+
+    /// Snapshot the uncommitted changes of the working tree, untracked files
+    /// included, to find out what changed in between two snapshots with
+    /// [`working_tree_changes`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the working tree cannot be diffed.
+    pub fn snapshot_working_tree(&self) -> Result<DiffResult> {
+        let repo = self.open_repo()?;
+        let options = WorkingTreeDiffOptions {
+            staged_only: false,
+            include_untracked: true,
+            include_ignored: false,
+            recurse_submodules: false,
+            diff: DiffOptions::default(),
+        };
+
+        Ok(repo.diff_working_tree("HEAD", options)?)
+    }
+
     /// Get diff files for a given specification.
     ///
     /// # Arguments
@@ -89,24 +532,183 @@ impl<B: GitBackend> LocalDiffProvider<B> {
     ///
     /// Returns an error if the diff cannot be computed.
     pub fn get_diff(&self, spec: &DiffSpec) -> Result<Vec<DiffFile>> {
+        if spec.is_by_commit() {
+            // Concatenate all commit diffs
+            let commit_diffs = self.get_commit_diffs(spec)?;
+            return Ok(commit_diffs.into_iter().flat_map(|c| c.files).collect());
+        }
+
+        let highlighter = SyntaxHighlighter::new();
+
+        // Convert to DiffFile with syntax highlighting
+        Self::convert_and_highlight(self.diff_result(spec)?, &highlighter)
+    }
+
+    /// Get `limit` files of the diff of `spec`, sorted in `order`, from
+    /// `offset` on, and the paths of all of its files.
+    ///
+    /// Files are sorted by their line counts, which are computed without
+    /// loading any hunks, so only the hunks of the files of the page are
+    /// loaded and highlighted and the first files of a very large diff are
+    /// shown without computing the rest of it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the diff cannot be computed.
+    pub fn get_diff_page(
+        &self,
+        spec: &DiffSpec,
+        offset: usize,
+        limit: usize,
+        order: FileOrder,
+    ) -> Result<DiffPage> {
+        if spec.is_by_commit() {
+            let mut files = self.get_diff(spec)?;
+            sort_diff_files(&mut files, order);
+            let paths = files.iter().map(|file| file.filename.clone()).collect();
+            return Ok(DiffPage {
+                files: files.into_iter().skip(offset).take(limit).collect(),
+                paths,
+            });
+        }
+
+        let highlighter = SyntaxHighlighter::new();
         let repo = self.open_repo()?;
+        let diff = self.lazy_diff(repo.as_ref(), spec)?;
+
+        let mut stats: Vec<_> = diff.stats()?.into_iter().enumerate().collect();
+        sort_by_order(&mut stats, order, |(_, stat)| {
+            (
+                stat.path().unwrap_or("unknown"),
+                file_status(stat.status),
+                stat.additions + stat.deletions,
+            )
+        });
+
+        let paths = stats
+            .iter()
+            .map(|(_, stat)| stat.path().unwrap_or("unknown").to_string())
+            .collect();
+        let files = stats
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(index, _)| Self::convert_file(diff.file(index)?, &highlighter))
+            .collect::<Result<_>>()?;
+
+        Ok(DiffPage { files, paths })
+    }
+
+    /// Get the file at `path` in the diff of `spec`, if it changed.
+    ///
+    /// Only that file's hunks are loaded and highlighted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the diff cannot be computed.
+    pub fn get_diff_file(&self, spec: &DiffSpec, path: &str) -> Result<Option<DiffFile>> {
+        Ok(self.get_diff_files(spec, &[path])?.into_iter().next())
+    }
+
+    /// Get the files at `paths` in the diff of `spec`, those that changed,
+    /// in the diff's order.
+    ///
+    /// Only these files' hunks are loaded and highlighted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the diff cannot be computed.
+    pub fn get_diff_files(&self, spec: &DiffSpec, paths: &[&str]) -> Result<Vec<DiffFile>> {
+        if spec.is_by_commit() {
+            return Ok(self
+                .get_diff(spec)?
+                .into_iter()
+                .filter(|file| paths.contains(&file.filename.as_str()))
+                .collect());
+        }
+
         let highlighter = SyntaxHighlighter::new();
+        let repo = self.open_repo()?;
+        let diff = self.lazy_diff(repo.as_ref(), spec)?;
+        diff.stats()?
+            .iter()
+            .enumerate()
+            .filter(|(_, stat)| stat.path().is_some_and(|path| paths.contains(&path)))
+            .map(|(index, _)| Self::convert_file(diff.file(index)?, &highlighter))
+            .collect()
+    }
 
-        let diff_result = match spec {
+    /// The diff of `spec`, with each file's hunks loaded only when asked
+    /// for. `spec` mustn't be reviewed commit by commit.
+    fn lazy_diff<'repo>(
+        &self,
+        repo: &'repo dyn GitRepository,
+        spec: &DiffSpec,
+    ) -> Result<Box<dyn LazyDiff + 'repo>> {
+        let (base, head) = match spec {
             DiffSpec::Range {
                 base,
                 head,
                 three_dot,
+                ..
+            } => self.resolve_range(repo, base, head, *three_dot)?,
+
+            DiffSpec::WorkingTree {
+                against,
+                staged_only,
+                include_untracked,
             } => {
-                let base_sha = if *three_dot {
-                    let base_resolved = repo.resolve_ref(base)?.sha;
-                    let head_resolved = repo.resolve_ref(head)?.sha;
-                    repo.merge_base(&base_resolved, &head_resolved)?
-                } else {
-                    repo.resolve_ref(base)?.sha
+                let options = WorkingTreeDiffOptions {
+                    staged_only: *staged_only,
+                    include_untracked: *include_untracked,
+                    include_ignored: false,
+                    recurse_submodules: self.recurse_submodules,
+                    diff: self.diff_options,
                 };
-                let head_sha = repo.resolve_ref(head)?.sha;
-                repo.diff_commits(&base_sha, &head_sha)?
+                return Ok(repo.diff_working_tree_lazy(against, options)?);
+            }
+
+            DiffSpec::Commit { sha } => (Self::parent_or_empty_tree(repo, sha)?, sha.clone()),
+
+            // From the first commit's parent to the last commit
+            DiffSpec::Commits { shas, .. } => match (shas.first(), shas.last()) {
+                (Some(first), Some(last)) => {
+                    (Self::parent_or_empty_tree(repo, first)?, last.clone())
+                }
+                // Nothing to diff: the empty tree against itself
+                _ => (EMPTY_TREE_SHA.to_string(), EMPTY_TREE_SHA.to_string()),
+            },
+        };
+
+        Ok(repo.diff_commits_lazy(&base, &head, self.diff_options)?)
+    }
+
+    /// The first parent of commit `sha`, or the empty tree for a commit
+    /// without parents.
+    fn parent_or_empty_tree(repo: &dyn GitRepository, sha: &str) -> Result<String> {
+        Ok(repo
+            .get_commit(sha)?
+            .parent_shas
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| EMPTY_TREE_SHA.to_string()))
+    }
+
+    /// The diff of `spec` as returned by the backend, without highlighting.
+    /// `spec` mustn't be reviewed commit by commit.
+    fn diff_result(&self, spec: &DiffSpec) -> Result<DiffResult> {
+        let repo = self.open_repo()?;
+
+        Ok(match spec {
+            DiffSpec::Range {
+                base,
+                head,
+                three_dot,
+                ..
+            } => {
+                let (base_sha, head_sha) =
+                    self.resolve_range(repo.as_ref(), base, head, *three_dot)?;
+                repo.diff_commits(&base_sha, &head_sha, self.diff_options)?
             }
 
             DiffSpec::WorkingTree {
@@ -118,70 +720,183 @@ impl<B: GitBackend> LocalDiffProvider<B> {
                     staged_only: *staged_only,
                     include_untracked: *include_untracked,
                     include_ignored: false,
+                    recurse_submodules: self.recurse_submodules,
+                    diff: self.diff_options,
                 };
-                repo.diff_working_tree(against, options)?
+                repo.diff_working_tree(against, options)?.flatten()
             }
 
-            DiffSpec::Commit { sha } => repo.diff_commit(sha)?,
-
-            DiffSpec::Commits { shas, mode } => match mode {
-                MultiCommitMode::Separate => {
-                    // Concatenate all commit diffs
-                    let mut all_files = Vec::new();
-                    for sha in shas {
-                        let diff = repo.diff_commit(sha)?;
-                        all_files.extend(diff.files);
-                    }
-                    DiffResult { files: all_files }
+            DiffSpec::Commit { sha } => repo.diff_commit(sha, self.diff_options)?,
+
+            DiffSpec::Commits { shas, .. } => {
+                // Diff from first commit's parent to last commit
+                if shas.is_empty() {
+                    return Ok(DiffResult::default());
                 }
-                MultiCommitMode::Squashed => {
-                    // Diff from first commit's parent to last commit
-                    if shas.is_empty() {
-                        return Ok(Vec::new());
-                    }
-                    let first = repo.get_commit(&shas[0])?;
-                    let last_sha = &shas[shas.len() - 1];
-
-                    if let Some(parent) = first.parent_shas.first() {
-                        repo.diff_commits(parent, last_sha)?
-                    } else {
-                        // First commit has no parent - diff against empty tree
-                        repo.diff_commit(&shas[0])?
-                    }
+                let first = repo.get_commit(&shas[0])?;
+                let last_sha = &shas[shas.len() - 1];
+
+                if let Some(parent) = first.parent_shas.first() {
+                    repo.diff_commits(parent, last_sha, self.diff_options)?
+                } else {
+                    // First commit has no parent - diff against empty tree
+                    repo.diff_commit(&shas[0], self.diff_options)?
                 }
-            },
+            }
+        })
+    }
+
+    /// Split a diff into one diff per commit, oldest first, for reviewing it
+    /// commit by commit.
+    ///
+    /// Returns an empty list unless [`DiffSpec::is_by_commit`] is true. The
+    /// signatures of signed commits are verified.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the commits or their diffs cannot be read.
+    pub fn get_commit_diffs(&self, spec: &DiffSpec) -> Result<Vec<CommitDiff>> {
+        if !spec.is_by_commit() {
+            return Ok(Vec::new());
+        }
+
+        let repo = self.open_repo()?;
+        let highlighter = SyntaxHighlighter::new();
+
+        let commits = match spec {
+            DiffSpec::Range {
+                base,
+                head,
+                three_dot,
+                ..
+            } => {
+                let (base_sha, head_sha) =
+                    self.resolve_range(repo.as_ref(), base, head, *three_dot)?;
+                // Commits are listed newest first
+                let mut commits = repo.list_commits(&base_sha, &head_sha)?;
+                commits.reverse();
+                commits
+            }
+            DiffSpec::Commits { shas, .. } => shas
+                .iter()
+                .map(|sha| repo.get_commit(sha))
+                .collect::<Result<Vec<_>, _>>()?,
+            DiffSpec::WorkingTree { .. } | DiffSpec::Commit { .. } => Vec::new(),
         };
 
-        // Convert to DiffFile with syntax highlighting
-        Self::convert_and_highlight(diff_result, &highlighter)
+        commits
+            .into_iter()
+            .map(|mut commit| {
+                if commit.signature.is_some() {
+                    commit.signature = repo.verify_signature(&commit.sha)?;
+                }
+                let diff = repo.diff_commit(&commit.sha, self.diff_options)?;
+                let files = Self::convert_and_highlight(diff, &highlighter)?;
+                Ok(CommitDiff { commit, files })
+            })
+            .collect()
     }
 
-    /// Get metadata about a diff.
+    /// Search a diff for lines containing `query`, ignoring case.
     ///
-    /// # Arguments
+    /// With `include_files`, the working tree contents of the changed files
+    /// are searched too, so matches outside the diff's hunks are found.
+    /// Returns at most `limit` matches, ordered by file and line.
     ///
-    /// * `spec` - The diff specification describing what to diff.
+    /// # Errors
+    ///
+    /// Returns an error if the diff cannot be computed.
+    pub fn search(
+        &self,
+        spec: &DiffSpec,
+        query: &str,
+        include_files: bool,
+        limit: usize,
+    ) -> Result<Vec<SearchMatch>> {
+        let diffs = self.get_diff(spec)?;
+        let mut matches = search_diff(&diffs, query, limit);
+
+        if include_files {
+            let repo = self.open_repo()?;
+            let workdir = repo
+                .workdir()
+                .ok_or_else(|| anyhow::anyhow!("Bare repositories are not supported"))?;
+
+            for file in diffs.iter().filter(|f| f.status != FileStatus::Deleted) {
+                if matches.len() >= limit {
+                    break;
+                }
+
+                // New-side lines already matched in the diff
+                let skip_lines: Vec<u64> = matches
+                    .iter()
+                    .filter(|m| m.filename == file.filename && m.source != MatchSource::Deletion)
+                    .map(|m| m.line_number)
+                    .collect();
+                let Ok(content) = std::fs::read_to_string(workdir.join(&file.filename)) else {
+                    continue;
+                };
+
+                let remaining = limit - matches.len();
+                matches.extend(search_text(
+                    &file.filename,
+                    &content,
+                    query,
+                    &skip_lines,
+                    remaining,
+                ));
+            }
+
+            matches.sort_by_key(|m| {
+                let file_index = diffs.iter().position(|f| f.filename == m.filename);
+                (file_index, m.line_number)
+            });
+        }
+
+        Ok(matches)
+    }
+
+    /// Look up the definitions and references of the symbol `name` in the
+    /// repository, with its index from `indexes`, built from the tracked
+    /// files if needed. Returns at most `limit` references.
     ///
     /// # Errors
     ///
-    /// Returns an error if the metadata cannot be computed.
-    pub fn get_diff_info(&self, spec: &DiffSpec) -> Result<LocalDiffInfo> {
+    /// Returns an error if the repository cannot be opened or the index
+    /// cannot be built.
+    pub fn lookup_symbol(
+        &self,
+        indexes: &SymbolIndexes,
+        name: &str,
+        limit: usize,
+    ) -> Result<SymbolLookup> {
         let repo = self.open_repo()?;
+        // An empty repository has nothing to index until its first commit
+        let head = repo.head().unwrap_or_default();
+        let index = indexes.get(&self.repo_path, &head, || {
+            SymbolIndex::build(&self.repo_path, repo.list_files()?)
+        })?;
 
-        let commits = match spec {
+        Ok(index.lookup(&self.repo_path, name, limit))
+    }
+
+    /// Get the commits a diff is made of (none for working tree diffs).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a ref or commit cannot be resolved.
+    pub fn get_commits(&self, spec: &DiffSpec) -> Result<Vec<CommitInfo>> {
+        let repo = self.open_repo()?;
+
+        Ok(match spec {
             DiffSpec::Range {
                 base,
                 head,
                 three_dot,
+                ..
             } => {
-                let base_sha = if *three_dot {
-                    let base_resolved = repo.resolve_ref(base)?.sha;
-                    let head_resolved = repo.resolve_ref(head)?.sha;
-                    repo.merge_base(&base_resolved, &head_resolved)?
-                } else {
-                    repo.resolve_ref(base)?.sha
-                };
-                let head_sha = repo.resolve_ref(head)?.sha;
+                let (base_sha, head_sha) =
+                    self.resolve_range(repo.as_ref(), base, head, *three_dot)?;
                 repo.list_commits(&base_sha, &head_sha)?
             }
             DiffSpec::Commit { sha } => {
@@ -193,9 +908,44 @@ impl<B: GitBackend> LocalDiffProvider<B> {
                 .collect::<Result<Vec<_>, _>>()
                 .map_err(|e| anyhow::anyhow!("{e}"))?,
             DiffSpec::WorkingTree { .. } => vec![],
-        };
+        })
+    }
 
-        let diffs = self.get_diff(spec)?;
+    /// List the repository's commit authors, most commits first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository's history cannot be walked.
+    pub fn list_authors(&self) -> Result<Vec<Author>> {
+        Ok(self.open_repo()?.list_authors()?)
+    }
+
+    /// Get metadata about a diff.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - The diff specification describing what to diff.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the metadata cannot be computed.
+    pub fn get_diff_info(&self, spec: &DiffSpec) -> Result<LocalDiffInfo> {
+        let repo = self.open_repo()?;
+
+        let commits = self.get_commits(spec)?;
+        // Counting lines doesn't need the hunks
+        let changes: Vec<(u64, u64)> = if spec.is_by_commit() {
+            self.get_diff(spec)?
+                .iter()
+                .map(|f| (f.additions, f.deletions))
+                .collect()
+        } else {
+            self.lazy_diff(repo.as_ref(), spec)?
+                .stats()?
+                .iter()
+                .map(|stat| (stat.additions, stat.deletions))
+                .collect()
+        };
         let is_dirty = repo.is_dirty()?;
 
         Ok(LocalDiffInfo {
@@ -212,9 +962,9 @@ impl<B: GitBackend> LocalDiffProvider<B> {
                 DiffSpec::Range { head, .. } => Some(head.clone()),
                 _ => None,
             },
-            total_additions: diffs.iter().map(|f| f.additions).sum(),
-            total_deletions: diffs.iter().map(|f| f.deletions).sum(),
-            files_changed: diffs.len(),
+            total_additions: changes.iter().map(|(additions, _)| additions).sum(),
+            total_deletions: changes.iter().map(|(_, deletions)| deletions).sum(),
+            files_changed: changes.len(),
             is_dirty,
         })
     }
@@ -223,45 +973,80 @@ impl<B: GitBackend> LocalDiffProvider<B> {
         result: DiffResult,
         highlighter: &SyntaxHighlighter,
     ) -> Result<Vec<DiffFile>> {
-        let mut files = Vec::new();
-
-        for file_diff in result.files {
-            let filename = file_diff
-                .new_path
-                .clone()
-                .or_else(|| file_diff.old_path.clone())
-                .unwrap_or_else(|| "unknown".to_string());
-
-            let status = match file_diff.status {
-                DiffStatus::Added | DiffStatus::Untracked => FileStatus::Added,
-                DiffStatus::Deleted => FileStatus::Deleted,
-                DiffStatus::Modified => FileStatus::Modified,
-                DiffStatus::Renamed | DiffStatus::Copied => FileStatus::Renamed,
-            };
+        result
+            .files
+            .into_iter()
+            .map(|file_diff| Self::convert_file(file_diff, highlighter))
+            .collect()
+    }
 
-            if file_diff.binary || file_diff.patch.is_none() {
-                // Binary file - no diff content
-                files.push(DiffFile {
-                    filename,
-                    status,
-                    additions: 0,
-                    deletions: 0,
-                    hunks: vec![],
-                });
-                continue;
-            }
+    fn convert_file(file_diff: FileDiff, highlighter: &SyntaxHighlighter) -> Result<DiffFile> {
+        let filename = file_diff.path().unwrap_or("unknown").to_string();
+        let mode_change = file_diff
+            .mode_change()
+            .map(|(old_mode, new_mode)| ModeChange { old_mode, new_mode });
 
-            let patch = file_diff.patch.unwrap();
-            let (additions, deletions) = chadreview_diff::parser::count_additions_deletions(&patch);
+        let status = file_status(file_diff.status);
 
-            let diff_file =
-                parse_unified_diff(&filename, status, additions, deletions, &patch, highlighter)
+        if file_diff.binary {
+            // Binary file - no diff content
+            return Ok(DiffFile {
+                filename,
+                status,
+                additions: 0,
+                deletions: 0,
+                mode_change,
+                hunks: vec![],
+                decoded_lossy: false,
+                submodule: file_diff.submodule,
+            });
+        }
+
+        let (additions, deletions) = file_diff.line_changes();
+        let mut hunks = Vec::with_capacity(file_diff.hunks.len());
+        for hunk in file_diff.hunks {
+            let mut lines = Vec::with_capacity(hunk.lines.len());
+            for line in hunk.lines {
+                let line_type = match line.origin {
+                    LineOrigin::Context => LineType::Context,
+                    LineOrigin::Addition => LineType::Addition,
+                    LineOrigin::Deletion => LineType::Deletion,
+                };
+                let mut content = line.content;
+                if content.ends_with('\r') {
+                    content.pop();
+                }
+                let highlighted_html = highlight_to_html(highlighter, &filename, &content)
                     .map_err(|e| anyhow::anyhow!("{e}"))?;
 
-            files.push(diff_file);
+                lines.push(DiffLine {
+                    line_type,
+                    old_line_number: line.old_lineno.map(u64::from),
+                    new_line_number: line.new_lineno.map(u64::from),
+                    content,
+                    highlighted_html,
+                });
+            }
+
+            hunks.push(DiffHunk {
+                old_start: hunk.old_start.into(),
+                old_lines: hunk.old_lines.into(),
+                new_start: hunk.new_start.into(),
+                new_lines: hunk.new_lines.into(),
+                lines,
+            });
         }
 
-        Ok(files)
+        Ok(DiffFile {
+            filename,
+            status,
+            additions,
+            deletions,
+            mode_change,
+            hunks,
+            decoded_lossy: file_diff.decoded_lossy,
+            submodule: file_diff.submodule,
+        })
     }
 }
 
@@ -270,6 +1055,603 @@ impl<B: GitBackend> Clone for LocalDiffProvider<B> {
         Self {
             backend: Arc::clone(&self.backend),
             repo_path: self.repo_path.clone(),
+            diff_options: self.diff_options,
+            recurse_submodules: self.recurse_submodules,
+        }
+    }
+}
+
+/// Narrow down the commits of a diff reviewed commit by commit, as split by
+/// [`LocalDiffProvider::get_commit_diffs`], to the ones passing `filter`.
+///
+/// With a path filter, only the commits changing files under the path are
+/// kept, showing just those files.
+#[must_use]
+pub fn filter_commit_diffs(
+    commit_diffs: Vec<CommitDiff>,
+    filter: &CommitFilter,
+) -> Vec<CommitDiff> {
+    commit_diffs
+        .into_iter()
+        .filter(|commit_diff| filter.matches_commit(&commit_diff.commit))
+        .filter_map(|mut commit_diff| {
+            if filter.path.is_some() {
+                commit_diff
+                    .files
+                    .retain(|file| filter.matches_path(&file.filename));
+                if commit_diff.files.is_empty() {
+                    return None;
+                }
+            }
+            Some(commit_diff)
+        })
+        .collect()
+}
+
+/// Sort the files of a diff in `order`. Files that tie, like ones with as
+/// many changed lines, are sorted by path.
+pub fn sort_diff_files(files: &mut [DiffFile], order: FileOrder) {
+    sort_by_order(files, order, |file| {
+        (
+            file.filename.as_str(),
+            file.status.clone(),
+            file.additions + file.deletions,
+        )
+    });
+}
+
+/// Sort `items` in `order` by the path, status and number of changed lines
+/// `key` gives for each.
+fn sort_by_order<T>(
+    items: &mut [T],
+    order: FileOrder,
+    key: impl Fn(&T) -> (&str, FileStatus, u64),
+) {
+    const fn status_rank(status: &FileStatus) -> u8 {
+        match status {
+            FileStatus::Added => 0,
+            FileStatus::Modified => 1,
+            FileStatus::Renamed => 2,
+            FileStatus::Deleted => 3,
+        }
+    }
+
+    fn directory(path: &str) -> &str {
+        path.rsplit_once('/').map_or("", |(directory, _)| directory)
+    }
+
+    items.sort_by(|a, b| {
+        let (a_path, a_status, a_changes) = key(a);
+        let (b_path, b_status, b_changes) = key(b);
+        match order {
+            FileOrder::Path => std::cmp::Ordering::Equal,
+            FileOrder::Changes => b_changes.cmp(&a_changes),
+            FileOrder::Status => status_rank(&a_status).cmp(&status_rank(&b_status)),
+            FileOrder::Directory => directory(a_path).cmp(directory(b_path)),
         }
+        .then_with(|| a_path.cmp(b_path))
+    });
+}
+
+/// The status a file of a diff is shown with.
+const fn file_status(status: DiffStatus) -> FileStatus {
+    match status {
+        DiffStatus::Added | DiffStatus::Untracked => FileStatus::Added,
+        DiffStatus::Deleted => FileStatus::Deleted,
+        DiffStatus::Modified => FileStatus::Modified,
+        DiffStatus::Renamed | DiffStatus::Copied => FileStatus::Renamed,
+    }
+}
+
+/// The working tree changes made between two snapshots taken with
+/// [`LocalDiffProvider::snapshot_working_tree`], as a unified diff, or
+/// `None` if nothing changed.
+///
+/// Each file whose changes differ between the snapshots is shown with its
+/// changes against `HEAD` after, so a file that already had changes shows
+/// them too. A file whose changes were undone shows them reverted.
+#[must_use]
+pub fn working_tree_changes(before: &DiffResult, after: &DiffResult) -> Option<String> {
+    let key = |file: &FileDiff| file.new_path.clone().or_else(|| file.old_path.clone());
+    let mut patch = String::new();
+
+    for file in &after.files {
+        let unchanged = before
+            .files
+            .iter()
+            .any(|earlier| key(earlier) == key(file) && earlier.hunks == file.hunks);
+        if !unchanged {
+            push_file_patch(&mut patch, file, file.patch());
+        }
+    }
+    for file in &before.files {
+        if !after.files.iter().any(|later| key(later) == key(file)) {
+            let undone = FileDiff {
+                hunks: file.hunks.iter().map(Hunk::reversed).collect(),
+                ..file.clone()
+            };
+            push_file_patch(&mut patch, file, undone.patch());
+        }
+    }
+
+    (!patch.is_empty()).then_some(patch)
+}
+
+fn push_file_patch(patch: &mut String, file: &FileDiff, hunks: Option<String>) {
+    let old_path = file.old_path.as_ref().or(file.new_path.as_ref());
+    let new_path = file.new_path.as_ref().or(file.old_path.as_ref());
+    let (Some(old_path), Some(new_path)) = (old_path, new_path) else {
+        return;
+    };
+
+    writeln!(patch, "diff --git a/{old_path} b/{new_path}").unwrap();
+    match hunks {
+        Some(hunks) => {
+            writeln!(patch, "--- a/{old_path}\n+++ b/{new_path}").unwrap();
+            patch.push_str(&hunks);
+            if !hunks.ends_with('\n') {
+                patch.push('\n');
+            }
+        }
+        None => patch.push_str("Binary files differ\n"),
+    }
+}
+
+/// `message` ending with a `Signed-off-by` trailer for `identity`, added
+/// to its trailers if it ends with some and left out if it already has it.
+fn with_sign_off(message: &str, identity: &Identity) -> String {
+    let message = message.trim_end();
+    let trailer = format!("Signed-off-by: {} <{}>", identity.name, identity.email);
+
+    let last_paragraph = message.rsplit("\n\n").next().unwrap_or_default();
+    if last_paragraph.lines().any(|line| line == trailer) {
+        return format!("{message}\n");
+    }
+    let has_trailers = message.contains("\n\n")
+        && last_paragraph.lines().all(|line| {
+            line.split_once(": ")
+                .is_some_and(|(key, _)| !key.is_empty() && !key.contains(' '))
+        });
+
+    if has_trailers {
+        format!("{message}\n{trailer}\n")
+    } else {
+        format!("{message}\n\n{trailer}\n")
+    }
+}
+
+/// Whether `content` still has the `<<<<<<<`/`>>>>>>>` lines git marks
+/// conflicts with.
+fn has_conflict_markers(content: &str) -> bool {
+    content.lines().any(|line| {
+        line.starts_with("<<<<<<< ")
+            || line.starts_with(">>>>>>> ")
+            || line == "<<<<<<<"
+            || line == ">>>>>>>"
+    })
+}
+
+/// `path` as a path relative to the repository root, rejecting ones that
+/// could escape it.
+fn relative_path(path: &str) -> Result<&Path> {
+    let relative = Path::new(path);
+    if path.is_empty()
+        || !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+    {
+        anyhow::bail!("Invalid file path: {path}");
+    }
+
+    Ok(relative)
+}
+
+/// Replace line `line` (1-based) of `content` with `replacement`, or return
+/// `None` if there is no such line.
+fn replace_line(content: &str, line: u64, replacement: &str) -> Option<String> {
+    let index = usize::try_from(line).ok()?.checked_sub(1)?;
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+
+    let mut lines: Vec<&str> = content.lines().collect();
+    if index >= lines.len() {
+        return None;
+    }
+
+    if replacement.is_empty() {
+        lines.remove(index);
+    } else {
+        lines.splice(index..=index, replacement.lines());
+    }
+
+    let mut updated = lines.join(newline);
+    if content.ends_with('\n') && !lines.is_empty() {
+        updated.push_str(newline);
+    }
+
+    Some(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use chadreview_git_backend_git2::Git2Backend;
+    use chadreview_git_backend_models::Line;
+
+    use super::*;
+
+    #[test]
+    fn test_replace_line() {
+        assert_eq!(
+            replace_line("a\nb\nc\n", 2, "x\ny").as_deref(),
+            Some("a\nx\ny\nc\n")
+        );
+        assert_eq!(replace_line("a\nb", 2, "x").as_deref(), Some("a\nx"));
+        assert_eq!(replace_line("a\nb\n", 1, "").as_deref(), Some("b\n"));
+        assert_eq!(replace_line("a\n", 1, "").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn test_replace_line_preserves_crlf() {
+        assert_eq!(
+            replace_line("a\r\nb\r\n", 1, "x").as_deref(),
+            Some("x\r\nb\r\n")
+        );
+    }
+
+    #[test]
+    fn test_replace_line_out_of_range() {
+        assert!(replace_line("a\n", 0, "x").is_none());
+        assert!(replace_line("a\n", 2, "x").is_none());
+    }
+
+    #[test]
+    fn test_with_sign_off() {
+        let identity = Identity {
+            name: "Alice".to_string(),
+            email: "alice@example.com".to_string(),
+        };
+        let trailer = "Signed-off-by: Alice <alice@example.com>";
+
+        assert_eq!(
+            with_sign_off("Fix bug\n", &identity),
+            format!("Fix bug\n\n{trailer}\n")
+        );
+        assert_eq!(
+            with_sign_off(
+                "Fix bug\n\nCo-authored-by: Bob <bob@example.com>",
+                &identity
+            ),
+            format!("Fix bug\n\nCo-authored-by: Bob <bob@example.com>\n{trailer}\n")
+        );
+        assert_eq!(
+            with_sign_off("Fix bug\n\nIt was broken: badly so", &identity),
+            format!("Fix bug\n\nIt was broken: badly so\n\n{trailer}\n")
+        );
+        assert_eq!(
+            with_sign_off(&format!("Fix bug\n\n{trailer}"), &identity),
+            format!("Fix bug\n\n{trailer}\n")
+        );
+    }
+
+    #[test]
+    fn test_has_conflict_markers() {
+        assert!(has_conflict_markers(
+            "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> feature\n"
+        ));
+        assert!(!has_conflict_markers("a\n=======\nb\n"));
+        assert!(!has_conflict_markers("// <<<<<<< in a comment\n"));
+    }
+
+    #[test]
+    fn test_relative_path() {
+        assert!(relative_path("src/lib.rs").is_ok());
+        assert!(relative_path("").is_err());
+        assert!(relative_path("/etc/passwd").is_err());
+        assert!(relative_path("src/../../secret").is_err());
+    }
+
+    fn commit_diff(author: &str, parents: usize, files: &[&str]) -> CommitDiff {
+        CommitDiff {
+            commit: CommitInfo {
+                sha: format!("{author}-sha"),
+                short_sha: format!("{author}-sha"),
+                message: "Change".to_string(),
+                summary: "Change".to_string(),
+                author_name: author.to_string(),
+                author_email: format!("{author}@example.com"),
+                timestamp: 0,
+                parent_shas: vec!["parent".to_string(); parents],
+                signature: None,
+            },
+            files: files
+                .iter()
+                .map(|filename| DiffFile {
+                    filename: (*filename).to_string(),
+                    status: FileStatus::Modified,
+                    additions: 1,
+                    deletions: 0,
+                    mode_change: None,
+                    hunks: vec![],
+                    decoded_lossy: false,
+                    submodule: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_filter_commit_diffs() {
+        let commit_diffs = vec![
+            commit_diff("ada", 1, &["src/lib.rs", "README.md"]),
+            commit_diff("grace", 1, &["src/main.rs"]),
+            commit_diff("ada", 2, &["src/lib.rs"]),
+            commit_diff("ada", 1, &["docs/guide.md"]),
+        ];
+        let filter = CommitFilter {
+            author: Some("ada".to_string()),
+            path: Some("src".to_string()),
+            hide_merges: true,
+        };
+
+        let filtered = filter_commit_diffs(commit_diffs, &filter);
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].commit.author_name, "ada");
+        let files: Vec<_> = filtered[0]
+            .files
+            .iter()
+            .map(|f| f.filename.as_str())
+            .collect();
+        assert_eq!(files, vec!["src/lib.rs"]);
+    }
+
+    #[test]
+    fn test_sort_diff_files() {
+        let file = |filename: &str, status: FileStatus, additions: u64| DiffFile {
+            filename: filename.to_string(),
+            status,
+            additions,
+            deletions: 0,
+            mode_change: None,
+            hunks: vec![],
+            decoded_lossy: false,
+            submodule: None,
+        };
+        let mut files = vec![
+            file("src/lib.rs", FileStatus::Modified, 3),
+            file("src/a/mod.rs", FileStatus::Deleted, 0),
+            file("src/main.rs", FileStatus::Added, 10),
+            file("README.md", FileStatus::Modified, 3),
+        ];
+        let sorted = |files: &[DiffFile]| -> Vec<String> {
+            files.iter().map(|f| f.filename.clone()).collect()
+        };
+
+        sort_diff_files(&mut files, FileOrder::Path);
+        assert_eq!(
+            sorted(&files),
+            vec!["README.md", "src/a/mod.rs", "src/lib.rs", "src/main.rs"]
+        );
+
+        sort_diff_files(&mut files, FileOrder::Changes);
+        assert_eq!(
+            sorted(&files),
+            vec!["src/main.rs", "README.md", "src/lib.rs", "src/a/mod.rs"]
+        );
+
+        sort_diff_files(&mut files, FileOrder::Status);
+        assert_eq!(
+            sorted(&files),
+            vec!["src/main.rs", "README.md", "src/lib.rs", "src/a/mod.rs"]
+        );
+
+        sort_diff_files(&mut files, FileOrder::Directory);
+        assert_eq!(
+            sorted(&files),
+            vec!["README.md", "src/lib.rs", "src/main.rs", "src/a/mod.rs"]
+        );
+    }
+
+    /// A hunk of `lines`, each prefixed with its origin like in a patch.
+    fn hunk(old_start: u32, new_start: u32, section: &str, lines: &[&str]) -> Hunk {
+        let mut old_lineno = old_start;
+        let mut new_lineno = new_start;
+        let lines: Vec<Line> = lines
+            .iter()
+            .map(|line| {
+                let (origin, content) = line.split_at(1);
+                let origin = match origin {
+                    "+" => LineOrigin::Addition,
+                    "-" => LineOrigin::Deletion,
+                    _ => LineOrigin::Context,
+                };
+                let old = (origin != LineOrigin::Addition).then(|| {
+                    old_lineno += 1;
+                    old_lineno - 1
+                });
+                let new = (origin != LineOrigin::Deletion).then(|| {
+                    new_lineno += 1;
+                    new_lineno - 1
+                });
+                Line {
+                    origin,
+                    old_lineno: old,
+                    new_lineno: new,
+                    content: content.to_string(),
+                }
+            })
+            .collect();
+
+        Hunk {
+            old_start,
+            old_lines: old_lineno - old_start,
+            new_start,
+            new_lines: new_lineno - new_start,
+            section: section.to_string(),
+            lines,
+        }
+    }
+
+    fn file_diff(filename: &str, hunk: Hunk) -> FileDiff {
+        FileDiff {
+            old_path: Some(filename.to_string()),
+            new_path: Some(filename.to_string()),
+            status: DiffStatus::Modified,
+            old_mode: Some(0o100_644),
+            new_mode: Some(0o100_644),
+            hunks: vec![hunk],
+            binary: false,
+            decoded_lossy: false,
+            submodule: None,
+        }
+    }
+
+    #[test]
+    fn test_working_tree_changes() {
+        let before = DiffResult {
+            files: vec![
+                file_diff("kept.rs", hunk(1, 1, "", &["-a", "+b"])),
+                file_diff("undone.rs", hunk(1, 1, "", &[" x", "-y"])),
+            ],
+            submodules: vec![],
+        };
+        let after = DiffResult {
+            files: vec![
+                file_diff("kept.rs", hunk(1, 1, "", &["-a", "+b"])),
+                file_diff("edited.rs", hunk(3, 3, "fn main", &["+z"])),
+            ],
+            submodules: vec![],
+        };
+
+        assert_eq!(
+            working_tree_changes(&before, &after).as_deref(),
+            Some(
+                "diff --git a/edited.rs b/edited.rs\n--- a/edited.rs\n+++ b/edited.rs\n@@ -3,0 +3 @@ fn main\n+z\n\
+                 diff --git a/undone.rs b/undone.rs\n--- a/undone.rs\n+++ b/undone.rs\n@@ -1 +1,2 @@\n x\n+y\n"
+            )
+        );
+        assert_eq!(working_tree_changes(&after, &after), None);
+    }
+
+    fn init_repo(path: &Path) -> git2::Repository {
+        let repo = git2::Repository::init(path).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test User").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        repo
+    }
+
+    /// Commit `files` along with whatever else is staged.
+    fn commit(repo: &git2::Repository, files: &[(&str, &str)]) {
+        let mut index = repo.index().unwrap();
+        for (path, content) in files {
+            std::fs::write(repo.workdir().unwrap().join(path), content).unwrap();
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = repo.signature().unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Commit",
+            &tree,
+            &parents,
+        )
+        .unwrap();
+    }
+
+    /// A repository with submodule `lib`, whose `lib.rs` is modified in the
+    /// working tree, and a provider showing the changes inside it.
+    fn modified_submodule() -> (tempfile::TempDir, LocalDiffProvider<Git2Backend>) {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = init_repo(dir.path());
+        commit(&repo, &[("README.md", "# Test\n")]);
+
+        let lib_repo = init_repo(&dir.path().join("lib"));
+        commit(&lib_repo, &[("lib.rs", "fn lib() {}\n")]);
+        let mut submodule = repo
+            .submodule("https://example.com/lib.git", Path::new("lib"), false)
+            .unwrap();
+        submodule.add_finalize().unwrap();
+        commit(&repo, &[]);
+
+        std::fs::write(dir.path().join("lib/lib.rs"), "fn lib() {}\nfn more() {}\n").unwrap();
+
+        let provider = LocalDiffProvider::new(Arc::new(Git2Backend::new()), dir.path().into())
+            .with_submodules(true);
+        (dir, provider)
+    }
+
+    fn working_tree() -> DiffSpec {
+        DiffSpec::WorkingTree {
+            against: "HEAD".to_string(),
+            staged_only: false,
+            include_untracked: false,
+        }
+    }
+
+    #[test]
+    fn test_discard_changes_in_submodule() {
+        let (dir, provider) = modified_submodule();
+        let spec = working_tree();
+
+        let diffs = provider.get_diff(&spec).unwrap();
+        let file = diffs
+            .iter()
+            .find(|file| file.filename == "lib/lib.rs")
+            .unwrap();
+        assert_eq!(file.submodule.as_deref(), Some("lib"));
+
+        provider
+            .discard_changes(&spec, "lib/lib.rs", false)
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("lib/lib.rs")).unwrap(),
+            "fn lib() {}\n"
+        );
+        assert!(
+            !provider
+                .get_diff(&spec)
+                .unwrap()
+                .iter()
+                .any(|file| file.filename == "lib/lib.rs")
+        );
+    }
+
+    #[test]
+    fn test_stage_path_in_submodule() {
+        let (dir, provider) = modified_submodule();
+        let spec = working_tree();
+
+        provider.stage_path(&spec, "lib/lib.rs").unwrap();
+        assert!(
+            provider
+                .staged_paths("HEAD")
+                .unwrap()
+                .contains("lib/lib.rs")
+        );
+
+        let lib_repo = git2::Repository::open(dir.path().join("lib")).unwrap();
+        let statuses = lib_repo.statuses(None).unwrap();
+        let status = statuses.get(0).unwrap();
+        assert_eq!(status.path(), Some("lib.rs"));
+        assert!(status.status().is_index_modified());
+
+        provider.unstage_path(&spec, "lib/lib.rs").unwrap();
+        assert!(
+            !provider
+                .staged_paths("HEAD")
+                .unwrap()
+                .contains("lib/lib.rs")
+        );
     }
 }
@@ -0,0 +1,174 @@
+//! Cache of the refs and merge bases resolved in each repository.
+//!
+//! Every page and file of a range diff resolves the range's endpoints
+//! again, and a three-dot range its merge base too, which adds up on big
+//! repositories. Merge bases of two commits never change, so they are kept
+//! as they are. Resolved refs are dropped whenever the repository's refs
+//! change, as told by [`GitRepository::refs_fingerprint`], and the caches
+//! of repositories that are no longer viewed are dropped by
+//! [`evict_idle_caches`].
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use chadreview_git_backend::GitRepository;
+use chadreview_git_backend_models::{GitBackendError, ResolvedRef};
+
+/// How many refs or merge bases are kept per repository before starting
+/// over.
+const MAX_ENTRIES: usize = 256;
+
+/// How many repositories have a cache before starting over.
+const MAX_REPOS: usize = 32;
+
+static CACHES: LazyLock<Mutex<HashMap<PathBuf, RefCache>>> = LazyLock::new(Mutex::default);
+
+#[derive(Debug)]
+struct RefCache {
+    last_used: Instant,
+    /// Fingerprint of the refs the refs in `refs` were resolved with.
+    refs_fingerprint: Option<u64>,
+    refs: HashMap<String, ResolvedRef>,
+    /// Merge bases by the SHAs of the two commits.
+    merge_bases: HashMap<(String, String), String>,
+}
+
+impl Default for RefCache {
+    fn default() -> Self {
+        Self {
+            last_used: Instant::now(),
+            refs_fingerprint: None,
+            refs: HashMap::new(),
+            merge_bases: HashMap::new(),
+        }
+    }
+}
+
+impl RefCache {
+    /// Mark the cache as used now.
+    fn touch(&mut self) -> &mut Self {
+        self.last_used = Instant::now();
+        self
+    }
+
+    /// The cached refs, dropped first if the refs changed since they were
+    /// resolved.
+    fn refs_at(&mut self, fingerprint: u64) -> &mut HashMap<String, ResolvedRef> {
+        if self.refs_fingerprint != Some(fingerprint) {
+            self.refs.clear();
+            self.refs_fingerprint = Some(fingerprint);
+        }
+        &mut self.refs
+    }
+}
+
+fn with_cache<T>(repo_path: &Path, f: impl FnOnce(&mut RefCache) -> T) -> T {
+    let mut caches = CACHES.lock().unwrap_or_else(PoisonError::into_inner);
+    if caches.len() >= MAX_REPOS && !caches.contains_key(repo_path) {
+        caches.clear();
+    }
+    f(caches.entry(repo_path.to_path_buf()).or_default().touch())
+}
+
+/// Drop the cached refs and merge bases of the repositories that weren't
+/// used for `idle`. Returns the number of repositories dropped.
+pub fn evict_idle_caches(idle: Duration) -> usize {
+    let mut caches = CACHES.lock().unwrap_or_else(PoisonError::into_inner);
+    let before = caches.len();
+    caches.retain(|_, cache| cache.last_used.elapsed() < idle);
+    before - caches.len()
+}
+
+fn insert_bounded<K: Eq + Hash, V>(map: &mut HashMap<K, V>, key: K, value: V) {
+    if map.len() >= MAX_ENTRIES {
+        map.clear();
+    }
+    map.insert(key, value);
+}
+
+/// Resolve `ref_name` in `repo`, the repository at `repo_path`, reusing the
+/// last resolution unless the refs changed since.
+///
+/// # Errors
+///
+/// Returns an error if the ref cannot be resolved.
+pub fn resolve_ref(
+    repo_path: &Path,
+    repo: &dyn GitRepository,
+    ref_name: &str,
+) -> Result<ResolvedRef, GitBackendError> {
+    // Without a way to tell the refs changed, nothing can be reused
+    let Ok(fingerprint) = repo.refs_fingerprint() else {
+        return repo.resolve_ref(ref_name);
+    };
+
+    let cached = with_cache(repo_path, |cache| {
+        cache.refs_at(fingerprint).get(ref_name).cloned()
+    });
+    if let Some(resolved) = cached {
+        return Ok(resolved);
+    }
+
+    let resolved = repo.resolve_ref(ref_name)?;
+    with_cache(repo_path, |cache| {
+        insert_bounded(
+            cache.refs_at(fingerprint),
+            ref_name.to_string(),
+            resolved.clone(),
+        );
+    });
+    Ok(resolved)
+}
+
+/// Get the merge base of the commits `sha1` and `sha2` in `repo`, the
+/// repository at `repo_path`, reusing the last one found for them.
+///
+/// # Errors
+///
+/// Returns an error if either commit doesn't exist or they have no common
+/// ancestor.
+pub fn merge_base(
+    repo_path: &Path,
+    repo: &dyn GitRepository,
+    sha1: &str,
+    sha2: &str,
+) -> Result<String, GitBackendError> {
+    let key = (sha1.to_string(), sha2.to_string());
+    let cached = with_cache(repo_path, |cache| cache.merge_bases.get(&key).cloned());
+    if let Some(merge_base) = cached {
+        return Ok(merge_base);
+    }
+
+    let merge_base = repo.merge_base(sha1, sha2)?;
+    with_cache(repo_path, |cache| {
+        insert_bounded(&mut cache.merge_bases, key, merge_base.clone());
+    });
+    Ok(merge_base)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_cached(repo_path: &Path) -> bool {
+        CACHES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(repo_path)
+    }
+
+    #[test]
+    fn test_evict_idle_caches() {
+        let repo_path = Path::new("/nonexistent/evict-idle");
+        with_cache(repo_path, |_| ());
+
+        evict_idle_caches(Duration::from_hours(1));
+        assert!(is_cached(repo_path));
+
+        evict_idle_caches(Duration::ZERO);
+        assert!(!is_cached(repo_path));
+    }
+}
@@ -0,0 +1,298 @@
+//! Symbol lookup within a repository.
+//!
+//! Definitions come from an index built by running `ctags` over the
+//! repository's tracked files. References are the whole-word occurrences of
+//! a symbol's name in the working tree contents of those files, found when
+//! looking it up, so they are always current. The index is only rebuilt
+//! when `HEAD` moves, so definitions added or moved by uncommitted changes
+//! may be missing or off by a few lines.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context as _, Result};
+use chadreview_local_diff_models::{SymbolLocation, SymbolLookup};
+
+/// Command building the symbol index. Any `ctags` understanding
+/// `--excmd=number` works, such as Universal Ctags.
+pub const CTAGS_COMMAND: &str = "ctags";
+
+/// A definition found by `ctags`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Definition {
+    path: String,
+    line: u64,
+    kind: Option<String>,
+}
+
+/// The definitions of a repository's symbols, by name.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolIndex {
+    definitions: HashMap<String, Vec<Definition>>,
+    /// Tracked files, searched for references.
+    files: Vec<String>,
+}
+
+impl SymbolIndex {
+    /// Build the index of `files`, relative to `workdir`, with `ctags`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `ctags` cannot be run or fails.
+    pub fn build(workdir: &Path, files: Vec<String>) -> Result<Self> {
+        let mut child = Command::new(CTAGS_COMMAND)
+            .args([
+                "--excmd=number",
+                "--fields=+K",
+                "--sort=no",
+                "-f",
+                "-",
+                "-L",
+                "-",
+            ])
+            .current_dir(workdir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to run {CTAGS_COMMAND}; is it installed?"))?;
+
+        // Write the file list while the output is read, so neither pipe fills
+        let mut stdin = child.stdin.take().context("Failed to open ctags stdin")?;
+        let list = files.join("\n");
+        let writer = std::thread::spawn(move || stdin.write_all(list.as_bytes()));
+
+        let output = child.wait_with_output()?;
+        // ctags may stop reading early; its exit status tells whether it failed
+        let _ = writer.join();
+        if !output.status.success() {
+            anyhow::bail!("{CTAGS_COMMAND} exited with {}", output.status);
+        }
+
+        Ok(Self::parse(&String::from_utf8_lossy(&output.stdout), files))
+    }
+
+    /// Build the index from `ctags` output in its tab-separated format with
+    /// line number addresses and long kinds, e.g.
+    /// `main\tsrc/main.rs\t3;"\tfunction`.
+    #[must_use]
+    pub fn parse(tags: &str, files: Vec<String>) -> Self {
+        let mut definitions: HashMap<String, Vec<Definition>> = HashMap::new();
+
+        for tag in tags.lines().filter(|line| !line.starts_with("!_TAG_")) {
+            let mut fields = tag.split('\t');
+            let (Some(name), Some(path), Some(address)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let Ok(line) = address.trim_end_matches(";\"").parse::<u64>() else {
+                continue;
+            };
+            let kind = fields
+                .next()
+                .map(|kind| kind.strip_prefix("kind:").unwrap_or(kind).to_string());
+
+            definitions
+                .entry(name.to_string())
+                .or_default()
+                .push(Definition {
+                    path: path.to_string(),
+                    line,
+                    kind,
+                });
+        }
+
+        Self { definitions, files }
+    }
+
+    /// Look up the definitions and references of `name`, reading the files
+    /// from `workdir`. Returns at most `limit` references.
+    #[must_use]
+    pub fn lookup(&self, workdir: &Path, name: &str, limit: usize) -> SymbolLookup {
+        let mut contents: HashMap<&str, Option<String>> = HashMap::new();
+        let mut definitions = vec![];
+        for definition in self.definitions.get(name).into_iter().flatten() {
+            let content = contents
+                .entry(&definition.path)
+                .or_insert_with(|| fs::read_to_string(workdir.join(&definition.path)).ok());
+            let text = content.as_deref().and_then(|content| {
+                let index = usize::try_from(definition.line).ok()?.checked_sub(1)?;
+                content.lines().nth(index)
+            });
+
+            definitions.push(SymbolLocation {
+                path: definition.path.clone(),
+                line: definition.line,
+                kind: definition.kind.clone(),
+                text: text.unwrap_or_default().to_string(),
+            });
+        }
+
+        let mut references = vec![];
+        let mut references_truncated = false;
+        'files: for path in &self.files {
+            let Ok(content) = fs::read_to_string(workdir.join(path)) else {
+                continue;
+            };
+            for (text, line) in content.lines().zip(1..) {
+                if !contains_word(text, name)
+                    || definitions
+                        .iter()
+                        .any(|definition| definition.path == *path && definition.line == line)
+                {
+                    continue;
+                }
+                if references.len() == limit {
+                    references_truncated = true;
+                    break 'files;
+                }
+                references.push(SymbolLocation {
+                    path: path.clone(),
+                    line,
+                    kind: None,
+                    text: text.to_string(),
+                });
+            }
+        }
+
+        SymbolLookup {
+            definitions,
+            references,
+            references_truncated,
+        }
+    }
+}
+
+/// Whether `text` contains `word` not directly preceded or followed by
+/// another identifier character.
+fn contains_word(text: &str, word: &str) -> bool {
+    if word.is_empty() {
+        return false;
+    }
+
+    let is_identifier = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+        !before.is_some_and(is_identifier) && !after.is_some_and(is_identifier)
+    })
+}
+
+/// Symbol indexes of the repositories looked up in, each rebuilt when its
+/// `HEAD` moves.
+#[derive(Debug, Default)]
+pub struct SymbolIndexes {
+    indexes: Mutex<HashMap<PathBuf, (String, Arc<SymbolIndex>)>>,
+}
+
+impl SymbolIndexes {
+    /// Get the index of the repository at `repo_path` for `head`, building
+    /// it with `build` if there is none or it was built for another `HEAD`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index has to be built and `build` fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the indexes lock is poisoned.
+    pub fn get(
+        &self,
+        repo_path: &Path,
+        head: &str,
+        build: impl FnOnce() -> Result<SymbolIndex>,
+    ) -> Result<Arc<SymbolIndex>> {
+        let cached = self
+            .indexes
+            .lock()
+            .unwrap()
+            .get(repo_path)
+            .filter(|(built_at, _)| built_at == head)
+            .map(|(_, index)| Arc::clone(index));
+        if let Some(index) = cached {
+            return Ok(index);
+        }
+
+        let index = Arc::new(build()?);
+        self.indexes.lock().unwrap().insert(
+            repo_path.to_path_buf(),
+            (head.to_string(), Arc::clone(&index)),
+        );
+        Ok(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_ctags_output() {
+        let index = SymbolIndex::parse(
+            "!_TAG_FILE_FORMAT\t2\t/extended format/\n\
+             render\tsrc/view.rs\t12;\"\tfunction\n\
+             render\tsrc/other.rs\t3;\"\tkind:method\n\
+             View\tsrc/view.rs\t4;\"\tstruct\n\
+             broken\tsrc/view.rs\t/^fn broken/;\"\tfunction\n",
+            vec![],
+        );
+
+        assert_eq!(
+            index.definitions["render"],
+            vec![
+                Definition {
+                    path: "src/view.rs".to_string(),
+                    line: 12,
+                    kind: Some("function".to_string()),
+                },
+                Definition {
+                    path: "src/other.rs".to_string(),
+                    line: 3,
+                    kind: Some("method".to_string()),
+                },
+            ]
+        );
+        assert_eq!(index.definitions["View"].len(), 1);
+        assert!(!index.definitions.contains_key("broken"));
+    }
+
+    #[test]
+    fn test_lookup_finds_whole_word_references() {
+        let workdir = env::temp_dir().join(format!("chadreview-symbols-{}", std::process::id()));
+        fs::create_dir_all(workdir.join("src")).unwrap();
+        fs::write(
+            workdir.join("src/lib.rs"),
+            "fn render() {}\n\nfn main() {\n    render();\n    render_all();\n}\n",
+        )
+        .unwrap();
+        fs::write(workdir.join("src/app.rs"), "use crate::render;\n").unwrap();
+        let index = SymbolIndex::parse(
+            "render\tsrc/lib.rs\t1;\"\tfunction\n",
+            vec!["src/app.rs".to_string(), "src/lib.rs".to_string()],
+        );
+
+        let lookup = index.lookup(&workdir, "render", 10);
+        assert_eq!(lookup.definitions.len(), 1);
+        assert_eq!(lookup.definitions[0].text, "fn render() {}");
+        let references: Vec<_> = lookup
+            .references
+            .iter()
+            .map(|r| (r.path.as_str(), r.line))
+            .collect();
+        assert_eq!(references, vec![("src/app.rs", 1), ("src/lib.rs", 4)]);
+        assert!(!lookup.references_truncated);
+
+        let lookup = index.lookup(&workdir, "render", 1);
+        assert_eq!(lookup.references.len(), 1);
+        assert!(lookup.references_truncated);
+
+        let _ = fs::remove_dir_all(&workdir);
+    }
+}
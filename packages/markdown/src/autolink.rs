@@ -0,0 +1,459 @@
+//! Linkification of issue, commit and user references.
+
+use pulldown_cmark::{CowStr, Event, LinkType, Tag, TagEnd};
+
+/// Maximum length of a `@username` (GitHub's limit).
+const MAX_USERNAME_LEN: usize = 39;
+/// Minimum length of a bare commit SHA reference.
+const MIN_SHA_LEN: usize = 7;
+/// Length a commit SHA is shortened to when displayed.
+const SHORT_SHA_LEN: usize = 7;
+
+/// Git hosting provider that references are linked to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceProvider {
+    GitHub,
+    GitLab,
+    Bitbucket,
+    /// Gitea or Forgejo.
+    Gitea,
+}
+
+impl ReferenceProvider {
+    /// Look up a provider by its identifier (e.g. `PullRequest::provider`).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "github" => Some(Self::GitHub),
+            "gitlab" => Some(Self::GitLab),
+            "bitbucket" => Some(Self::Bitbucket),
+            "gitea" | "forgejo" => Some(Self::Gitea),
+            _ => None,
+        }
+    }
+
+    /// Base URL of the provider's public instance.
+    #[must_use]
+    pub const fn default_base_url(self) -> &'static str {
+        match self {
+            Self::GitHub => "https://github.com",
+            Self::GitLab => "https://gitlab.com",
+            Self::Bitbucket => "https://bitbucket.org",
+            Self::Gitea => "https://gitea.com",
+        }
+    }
+}
+
+/// Configuration for linking `#123`, `owner/repo#123`, commit SHA and
+/// `@username` references.
+///
+/// `#123` and SHA references are only linked when a repository is set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceLinks {
+    provider: ReferenceProvider,
+    base_url: String,
+    repository: Option<(String, String)>,
+}
+
+impl ReferenceLinks {
+    /// Link references to the provider's public instance.
+    #[must_use]
+    pub fn new(provider: ReferenceProvider) -> Self {
+        Self {
+            provider,
+            base_url: provider.default_base_url().to_string(),
+            repository: None,
+        }
+    }
+
+    /// Link references to a self-hosted instance (e.g. GitHub Enterprise).
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into();
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Resolve `#123` and commit SHA references against a repository.
+    #[must_use]
+    pub fn with_repository(mut self, owner: impl Into<String>, repo: impl Into<String>) -> Self {
+        self.repository = Some((owner.into(), repo.into()));
+        self
+    }
+
+    fn issue_url(&self, owner: &str, repo: &str, number: &str) -> String {
+        let base = &self.base_url;
+        match self.provider {
+            ReferenceProvider::GitHub | ReferenceProvider::Bitbucket | ReferenceProvider::Gitea => {
+                format!("{base}/{owner}/{repo}/issues/{number}")
+            }
+            ReferenceProvider::GitLab => format!("{base}/{owner}/{repo}/-/issues/{number}"),
+        }
+    }
+
+    fn commit_url(&self, owner: &str, repo: &str, sha: &str) -> String {
+        let base = &self.base_url;
+        match self.provider {
+            ReferenceProvider::GitHub | ReferenceProvider::Gitea => {
+                format!("{base}/{owner}/{repo}/commit/{sha}")
+            }
+            ReferenceProvider::GitLab => format!("{base}/{owner}/{repo}/-/commit/{sha}"),
+            ReferenceProvider::Bitbucket => format!("{base}/{owner}/{repo}/commits/{sha}"),
+        }
+    }
+
+    fn user_url(&self, username: &str) -> String {
+        format!("{}/{username}", self.base_url)
+    }
+
+    fn url_for(&self, reference: &Reference<'_>) -> Option<String> {
+        match *reference {
+            Reference::Issue {
+                repository: Some((owner, repo)),
+                number,
+            } => Some(self.issue_url(owner, repo, number)),
+            Reference::Issue {
+                repository: None,
+                number,
+            } => {
+                let (owner, repo) = self.repository.as_ref()?;
+                Some(self.issue_url(owner, repo, number))
+            }
+            Reference::Commit(sha) => {
+                let (owner, repo) = self.repository.as_ref()?;
+                Some(self.commit_url(owner, repo, sha))
+            }
+            Reference::Mention(username) => Some(self.user_url(username)),
+        }
+    }
+}
+
+/// A reference found in text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reference<'a> {
+    Issue {
+        repository: Option<(&'a str, &'a str)>,
+        number: &'a str,
+    },
+    Commit(&'a str),
+    Mention(&'a str),
+}
+
+impl Reference<'_> {
+    fn display_text<'t>(&self, matched: &'t str) -> &'t str {
+        match self {
+            Self::Commit(_) => &matched[..SHORT_SHA_LEN],
+            Self::Issue { .. } | Self::Mention(_) => matched,
+        }
+    }
+}
+
+/// Push `text` as events, wrapping recognized references in links.
+pub fn push_linkified<'a>(events: &mut Vec<Event<'a>>, text: CowStr<'a>, links: &ReferenceLinks) {
+    let mut last = 0;
+    let mut pending = Vec::new();
+
+    for (start, end, reference) in find_references(&text) {
+        let Some(url) = links.url_for(&reference) else {
+            continue;
+        };
+        let matched = &text[start..end];
+
+        if start > last {
+            pending.push(Event::Text(text[last..start].to_string().into()));
+        }
+        pending.push(Event::Start(Tag::Link {
+            link_type: LinkType::Inline,
+            dest_url: url.into(),
+            title: CowStr::Borrowed(""),
+            id: CowStr::Borrowed(""),
+        }));
+        pending.push(Event::Text(
+            reference.display_text(matched).to_string().into(),
+        ));
+        pending.push(Event::End(TagEnd::Link));
+        last = end;
+    }
+
+    if pending.is_empty() {
+        events.push(Event::Text(text));
+        return;
+    }
+
+    if last < text.len() {
+        pending.push(Event::Text(text[last..].to_string().into()));
+    }
+    events.extend(pending);
+}
+
+/// Find all references in `text` as `(start, end, reference)` byte ranges.
+fn find_references(text: &str) -> Vec<(usize, usize, Reference<'_>)> {
+    let bytes = text.as_bytes();
+    let mut references = Vec::new();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let at_boundary = index == 0 || !is_reference_continuation(bytes[index - 1]);
+        if at_boundary && let Some((end, reference)) = parse_reference(text, index) {
+            references.push((index, end, reference));
+            index = end;
+        } else {
+            index += 1;
+        }
+    }
+
+    references
+}
+
+/// Whether a byte preceding a reference means it is part of a larger token
+/// (a word, path, URL, email address or entity).
+const fn is_reference_continuation(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'-' | b'/' | b'.' | b'@' | b'#' | b'&')
+}
+
+/// Whether a reference ending before `index` is followed by a word boundary.
+fn ends_at_boundary(bytes: &[u8], index: usize) -> bool {
+    bytes
+        .get(index)
+        .is_none_or(|&byte| !byte.is_ascii_alphanumeric() && byte != b'_')
+}
+
+fn parse_reference(text: &str, start: usize) -> Option<(usize, Reference<'_>)> {
+    let bytes = text.as_bytes();
+
+    match bytes[start] {
+        b'@' => parse_mention(text, start + 1),
+        b'#' => {
+            let end = take_while(bytes, start + 1, |byte| byte.is_ascii_digit());
+            (end > start + 1 && ends_at_boundary(bytes, end)).then(|| {
+                (
+                    end,
+                    Reference::Issue {
+                        repository: None,
+                        number: &text[start + 1..end],
+                    },
+                )
+            })
+        }
+        byte if byte.is_ascii_alphanumeric() => {
+            parse_cross_repo_issue(text, start).or_else(|| parse_commit(text, start))
+        }
+        _ => None,
+    }
+}
+
+fn parse_mention(text: &str, start: usize) -> Option<(usize, Reference<'_>)> {
+    let bytes = text.as_bytes();
+    let end = take_while(bytes, start, |byte| {
+        byte.is_ascii_alphanumeric() || byte == b'-'
+    });
+    let username = &text[start..end];
+
+    let valid = !username.is_empty()
+        && username.len() <= MAX_USERNAME_LEN
+        && !username.starts_with('-')
+        && !username.ends_with('-')
+        && ends_at_boundary(bytes, end)
+        // `@org/team` mentions are not linked
+        && bytes.get(end) != Some(&b'/');
+
+    valid.then_some((end, Reference::Mention(username)))
+}
+
+fn parse_cross_repo_issue(text: &str, start: usize) -> Option<(usize, Reference<'_>)> {
+    let bytes = text.as_bytes();
+
+    let owner_end = take_while(bytes, start, |byte| {
+        byte.is_ascii_alphanumeric() || byte == b'-'
+    });
+    if bytes.get(owner_end) != Some(&b'/') {
+        return None;
+    }
+
+    let repo_start = owner_end + 1;
+    let repo_end = take_while(bytes, repo_start, |byte| {
+        byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.')
+    });
+    if repo_end == repo_start || bytes.get(repo_end) != Some(&b'#') {
+        return None;
+    }
+
+    let number_start = repo_end + 1;
+    let end = take_while(bytes, number_start, |byte| byte.is_ascii_digit());
+    if end == number_start || !ends_at_boundary(bytes, end) {
+        return None;
+    }
+
+    Some((
+        end,
+        Reference::Issue {
+            repository: Some((&text[start..owner_end], &text[repo_start..repo_end])),
+            number: &text[number_start..end],
+        },
+    ))
+}
+
+fn parse_commit(text: &str, start: usize) -> Option<(usize, Reference<'_>)> {
+    let bytes = text.as_bytes();
+    let end = take_while(bytes, start, |byte| byte.is_ascii_hexdigit());
+    let sha = &text[start..end];
+
+    // Require both digits and letters so plain numbers and words like
+    // `deadbeef` aren't mistaken for commits
+    let valid = (MIN_SHA_LEN..=40).contains(&sha.len())
+        && sha.bytes().any(|byte| byte.is_ascii_digit())
+        && sha.bytes().any(|byte| byte.is_ascii_alphabetic())
+        && ends_at_boundary(bytes, end);
+
+    valid.then_some((end, Reference::Commit(sha)))
+}
+
+fn take_while(bytes: &[u8], start: usize, predicate: impl Fn(u8) -> bool) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|&byte| !predicate(byte))
+        .map_or(bytes.len(), |offset| start + offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn references(text: &str) -> Vec<Reference<'_>> {
+        find_references(text)
+            .into_iter()
+            .map(|(_, _, reference)| reference)
+            .collect()
+    }
+
+    #[test]
+    fn test_find_issue_references() {
+        assert_eq!(
+            references("Fixes #12 and (owner/repo.rs#345)."),
+            vec![
+                Reference::Issue {
+                    repository: None,
+                    number: "12",
+                },
+                Reference::Issue {
+                    repository: Some(("owner", "repo.rs")),
+                    number: "345",
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_mentions() {
+        assert_eq!(
+            references("cc @octo-cat, @someone."),
+            vec![
+                Reference::Mention("octo-cat"),
+                Reference::Mention("someone")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_commit_shas() {
+        assert_eq!(
+            references("Reverts a1b2c3d4e5 and 0123456789abcdef0123456789abcdef01234567"),
+            vec![
+                Reference::Commit("a1b2c3d4e5"),
+                Reference::Commit("0123456789abcdef0123456789abcdef01234567"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ignores_non_references() {
+        assert!(references("me@example.com").is_empty());
+        assert!(references("https://example.com/page#123").is_empty());
+        assert!(references("issue#12 #12abc &#123;").is_empty());
+        assert!(references("1234567 deadbeef abc123").is_empty());
+        assert!(references("@org/team @-nope").is_empty());
+    }
+
+    #[test]
+    fn test_urls_per_provider() {
+        let github = ReferenceLinks::new(ReferenceProvider::GitHub).with_repository("o", "r");
+        let gitlab = ReferenceLinks::new(ReferenceProvider::GitLab)
+            .with_base_url("https://git.example.com/")
+            .with_repository("o", "r");
+        let issue = Reference::Issue {
+            repository: None,
+            number: "7",
+        };
+
+        assert_eq!(
+            github.url_for(&issue).as_deref(),
+            Some("https://github.com/o/r/issues/7")
+        );
+        assert_eq!(
+            gitlab.url_for(&issue).as_deref(),
+            Some("https://git.example.com/o/r/-/issues/7")
+        );
+        assert_eq!(
+            gitlab.url_for(&Reference::Commit("abc1234")).as_deref(),
+            Some("https://git.example.com/o/r/-/commit/abc1234")
+        );
+        assert_eq!(
+            github.url_for(&Reference::Mention("me")).as_deref(),
+            Some("https://github.com/me")
+        );
+    }
+
+    #[test]
+    fn test_gitea_and_enterprise_urls() {
+        let gitea = ReferenceLinks::new(ReferenceProvider::Gitea)
+            .with_base_url("https://codeberg.org")
+            .with_repository("o", "r");
+        let enterprise = ReferenceLinks::new(ReferenceProvider::GitHub)
+            .with_base_url("https://github.example.com")
+            .with_repository("o", "r");
+        let issue = Reference::Issue {
+            repository: Some(("other", "repo")),
+            number: "7",
+        };
+
+        assert_eq!(
+            gitea.url_for(&issue).as_deref(),
+            Some("https://codeberg.org/other/repo/issues/7")
+        );
+        assert_eq!(
+            gitea.url_for(&Reference::Commit("abc1234")).as_deref(),
+            Some("https://codeberg.org/o/r/commit/abc1234")
+        );
+        assert_eq!(
+            enterprise.url_for(&Reference::Mention("me")).as_deref(),
+            Some("https://github.example.com/me")
+        );
+        assert_eq!(
+            ReferenceProvider::from_name("Forgejo"),
+            Some(ReferenceProvider::Gitea)
+        );
+    }
+
+    #[test]
+    fn test_repository_relative_references_need_repository() {
+        let links = ReferenceLinks::new(ReferenceProvider::GitHub);
+
+        assert_eq!(
+            links.url_for(&Reference::Issue {
+                repository: None,
+                number: "7",
+            }),
+            None
+        );
+        assert_eq!(links.url_for(&Reference::Commit("abc1234")), None);
+        assert_eq!(
+            links
+                .url_for(&Reference::Issue {
+                    repository: Some(("a", "b")),
+                    number: "7",
+                })
+                .as_deref(),
+            Some("https://github.com/a/b/issues/7")
+        );
+    }
+}
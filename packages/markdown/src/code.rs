@@ -0,0 +1,100 @@
+//! Syntax highlighting for fenced code blocks.
+
+use std::sync::LazyLock;
+
+use chadreview_syntax::SyntaxHighlighter;
+
+/// Theme used for the code block stylesheet.
+pub const CODE_THEME: &str = "InspiredGitHub";
+
+static HIGHLIGHTER: LazyLock<SyntaxHighlighter> = LazyLock::new(SyntaxHighlighter::new);
+
+/// Extract the language token from a code fence info string.
+///
+/// Handles info strings such as `rust`, `rust,ignore` and `js title="x"`.
+pub(crate) fn fence_language(info: &str) -> Option<&str> {
+    info.split(|c: char| c.is_whitespace() || c == ',' || c == '{')
+        .next()
+        .map(str::trim)
+        .filter(|lang| !lang.is_empty())
+}
+
+/// Render a fenced code block as highlighted HTML.
+///
+/// Returns `None` when the language is unknown or highlighting fails, in
+/// which case the caller should fall back to the default `<pre><code>`
+/// output.
+pub(crate) fn render_code_block(language: &str, code: &str) -> Option<String> {
+    if !HIGHLIGHTER.supports_language(language) {
+        return None;
+    }
+
+    let highlighted = HIGHLIGHTER
+        .highlight_language_to_html(language, code)
+        .inspect_err(|e| log::warn!("Failed to highlight {language} code block: {e}"))
+        .ok()?;
+
+    let language = escape_html(language);
+
+    Some(format!(
+        "<pre class=\"syn-code\" data-lang=\"{language}\"><code class=\"language-{language}\">{highlighted}</code></pre>\n"
+    ))
+}
+
+static CODE_THEME_CSS: LazyLock<String> = LazyLock::new(|| {
+    let mut css = HIGHLIGHTER
+        .theme_css(CODE_THEME)
+        .inspect_err(|e| log::error!("Failed to generate code theme CSS: {e}"))
+        .unwrap_or_default();
+    css.push_str(
+        ".syn-code { padding: 12px; border-radius: 6px; overflow-x: auto; font-size: 12px; }\n",
+    );
+    css.push_str(concat!(
+        ".suggestion { border: 1px solid #d0d7de; border-radius: 6px; overflow: hidden; }\n",
+        ".suggestion-header { padding: 4px 8px; background: #f6f8fa; font-size: 12px; color: #57606a; }\n",
+        ".suggestion-diff { margin: 0; padding: 8px 0; overflow-x: auto; font-size: 12px; }\n",
+        ".suggestion-add, .suggestion-del { display: block; padding: 0 8px; }\n",
+        ".suggestion-add { background: #e6ffec; }\n",
+        ".suggestion-del { background: #ffebe9; }\n",
+    ));
+    css
+});
+
+/// CSS for the classes emitted in highlighted code blocks.
+#[must_use]
+pub fn code_theme_css() -> &'static str {
+    &CODE_THEME_CSS
+}
+
+pub(crate) fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fence_language() {
+        assert_eq!(fence_language("rust"), Some("rust"));
+        assert_eq!(fence_language("rust,ignore"), Some("rust"));
+        assert_eq!(fence_language("js title=\"x\""), Some("js"));
+        assert_eq!(fence_language(""), None);
+    }
+
+    #[test]
+    fn test_render_code_block_known_language() {
+        let html = render_code_block("rust", "fn main() {}\n").unwrap();
+        assert!(html.starts_with("<pre class=\"syn-code\" data-lang=\"rust\">"));
+        assert!(html.contains("class=\"syn-"));
+    }
+
+    #[test]
+    fn test_render_code_block_unknown_language() {
+        assert!(render_code_block("not-a-real-language", "hello").is_none());
+    }
+}
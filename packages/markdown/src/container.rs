@@ -0,0 +1,85 @@
+//! Markdown to HyperChad container rendering.
+
+use hyperchad::template::{Containers, container};
+
+use crate::code::code_theme_css;
+use crate::html::{MarkdownOptions, render_markdown, render_markdown_with_scripts};
+
+/// Render markdown into a container for embedding in UI components.
+#[must_use]
+pub fn markdown_to_container(markdown: &str) -> Containers {
+    wrap_html(&render_markdown(markdown), &[])
+}
+
+/// Render markdown into a container with custom options.
+#[must_use]
+pub fn markdown_to_container_with_options(markdown: &str, options: &MarkdownOptions) -> Containers {
+    let (html, scripts) = render_markdown_with_scripts(markdown, options);
+    wrap_html(&html, &scripts)
+}
+
+/// Wrap the rendered `html`, followed by the `scripts` its diagrams and
+/// math need on the client, so pages without them never load them.
+fn wrap_html(html: &str, scripts: &[&str]) -> Containers {
+    let html = format!("{html}{}", scripts.concat());
+
+    container! {
+        div class="markdown-body" {
+            raw { (html) }
+        }
+    }
+}
+
+/// Stylesheet for highlighted code blocks produced by [`markdown_to_container`].
+///
+/// Render this once per page.
+#[must_use]
+pub fn markdown_stylesheet() -> Containers {
+    let style = format!("<style>{}</style>", code_theme_css());
+
+    container! {
+        div {
+            raw { (style) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::MATH_SCRIPT;
+    use crate::mermaid::MERMAID_SCRIPT;
+
+    use super::*;
+
+    fn scripts(markdown: &str, options: &MarkdownOptions) -> Vec<&'static str> {
+        render_markdown_with_scripts(markdown, options).1
+    }
+
+    #[test]
+    fn test_scripts_only_for_rendered_diagrams() {
+        let options = MarkdownOptions::default().with_mermaid();
+
+        assert!(scripts("Just text", &options).is_empty());
+        assert!(scripts("<div class=\"mermaid\">graph TD</div>", &options).is_empty());
+        assert_eq!(
+            scripts(
+                "```mermaid\ngraph TD\n```\n\n```mermaid\ngraph LR\n```",
+                &options
+            ),
+            [MERMAID_SCRIPT]
+        );
+    }
+
+    #[test]
+    fn test_scripts_only_for_rendered_math() {
+        let options = MarkdownOptions::default().with_math();
+
+        assert!(scripts("It costs $5 and $10", &options).is_empty());
+        assert!(scripts("<span class=\"math math-inline\">x</span>", &options).is_empty());
+        assert_eq!(
+            scripts("Euler: $e^{i\\pi} + 1 = 0$", &options),
+            [MATH_SCRIPT]
+        );
+        assert_eq!(scripts("```math\nx^2\n```", &options), [MATH_SCRIPT]);
+    }
+}
@@ -0,0 +1,52 @@
+//! `:emoji:` shortcode lookup.
+
+/// Find the emoji whose shortcodes contain `query`, ignoring case, as
+/// `(shortcode, emoji)` pairs.
+///
+/// Shortcodes starting with the query come first, followed by the ones
+/// containing it elsewhere, each sorted by length and then alphabetically so
+/// the closest matches lead. At most `limit` matches are returned.
+#[must_use]
+pub fn search_emoji(query: &str, limit: usize) -> Vec<(&'static str, &'static str)> {
+    let query = query.to_lowercase();
+
+    let mut matches: Vec<_> = gh_emoji::all()
+        .filter(|(shortcode, _)| shortcode.contains(&query))
+        .collect();
+    matches.sort_by(|(a, _), (b, _)| {
+        b.starts_with(&query)
+            .cmp(&a.starts_with(&query))
+            .then_with(|| a.len().cmp(&b.len()))
+            .then_with(|| a.cmp(b))
+    });
+    matches.truncate(limit);
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_emoji_prefers_prefix_matches() {
+        let matches = search_emoji("smile", 5);
+
+        assert_eq!(matches[0], ("smile", "😄"));
+        let prefixed = matches
+            .iter()
+            .take_while(|(shortcode, _)| shortcode.starts_with("smile"))
+            .count();
+        assert!(
+            matches[prefixed..]
+                .iter()
+                .all(|(shortcode, _)| shortcode.contains("smile"))
+        );
+    }
+
+    #[test]
+    fn test_search_emoji_limits_matches() {
+        assert_eq!(search_emoji("", 10).len(), 10);
+        assert!(search_emoji("not-an-emoji-shortcode", 10).is_empty());
+    }
+}
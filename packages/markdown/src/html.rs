@@ -0,0 +1,496 @@
+//! Markdown to HTML rendering.
+
+use std::borrow::Cow;
+use std::sync::{Arc, LazyLock};
+
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd, html};
+
+use crate::autolink::{ReferenceLinks, push_linkified};
+use crate::code::{fence_language, render_code_block};
+use crate::math::{MATH_SCRIPT, MathRenderer, render_math};
+use crate::mermaid::MermaidRenderer;
+use crate::relative::RepositoryContent;
+use crate::sanitize::{SanitizePolicy, sanitize_html};
+use crate::suggestion::SuggestionRenderer;
+
+static DEFAULT_OPTIONS: LazyLock<MarkdownOptions> = LazyLock::new(MarkdownOptions::default);
+
+/// Hook for rendering fenced code blocks of specific languages.
+///
+/// Renderers are consulted in order before syntax highlighting; the first
+/// one returning `Some` wins. The returned HTML still goes through the
+/// sanitizer, so it must only use tags and attributes the policy allows.
+pub trait CodeFenceRenderer: std::fmt::Debug + Send + Sync {
+    /// Render the code block, or return `None` to leave it to the next renderer.
+    fn render(&self, language: &str, code: &str) -> Option<String>;
+
+    /// Script the rendered blocks need on the client, added once after
+    /// markdown with any of them.
+    fn client_script(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+/// Options controlling markdown rendering.
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownOptions {
+    /// Policy used to sanitize the rendered HTML.
+    pub sanitize_policy: SanitizePolicy,
+    /// Custom renderers for fenced code blocks.
+    pub fence_renderers: Vec<Arc<dyn CodeFenceRenderer>>,
+    /// Link issue, commit and `@mention` references in text.
+    pub reference_links: Option<ReferenceLinks>,
+    /// Parse `$inline$` and `$$display$$` math.
+    pub math: bool,
+    /// Rewrite relative link and image destinations to repository URLs.
+    pub repository_content: Option<RepositoryContent>,
+}
+
+impl MarkdownOptions {
+    /// Add a custom fenced code block renderer.
+    #[must_use]
+    pub fn with_fence_renderer(mut self, renderer: Arc<dyn CodeFenceRenderer>) -> Self {
+        self.fence_renderers.push(renderer);
+        self
+    }
+
+    /// Render ```` ```mermaid ```` fences as diagrams, drawn client-side by a
+    /// script added to the markdown that has them.
+    #[must_use]
+    pub fn with_mermaid(self) -> Self {
+        self.with_fence_renderer(Arc::new(MermaidRenderer))
+    }
+
+    /// Render `$inline$`, `$$display$$` and ```` ```math ```` blocks as math,
+    /// typeset client-side by a script added to the markdown that has it.
+    #[must_use]
+    pub fn with_math(mut self) -> Self {
+        self.math = true;
+        self.with_fence_renderer(Arc::new(MathRenderer))
+    }
+
+    /// Render ```` ```suggestion ```` fences as suggested changes (see
+    /// [`crate::extract_suggestions`]).
+    #[must_use]
+    pub fn with_suggestions(self) -> Self {
+        self.with_fence_renderer(Arc::new(SuggestionRenderer))
+    }
+
+    /// Turn `#123`, `owner/repo#123`, commit SHAs and `@username` into links.
+    #[must_use]
+    pub fn with_reference_links(mut self, links: ReferenceLinks) -> Self {
+        self.reference_links = Some(links);
+        self
+    }
+
+    /// Point relative links and images (e.g. `./docs/img.png`) at a
+    /// repository's files so embedded screenshots load.
+    #[must_use]
+    pub fn with_repository_content(mut self, content: RepositoryContent) -> Self {
+        self.repository_content = Some(content);
+        self
+    }
+
+    /// Set the sanitization policy.
+    #[must_use]
+    pub fn with_sanitize_policy(mut self, policy: SanitizePolicy) -> Self {
+        self.sanitize_policy = policy;
+        self
+    }
+}
+
+/// A fenced code block with a language tag that is being collected.
+struct FencedBlock {
+    language: String,
+    code: String,
+    /// Index in the output events where the block's start event was pushed.
+    start_index: usize,
+}
+
+/// Render markdown to sanitized HTML.
+///
+/// Supports GitHub-flavored extensions (tables, task lists, strikethrough,
+/// footnotes) and `:emoji:` shortcodes. Fenced code blocks with a known
+/// language tag are syntax highlighted; other code blocks are emitted as
+/// plain `<pre><code>`.
+#[must_use]
+pub fn render_markdown(markdown: &str) -> String {
+    render_markdown_with_options(markdown, &DEFAULT_OPTIONS)
+}
+
+/// Render markdown to sanitized HTML with custom options.
+#[must_use]
+pub fn render_markdown_with_options(markdown: &str, options: &MarkdownOptions) -> String {
+    render_markdown_with_scripts(markdown, options).0
+}
+
+/// Render markdown to sanitized HTML with custom options, along with the
+/// scripts its rendered diagrams and math need on the client.
+///
+/// The scripts are those of the blocks the renderers produced, not of
+/// anything found in the output, so raw HTML mimicking a diagram doesn't
+/// load them.
+pub(crate) fn render_markdown_with_scripts(
+    markdown: &str,
+    options: &MarkdownOptions,
+) -> (String, Vec<&'static str>) {
+    let parser = Parser::new_ext(markdown, parser_options(options));
+    let mut scripts = vec![];
+    let events = transform_events(parser, options, &mut scripts);
+
+    let mut output = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut output, events.into_iter());
+
+    (sanitize_html(&output, &options.sanitize_policy), scripts)
+}
+
+fn parser_options(options: &MarkdownOptions) -> Options {
+    let mut parser_options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_GFM;
+
+    if options.math {
+        parser_options |= Options::ENABLE_MATH;
+    }
+
+    parser_options
+}
+
+fn transform_events<'a>(
+    parser: impl Iterator<Item = Event<'a>>,
+    options: &MarkdownOptions,
+    scripts: &mut Vec<&'static str>,
+) -> Vec<Event<'a>> {
+    let replacer = gh_emoji::Replacer::new();
+    let mut events = Vec::new();
+    let mut fenced: Option<FencedBlock> = None;
+    let mut in_code_block = false;
+    let mut link_depth = 0_usize;
+
+    for event in parser {
+        match event {
+            Event::Start(tag @ (Tag::Link { .. } | Tag::Image { .. })) => {
+                link_depth += 1;
+                let tag = match &options.repository_content {
+                    Some(content) => rewrite_relative(tag, content),
+                    None => tag,
+                };
+                events.push(Event::Start(tag));
+            }
+            Event::End(tag @ (TagEnd::Link | TagEnd::Image)) => {
+                link_depth = link_depth.saturating_sub(1);
+                events.push(Event::End(tag));
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                in_code_block = true;
+                if let CodeBlockKind::Fenced(info) = &kind
+                    && let Some(language) = fence_language(info)
+                {
+                    fenced = Some(FencedBlock {
+                        language: language.to_string(),
+                        code: String::new(),
+                        start_index: events.len(),
+                    });
+                }
+                events.push(Event::Start(Tag::CodeBlock(kind)));
+            }
+            Event::Text(text) if in_code_block => {
+                if let Some(block) = &mut fenced {
+                    block.code.push_str(&text);
+                }
+                events.push(Event::Text(text));
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                if let Some(block) = fenced.take()
+                    && let Some((rendered, script)) = render_fenced_block(&block, options)
+                {
+                    if let Some(script) = script {
+                        add_script(scripts, script);
+                    }
+                    // Replace the default `<pre><code>` events with the rendered block
+                    events.truncate(block.start_index);
+                    events.push(Event::Html(rendered.into()));
+                    continue;
+                }
+                events.push(Event::End(TagEnd::CodeBlock));
+            }
+            Event::InlineMath(tex) => {
+                add_script(scripts, MATH_SCRIPT);
+                events.push(Event::InlineHtml(render_math(&tex, false).into()));
+            }
+            Event::DisplayMath(tex) => {
+                add_script(scripts, MATH_SCRIPT);
+                events.push(Event::InlineHtml(render_math(&tex, true).into()));
+            }
+            Event::Text(text) => {
+                let text = replace_emoji(&replacer, text);
+                match &options.reference_links {
+                    Some(links) if link_depth == 0 => push_linkified(&mut events, text, links),
+                    _ => events.push(Event::Text(text)),
+                }
+            }
+            other => events.push(other),
+        }
+    }
+
+    events
+}
+
+fn rewrite_relative<'a>(tag: Tag<'a>, content: &RepositoryContent) -> Tag<'a> {
+    match tag {
+        Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        } => Tag::Link {
+            link_type,
+            dest_url: content
+                .rewrite(&dest_url, false)
+                .map_or(dest_url, Into::into),
+            title,
+            id,
+        },
+        Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        } => Tag::Image {
+            link_type,
+            dest_url: content
+                .rewrite(&dest_url, true)
+                .map_or(dest_url, Into::into),
+            title,
+            id,
+        },
+        other => other,
+    }
+}
+
+/// Render a fenced block, along with the script its renderer needs on the
+/// client.
+fn render_fenced_block(
+    block: &FencedBlock,
+    options: &MarkdownOptions,
+) -> Option<(String, Option<&'static str>)> {
+    options
+        .fence_renderers
+        .iter()
+        .find_map(|renderer| {
+            renderer
+                .render(&block.language, &block.code)
+                .map(|html| (html, renderer.client_script()))
+        })
+        .or_else(|| render_code_block(&block.language, &block.code).map(|html| (html, None)))
+}
+
+fn add_script(scripts: &mut Vec<&'static str>, script: &'static str) {
+    if !scripts.contains(&script) {
+        scripts.push(script);
+    }
+}
+
+fn replace_emoji<'a>(replacer: &gh_emoji::Replacer, text: CowStr<'a>) -> CowStr<'a> {
+    let owned = match replacer.replace_all(&text) {
+        Cow::Owned(owned) => Some(owned),
+        Cow::Borrowed(_) => None,
+    };
+
+    owned.map_or(text, CowStr::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::autolink::ReferenceProvider;
+
+    use super::*;
+
+    #[test]
+    fn test_render_basic_markdown() {
+        let html = render_markdown("# Title\n\nSome **bold** text");
+        assert!(html.contains("<h1>Title</h1>"));
+        assert!(html.contains("<strong>bold</strong>"));
+    }
+
+    #[test]
+    fn test_render_emoji_shortcodes() {
+        let html = render_markdown("Looks good :+1:");
+        assert!(html.contains('👍'));
+    }
+
+    #[test]
+    fn test_emoji_not_replaced_in_code() {
+        let html = render_markdown("```\n:+1:\n```");
+        assert!(html.contains(":+1:"));
+    }
+
+    #[test]
+    fn test_fenced_code_with_language_is_highlighted() {
+        let html = render_markdown("```rust\nfn main() {}\n```");
+        assert!(html.contains("<pre class=\"syn-code\" data-lang=\"rust\">"));
+        assert!(html.contains("class=\"syn-"));
+        assert!(!html.contains("<pre><code"));
+    }
+
+    #[test]
+    fn test_fenced_code_without_language_is_plain() {
+        let html = render_markdown("```\nfn main() {}\n```");
+        assert!(html.contains("<pre><code>fn main() {}"));
+        assert!(!html.contains("syn-code"));
+    }
+
+    #[test]
+    fn test_fenced_code_with_unknown_language_is_plain() {
+        let html = render_markdown("```not-a-language\nhello\n```");
+        assert!(html.contains("<code class=\"language-not-a-language\">hello"));
+    }
+
+    #[test]
+    fn test_render_sanitizes_inline_html() {
+        let html = render_markdown(
+            "<img src=x onerror=alert(1)> <kbd>K</kbd>\n\n[x](javascript:alert(1))",
+        );
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("<kbd>K</kbd>"));
+    }
+
+    #[test]
+    fn test_render_with_custom_policy() {
+        let options = MarkdownOptions::default()
+            .with_sanitize_policy(SanitizePolicy::default().without_tag("kbd"));
+        let html = render_markdown_with_options("<kbd>K</kbd>", &options);
+        assert!(!html.contains("<kbd>"));
+    }
+
+    #[test]
+    fn test_mermaid_is_opt_in() {
+        let markdown = "```mermaid\ngraph TD\n  A --> B\n```";
+
+        let html = render_markdown(markdown);
+        assert!(!html.contains("class=\"mermaid\""));
+
+        let options = MarkdownOptions::default().with_mermaid();
+        let html = render_markdown_with_options(markdown, &options);
+        assert!(html.contains("<div class=\"mermaid\">graph TD\n  A --&gt; B\n</div>"));
+    }
+
+    #[test]
+    fn test_custom_fence_renderer() {
+        #[derive(Debug)]
+        struct Shout;
+
+        impl CodeFenceRenderer for Shout {
+            fn render(&self, language: &str, code: &str) -> Option<String> {
+                (language == "shout").then(|| format!("<p>{}</p>", code.trim().to_uppercase()))
+            }
+        }
+
+        let options = MarkdownOptions::default().with_fence_renderer(Arc::new(Shout));
+        let html = render_markdown_with_options("```shout\nhello\n```", &options);
+        assert_eq!(html, "<p>HELLO</p>");
+
+        // Other languages still get highlighted
+        let html = render_markdown_with_options("```rust\nfn x() {}\n```", &options);
+        assert!(html.contains("syn-code"));
+    }
+
+    #[test]
+    fn test_suggestions_are_opt_in() {
+        let markdown = "```suggestion\nlet x = 1;\n```";
+
+        let html = render_markdown(markdown);
+        assert!(!html.contains("class=\"suggestion\""));
+
+        let options = MarkdownOptions::default().with_suggestions();
+        let html = render_markdown_with_options(markdown, &options);
+        assert!(html.contains("<div class=\"suggestion\">"));
+        assert!(html.contains("<span class=\"suggestion-add\">+ let x = 1;</span>"));
+    }
+
+    #[test]
+    fn test_math_is_opt_in() {
+        let markdown = "Euler: $e^{i\\pi} = -1$\n\n$$\n\\sum_{i=0}^n i\n$$";
+
+        let html = render_markdown(markdown);
+        assert!(!html.contains("class=\"math"));
+        assert!(html.contains("$e^{i\\pi} = -1$"));
+
+        let options = MarkdownOptions::default().with_math();
+        let html = render_markdown_with_options(markdown, &options);
+        assert!(html.contains("<span class=\"math math-inline\">e^{i\\pi} = -1</span>"));
+        assert!(html.contains("<span class=\"math math-display\">\n\\sum_{i=0}^n i\n</span>"));
+    }
+
+    #[test]
+    fn test_math_escapes_and_ignores_prices() {
+        let options = MarkdownOptions::default().with_math();
+
+        let html = render_markdown_with_options("$a<b$ costs $5 and $10", &options);
+        assert!(html.contains("<span class=\"math math-inline\">a&lt;b</span>"));
+        assert!(html.contains("costs $5 and $10"));
+
+        let html = render_markdown_with_options("```math\nx^2\n```", &options);
+        assert!(html.contains("<span class=\"math math-display\">x^2</span>"));
+    }
+
+    #[test]
+    fn test_reference_links() {
+        let options = MarkdownOptions::default().with_reference_links(
+            ReferenceLinks::new(ReferenceProvider::GitHub).with_repository("o", "r"),
+        );
+        let html = render_markdown_with_options(
+            "Fixes #12 (see a1b2c3d4e5f6) thanks @octocat\n\n`#13` [#14](https://example.com)",
+            &options,
+        );
+
+        assert!(html.contains("Fixes <a href=\"https://github.com/o/r/issues/12\">#12</a>"));
+        assert!(
+            html.contains("<a href=\"https://github.com/o/r/commit/a1b2c3d4e5f6\">a1b2c3d</a>")
+        );
+        assert!(html.contains("thanks <a href=\"https://github.com/octocat\">@octocat</a>"));
+        assert!(html.contains("<code>#13</code>"));
+        assert!(html.contains("<a href=\"https://example.com\">#14</a>"));
+    }
+
+    #[test]
+    fn test_reference_links_are_opt_in() {
+        let html = render_markdown("Fixes #12 thanks @octocat");
+        assert!(!html.contains("<a"));
+    }
+
+    #[test]
+    fn test_relative_links_and_images() {
+        let options = MarkdownOptions::default().with_repository_content(RepositoryContent::new(
+            ReferenceProvider::GitHub,
+            "owner",
+            "repo",
+            "abc123",
+        ));
+        let html = render_markdown_with_options(
+            "![shot](./docs/img.png) see [setup](docs/setup.md) or [site](https://example.com)",
+            &options,
+        );
+
+        assert!(html.contains("src=\"https://github.com/owner/repo/raw/abc123/docs/img.png\""));
+        assert!(html.contains("href=\"https://github.com/owner/repo/blob/abc123/docs/setup.md\""));
+        assert!(html.contains("href=\"https://example.com\""));
+    }
+
+    #[test]
+    fn test_relative_links_are_opt_in() {
+        let html = render_markdown("![shot](./docs/img.png)");
+        assert!(html.contains("src=\"./docs/img.png\""));
+    }
+
+    #[test]
+    fn test_render_tables_and_tasklists() {
+        let html = render_markdown("| a | b |\n|---|---|\n| 1 | 2 |\n\n- [x] done");
+        assert!(html.contains("<table>"));
+        assert!(html.contains("checkbox"));
+    }
+}
@@ -0,0 +1,41 @@
+#![cfg_attr(feature = "fail-on-warnings", deny(warnings))]
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![allow(clippy::multiple_crate_versions)]
+
+//! Markdown rendering for `ChadReview`.
+//!
+//! Renders comment bodies and PR descriptions to HTML and HyperChad
+//! containers. Fenced code blocks with a language tag are syntax
+//! highlighted with class-based spans from `chadreview_syntax`; the
+//! matching stylesheet is provided by [`markdown_stylesheet`]. Issue,
+//! commit and `@mention` references can optionally be linked to a git
+//! hosting provider (see [`MarkdownOptions::with_reference_links`]), and
+//! relative links and images can be resolved against a repository (see
+//! [`MarkdownOptions::with_repository_content`]). GitHub ```` ```suggestion ````
+//! blocks can be rendered as suggested changes (see
+//! [`MarkdownOptions::with_suggestions`]). The `:emoji:` shortcodes rendered
+//! in comments can be looked up with [`search_emoji`].
+
+mod autolink;
+mod code;
+mod container;
+mod emoji;
+mod html;
+mod math;
+mod mermaid;
+mod relative;
+mod sanitize;
+mod suggestion;
+
+pub use autolink::{ReferenceLinks, ReferenceProvider};
+pub use code::{CODE_THEME, code_theme_css};
+pub use container::{
+    markdown_stylesheet, markdown_to_container, markdown_to_container_with_options,
+};
+pub use emoji::search_emoji;
+pub use html::{CodeFenceRenderer, MarkdownOptions, render_markdown, render_markdown_with_options};
+pub use math::MathRenderer;
+pub use mermaid::MermaidRenderer;
+pub use relative::RepositoryContent;
+pub use sanitize::{SanitizePolicy, USER_CONTENT_ID_PREFIX, filter_dangerous_html, sanitize_html};
+pub use suggestion::{SuggestionRenderer, extract_suggestions};
@@ -0,0 +1,94 @@
+//! Math (TeX) support.
+
+use crate::code::escape_html;
+use crate::html::CodeFenceRenderer;
+
+/// Stylesheet and script that typeset `.math` elements client-side with
+/// `KaTeX`, including elements added later by partial page updates. It's
+/// emitted after every markdown block with math, and only loads `KaTeX` the
+/// first time, from the app's own `public/vendor` like mermaid.
+pub(crate) const MATH_SCRIPT: &str = r#"<style>.math-display { display: block; overflow-x: auto; }</style>
+<script type="module">
+if (!window.chadreviewKatex) {
+window.chadreviewKatex = true;
+const stylesheet = document.createElement("link");
+stylesheet.rel = "stylesheet";
+stylesheet.href = "/public/vendor/katex/katex.min.css";
+document.head.append(stylesheet);
+const { default: katex } = await import("/public/vendor/katex/katex.mjs");
+let pending = false;
+const render = () => {
+    if (pending) return;
+    pending = true;
+    requestAnimationFrame(() => {
+        pending = false;
+        for (const el of document.querySelectorAll(".math:not([data-processed])")) {
+            el.setAttribute("data-processed", "true");
+            katex.render(el.textContent, el, {
+                displayMode: el.classList.contains("math-display"),
+                throwOnError: false,
+            });
+        }
+    });
+};
+render();
+new MutationObserver(render).observe(document.body, { childList: true, subtree: true });
+}
+</script>"#;
+
+/// Render TeX source as an annotated math element.
+///
+/// The source is kept as escaped text so it stays readable until
+/// the `KaTeX` script typesets it.
+pub(crate) fn render_math(tex: &str, display: bool) -> String {
+    let class = if display {
+        "math math-display"
+    } else {
+        "math math-inline"
+    };
+
+    format!("<span class=\"{class}\">{}</span>", escape_html(tex))
+}
+
+/// Renders ```` ```math ```` fences as display math.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MathRenderer;
+
+impl CodeFenceRenderer for MathRenderer {
+    fn render(&self, language: &str, code: &str) -> Option<String> {
+        language
+            .eq_ignore_ascii_case("math")
+            .then(|| format!("<p>{}</p>\n", render_math(code.trim_end(), true)))
+    }
+
+    fn client_script(&self) -> Option<&'static str> {
+        Some(MATH_SCRIPT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_math() {
+        assert_eq!(
+            render_math("a < b", false),
+            "<span class=\"math math-inline\">a &lt; b</span>"
+        );
+        assert_eq!(
+            render_math("x^2", true),
+            "<span class=\"math math-display\">x^2</span>"
+        );
+    }
+
+    #[test]
+    fn test_renders_math_fences_only() {
+        let renderer = MathRenderer;
+        assert_eq!(
+            renderer.render("math", "e^{i\\pi} + 1 = 0\n").unwrap(),
+            "<p><span class=\"math math-display\">e^{i\\pi} + 1 = 0</span></p>\n"
+        );
+        assert!(renderer.render("rust", "fn x() {}").is_none());
+    }
+}
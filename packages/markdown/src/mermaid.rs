@@ -0,0 +1,67 @@
+//! Mermaid diagram support.
+
+use crate::code::escape_html;
+use crate::html::CodeFenceRenderer;
+
+/// Opening tag of a rendered diagram.
+const MERMAID_TAG: &str = "<div class=\"mermaid\">";
+
+/// Script that renders `.mermaid` blocks client-side, including blocks added
+/// later by partial page updates. It's emitted after every markdown block
+/// with a diagram, and only loads mermaid the first time.
+///
+/// Mermaid is served by the app itself from `public/vendor` (see
+/// `vendor-assets.sh`), so diagrams render offline and in share mode, and
+/// no third party can change the script.
+pub(crate) const MERMAID_SCRIPT: &str = r#"<script type="module">
+if (!window.chadreviewMermaid) {
+window.chadreviewMermaid = true;
+const { default: mermaid } = await import("/public/vendor/mermaid/mermaid.esm.min.mjs");
+mermaid.initialize({ startOnLoad: false, securityLevel: "strict" });
+let pending = false;
+const render = () => {
+    if (pending) return;
+    pending = true;
+    requestAnimationFrame(() => {
+        pending = false;
+        mermaid.run({ querySelector: ".mermaid:not([data-processed])" }).catch(console.error);
+    });
+};
+render();
+new MutationObserver(render).observe(document.body, { childList: true, subtree: true });
+}
+</script>"#;
+
+/// Renders ```` ```mermaid ```` fences as `<div class="mermaid">` blocks.
+///
+/// The diagram source is kept as escaped text so it stays readable until
+/// the mermaid script draws it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MermaidRenderer;
+
+impl CodeFenceRenderer for MermaidRenderer {
+    fn render(&self, language: &str, code: &str) -> Option<String> {
+        language
+            .eq_ignore_ascii_case("mermaid")
+            .then(|| format!("{MERMAID_TAG}{}</div>\n", escape_html(code)))
+    }
+
+    fn client_script(&self) -> Option<&'static str> {
+        Some(MERMAID_SCRIPT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_mermaid_only() {
+        let renderer = MermaidRenderer;
+        assert_eq!(
+            renderer.render("mermaid", "graph TD\nA-->B\n").unwrap(),
+            "<div class=\"mermaid\">graph TD\nA--&gt;B\n</div>\n"
+        );
+        assert!(renderer.render("rust", "fn x() {}").is_none());
+    }
+}
@@ -0,0 +1,224 @@
+//! Rewriting of relative links and images to repository content URLs.
+
+use crate::autolink::ReferenceProvider;
+
+/// Configuration for resolving relative link and image destinations
+/// (e.g. `./docs/screenshot.png`) against a repository at a given ref.
+///
+/// Images point at the provider's raw content so they load inline; links
+/// point at the provider's file view. Paths are resolved from the
+/// repository root, or from [`RepositoryContent::with_base_path`] when set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryContent {
+    provider: ReferenceProvider,
+    base_url: String,
+    owner: String,
+    repo: String,
+    git_ref: String,
+    base_path: String,
+}
+
+impl RepositoryContent {
+    /// Resolve against `owner/repo` at `git_ref` (a branch, tag or commit SHA)
+    /// on the provider's public instance.
+    #[must_use]
+    pub fn new(
+        provider: ReferenceProvider,
+        owner: impl Into<String>,
+        repo: impl Into<String>,
+        git_ref: impl Into<String>,
+    ) -> Self {
+        Self {
+            provider,
+            base_url: provider.default_base_url().to_string(),
+            owner: owner.into(),
+            repo: repo.into(),
+            git_ref: git_ref.into(),
+            base_path: String::new(),
+        }
+    }
+
+    /// Use a self-hosted instance (e.g. GitHub Enterprise).
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into();
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Resolve relative paths from a directory in the repository (e.g. the
+    /// directory of the markdown file being rendered).
+    #[must_use]
+    pub fn with_base_path(mut self, base_path: impl Into<String>) -> Self {
+        self.base_path = base_path.into().trim_matches('/').to_string();
+        self
+    }
+
+    /// Rewrite a link or image destination, or return `None` if it isn't
+    /// relative.
+    pub(crate) fn rewrite(&self, destination: &str, image: bool) -> Option<String> {
+        if !is_relative(destination) {
+            return None;
+        }
+
+        let split = destination.find(['?', '#']).unwrap_or(destination.len());
+        let (path, suffix) = destination.split_at(split);
+        let path = self.resolve_path(path);
+        let Self {
+            base_url,
+            owner,
+            repo,
+            git_ref,
+            ..
+        } = self;
+
+        let kind = match (self.provider, image) {
+            (
+                ReferenceProvider::GitHub | ReferenceProvider::Bitbucket | ReferenceProvider::Gitea,
+                true,
+            ) => "raw",
+            (ReferenceProvider::GitHub, false) => "blob",
+            (ReferenceProvider::GitLab, true) => "-/raw",
+            (ReferenceProvider::GitLab, false) => "-/blob",
+            (ReferenceProvider::Bitbucket | ReferenceProvider::Gitea, false) => "src",
+        };
+
+        Some(format!(
+            "{base_url}/{owner}/{repo}/{kind}/{git_ref}/{path}{suffix}"
+        ))
+    }
+
+    /// Join `path` onto the base path, normalizing `.` and `..` segments.
+    ///
+    /// Paths starting with `/` are relative to the repository root, and `..`
+    /// never escapes it.
+    fn resolve_path(&self, path: &str) -> String {
+        let mut segments: Vec<&str> = Vec::new();
+        if !path.starts_with('/') {
+            segments.extend(self.base_path.split('/').filter(|s| !s.is_empty()));
+        }
+
+        for segment in path.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." => {
+                    segments.pop();
+                }
+                segment => segments.push(segment),
+            }
+        }
+
+        segments.join("/")
+    }
+}
+
+/// Whether a destination is a path within the repository rather than a
+/// URL, protocol-relative URL or in-page anchor.
+fn is_relative(destination: &str) -> bool {
+    if destination.is_empty()
+        || destination.starts_with(['#', '?'])
+        || destination.starts_with("//")
+    {
+        return false;
+    }
+
+    // A scheme (`https:`, `mailto:`, ...) comes before any `/`, `?` or `#`
+    let end = destination
+        .find(['/', '?', '#'])
+        .unwrap_or(destination.len());
+    !destination[..end].contains(':')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn github() -> RepositoryContent {
+        RepositoryContent::new(ReferenceProvider::GitHub, "owner", "repo", "abc123")
+    }
+
+    #[test]
+    fn test_rewrites_images_to_raw_content() {
+        assert_eq!(
+            github().rewrite("./docs/img.png", true).unwrap(),
+            "https://github.com/owner/repo/raw/abc123/docs/img.png"
+        );
+        assert_eq!(
+            github().rewrite("/docs/img.png", true).unwrap(),
+            "https://github.com/owner/repo/raw/abc123/docs/img.png"
+        );
+    }
+
+    #[test]
+    fn test_rewrites_links_to_file_view() {
+        assert_eq!(
+            github().rewrite("docs/setup.md#install", false).unwrap(),
+            "https://github.com/owner/repo/blob/abc123/docs/setup.md#install"
+        );
+    }
+
+    #[test]
+    fn test_provider_url_formats() {
+        let gitlab = RepositoryContent::new(ReferenceProvider::GitLab, "group", "project", "main");
+        assert_eq!(
+            gitlab.rewrite("a.png", true).unwrap(),
+            "https://gitlab.com/group/project/-/raw/main/a.png"
+        );
+        assert_eq!(
+            gitlab.rewrite("a.md", false).unwrap(),
+            "https://gitlab.com/group/project/-/blob/main/a.md"
+        );
+
+        let bitbucket = RepositoryContent::new(ReferenceProvider::Bitbucket, "team", "repo", "dev")
+            .with_base_url("https://bitbucket.example.com/");
+        assert_eq!(
+            bitbucket.rewrite("a.md", false).unwrap(),
+            "https://bitbucket.example.com/team/repo/src/dev/a.md"
+        );
+
+        let gitea = RepositoryContent::new(ReferenceProvider::Gitea, "o", "r", "main")
+            .with_base_url("https://codeberg.org");
+        assert_eq!(
+            gitea.rewrite("a.png", true).unwrap(),
+            "https://codeberg.org/o/r/raw/main/a.png"
+        );
+        assert_eq!(
+            gitea.rewrite("a.md", false).unwrap(),
+            "https://codeberg.org/o/r/src/main/a.md"
+        );
+    }
+
+    #[test]
+    fn test_resolves_against_base_path() {
+        let content = github().with_base_path("/docs/guide/");
+        assert_eq!(
+            content.rewrite("../img/a.png", true).unwrap(),
+            "https://github.com/owner/repo/raw/abc123/docs/img/a.png"
+        );
+        assert_eq!(
+            content.rewrite("../../../../a.png", true).unwrap(),
+            "https://github.com/owner/repo/raw/abc123/a.png"
+        );
+        assert_eq!(
+            content.rewrite("/a.png", true).unwrap(),
+            "https://github.com/owner/repo/raw/abc123/a.png"
+        );
+    }
+
+    #[test]
+    fn test_leaves_absolute_destinations_alone() {
+        for destination in [
+            "https://example.com/a.png",
+            "mailto:someone@example.com",
+            "//cdn.example.com/a.png",
+            "#section",
+            "?tab=files",
+            "",
+        ] {
+            assert!(
+                github().rewrite(destination, true).is_none(),
+                "{destination}"
+            );
+        }
+    }
+}
@@ -0,0 +1,697 @@
+//! HTML sanitization for rendered markdown.
+//!
+//! The sanitizer tokenizes the HTML and re-emits only allowlisted tags and
+//! attributes. URL attributes are checked against an allowlist of schemes
+//! after entity decoding, so obfuscated `javascript:` URLs are rejected too.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// Prefix applied to `id` attributes (and matching `#fragment` links) so user
+/// content cannot clobber element ids used by the UI.
+pub const USER_CONTENT_ID_PREFIX: &str = "user-content-";
+
+/// Tags allowed by the default policy.
+const DEFAULT_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "code",
+    "dd",
+    "del",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "section",
+    "span",
+    "strike",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "tt",
+    "ul",
+    "var",
+];
+
+/// Tags removed together with everything inside them.
+const DEFAULT_STRIPPED_CONTENT_TAGS: &[&str] = &[
+    "applet", "frameset", "iframe", "math", "noembed", "noframes", "noscript", "object", "script",
+    "select", "style", "svg", "template", "textarea", "title", "xmp",
+];
+
+/// Attributes allowed on every allowed tag.
+const DEFAULT_GLOBAL_ATTRIBUTES: &[&str] = &["dir", "id", "lang", "title"];
+
+/// Attributes allowed on specific tags.
+const DEFAULT_TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("a", &["href"]),
+    ("blockquote", &["cite"]),
+    ("code", &["class"]),
+    ("del", &["cite"]),
+    ("details", &["open"]),
+    ("div", &["class"]),
+    ("img", &["align", "alt", "height", "src", "width"]),
+    ("input", &["checked", "disabled", "type"]),
+    ("ins", &["cite"]),
+    ("ol", &["start"]),
+    ("pre", &["class", "data-lang"]),
+    ("q", &["cite"]),
+    ("span", &["class"]),
+    ("sup", &["class"]),
+    ("td", &["align", "colspan", "rowspan", "style"]),
+    ("th", &["align", "colspan", "rowspan", "style"]),
+];
+
+/// Attributes whose values are URLs and must pass the scheme check.
+const URL_ATTRIBUTES: &[&str] = &["cite", "href", "src"];
+
+/// URL schemes allowed by the default policy.
+const DEFAULT_URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Allowlist-based sanitization policy.
+///
+/// [`SanitizePolicy::default`] mirrors the HTML GitHub allows in comments
+/// (including `<kbd>`, `<details>`, tables and task list checkboxes).
+/// Use the `with_*`/`without_*` builders to adjust it.
+#[derive(Debug, Clone)]
+pub struct SanitizePolicy {
+    allowed_tags: BTreeSet<String>,
+    stripped_content_tags: BTreeSet<String>,
+    global_attributes: BTreeSet<String>,
+    tag_attributes: BTreeMap<String, BTreeSet<String>>,
+    allowed_url_schemes: BTreeSet<String>,
+    prefix_ids: bool,
+}
+
+impl Default for SanitizePolicy {
+    fn default() -> Self {
+        Self {
+            allowed_tags: to_set(DEFAULT_TAGS),
+            stripped_content_tags: to_set(DEFAULT_STRIPPED_CONTENT_TAGS),
+            global_attributes: to_set(DEFAULT_GLOBAL_ATTRIBUTES),
+            tag_attributes: DEFAULT_TAG_ATTRIBUTES
+                .iter()
+                .map(|(tag, attributes)| ((*tag).to_string(), to_set(attributes)))
+                .collect(),
+            allowed_url_schemes: to_set(DEFAULT_URL_SCHEMES),
+            prefix_ids: true,
+        }
+    }
+}
+
+impl SanitizePolicy {
+    /// Allow an additional tag.
+    #[must_use]
+    pub fn with_allowed_tag(mut self, tag: &str) -> Self {
+        let tag = tag.to_ascii_lowercase();
+        self.stripped_content_tags.remove(&tag);
+        self.allowed_tags.insert(tag);
+        self
+    }
+
+    /// Disallow a tag. Its content is kept.
+    #[must_use]
+    pub fn without_tag(mut self, tag: &str) -> Self {
+        self.allowed_tags.remove(&tag.to_ascii_lowercase());
+        self
+    }
+
+    /// Allow an additional attribute on a tag.
+    #[must_use]
+    pub fn with_tag_attribute(mut self, tag: &str, attribute: &str) -> Self {
+        self.tag_attributes
+            .entry(tag.to_ascii_lowercase())
+            .or_default()
+            .insert(attribute.to_ascii_lowercase());
+        self
+    }
+
+    /// Allow an additional URL scheme (e.g. `ftp`).
+    #[must_use]
+    pub fn with_url_scheme(mut self, scheme: &str) -> Self {
+        self.allowed_url_schemes.insert(scheme.to_ascii_lowercase());
+        self
+    }
+
+    /// Set whether `id` attributes are prefixed with [`USER_CONTENT_ID_PREFIX`].
+    #[must_use]
+    pub const fn with_prefixed_ids(mut self, prefix_ids: bool) -> Self {
+        self.prefix_ids = prefix_ids;
+        self
+    }
+
+    fn allows_tag(&self, tag: &str) -> bool {
+        self.allowed_tags.contains(tag)
+    }
+
+    fn strips_content(&self, tag: &str) -> bool {
+        self.stripped_content_tags.contains(tag)
+    }
+
+    fn allows_attribute(&self, tag: &str, attribute: &str) -> bool {
+        // Event handlers are never allowed, whatever the policy says
+        if attribute.starts_with("on") {
+            return false;
+        }
+
+        self.global_attributes.contains(attribute)
+            || self
+                .tag_attributes
+                .get(tag)
+                .is_some_and(|attributes| attributes.contains(attribute))
+    }
+
+    fn is_safe_url(&self, value: &str) -> bool {
+        let normalized: String = decode_entities(value)
+            .chars()
+            .filter(|c| !c.is_whitespace() && !c.is_control())
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        match normalized.find([':', '/', '?', '#']) {
+            Some(index) if normalized.as_bytes()[index] == b':' => {
+                self.allowed_url_schemes.contains(&normalized[..index])
+            }
+            // No scheme: relative URL
+            _ => true,
+        }
+    }
+}
+
+fn to_set(values: &[&str]) -> BTreeSet<String> {
+    values.iter().map(|value| (*value).to_string()).collect()
+}
+
+/// A parsed start or end tag.
+struct Tag {
+    name: String,
+    attributes: Vec<(String, Option<String>)>,
+    closing: bool,
+    self_closing: bool,
+}
+
+/// A piece of markup starting with `<`.
+enum Markup {
+    /// A comment, doctype or processing instruction (always dropped).
+    Ignored,
+    Tag(Tag),
+}
+
+/// Sanitize HTML using the default policy.
+#[must_use]
+pub fn filter_dangerous_html(html: &str) -> String {
+    sanitize_html(html, &SanitizePolicy::default())
+}
+
+/// Sanitize HTML using the given policy.
+///
+/// Disallowed tags are removed but their content is kept, except for tags
+/// like `<script>` whose content is removed as well. Disallowed attributes,
+/// event handlers and URLs with disallowed schemes are removed. A `<` that
+/// does not start a valid tag is escaped.
+#[must_use]
+pub fn sanitize_html(html: &str, policy: &SanitizePolicy) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(position) = rest.find('<') {
+        output.push_str(&rest[..position]);
+        rest = &rest[position..];
+
+        let Some((markup, length)) = parse_markup(rest) else {
+            output.push_str("&lt;");
+            rest = &rest[1..];
+            continue;
+        };
+        rest = &rest[length..];
+
+        let Markup::Tag(tag) = markup else {
+            continue;
+        };
+
+        if tag.closing {
+            if policy.allows_tag(&tag.name) {
+                write!(output, "</{}>", tag.name).unwrap();
+            }
+        } else if policy.strips_content(&tag.name) {
+            if !tag.self_closing {
+                rest = skip_past_end_tag(rest, &tag.name);
+            }
+        } else if policy.allows_tag(&tag.name) {
+            if tag.name == "input" && !is_task_list_checkbox(&tag) {
+                continue;
+            }
+            write_start_tag(&mut output, &tag, policy);
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn write_start_tag(output: &mut String, tag: &Tag, policy: &SanitizePolicy) {
+    output.push('<');
+    output.push_str(&tag.name);
+
+    for (name, value) in &tag.attributes {
+        if !policy.allows_attribute(&tag.name, name) {
+            continue;
+        }
+
+        let Some(value) = value else {
+            write!(output, " {name}").unwrap();
+            continue;
+        };
+
+        let value = match name.as_str() {
+            "id" if policy.prefix_ids => format!("{USER_CONTENT_ID_PREFIX}{value}"),
+            "href" if policy.prefix_ids && value.starts_with('#') && value.len() > 1 => {
+                format!("#{USER_CONTENT_ID_PREFIX}{}", &value[1..])
+            }
+            "style" if !is_safe_style(value) => continue,
+            _ if URL_ATTRIBUTES.contains(&name.as_str()) && !policy.is_safe_url(value) => continue,
+            _ => value.clone(),
+        };
+
+        write!(output, " {name}=\"{}\"", escape_attribute_value(&value)).unwrap();
+    }
+
+    if tag.self_closing {
+        output.push_str(" />");
+    } else {
+        output.push('>');
+    }
+}
+
+/// Only the disabled checkboxes of task lists (as emitted by the markdown
+/// renderer) are allowed as inputs, so content can't add form fields.
+fn is_task_list_checkbox(tag: &Tag) -> bool {
+    let attribute = |name: &str| tag.attributes.iter().find(|(n, _)| n == name);
+
+    attribute("disabled").is_some()
+        && attribute("type")
+            .and_then(|(_, value)| value.as_deref())
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case("checkbox"))
+}
+
+/// Only table cell alignment (as emitted by the markdown renderer) is allowed.
+fn is_safe_style(value: &str) -> bool {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .all(|declaration| {
+            let Some((property, value)) = declaration.split_once(':') else {
+                return false;
+            };
+            property.trim().eq_ignore_ascii_case("text-align")
+                && matches!(
+                    value.trim().to_ascii_lowercase().as_str(),
+                    "left" | "center" | "right"
+                )
+        })
+}
+
+fn escape_attribute_value(value: &str) -> String {
+    value
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Skip past the end tag `</name>`, dropping everything before it.
+fn skip_past_end_tag<'a>(html: &'a str, name: &str) -> &'a str {
+    let lower = html.to_ascii_lowercase();
+    let needle = format!("</{name}");
+
+    let mut search_from = 0;
+    while let Some(found) = lower[search_from..].find(&needle) {
+        let start = search_from + found;
+        let after_name = start + needle.len();
+
+        // Make sure we matched the whole tag name (e.g. `</style` vs `</styles`)
+        let boundary = lower.as_bytes().get(after_name).copied();
+        if boundary.is_none_or(|b| b == b'>' || b == b'/' || b.is_ascii_whitespace()) {
+            return lower[after_name..]
+                .find('>')
+                .map_or("", |end| &html[after_name + end + 1..]);
+        }
+
+        search_from = after_name;
+    }
+
+    ""
+}
+
+/// Parse markup at the start of `html` (which must start with `<`).
+///
+/// Returns the markup and its length in bytes, or `None` if the `<` does not
+/// start valid markup.
+fn parse_markup(html: &str) -> Option<(Markup, usize)> {
+    let bytes = html.as_bytes();
+
+    if let Some(comment) = html.strip_prefix("<!--") {
+        let length = comment.find("-->").map_or(html.len(), |end| 4 + end + 3);
+        return Some((Markup::Ignored, length));
+    }
+
+    if html.starts_with("<!") || html.starts_with("<?") {
+        return html.find('>').map(|end| (Markup::Ignored, end + 1));
+    }
+
+    let mut index = 1;
+    let closing = bytes.get(index) == Some(&b'/');
+    if closing {
+        index += 1;
+    }
+
+    let name_start = index;
+    if !bytes.get(index).is_some_and(u8::is_ascii_alphabetic) {
+        return None;
+    }
+    while bytes
+        .get(index)
+        .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'-')
+    {
+        index += 1;
+    }
+    let name = html[name_start..index].to_ascii_lowercase();
+
+    let mut attributes = Vec::new();
+    let mut self_closing = false;
+
+    loop {
+        while bytes.get(index).is_some_and(u8::is_ascii_whitespace) {
+            index += 1;
+        }
+
+        match bytes.get(index)? {
+            b'>' => {
+                index += 1;
+                break;
+            }
+            b'/' => {
+                index += 1;
+                if bytes.get(index) == Some(&b'>') {
+                    self_closing = true;
+                    index += 1;
+                    break;
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let attribute_start = index;
+        while bytes
+            .get(index)
+            .is_some_and(|b| !b.is_ascii_whitespace() && !matches!(b, b'=' | b'>' | b'/'))
+        {
+            index += 1;
+        }
+        if index == attribute_start {
+            // Stray `=`; skip it so we always make progress
+            index += 1;
+            continue;
+        }
+        let attribute_name = html[attribute_start..index].to_ascii_lowercase();
+
+        while bytes.get(index).is_some_and(u8::is_ascii_whitespace) {
+            index += 1;
+        }
+
+        let mut value = None;
+        if bytes.get(index) == Some(&b'=') {
+            index += 1;
+            while bytes.get(index).is_some_and(u8::is_ascii_whitespace) {
+                index += 1;
+            }
+
+            let quote = *bytes.get(index)?;
+            if matches!(quote, b'"' | b'\'') {
+                let value_start = index + 1;
+                let end = html[value_start..].find(char::from(quote))?;
+                value = Some(html[value_start..value_start + end].to_string());
+                index = value_start + end + 1;
+            } else {
+                let value_start = index;
+                while bytes
+                    .get(index)
+                    .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'>')
+                {
+                    index += 1;
+                }
+                value = Some(html[value_start..index].to_string());
+            }
+        }
+
+        attributes.push((attribute_name, value));
+    }
+
+    Some((
+        Markup::Tag(Tag {
+            name,
+            attributes,
+            closing,
+            self_closing,
+        }),
+        index,
+    ))
+}
+
+/// Decode the HTML character references that matter for URL scheme checks.
+fn decode_entities(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(position) = rest.find('&') {
+        output.push_str(&rest[..position]);
+        rest = &rest[position + 1..];
+
+        let (reference_length, decoded) = rest.strip_prefix('#').map_or_else(
+            || decode_named_reference(rest),
+            |number| {
+                let (length, decoded) = decode_numeric_reference(number);
+                (length + 1, decoded)
+            },
+        );
+
+        if let Some(decoded) = decoded {
+            output.push(decoded);
+            rest = &rest[reference_length..];
+            rest = rest.strip_prefix(';').unwrap_or(rest);
+        } else {
+            output.push('&');
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Decode a numeric reference (after the `#`), returning its length.
+///
+/// Browsers decode numeric references even without a trailing `;`.
+fn decode_numeric_reference(number: &str) -> (usize, Option<char>) {
+    let (prefix_length, radix) = if number.starts_with(['x', 'X']) {
+        (1, 16)
+    } else {
+        (0, 10)
+    };
+    let digits = &number[prefix_length..];
+    let digits_length = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len());
+    let decoded = u32::from_str_radix(&digits[..digits_length], radix)
+        .ok()
+        .and_then(char::from_u32);
+
+    (prefix_length + digits_length, decoded)
+}
+
+/// Decode a named reference, returning its length.
+fn decode_named_reference(reference: &str) -> (usize, Option<char>) {
+    let name_length = reference
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(reference.len());
+
+    (name_length, decode_named_entity(&reference[..name_length]))
+}
+
+fn decode_named_entity(name: &str) -> Option<char> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "amp" => '&',
+        "apos" => '\'',
+        "colon" => ':',
+        "gt" => '>',
+        "lt" => '<',
+        "newline" => '\n',
+        "quot" => '"',
+        "tab" => '\t',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removes_script_blocks() {
+        let html = "<p>hi</p><script>alert(1)</script><p>bye</p>";
+        assert_eq!(filter_dangerous_html(html), "<p>hi</p><p>bye</p>");
+    }
+
+    #[test]
+    fn test_removes_unclosed_tags() {
+        let html = "<p>hi</p><iframe src=\"https://example.com\"><embed src=x>";
+        assert_eq!(filter_dangerous_html(html), "<p>hi</p>");
+    }
+
+    #[test]
+    fn test_keeps_safe_html() {
+        let html = "<p>Press <kbd>Ctrl</kbd>+<kbd>C</kbd></p>";
+        assert_eq!(filter_dangerous_html(html), html);
+    }
+
+    #[test]
+    fn test_removes_event_handlers() {
+        let html = "<img src=\"x.png\" onerror=\"alert(1)\" alt=\"x\">";
+        assert_eq!(filter_dangerous_html(html), "<img src=\"x.png\" alt=\"x\">");
+    }
+
+    #[test]
+    fn test_removes_javascript_urls() {
+        assert_eq!(
+            filter_dangerous_html("<a href=\"javascript:alert(1)\">x</a>"),
+            "<a>x</a>"
+        );
+        assert_eq!(
+            filter_dangerous_html("<a href=\"JaVa&#x09;Script&colon;alert(1)\">x</a>"),
+            "<a>x</a>"
+        );
+        assert_eq!(
+            filter_dangerous_html("<a href='&#106;avascript:alert(1)'>x</a>"),
+            "<a>x</a>"
+        );
+        assert_eq!(
+            filter_dangerous_html("<a href=\"&#106avascript:alert(1)\">x</a>"),
+            "<a>x</a>"
+        );
+    }
+
+    #[test]
+    fn test_keeps_safe_urls() {
+        let html = "<a href=\"https://example.com/a?b=c\">x</a><a href=\"docs/readme.md\">y</a>";
+        assert_eq!(filter_dangerous_html(html), html);
+    }
+
+    #[test]
+    fn test_unknown_tags_keep_content() {
+        assert_eq!(
+            filter_dangerous_html("<custom-tag>content</custom-tag>"),
+            "content"
+        );
+    }
+
+    #[test]
+    fn test_escapes_stray_angle_brackets() {
+        assert_eq!(filter_dangerous_html("a < b <3"), "a &lt; b &lt;3");
+        assert_eq!(filter_dangerous_html("<a href=\"x"), "&lt;a href=\"x");
+    }
+
+    #[test]
+    fn test_removes_comments() {
+        assert_eq!(filter_dangerous_html("a<!-- <script> -->b"), "ab");
+    }
+
+    #[test]
+    fn test_keeps_highlighted_code_classes() {
+        let html = "<pre class=\"syn-code\" data-lang=\"rust\"><code><span class=\"syn-source syn-rust\">x</span></code></pre>";
+        assert_eq!(filter_dangerous_html(html), html);
+    }
+
+    #[test]
+    fn test_style_only_allows_alignment() {
+        assert_eq!(
+            filter_dangerous_html("<td style=\"text-align: center\">x</td>"),
+            "<td style=\"text-align: center\">x</td>"
+        );
+        assert_eq!(
+            filter_dangerous_html("<td style=\"background: url(x)\">x</td>"),
+            "<td>x</td>"
+        );
+    }
+
+    #[test]
+    fn test_only_allows_task_list_checkboxes() {
+        assert_eq!(
+            filter_dangerous_html("<input disabled=\"\" type=\"checkbox\" checked=\"\"/>"),
+            "<input disabled=\"\" type=\"checkbox\" checked=\"\" />"
+        );
+        assert_eq!(filter_dangerous_html("<input type=\"checkbox\">"), "");
+        assert_eq!(
+            filter_dangerous_html("<input disabled type=\"password\">x"),
+            "x"
+        );
+        assert_eq!(filter_dangerous_html("<input disabled>"), "");
+    }
+
+    #[test]
+    fn test_prefixes_ids_and_fragment_links() {
+        assert_eq!(
+            filter_dangerous_html("<a href=\"#note\">1</a><div id=\"note\">n</div>"),
+            "<a href=\"#user-content-note\">1</a><div id=\"user-content-note\">n</div>"
+        );
+    }
+
+    #[test]
+    fn test_custom_policy() {
+        let policy = SanitizePolicy::default()
+            .without_tag("img")
+            .with_allowed_tag("video")
+            .with_tag_attribute("video", "src")
+            .with_url_scheme("ftp");
+
+        assert_eq!(sanitize_html("<img src=\"x.png\">", &policy), "");
+        assert_eq!(
+            sanitize_html("<video src=\"ftp://host/v.mp4\"></video>", &policy),
+            "<video src=\"ftp://host/v.mp4\"></video>"
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! GitHub suggested changes.
+
+use std::fmt::Write as _;
+
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+
+use crate::code::{escape_html, fence_language};
+use crate::html::CodeFenceRenderer;
+
+/// Renders ```` ```suggestion ```` fences as a diff of the lines that would
+/// replace the commented line(s).
+///
+/// An empty suggestion is shown as a removal of the commented line(s).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SuggestionRenderer;
+
+impl CodeFenceRenderer for SuggestionRenderer {
+    fn render(&self, language: &str, code: &str) -> Option<String> {
+        if !language.eq_ignore_ascii_case("suggestion") {
+            return None;
+        }
+
+        let mut lines = String::new();
+        if code.is_empty() {
+            lines.push_str("<span class=\"suggestion-del\">- (remove line)</span>\n");
+        }
+        for line in code.lines() {
+            let _ = writeln!(
+                lines,
+                "<span class=\"suggestion-add\">+ {}</span>",
+                escape_html(line)
+            );
+        }
+
+        Some(format!(
+            "<div class=\"suggestion\"><div class=\"suggestion-header\">Suggested change</div><pre class=\"suggestion-diff\"><code>{lines}</code></pre></div>\n"
+        ))
+    }
+}
+
+/// Extract the contents of every ```` ```suggestion ```` fence in a comment
+/// body, in order.
+///
+/// Each suggestion is the replacement for the commented line(s), without a
+/// trailing newline; an empty string removes them.
+#[must_use]
+pub fn extract_suggestions(markdown: &str) -> Vec<String> {
+    let mut suggestions = Vec::new();
+    let mut current: Option<String> = None;
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info)))
+                if fence_language(&info).is_some_and(|l| l.eq_ignore_ascii_case("suggestion")) =>
+            {
+                current = Some(String::new());
+            }
+            Event::Text(text) => {
+                if let Some(code) = &mut current {
+                    code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some(mut code) = current.take() {
+                    if code.ends_with('\n') {
+                        code.pop();
+                    }
+                    suggestions.push(code);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_suggestions_only() {
+        let renderer = SuggestionRenderer;
+        let html = renderer
+            .render("suggestion", "let x = 1;\nlet y = <2>;\n")
+            .unwrap();
+        assert!(html.contains("Suggested change"));
+        assert!(html.contains("<span class=\"suggestion-add\">+ let x = 1;</span>"));
+        assert!(html.contains("<span class=\"suggestion-add\">+ let y = &lt;2&gt;;</span>"));
+        assert!(renderer.render("rust", "fn x() {}").is_none());
+    }
+
+    #[test]
+    fn test_renders_empty_suggestion_as_removal() {
+        let html = SuggestionRenderer.render("suggestion", "").unwrap();
+        assert!(html.contains("suggestion-del"));
+        assert!(!html.contains("suggestion-add"));
+    }
+
+    #[test]
+    fn test_extract_suggestions() {
+        let markdown = "Try this:\n\n```suggestion\nlet x = 1;\nlet y = 2;\n```\n\n```rust\nignored\n```\n\n```suggestion\n```";
+        assert_eq!(
+            extract_suggestions(markdown),
+            vec!["let x = 1;\nlet y = 2;".to_string(), String::new()]
+        );
+        assert!(extract_suggestions("No code here").is_empty());
+    }
+}
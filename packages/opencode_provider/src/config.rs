@@ -1,7 +1,7 @@
 //! `OpenCode` configuration parsing.
 
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use serde::Deserialize;
 
@@ -23,6 +23,10 @@ pub enum OpenCodeConfigError {
     Parse(#[from] serde_json::Error),
 }
 
+/// Directories of a repository holding agent definitions, one markdown
+/// file per agent.
+const REPO_AGENT_DIRS: &[&str] = &[".opencode/agent", ".opencode/agents"];
+
 /// Parsed opencode.json structure.
 #[derive(Debug, Default, Deserialize)]
 pub struct OpenCodeConfig {
@@ -36,8 +40,10 @@ pub struct OpenCodeConfig {
 }
 
 /// Configuration for a single agent.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct AgentConfig {
+    /// What the agent is for.
+    pub description: Option<String>,
     /// Agent mode (e.g., "primary").
     pub mode: Option<String>,
     /// Default model for this agent.
@@ -113,29 +119,132 @@ impl OpenCodeConfig {
         self.agent
             .iter()
             .map(|(name, config)| {
-                let description = config.mode.as_ref().map_or_else(
-                    || format!("{name} agent"),
-                    |mode| format!("{name} ({mode})"),
-                );
-
-                AiActionDefinition {
-                    id: format!("opencode:{name}"),
-                    name: name.clone(),
-                    description,
-                    provider: "opencode".to_string(),
-                    default_model: config.model.clone(),
-                    capabilities: AgentCapabilities {
-                        can_read: true, // All agents can read
-                        can_write: config.tools.write || config.tools.edit,
-                        can_execute: config.tools.bash,
-                    },
-                    source: AgentSource::GlobalConfig {
+                config.to_action_definition(
+                    name,
+                    AgentSource::GlobalConfig {
                         path: self.config_path.clone().unwrap_or_default(),
                     },
-                }
+                )
             })
             .collect()
     }
+
+    /// Load the agents defined in `repo_path`'s `.opencode/agent/` (or
+    /// `.opencode/agents/`) directory, each named after its file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an agent directory or file can't be read.
+    pub fn load_repo_agents(
+        repo_path: &Path,
+    ) -> Result<Vec<AiActionDefinition>, OpenCodeConfigError> {
+        let mut agents = Vec::new();
+
+        for dir in REPO_AGENT_DIRS.iter().map(|dir| repo_path.join(dir)) {
+            if !dir.is_dir() {
+                continue;
+            }
+
+            for entry in std::fs::read_dir(&dir)? {
+                let path = entry?.path();
+                let Some(name) = path
+                    .extension()
+                    .is_some_and(|extension| extension == "md")
+                    .then(|| path.file_stem())
+                    .flatten()
+                else {
+                    continue;
+                };
+
+                let config = AgentConfig::from_markdown(&std::fs::read_to_string(&path)?);
+                agents.push(config.to_action_definition(
+                    &name.to_string_lossy(),
+                    AgentSource::RepoConfig { path: path.clone() },
+                ));
+            }
+        }
+
+        Ok(agents)
+    }
+}
+
+impl AgentConfig {
+    /// Parse an agent markdown file: YAML frontmatter with the agent's
+    /// settings, followed by its system prompt.
+    ///
+    /// Only the flat keys `OpenCode` agents use are read (`description`,
+    /// `mode`, `model` and the booleans under `tools`).
+    #[must_use]
+    pub fn from_markdown(content: &str) -> Self {
+        let mut config = Self::default();
+        let Some(rest) = content.trim_start().strip_prefix("---") else {
+            config.system_prompt = non_empty(content);
+            return config;
+        };
+        let (frontmatter, body) = rest.split_once("\n---").unwrap_or((rest, ""));
+        config.system_prompt = non_empty(body.split_once('\n').map_or("", |(_, body)| body));
+
+        let mut in_tools = false;
+        for line in frontmatter.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim().trim_matches(['"', '\'']);
+
+            if line.starts_with([' ', '\t']) {
+                if in_tools {
+                    let enabled = value == "true";
+                    match key.trim() {
+                        "write" => config.tools.write = enabled,
+                        "edit" => config.tools.edit = enabled,
+                        "bash" => config.tools.bash = enabled,
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+
+            in_tools = false;
+            match key {
+                "description" => config.description = non_empty(value),
+                "mode" => config.mode = non_empty(value),
+                "model" => config.model = non_empty(value),
+                "tools" => in_tools = true,
+                _ => {}
+            }
+        }
+
+        config
+    }
+
+    /// The action definition of this agent, named `name`.
+    fn to_action_definition(&self, name: &str, source: AgentSource) -> AiActionDefinition {
+        let description = self.description.clone().unwrap_or_else(|| {
+            self.mode.as_ref().map_or_else(
+                || format!("{name} agent"),
+                |mode| format!("{name} ({mode})"),
+            )
+        });
+
+        AiActionDefinition {
+            id: format!("opencode:{name}"),
+            name: name.to_string(),
+            description,
+            provider: "opencode".to_string(),
+            default_model: self.model.clone(),
+            capabilities: AgentCapabilities {
+                can_read: true, // All agents can read
+                can_write: self.tools.write || self.tools.edit,
+                can_execute: self.tools.bash,
+            },
+            source,
+        }
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
 }
 
 #[cfg(test)]
@@ -198,4 +307,57 @@ mod tests {
         assert_eq!(actions[0].name, "plan");
         assert_eq!(actions[0].provider, "opencode");
     }
+
+    #[test]
+    fn test_parse_agent_markdown() {
+        let config = AgentConfig::from_markdown(
+            "---\ndescription: Reviews changes for security issues\nmode: subagent\nmodel: \"anthropic/claude-sonnet-4-20250514\"\ntools:\n  write: false\n  bash: true\n---\nYou are a security reviewer.\n",
+        );
+
+        assert_eq!(
+            config.description.as_deref(),
+            Some("Reviews changes for security issues")
+        );
+        assert_eq!(config.mode.as_deref(), Some("subagent"));
+        assert_eq!(
+            config.model.as_deref(),
+            Some("anthropic/claude-sonnet-4-20250514")
+        );
+        assert!(!config.tools.write);
+        assert!(config.tools.bash);
+        assert_eq!(
+            config.system_prompt.as_deref(),
+            Some("You are a security reviewer.")
+        );
+    }
+
+    #[test]
+    fn test_parse_agent_markdown_without_frontmatter() {
+        let config = AgentConfig::from_markdown("Just a prompt.");
+
+        assert_eq!(config.description, None);
+        assert_eq!(config.system_prompt.as_deref(), Some("Just a prompt."));
+    }
+
+    #[test]
+    fn test_load_repo_agents() {
+        let repo =
+            std::env::temp_dir().join(format!("chadreview-opencode-test-{}", std::process::id()));
+        let agents_dir = repo.join(".opencode/agents");
+        std::fs::create_dir_all(&agents_dir).unwrap();
+        std::fs::write(
+            agents_dir.join("security.md"),
+            "---\ndescription: Security review\n---\nReview for security issues.",
+        )
+        .unwrap();
+        std::fs::write(agents_dir.join("notes.txt"), "not an agent").unwrap();
+
+        let agents = OpenCodeConfig::load_repo_agents(&repo).unwrap();
+        let _ = std::fs::remove_dir_all(&repo);
+
+        assert_eq!(agents.len(), 1);
+        assert_eq!(agents[0].id, "opencode:security");
+        assert_eq!(agents[0].description, "Security review");
+        assert!(matches!(agents[0].source, AgentSource::RepoConfig { .. }));
+    }
 }
@@ -10,9 +10,11 @@ use switchy::unsync::process::{Command, Stdio};
 use switchy::unsync::task;
 use switchy::unsync::time::{Duration, timeout};
 
-use chadreview_ai_provider::{AiProviderError, mpsc};
+use chadreview_ai_provider::{
+    AbortSignal, AiProviderError, format_thread_history, mpsc, render_prompt_template,
+};
 use chadreview_ai_provider_models::{
-    AiContext, AiResponse,
+    AiContext, AiProgress, AiResponse,
     models::{ExecutionDetails, TokenUsage, ToolExecution},
 };
 use chadreview_local_comment_models::{AiAction, ProgressEntry};
@@ -34,7 +36,8 @@ impl<'a> OpenCodeExecutor<'a> {
     ///
     /// When `is_continuation` is true (replying to an existing thread with a session),
     /// only the user's comment body is returned since `OpenCode` already has all the
-    /// context from the previous conversation.
+    /// context from the previous conversation. Otherwise the repository's prompt
+    /// template, if the context has one, replaces the built prompt.
     #[must_use]
     pub fn build_prompt(context: &AiContext, is_continuation: bool) -> String {
         // For continuations, just send the user's message - OpenCode has the context
@@ -42,6 +45,10 @@ impl<'a> OpenCodeExecutor<'a> {
             return context.comment_body.clone();
         }
 
+        if let Some(template) = &context.prompt_template {
+            return render_prompt_template(template, context);
+        }
+
         // For new conversations, include full context
         let mut prompt = String::new();
 
@@ -71,24 +78,15 @@ impl<'a> OpenCodeExecutor<'a> {
             }
         }
 
+        // The entire diff (if the action is about all of it)
+        if let Some(diff) = &context.diff {
+            write!(prompt, "\nFULL DIFF:\n```\n{diff}\n```\n").unwrap();
+        }
+
         // Thread history (if this is part of a conversation)
         if !context.thread_history.is_empty() {
             prompt.push_str("\nTHREAD HISTORY (previous discussion):\n");
-            for msg in &context.thread_history {
-                let author = if msg.is_ai_response {
-                    "AI"
-                } else {
-                    &msg.author
-                };
-                write!(
-                    prompt,
-                    "[@{} at {}]:\n{}\n\n",
-                    author,
-                    msg.timestamp.format("%Y-%m-%d %H:%M"),
-                    msg.body
-                )
-                .unwrap();
-            }
+            prompt.push_str(&format_thread_history(&context.thread_history));
         }
 
         // User's request
@@ -114,18 +112,20 @@ impl<'a> OpenCodeExecutor<'a> {
     /// * `context` - The context for execution (repo, file, comment, etc.)
     /// * `action` - The action to execute (agent, model, etc.)
     /// * `session_id` - Optional session ID to continue a previous conversation
-    /// * `progress_tx` - Channel to send progress updates
+    /// * `progress_tx` - Channel to send tool calls and the response text so far
+    /// * `abort` - Signal killing the `OpenCode` process when aborted
     ///
     /// # Errors
     ///
-    /// Returns an error if execution fails.
+    /// Returns an error if execution fails or is cancelled.
     #[allow(clippy::too_many_lines)]
     pub async fn execute(
         &self,
         context: &AiContext,
         action: &AiAction,
         session_id: Option<&str>,
-        progress_tx: mpsc::Sender<ProgressEntry>,
+        progress_tx: mpsc::Sender<AiProgress>,
+        abort: AbortSignal,
     ) -> Result<AiResponse, AiProviderError> {
         let is_continuation = session_id.is_some();
         let prompt = Self::build_prompt(context, is_continuation);
@@ -194,6 +194,7 @@ impl<'a> OpenCodeExecutor<'a> {
 
         // State for collecting response
         let mut response_text = String::new();
+        let mut streamed_text = String::new();
         let mut tools_used = Vec::new();
         let mut tokens = TokenUsage::default();
         let mut cost = None;
@@ -249,7 +250,7 @@ impl<'a> OpenCodeExecutor<'a> {
                                 title: title.clone(),
                                 timestamp: Utc::now(),
                             };
-                            let _ = progress_tx.send(entry);
+                            let _ = progress_tx.send(AiProgress::Step(entry));
 
                             // Record tool execution
                             tools_used.push(ToolExecution {
@@ -264,9 +265,17 @@ impl<'a> OpenCodeExecutor<'a> {
                     }
                     Some("text") => {
                         if let Some(part) = event.get("part") {
+                            let text = part["text"].as_str().unwrap_or("");
+
+                            // Stream the text so far, final or not
+                            if !text.is_empty() && text != streamed_text {
+                                streamed_text = text.to_string();
+                                let _ = progress_tx.send(AiProgress::Text(streamed_text.clone()));
+                            }
+
                             // Only capture final text (has time.end)
                             if part["time"]["end"].is_number() {
-                                response_text = part["text"].as_str().unwrap_or("").to_string();
+                                response_text = text.to_string();
                                 log::debug!(
                                     "OpenCode text response: {} chars",
                                     response_text.len()
@@ -310,6 +319,9 @@ impl<'a> OpenCodeExecutor<'a> {
             Ok(())
         };
 
+        // Stop reading as soon as the execution is aborted
+        let process_lines = abort.abortable(process_lines);
+
         // Apply timeout only if configured
         let inner_result = if let Some(secs) = timeout_secs {
             match timeout(Duration::from_secs(secs), process_lines).await {
@@ -327,6 +339,14 @@ impl<'a> OpenCodeExecutor<'a> {
             process_lines.await
         };
 
+        let Some(inner_result) = inner_result else {
+            log::info!("OpenCode execution cancelled");
+            if let Err(e) = child.kill().await {
+                log::warn!("Failed to kill OpenCode process: {e}");
+            }
+            return Err(AiProviderError::Cancelled);
+        };
+
         // Check for errors from the read loop
         inner_result?;
 
@@ -424,6 +444,40 @@ mod tests {
         assert!(prompt.contains("fn main()"));
     }
 
+    #[test]
+    fn test_build_prompt_with_full_diff() {
+        let context = AiContext::new(
+            PathBuf::from("/path/to/repo"),
+            "main..feature".to_string(),
+            "Review this diff".to_string(),
+        )
+        .with_diff("=== src/lib.rs (Modified)\n   11 |+let x = 1;".to_string());
+
+        let prompt = OpenCodeExecutor::build_prompt(&context, false);
+
+        assert!(prompt.contains("FULL DIFF:"));
+        assert!(prompt.contains("   11 |+let x = 1;"));
+    }
+
+    #[test]
+    fn test_build_prompt_with_template() {
+        let context = AiContext::new(
+            PathBuf::from("/path/to/repo"),
+            "main..feature".to_string(),
+            "Review this".to_string(),
+        )
+        .with_prompt_template("Check CONTRIBUTING.md first.\n\n{{comment_body}}".to_string());
+
+        assert_eq!(
+            OpenCodeExecutor::build_prompt(&context, false),
+            "Check CONTRIBUTING.md first.\n\nReview this"
+        );
+        assert_eq!(
+            OpenCodeExecutor::build_prompt(&context, true),
+            "Review this"
+        );
+    }
+
     #[test]
     fn test_truncate_output() {
         let short = "hello";
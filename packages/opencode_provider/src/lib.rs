@@ -15,11 +15,11 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use chadreview_ai_provider::{
-    AiProvider, AiProviderError,
-    models::{AiActionDefinition, AiContext, AiResponse},
+    AbortSignal, AiProvider, AiProviderError,
+    models::{AiActionDefinition, AiContext, AiProgress, AiResponse},
     mpsc,
 };
-use chadreview_local_comment_models::{AiAction, ProgressEntry};
+use chadreview_local_comment_models::AiAction;
 
 pub use config::{OpenCodeConfig, OpenCodeConfigError};
 pub use executor::OpenCodeExecutor;
@@ -83,12 +83,30 @@ impl AiProvider for OpenCodeProvider {
         "opencode"
     }
 
+    /// Global agents from `opencode.json`, overridden by the repository's
+    /// `.opencode/agent/` files of the same name.
     async fn list_agents(
         &self,
-        _repo_path: &Path,
+        repo_path: &Path,
     ) -> Result<Vec<AiActionDefinition>, AiProviderError> {
-        let config = self.get_config()?;
-        Ok(config.to_action_definitions())
+        let mut agents = match self.get_config() {
+            Ok(config) => config.to_action_definitions(),
+            Err(e) => {
+                log::debug!("No global OpenCode agents: {e}");
+                vec![]
+            }
+        };
+
+        let repo_agents = OpenCodeConfig::load_repo_agents(repo_path).map_err(|e| {
+            AiProviderError::ConfigError(format!("Failed to load repository agents: {e}"))
+        })?;
+        for agent in repo_agents {
+            agents.retain(|existing| existing.name != agent.name);
+            agents.push(agent);
+        }
+
+        agents.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(agents)
     }
 
     async fn execute(
@@ -96,11 +114,12 @@ impl AiProvider for OpenCodeProvider {
         context: &AiContext,
         action: &AiAction,
         session_id: Option<&str>,
-        progress_tx: mpsc::Sender<ProgressEntry>,
+        progress_tx: mpsc::Sender<AiProgress>,
+        abort: AbortSignal,
     ) -> Result<AiResponse, AiProviderError> {
         let executor = OpenCodeExecutor::new(&self.binary_path);
         executor
-            .execute(context, action, session_id, progress_tx)
+            .execute(context, action, session_id, progress_tx, abort)
             .await
     }
 }
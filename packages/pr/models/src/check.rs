@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pending,
+    Success,
+    Failure,
+    Neutral,
+    Skipped,
+}
+
+impl CheckStatus {
+    /// Human-readable label for status badges.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Pending => "Pending",
+            Self::Success => "Passed",
+            Self::Failure => "Failed",
+            Self::Neutral => "Neutral",
+            Self::Skipped => "Skipped",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub description: Option<String>,
+    pub details_url: Option<String>,
+}
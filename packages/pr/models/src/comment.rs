@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use chadreview_diff_models::LineNumber;
 
+use crate::reaction::Reaction;
 use crate::user::User;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +16,10 @@ pub struct Comment {
     pub comment_type: CommentType,
     pub replies: Vec<Self>,
     pub resolved: bool,
+    #[serde(default)]
+    pub thread_id: Option<String>,
+    #[serde(default)]
+    pub reactions: Vec<Reaction>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
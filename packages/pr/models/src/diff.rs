@@ -7,7 +7,74 @@ pub struct DiffFile {
     pub status: FileStatus,
     pub additions: u64,
     pub deletions: u64,
+    /// The change of the file's mode, such as it becoming executable, if
+    /// known.
+    #[serde(default)]
+    pub mode_change: Option<ModeChange>,
     pub hunks: Vec<DiffHunk>,
+    /// Whether some of the file's lines weren't valid UTF-8 and were
+    /// re-encoded for display, so they don't match the file's bytes.
+    #[serde(default)]
+    pub decoded_lossy: bool,
+    /// Path of the submodule the file is in, when a working tree diff
+    /// shows the changes inside submodules. Staging and discarding it act
+    /// on the submodule's repository.
+    #[serde(default)]
+    pub submodule: Option<String>,
+}
+
+impl DiffFile {
+    /// `hunk` as a unified diff patch of this file, with `---`/`+++`
+    /// headers, that `git apply` accepts.
+    #[must_use]
+    pub fn hunk_patch(&self, hunk: &DiffHunk) -> String {
+        use std::fmt::Write as _;
+
+        let old_path = if self.status == FileStatus::Added {
+            "/dev/null".to_string()
+        } else {
+            format!("a/{}", self.filename)
+        };
+        let new_path = if self.status == FileStatus::Deleted {
+            "/dev/null".to_string()
+        } else {
+            format!("b/{}", self.filename)
+        };
+
+        let mut patch = format!(
+            "--- {old_path}\n+++ {new_path}\n@@ -{},{} +{},{} @@\n",
+            hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+        );
+        for line in &hunk.lines {
+            let marker = match line.line_type {
+                LineType::Addition => '+',
+                LineType::Deletion => '-',
+                LineType::Context => ' ',
+            };
+            writeln!(
+                patch,
+                "{marker}{}",
+                line.content.trim_end_matches(['\r', '\n'])
+            )
+            .unwrap();
+        }
+
+        patch
+    }
+}
+
+/// A change of a file's mode, as git modes such as `0o100644`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModeChange {
+    pub old_mode: u32,
+    pub new_mode: u32,
+}
+
+/// Formats the modes the way git shows them, e.g. `100644 → 100755`.
+impl std::fmt::Display for ModeChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:o} → {:o}", self.old_mode, self.new_mode)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -27,6 +94,44 @@ pub struct DiffHunk {
     pub lines: Vec<DiffLine>,
 }
 
+/// A run of a hunk's lines, as split by [`DiffHunk::segments`].
+#[derive(Debug, Clone, Copy)]
+pub enum HunkSegment<'a> {
+    /// Changed lines and the context around them.
+    Lines(&'a [DiffLine]),
+    /// Context lines long enough to be folded away.
+    Unchanged(&'a [DiffLine]),
+}
+
+impl DiffHunk {
+    /// Split the hunk's lines into runs, separating runs of more than
+    /// `max_context` context lines from the lines around them.
+    #[must_use]
+    pub fn segments(&self, max_context: usize) -> Vec<HunkSegment<'_>> {
+        let mut segments = vec![];
+        let mut lines_start = 0;
+        let mut start = 0;
+
+        let is_context = |line: &DiffLine| line.line_type == LineType::Context;
+        for run in self.lines.chunk_by(|a, b| is_context(a) == is_context(b)) {
+            let end = start + run.len();
+            if is_context(&run[0]) && run.len() > max_context {
+                if lines_start < start {
+                    segments.push(HunkSegment::Lines(&self.lines[lines_start..start]));
+                }
+                segments.push(HunkSegment::Unchanged(run));
+                lines_start = end;
+            }
+            start = end;
+        }
+        if lines_start < self.lines.len() {
+            segments.push(HunkSegment::Lines(&self.lines[lines_start..]));
+        }
+
+        segments
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiffLine {
     pub line_type: LineType,
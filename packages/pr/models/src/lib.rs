@@ -2,12 +2,20 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 #![allow(clippy::multiple_crate_versions)]
 
+pub mod check;
 pub mod comment;
 pub mod diff;
 pub mod pr;
+pub mod rate_limit;
+pub mod reaction;
+pub mod review;
 pub mod user;
 
+pub use check::{Check, CheckStatus};
 pub use comment::{Comment, CommentType, CreateComment};
-pub use diff::{DiffFile, DiffHunk, DiffLine, FileStatus, LineType};
-pub use pr::{PrState, PullRequest};
+pub use diff::{DiffFile, DiffHunk, DiffLine, FileStatus, HunkSegment, LineType, ModeChange};
+pub use pr::{MergeStrategy, MergeableState, PrState, PullRequest};
+pub use rate_limit::RateLimitStatus;
+pub use reaction::{Reaction, ReactionContent};
+pub use review::{PendingComment, PendingReview, Review, ReviewVerdict};
 pub use user::{Commit, Label, User};
@@ -22,6 +22,8 @@ pub struct PullRequest {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub provider: String,
+    #[serde(default)]
+    pub mergeable_state: MergeableState,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -30,3 +32,41 @@ pub enum PrState {
     Closed,
     Merged,
 }
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeableState {
+    /// Can be merged.
+    Clean,
+    /// Can be merged, but some checks that aren't required are failing.
+    Unstable,
+    /// Can be merged, but the head branch is behind the base branch.
+    Behind,
+    /// Required checks or reviews have not passed.
+    Blocked,
+    /// Has merge conflicts with the base branch.
+    Conflicting,
+    /// The provider hasn't computed mergeability yet.
+    #[default]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    Merge,
+    Squash,
+    Rebase,
+}
+
+impl MergeStrategy {
+    /// Human-readable label for buttons and status messages.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Merge => "Create a merge commit",
+            Self::Squash => "Squash and merge",
+            Self::Rebase => "Rebase and merge",
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// API quota reported by a provider's rate limit headers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitStatus {
+    /// Quota bucket the limit applies to, e.g. `core` or `graphql`.
+    pub resource: String,
+    pub limit: u64,
+    pub remaining: u64,
+    pub used: u64,
+    pub reset_at: DateTime<Utc>,
+}
+
+impl RateLimitStatus {
+    /// Whether less than a tenth of the quota is left.
+    #[must_use]
+    pub const fn is_low(&self) -> bool {
+        self.remaining.saturating_mul(10) < self.limit
+    }
+}
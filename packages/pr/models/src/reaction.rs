@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+/// The emoji reactions supported by GitHub, named after its REST API values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReactionContent {
+    #[serde(rename = "+1")]
+    ThumbsUp,
+    #[serde(rename = "-1")]
+    ThumbsDown,
+    Laugh,
+    Hooray,
+    Confused,
+    Heart,
+    Rocket,
+    Eyes,
+}
+
+impl ReactionContent {
+    /// Every reaction, in the order GitHub displays them.
+    pub const ALL: [Self; 8] = [
+        Self::ThumbsUp,
+        Self::ThumbsDown,
+        Self::Laugh,
+        Self::Hooray,
+        Self::Confused,
+        Self::Heart,
+        Self::Rocket,
+        Self::Eyes,
+    ];
+
+    /// The REST API value, e.g. `+1` or `heart`.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::ThumbsUp => "+1",
+            Self::ThumbsDown => "-1",
+            Self::Laugh => "laugh",
+            Self::Hooray => "hooray",
+            Self::Confused => "confused",
+            Self::Heart => "heart",
+            Self::Rocket => "rocket",
+            Self::Eyes => "eyes",
+        }
+    }
+
+    /// Emoji for reaction pills.
+    #[must_use]
+    pub const fn emoji(self) -> &'static str {
+        match self {
+            Self::ThumbsUp => "👍",
+            Self::ThumbsDown => "👎",
+            Self::Laugh => "😄",
+            Self::Hooray => "🎉",
+            Self::Confused => "😕",
+            Self::Heart => "❤️",
+            Self::Rocket => "🚀",
+            Self::Eyes => "👀",
+        }
+    }
+}
+
+/// How many users reacted to a comment with one emoji.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reaction {
+    pub content: ReactionContent,
+    pub count: u64,
+    /// Whether the authenticated user is one of them. Always false where the
+    /// provider's comment payloads only carry counts.
+    pub viewer_reacted: bool,
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::comment::CreateComment;
+use crate::user::User;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewVerdict {
+    Approve,
+    RequestChanges,
+    Comment,
+}
+
+impl ReviewVerdict {
+    /// Human-readable label for buttons and status messages.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Approve => "Approve",
+            Self::RequestChanges => "Request changes",
+            Self::Comment => "Comment",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Review {
+    pub id: u64,
+    pub author: User,
+    pub verdict: ReviewVerdict,
+    pub body: String,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingComment {
+    pub id: u64,
+    pub created_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub comment: CreateComment,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingReview {
+    pub started_at: DateTime<Utc>,
+    pub comments: Vec<PendingComment>,
+}
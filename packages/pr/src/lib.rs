@@ -1,3 +1,9 @@
 #![cfg_attr(feature = "fail-on-warnings", deny(warnings))]
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 #![allow(clippy::multiple_crate_versions)]
+
+pub mod pending;
+pub mod review_state;
+
+pub use pending::{PendingReviewKey, PendingReviewStore, PendingReviewStoreError};
+pub use review_state::{PrReviewStateStore, PrReviewStateStoreError};
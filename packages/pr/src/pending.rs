@@ -0,0 +1,370 @@
+//! File-based storage for pending (unsubmitted) pull request reviews.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chadreview_pr_models::{CreateComment, PendingComment, PendingReview};
+
+/// Errors that can occur when using the pending review store.
+#[derive(Debug, thiserror::Error)]
+pub enum PendingReviewStoreError {
+    /// Failed to create storage directory.
+    #[error("Failed to create storage directory: {0}")]
+    CreateDir(std::io::Error),
+
+    /// Failed to read from storage.
+    #[error("Failed to read from storage: {0}")]
+    Read(std::io::Error),
+
+    /// Failed to write to storage.
+    #[error("Failed to write to storage: {0}")]
+    Write(std::io::Error),
+
+    /// Failed to parse stored data.
+    #[error("Failed to parse stored data: {0}")]
+    Parse(serde_json::Error),
+
+    /// Failed to serialize data.
+    #[error("Failed to serialize data: {0}")]
+    Serialize(serde_json::Error),
+
+    /// No review has been started for the pull request.
+    #[error("No pending review for {0}")]
+    NotStarted(PendingReviewKey),
+
+    /// Pending comment not found.
+    #[error("Pending comment not found: {0}")]
+    NotFound(u64),
+
+    /// The key contains a path component that isn't a plain name.
+    #[error("Invalid pull request reference: {0}")]
+    InvalidKey(PendingReviewKey),
+
+    /// Could not determine data directory.
+    #[error("Could not determine XDG data directory")]
+    NoDataDir,
+}
+
+/// Identifies the pull request a pending review belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingReviewKey {
+    /// Provider name (e.g. `github`).
+    pub provider: String,
+    /// Repository owner.
+    pub owner: String,
+    /// Repository name.
+    pub repo: String,
+    /// Pull request number.
+    pub number: u64,
+}
+
+impl PendingReviewKey {
+    /// Create a key for a pull request.
+    #[must_use]
+    pub fn new(
+        provider: impl Into<String>,
+        owner: impl Into<String>,
+        repo: impl Into<String>,
+        number: u64,
+    ) -> Self {
+        Self {
+            provider: provider.into(),
+            owner: owner.into(),
+            repo: repo.into(),
+            number,
+        }
+    }
+
+    /// Whether every component is safe to use as a directory name.
+    pub(crate) fn is_valid(&self) -> bool {
+        [&self.provider, &self.owner, &self.repo]
+            .iter()
+            .all(|part| {
+                !part.is_empty() && *part != "." && *part != ".." && !part.contains(['/', '\\'])
+            })
+    }
+}
+
+impl std::fmt::Display for PendingReviewKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}/{}#{}",
+            self.provider, self.owner, self.repo, self.number
+        )
+    }
+}
+
+/// File-based storage for pending reviews.
+///
+/// A pull request has a pending review from [`Self::start`] until it is
+/// submitted or [`Self::discard`]ed. While one exists, new review comments
+/// are added to it rather than being published.
+///
+/// Storage layout:
+/// ```text
+/// $XDG_DATA_HOME/chadreview/pending/{provider}/{owner}/{repo}/
+/// └── {number}.json       # Pending review for a pull request
+/// ```
+pub struct PendingReviewStore {
+    /// Base path for all pending reviews.
+    root: PathBuf,
+    /// Serializes read-modify-write updates.
+    lock: Mutex<()>,
+}
+
+impl PendingReviewStore {
+    /// Create a store in the XDG data directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the XDG data directory cannot be determined.
+    pub fn new() -> Result<Self, PendingReviewStoreError> {
+        let data_dir = dirs::data_dir().ok_or(PendingReviewStoreError::NoDataDir)?;
+
+        Ok(Self {
+            root: data_dir.join("chadreview").join("pending"),
+            lock: Mutex::new(()),
+        })
+    }
+
+    /// Get the path to a pull request's pending review file.
+    fn review_path(&self, key: &PendingReviewKey) -> Result<PathBuf, PendingReviewStoreError> {
+        if !key.is_valid() {
+            return Err(PendingReviewStoreError::InvalidKey(key.clone()));
+        }
+
+        Ok(self
+            .root
+            .join(&key.provider)
+            .join(&key.owner)
+            .join(&key.repo)
+            .join(format!("{}.json", key.number)))
+    }
+
+    /// Get the pending review for a pull request, if one has been started.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the review can't be read.
+    pub fn get(
+        &self,
+        key: &PendingReviewKey,
+    ) -> Result<Option<PendingReview>, PendingReviewStoreError> {
+        let path = self.review_path(key)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let file = File::open(&path).map_err(PendingReviewStoreError::Read)?;
+        let reader = BufReader::new(file);
+        serde_json::from_reader(reader)
+            .map(Some)
+            .map_err(PendingReviewStoreError::Parse)
+    }
+
+    /// Start a pending review, or return the one already in progress.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the review can't be read or written.
+    pub fn start(&self, key: &PendingReviewKey) -> Result<PendingReview, PendingReviewStoreError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        if let Some(review) = self.get(key)? {
+            return Ok(review);
+        }
+
+        let review = PendingReview {
+            started_at: chrono::Utc::now(),
+            comments: Vec::new(),
+        };
+        self.save(key, &review)?;
+
+        Ok(review)
+    }
+
+    /// Add a comment to a pull request's pending review.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no review has been started or it can't be
+    /// read or written.
+    pub fn add_comment(
+        &self,
+        key: &PendingReviewKey,
+        comment: CreateComment,
+    ) -> Result<PendingComment, PendingReviewStoreError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let mut review = self
+            .get(key)?
+            .ok_or_else(|| PendingReviewStoreError::NotStarted(key.clone()))?;
+
+        let pending = PendingComment {
+            id: review.comments.iter().map(|c| c.id).max().unwrap_or(0) + 1,
+            created_at: chrono::Utc::now(),
+            comment,
+        };
+        review.comments.push(pending.clone());
+        self.save(key, &review)?;
+
+        Ok(pending)
+    }
+
+    /// Remove a comment from a pull request's pending review.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the review or comment doesn't exist or the
+    /// review can't be read or written.
+    pub fn remove_comment(
+        &self,
+        key: &PendingReviewKey,
+        id: u64,
+    ) -> Result<(), PendingReviewStoreError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let mut review = self
+            .get(key)?
+            .ok_or_else(|| PendingReviewStoreError::NotStarted(key.clone()))?;
+
+        let count = review.comments.len();
+        review.comments.retain(|c| c.id != id);
+        if review.comments.len() == count {
+            return Err(PendingReviewStoreError::NotFound(id));
+        }
+
+        self.save(key, &review)
+    }
+
+    /// Delete a pull request's pending review and all of its comments.
+    ///
+    /// Call this after the review has been submitted, or to abandon it.
+    /// Does nothing if no review has been started.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the review file can't be removed.
+    pub fn discard(&self, key: &PendingReviewKey) -> Result<(), PendingReviewStoreError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let path = self.review_path(key)?;
+        if path.exists() {
+            fs::remove_file(&path).map_err(PendingReviewStoreError::Write)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a pending review to disk.
+    fn save(
+        &self,
+        key: &PendingReviewKey,
+        review: &PendingReview,
+    ) -> Result<(), PendingReviewStoreError> {
+        let path = self.review_path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(PendingReviewStoreError::CreateDir)?;
+        }
+
+        let file = File::create(&path).map_err(PendingReviewStoreError::Write)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, review).map_err(PendingReviewStoreError::Serialize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chadreview_pr_models::CommentType;
+    use std::env;
+
+    fn temp_store(name: &str) -> PendingReviewStore {
+        let root = env::temp_dir().join(format!(
+            "chadreview-pending-test-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        PendingReviewStore {
+            root,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn key() -> PendingReviewKey {
+        PendingReviewKey::new("github", "owner", "repo", 1)
+    }
+
+    fn file_comment(body: &str) -> CreateComment {
+        CreateComment {
+            body: body.to_string(),
+            comment_type: CommentType::FileLevelComment {
+                path: "src/lib.rs".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_start_add_and_remove() {
+        let store = temp_store("start-add-remove");
+        assert!(store.get(&key()).unwrap().is_none());
+
+        store.start(&key()).unwrap();
+        let first = store.add_comment(&key(), file_comment("First")).unwrap();
+        let second = store.add_comment(&key(), file_comment("Second")).unwrap();
+        assert_eq!((first.id, second.id), (1, 2));
+
+        // Starting again keeps the review in progress
+        let review = store.start(&key()).unwrap();
+        assert_eq!(review.comments.len(), 2);
+
+        store.remove_comment(&key(), first.id).unwrap();
+        let review = store.get(&key()).unwrap().unwrap();
+        assert_eq!(review.comments.len(), 1);
+        assert_eq!(review.comments[0].comment.body, "Second");
+
+        assert!(matches!(
+            store.remove_comment(&key(), first.id),
+            Err(PendingReviewStoreError::NotFound(1))
+        ));
+
+        store.discard(&key()).unwrap();
+        assert!(store.get(&key()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_add_comment_requires_started_review() {
+        let store = temp_store("not-started");
+
+        assert!(matches!(
+            store.add_comment(&key(), file_comment("Body")),
+            Err(PendingReviewStoreError::NotStarted(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_path_traversal() {
+        let store = temp_store("traversal");
+        let key = PendingReviewKey::new("github", "..", "repo", 1);
+
+        assert!(matches!(
+            store.start(&key),
+            Err(PendingReviewStoreError::InvalidKey(_))
+        ));
+    }
+}
@@ -0,0 +1,306 @@
+//! File-based storage for which files of a pull request have been viewed
+//! and which of its review checklist items have been checked.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::PendingReviewKey;
+
+/// Errors that can occur when using the review state store.
+#[derive(Debug, thiserror::Error)]
+pub enum PrReviewStateStoreError {
+    /// Failed to create storage directory.
+    #[error("Failed to create storage directory: {0}")]
+    CreateDir(std::io::Error),
+
+    /// Failed to read from storage.
+    #[error("Failed to read from storage: {0}")]
+    Read(std::io::Error),
+
+    /// Failed to write to storage.
+    #[error("Failed to write to storage: {0}")]
+    Write(std::io::Error),
+
+    /// Failed to parse stored data.
+    #[error("Failed to parse stored data: {0}")]
+    Parse(serde_json::Error),
+
+    /// Failed to serialize data.
+    #[error("Failed to serialize data: {0}")]
+    Serialize(serde_json::Error),
+
+    /// The key contains a path component that isn't a plain name.
+    #[error("Invalid pull request reference: {0}")]
+    InvalidKey(PendingReviewKey),
+
+    /// Could not determine data directory.
+    #[error("Could not determine XDG data directory")]
+    NoDataDir,
+}
+
+/// Files viewed at a particular head commit, and the checked checklist
+/// items.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct ReviewState {
+    head_sha: String,
+    paths: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    checked: BTreeSet<String>,
+}
+
+/// File-based storage for per-pull-request review progress.
+///
+/// Viewed files are recorded against the head commit they were viewed at.
+/// Once new commits are pushed, the pull request starts over with nothing
+/// viewed, since any file may have changed. Checked checklist items are
+/// kept across pushes.
+///
+/// Storage layout:
+/// ```text
+/// $XDG_DATA_HOME/chadreview/review_state/{provider}/{owner}/{repo}/
+/// └── {number}.json       # Viewed files and checked items for a pull request
+/// ```
+pub struct PrReviewStateStore {
+    /// Base path for all review state.
+    root: PathBuf,
+    /// Serializes read-modify-write updates.
+    lock: Mutex<()>,
+}
+
+impl PrReviewStateStore {
+    /// Create a store in the XDG data directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the XDG data directory cannot be determined.
+    pub fn new() -> Result<Self, PrReviewStateStoreError> {
+        let data_dir = dirs::data_dir().ok_or(PrReviewStateStoreError::NoDataDir)?;
+
+        Ok(Self {
+            root: data_dir.join("chadreview").join("review_state"),
+            lock: Mutex::new(()),
+        })
+    }
+
+    /// Get the path to a pull request's review state file.
+    fn state_path(&self, key: &PendingReviewKey) -> Result<PathBuf, PrReviewStateStoreError> {
+        if !key.is_valid() {
+            return Err(PrReviewStateStoreError::InvalidKey(key.clone()));
+        }
+
+        Ok(self
+            .root
+            .join(&key.provider)
+            .join(&key.owner)
+            .join(&key.repo)
+            .join(format!("{}.json", key.number)))
+    }
+
+    fn load(&self, key: &PendingReviewKey) -> Result<ReviewState, PrReviewStateStoreError> {
+        let path = self.state_path(key)?;
+        if !path.exists() {
+            return Ok(ReviewState::default());
+        }
+
+        let file = File::open(&path).map_err(PrReviewStateStoreError::Read)?;
+        let reader = BufReader::new(file);
+        serde_json::from_reader(reader).map_err(PrReviewStateStoreError::Parse)
+    }
+
+    fn save(
+        &self,
+        key: &PendingReviewKey,
+        state: &ReviewState,
+    ) -> Result<(), PrReviewStateStoreError> {
+        let path = self.state_path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(PrReviewStateStoreError::CreateDir)?;
+        }
+
+        let file = File::create(&path).map_err(PrReviewStateStoreError::Write)?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, state).map_err(PrReviewStateStoreError::Serialize)
+    }
+
+    /// Get the paths of files viewed at `head_sha`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the review state can't be read.
+    pub fn viewed_paths(
+        &self,
+        key: &PendingReviewKey,
+        head_sha: &str,
+    ) -> Result<HashSet<String>, PrReviewStateStoreError> {
+        let viewed = self.load(key)?;
+        if viewed.head_sha != head_sha {
+            return Ok(HashSet::new());
+        }
+
+        Ok(viewed.paths.into_iter().collect())
+    }
+
+    /// Mark a file as viewed or not viewed at `head_sha`, forgetting files
+    /// viewed at any other head.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the review state can't be read or written.
+    pub fn set_file_viewed(
+        &self,
+        key: &PendingReviewKey,
+        head_sha: &str,
+        path: &str,
+        viewed: bool,
+    ) -> Result<(), PrReviewStateStoreError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let mut state = self.load(key)?;
+        if state.head_sha != head_sha {
+            state.head_sha = head_sha.to_string();
+            state.paths.clear();
+        }
+
+        if viewed {
+            state.paths.insert(path.to_string());
+        } else {
+            state.paths.remove(path);
+        }
+
+        self.save(key, &state)
+    }
+
+    /// Get the checklist items checked on a pull request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the review state can't be read.
+    pub fn checked_items(
+        &self,
+        key: &PendingReviewKey,
+    ) -> Result<HashSet<String>, PrReviewStateStoreError> {
+        Ok(self.load(key)?.checked.into_iter().collect())
+    }
+
+    /// Check or uncheck a checklist item on a pull request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the review state can't be read or written.
+    pub fn set_checklist_item(
+        &self,
+        key: &PendingReviewKey,
+        item: &str,
+        checked: bool,
+    ) -> Result<(), PrReviewStateStoreError> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let mut state = self.load(key)?;
+        if checked {
+            state.checked.insert(item.to_string());
+        } else {
+            state.checked.remove(item);
+        }
+
+        self.save(key, &state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn temp_store(name: &str) -> PrReviewStateStore {
+        let root = env::temp_dir().join(format!(
+            "chadreview-review-state-test-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        PrReviewStateStore {
+            root,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn key() -> PendingReviewKey {
+        PendingReviewKey::new("github", "owner", "repo", 1)
+    }
+
+    #[test]
+    fn test_mark_viewed_and_unviewed() {
+        let store = temp_store("mark");
+        assert!(store.viewed_paths(&key(), "abc").unwrap().is_empty());
+
+        store
+            .set_file_viewed(&key(), "abc", "src/lib.rs", true)
+            .unwrap();
+        store
+            .set_file_viewed(&key(), "abc", "README.md", true)
+            .unwrap();
+        store
+            .set_file_viewed(&key(), "abc", "README.md", false)
+            .unwrap();
+
+        let viewed = store.viewed_paths(&key(), "abc").unwrap();
+        assert_eq!(viewed, HashSet::from(["src/lib.rs".to_string()]));
+    }
+
+    #[test]
+    fn test_new_head_resets_viewed_files() {
+        let store = temp_store("new-head");
+        store
+            .set_file_viewed(&key(), "abc", "src/lib.rs", true)
+            .unwrap();
+
+        assert!(store.viewed_paths(&key(), "def").unwrap().is_empty());
+
+        store
+            .set_file_viewed(&key(), "def", "src/main.rs", true)
+            .unwrap();
+        assert!(store.viewed_paths(&key(), "abc").unwrap().is_empty());
+        assert_eq!(store.viewed_paths(&key(), "def").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_checked_items_survive_new_head() {
+        let store = temp_store("checklist");
+        store
+            .set_checklist_item(&key(), "Tests added", true)
+            .unwrap();
+        store
+            .set_checklist_item(&key(), "Docs updated", true)
+            .unwrap();
+        store
+            .set_checklist_item(&key(), "Docs updated", false)
+            .unwrap();
+        store
+            .set_file_viewed(&key(), "def", "src/main.rs", true)
+            .unwrap();
+
+        assert_eq!(
+            store.checked_items(&key()).unwrap(),
+            HashSet::from(["Tests added".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_rejects_path_traversal() {
+        let store = temp_store("traversal");
+        let key = PendingReviewKey::new("github", "owner", "..", 1);
+
+        assert!(matches!(
+            store.set_file_viewed(&key, "abc", "src/lib.rs", true),
+            Err(PrReviewStateStoreError::InvalidKey(_))
+        ));
+    }
+}
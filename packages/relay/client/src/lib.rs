@@ -7,56 +7,115 @@ use chadreview_relay_models::{
     ClientMessage, PrKey, RelayMessage, ServerMessage, SubscribeMessage, UnsubscribeMessage,
     WebhookEvent,
 };
+use chrono::{DateTime, Utc};
 use futures::{SinkExt, StreamExt};
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 use tokio::sync::RwLock;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{
+        Error as WsError, Message,
+        client::IntoClientRequest as _,
+        http::{HeaderValue, StatusCode, header::AUTHORIZATION},
+    },
+};
 
 pub type EventCallback = Arc<dyn Fn(WebhookEvent) + Send + Sync>;
 
+/// Called after a reconnect with the PRs the relay confirmed the client is
+/// subscribed to again.
+pub type ReconnectCallback = Arc<dyn Fn(Vec<PrKey>) + Send + Sync>;
+
+/// How long to wait for the relay to confirm resubscriptions after a
+/// reconnect.
+const RESUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Outcome of a subscription change, with the reason it was rejected.
+type Confirmation = tokio::sync::oneshot::Sender<std::result::Result<(), String>>;
+type ConfirmationReceiver = tokio::sync::oneshot::Receiver<std::result::Result<(), String>>;
+
 pub struct RelayClient {
     instance_id: String,
     relay_url: String,
+    token: Option<String>,
     subscriptions: Arc<RwLock<HashMap<PrKey, EventCallback>>>,
     sender: Arc<RwLock<Option<futures::channel::mpsc::UnboundedSender<Message>>>>,
     ready: Arc<tokio::sync::Notify>,
-    pending_confirmations: Arc<RwLock<HashMap<PrKey, tokio::sync::oneshot::Sender<()>>>>,
+    rejected: Arc<tokio::sync::Notify>,
+    pending_confirmations: Arc<RwLock<HashMap<PrKey, Confirmation>>>,
+    /// When the relay received the latest event delivered to this client,
+    /// which missed events are replayed from after a reconnect.
+    last_event_at: Arc<RwLock<Option<DateTime<Utc>>>>,
+    on_reconnect: RwLock<Option<ReconnectCallback>>,
+    /// Whether a connection was established before, so the next one is a
+    /// reconnect.
+    connected: AtomicBool,
 }
 
 impl RelayClient {
-    /// Connect to the relay server
+    /// Connect to the relay server, authenticating with `token` if the server
+    /// requires one
     ///
     /// # Errors
     /// Returns an error if the connection cannot be established
-    pub async fn connect_async(relay_url: &str, instance_id: String) -> Result<Arc<Self>> {
+    pub async fn connect_async(
+        relay_url: &str,
+        instance_id: String,
+        token: Option<String>,
+    ) -> Result<Arc<Self>> {
         let client = Arc::new(Self {
             instance_id,
             relay_url: relay_url.to_string(),
+            token,
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
             sender: Arc::new(RwLock::new(None)),
             ready: Arc::new(tokio::sync::Notify::new()),
+            rejected: Arc::new(tokio::sync::Notify::new()),
             pending_confirmations: Arc::new(RwLock::new(HashMap::new())),
+            last_event_at: Arc::new(RwLock::new(None)),
+            on_reconnect: RwLock::new(None),
+            connected: AtomicBool::new(false),
         });
 
         let notified = client.ready.notified();
+        let rejected = client.rejected.notified();
         client.clone().spawn_connection_loop();
-        notified.await;
 
-        Ok(client)
+        tokio::select! {
+            () = notified => Ok(client),
+            () = rejected => anyhow::bail!("Relay server rejected the token for this instance"),
+        }
     }
 
-    /// Connect to the relay server
+    /// Connect to the relay server, authenticating with `token` if the server
+    /// requires one
     ///
     /// # Errors
     /// Returns an error if the connection cannot be established
-    pub fn connect(relay_url: &str, instance_id: String) -> Result<Arc<Self>> {
+    pub fn connect(
+        relay_url: &str,
+        instance_id: String,
+        token: Option<String>,
+    ) -> Result<Arc<Self>> {
         let client = Arc::new(Self {
             instance_id,
             relay_url: relay_url.to_string(),
+            token,
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
             sender: Arc::new(RwLock::new(None)),
             ready: Arc::new(tokio::sync::Notify::new()),
+            rejected: Arc::new(tokio::sync::Notify::new()),
             pending_confirmations: Arc::new(RwLock::new(HashMap::new())),
+            last_event_at: Arc::new(RwLock::new(None)),
+            on_reconnect: RwLock::new(None),
+            connected: AtomicBool::new(false),
         });
 
         client.clone().spawn_connection_loop();
@@ -64,6 +123,27 @@ impl RelayClient {
         Ok(client)
     }
 
+    /// Check that the relay server accepts connections
+    ///
+    /// Opens and closes a connection under a throwaway instance ID so an
+    /// existing connection for this instance isn't replaced. A server
+    /// requiring tokens rejects the throwaway instance, which still shows it
+    /// is up.
+    ///
+    /// # Errors
+    /// Returns an error if the connection cannot be established within `timeout`
+    pub async fn check_health(relay_url: &str, timeout: Duration) -> Result<()> {
+        let url = format!("{relay_url}/ws/health-check-{}", uuid::Uuid::new_v4());
+
+        match tokio::time::timeout(timeout, connect_async(&url)).await? {
+            Ok((mut ws_stream, _)) => ws_stream.close(None).await?,
+            Err(e) if is_unauthorized_response(&e) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        Ok(())
+    }
+
     /// Subscribe to PR webhook events
     ///
     /// # Errors
@@ -80,14 +160,29 @@ impl RelayClient {
             .await
             .insert(pr_key.clone(), tx);
 
-        self.send_message(ClientMessage::Subscribe(SubscribeMessage { pr_key }))
-            .await?;
+        self.send_message(ClientMessage::Subscribe(SubscribeMessage {
+            pr_key: pr_key.clone(),
+        }))
+        .await?;
 
-        rx.await?;
+        if let Err(reason) = rx.await? {
+            self.subscriptions.write().await.remove(&pr_key);
+            anyhow::bail!(reason);
+        }
 
         Ok(())
     }
 
+    /// Call `callback` whenever the client reconnects, once the relay has
+    /// confirmed its subscriptions again
+    ///
+    /// Events received while disconnected are replayed if the relay still
+    /// has them, but the callback can refetch the PRs to be sure nothing was
+    /// missed.
+    pub async fn on_reconnect(&self, callback: ReconnectCallback) {
+        *self.on_reconnect.write().await = Some(callback);
+    }
+
     /// Unsubscribe from PR webhook events
     ///
     /// # Errors
@@ -106,7 +201,7 @@ impl RelayClient {
         }))
         .await?;
 
-        rx.await?;
+        rx.await?.map_err(anyhow::Error::msg)?;
 
         Ok(())
     }
@@ -123,6 +218,11 @@ impl RelayClient {
         tokio::spawn(async move {
             loop {
                 if let Err(e) = self.clone().connection_loop().await {
+                    if is_unauthorized(&e) {
+                        log::error!("Relay server rejected the token, not reconnecting");
+                        self.rejected.notify_waiters();
+                        return;
+                    }
                     log::error!("Connection error: {e}");
                 }
 
@@ -136,15 +236,28 @@ impl RelayClient {
         let url = format!("{}/ws/{}", self.relay_url, self.instance_id);
         log::info!("Connecting to relay server at {url}");
 
-        let (ws_stream, _) = connect_async(&url).await?;
+        let mut request = url.into_client_request()?;
+        if let Some(token) = &self.token {
+            request.headers_mut().insert(
+                AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {token}"))?,
+            );
+        }
+
+        let (ws_stream, _) = connect_async(request).await?;
         log::info!("Connected to relay server");
 
         let (mut write, mut read) = ws_stream.split();
 
         let (tx, mut rx) = futures::channel::mpsc::unbounded::<Message>();
+        let resubscriptions = self.resume(&tx).await?;
         *self.sender.write().await = Some(tx);
         self.ready.notify_waiters();
 
+        if self.connected.swap(true, Ordering::SeqCst) {
+            tokio::spawn(self.clone().confirm_resubscriptions(resubscriptions));
+        }
+
         let write_task = {
             tokio::spawn(async move {
                 while let Some(msg) = rx.next().await {
@@ -159,6 +272,7 @@ impl RelayClient {
             let subscriptions = self.subscriptions.clone();
             let sender = self.sender.clone();
             let pending_confirmations = self.pending_confirmations.clone();
+            let last_event_at = self.last_event_at.clone();
 
             tokio::spawn(async move {
                 while let Some(msg) = read.next().await {
@@ -167,8 +281,12 @@ impl RelayClient {
                             if let Ok(server_msg) = serde_json::from_str::<ServerMessage>(&text) {
                                 match server_msg {
                                     ServerMessage::Webhook(relay_msg) => {
-                                        Self::handle_webhook_event(&subscriptions, *relay_msg)
-                                            .await;
+                                        Self::handle_webhook_event(
+                                            &subscriptions,
+                                            &last_event_at,
+                                            *relay_msg,
+                                        )
+                                        .await;
                                     }
                                     ServerMessage::Pong => {
                                         log::trace!("Received pong");
@@ -177,14 +295,23 @@ impl RelayClient {
                                         log::info!("Subscribed to {pr_key:?}");
                                         let mut confirmations = pending_confirmations.write().await;
                                         if let Some(tx) = confirmations.remove(&pr_key) {
-                                            let _ = tx.send(());
+                                            let _ = tx.send(Ok(()));
                                         }
                                     }
                                     ServerMessage::Unsubscribed { pr_key } => {
                                         log::info!("Unsubscribed from {pr_key:?}");
                                         let mut confirmations = pending_confirmations.write().await;
                                         if let Some(tx) = confirmations.remove(&pr_key) {
-                                            let _ = tx.send(());
+                                            let _ = tx.send(Ok(()));
+                                        }
+                                    }
+                                    ServerMessage::Unauthorized { pr_key } => {
+                                        log::warn!("Not authorized to subscribe to {pr_key:?}");
+                                        let mut confirmations = pending_confirmations.write().await;
+                                        if let Some(tx) = confirmations.remove(&pr_key) {
+                                            let _ = tx.send(Err(format!(
+                                                "Relay server rejected the token for {pr_key:?}"
+                                            )));
                                         }
                                     }
                                 }
@@ -214,10 +341,100 @@ impl RelayClient {
         Ok(())
     }
 
+    /// Restore this client's subscriptions on a new connection and ask for
+    /// the events missed while it was disconnected, returning the pending
+    /// confirmations of the subscriptions.
+    ///
+    /// Subscriptions are sent again in case the relay restarted without
+    /// persisting them; the relay ignores ones it already has.
+    async fn resume(
+        &self,
+        tx: &futures::channel::mpsc::UnboundedSender<Message>,
+    ) -> Result<Vec<(PrKey, ConfirmationReceiver)>> {
+        let send = |msg: &ClientMessage| -> Result<()> {
+            let json = serde_json::to_string(msg)?;
+            tx.unbounded_send(Message::Text(json.into()))?;
+            Ok(())
+        };
+
+        let pr_keys: Vec<PrKey> = self.subscriptions.read().await.keys().cloned().collect();
+        let mut resubscriptions = Vec::with_capacity(pr_keys.len());
+        for pr_key in pr_keys {
+            // A subscription made while disconnected is confirmed to its caller
+            if let Entry::Vacant(entry) = self
+                .pending_confirmations
+                .write()
+                .await
+                .entry(pr_key.clone())
+            {
+                let (confirmation_tx, confirmation_rx) = tokio::sync::oneshot::channel();
+                entry.insert(confirmation_tx);
+                resubscriptions.push((pr_key.clone(), confirmation_rx));
+            }
+
+            send(&ClientMessage::Subscribe(SubscribeMessage { pr_key }))?;
+        }
+
+        let last_event_at = *self.last_event_at.read().await;
+        if let Some(since) = last_event_at {
+            log::info!("Resuming relay events since {since}");
+            send(&ClientMessage::Resume { since })?;
+        }
+
+        Ok(resubscriptions)
+    }
+
+    /// Wait for the relay to confirm the subscriptions sent again on a
+    /// reconnect, then call the reconnect callback with the confirmed ones.
+    ///
+    /// Subscriptions the relay rejects are dropped, as they would be when
+    /// first subscribing.
+    async fn confirm_resubscriptions(
+        self: Arc<Self>,
+        resubscriptions: Vec<(PrKey, ConfirmationReceiver)>,
+    ) {
+        let deadline = tokio::time::Instant::now() + RESUBSCRIBE_TIMEOUT;
+        let mut resubscribed = Vec::with_capacity(resubscriptions.len());
+
+        for (pr_key, rx) in resubscriptions {
+            match tokio::time::timeout_at(deadline, rx).await {
+                Ok(Ok(Ok(()))) => resubscribed.push(pr_key),
+                Ok(Ok(Err(reason))) => {
+                    log::warn!("Failed to resubscribe to {pr_key:?}: {reason}");
+                    self.subscriptions.write().await.remove(&pr_key);
+                }
+                // Replaced by a newer subscription change, which confirms itself
+                Ok(Err(_)) => {}
+                Err(_) => {
+                    log::warn!("Relay server didn't confirm resubscription to {pr_key:?}");
+                    self.pending_confirmations.write().await.remove(&pr_key);
+                }
+            }
+        }
+
+        log::info!(
+            "Resubscribed to {} PR(s) after reconnecting",
+            resubscribed.len()
+        );
+
+        let callback = self.on_reconnect.read().await.clone();
+        if let Some(callback) = callback {
+            callback(resubscribed);
+        }
+    }
+
     async fn handle_webhook_event(
         subscriptions: &Arc<RwLock<HashMap<PrKey, EventCallback>>>,
+        last_event_at: &Arc<RwLock<Option<DateTime<Utc>>>>,
         relay_msg: RelayMessage,
     ) {
+        {
+            let mut last_event_at = last_event_at.write().await;
+            if last_event_at.is_none_or(|at| at < relay_msg.received_at) {
+                *last_event_at = Some(relay_msg.received_at);
+            }
+        }
+
         let subs = subscriptions.read().await;
         if let Some(callback) = subs.get(&relay_msg.pr_key) {
             callback(relay_msg.event);
@@ -252,3 +469,13 @@ impl RelayClient {
         )
     }
 }
+
+fn is_unauthorized(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<WsError>()
+        .is_some_and(is_unauthorized_response)
+}
+
+fn is_unauthorized_response(error: &WsError) -> bool {
+    matches!(error, WsError::Http(response) if response.status() == StatusCode::UNAUTHORIZED)
+}
@@ -6,6 +6,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumDiscriminants, EnumIter, EnumString};
 
+#[cfg(feature = "webhook")]
+pub mod webhook;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq)]
 pub struct PrKey {
     pub owner: String,
@@ -17,6 +20,9 @@ pub struct PrKey {
 pub struct RelayMessage {
     pub pr_key: PrKey,
     pub event: WebhookEvent,
+    /// When the relay received the event, which clients resume from.
+    #[serde(default)]
+    pub received_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, EnumDiscriminants, AsRefStr)]
@@ -44,6 +50,67 @@ pub enum WebhookEvent {
         pull_request: GitHubPullRequest,
         repository: GitHubRepository,
     },
+    PullRequestReview {
+        action: ReviewAction,
+        review: GitHubReview,
+        pull_request: GitHubPullRequest,
+        repository: GitHubRepository,
+    },
+    PullRequestReviewThread {
+        action: ReviewThreadAction,
+        thread: GitHubReviewThread,
+        pull_request: GitHubPullRequest,
+        repository: GitHubRepository,
+    },
+    CheckRun {
+        action: CheckRunAction,
+        check_run: Box<GitHubCheckRun>,
+        repository: GitHubRepository,
+    },
+    Status {
+        sha: String,
+        state: String,
+        context: String,
+        description: Option<String>,
+        target_url: Option<String>,
+        repository: GitHubRepository,
+    },
+}
+
+impl WebhookEvent {
+    #[must_use]
+    pub const fn repository(&self) -> &GitHubRepository {
+        match self {
+            Self::IssueComment { repository, .. }
+            | Self::PullRequestReviewComment { repository, .. }
+            | Self::PullRequest { repository, .. }
+            | Self::PullRequestReview { repository, .. }
+            | Self::PullRequestReviewThread { repository, .. }
+            | Self::CheckRun { repository, .. }
+            | Self::Status { repository, .. } => repository,
+        }
+    }
+
+    /// Numbers of the PRs the event names.
+    ///
+    /// Check runs name the PRs of their commit, but commit statuses name
+    /// none; whether a status is for a PR is told by its `sha`.
+    #[must_use]
+    pub fn pr_numbers(&self) -> Vec<u64> {
+        match self {
+            Self::IssueComment { issue, .. } => vec![issue.number],
+            Self::PullRequestReviewComment { pull_request, .. }
+            | Self::PullRequest { pull_request, .. }
+            | Self::PullRequestReview { pull_request, .. }
+            | Self::PullRequestReviewThread { pull_request, .. } => vec![pull_request.number],
+            Self::CheckRun { check_run, .. } => check_run
+                .pull_requests
+                .iter()
+                .map(|pull_request| pull_request.number)
+                .collect(),
+            Self::Status { .. } => vec![],
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -64,6 +131,30 @@ pub enum PrAction {
     Synchronize,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewAction {
+    Submitted,
+    Edited,
+    Dismissed,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewThreadAction {
+    Resolved,
+    Unresolved,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckRunAction {
+    Created,
+    Completed,
+    Rerequested,
+    RequestedAction,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GitHubComment {
     pub id: u64,
@@ -89,6 +180,42 @@ pub struct GitHubReviewComment {
     pub in_reply_to_id: Option<u64>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitHubReview {
+    pub id: u64,
+    pub body: Option<String>,
+    pub state: String,
+    pub commit_id: String,
+    pub user: GitHubUser,
+    pub submitted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitHubReviewThread {
+    pub node_id: String,
+    pub comments: Vec<GitHubReviewComment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitHubCheckRun {
+    pub id: u64,
+    pub name: String,
+    pub head_sha: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+    pub html_url: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub pull_requests: Vec<GitHubCheckRunPullRequest>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitHubCheckRunPullRequest {
+    pub number: u64,
+    pub head: GitHubRef,
+    pub base: GitHubRef,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GitHubIssue {
     pub number: u64,
@@ -143,6 +270,8 @@ pub struct UnsubscribeMessage {
 pub enum ClientMessage {
     Subscribe(SubscribeMessage),
     Unsubscribe(UnsubscribeMessage),
+    /// Replay the events of the subscribed PRs received after `since`.
+    Resume { since: DateTime<Utc> },
     Ping,
 }
 
@@ -153,4 +282,5 @@ pub enum ServerMessage {
     Pong,
     Subscribed { pr_key: PrKey },
     Unsubscribed { pr_key: PrKey },
+    Unauthorized { pr_key: PrKey },
 }
@@ -0,0 +1,125 @@
+//! Parsing and signature verification of GitHub webhook deliveries.
+//!
+//! Shared by the relay server and by instances receiving webhooks directly,
+//! so both accept exactly the same deliveries.
+
+use std::str::FromStr as _;
+
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+use crate::{
+    CheckRunAction, CommentAction, PrAction, ReviewAction, ReviewThreadAction, WebhookEvent,
+    WebhookEventType,
+};
+
+/// Header naming the event type of a delivery.
+pub const EVENT_HEADER: &str = "X-GitHub-Event";
+
+/// Header carrying the HMAC-SHA256 signature of a delivery's body.
+pub const SIGNATURE_HEADER: &str = "X-Hub-Signature-256";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Verify the `X-Hub-Signature-256` header of a delivery against its body.
+///
+/// # Errors
+///
+/// Returns why the signature was rejected if it is missing, malformed, or
+/// doesn't match the body signed with `secret`.
+pub fn verify_signature(
+    signature_header: Option<&str>,
+    body: &[u8],
+    secret: &str,
+) -> Result<(), &'static str> {
+    let expected_signature = signature_header
+        .ok_or("Missing signature header")?
+        .strip_prefix("sha256=")
+        .ok_or("Invalid signature format")?;
+
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).map_err(|_| "Invalid secret")?;
+    mac.update(body);
+
+    let computed_signature = hex::encode(mac.finalize().into_bytes());
+
+    // Use constant-time comparison to prevent timing attacks
+    if computed_signature
+        .as_bytes()
+        .ct_eq(expected_signature.as_bytes())
+        .into()
+    {
+        Ok(())
+    } else {
+        Err("Signature mismatch")
+    }
+}
+
+/// Parse the body of a delivery of `event_type`, or `None` if the event
+/// type isn't one the relay handles.
+///
+/// # Errors
+///
+/// Returns an error if the body isn't a valid payload of `event_type`.
+pub fn parse_webhook_event(
+    event_type: &str,
+    body: &[u8],
+) -> Result<Option<WebhookEvent>, serde_json::Error> {
+    let Ok(webhook_type) = WebhookEventType::from_str(event_type) else {
+        return Ok(None);
+    };
+
+    let value: serde_json::Value = serde_json::from_slice(body)?;
+
+    Ok(Some(match webhook_type {
+        WebhookEventType::IssueComment => WebhookEvent::IssueComment {
+            action: parse_action(&value, CommentAction::Created),
+            comment: serde_json::from_value(value["comment"].clone())?,
+            issue: serde_json::from_value(value["issue"].clone())?,
+            repository: serde_json::from_value(value["repository"].clone())?,
+        },
+        WebhookEventType::PullRequestReviewComment => WebhookEvent::PullRequestReviewComment {
+            action: parse_action(&value, CommentAction::Created),
+            comment: Box::new(serde_json::from_value(value["comment"].clone())?),
+            pull_request: serde_json::from_value(value["pull_request"].clone())?,
+            repository: serde_json::from_value(value["repository"].clone())?,
+        },
+        WebhookEventType::PullRequest => WebhookEvent::PullRequest {
+            action: parse_action(&value, PrAction::Opened),
+            pull_request: serde_json::from_value(value["pull_request"].clone())?,
+            repository: serde_json::from_value(value["repository"].clone())?,
+        },
+        WebhookEventType::PullRequestReview => WebhookEvent::PullRequestReview {
+            action: parse_action(&value, ReviewAction::Submitted),
+            review: serde_json::from_value(value["review"].clone())?,
+            pull_request: serde_json::from_value(value["pull_request"].clone())?,
+            repository: serde_json::from_value(value["repository"].clone())?,
+        },
+        WebhookEventType::PullRequestReviewThread => WebhookEvent::PullRequestReviewThread {
+            action: parse_action(&value, ReviewThreadAction::Resolved),
+            thread: serde_json::from_value(value["thread"].clone())?,
+            pull_request: serde_json::from_value(value["pull_request"].clone())?,
+            repository: serde_json::from_value(value["repository"].clone())?,
+        },
+        WebhookEventType::CheckRun => WebhookEvent::CheckRun {
+            action: parse_action(&value, CheckRunAction::Created),
+            check_run: Box::new(serde_json::from_value(value["check_run"].clone())?),
+            repository: serde_json::from_value(value["repository"].clone())?,
+        },
+        WebhookEventType::Status => WebhookEvent::Status {
+            sha: serde_json::from_value(value["sha"].clone())?,
+            state: serde_json::from_value(value["state"].clone())?,
+            context: serde_json::from_value(value["context"].clone())?,
+            description: serde_json::from_value(value["description"].clone())?,
+            target_url: serde_json::from_value(value["target_url"].clone())?,
+            repository: serde_json::from_value(value["repository"].clone())?,
+        },
+    }))
+}
+
+/// The `action` of a webhook payload, or `default` if it is missing or not
+/// one this relay knows.
+fn parse_action<T: DeserializeOwned>(value: &serde_json::Value, default: T) -> T {
+    serde_json::from_value(value["action"].clone()).unwrap_or(default)
+}
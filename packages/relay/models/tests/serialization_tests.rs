@@ -157,6 +157,7 @@ fn test_pr_action_copy() {
 #[test]
 fn test_relay_message_roundtrip() {
     let relay_msg = RelayMessage {
+        received_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
         pr_key: PrKey {
             owner: "owner".to_string(),
             repo: "repo".to_string(),
@@ -214,6 +215,9 @@ fn test_client_message_roundtrip() {
                 number: 1,
             },
         }),
+        ClientMessage::Resume {
+            since: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        },
     ];
 
     for msg in messages {
@@ -0,0 +1,57 @@
+#![cfg(feature = "webhook")]
+
+use chadreview_relay_models::WebhookEvent;
+use chadreview_relay_models::webhook::{parse_webhook_event, verify_signature};
+
+const BODY: &[u8] = br#"{"zen":"hi"}"#;
+const SIGNATURE: &str = "sha256=a3fb8cb2d37fc18eec1b036a46a9a8ebd8e210d829b0b02a4be4d03b1d259492";
+
+#[test]
+fn test_verify_signature() {
+    assert_eq!(verify_signature(Some(SIGNATURE), BODY, "secret"), Ok(()));
+    assert_eq!(
+        verify_signature(Some(SIGNATURE), BODY, "other-secret"),
+        Err("Signature mismatch")
+    );
+    assert_eq!(
+        verify_signature(Some(SIGNATURE), br#"{"zen":"bye"}"#, "secret"),
+        Err("Signature mismatch")
+    );
+    assert_eq!(
+        verify_signature(Some(&SIGNATURE[7..]), BODY, "secret"),
+        Err("Invalid signature format")
+    );
+    assert_eq!(
+        verify_signature(None, BODY, "secret"),
+        Err("Missing signature header")
+    );
+}
+
+#[test]
+fn test_parse_webhook_event() {
+    let body = br#"{
+        "sha": "abc123",
+        "state": "success",
+        "context": "ci/build",
+        "description": null,
+        "target_url": null,
+        "repository": {
+            "name": "repo",
+            "full_name": "owner/repo",
+            "owner": {
+                "id": 1,
+                "login": "owner",
+                "avatar_url": "https://github.com/owner.png",
+                "html_url": "https://github.com/owner"
+            }
+        }
+    }"#;
+
+    let event = parse_webhook_event("status", body).unwrap().unwrap();
+    assert!(matches!(&event, WebhookEvent::Status { context, .. } if context == "ci/build"));
+    assert_eq!(event.repository().full_name, "owner/repo");
+    assert!(event.pr_numbers().is_empty());
+
+    assert_eq!(parse_webhook_event("ping", BODY).unwrap(), None);
+    assert!(parse_webhook_event("status", BODY).is_err());
+}
@@ -0,0 +1,164 @@
+//! Admin routes for inspecting and managing the relay's live state.
+//!
+//! Every route requires an `Authorization: Bearer <token>` header matching
+//! the configured admin token, and the routes don't exist when no admin
+//! token is configured.
+
+use std::collections::BTreeMap;
+
+use actix_web::{HttpRequest, HttpResponse, web};
+use chadreview_relay_models::PrKey;
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+
+use crate::state::AppState;
+use crate::websocket::bearer_token;
+
+/// A connected instance, as listed by `/admin/clients`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientInfo {
+    pub instance_id: String,
+    /// Open WebSocket connections of the instance.
+    pub connections: usize,
+    pub subscriptions: Vec<PrKey>,
+}
+
+/// A subscribed PR, as listed by `/admin/subscriptions`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionInfo {
+    pub pr_key: PrKey,
+    /// Subscribed instances, connected or not.
+    pub instances: Vec<String>,
+    /// Events kept for instances resuming after a disconnect.
+    pub logged_events: usize,
+}
+
+/// Response of `/admin/disconnect/{instance_id}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisconnectResponse {
+    /// Connections that were closed.
+    pub disconnected: usize,
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/admin")
+            .route("/clients", web::get().to(clients))
+            .route("/subscriptions", web::get().to(subscriptions))
+            .route("/disconnect/{instance_id}", web::post().to(disconnect)),
+    );
+}
+
+/// The response rejecting `req` if it lacks the admin token, or every
+/// request if there is none.
+fn rejection(req: &HttpRequest, state: &AppState) -> Option<HttpResponse> {
+    let Some(expected) = &state.admin_token else {
+        return Some(HttpResponse::NotFound().finish());
+    };
+
+    match bearer_token(req) {
+        Some(token) if bool::from(expected.as_bytes().ct_eq(token.as_bytes())) => None,
+        _ => {
+            log::warn!("Rejected admin request to {}: invalid token", req.path());
+            Some(HttpResponse::Unauthorized().finish())
+        }
+    }
+}
+
+#[allow(clippy::future_not_send)]
+async fn clients(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Some(response) = rejection(&req, &state) {
+        return response;
+    }
+
+    let mut clients: BTreeMap<String, ClientInfo> = state
+        .connections
+        .read()
+        .await
+        .iter()
+        .map(|(instance_id, senders)| {
+            let client = ClientInfo {
+                instance_id: instance_id.clone(),
+                connections: senders.iter().filter(|sender| !sender.is_closed()).count(),
+                subscriptions: vec![],
+            };
+            (instance_id.clone(), client)
+        })
+        .collect();
+
+    for (pr_key, instances) in state.subscriptions.read().await.iter() {
+        for instance_id in instances {
+            if let Some(client) = clients.get_mut(instance_id) {
+                client.subscriptions.push(pr_key.clone());
+            }
+        }
+    }
+
+    let clients: Vec<ClientInfo> = clients
+        .into_values()
+        .map(|mut client| {
+            client
+                .subscriptions
+                .sort_by(|a, b| (&a.owner, &a.repo, a.number).cmp(&(&b.owner, &b.repo, b.number)));
+            client
+        })
+        .collect();
+
+    HttpResponse::Ok().json(clients)
+}
+
+#[allow(clippy::future_not_send)]
+async fn subscriptions(req: HttpRequest, state: web::Data<AppState>) -> HttpResponse {
+    if let Some(response) = rejection(&req, &state) {
+        return response;
+    }
+
+    let events = state.events.read().await;
+    let mut subscriptions: Vec<SubscriptionInfo> = state
+        .subscriptions
+        .read()
+        .await
+        .iter()
+        .map(|(pr_key, instances)| SubscriptionInfo {
+            pr_key: pr_key.clone(),
+            instances: instances.clone(),
+            logged_events: events
+                .get(pr_key)
+                .map_or(0, std::collections::VecDeque::len),
+        })
+        .collect();
+    drop(events);
+
+    subscriptions.sort_by(|a, b| {
+        (&a.pr_key.owner, &a.pr_key.repo, a.pr_key.number).cmp(&(
+            &b.pr_key.owner,
+            &b.pr_key.repo,
+            b.pr_key.number,
+        ))
+    });
+
+    HttpResponse::Ok().json(subscriptions)
+}
+
+/// Close the connections of an instance. Its subscriptions are kept, and it
+/// can reconnect unless its instance token is revoked too.
+#[allow(clippy::future_not_send)]
+async fn disconnect(
+    req: HttpRequest,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> HttpResponse {
+    if let Some(response) = rejection(&req, &state) {
+        return response;
+    }
+
+    let instance_id = path.into_inner();
+    let disconnected = state.disconnect(&instance_id).await;
+    if disconnected == 0 {
+        return HttpResponse::NotFound().body(format!("Instance {instance_id} is not connected"));
+    }
+
+    log::info!("Admin disconnected {disconnected} connection(s) of instance {instance_id}");
+
+    HttpResponse::Ok().json(DisconnectResponse { disconnected })
+}
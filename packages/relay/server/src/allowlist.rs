@@ -0,0 +1,78 @@
+//! Repositories whose webhooks the relay forwards.
+//!
+//! A relay shared by several people only needs their repositories' events;
+//! with an allowlist, events of every other repository are turned away
+//! instead of being relayed to whoever subscribes to them.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// An `owner/repo` pattern, where either part may be `*` to match any
+/// owner or repository. A lone `*` matches every repository.
+///
+/// Names are matched case-insensitively, as GitHub treats them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoPattern {
+    owner: Option<String>,
+    repo: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid repository pattern {0:?}, expected owner/repo, owner/* or *")]
+pub struct InvalidRepoPattern(pub String);
+
+impl RepoPattern {
+    #[must_use]
+    pub fn matches(&self, owner: &str, repo: &str) -> bool {
+        let part_matches = |pattern: &Option<String>, name: &str| {
+            pattern
+                .as_ref()
+                .is_none_or(|p| p.eq_ignore_ascii_case(name))
+        };
+
+        part_matches(&self.owner, owner) && part_matches(&self.repo, repo)
+    }
+}
+
+impl FromStr for RepoPattern {
+    type Err = InvalidRepoPattern;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let part = |part: &str| match part.trim() {
+            "" => Err(InvalidRepoPattern(s.to_string())),
+            "*" => Ok(None),
+            name if name.contains(['*', '/']) => Err(InvalidRepoPattern(s.to_string())),
+            name => Ok(Some(name.to_string())),
+        };
+
+        match s.trim() {
+            "*" => Ok(Self {
+                owner: None,
+                repo: None,
+            }),
+            pattern => {
+                let (owner, repo) = pattern
+                    .split_once('/')
+                    .ok_or_else(|| InvalidRepoPattern(s.to_string()))?;
+                Ok(Self {
+                    owner: part(owner)?,
+                    repo: part(repo)?,
+                })
+            }
+        }
+    }
+}
+
+impl fmt::Display for RepoPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let part = |part: &Option<String>| part.clone().unwrap_or_else(|| "*".to_string());
+        write!(f, "{}/{}", part(&self.owner), part(&self.repo))
+    }
+}
+
+/// Whether the repository `owner/repo` is on `allowlist`. Every repository
+/// is when the allowlist is empty.
+#[must_use]
+pub fn is_allowed(allowlist: &[RepoPattern], owner: &str, repo: &str) -> bool {
+    allowlist.is_empty() || allowlist.iter().any(|pattern| pattern.matches(owner, repo))
+}
@@ -0,0 +1,81 @@
+//! Client addresses of requests forwarded by reverse proxies.
+//!
+//! Behind a proxy every request comes from the proxy's address, with the
+//! client's in an `X-Forwarded-For` header. Anyone can send that header,
+//! though, so it is only believed when the request comes from a trusted
+//! proxy.
+
+use std::net::IpAddr;
+
+use actix_web::HttpRequest;
+
+/// The address of the client that sent `req`, looking through the trusted
+/// proxies in `trusted_proxies`.
+#[must_use]
+pub fn request_client_ip(req: &HttpRequest, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+    let forwarded_for = req
+        .headers()
+        .get("X-Forwarded-For")
+        .and_then(|value| value.to_str().ok());
+
+    client_ip(
+        req.peer_addr().map(|addr| addr.ip()),
+        forwarded_for,
+        trusted_proxies,
+    )
+}
+
+/// The scheme the client used to reach the relay: `X-Forwarded-Proto` if
+/// the request comes from a trusted proxy, or the connection's own.
+#[must_use]
+pub fn request_scheme(req: &HttpRequest, trusted_proxies: &[IpAddr]) -> String {
+    let from_proxy = req
+        .peer_addr()
+        .is_some_and(|addr| trusted_proxies.contains(&addr.ip()));
+
+    let forwarded = req
+        .headers()
+        .get("X-Forwarded-Proto")
+        .and_then(|value| value.to_str().ok())
+        .filter(|_| from_proxy)
+        .and_then(|value| value.split(',').next());
+
+    match forwarded {
+        Some(scheme) => scheme.trim().to_string(),
+        // `connection_info` would believe the header from anyone
+        None if req.app_config().secure() => "https".to_string(),
+        None => "http".to_string(),
+    }
+}
+
+/// The client address of a request from `peer` with the given
+/// `X-Forwarded-For` header.
+///
+/// Each proxy appends the address it got the request from, so the header
+/// is read from the right, skipping trusted proxies, and the first other
+/// address is the client's. Requests not from a trusted proxy are taken at
+/// face value.
+#[must_use]
+pub fn client_ip(
+    peer: Option<IpAddr>,
+    forwarded_for: Option<&str>,
+    trusted_proxies: &[IpAddr],
+) -> Option<IpAddr> {
+    let peer = peer?;
+    if !trusted_proxies.contains(&peer) {
+        return Some(peer);
+    }
+
+    let mut client = peer;
+    for addr in forwarded_for.unwrap_or_default().rsplit(',') {
+        let Ok(addr) = addr.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = addr;
+        if !trusted_proxies.contains(&addr) {
+            break;
+        }
+    }
+
+    Some(client)
+}
@@ -2,12 +2,25 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 #![allow(clippy::multiple_crate_versions, clippy::cargo_common_metadata)]
 
+pub mod admin;
+pub mod allowlist;
+pub mod forwarded;
+pub mod rate_limit;
 pub mod state;
+#[cfg(feature = "sqlite")]
+pub mod store;
 pub mod webhook;
 pub mod websocket;
 mod ws;
 
+use std::collections::HashMap;
+use std::net::IpAddr;
+#[cfg(any(feature = "sqlite", feature = "tls"))]
+use std::path::PathBuf;
+
 use actix_web::{App, HttpServer, middleware, web};
+use allowlist::RepoPattern;
+use rate_limit::RateLimit;
 use state::AppState;
 use tokio::task::JoinHandle;
 
@@ -16,6 +29,32 @@ pub struct ServerConfig {
     pub host: String,
     pub port: u16,
     pub webhook_secret: Option<String>,
+    /// Bearer token each instance must connect with, keyed by instance ID.
+    /// When empty, any instance can connect.
+    pub instance_tokens: HashMap<String, String>,
+    /// Bearer token of the `/admin` routes, which are disabled without one.
+    pub admin_token: Option<String>,
+    /// Reverse proxies whose `X-Forwarded-*` headers are believed when
+    /// logging where requests come from.
+    pub trusted_proxies: Vec<IpAddr>,
+    /// Repositories whose webhooks are relayed. When empty, every
+    /// repository's are.
+    pub repository_allowlist: Vec<RepoPattern>,
+    /// Limit of verified webhook deliveries per repository, and of failed
+    /// signature checks per address. Unlimited when unset.
+    pub webhook_rate_limit: Option<RateLimit>,
+    /// Largest webhook body accepted, in bytes.
+    pub webhook_max_body_size: usize,
+    /// Certificate and key to serve HTTPS and WSS with. Served over plain
+    /// HTTP when unset, e.g. behind a proxy terminating TLS.
+    #[cfg(feature = "tls")]
+    pub tls: Option<TlsConfig>,
+    /// Events kept per PR for instances resuming after a disconnect.
+    pub event_log_size: usize,
+    /// `SQLite` database persisting subscriptions and event logs across
+    /// restarts. Kept in memory only when unset.
+    #[cfg(feature = "sqlite")]
+    pub database_path: Option<PathBuf>,
 }
 
 impl Default for ServerConfig {
@@ -24,17 +63,39 @@ impl Default for ServerConfig {
             host: "0.0.0.0".to_string(),
             port: 8080,
             webhook_secret: None,
+            instance_tokens: HashMap::new(),
+            admin_token: None,
+            trusted_proxies: vec![],
+            repository_allowlist: vec![],
+            webhook_rate_limit: None,
+            webhook_max_body_size: state::DEFAULT_WEBHOOK_MAX_BODY_SIZE,
+            #[cfg(feature = "tls")]
+            tls: None,
+            event_log_size: state::DEFAULT_EVENT_LOG_SIZE,
+            #[cfg(feature = "sqlite")]
+            database_path: None,
         }
     }
 }
 
 impl ServerConfig {
     #[must_use]
-    pub const fn new(host: String, port: u16) -> Self {
+    pub fn new(host: String, port: u16) -> Self {
         Self {
             host,
             port,
             webhook_secret: None,
+            instance_tokens: HashMap::new(),
+            admin_token: None,
+            trusted_proxies: vec![],
+            repository_allowlist: vec![],
+            webhook_rate_limit: None,
+            webhook_max_body_size: state::DEFAULT_WEBHOOK_MAX_BODY_SIZE,
+            #[cfg(feature = "tls")]
+            tls: None,
+            event_log_size: state::DEFAULT_EVENT_LOG_SIZE,
+            #[cfg(feature = "sqlite")]
+            database_path: None,
         }
     }
 
@@ -55,6 +116,83 @@ impl ServerConfig {
         self.webhook_secret = secret;
         self
     }
+
+    #[must_use]
+    pub fn with_instance_token(mut self, instance_id: String, token: String) -> Self {
+        self.instance_tokens.insert(instance_id, token);
+        self
+    }
+
+    #[must_use]
+    pub fn with_instance_tokens(mut self, tokens: HashMap<String, String>) -> Self {
+        self.instance_tokens.extend(tokens);
+        self
+    }
+
+    #[must_use]
+    pub fn with_admin_token(mut self, token: Option<String>) -> Self {
+        self.admin_token = token;
+        self
+    }
+
+    #[must_use]
+    pub fn with_trusted_proxy(mut self, proxy: IpAddr) -> Self {
+        self.trusted_proxies.push(proxy);
+        self
+    }
+
+    #[must_use]
+    pub fn with_trusted_proxies(mut self, proxies: impl IntoIterator<Item = IpAddr>) -> Self {
+        self.trusted_proxies.extend(proxies);
+        self
+    }
+
+    #[must_use]
+    pub fn with_allowed_repository(mut self, pattern: RepoPattern) -> Self {
+        self.repository_allowlist.push(pattern);
+        self
+    }
+
+    #[must_use]
+    pub fn with_allowed_repositories(
+        mut self,
+        patterns: impl IntoIterator<Item = RepoPattern>,
+    ) -> Self {
+        self.repository_allowlist.extend(patterns);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_webhook_rate_limit(mut self, limit: Option<RateLimit>) -> Self {
+        self.webhook_rate_limit = limit;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_webhook_max_body_size(mut self, size: usize) -> Self {
+        self.webhook_max_body_size = size;
+        self
+    }
+
+    #[cfg(feature = "tls")]
+    #[must_use]
+    pub fn with_tls(mut self, tls: Option<TlsConfig>) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_event_log_size(mut self, size: usize) -> Self {
+        self.event_log_size = size;
+        self
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[must_use]
+    pub fn with_database_path(mut self, path: Option<PathBuf>) -> Self {
+        self.database_path = path;
+        self
+    }
 }
 
 /// # Errors
@@ -75,7 +213,8 @@ pub struct RunServerResponse {
 
 /// # Errors
 ///
-/// Returns an error if the server fails to bind
+/// Returns an error if the server fails to bind or, with a database or TLS
+/// configured, the database can't be opened or the certificate can't be read
 pub fn run_server_with_handle(config: &ServerConfig) -> std::io::Result<RunServerResponse> {
     log::info!(
         "Starting relay server on {}:{} (test mode)",
@@ -83,17 +222,68 @@ pub fn run_server_with_handle(config: &ServerConfig) -> std::io::Result<RunServe
         config.port
     );
 
-    let state = web::Data::new(AppState::new(config.webhook_secret.clone()));
+    if config.instance_tokens.is_empty() {
+        log::warn!("No instance tokens configured, any instance can connect");
+    }
+
+    if config.repository_allowlist.is_empty() {
+        log::warn!("No repository allowlist configured, every repository's webhooks are relayed");
+    }
+
+    let state = AppState::new(config.webhook_secret.clone())
+        .with_instance_tokens(config.instance_tokens.clone())
+        .with_admin_token(config.admin_token.clone())
+        .with_trusted_proxies(config.trusted_proxies.clone())
+        .with_repository_allowlist(config.repository_allowlist.clone())
+        .with_webhook_rate_limit(config.webhook_rate_limit)
+        .with_webhook_max_body_size(config.webhook_max_body_size)
+        .with_event_log_size(config.event_log_size);
+
+    #[cfg(feature = "sqlite")]
+    let state = match &config.database_path {
+        Some(path) => {
+            log::info!("Persisting subscriptions in {}", path.display());
+            store::RelayStore::open(path)
+                .and_then(|store| state.with_store(store))
+                .map_err(std::io::Error::other)?
+        }
+        None => state,
+    };
+
+    let state = web::Data::new(state);
+    let trusted_proxies = config.trusted_proxies.clone();
 
     let server = HttpServer::new(move || {
+        let trusted_proxies = trusted_proxies.clone();
+
         App::new()
             .app_data(state.clone())
-            .wrap(middleware::Logger::default())
+            .wrap(
+                // The default format's `%a` believes `X-Forwarded-For` from anyone
+                middleware::Logger::new(
+                    r#"%{client}xi "%r" %s %b "%{Referer}i" "%{User-Agent}i" %T"#,
+                )
+                .custom_request_replace("client", move |req| {
+                    forwarded::request_client_ip(req.request(), &trusted_proxies)
+                        .map_or_else(|| "-".to_string(), |ip| ip.to_string())
+                }),
+            )
             .route("/webhook", web::post().to(webhook::handler))
             .route("/ws/{instance_id}", web::get().to(websocket::handler))
             .route("/health", web::get().to(|| async { "OK" }))
-    })
-    .bind((config.host.as_str(), config.port))?;
+            .configure(admin::configure)
+    });
+
+    #[cfg(feature = "tls")]
+    let server = match &config.tls {
+        Some(tls) => {
+            log::info!("Serving TLS with {}", tls.cert_path.display());
+            server.bind_rustls_0_23((config.host.as_str(), config.port), tls.load()?)?
+        }
+        None => server.bind((config.host.as_str(), config.port))?,
+    };
+    #[cfg(not(feature = "tls"))]
+    let server = server.bind((config.host.as_str(), config.port))?;
 
     let addrs = server.addrs();
     let server = server.run();
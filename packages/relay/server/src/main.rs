@@ -2,7 +2,9 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
 #![allow(clippy::multiple_crate_versions)]
 
-use chadreview_relay_server::{ServerConfig, run_server};
+use chadreview_relay_server::{
+    ServerConfig, allowlist::RepoPattern, rate_limit::RateLimit, run_server,
+};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -16,7 +18,79 @@ async fn main() -> std::io::Result<()> {
     let host = std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
 
     let webhook_secret = std::env::var("GITHUB_WEBHOOK_SECRET").ok();
+    let admin_token = std::env::var("RELAY_ADMIN_TOKEN").ok();
+
+    // Comma-separated `instance_id=token` pairs
+    let instance_tokens = std::env::var("RELAY_INSTANCE_TOKENS")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .map(|(instance_id, token)| (instance_id.trim().to_string(), token.trim().to_string()))
+        .collect();
+
+    let config = ServerConfig::new(host, port)
+        .with_webhook_secret(webhook_secret)
+        .with_instance_tokens(instance_tokens)
+        .with_admin_token(admin_token);
+
+    // Comma-separated addresses of reverse proxies in front of the relay
+    let trusted_proxies = std::env::var("RELAY_TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|addr| !addr.is_empty())
+        .map(|addr| addr.parse().expect("Invalid RELAY_TRUSTED_PROXIES address"))
+        .collect::<Vec<std::net::IpAddr>>();
+    let config = config.with_trusted_proxies(trusted_proxies);
+
+    #[cfg(feature = "tls")]
+    let config = config.with_tls(
+        match (
+            std::env::var_os("RELAY_TLS_CERT"),
+            std::env::var_os("RELAY_TLS_KEY"),
+        ) {
+            (Some(cert), Some(key)) => Some(chadreview_relay_server::TlsConfig::new(
+                cert.into(),
+                key.into(),
+            )),
+            (None, None) => None,
+            _ => panic!("RELAY_TLS_CERT and RELAY_TLS_KEY must be set together"),
+        },
+    );
+
+    // Comma-separated `owner/repo` patterns, e.g. `octocat/*,rust-lang/rust`
+    let allowed_repositories = std::env::var("RELAY_ALLOWED_REPOSITORIES")
+        .unwrap_or_default()
+        .split(',')
+        .filter(|pattern| !pattern.trim().is_empty())
+        .map(|pattern| {
+            pattern
+                .parse::<RepoPattern>()
+                .unwrap_or_else(|e| panic!("{e}"))
+        })
+        .collect::<Vec<_>>();
+    let config = config.with_allowed_repositories(allowed_repositories);
+
+    let config = match std::env::var("RELAY_WEBHOOK_RATE_LIMIT") {
+        Ok(requests) => config.with_webhook_rate_limit(Some(RateLimit::per_minute(
+            requests.parse().expect("Invalid RELAY_WEBHOOK_RATE_LIMIT"),
+        ))),
+        Err(_) => config,
+    };
+
+    let config = match std::env::var("RELAY_WEBHOOK_MAX_BODY_SIZE") {
+        Ok(size) => config
+            .with_webhook_max_body_size(size.parse().expect("Invalid RELAY_WEBHOOK_MAX_BODY_SIZE")),
+        Err(_) => config,
+    };
+
+    let config = match std::env::var("RELAY_EVENT_LOG_SIZE") {
+        Ok(size) => config.with_event_log_size(size.parse().expect("Invalid RELAY_EVENT_LOG_SIZE")),
+        Err(_) => config,
+    };
+
+    #[cfg(feature = "sqlite")]
+    let config = config.with_database_path(std::env::var_os("RELAY_DATABASE_PATH").map(Into::into));
 
-    let config = ServerConfig::new(host, port).with_webhook_secret(webhook_secret);
     run_server(config).await
 }
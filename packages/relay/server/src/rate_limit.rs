@@ -0,0 +1,120 @@
+//! Rate limiting of the webhook endpoint.
+//!
+//! Each key (an address or a repository) may make a set number of requests
+//! per window; the window starts with its first request, and requests over
+//! the limit are turned away until it ends.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Keys tracked before windows that ended are cleared out.
+const PRUNE_THRESHOLD: usize = 1_024;
+
+/// How many requests a key may make per window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub per: Duration,
+}
+
+impl RateLimit {
+    #[must_use]
+    pub const fn new(requests: u32, per: Duration) -> Self {
+        Self { requests, per }
+    }
+
+    /// `requests` per minute.
+    #[must_use]
+    pub const fn per_minute(requests: u32) -> Self {
+        Self::new(requests, Duration::from_mins(1))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    started: Instant,
+    requests: u32,
+}
+
+pub struct RateLimiter<K = IpAddr> {
+    limit: RateLimit,
+    windows: Mutex<HashMap<K, Window>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    #[must_use]
+    pub fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count a request from `key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns how long until `key` may make requests again if it is over
+    /// the limit.
+    pub fn check(&self, key: K) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+
+    /// Count a request from `key` made at `now`.
+    ///
+    /// # Errors
+    ///
+    /// Returns how long until `key` may make requests again if it is over
+    /// the limit.
+    pub fn check_at(&self, key: K, now: Instant) -> Result<(), Duration> {
+        let mut windows = self.windows.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if windows.len() >= PRUNE_THRESHOLD {
+            windows.retain(|_, window| now.duration_since(window.started) < self.limit.per);
+        }
+
+        let window = windows.entry(key).or_insert(Window {
+            started: now,
+            requests: 0,
+        });
+        if now.duration_since(window.started) >= self.limit.per {
+            *window = Window {
+                started: now,
+                requests: 0,
+            };
+        }
+
+        if window.requests >= self.limit.requests {
+            let elapsed = now.duration_since(window.started);
+            return Err(self.limit.per.saturating_sub(elapsed));
+        }
+        window.requests += 1;
+        drop(windows);
+
+        Ok(())
+    }
+    /// How long until `key` may make requests again, without counting a
+    /// request, or `None` if it is under the limit.
+    #[must_use]
+    pub fn retry_after(&self, key: &K) -> Option<Duration> {
+        self.retry_after_at(key, Instant::now())
+    }
+
+    /// How long at `now` until `key` may make requests again, without
+    /// counting a request, or `None` if it is under the limit.
+    #[must_use]
+    pub fn retry_after_at(&self, key: &K, now: Instant) -> Option<Duration> {
+        let window = *self
+            .windows
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)?;
+        let elapsed = now.duration_since(window.started);
+
+        (elapsed < self.limit.per && window.requests >= self.limit.requests)
+            .then(|| self.limit.per.saturating_sub(elapsed))
+    }
+}
@@ -1,13 +1,47 @@
-use chadreview_relay_models::PrKey;
-use std::collections::HashMap;
+use chadreview_relay_models::{PrKey, RelayMessage};
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use subtle::ConstantTimeEq;
 use tokio::sync::{RwLock, mpsc};
 
+use crate::allowlist::RepoPattern;
+use crate::rate_limit::{RateLimit, RateLimiter};
+#[cfg(feature = "sqlite")]
+use crate::store::{RelayStore, RelayStoreError};
+
 pub type MessageSender = mpsc::UnboundedSender<String>;
 
+/// Events kept per PR for instances resuming after a disconnect.
+pub const DEFAULT_EVENT_LOG_SIZE: usize = 100;
+
+/// Largest webhook body accepted by default: the most GitHub sends.
+pub const DEFAULT_WEBHOOK_MAX_BODY_SIZE: usize = 25 * 1024 * 1024;
+
 pub struct AppState {
     pub connections: RwLock<HashMap<String, Vec<MessageSender>>>,
     pub subscriptions: RwLock<HashMap<PrKey, Vec<String>>>,
     pub webhook_secret: Option<String>,
+    pub instance_tokens: RwLock<HashMap<String, String>>,
+    /// Bearer token of the admin routes, which are disabled without one.
+    pub admin_token: Option<String>,
+    /// Reverse proxies whose `X-Forwarded-*` headers are believed.
+    pub trusted_proxies: Vec<IpAddr>,
+    /// Repositories whose webhooks are relayed, every one when empty.
+    pub repository_allowlist: Vec<RepoPattern>,
+    /// Limit of verified webhook deliveries per repository (lowercase
+    /// `owner/repo`), unlimited when unset.
+    pub webhook_rate_limiter: Option<RateLimiter<String>>,
+    /// Limit of webhooks failing signature verification per address, set
+    /// along with `webhook_rate_limiter`.
+    pub webhook_failure_limiter: Option<RateLimiter>,
+    /// Largest webhook body accepted, in bytes.
+    pub webhook_max_body_size: usize,
+    /// Latest events of each subscribed PR, oldest first.
+    pub events: RwLock<HashMap<PrKey, VecDeque<RelayMessage>>>,
+    pub event_log_size: usize,
+    #[cfg(feature = "sqlite")]
+    store: Option<RelayStore>,
 }
 
 impl AppState {
@@ -17,6 +51,99 @@ impl AppState {
             connections: RwLock::new(HashMap::new()),
             subscriptions: RwLock::new(HashMap::new()),
             webhook_secret,
+            instance_tokens: RwLock::new(HashMap::new()),
+            admin_token: None,
+            trusted_proxies: vec![],
+            repository_allowlist: vec![],
+            webhook_rate_limiter: None,
+            webhook_failure_limiter: None,
+            webhook_max_body_size: DEFAULT_WEBHOOK_MAX_BODY_SIZE,
+            events: RwLock::new(HashMap::new()),
+            event_log_size: DEFAULT_EVENT_LOG_SIZE,
+            #[cfg(feature = "sqlite")]
+            store: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_event_log_size(mut self, size: usize) -> Self {
+        self.event_log_size = size;
+        self
+    }
+
+    /// Persist subscriptions and event logs in `store`, starting from the
+    /// ones it already holds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored subscriptions or events can't be read.
+    #[cfg(feature = "sqlite")]
+    pub fn with_store(mut self, store: RelayStore) -> Result<Self, RelayStoreError> {
+        *self.subscriptions.get_mut() = store.load_subscriptions()?;
+        *self.events.get_mut() = store.load_events()?;
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    #[must_use]
+    pub fn with_instance_tokens(mut self, tokens: HashMap<String, String>) -> Self {
+        self.instance_tokens.get_mut().extend(tokens);
+        self
+    }
+
+    #[must_use]
+    pub fn with_admin_token(mut self, token: Option<String>) -> Self {
+        self.admin_token = token;
+        self
+    }
+
+    #[must_use]
+    pub fn with_trusted_proxies(mut self, proxies: Vec<IpAddr>) -> Self {
+        self.trusted_proxies = proxies;
+        self
+    }
+
+    #[must_use]
+    pub fn with_repository_allowlist(mut self, patterns: Vec<RepoPattern>) -> Self {
+        self.repository_allowlist = patterns;
+        self
+    }
+
+    #[must_use]
+    pub fn with_webhook_rate_limit(mut self, limit: Option<RateLimit>) -> Self {
+        self.webhook_rate_limiter = limit.map(RateLimiter::new);
+        self.webhook_failure_limiter = limit.map(RateLimiter::new);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_webhook_max_body_size(mut self, size: usize) -> Self {
+        self.webhook_max_body_size = size;
+        self
+    }
+
+    pub async fn set_instance_token(&self, instance_id: String, token: String) {
+        self.instance_tokens
+            .write()
+            .await
+            .insert(instance_id, token);
+    }
+
+    pub async fn revoke_instance_token(&self, instance_id: &str) {
+        self.instance_tokens.write().await.remove(instance_id);
+    }
+
+    /// Whether `instance_id` may connect with `token`. Any instance may when
+    /// no tokens are configured.
+    pub async fn is_authorized(&self, instance_id: &str, token: Option<&str>) -> bool {
+        let tokens = self.instance_tokens.read().await;
+        if tokens.is_empty() {
+            return true;
+        }
+
+        match (tokens.get(instance_id), token) {
+            (Some(expected), Some(token)) => expected.as_bytes().ct_eq(token.as_bytes()).into(),
+            _ => false,
         }
     }
 
@@ -29,29 +156,115 @@ impl AppState {
             .push(sender);
     }
 
+    /// Drop the closed connections of `instance_id`, keeping any others it
+    /// has open. Its subscriptions are kept, so events are logged for it
+    /// until it reconnects and resumes.
     pub async fn remove_connection(&self, instance_id: &str) {
-        self.connections.write().await.remove(instance_id);
-
-        let mut subs = self.subscriptions.write().await;
-        subs.retain(|_, instances| {
-            instances.retain(|id| id != instance_id);
-            !instances.is_empty()
-        });
+        let mut connections = self.connections.write().await;
+        if let Some(senders) = connections.get_mut(instance_id) {
+            senders.retain(|sender| !sender.is_closed());
+            if senders.is_empty() {
+                connections.remove(instance_id);
+            }
+        }
     }
 
-    pub async fn subscribe(&self, instance_id: String, pr_key: PrKey) {
-        self.subscriptions
+    /// Close every connection of `instance_id`, returning how many were
+    /// open.
+    pub async fn disconnect(&self, instance_id: &str) -> usize {
+        // Dropping the senders ends the connections' loops
+        self.connections
             .write()
             .await
-            .entry(pr_key)
-            .or_default()
-            .push(instance_id);
+            .remove(instance_id)
+            .map_or(0, |senders| senders.len())
+    }
+
+    pub async fn subscribe(&self, instance_id: String, pr_key: PrKey) {
+        #[cfg(feature = "sqlite")]
+        if let Some(store) = &self.store
+            && let Err(e) = store.add_subscription(&instance_id, &pr_key)
+        {
+            log::error!("Failed to store subscription: {e}");
+        }
+
+        let mut subs = self.subscriptions.write().await;
+        let instances = subs.entry(pr_key).or_default();
+        if !instances.contains(&instance_id) {
+            instances.push(instance_id);
+        }
+        drop(subs);
     }
 
+    /// Unsubscribe `instance_id` from `pr_key`, dropping the PR's event log
+    /// once nobody is subscribed to it.
     pub async fn unsubscribe(&self, instance_id: &str, pr_key: &PrKey) {
-        if let Some(instances) = self.subscriptions.write().await.get_mut(pr_key) {
-            instances.retain(|id| id != instance_id);
+        #[cfg(feature = "sqlite")]
+        if let Some(store) = &self.store
+            && let Err(e) = store.remove_subscription(instance_id, pr_key)
+        {
+            log::error!("Failed to remove stored subscription: {e}");
+        }
+
+        let mut subs = self.subscriptions.write().await;
+        let Some(instances) = subs.get_mut(pr_key) else {
+            return;
+        };
+        instances.retain(|id| id != instance_id);
+        if !instances.is_empty() {
+            return;
         }
+        subs.remove(pr_key);
+        drop(subs);
+
+        self.events.write().await.remove(pr_key);
+
+        #[cfg(feature = "sqlite")]
+        if let Some(store) = &self.store
+            && let Err(e) = store.remove_events(pr_key)
+        {
+            log::error!("Failed to remove stored events: {e}");
+        }
+    }
+
+    /// Log `message` for instances that aren't connected to receive it,
+    /// keeping the latest [`Self::event_log_size`] events of its PR.
+    pub async fn record_event(&self, message: &RelayMessage) {
+        #[cfg(feature = "sqlite")]
+        if let Some(store) = &self.store
+            && let Err(e) = store.add_event(message, self.event_log_size)
+        {
+            log::error!("Failed to store event: {e}");
+        }
+
+        let mut events = self.events.write().await;
+        let log = events.entry(message.pr_key.clone()).or_default();
+        log.push_back(message.clone());
+        while log.len() > self.event_log_size {
+            log.pop_front();
+        }
+        drop(events);
+    }
+
+    /// The logged events of the PRs `instance_id` is subscribed to that were
+    /// received after `since`, oldest first.
+    pub async fn events_since(&self, instance_id: &str, since: DateTime<Utc>) -> Vec<RelayMessage> {
+        let subs = self.subscriptions.read().await;
+        let events = self.events.read().await;
+
+        let mut missed: Vec<RelayMessage> = subs
+            .iter()
+            .filter(|(_, instances)| instances.iter().any(|id| id == instance_id))
+            .filter_map(|(pr_key, _)| events.get(pr_key))
+            .flatten()
+            .filter(|message| message.received_at > since)
+            .cloned()
+            .collect();
+        drop(events);
+        drop(subs);
+        missed.sort_by_key(|message| message.received_at);
+
+        missed
     }
 
     pub async fn get_subscribed_instances(&self, pr_key: &PrKey) -> Vec<String> {
@@ -62,4 +275,16 @@ impl AppState {
             .cloned()
             .unwrap_or_default()
     }
+
+    pub async fn get_subscribed_prs(&self, owner: &str, repo: &str) -> Vec<PrKey> {
+        self.subscriptions
+            .read()
+            .await
+            .iter()
+            .filter(|(pr_key, instances)| {
+                pr_key.owner == owner && pr_key.repo == repo && !instances.is_empty()
+            })
+            .map(|(pr_key, _)| pr_key.clone())
+            .collect()
+    }
 }
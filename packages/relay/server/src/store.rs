@@ -0,0 +1,219 @@
+//! `SQLite` persistence of subscriptions and event logs.
+//!
+//! Without a store the relay forgets every subscription when it restarts,
+//! and events received while it was down for an instance are lost. With
+//! one, subscriptions are written through as they change and each PR's
+//! recent events are kept, so reconnecting instances can resume from the
+//! last event they saw.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use chadreview_relay_models::{PrKey, RelayMessage};
+use rusqlite::{Connection, params};
+
+/// How long to wait for another process holding the database lock.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+    PRAGMA journal_mode = WAL;
+
+    CREATE TABLE IF NOT EXISTS subscriptions (
+        instance_id TEXT NOT NULL,
+        owner       TEXT NOT NULL,
+        repo        TEXT NOT NULL,
+        number      INTEGER NOT NULL,
+        PRIMARY KEY (instance_id, owner, repo, number)
+    );
+
+    CREATE TABLE IF NOT EXISTS events (
+        id          INTEGER PRIMARY KEY,
+        owner       TEXT NOT NULL,
+        repo        TEXT NOT NULL,
+        number      INTEGER NOT NULL,
+        received_at TEXT NOT NULL,
+        message     TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS events_pr ON events (owner, repo, number, id);
+";
+
+#[derive(Debug, thiserror::Error)]
+pub enum RelayStoreError {
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("Failed to serialize event: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Subscriptions and per-PR event logs kept in a `SQLite` database.
+pub struct RelayStore {
+    conn: Mutex<Connection>,
+}
+
+impl RelayStore {
+    /// Open (or create) the database at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database can't be opened.
+    pub fn open(path: &Path) -> Result<Self, RelayStoreError> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.execute_batch(SCHEMA)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// The subscribed instances of each PR.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database can't be read.
+    pub fn load_subscriptions(&self) -> Result<HashMap<PrKey, Vec<String>>, RelayStoreError> {
+        let conn = self.conn();
+        let rows = conn
+            .prepare(
+                "SELECT instance_id, owner, repo, number FROM subscriptions
+                 ORDER BY owner, repo, number, instance_id",
+            )?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    PrKey {
+                        owner: row.get(1)?,
+                        repo: row.get(2)?,
+                        number: row.get(3)?,
+                    },
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        drop(conn);
+
+        let mut subscriptions: HashMap<PrKey, Vec<String>> = HashMap::new();
+        for (instance_id, pr_key) in rows {
+            subscriptions.entry(pr_key).or_default().push(instance_id);
+        }
+
+        Ok(subscriptions)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the database can't be written.
+    pub fn add_subscription(
+        &self,
+        instance_id: &str,
+        pr_key: &PrKey,
+    ) -> Result<(), RelayStoreError> {
+        self.conn().execute(
+            "INSERT OR IGNORE INTO subscriptions (instance_id, owner, repo, number)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![instance_id, pr_key.owner, pr_key.repo, pr_key.number],
+        )?;
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the database can't be written.
+    pub fn remove_subscription(
+        &self,
+        instance_id: &str,
+        pr_key: &PrKey,
+    ) -> Result<(), RelayStoreError> {
+        self.conn().execute(
+            "DELETE FROM subscriptions
+             WHERE instance_id = ?1 AND owner = ?2 AND repo = ?3 AND number = ?4",
+            params![instance_id, pr_key.owner, pr_key.repo, pr_key.number],
+        )?;
+        Ok(())
+    }
+
+    /// The logged events of each PR, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database can't be read or an event can't be
+    /// deserialized.
+    pub fn load_events(&self) -> Result<HashMap<PrKey, VecDeque<RelayMessage>>, RelayStoreError> {
+        let conn = self.conn();
+        let rows = conn
+            .prepare("SELECT message FROM events ORDER BY id")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        drop(conn);
+
+        let mut events: HashMap<PrKey, VecDeque<RelayMessage>> = HashMap::new();
+        for row in rows {
+            let message: RelayMessage = serde_json::from_str(&row)?;
+            events
+                .entry(message.pr_key.clone())
+                .or_default()
+                .push_back(message);
+        }
+
+        Ok(events)
+    }
+
+    /// Log `message` for its PR, dropping all but the latest `keep` events
+    /// of that PR.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event can't be serialized or the database
+    /// can't be written.
+    pub fn add_event(&self, message: &RelayMessage, keep: usize) -> Result<(), RelayStoreError> {
+        let json = serde_json::to_string(message)?;
+        let pr_key = &message.pr_key;
+        let keep = i64::try_from(keep).unwrap_or(i64::MAX);
+
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO events (owner, repo, number, received_at, message)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                pr_key.owner,
+                pr_key.repo,
+                pr_key.number,
+                message.received_at,
+                json
+            ],
+        )?;
+        tx.execute(
+            "DELETE FROM events
+             WHERE owner = ?1 AND repo = ?2 AND number = ?3 AND id NOT IN (
+                 SELECT id FROM events
+                 WHERE owner = ?1 AND repo = ?2 AND number = ?3
+                 ORDER BY id DESC LIMIT ?4
+             )",
+            params![pr_key.owner, pr_key.repo, pr_key.number, keep],
+        )?;
+        tx.commit()?;
+        drop(conn);
+
+        Ok(())
+    }
+
+    /// Drop the event log of `pr_key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database can't be written.
+    pub fn remove_events(&self, pr_key: &PrKey) -> Result<(), RelayStoreError> {
+        self.conn().execute(
+            "DELETE FROM events WHERE owner = ?1 AND repo = ?2 AND number = ?3",
+            params![pr_key.owner, pr_key.repo, pr_key.number],
+        )?;
+        Ok(())
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -1,27 +1,57 @@
-use std::str::FromStr as _;
+use std::time::Duration;
 
-use actix_web::{HttpRequest, HttpResponse, web};
-use chadreview_relay_models::{PrKey, RelayMessage, ServerMessage, WebhookEvent, WebhookEventType};
-use hmac::{Hmac, Mac};
-use sha2::Sha256;
-use subtle::ConstantTimeEq;
+use actix_web::{HttpRequest, HttpResponse, http::header, web};
+use chadreview_relay_models::webhook;
+use chadreview_relay_models::{PrKey, RelayMessage, ServerMessage, WebhookEvent};
+use chrono::Utc;
 
+use crate::allowlist;
+use crate::forwarded;
 use crate::state::AppState;
 
-type HmacSha256 = Hmac<Sha256>;
-
 #[allow(clippy::future_not_send)]
 pub async fn handler(
     req: HttpRequest,
-    body: web::Bytes,
+    payload: web::Payload,
     state: web::Data<AppState>,
 ) -> HttpResponse {
+    let source_ip = forwarded::request_client_ip(&req, &state.trusted_proxies);
+    let source = source_ip.map_or_else(|| "unknown address".to_string(), |ip| ip.to_string());
+
+    // Addresses that keep failing signature checks are turned away before
+    // their bodies are read. Verified deliveries are limited per repository
+    // below, since GitHub sends every repository's from the same addresses.
+    if let (Some(limiter), Some(ip)) = (&state.webhook_failure_limiter, source_ip)
+        && let Some(retry_after) = limiter.retry_after(&ip)
+    {
+        log::warn!("Rate limited webhook from {source} after failed signature checks");
+        return too_many_requests(retry_after);
+    }
+
+    let body = match payload.to_bytes_limited(state.webhook_max_body_size).await {
+        Ok(Ok(body)) => body,
+        Ok(Err(e)) => {
+            log::error!("Failed to read webhook body from {source}: {e}");
+            return HttpResponse::BadRequest().finish();
+        }
+        Err(_) => {
+            log::warn!(
+                "Rejected webhook from {source}: body over {} bytes",
+                state.webhook_max_body_size
+            );
+            return HttpResponse::PayloadTooLarge().finish();
+        }
+    };
+
     if let Err(e) = verify_github_signature(&req, &body, state.webhook_secret.as_deref()) {
-        log::warn!("Invalid GitHub signature: {e}");
+        log::warn!("Invalid GitHub signature from {source}: {e}");
+        if let (Some(limiter), Some(ip)) = (&state.webhook_failure_limiter, source_ip) {
+            limiter.check(ip).ok();
+        }
         return HttpResponse::Unauthorized().finish();
     }
 
-    let Some(event_type) = req.headers().get("X-GitHub-Event") else {
+    let Some(event_type) = req.headers().get(webhook::EVENT_HEADER) else {
         return HttpResponse::NoContent().finish();
     };
 
@@ -33,9 +63,14 @@ pub async fn handler(
 
     let event_type = event_type.to_string();
 
+    log::debug!(
+        "Received {event_type:?} webhook from {source} over {}",
+        forwarded::request_scheme(&req, &state.trusted_proxies)
+    );
+
     drop(req);
 
-    let event = match parse_webhook_event(&event_type, &body) {
+    let event = match webhook::parse_webhook_event(&event_type, &body) {
         Ok(event) => event,
         Err(e) => {
             log::error!("Failed to parse webhook: {e}");
@@ -44,165 +79,133 @@ pub async fn handler(
     };
 
     let Some(event) = event else {
+        log::debug!("Unsupported webhook event: {event_type:?}");
         return HttpResponse::NoContent().finish();
     };
 
-    let pr_key = extract_pr_key(&event);
+    let repository = event.repository();
+    if !allowlist::is_allowed(
+        &state.repository_allowlist,
+        &repository.owner.login,
+        &repository.name,
+    ) {
+        log::warn!(
+            "Rejected {event_type:?} webhook for {} from {source}: repository not allowed",
+            repository.full_name
+        );
+        return HttpResponse::Forbidden().body("Repository not allowed");
+    }
+
+    if let Some(limiter) = &state.webhook_rate_limiter
+        && let Err(retry_after) = limiter.check(repository.full_name.to_lowercase())
+    {
+        log::warn!(
+            "Rate limited {event_type:?} webhook for {}",
+            repository.full_name
+        );
+        return too_many_requests(retry_after);
+    }
 
-    let relay_msg = RelayMessage {
-        pr_key: pr_key.clone(),
-        event,
-    };
+    if let Err(e) = relay_event(&state, &event).await {
+        log::error!("Failed to serialize message: {e}");
+        return HttpResponse::InternalServerError().finish();
+    }
 
-    let instances = state.get_subscribed_instances(&pr_key).await;
+    HttpResponse::Ok().finish()
+}
 
-    let server_msg = ServerMessage::Webhook(Box::new(relay_msg));
-    let json = match serde_json::to_string(&server_msg) {
-        Ok(json) => json,
-        Err(e) => {
-            log::error!("Failed to serialize message: {e}");
-            return HttpResponse::InternalServerError().finish();
+fn too_many_requests(retry_after: Duration) -> HttpResponse {
+    HttpResponse::TooManyRequests()
+        .insert_header((
+            header::RETRY_AFTER,
+            retry_after.as_millis().div_ceil(1000).to_string(),
+        ))
+        .finish()
+}
+
+/// Send `event` to the instances subscribed to its PRs, logging it for the
+/// ones resuming later.
+async fn relay_event(state: &AppState, event: &WebhookEvent) -> Result<(), serde_json::Error> {
+    let received_at = Utc::now();
+
+    for pr_key in extract_pr_keys(event, state).await {
+        let relay_msg = RelayMessage {
+            pr_key: pr_key.clone(),
+            event: event.clone(),
+            received_at,
+        };
+
+        let instances = state.get_subscribed_instances(&pr_key).await;
+        if !instances.is_empty() {
+            state.record_event(&relay_msg).await;
         }
-    };
 
-    let mut sent_count = 0;
-    for target_instance in instances {
-        if let Some(senders) = state.connections.read().await.get(&target_instance) {
-            for sender in senders {
-                if sender.send(json.clone()).is_ok() {
-                    sent_count += 1;
+        let server_msg = ServerMessage::Webhook(Box::new(relay_msg));
+        let json = serde_json::to_string(&server_msg)?;
+
+        let mut sent_count = 0;
+        for target_instance in instances {
+            if let Some(senders) = state.connections.read().await.get(&target_instance) {
+                for sender in senders {
+                    if sender.send(json.clone()).is_ok() {
+                        sent_count += 1;
+                    }
                 }
             }
         }
-    }
 
-    log::info!(
-        "Relayed webhook for PR {}/{} #{} to {} instance(s)",
-        pr_key.owner,
-        pr_key.repo,
-        pr_key.number,
-        sent_count
-    );
+        log::info!(
+            "Relayed webhook for PR {}/{} #{} to {} instance(s)",
+            pr_key.owner,
+            pr_key.repo,
+            pr_key.number,
+            sent_count
+        );
+    }
 
-    HttpResponse::Ok().finish()
+    Ok(())
 }
 
+/// Verify the signature of a delivery, unless no secret is configured.
 fn verify_github_signature(
     req: &HttpRequest,
     body: &[u8],
     secret: Option<&str>,
 ) -> Result<(), &'static str> {
-    if secret.is_none() {
+    let Some(secret) = secret else {
         log::warn!("GITHUB_WEBHOOK_SECRET not set, skipping signature verification");
         return Ok(());
-    }
+    };
 
     let signature_header = req
         .headers()
-        .get("X-Hub-Signature-256")
-        .and_then(|v| v.to_str().ok())
-        .ok_or("Missing signature header")?;
-
-    let expected_signature = signature_header
-        .strip_prefix("sha256=")
-        .ok_or("Invalid signature format")?;
-
-    let mut mac =
-        HmacSha256::new_from_slice(secret.unwrap().as_bytes()).map_err(|_| "Invalid secret")?;
-    mac.update(body);
-
-    let result = mac.finalize();
-    let code_bytes = result.into_bytes();
-    let computed_signature = hex::encode(code_bytes);
-
-    // Use constant-time comparison to prevent timing attacks
-    if computed_signature
-        .as_bytes()
-        .ct_eq(expected_signature.as_bytes())
-        .into()
-    {
-        Ok(())
-    } else {
-        Err("Signature mismatch")
-    }
-}
-
-fn parse_webhook_event(
-    event_type: &str,
-    body: &[u8],
-) -> Result<Option<WebhookEvent>, serde_json::Error> {
-    let value: serde_json::Value = serde_json::from_slice(body)?;
-
-    log::debug!("Received webhook event: {event_type:?}");
-    let Ok(webhook_type) = WebhookEventType::from_str(event_type) else {
-        log::debug!("Unsupported webhook event: {event_type:?}");
-        return Ok(None);
-    };
+        .get(webhook::SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok());
 
-    Ok(Some(match webhook_type {
-        WebhookEventType::IssueComment => {
-            let action = value["action"]
-                .as_str()
-                .and_then(|s| serde_json::from_str(&format!("\"{s}\"")).ok())
-                .unwrap_or(chadreview_relay_models::CommentAction::Created);
-
-            WebhookEvent::IssueComment {
-                action,
-                comment: serde_json::from_value(value["comment"].clone())?,
-                issue: serde_json::from_value(value["issue"].clone())?,
-                repository: serde_json::from_value(value["repository"].clone())?,
-            }
-        }
-        WebhookEventType::PullRequestReviewComment => {
-            let action = value["action"]
-                .as_str()
-                .and_then(|s| serde_json::from_str(&format!("\"{s}\"")).ok())
-                .unwrap_or(chadreview_relay_models::CommentAction::Created);
-
-            WebhookEvent::PullRequestReviewComment {
-                action,
-                comment: Box::new(serde_json::from_value(value["comment"].clone())?),
-                pull_request: serde_json::from_value(value["pull_request"].clone())?,
-                repository: serde_json::from_value(value["repository"].clone())?,
-            }
-        }
-        WebhookEventType::PullRequest => {
-            let action = value["action"]
-                .as_str()
-                .and_then(|s| serde_json::from_str(&format!("\"{s}\"")).ok())
-                .unwrap_or(chadreview_relay_models::PrAction::Opened);
-
-            WebhookEvent::PullRequest {
-                action,
-                pull_request: serde_json::from_value(value["pull_request"].clone())?,
-                repository: serde_json::from_value(value["repository"].clone())?,
-            }
-        }
-    }))
+    webhook::verify_signature(signature_header, body, secret)
 }
 
-fn extract_pr_key(event: &WebhookEvent) -> PrKey {
-    match event {
-        WebhookEvent::IssueComment {
-            issue, repository, ..
-        } => PrKey {
-            owner: repository.owner.login.clone(),
-            repo: repository.name.clone(),
-            number: issue.number,
-        },
-        WebhookEvent::PullRequestReviewComment {
-            pull_request,
-            repository,
-            ..
-        }
-        | WebhookEvent::PullRequest {
-            pull_request,
-            repository,
-            ..
-        } => PrKey {
+/// The PRs `event` is about.
+///
+/// Check runs name the PRs of their commit, but commit statuses don't, so
+/// statuses go to every subscribed PR of their repository; subscribers tell
+/// whether the status is for their PR by its `sha`.
+async fn extract_pr_keys(event: &WebhookEvent, state: &AppState) -> Vec<PrKey> {
+    let repository = event.repository();
+
+    if let WebhookEvent::Status { .. } = event {
+        return state
+            .get_subscribed_prs(&repository.owner.login, &repository.name)
+            .await;
+    }
+
+    event
+        .pr_numbers()
+        .into_iter()
+        .map(|number| PrKey {
             owner: repository.owner.login.clone(),
             repo: repository.name.clone(),
-            number: pull_request.number,
-        },
-    }
+            number,
+        })
+        .collect()
 }
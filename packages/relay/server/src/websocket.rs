@@ -1,8 +1,10 @@
+use actix_http::ws::CloseCode;
 use actix_web::{HttpRequest, HttpResponse, web};
 use chadreview_relay_models::{ClientMessage, ServerMessage};
 use futures::StreamExt;
 use tokio::sync::mpsc;
 
+use crate::forwarded;
 use crate::state::AppState;
 use crate::ws::{Message, MessageStream, Session};
 
@@ -18,6 +20,16 @@ pub async fn handler(
     state: web::Data<AppState>,
 ) -> Result<HttpResponse, actix_web::Error> {
     let instance_id = path.into_inner();
+    let token = bearer_token(&req);
+
+    if !state.is_authorized(&instance_id, token.as_deref()).await {
+        let source = forwarded::request_client_ip(&req, &state.trusted_proxies)
+            .map_or_else(|| "unknown address".to_string(), |ip| ip.to_string());
+        log::warn!(
+            "Rejected WebSocket connection for instance {instance_id} from {source}: invalid token"
+        );
+        return Ok(HttpResponse::Unauthorized().finish());
+    }
 
     let (response, session, msg_stream) = crate::ws::handle(&req, body)?;
 
@@ -28,6 +40,7 @@ pub async fn handler(
     actix_web::rt::spawn(handle_websocket_connection(
         state.clone(),
         instance_id,
+        token,
         session,
         msg_stream,
         rx,
@@ -36,10 +49,21 @@ pub async fn handler(
     Ok(response)
 }
 
+/// The token of the request's `Authorization: Bearer <token>` header.
+pub(crate) fn bearer_token(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get("Authorization")?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(|token| token.trim().to_string())
+}
+
 #[allow(clippy::future_not_send)]
 async fn handle_websocket_connection(
     state: web::Data<AppState>,
     instance_id: String,
+    token: Option<String>,
     session: Session,
     mut msg_stream: MessageStream,
     mut rx: mpsc::UnboundedReceiver<String>,
@@ -48,7 +72,13 @@ async fn handle_websocket_connection(
 
     loop {
         tokio::select! {
-            Some(text) = rx.recv() => {
+            text = rx.recv() => {
+                // The relay dropped this connection, e.g. from the admin API
+                let Some(text) = text else {
+                    log::info!("Disconnecting instance: {instance_id}");
+                    let _ = session.close(Some(CloseCode::Policy.into())).await;
+                    break;
+                };
                 if session.text(text).await.is_err() {
                     break;
                 }
@@ -60,6 +90,7 @@ async fn handle_websocket_connection(
                             handle_client_message(
                                 &state,
                                 &instance_id,
+                                token.as_deref(),
                                 &session,
                                 client_msg,
                             )
@@ -80,6 +111,7 @@ async fn handle_websocket_connection(
         }
     }
 
+    drop(rx);
     state.remove_connection(&instance_id).await;
     log::info!("Connection closed for instance: {instance_id}");
 }
@@ -87,12 +119,33 @@ async fn handle_websocket_connection(
 async fn handle_client_message(
     state: &AppState,
     instance_id: &str,
+    token: Option<&str>,
     session: &Session,
     msg: ClientMessage,
 ) {
     match msg {
         ClientMessage::Subscribe(sub_msg) => {
             let pr_key = sub_msg.pr_key.clone();
+
+            // Tokens can be revoked while the instance is connected
+            if !state.is_authorized(instance_id, token).await {
+                let response = ServerMessage::Unauthorized {
+                    pr_key: pr_key.clone(),
+                };
+                if let Ok(json) = serde_json::to_string(&response) {
+                    let _ = session.text(json).await;
+                }
+
+                log::warn!(
+                    "Rejected subscription of instance {} to PR {}/{} #{}: invalid token",
+                    instance_id,
+                    pr_key.owner,
+                    pr_key.repo,
+                    pr_key.number
+                );
+                return;
+            }
+
             state
                 .subscribe(instance_id.to_string(), pr_key.clone())
                 .await;
@@ -131,6 +184,23 @@ async fn handle_client_message(
                 pr_key.number
             );
         }
+        ClientMessage::Resume { since } => {
+            let missed = state.events_since(instance_id, since).await;
+
+            log::info!(
+                "Replaying {} event(s) to instance {} since {}",
+                missed.len(),
+                instance_id,
+                since
+            );
+
+            for relay_msg in missed {
+                let response = ServerMessage::Webhook(Box::new(relay_msg));
+                if let Ok(json) = serde_json::to_string(&response) {
+                    let _ = session.text(json).await;
+                }
+            }
+        }
         ClientMessage::Ping => {
             let response = ServerMessage::Pong;
             if let Ok(json) = serde_json::to_string(&response) {
@@ -13,7 +13,7 @@ use actix_codec::{Decoder, Encoder};
 use actix_http::{
     Payload,
     body::BodyStream,
-    ws::{Codec, Frame, HandshakeError},
+    ws::{CloseReason, Codec, Frame, HandshakeError},
 };
 use actix_web::{HttpRequest, HttpResponse, web};
 use bytes::{Bytes, BytesMut};
@@ -59,6 +59,17 @@ impl Session {
             .await
             .map_err(|_| Closed)
     }
+
+    /// Close the session, telling the client why.
+    ///
+    /// # Errors
+    /// Returns `Closed` if the session has already been closed.
+    pub async fn close(self, reason: Option<CloseReason>) -> Result<(), Closed> {
+        self.tx
+            .send(Message::Close(reason))
+            .await
+            .map_err(|_| Closed)
+    }
 }
 
 /// Stream of WebSocket messages from the client.
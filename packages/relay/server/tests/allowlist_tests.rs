@@ -0,0 +1,32 @@
+use chadreview_relay_server::allowlist::{RepoPattern, is_allowed};
+
+fn patterns(patterns: &[&str]) -> Vec<RepoPattern> {
+    patterns
+        .iter()
+        .map(|pattern| pattern.parse().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_repo_pattern_parsing() {
+    for pattern in ["octocat/hello-world", "octocat/*", "*/hello-world", "*/*"] {
+        assert_eq!(pattern.parse::<RepoPattern>().unwrap().to_string(), pattern);
+    }
+    assert_eq!("*".parse::<RepoPattern>().unwrap().to_string(), "*/*");
+
+    for invalid in ["", "octocat", "octocat/", "/repo", "octo*/repo", "a/b/c"] {
+        assert!(invalid.parse::<RepoPattern>().is_err(), "{invalid:?}");
+    }
+}
+
+#[test]
+fn test_is_allowed() {
+    assert!(is_allowed(&[], "anyone", "anything"));
+
+    let allowlist = patterns(&["octocat/*", "rust-lang/rust"]);
+    assert!(is_allowed(&allowlist, "octocat", "hello-world"));
+    assert!(is_allowed(&allowlist, "OctoCat", "Spoon-Knife"));
+    assert!(is_allowed(&allowlist, "rust-lang", "rust"));
+    assert!(!is_allowed(&allowlist, "rust-lang", "cargo"));
+    assert!(!is_allowed(&allowlist, "someone", "hello-world"));
+}
@@ -1,7 +1,12 @@
 mod helpers;
 
 use chadreview_relay_client::RelayClient;
-use chadreview_relay_models::{CommentAction, PrAction, PrKey, WebhookEvent};
+use chadreview_relay_models::{
+    CheckRunAction, CommentAction, PrAction, PrKey, ReviewAction, ReviewThreadAction, WebhookEvent,
+};
+use chadreview_relay_server::ServerConfig;
+use chadreview_relay_server::admin::{ClientInfo, DisconnectResponse, SubscriptionInfo};
+use chadreview_relay_server::rate_limit::RateLimit;
 use chadreview_relay_testing::{WebhookBuilder, WebhookSender};
 use helpers::TestRelayServer;
 use std::sync::{Arc, Mutex};
@@ -15,7 +20,7 @@ async fn test_full_webhook_relay_flow() {
     println!("Test server started on port {}", server.port());
 
     let instance_id = "test-instance-123";
-    let client = RelayClient::connect_async(server.ws_url(), instance_id.to_string())
+    let client = RelayClient::connect_async(server.ws_url(), instance_id.to_string(), None)
         .await
         .unwrap();
 
@@ -76,10 +81,10 @@ async fn test_multiple_clients_receive_same_webhook() {
 
     let server = TestRelayServer::start().await.unwrap();
 
-    let client1 = RelayClient::connect_async(server.ws_url(), "instance-1".to_string())
+    let client1 = RelayClient::connect_async(server.ws_url(), "instance-1".to_string(), None)
         .await
         .unwrap();
-    let client2 = RelayClient::connect_async(server.ws_url(), "instance-2".to_string())
+    let client2 = RelayClient::connect_async(server.ws_url(), "instance-2".to_string(), None)
         .await
         .unwrap();
 
@@ -190,7 +195,7 @@ async fn test_unsubscribe_stops_receiving_webhooks() {
 
     let server = TestRelayServer::start().await.unwrap();
     let instance_id = "test-instance";
-    let client = RelayClient::connect_async(server.ws_url(), instance_id.to_string())
+    let client = RelayClient::connect_async(server.ws_url(), instance_id.to_string(), None)
         .await
         .unwrap();
 
@@ -268,7 +273,7 @@ async fn test_different_event_types() {
 
     let server = TestRelayServer::start().await.unwrap();
     let instance_id = "test-instance";
-    let client = RelayClient::connect_async(server.ws_url(), instance_id.to_string())
+    let client = RelayClient::connect_async(server.ws_url(), instance_id.to_string(), None)
         .await
         .unwrap();
 
@@ -323,3 +328,456 @@ async fn test_different_event_types() {
         _ => panic!("Expected PullRequestReviewComment event"),
     }
 }
+
+#[test_log::test(tokio::test)]
+async fn test_review_and_check_events() {
+    env_logger::try_init().ok();
+
+    let server = TestRelayServer::start().await.unwrap();
+    let client = RelayClient::connect_async(server.ws_url(), "test-instance".to_string(), None)
+        .await
+        .unwrap();
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel(10);
+
+    let pr_key = PrKey {
+        owner: "octocat".to_string(),
+        repo: "hello-world".to_string(),
+        number: 222,
+    };
+
+    client
+        .subscribe(
+            pr_key.clone(),
+            Arc::new(move |event| {
+                let _ = tx.try_send(event);
+            }),
+        )
+        .await
+        .unwrap();
+
+    let builder = WebhookBuilder::new("octocat", "hello-world", 222);
+    let sender = WebhookSender::new(server.http_url());
+
+    sender
+        .send_webhook(
+            "pull_request_review",
+            builder.build_review(ReviewAction::Submitted, "approved", "Ship it"),
+            None,
+        )
+        .await
+        .unwrap();
+    sender
+        .send_webhook(
+            "pull_request_review_thread",
+            builder.build_review_thread(ReviewThreadAction::Resolved, "src/main.rs", 42),
+            None,
+        )
+        .await
+        .unwrap();
+    sender
+        .send_webhook(
+            "check_run",
+            builder.build_check_run(
+                CheckRunAction::Completed,
+                "ci",
+                "completed",
+                Some("success"),
+            ),
+            None,
+        )
+        .await
+        .unwrap();
+    sender
+        .send_webhook("status", builder.build_status("failure", "lint"), None)
+        .await
+        .unwrap();
+
+    let mut next_event = async || {
+        tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("Timeout waiting for webhook")
+            .expect("Failed to receive webhook")
+    };
+
+    match next_event().await {
+        WebhookEvent::PullRequestReview {
+            action,
+            review,
+            pull_request,
+            ..
+        } => {
+            assert_eq!(action, ReviewAction::Submitted);
+            assert_eq!(review.state, "approved");
+            assert_eq!(review.body.as_deref(), Some("Ship it"));
+            assert_eq!(pull_request.number, 222);
+        }
+        event => panic!("Expected PullRequestReview event, got {event:?}"),
+    }
+
+    match next_event().await {
+        WebhookEvent::PullRequestReviewThread { action, thread, .. } => {
+            assert_eq!(action, ReviewThreadAction::Resolved);
+            assert_eq!(thread.comments[0].path, "src/main.rs");
+        }
+        event => panic!("Expected PullRequestReviewThread event, got {event:?}"),
+    }
+
+    match next_event().await {
+        WebhookEvent::CheckRun {
+            action, check_run, ..
+        } => {
+            assert_eq!(action, CheckRunAction::Completed);
+            assert_eq!(check_run.name, "ci");
+            assert_eq!(check_run.conclusion.as_deref(), Some("success"));
+        }
+        event => panic!("Expected CheckRun event, got {event:?}"),
+    }
+
+    match next_event().await {
+        WebhookEvent::Status {
+            sha,
+            state,
+            context,
+            ..
+        } => {
+            assert_eq!(sha, "abc123def456");
+            assert_eq!(state, "failure");
+            assert_eq!(context, "lint");
+        }
+        event => panic!("Expected Status event, got {event:?}"),
+    }
+}
+
+#[test_log::test(tokio::test)]
+async fn test_instance_token_authentication() {
+    env_logger::try_init().ok();
+
+    let config = ServerConfig::new("127.0.0.1".to_string(), 0)
+        .with_instance_token("instance-a".to_string(), "token-a".to_string());
+    let server = TestRelayServer::start_with_config(config).await.unwrap();
+
+    let without_token = tokio::time::timeout(
+        Duration::from_secs(5),
+        RelayClient::connect_async(server.ws_url(), "instance-a".to_string(), None),
+    )
+    .await
+    .expect("Timeout waiting for rejection");
+    assert!(without_token.is_err());
+
+    let wrong_token = tokio::time::timeout(
+        Duration::from_secs(5),
+        RelayClient::connect_async(
+            server.ws_url(),
+            "instance-b".to_string(),
+            Some("token-a".to_string()),
+        ),
+    )
+    .await
+    .expect("Timeout waiting for rejection");
+    assert!(wrong_token.is_err());
+
+    let client = RelayClient::connect_async(
+        server.ws_url(),
+        "instance-a".to_string(),
+        Some("token-a".to_string()),
+    )
+    .await
+    .unwrap();
+
+    let pr_key = PrKey {
+        owner: "octocat".to_string(),
+        repo: "hello-world".to_string(),
+        number: 333,
+    };
+    client.subscribe(pr_key, Arc::new(|_| {})).await.unwrap();
+
+    RelayClient::check_health(server.ws_url(), Duration::from_secs(5))
+        .await
+        .unwrap();
+}
+
+#[test_log::test(tokio::test)]
+async fn test_client_resubscribes_after_server_restart() {
+    env_logger::try_init().ok();
+
+    let server = TestRelayServer::start().await.unwrap();
+    let port = server.port();
+
+    let client =
+        RelayClient::connect_async(server.ws_url(), "test-instance-restart".to_string(), None)
+            .await
+            .unwrap();
+
+    let (reconnect_tx, reconnect_rx) = tokio::sync::oneshot::channel();
+    let reconnect_tx = Arc::new(Mutex::new(Some(reconnect_tx)));
+    client
+        .on_reconnect(Arc::new(move |pr_keys| {
+            if let Some(sender) = reconnect_tx.lock().unwrap().take() {
+                let _ = sender.send(pr_keys);
+            }
+        }))
+        .await;
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let tx = Arc::new(Mutex::new(Some(tx)));
+
+    let pr_key = PrKey {
+        owner: "octocat".to_string(),
+        repo: "hello-world".to_string(),
+        number: 444,
+    };
+    client
+        .subscribe(
+            pr_key.clone(),
+            Arc::new(move |event| {
+                if let Some(sender) = tx.lock().unwrap().take() {
+                    let _ = sender.send(event);
+                }
+            }),
+        )
+        .await
+        .unwrap();
+
+    // A restarted server starts without the subscription
+    server.stop().await;
+    drop(server);
+    let server =
+        TestRelayServer::start_with_config(ServerConfig::new("127.0.0.1".to_string(), port))
+            .await
+            .unwrap();
+
+    let resubscribed = tokio::time::timeout(Duration::from_secs(15), reconnect_rx)
+        .await
+        .expect("Timeout waiting for reconnect")
+        .expect("Failed to receive reconnect");
+    assert_eq!(resubscribed, vec![pr_key]);
+
+    let builder = WebhookBuilder::new("octocat", "hello-world", 444);
+    WebhookSender::new(server.http_url())
+        .send_webhook(
+            "issue_comment",
+            builder.build_issue_comment(CommentAction::Created, "Still here"),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let event = tokio::time::timeout(Duration::from_secs(5), rx)
+        .await
+        .expect("Timeout waiting for webhook")
+        .expect("Failed to receive webhook");
+    assert!(matches!(event, WebhookEvent::IssueComment { .. }));
+}
+
+#[test_log::test(tokio::test)]
+async fn test_admin_routes() {
+    env_logger::try_init().ok();
+
+    let config =
+        ServerConfig::new("127.0.0.1".to_string(), 0).with_admin_token(Some("admin".to_string()));
+    let server = TestRelayServer::start_with_config(config).await.unwrap();
+    let http = reqwest::Client::new();
+
+    let client =
+        RelayClient::connect_async(server.ws_url(), "test-instance-admin".to_string(), None)
+            .await
+            .unwrap();
+    let pr_key = PrKey {
+        owner: "octocat".to_string(),
+        repo: "hello-world".to_string(),
+        number: 555,
+    };
+    client
+        .subscribe(pr_key.clone(), Arc::new(|_| {}))
+        .await
+        .unwrap();
+
+    let unauthorized = http
+        .get(format!("{}/admin/clients", server.http_url()))
+        .bearer_auth("wrong")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(unauthorized.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    let clients: Vec<ClientInfo> = http
+        .get(format!("{}/admin/clients", server.http_url()))
+        .bearer_auth("admin")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        clients,
+        vec![ClientInfo {
+            instance_id: "test-instance-admin".to_string(),
+            connections: 1,
+            subscriptions: vec![pr_key.clone()],
+        }]
+    );
+
+    let subscriptions: Vec<SubscriptionInfo> = http
+        .get(format!("{}/admin/subscriptions", server.http_url()))
+        .bearer_auth("admin")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        subscriptions,
+        vec![SubscriptionInfo {
+            pr_key,
+            instances: vec!["test-instance-admin".to_string()],
+            logged_events: 0,
+        }]
+    );
+
+    let disconnect_url = format!("{}/admin/disconnect/test-instance-admin", server.http_url());
+    let disconnected: DisconnectResponse = http
+        .post(&disconnect_url)
+        .bearer_auth("admin")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(disconnected.disconnected, 1);
+
+    let not_connected = http
+        .post(&disconnect_url)
+        .bearer_auth("admin")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(not_connected.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+#[test_log::test(tokio::test)]
+async fn test_admin_routes_disabled_without_token() {
+    env_logger::try_init().ok();
+
+    let server = TestRelayServer::start().await.unwrap();
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/admin/clients", server.http_url()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+#[test_log::test(tokio::test)]
+async fn test_webhook_rate_and_size_limits() {
+    env_logger::try_init().ok();
+
+    let config = ServerConfig::new("127.0.0.1".to_string(), 0)
+        .with_webhook_rate_limit(Some(RateLimit::per_minute(2)))
+        .with_webhook_max_body_size(1024);
+    let server = TestRelayServer::start_with_config(config).await.unwrap();
+    let sender = WebhookSender::new(server.http_url());
+    let builder = WebhookBuilder::new("octocat", "hello-world", 666);
+
+    let too_large = sender
+        .send_webhook(
+            "issue_comment",
+            builder.build_issue_comment(CommentAction::Created, &"a".repeat(2048)),
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert!(too_large.to_string().contains("413"), "{too_large}");
+
+    for _ in 0..2 {
+        sender
+            .send_webhook(
+                "issue_comment",
+                builder.build_issue_comment(CommentAction::Created, "LGTM!"),
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    let rate_limited = sender
+        .send_webhook(
+            "issue_comment",
+            builder.build_issue_comment(CommentAction::Created, "LGTM!"),
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert!(rate_limited.to_string().contains("429"), "{rate_limited}");
+
+    // Other repositories' deliveries from the same address aren't limited
+    sender
+        .send_webhook(
+            "issue_comment",
+            WebhookBuilder::new("octocat", "spoon-knife", 1)
+                .build_issue_comment(CommentAction::Created, "LGTM!"),
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[test_log::test(tokio::test)]
+async fn test_webhook_failed_signatures_rate_limited() {
+    env_logger::try_init().ok();
+
+    let config = ServerConfig::new("127.0.0.1".to_string(), 0)
+        .with_webhook_secret(Some("secret".to_string()))
+        .with_webhook_rate_limit(Some(RateLimit::per_minute(2)));
+    let server = TestRelayServer::start_with_config(config).await.unwrap();
+    let sender = WebhookSender::new(server.http_url());
+    let payload =
+        WebhookBuilder::new("octocat", "hello-world", 1).build_pull_request(PrAction::Opened);
+
+    for _ in 0..2 {
+        let unauthorized = sender
+            .send_webhook("pull_request", payload.clone(), Some("wrong"))
+            .await
+            .unwrap_err();
+        assert!(unauthorized.to_string().contains("401"), "{unauthorized}");
+    }
+
+    let rate_limited = sender
+        .send_webhook("pull_request", payload, Some("secret"))
+        .await
+        .unwrap_err();
+    assert!(rate_limited.to_string().contains("429"), "{rate_limited}");
+}
+
+#[test_log::test(tokio::test)]
+async fn test_repository_allowlist() {
+    env_logger::try_init().ok();
+
+    let config = ServerConfig::new("127.0.0.1".to_string(), 0)
+        .with_allowed_repository("octocat/*".parse().unwrap());
+    let server = TestRelayServer::start_with_config(config).await.unwrap();
+    let sender = WebhookSender::new(server.http_url());
+
+    sender
+        .send_webhook(
+            "pull_request",
+            WebhookBuilder::new("octocat", "hello-world", 1).build_pull_request(PrAction::Opened),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let rejected = sender
+        .send_webhook(
+            "pull_request",
+            WebhookBuilder::new("someone", "hello-world", 1).build_pull_request(PrAction::Opened),
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert!(rejected.to_string().contains("403"), "{rejected}");
+}
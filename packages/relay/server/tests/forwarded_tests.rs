@@ -0,0 +1,47 @@
+use std::net::IpAddr;
+
+use chadreview_relay_server::forwarded::client_ip;
+
+fn ip(addr: &str) -> IpAddr {
+    addr.parse().unwrap()
+}
+
+#[test]
+fn test_client_ip_ignores_header_from_untrusted_peer() {
+    assert_eq!(
+        client_ip(Some(ip("203.0.113.7")), Some("198.51.100.1"), &[]),
+        Some(ip("203.0.113.7"))
+    );
+    assert_eq!(
+        client_ip(
+            Some(ip("203.0.113.7")),
+            Some("198.51.100.1"),
+            &[ip("10.0.0.1")]
+        ),
+        Some(ip("203.0.113.7"))
+    );
+}
+
+#[test]
+fn test_client_ip_skips_trusted_proxies() {
+    let trusted = [ip("10.0.0.1"), ip("10.0.0.2")];
+
+    // A spoofed address in front of the real client is ignored
+    assert_eq!(
+        client_ip(
+            Some(ip("10.0.0.1")),
+            Some("1.2.3.4, 198.51.100.1, 10.0.0.2"),
+            &trusted
+        ),
+        Some(ip("198.51.100.1"))
+    );
+    assert_eq!(
+        client_ip(Some(ip("10.0.0.1")), None, &trusted),
+        Some(ip("10.0.0.1"))
+    );
+    assert_eq!(
+        client_ip(Some(ip("10.0.0.1")), Some("garbage"), &trusted),
+        Some(ip("10.0.0.1"))
+    );
+    assert_eq!(client_ip(None, Some("198.51.100.1"), &trusted), None);
+}
@@ -19,9 +19,16 @@ impl TestRelayServer {
     ///
     /// Returns an error if the server fails to start or no ports are available
     pub async fn start_with_secret(webhook_secret: Option<String>) -> anyhow::Result<Self> {
-        let config =
-            ServerConfig::new("127.0.0.1".to_string(), 0).with_webhook_secret(webhook_secret);
+        Self::start_with_config(
+            ServerConfig::new("127.0.0.1".to_string(), 0).with_webhook_secret(webhook_secret),
+        )
+        .await
+    }
 
+    /// # Errors
+    ///
+    /// Returns an error if the server fails to start or no ports are available
+    pub async fn start_with_config(config: ServerConfig) -> anyhow::Result<Self> {
         let response = run_server_with_handle(&config)?;
         let port = response
             .addrs
@@ -55,6 +62,11 @@ impl TestRelayServer {
     pub fn port(&self) -> u16 {
         self.port
     }
+
+    /// Stop the server without waiting for open connections to close.
+    pub async fn stop(&self) {
+        self.handle.stop(false).await;
+    }
 }
 
 impl Drop for TestRelayServer {
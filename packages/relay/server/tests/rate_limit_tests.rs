@@ -0,0 +1,49 @@
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use chadreview_relay_server::rate_limit::{RateLimit, RateLimiter};
+
+#[test]
+fn test_rate_limiter_resets_after_window() {
+    let limiter = RateLimiter::new(RateLimit::new(2, Duration::from_secs(10)));
+    let addr: IpAddr = "203.0.113.7".parse().unwrap();
+    let other: IpAddr = "203.0.113.8".parse().unwrap();
+    let start = Instant::now();
+
+    assert_eq!(limiter.check_at(addr, start), Ok(()));
+    assert_eq!(
+        limiter.check_at(addr, start + Duration::from_secs(1)),
+        Ok(())
+    );
+    assert_eq!(
+        limiter.check_at(addr, start + Duration::from_secs(4)),
+        Err(Duration::from_secs(6))
+    );
+    assert_eq!(
+        limiter.check_at(other, start + Duration::from_secs(4)),
+        Ok(())
+    );
+    assert_eq!(
+        limiter.check_at(addr, start + Duration::from_secs(10)),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_rate_limiter_retry_after_does_not_count() {
+    let limiter = RateLimiter::new(RateLimit::new(1, Duration::from_secs(10)));
+    let key = "octocat/hello-world".to_string();
+    let start = Instant::now();
+
+    assert_eq!(limiter.retry_after_at(&key, start), None);
+    assert_eq!(limiter.retry_after_at(&key, start), None);
+    assert_eq!(limiter.check_at(key.clone(), start), Ok(()));
+    assert_eq!(
+        limiter.retry_after_at(&key, start + Duration::from_secs(3)),
+        Some(Duration::from_secs(7))
+    );
+    assert_eq!(
+        limiter.retry_after_at(&key, start + Duration::from_secs(10)),
+        None
+    );
+}
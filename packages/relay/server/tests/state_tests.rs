@@ -0,0 +1,145 @@
+use chadreview_relay_models::{PrKey, RelayMessage, WebhookEvent};
+use chadreview_relay_server::state::AppState;
+use chadreview_relay_testing::WebhookBuilder;
+use chrono::{DateTime, TimeZone, Utc};
+
+fn pr_key(number: u64) -> PrKey {
+    PrKey {
+        owner: "owner".to_string(),
+        repo: "repo".to_string(),
+        number,
+    }
+}
+
+fn at(minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, minute, 0).unwrap()
+}
+
+fn status_message(pr_key: PrKey, context: &str, received_at: DateTime<Utc>) -> RelayMessage {
+    let payload =
+        WebhookBuilder::new("owner", "repo", pr_key.number).build_status("success", context);
+
+    RelayMessage {
+        pr_key,
+        event: WebhookEvent::Status {
+            sha: "abc123def456".to_string(),
+            state: "success".to_string(),
+            context: context.to_string(),
+            description: None,
+            target_url: None,
+            repository: serde_json::from_value(payload["repository"].clone()).unwrap(),
+        },
+        received_at,
+    }
+}
+
+fn contexts(messages: &[RelayMessage]) -> Vec<&str> {
+    messages
+        .iter()
+        .map(|message| match &message.event {
+            WebhookEvent::Status { context, .. } => context.as_str(),
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_events_since_replays_subscribed_prs() {
+    let state = AppState::new(None);
+    state.subscribe("instance".to_string(), pr_key(1)).await;
+    state.subscribe("instance".to_string(), pr_key(2)).await;
+    state.subscribe("other".to_string(), pr_key(3)).await;
+
+    state
+        .record_event(&status_message(pr_key(2), "ci/b", at(3)))
+        .await;
+    state
+        .record_event(&status_message(pr_key(1), "ci/a", at(1)))
+        .await;
+    state
+        .record_event(&status_message(pr_key(1), "ci/c", at(5)))
+        .await;
+    state
+        .record_event(&status_message(pr_key(3), "ci/other", at(4)))
+        .await;
+
+    let missed = state.events_since("instance", at(1)).await;
+    assert_eq!(contexts(&missed), vec!["ci/b", "ci/c"]);
+
+    state.remove_connection("instance").await;
+    assert_eq!(state.events_since("instance", at(0)).await.len(), 3);
+
+    state.unsubscribe("instance", &pr_key(1)).await;
+    assert_eq!(
+        contexts(&state.events_since("instance", at(0)).await),
+        vec!["ci/b"]
+    );
+    assert!(!state.events.read().await.contains_key(&pr_key(1)));
+}
+
+#[tokio::test]
+async fn test_event_log_is_bounded() {
+    let state = AppState::new(None).with_event_log_size(2);
+    state.subscribe("instance".to_string(), pr_key(1)).await;
+    state.subscribe("instance".to_string(), pr_key(1)).await;
+
+    for (minute, context) in [(1, "ci/a"), (2, "ci/b"), (3, "ci/c")] {
+        state
+            .record_event(&status_message(pr_key(1), context, at(minute)))
+            .await;
+    }
+
+    assert_eq!(
+        state.get_subscribed_instances(&pr_key(1)).await,
+        vec!["instance".to_string()]
+    );
+    assert_eq!(
+        contexts(&state.events_since("instance", at(0)).await),
+        vec!["ci/b", "ci/c"]
+    );
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn test_store_persists_subscriptions_and_events() {
+    use chadreview_relay_server::store::RelayStore;
+
+    let path =
+        std::env::temp_dir().join(format!("chadreview-relay-test-{}.db", std::process::id()));
+    let open = || {
+        AppState::new(None)
+            .with_event_log_size(2)
+            .with_store(RelayStore::open(&path).unwrap())
+            .unwrap()
+    };
+
+    let state = open();
+    state.subscribe("instance".to_string(), pr_key(1)).await;
+    state.subscribe("instance".to_string(), pr_key(2)).await;
+    for (minute, context) in [(1, "ci/a"), (2, "ci/b"), (3, "ci/c")] {
+        state
+            .record_event(&status_message(pr_key(1), context, at(minute)))
+            .await;
+    }
+    state
+        .record_event(&status_message(pr_key(2), "ci/d", at(4)))
+        .await;
+    state.unsubscribe("instance", &pr_key(2)).await;
+    drop(state);
+
+    let state = open();
+    assert_eq!(
+        state.get_subscribed_instances(&pr_key(1)).await,
+        vec!["instance".to_string()]
+    );
+    assert!(state.get_subscribed_instances(&pr_key(2)).await.is_empty());
+    assert_eq!(
+        contexts(&state.events_since("instance", at(0)).await),
+        vec!["ci/b", "ci/c"]
+    );
+    drop(state);
+
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+}
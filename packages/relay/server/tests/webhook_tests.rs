@@ -160,6 +160,7 @@ fn test_relay_message_serialization() {
     use chrono::{TimeZone, Utc};
 
     let relay_msg = RelayMessage {
+        received_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
         pr_key: PrKey {
             owner: "owner".to_string(),
             repo: "repo".to_string(),
@@ -205,7 +206,10 @@ fn test_relay_message_serialization() {
 
 #[test]
 fn test_server_message_webhook_with_boxed_relay_message() {
+    use chrono::{TimeZone, Utc};
+
     let server_msg = ServerMessage::Webhook(Box::new(RelayMessage {
+        received_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
         pr_key: PrKey {
             owner: "o".to_string(),
             repo: "r".to_string(),
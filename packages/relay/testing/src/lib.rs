@@ -109,6 +109,99 @@ impl WebhookBuilder {
         })
     }
 
+    #[must_use]
+    pub fn build_review(
+        &self,
+        action: chadreview_relay_models::ReviewAction,
+        state: &str,
+        body: &str,
+    ) -> serde_json::Value {
+        json!({
+            "action": action,
+            "review": {
+                "id": 1_234_567_892_u64,
+                "body": body,
+                "state": state,
+                "commit_id": "abc123def456",
+                "user": self.build_user(),
+                "submitted_at": self.timestamp,
+            },
+            "pull_request": self.build_pull_request_data(),
+            "repository": self.build_repository(),
+        })
+    }
+
+    #[must_use]
+    pub fn build_review_thread(
+        &self,
+        action: chadreview_relay_models::ReviewThreadAction,
+        path: &str,
+        line: u64,
+    ) -> serde_json::Value {
+        let comment = self.build_review_comment(
+            chadreview_relay_models::CommentAction::Created,
+            "Thread comment",
+            path,
+            line,
+        );
+
+        json!({
+            "action": action,
+            "thread": {
+                "node_id": "PRRT_kwDOAAABBB",
+                "comments": [comment["comment"]],
+            },
+            "pull_request": self.build_pull_request_data(),
+            "repository": self.build_repository(),
+        })
+    }
+
+    #[must_use]
+    pub fn build_check_run(
+        &self,
+        action: chadreview_relay_models::CheckRunAction,
+        name: &str,
+        status: &str,
+        conclusion: Option<&str>,
+    ) -> serde_json::Value {
+        let pull_request = self.build_pull_request_data();
+
+        json!({
+            "action": action,
+            "check_run": {
+                "id": 1_234_567_893_u64,
+                "name": name,
+                "head_sha": "abc123def456",
+                "status": status,
+                "conclusion": conclusion,
+                "html_url": format!(
+                    "https://github.com/{}/{}/runs/1234567893",
+                    self.owner, self.repo
+                ),
+                "started_at": self.timestamp,
+                "completed_at": conclusion.map(|_| self.timestamp),
+                "pull_requests": [{
+                    "number": pull_request["number"],
+                    "head": pull_request["head"],
+                    "base": pull_request["base"],
+                }],
+            },
+            "repository": self.build_repository(),
+        })
+    }
+
+    #[must_use]
+    pub fn build_status(&self, state: &str, context: &str) -> serde_json::Value {
+        json!({
+            "sha": "abc123def456",
+            "state": state,
+            "context": context,
+            "description": format!("{context} is {state}"),
+            "target_url": format!("https://ci.example.com/{}/{}", self.owner, self.repo),
+            "repository": self.build_repository(),
+        })
+    }
+
     fn build_user(&self) -> serde_json::Value {
         json!({
             "id": self.user_id,
@@ -4,9 +4,17 @@
 
 use syntect::easy::HighlightLines;
 use syntect::highlighting::{Style, ThemeSet};
-use syntect::parsing::SyntaxSet;
+use syntect::html::{ClassStyle, css_for_theme_with_class_style, line_tokens_to_classed_spans};
+use syntect::parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet};
 use syntect::util::LinesWithEndings;
 
+/// Prefix applied to every CSS class emitted by [`SyntaxHighlighter::highlight_to_html`].
+pub const CLASS_PREFIX: &str = "syn-";
+
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed {
+    prefix: CLASS_PREFIX,
+};
+
 pub struct SyntaxHighlighter {
     syntax_set: SyntaxSet,
     theme_set: ThemeSet,
@@ -28,12 +36,7 @@ impl SyntaxHighlighter {
         filename: &str,
         content: &str,
     ) -> Result<Vec<(Style, String)>, String> {
-        let syntax = self
-            .syntax_set
-            .find_syntax_for_file(filename)
-            .ok()
-            .flatten()
-            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+        let syntax = self.find_syntax(filename);
 
         let theme = &self.theme_set.themes["base16-ocean.dark"];
         let mut highlighter = HighlightLines::new(syntax, theme);
@@ -52,6 +55,103 @@ impl SyntaxHighlighter {
 
         Ok(result)
     }
+
+    /// Highlight `content` as HTML using class-based spans instead of inline styles.
+    ///
+    /// Every span carries space-separated scope classes prefixed with
+    /// [`CLASS_PREFIX`] (e.g. `syn-keyword syn-control syn-rust`), so the
+    /// colors are supplied by a stylesheet from [`Self::theme_css`] and the
+    /// theme can be switched without re-highlighting. Lines are joined with
+    /// `\n` and all open spans are closed at the end of the output.
+    ///
+    /// # Errors
+    /// Returns an error if highlighting fails
+    pub fn highlight_to_html(&self, filename: &str, content: &str) -> Result<String, String> {
+        self.classed_html(self.find_syntax(filename), content)
+    }
+
+    /// Highlight `content` as class-based HTML using a language token such as
+    /// `rust` or `py` (e.g. the info string of a markdown code fence) instead
+    /// of a filename. Unknown languages fall back to plain text.
+    ///
+    /// # Errors
+    /// Returns an error if highlighting fails
+    pub fn highlight_language_to_html(
+        &self,
+        language: &str,
+        content: &str,
+    ) -> Result<String, String> {
+        let syntax = self
+            .syntax_set
+            .find_syntax_by_token(language)
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+
+        self.classed_html(syntax, content)
+    }
+
+    /// Whether a syntax definition exists for the given language token.
+    #[must_use]
+    pub fn supports_language(&self, language: &str) -> bool {
+        self.syntax_set.find_syntax_by_token(language).is_some()
+    }
+
+    /// Generate the stylesheet for `theme_name` matching the classes emitted by
+    /// [`Self::highlight_to_html`].
+    ///
+    /// # Errors
+    /// Returns an error if the theme does not exist or CSS generation fails
+    pub fn theme_css(&self, theme_name: &str) -> Result<String, String> {
+        let theme = self
+            .theme_set
+            .themes
+            .get(theme_name)
+            .ok_or_else(|| format!("Unknown theme: {theme_name}"))?;
+
+        css_for_theme_with_class_style(theme, CLASS_STYLE)
+            .map_err(|e| format!("Failed to generate theme CSS: {e}"))
+    }
+
+    /// Names of the themes available to [`Self::theme_css`].
+    pub fn theme_names(&self) -> impl Iterator<Item = &str> {
+        self.theme_set.themes.keys().map(String::as_str)
+    }
+
+    fn find_syntax(&self, filename: &str) -> &SyntaxReference {
+        self.syntax_set
+            .find_syntax_for_file(filename)
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text())
+    }
+
+    fn classed_html(&self, syntax: &SyntaxReference, content: &str) -> Result<String, String> {
+        let mut parse_state = ParseState::new(syntax);
+        let mut scope_stack = ScopeStack::new();
+        let mut open_spans: isize = 0;
+        let mut html = String::with_capacity(content.len() * 2);
+
+        for (index, line) in content.lines().enumerate() {
+            if index > 0 {
+                html.push('\n');
+            }
+
+            let ops = parse_state
+                .parse_line(line, &self.syntax_set)
+                .map_err(|e| format!("Failed to parse line: {e}"))?;
+            let (formatted, delta) =
+                line_tokens_to_classed_spans(line, &ops, CLASS_STYLE, &mut scope_stack)
+                    .map_err(|e| format!("Failed to highlight line: {e}"))?;
+
+            html.push_str(&formatted);
+            open_spans += delta;
+        }
+
+        for _ in 0..open_spans {
+            html.push_str("</span>");
+        }
+
+        Ok(html)
+    }
 }
 
 impl Default for SyntaxHighlighter {
@@ -103,4 +203,44 @@ mod tests {
         let ranges = result.unwrap();
         assert!(!ranges.is_empty());
     }
+
+    #[test]
+    fn test_highlight_to_html_uses_classes() {
+        let h = SyntaxHighlighter::new();
+        let html = h.highlight_to_html("test.rs", "fn main() {}\n").unwrap();
+        assert!(html.contains("class=\"syn-"));
+        assert!(!html.contains("style="));
+        assert!(html.contains("main"));
+    }
+
+    #[test]
+    fn test_highlight_to_html_balances_spans() {
+        let h = SyntaxHighlighter::new();
+        let content = "/* open\ncomment */\nlet x = \"<tag>\";\n";
+        let html = h.highlight_to_html("test.js", content).unwrap();
+        assert_eq!(
+            html.matches("<span").count(),
+            html.matches("</span>").count()
+        );
+        assert!(html.contains("&lt;tag&gt;"));
+    }
+
+    #[test]
+    fn test_highlight_language_to_html() {
+        let h = SyntaxHighlighter::new();
+        assert!(h.supports_language("rust"));
+        assert!(!h.supports_language("definitely-not-a-language"));
+
+        let html = h.highlight_language_to_html("rust", "let x = 1;").unwrap();
+        assert!(html.contains("class=\"syn-"));
+    }
+
+    #[test]
+    fn test_theme_css() {
+        let h = SyntaxHighlighter::new();
+        let css = h.theme_css("base16-ocean.dark").unwrap();
+        assert!(css.contains(".syn-"));
+        assert!(h.theme_css("no-such-theme").is_err());
+        assert!(h.theme_names().any(|name| name == "base16-ocean.dark"));
+    }
 }
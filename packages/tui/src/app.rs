@@ -0,0 +1,500 @@
+//! Terminal frontend state and key handling.
+
+use chadreview_local_comment_models::{
+    AiExecutionStatus, LineNumber, LocalComment, LocalCommentType, ThreadState,
+};
+use chadreview_pr_models::{DiffFile, DiffLine};
+use ratatui::crossterm::event::KeyCode;
+use switchy::uuid::Uuid;
+
+use crate::ReviewSnapshot;
+
+/// Number of lines scrolled by page up/down in the diff pane.
+const PAGE_SIZE: usize = 20;
+
+/// The pane receiving keyboard input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Focus {
+    #[default]
+    Files,
+    Diff,
+    Threads,
+}
+
+impl Focus {
+    const fn next(self) -> Self {
+        match self {
+            Self::Files => Self::Diff,
+            Self::Diff => Self::Threads,
+            Self::Threads => Self::Files,
+        }
+    }
+
+    const fn previous(self) -> Self {
+        match self {
+            Self::Files => Self::Threads,
+            Self::Diff => Self::Files,
+            Self::Threads => Self::Diff,
+        }
+    }
+}
+
+/// Side effect requested by a key press.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    None,
+    Quit,
+    Reload,
+    SetFileViewed { path: String, viewed: bool },
+    SetThreadState { thread_id: Uuid, state: ThreadState },
+}
+
+/// State of the terminal frontend.
+#[derive(Debug)]
+pub struct App {
+    snapshot: ReviewSnapshot,
+    focus: Focus,
+    selected_file: usize,
+    diff_scroll: usize,
+    selected_thread: usize,
+    status: Option<String>,
+}
+
+impl App {
+    #[must_use]
+    pub fn new(snapshot: ReviewSnapshot) -> Self {
+        Self {
+            snapshot,
+            focus: Focus::default(),
+            selected_file: 0,
+            diff_scroll: 0,
+            selected_thread: 0,
+            status: None,
+        }
+    }
+
+    /// Replace the review data, keeping the selected file if it still exists.
+    pub fn replace_snapshot(&mut self, snapshot: ReviewSnapshot) {
+        let selected_path = self.current_file().map(|file| file.filename.clone());
+        self.snapshot = snapshot;
+
+        if let Some(index) = selected_path.and_then(|path| {
+            self.snapshot
+                .diffs
+                .iter()
+                .position(|file| file.filename == path)
+        }) {
+            self.selected_file = index;
+        } else {
+            self.selected_file = 0;
+            self.diff_scroll = 0;
+        }
+
+        self.diff_scroll = self.diff_scroll.min(self.max_diff_scroll());
+        self.selected_thread = self
+            .selected_thread
+            .min(self.visible_threads().len().saturating_sub(1));
+    }
+
+    #[must_use]
+    pub const fn snapshot(&self) -> &ReviewSnapshot {
+        &self.snapshot
+    }
+
+    #[must_use]
+    pub const fn focus(&self) -> Focus {
+        self.focus
+    }
+
+    #[must_use]
+    pub const fn selected_file(&self) -> usize {
+        self.selected_file
+    }
+
+    #[must_use]
+    pub const fn diff_scroll(&self) -> usize {
+        self.diff_scroll
+    }
+
+    #[must_use]
+    pub const fn selected_thread(&self) -> usize {
+        self.selected_thread
+    }
+
+    #[must_use]
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = Some(status.into());
+    }
+
+    #[must_use]
+    pub fn current_file(&self) -> Option<&DiffFile> {
+        self.snapshot.diffs.get(self.selected_file)
+    }
+
+    #[must_use]
+    pub fn is_viewed(&self, path: &str) -> bool {
+        self.snapshot.viewed_paths.contains(path)
+    }
+
+    /// Number of comment threads on a file.
+    #[must_use]
+    pub fn thread_count(&self, path: &str) -> usize {
+        self.snapshot
+            .threads
+            .iter()
+            .filter(|thread| thread_path(thread) == Some(path))
+            .count()
+    }
+
+    /// Threads shown in the thread pane: the current file's threads
+    /// followed by general comments.
+    #[must_use]
+    pub fn visible_threads(&self) -> Vec<&LocalComment> {
+        let path = self.current_file().map(|file| file.filename.as_str());
+
+        let file_threads = self
+            .snapshot
+            .threads
+            .iter()
+            .filter(|thread| path.is_some() && thread_path(thread) == path);
+        let general_threads = self
+            .snapshot
+            .threads
+            .iter()
+            .filter(|thread| matches!(thread.comment_type, LocalCommentType::General));
+
+        file_threads.chain(general_threads).collect()
+    }
+
+    /// Whether a line of the current file has a comment thread.
+    #[must_use]
+    pub fn line_has_thread(&self, line: &DiffLine) -> bool {
+        let Some(file) = self.current_file() else {
+            return false;
+        };
+
+        self.snapshot
+            .threads
+            .iter()
+            .any(|thread| match &thread.comment_type {
+                LocalCommentType::LineLevelComment { path, line: number }
+                    if *path == file.filename =>
+                {
+                    match *number {
+                        LineNumber::New { line: n } => line.new_line_number == Some(n),
+                        LineNumber::Old { line: n } => line.old_line_number == Some(n),
+                    }
+                }
+                _ => false,
+            })
+    }
+
+    /// Whether any AI action is pending or running.
+    #[must_use]
+    pub fn has_running_ai(&self) -> bool {
+        self.snapshot.threads.iter().any(has_running_ai)
+    }
+
+    /// Handle a key press, returning the side effect to perform.
+    pub fn handle_key(&mut self, key: KeyCode) -> Action {
+        self.status = None;
+
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+            KeyCode::Char('R') => Action::Reload,
+            KeyCode::Tab => {
+                self.focus = self.focus.next();
+                Action::None
+            }
+            KeyCode::BackTab => {
+                self.focus = self.focus.previous();
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.move_selection(1);
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.move_selection(-1);
+                Action::None
+            }
+            KeyCode::PageDown => {
+                self.scroll_diff(PAGE_SIZE.cast_signed());
+                Action::None
+            }
+            KeyCode::PageUp => {
+                self.scroll_diff(-PAGE_SIZE.cast_signed());
+                Action::None
+            }
+            KeyCode::Char('v') => {
+                self.current_file()
+                    .map_or(Action::None, |file| Action::SetFileViewed {
+                        path: file.filename.clone(),
+                        viewed: !self.is_viewed(&file.filename),
+                    })
+            }
+            KeyCode::Char('r') => self.thread_state_action(|state| match state {
+                ThreadState::Resolved => ThreadState::Open,
+                ThreadState::Open | ThreadState::SavedForLater => ThreadState::Resolved,
+            }),
+            KeyCode::Char('s') => self.thread_state_action(|state| match state {
+                ThreadState::SavedForLater => ThreadState::Open,
+                ThreadState::Open | ThreadState::Resolved => ThreadState::SavedForLater,
+            }),
+            _ => Action::None,
+        }
+    }
+
+    fn thread_state_action(&self, next: impl Fn(ThreadState) -> ThreadState) -> Action {
+        self.visible_threads()
+            .get(self.selected_thread)
+            .map_or(Action::None, |thread| Action::SetThreadState {
+                thread_id: thread.id,
+                state: next(thread.state),
+            })
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        match self.focus {
+            Focus::Files => {
+                let selected = step(self.selected_file, delta, self.snapshot.diffs.len());
+                if selected != self.selected_file {
+                    self.selected_file = selected;
+                    self.diff_scroll = 0;
+                    self.selected_thread = 0;
+                }
+            }
+            Focus::Diff => self.scroll_diff(delta),
+            Focus::Threads => {
+                self.selected_thread =
+                    step(self.selected_thread, delta, self.visible_threads().len());
+            }
+        }
+    }
+
+    fn scroll_diff(&mut self, delta: isize) {
+        self.diff_scroll = self
+            .diff_scroll
+            .saturating_add_signed(delta)
+            .min(self.max_diff_scroll());
+    }
+
+    fn max_diff_scroll(&self) -> usize {
+        self.current_file()
+            .map_or(0, |file| {
+                file.hunks
+                    .iter()
+                    .map(|hunk| hunk.lines.len() + 1)
+                    .sum::<usize>()
+            })
+            .saturating_sub(1)
+    }
+}
+
+/// Move an index by `delta`, clamped to `0..len`.
+const fn step(index: usize, delta: isize, len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    let index = index.saturating_add_signed(delta);
+    if index >= len { len - 1 } else { index }
+}
+
+/// The file a thread is attached to.
+fn thread_path(thread: &LocalComment) -> Option<&str> {
+    match &thread.comment_type {
+        LocalCommentType::FileLevelComment { path }
+        | LocalCommentType::LineLevelComment { path, .. } => Some(path),
+        LocalCommentType::General | LocalCommentType::Reply { .. } => None,
+    }
+}
+
+fn has_running_ai(comment: &LocalComment) -> bool {
+    matches!(
+        comment.ai_status,
+        Some(AiExecutionStatus::Pending | AiExecutionStatus::Running { .. })
+    ) || comment.replies.iter().any(has_running_ai)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use chadreview_local_comment_models::LocalUser;
+    use chadreview_local_diff_models::{DiffSpec, LocalDiffInfo};
+    use chadreview_pr_models::{DiffHunk, FileStatus, LineType};
+
+    use super::*;
+
+    fn line(old: Option<u64>, new: Option<u64>) -> DiffLine {
+        DiffLine {
+            line_type: LineType::Context,
+            old_line_number: old,
+            new_line_number: new,
+            content: "line".to_string(),
+            highlighted_html: "line".to_string(),
+        }
+    }
+
+    fn file(name: &str, lines: usize) -> DiffFile {
+        DiffFile {
+            filename: name.to_string(),
+            status: FileStatus::Modified,
+            additions: 0,
+            deletions: 0,
+            mode_change: None,
+            hunks: vec![DiffHunk {
+                old_start: 1,
+                old_lines: lines as u64,
+                new_start: 1,
+                new_lines: lines as u64,
+                lines: (1..=lines as u64).map(|n| line(Some(n), Some(n))).collect(),
+            }],
+            decoded_lossy: false,
+            submodule: None,
+        }
+    }
+
+    fn thread(comment_type: LocalCommentType) -> LocalComment {
+        LocalComment::new(LocalUser::default(), "body".to_string(), comment_type)
+    }
+
+    fn snapshot(diffs: Vec<DiffFile>, threads: Vec<LocalComment>) -> ReviewSnapshot {
+        ReviewSnapshot {
+            info: LocalDiffInfo {
+                repo_path: "/repo".to_string(),
+                spec: DiffSpec::default(),
+                description: "Working tree".to_string(),
+                commits: vec![],
+                base_ref: None,
+                head_ref: None,
+                total_additions: 0,
+                total_deletions: 0,
+                files_changed: diffs.len(),
+                is_dirty: true,
+            },
+            diffs,
+            threads,
+            viewed_paths: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn test_file_navigation_resets_diff_scroll() {
+        let mut app = App::new(snapshot(vec![file("a.rs", 30), file("b.rs", 5)], vec![]));
+
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::PageDown);
+        assert_eq!(app.diff_scroll(), 20);
+
+        app.handle_key(KeyCode::BackTab);
+        app.handle_key(KeyCode::Char('j'));
+        assert_eq!(app.selected_file(), 1);
+        assert_eq!(app.diff_scroll(), 0);
+
+        // Clamped at the last file
+        app.handle_key(KeyCode::Down);
+        assert_eq!(app.selected_file(), 1);
+    }
+
+    #[test]
+    fn test_diff_scroll_is_clamped() {
+        let mut app = App::new(snapshot(vec![file("a.rs", 5)], vec![]));
+        app.handle_key(KeyCode::Tab);
+        app.handle_key(KeyCode::PageDown);
+
+        // 1 hunk header + 5 lines
+        assert_eq!(app.diff_scroll(), 5);
+        app.handle_key(KeyCode::PageUp);
+        assert_eq!(app.diff_scroll(), 0);
+    }
+
+    #[test]
+    fn test_visible_threads_and_line_markers() {
+        let line_thread = thread(LocalCommentType::LineLevelComment {
+            path: "a.rs".to_string(),
+            line: LineNumber::New { line: 2 },
+        });
+        let other_file_thread = thread(LocalCommentType::FileLevelComment {
+            path: "b.rs".to_string(),
+        });
+        let general_thread = thread(LocalCommentType::General);
+        let app = App::new(snapshot(
+            vec![file("a.rs", 3), file("b.rs", 3)],
+            vec![
+                general_thread.clone(),
+                other_file_thread,
+                line_thread.clone(),
+            ],
+        ));
+
+        let visible: Vec<_> = app.visible_threads().iter().map(|t| t.id).collect();
+        assert_eq!(visible, vec![line_thread.id, general_thread.id]);
+
+        assert!(app.line_has_thread(&line(Some(2), Some(2))));
+        assert!(!app.line_has_thread(&line(Some(3), Some(3))));
+        assert_eq!(app.thread_count("b.rs"), 1);
+    }
+
+    #[test]
+    fn test_toggle_actions() {
+        let general = thread(LocalCommentType::General);
+        let mut app = App::new(snapshot(vec![file("a.rs", 1)], vec![general.clone()]));
+
+        assert_eq!(
+            app.handle_key(KeyCode::Char('v')),
+            Action::SetFileViewed {
+                path: "a.rs".to_string(),
+                viewed: true,
+            }
+        );
+        assert_eq!(
+            app.handle_key(KeyCode::Char('r')),
+            Action::SetThreadState {
+                thread_id: general.id,
+                state: ThreadState::Resolved,
+            }
+        );
+        assert_eq!(
+            app.handle_key(KeyCode::Char('s')),
+            Action::SetThreadState {
+                thread_id: general.id,
+                state: ThreadState::SavedForLater,
+            }
+        );
+        assert_eq!(app.handle_key(KeyCode::Char('q')), Action::Quit);
+    }
+
+    #[test]
+    fn test_replace_snapshot_keeps_selected_file() {
+        let mut app = App::new(snapshot(vec![file("a.rs", 1), file("b.rs", 1)], vec![]));
+        app.handle_key(KeyCode::Down);
+
+        app.replace_snapshot(snapshot(
+            vec![file("new.rs", 1), file("a.rs", 1), file("b.rs", 1)],
+            vec![],
+        ));
+        assert_eq!(app.current_file().unwrap().filename, "b.rs");
+
+        app.replace_snapshot(snapshot(vec![file("a.rs", 1)], vec![]));
+        assert_eq!(app.selected_file(), 0);
+    }
+
+    #[test]
+    fn test_has_running_ai_checks_replies() {
+        let mut root = thread(LocalCommentType::General);
+        let mut reply = thread(LocalCommentType::Reply {
+            root_comment_id: root.id,
+            in_reply_to: root.id,
+        });
+        reply.ai_status = Some(AiExecutionStatus::Pending);
+        root.replies.push(reply);
+
+        let app = App::new(snapshot(vec![], vec![root]));
+        assert!(app.has_running_ai());
+    }
+}
@@ -0,0 +1,146 @@
+#![cfg_attr(feature = "fail-on-warnings", deny(warnings))]
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![allow(clippy::multiple_crate_versions)]
+
+//! Terminal frontend for `ChadReview` local reviews.
+//!
+//! Renders a local diff, its comment threads and AI execution status in a
+//! ratatui interface. Data access is abstracted behind [`ReviewSource`] so
+//! the terminal frontend shares loading and mutation logic with the web
+//! routes.
+
+mod app;
+mod view;
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use chadreview_local_comment_models::{LocalComment, ThreadState};
+use chadreview_local_diff_models::LocalDiffInfo;
+use chadreview_pr_models::DiffFile;
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use switchy::uuid::Uuid;
+
+pub use app::{Action, App, Focus};
+
+/// How long to wait for input before redrawing.
+const TICK_RATE: Duration = Duration::from_millis(250);
+/// How often to reload while AI actions are running.
+const AI_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Error type for the terminal frontend.
+#[derive(Debug, thiserror::Error)]
+pub enum TuiError {
+    /// Terminal I/O error.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The review data could not be loaded.
+    #[error("Failed to load review: {0}")]
+    Source(String),
+}
+
+/// A point-in-time view of a local review.
+#[derive(Debug, Clone)]
+pub struct ReviewSnapshot {
+    /// Description of what is being diffed.
+    pub info: LocalDiffInfo,
+    /// Changed files.
+    pub diffs: Vec<DiffFile>,
+    /// All comment threads in the repository.
+    pub threads: Vec<LocalComment>,
+    /// Paths of files marked as viewed.
+    pub viewed_paths: HashSet<String>,
+}
+
+/// Provides review data and applies reviewer actions.
+pub trait ReviewSource {
+    /// Error returned by source operations.
+    type Error: std::fmt::Display;
+
+    /// Load the current state of the review.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the diff or comments cannot be loaded.
+    fn load(&self) -> Result<ReviewSnapshot, Self::Error>;
+
+    /// Mark a file as viewed or unviewed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the viewed state cannot be saved.
+    fn set_file_viewed(&self, path: &str, viewed: bool) -> Result<(), Self::Error>;
+
+    /// Change the state of a comment thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thread cannot be updated.
+    fn set_thread_state(&self, thread_id: Uuid, state: ThreadState) -> Result<(), Self::Error>;
+}
+
+/// Run the terminal frontend until the user quits.
+///
+/// # Errors
+///
+/// Returns an error if the initial load fails or the terminal cannot be
+/// drawn to.
+pub fn run(source: &impl ReviewSource) -> Result<(), TuiError> {
+    let snapshot = source.load().map_err(|e| TuiError::Source(e.to_string()))?;
+    let mut app = App::new(snapshot);
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app, source);
+    ratatui::restore();
+
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    source: &impl ReviewSource,
+) -> Result<(), TuiError> {
+    let mut last_refresh = Instant::now();
+
+    loop {
+        terminal.draw(|frame| view::draw(frame, app))?;
+
+        if event::poll(TICK_RATE)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            let result = match app.handle_key(key.code) {
+                Action::Quit => return Ok(()),
+                Action::None => continue,
+                Action::Reload => Ok(()),
+                Action::SetFileViewed { path, viewed } => source.set_file_viewed(&path, viewed),
+                Action::SetThreadState { thread_id, state } => {
+                    source.set_thread_state(thread_id, state)
+                }
+            };
+
+            match result {
+                Ok(()) => reload(app, source),
+                Err(e) => app.set_status(format!("Error: {e}")),
+            }
+            last_refresh = Instant::now();
+        }
+
+        if app.has_running_ai() && last_refresh.elapsed() >= AI_REFRESH_INTERVAL {
+            reload(app, source);
+            last_refresh = Instant::now();
+        }
+    }
+}
+
+fn reload(app: &mut App, source: &impl ReviewSource) {
+    match source.load() {
+        Ok(snapshot) => app.replace_snapshot(snapshot),
+        Err(e) => {
+            log::warn!("Failed to reload review: {e}");
+            app.set_status(format!("Reload failed: {e}"));
+        }
+    }
+}
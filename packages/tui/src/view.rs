@@ -0,0 +1,276 @@
+//! Terminal frontend rendering.
+
+use chadreview_local_comment_models::{
+    AiExecutionStatus, LineNumber, LocalComment, LocalCommentType, ThreadState,
+};
+use chadreview_pr_models::{DiffFile, FileStatus, LineType};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+
+use crate::app::{App, Focus};
+
+const HELP: &str =
+    "q quit · tab focus · j/k move · PgUp/PgDn scroll · v viewed · r resolve · s later · R reload";
+
+/// Draw the whole interface.
+pub fn draw(frame: &mut Frame<'_>, app: &App) {
+    let [header, body, status] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let [files, right] =
+        Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(body);
+    let [diff, threads] =
+        Layout::vertical([Constraint::Percentage(65), Constraint::Percentage(35)]).areas(right);
+
+    draw_header(frame, app, header);
+    draw_files(frame, app, files);
+    draw_diff(frame, app, diff);
+    draw_threads(frame, app, threads);
+    draw_status(frame, app, status);
+}
+
+fn pane(title: &str, focused: bool) -> Block<'static> {
+    let border_style = if focused {
+        Style::new().fg(Color::Cyan)
+    } else {
+        Style::new().fg(Color::DarkGray)
+    };
+
+    Block::bordered()
+        .title(format!(" {title} "))
+        .border_style(border_style)
+}
+
+fn draw_header(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    let info = &app.snapshot().info;
+    let line = Line::from(vec![
+        Span::from(" ChadReview ").bold().reversed(),
+        Span::raw(format!(" {} ", info.description)),
+        Span::from(format!("{} files ", info.files_changed)).dark_gray(),
+        Span::from(format!("+{}", info.total_additions)).green(),
+        Span::raw(" "),
+        Span::from(format!("-{}", info.total_deletions)).red(),
+    ]);
+
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn draw_files(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    let items: Vec<ListItem<'_>> = app
+        .snapshot()
+        .diffs
+        .iter()
+        .map(|file| file_item(app, file))
+        .collect();
+
+    let list = List::new(items)
+        .block(pane("Files", app.focus() == Focus::Files))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+    let mut state = ListState::default()
+        .with_selected((!app.snapshot().diffs.is_empty()).then_some(app.selected_file()));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn file_item<'a>(app: &App, file: &'a DiffFile) -> ListItem<'a> {
+    let (status, color) = match file.status {
+        FileStatus::Added => ("A", Color::Green),
+        FileStatus::Modified => ("M", Color::Blue),
+        FileStatus::Deleted => ("D", Color::Red),
+        FileStatus::Renamed => ("R", Color::Magenta),
+    };
+    let viewed = if app.is_viewed(&file.filename) {
+        "[x] "
+    } else {
+        "[ ] "
+    };
+
+    let mut spans = vec![
+        Span::raw(viewed),
+        Span::styled(status, Style::new().fg(color).bold()),
+        Span::raw(" "),
+        Span::raw(file.filename.as_str()),
+        Span::from(format!(" +{}", file.additions)).green(),
+        Span::from(format!(" -{}", file.deletions)).red(),
+    ];
+
+    let thread_count = app.thread_count(&file.filename);
+    if thread_count > 0 {
+        spans.push(Span::from(format!(" ●{thread_count}")).yellow());
+    }
+
+    ListItem::new(Line::from(spans))
+}
+
+fn draw_diff(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    let Some(file) = app.current_file() else {
+        let empty = Paragraph::new("No changes.").block(pane("Diff", app.focus() == Focus::Diff));
+        frame.render_widget(empty, area);
+        return;
+    };
+
+    let mut lines = Vec::new();
+    for hunk in &file.hunks {
+        lines.push(
+            Line::from(format!(
+                "@@ -{},{} +{},{} @@",
+                hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+            ))
+            .cyan(),
+        );
+
+        for diff_line in &hunk.lines {
+            let (marker, style) = match diff_line.line_type {
+                LineType::Addition => ("+", Style::new().fg(Color::Green)),
+                LineType::Deletion => ("-", Style::new().fg(Color::Red)),
+                LineType::Context => (" ", Style::new()),
+            };
+            let comment_marker = if app.line_has_thread(diff_line) {
+                Span::from("●").yellow()
+            } else {
+                Span::raw(" ")
+            };
+
+            lines.push(Line::from(vec![
+                comment_marker,
+                Span::from(format!(
+                    "{:>5} {:>5} ",
+                    line_number(diff_line.old_line_number),
+                    line_number(diff_line.new_line_number)
+                ))
+                .dark_gray(),
+                Span::styled(format!("{marker}{}", diff_line.content), style),
+            ]));
+        }
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(pane(&file.filename, app.focus() == Focus::Diff))
+        .scroll((to_u16(app.diff_scroll()), 0));
+    frame.render_widget(paragraph, area);
+}
+
+fn line_number(number: Option<u64>) -> String {
+    number.map(|n| n.to_string()).unwrap_or_default()
+}
+
+fn draw_threads(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    let threads = app.visible_threads();
+    let focused = app.focus() == Focus::Threads;
+
+    if threads.is_empty() {
+        let empty = Paragraph::new("No comments.").block(pane("Threads", focused));
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    let mut lines = Vec::new();
+    let mut selected_line = 0;
+
+    for (index, thread) in threads.iter().enumerate() {
+        let selected = index == app.selected_thread();
+        if selected {
+            selected_line = lines.len();
+        }
+        push_thread_lines(&mut lines, thread, selected && focused);
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(pane(&format!("Threads ({})", threads.len()), focused))
+        .wrap(Wrap { trim: false })
+        .scroll((to_u16(selected_line), 0));
+    frame.render_widget(paragraph, area);
+}
+
+fn push_thread_lines<'a>(lines: &mut Vec<Line<'a>>, thread: &'a LocalComment, selected: bool) {
+    let location = match &thread.comment_type {
+        LocalCommentType::LineLevelComment { line, .. } => match line {
+            LineNumber::New { line } => format!("line {line}"),
+            LineNumber::Old { line } => format!("old line {line}"),
+        },
+        LocalCommentType::FileLevelComment { .. } => "file".to_string(),
+        LocalCommentType::General | LocalCommentType::Reply { .. } => "general".to_string(),
+    };
+    let (state, state_color) = match thread.state {
+        ThreadState::Open => ("open", Color::Green),
+        ThreadState::Resolved => ("resolved", Color::DarkGray),
+        ThreadState::SavedForLater => ("later", Color::Yellow),
+    };
+
+    let header_style = if selected {
+        Style::new().add_modifier(Modifier::REVERSED)
+    } else {
+        Style::new().add_modifier(Modifier::BOLD)
+    };
+
+    lines.push(Line::from(vec![
+        Span::styled(format!("{} · {location}", thread.author.name), header_style),
+        Span::raw(" "),
+        Span::styled(format!("[{state}]"), Style::new().fg(state_color)),
+    ]));
+
+    if thread.state.is_collapsed() {
+        return;
+    }
+
+    push_comment_lines(lines, thread, 1);
+    lines.push(Line::raw(""));
+}
+
+fn push_comment_lines<'a>(lines: &mut Vec<Line<'a>>, comment: &'a LocalComment, depth: usize) {
+    let indent = "  ".repeat(depth);
+
+    for body_line in comment.body.lines() {
+        lines.push(Line::raw(format!("{indent}{body_line}")));
+    }
+
+    if let Some(status) = &comment.ai_status {
+        lines.push(Line::from(format!("{indent}{}", ai_status_text(status))).magenta());
+    }
+
+    for reply in &comment.replies {
+        lines.push(Line::from(format!("{indent}↳ {}", reply.author.name)).dark_gray());
+        push_comment_lines(lines, reply, depth + 1);
+    }
+}
+
+fn ai_status_text(status: &AiExecutionStatus) -> String {
+    match status {
+        AiExecutionStatus::Pending => "AI: pending".to_string(),
+        AiExecutionStatus::Running { progress, .. } => progress.last().map_or_else(
+            || "AI: running".to_string(),
+            |entry| format!("AI: running ({}: {})", entry.tool, entry.title),
+        ),
+        AiExecutionStatus::Completed { .. } => "AI: completed".to_string(),
+        AiExecutionStatus::Failed { error, .. } => format!("AI: failed ({error})"),
+        AiExecutionStatus::OverBudget { spent, budget, .. } => {
+            format!("AI: over budget (${spent:.2} of ${budget:.2} spent)")
+        }
+    }
+}
+
+fn draw_status(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    let line = app.status().map_or_else(
+        || {
+            let mut spans = vec![Span::from(HELP).dark_gray()];
+            if app.has_running_ai() {
+                spans.push(Span::from("  AI running…").magenta());
+            }
+            Line::from(spans)
+        },
+        |status| Line::from(status.to_string()).yellow(),
+    );
+
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn to_u16(value: usize) -> u16 {
+    u16::try_from(value).unwrap_or(u16::MAX)
+}
@@ -0,0 +1,49 @@
+#!/usr/bin/env bash
+set -e
+
+# Vendor the browser libraries the markdown renderer loads
+# Usage: ./vendor-assets.sh
+#
+# Mermaid and KaTeX are served by the app from packages/app/public/vendor
+# rather than a CDN, so diagrams and math render offline and in share mode.
+# Run this after bumping a version below and commit the result.
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+cd "$SCRIPT_DIR"
+
+MERMAID_VERSION="11.4.1"
+KATEX_VERSION="0.16.11"
+
+VENDOR_DIR="packages/app/public/vendor"
+
+if ! command -v npm &> /dev/null; then
+    echo "npm not found; it's needed to download the packages" >&2
+    exit 1
+fi
+
+WORK_DIR="$(mktemp -d)"
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+# Download and unpack `$1@$2`, checked against the registry's integrity hash
+fetch() {
+    local tarball
+    tarball="$(npm pack --silent --pack-destination "$WORK_DIR" "$1@$2")"
+    mkdir -p "$WORK_DIR/$1"
+    tar -xzf "$WORK_DIR/$tarball" -C "$WORK_DIR/$1" --strip-components=1
+}
+
+fetch mermaid "$MERMAID_VERSION"
+rm -rf "$VENDOR_DIR/mermaid"
+mkdir -p "$VENDOR_DIR/mermaid/chunks"
+cp "$WORK_DIR/mermaid/dist/mermaid.esm.min.mjs" "$VENDOR_DIR/mermaid/"
+cp -r "$WORK_DIR/mermaid/dist/chunks/mermaid.esm.min" "$VENDOR_DIR/mermaid/chunks/"
+cp "$WORK_DIR/mermaid/LICENSE" "$VENDOR_DIR/mermaid/"
+
+fetch katex "$KATEX_VERSION"
+rm -rf "$VENDOR_DIR/katex"
+mkdir -p "$VENDOR_DIR/katex"
+cp "$WORK_DIR/katex/dist/katex.mjs" "$WORK_DIR/katex/dist/katex.min.css" "$VENDOR_DIR/katex/"
+cp -r "$WORK_DIR/katex/dist/fonts" "$VENDOR_DIR/katex/"
+cp "$WORK_DIR/katex/LICENSE" "$VENDOR_DIR/katex/"
+
+echo "Vendored mermaid $MERMAID_VERSION and KaTeX $KATEX_VERSION into $VENDOR_DIR"